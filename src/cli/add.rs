@@ -1,14 +1,17 @@
-use anyhow::Result;
-use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
 use colored::Colorize;
 
-use crate::core::detect;
+use crate::core::detect::{self, DetectedTool};
 use crate::core::errors::DotsmithError;
-use crate::core::manifest::{Manifest, ToolEntry};
+use crate::core::manifest::Manifest;
 use crate::core::module::ModuleRegistry;
+use crate::core::output::Printer;
+use crate::core::plugin;
 use crate::util;
 
-pub fn run(verbose: bool, tool: &str) -> Result<()> {
+pub fn run(out: &Printer, tool: &str, paths: &[String], force_tier: Option<u8>) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let mut manifest = Manifest::load(&config_dir)?;
 
@@ -17,50 +20,24 @@ pub fn run(verbose: bool, tool: &str) -> Result<()> {
         return Err(DotsmithError::ToolAlreadyTracked(tool.to_string()).into());
     }
 
-    // Determine tier and load module definition
-    let (tier, module_def) = match ModuleRegistry::get_builtin(tool) {
-        Some(def) => (1u8, Some(def)),
-        None => (2u8, None),
-    };
-
-    // Check if the tool is installed
-    let detect_cmd = module_def
-        .as_ref()
-        .map(|d| d.metadata.detect_command.as_str())
-        .unwrap_or(&format!("which {}", tool))
-        .to_string();
-    detect::check_installed(tool, &detect_cmd)?;
-
-    // Find config files
-    let config_paths = if let Some(ref def) = module_def {
-        detect::find_config_paths_from_module(def)?
+    // Resolve paths the caller named before detecting anything
+    let paths = if paths.is_empty() {
+        None
     } else {
-        detect::auto_detect_config_paths(tool)?
+        Some(explicit_paths(paths)?)
     };
+    let detected = detect::detect_tool(&config_dir, tool, paths, force_tier)?;
 
-    if config_paths.is_empty() {
-        return Err(DotsmithError::NoConfigFound(tool.to_string()).into());
-    }
-
-    // Detect existing plugin manager
-    let plugin_manager = detect::detect_plugin_manager(tool, &config_paths);
+    manifest.add_tool(tool, detected.to_entry())?;
+    manifest.save(&config_dir)?;
 
-    // Build manifest entry
-    let entry = ToolEntry {
+    let DetectedTool {
         tier,
-        config_paths: config_paths
-            .iter()
-            .map(|p| util::paths::contract_tilde(p))
-            .collect(),
-        plugins_managed: false, // Never auto-enable — respect existing setup
-        plugin_manager: plugin_manager.clone(),
-        added_at: Utc::now(),
-        last_snapshot: None,
-        plugins: std::collections::BTreeMap::new(),
-    };
-
-    manifest.add_tool(tool, entry)?;
-    manifest.save(&config_dir)?;
+        config_paths,
+        include_notes,
+        plugin_manager,
+        ..
+    } = detected;
 
     // Report results
     let tier_label = match tier {
@@ -70,14 +47,14 @@ pub fn run(verbose: bool, tool: &str) -> Result<()> {
         _ => "unknown tier",
     };
 
-    println!(
+    out.info(format_args!(
         "{} Added {} ({})",
         "OK".green().bold(),
         tool.bold(),
         tier_label
-    );
+    ));
 
-    println!("  Tracking {} config path(s):", config_paths.len());
+    out.info(format_args!("  Tracking {} config path(s):", config_paths.len()));
     for path in &config_paths {
         let contracted = util::paths::contract_tilde(path);
         let suffix = if util::fs::is_symlink(path) {
@@ -92,31 +69,129 @@ pub fn run(verbose: bool, tool: &str) -> Result<()> {
             String::new()
         };
 
-        if verbose {
-            println!("    {}{}", contracted, suffix);
+        if out.is_verbose() {
+            out.info(format_args!("    {}{}", contracted, suffix));
         } else {
-            println!("    {}", contracted);
+            out.info(format_args!("    {}", contracted));
         }
     }
 
+    for note in &include_notes {
+        println!("  {} not tracking include: {}", "!!".yellow(), note);
+    }
+
     // Option database info (Tier 1 only)
     if tier == 1
-        && let Some(opts) = ModuleRegistry::get_options(tool)
+        && let Some(opts) = ModuleRegistry::load_options(&config_dir, tool)
     {
-        println!(
+        out.info(format_args!(
             "  Option database: {} options available to explore",
             opts.options.len()
-        );
+        ));
     }
 
     // Plugin manager info
     if let Some(ref pm) = plugin_manager {
-        println!("  Detected existing plugin manager: {}", pm.bold());
-        println!(
-            "    dotsmith will not manage plugins (use {} to opt in)",
-            "dotsmith plugins".dimmed()
-        );
+        out.info(format_args!("  Detected existing plugin manager: {}", pm.bold()));
+        if plugin::external_manager(tool).is_some() {
+            out.info(format_args!(
+                "    plugins stay with {} (see {})",
+                pm,
+                format!("dotsmith plugins {} list", tool).dimmed()
+            ));
+        } else {
+            out.info(format_args!(
+                "    dotsmith will not manage plugins (use {} to opt in)",
+                "dotsmith plugins".dimmed()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Add several tools the way [`run`] adds one: the tools listed in `from`,
+/// or every built-in module. Tools that are already tracked, not installed,
+/// or have no config files are reported and skipped.
+pub fn run_bulk(out: &Printer, from: Option<&Path>) -> Result<()> {
+    let tools = match from {
+        Some(path) => read_tool_list(path)?,
+        None => ModuleRegistry::builtin_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    };
+
+    let (mut added, mut skipped, mut missing, mut failed) = (0, 0, 0, 0);
+    for tool in &tools {
+        let Err(e) = run(out, tool, &[], None) else {
+            added += 1;
+            continue;
+        };
+        match e.downcast_ref::<DotsmithError>() {
+            Some(DotsmithError::ToolAlreadyTracked(_)) => {
+                out.info(format_args!(
+                    "{} {} already tracked, skipping",
+                    "--".dimmed(),
+                    tool.bold()
+                ));
+                skipped += 1;
+            }
+            Some(DotsmithError::NoConfigFound(_)) => {
+                out.info(format_args!(
+                    "{} {} has no config files, skipping",
+                    "--".dimmed(),
+                    tool.bold()
+                ));
+                skipped += 1;
+            }
+            Some(DotsmithError::ToolNotInstalled(..)) => {
+                out.info(format_args!("{} {} is not installed", "--".dimmed(), tool.bold()));
+                missing += 1;
+            }
+            _ => {
+                println!("{} {}: {}", "ERR".red().bold(), tool.bold(), e);
+                failed += 1;
+            }
+        }
     }
 
+    out.info(format_args!(
+        "\n  {} added, {} skipped, {} not installed",
+        added, skipped, missing
+    ));
+    if failed > 0 {
+        anyhow::bail!("{} tool(s) could not be added", failed);
+    }
     Ok(())
 }
+
+/// Tool names from a file, one per line. Blank lines and `#` comments are
+/// ignored.
+fn read_tool_list(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Resolve `--path` values: expand `~`, make relative paths absolute, and
+/// require each to exist inside `$HOME`. Repeats are dropped.
+fn explicit_paths(paths: &[String]) -> Result<Vec<PathBuf>> {
+    let mut resolved: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        let expanded = std::path::absolute(util::paths::expand_tilde_checked(path)?)?;
+        if std::fs::symlink_metadata(&expanded).is_err() {
+            anyhow::bail!("'{}' does not exist", path);
+        }
+        util::fs::check_path_safety(&expanded)?;
+        if !resolved.contains(&expanded) {
+            resolved.push(expanded);
+        }
+    }
+    Ok(resolved)
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::core::errors::DotsmithError;
+use crate::core::manifest::Manifest;
+use crate::core::output::Printer;
+use crate::core::patch;
+use crate::core::snapshot::{self, SnapshotEngine};
+use crate::util;
+
+/// Apply a unified diff to a tool's tracked files. Every hunk is checked
+/// before anything is written; the files are snapshotted first and restored
+/// from that snapshot if writing fails part-way.
+pub fn run(out: &Printer, tool: &str, patch_arg: &str, dry_run: bool, force: bool) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let manifest = Manifest::load(&config_dir)?;
+    let entry = manifest
+        .get_tool(tool)
+        .ok_or_else(|| DotsmithError::ToolNotTracked(tool.to_string()))?;
+
+    let (patch_text, patch_name) = if patch_arg == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .context("failed to read patch from stdin")?;
+        (text, "stdin".to_string())
+    } else {
+        let text = fs::read_to_string(patch_arg)
+            .with_context(|| format!("failed to read {}", patch_arg))?;
+        let name = Path::new(patch_arg)
+            .file_name()
+            .map_or_else(|| patch_arg.to_string(), |n| n.to_string_lossy().to_string());
+        (text, name)
+    };
+
+    let file_patches = patch::parse(&patch_text)?;
+    let files = snapshot::config_files(&entry.config_paths)?;
+
+    // (path, current content, patched content), worked out before touching anything
+    let mut changes: Vec<(PathBuf, String, String)> = Vec::new();
+    for file_patch in &file_patches {
+        if file_patch.creates_or_deletes() {
+            anyhow::bail!(
+                "patch creates or deletes '{}' — only changes to tracked files can be applied",
+                file_patch.target()
+            );
+        }
+        let path = patch::match_target(file_patch.target(), &files)?;
+        out.detail(format_args!(
+            "  {} -> {}",
+            file_patch.target(),
+            util::paths::contract_tilde(&path)
+        ));
+
+        match changes.iter_mut().find(|(p, _, _)| *p == path) {
+            Some((_, _, patched)) => *patched = patch::apply(patched, file_patch)?,
+            None => {
+                let current = fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                let patched = patch::apply(&current, file_patch)?;
+                changes.push((path, current, patched));
+            }
+        }
+    }
+
+    let hunks: usize = file_patches.iter().map(|p| p.hunks.len()).sum();
+    for (path, current, patched) in &changes {
+        let diff = util::diff::unified_diff(current, patched, &util::paths::contract_tilde(path));
+        println!("{}", diff);
+    }
+
+    if dry_run {
+        println!(
+            "{} Would apply {} hunk(s) to {} file(s) of {}",
+            "[dry-run]".yellow().bold(),
+            hunks,
+            changes.len(),
+            tool.bold()
+        );
+        return Ok(());
+    }
+
+    if !force {
+        if !util::prompt::is_interactive() {
+            anyhow::bail!(
+                "refusing to apply the patch without confirmation — re-run with --force"
+            );
+        }
+        if !util::prompt::confirm("Apply this patch?")? {
+            println!("Apply cancelled.");
+            return Ok(());
+        }
+    }
+
+    let mut engine = SnapshotEngine::open(&config_dir)?;
+    engine.keep_secrets();
+    let message = format!("pre-apply snapshot ({})", patch_name);
+    engine.snapshot_tool(tool, &entry.config_paths, Some(&message))?;
+    crate::cli::snapshot::warn_kept_secrets(&engine);
+
+    let mut restore_points = Vec::new();
+    for (path, current, _) in &changes {
+        let path_str = util::paths::contract_tilde(path);
+        let id = engine
+            .find_snapshot(tool, &path_str, current.as_bytes())?
+            .ok_or_else(|| anyhow::anyhow!("failed to snapshot {} before applying", path_str))?;
+        restore_points.push(id);
+    }
+
+    for (i, (path, _, patched)) in changes.iter().enumerate() {
+        if let Err(e) = write_through(path, patched.as_bytes()) {
+            for ((path, _, _), id) in changes[..=i].iter().zip(&restore_points) {
+                if let Some((_, content)) = engine.get_snapshot(*id)? {
+                    write_through(path, content.as_bytes())?;
+                }
+            }
+            println!(
+                "{} Write failed; restored {} file(s) from the pre-apply snapshot",
+                "!!".yellow(),
+                i + 1
+            );
+            return Err(e);
+        }
+    }
+
+    let paths: Vec<String> = changes
+        .iter()
+        .map(|(path, _, _)| util::paths::contract_tilde(path))
+        .collect();
+    engine.record_operation("apply", &paths)?;
+
+    out.info(format_args!(
+        "{} Applied {} hunk(s) to {} file(s) of {}",
+        "OK".green().bold(),
+        hunks,
+        changes.len(),
+        tool.bold()
+    ));
+    for (path, id) in paths.iter().zip(&restore_points) {
+        out.info(format_args!(
+            "  {} — undo with {}",
+            path,
+            format!("dotsmith rollback {}", id).bold()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Write a file, following a symlinked config path to the file it points at
+/// so linked dotfiles stay linked.
+fn write_through(path: &Path, content: &[u8]) -> Result<()> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    util::fs::atomic_write_bytes(&target, content)
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::core::audit;
+use crate::core::manifest::Manifest;
+use crate::core::module::{ModuleRegistry, OptionEntry};
+use crate::core::output::Printer;
+use crate::core::snapshot;
+use crate::util;
+
+/// Compare a Tier 1 tool's config files with its option database: report
+/// options set but unknown to it (likely typos), deprecated options still
+/// in use, and the documented options that are never set.
+pub fn run(out: &Printer, tool: &str) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let manifest = Manifest::load(&config_dir)?;
+    let Some(entry) = manifest.tools.get(tool) else {
+        anyhow::bail!("'{}' is not tracked by dotsmith", tool);
+    };
+
+    let (Some(module), Some(catalog)) = (
+        ModuleRegistry::load_module(&config_dir, tool),
+        ModuleRegistry::load_options(&config_dir, tool),
+    ) else {
+        anyhow::bail!(
+            "no option database for {} — audit needs a Tier 1 tool (see `dotsmith list`)",
+            tool
+        );
+    };
+    let format = &module.metadata.config_format;
+
+    let mut set = Vec::new();
+    let mut unknown = Vec::new();
+    let mut deprecated = Vec::new();
+    let mut files = 0;
+    for path in snapshot::config_files(&entry.config_paths)? {
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        files += 1;
+        let path_str = util::paths::contract_tilde(&path);
+        let report = audit::audit_config(&content, format, &catalog.options);
+        unknown.extend(report.unknown.into_iter().map(|u| (path_str.clone(), u)));
+        deprecated.extend(report.deprecated.into_iter().map(|d| (path_str.clone(), d)));
+        set.extend(audit::extract_set_options(&content, format));
+    }
+
+    println!(
+        "{} {} ({} file(s), {} option(s) set)",
+        "Auditing".bold(),
+        tool.bold(),
+        files,
+        set.len()
+    );
+
+    if !unknown.is_empty() {
+        println!();
+        println!("Not in the option database:");
+        for (path, u) in &unknown {
+            match u.suggestion {
+                Some(ref suggestion) => println!(
+                    "  {} {}:{}: {} — did you mean '{}'?",
+                    "!!".yellow(),
+                    path,
+                    u.line,
+                    u.name,
+                    suggestion
+                ),
+                None => println!("  {} {}:{}: {}", "--".dimmed(), path, u.line, u.name),
+            }
+        }
+    }
+
+    if !deprecated.is_empty() {
+        println!();
+        println!("Deprecated:");
+        for (path, d) in &deprecated {
+            let mut note = match d.deprecated {
+                Some(ref since) => format!("deprecated ({})", since),
+                None => "deprecated".to_string(),
+            };
+            if let Some(ref replacement) = d.replaced_by {
+                note.push_str(&format!(" — use {}", replacement));
+            }
+            println!("  {} {}:{}: {} {}", "!!".yellow(), path, d.line, d.name, note);
+        }
+    }
+
+    let unset = audit::unset_options(format, &set, &catalog.options);
+    if !unset.is_empty() {
+        println!();
+        println!("Never set ({} documented option(s)):", unset.len());
+        // Group by category, in the order categories first appear
+        let mut groups: Vec<(&str, Vec<&OptionEntry>)> = Vec::new();
+        for &opt in &unset {
+            match groups.iter_mut().find(|(c, _)| *c == opt.category) {
+                Some((_, opts)) => opts.push(opt),
+                None => groups.push((&opt.category, vec![opt])),
+            }
+        }
+        for (category, opts) in &groups {
+            if out.is_verbose() {
+                println!("  {}", category.cyan());
+                for opt in opts {
+                    println!("    {}  {}", opt.name.bold(), opt.description.dimmed());
+                }
+            } else {
+                let names: Vec<&str> = opts.iter().map(|o| o.name.as_str()).collect();
+                println!("  {}: {}", category.cyan(), names.join(", "));
+            }
+        }
+        if !out.is_verbose() {
+            out.info(format_args!(
+                "  Use {} for descriptions, or {} to browse them.",
+                "--verbose".bold(),
+                format!("dotsmith explore {}", tool).bold()
+            ));
+        }
+    }
+
+    if unknown.is_empty() && deprecated.is_empty() && unset.is_empty() {
+        println!("{} Every documented option is set", "OK".green().bold());
+    }
+
+    Ok(())
+}
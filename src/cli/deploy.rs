@@ -1,27 +1,40 @@
 use anyhow::Result;
 use colored::Colorize;
 
-use crate::core::deploy::{self, DeployActionType};
+use crate::core::deploy::{self, DeployAction, DeployActionType, DeployMode, DeployOpts};
+use crate::core::output::Printer;
+use crate::core::snapshot::SnapshotEngine;
 use crate::util;
 
-/// Deploy config files by creating symlinks from a source to a target.
+/// Deploy config files by creating symlinks (or copies) from a source to a target.
 pub fn run(
-    verbose: bool,
+    out: &Printer,
     source: &str,
     target: &str,
     dry_run: bool,
+    per_file: bool,
+    copy: bool,
+    allow_missing_env: bool,
 ) -> Result<()> {
-    let source_path = util::paths::expand_tilde(source);
-    let target_path = util::paths::expand_tilde(target);
+    let source_path = util::paths::expand_tilde_checked(source)?;
+    let target_path = util::paths::expand_tilde_checked(target)?;
 
     // Safety check
     util::fs::check_path_safety(&source_path)?;
     util::fs::check_path_safety(&target_path)?;
 
-    let actions = deploy::plan_deploy(&source_path, &target_path)?;
+    let opts = DeployOpts {
+        allow_missing_env,
+        mode: if copy { DeployMode::Copy } else { DeployMode::Symlink },
+    };
+    let actions = if per_file {
+        deploy::plan_deploy_files(&source_path, &target_path, opts)?
+    } else {
+        deploy::plan_deploy(&source_path, &target_path, opts)?
+    };
 
     if actions.is_empty() {
-        println!("Nothing to deploy.");
+        out.info("Nothing to deploy.");
         return Ok(());
     }
 
@@ -33,18 +46,36 @@ pub fn run(
             DeployActionType::BackupAndLink => "backup+link".yellow(),
             DeployActionType::Relink => "relink".yellow(),
             DeployActionType::SourceMissing => "missing".red(),
+            DeployActionType::Render => "render".green(),
+            DeployActionType::BackupAndRender => "backup+render".yellow(),
+            DeployActionType::Copy => "copy".green(),
+            DeployActionType::BackupAndCopy => "backup+copy".yellow(),
         };
 
-        println!(
-            "  [{}] {} -> {}",
+        // Rendered and copied files are written, not linked
+        let arrow = if action.rendered.is_some() || copy { "<=" } else { "->" };
+        out.info(format_args!(
+            "  [{}] {} {} {}",
             status,
             action.target.display(),
+            arrow,
             action.source.display()
-        );
+        ));
+
+        // Show what the backup would keep, to judge whether it's needed
+        if dry_run && let Some(conflict) = deploy::describe_conflict(action) {
+            for line in conflict.lines() {
+                out.info(format_args!("      {}", line));
+            }
+        }
     }
 
+    let (correct, changed) = deploy::summarize(&actions);
+    out.info("");
+    out.info(format_args!("{} already correct, {} changed", correct, changed));
+
     if dry_run {
-        println!();
+        out.info("");
         println!(
             "{} No changes made (dry run)",
             "[dry-run]".yellow().bold()
@@ -52,18 +83,8 @@ pub fn run(
         return Ok(());
     }
 
-    // Check if any actions need user attention
-    let needs_action = actions.iter().any(|a| {
-        matches!(
-            a.action,
-            DeployActionType::CreateSymlink
-                | DeployActionType::BackupAndLink
-                | DeployActionType::Relink
-        )
-    });
-
-    if !needs_action {
-        println!("All symlinks are already correct.");
+    if changed == 0 {
+        out.info("All targets are already correct.");
         return Ok(());
     }
 
@@ -71,22 +92,44 @@ pub fn run(
     let backup_dir = config_dir.join("backups");
 
     let backed_up = deploy::execute_deploy(&actions, &backup_dir)?;
+    log_deploy(&config_dir, &actions, out);
 
-    println!();
-    println!("{} Deploy complete", "OK".green().bold());
+    out.info("");
+    out.info(format_args!("{} Deploy complete", "OK".green().bold()));
 
     if !backed_up.is_empty() {
-        println!(
+        out.info(format_args!(
             "  {} file(s) backed up to {}",
             backed_up.len(),
             backup_dir.display()
-        );
-        if verbose {
-            for path in &backed_up {
-                println!("    {}", path.display());
-            }
+        ));
+        for path in &backed_up {
+            out.detail(format_args!("    {}", path.display()));
         }
     }
 
     Ok(())
 }
+
+/// Record the targets this deploy wrote so doctor doesn't flag them as
+/// changed outside dotsmith.
+fn log_deploy(config_dir: &std::path::Path, actions: &[DeployAction], out: &Printer) {
+    let written: Vec<String> = actions
+        .iter()
+        .filter(|a| {
+            !matches!(
+                a.action,
+                DeployActionType::AlreadyCorrect | DeployActionType::SourceMissing
+            )
+        })
+        .map(|a| util::paths::contract_tilde(&a.target))
+        .collect();
+
+    let logged = SnapshotEngine::open(config_dir)
+        .and_then(|engine| engine.record_operation("deploy", &written));
+    if let Err(e) = logged
+        && out.is_verbose()
+    {
+        eprintln!("  warning: failed to log deploy: {}", e);
+    }
+}
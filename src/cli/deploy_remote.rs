@@ -1,12 +1,14 @@
 use anyhow::Result;
 use colored::Colorize;
 
+use crate::core::config::DotsmithConfig;
 use crate::core::manifest::Manifest;
+use crate::core::output::Printer;
 use crate::core::remote::{self, RemoteDeployOpts};
 use crate::util;
 
 pub fn run(
-    verbose: bool,
+    out: &Printer,
     host: &str,
     user: Option<&str>,
     tools: Option<&[String]>,
@@ -14,6 +16,8 @@ pub fn run(
 ) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let manifest = Manifest::load(&config_dir)?;
+    let config = DotsmithConfig::load(&config_dir);
+    let path_map = config.hosts.get(host).map(|h| h.path_map.as_slice()).unwrap_or_default();
 
     let tool_refs: Option<Vec<&str>> = tools.map(|t| t.iter().map(|s| s.as_str()).collect());
 
@@ -22,12 +26,13 @@ pub fn run(
         user,
         tools: tool_refs,
         dry_run,
+        path_map,
     };
 
     let actions = remote::plan_remote_deploy(&manifest, &opts)?;
 
     if actions.is_empty() {
-        println!("No files to deploy.");
+        out.info("No files to deploy.");
         return Ok(());
     }
 
@@ -36,7 +41,7 @@ pub fn run(
         None => host.to_string(),
     };
 
-    println!("Deploy to {}:\n", dest.bold());
+    out.info(format_args!("Deploy to {}:\n", dest.bold()));
 
     for action in &actions {
         let status = if action.remote_exists {
@@ -46,18 +51,18 @@ pub fn run(
         };
 
         let tool_prefix = format!("[{}]", action.tool).dimmed();
-        println!(
+        out.info(format_args!(
             "  {} [{}] {} -> {}:{}",
             tool_prefix,
             status,
             util::paths::contract_tilde(&action.local_path),
             dest,
             action.remote_path,
-        );
+        ));
     }
 
     if dry_run {
-        println!();
+        out.info("");
         println!(
             "{} No files copied (dry run)",
             "[dry-run]".yellow().bold(),
@@ -67,26 +72,26 @@ pub fn run(
 
     let result = remote::execute_remote_deploy(&actions, host, user)?;
 
-    println!();
-    println!(
+    out.info("");
+    out.info(format_args!(
         "{} Deployed {} file(s) to {}",
         "OK".green().bold(),
         result.files_copied,
         dest,
-    );
+    ));
 
     if result.files_backed_up > 0 {
-        println!(
+        out.info(format_args!(
             "  {} remote file(s) backed up before overwrite",
             result.files_backed_up,
-        );
+        ));
     }
 
-    if verbose && result.files_skipped > 0 {
-        println!(
+    if result.files_skipped > 0 {
+        out.detail(format_args!(
             "  {} file(s) skipped (not found locally)",
             result.files_skipped,
-        );
+        ));
     }
 
     Ok(())
@@ -1,59 +1,233 @@
 use anyhow::Result;
-use colored::Colorize;
+use colored::{Color, Colorize};
 
+use crate::core::config::DotsmithConfig;
+use crate::core::errors::DotsmithError;
 use crate::core::manifest::Manifest;
-use crate::core::snapshot::SnapshotEngine;
+use crate::core::profile;
+use crate::core::remote::{self, RemoteDeployOpts};
+use crate::core::snapshot::{FileDiff, SnapshotEngine};
+use crate::tui::diff::{DiffLine, DiffLineKind, DiffState, build_diff_lines};
 use crate::util;
 
-/// Show differences between current config files and last snapshot.
-pub fn run(_verbose: bool, tool: Option<&str>) -> Result<()> {
+/// Unchanged lines shown around each change, as in `git diff`.
+const CONTEXT_LINES: usize = 3;
+
+/// Where `dotsmith diff` takes the old side of each file from.
+pub enum DiffBase<'a> {
+    /// The last snapshot
+    Snapshot,
+    /// The copies saved in a profile
+    Profile(&'a str),
+    /// The live files on a remote host, as `(host, user)`
+    Remote(&'a str, Option<&'a str>),
+}
+
+/// Show differences between current config files and `base`: the last
+/// snapshot, a saved profile, or a remote host. With `stat`, print a
+/// per-file `+N/-M` summary instead of the diffs.
+pub fn run(tool: Option<&str>, base: DiffBase, stat: bool) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
+
+    match base {
+        DiffBase::Snapshot => {}
+        DiffBase::Profile(name) => return run_profile(&config_dir, tool, name, stat),
+        DiffBase::Remote(host, user) => return run_remote(&config_dir, tool, host, user, stat),
+    }
+
     let manifest = Manifest::load(&config_dir)?;
     let engine = SnapshotEngine::open(&config_dir)?;
 
-    let tools_to_diff: Vec<(&String, &crate::core::manifest::ToolEntry)> = match tool {
-        Some(name) => {
-            manifest
-                .tools
-                .get_key_value(name)
-                .map(|(k, v)| vec![(k, v)])
-                .ok_or_else(|| anyhow::anyhow!("'{}' is not tracked by dotsmith", name))?
-        }
-        None => manifest.tools.iter().collect(),
+    let tools_to_diff: Vec<(String, Vec<String>)> = match tool {
+        Some(name) => match manifest.tools.get(name) {
+            Some(entry) => vec![(name.to_string(), entry.config_paths.clone())],
+            // Snapshotted with `snapshot --file`: diff the files it has snapshots of
+            None => {
+                let paths = engine.snapshot_paths(name)?;
+                if paths.is_empty() {
+                    anyhow::bail!("'{}' is not tracked by dotsmith", name);
+                }
+                vec![(name.to_string(), paths)]
+            }
+        },
+        None => manifest
+            .tools
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry.config_paths.clone()))
+            .collect(),
     };
 
-    let mut any_diffs = false;
+    let mut all_diffs = Vec::new();
+    for (name, config_paths) in &tools_to_diff {
+        all_diffs.extend(engine.diff_current(name, config_paths)?);
+    }
 
-    for (name, entry) in &tools_to_diff {
-        let diffs = engine.diff_current(name, &entry.config_paths)?;
+    if stat {
+        print_stat(&all_diffs);
+    } else {
+        print_diffs(&all_diffs);
+    }
 
-        if diffs.is_empty() {
-            continue;
-        }
+    if all_diffs.is_empty() {
+        let scope = tool.unwrap_or("any tracked tool");
+        println!("No changes detected for {}", scope.bold());
+        println!(
+            "  Run {} first to establish a baseline.",
+            "dotsmith snapshot".bold()
+        );
+    }
 
-        any_diffs = true;
+    Ok(())
+}
 
-        for file_diff in &diffs {
-            let output = util::diff::unified_diff(
-                &file_diff.old_content,
-                &file_diff.new_content,
-                &file_diff.file_path,
-            );
+/// Diff current files against the copies stored in a named profile.
+fn run_profile(
+    config_dir: &std::path::Path,
+    tool: Option<&str>,
+    name: &str,
+    stat: bool,
+) -> Result<()> {
+    let tools: Vec<String> = match tool {
+        Some(t) => vec![t.to_string()],
+        None => profile::read_profile_meta(config_dir, name)?
+            .tools
+            .into_keys()
+            .collect(),
+    };
 
-            if !output.is_empty() {
-                println!("{}", output);
-            }
+    let mut all_diffs = Vec::new();
+    for tool_name in &tools {
+        all_diffs.extend(DiffState::from_profile(tool_name, config_dir, name)?.files);
+    }
+
+    if stat {
+        print_stat(&all_diffs);
+    } else {
+        print_diffs(&all_diffs);
+    }
+
+    if all_diffs.is_empty() {
+        let scope = tool.unwrap_or("any tool");
+        println!(
+            "No differences between profile '{}' and current files for {}",
+            name.bold(),
+            scope.bold()
+        );
+    }
+
+    Ok(())
+}
+
+/// Diff current files against their live copies on a remote host.
+fn run_remote(
+    config_dir: &std::path::Path,
+    tool: Option<&str>,
+    host: &str,
+    user: Option<&str>,
+    stat: bool,
+) -> Result<()> {
+    let manifest = Manifest::load(config_dir)?;
+    let tools: Vec<&str> = match tool {
+        Some(t) if !manifest.has_tool(t) => {
+            return Err(DotsmithError::ToolNotTracked(t.to_string()).into());
         }
+        Some(t) => vec![t],
+        None => manifest.tools.keys().map(String::as_str).collect(),
+    };
+
+    let config = DotsmithConfig::load(config_dir);
+    let path_map = config.hosts.get(host).map(|h| h.path_map.as_slice()).unwrap_or_default();
+    let opts = RemoteDeployOpts {
+        host,
+        user,
+        tools: Some(tools),
+        dry_run: true,
+        path_map,
+    };
+    let all_diffs = remote::diff_remote(&manifest, &opts)?;
+
+    if stat {
+        print_stat(&all_diffs);
+    } else {
+        print_diffs(&all_diffs);
     }
 
-    if !any_diffs {
+    if all_diffs.is_empty() {
         let scope = tool.unwrap_or("any tracked tool");
-        println!("No changes detected for {}", scope.bold());
         println!(
-            "  Run {} first to establish a baseline.",
-            "dotsmith snapshot".bold()
+            "No differences between {} and current files for {}",
+            host.bold(),
+            scope.bold()
         );
     }
 
     Ok(())
 }
+
+/// Print each file diff as a unified diff, colored like the TUI diff view:
+/// bold file headers, cyan hunk headers, green additions and red removals
+/// with the changed words in bold. `colored` drops the colors when stdout
+/// isn't a terminal or `NO_COLOR` is set.
+pub fn print_diffs(diffs: &[FileDiff]) {
+    let (lines, _) = build_diff_lines(diffs, Some(CONTEXT_LINES));
+    for line in &lines {
+        println!("{}", color_line(line));
+    }
+}
+
+fn color_line(line: &DiffLine) -> String {
+    let color = match line.kind {
+        DiffLineKind::Header => return line.content.bold().to_string(),
+        DiffLineKind::HunkHeader => return line.content.cyan().to_string(),
+        DiffLineKind::Context | DiffLineKind::Empty => return line.content.clone(),
+        DiffLineKind::Added => Color::Green,
+        DiffLineKind::Removed => Color::Red,
+    };
+    if line.emphasis.is_empty() {
+        return line.content.color(color).to_string();
+    }
+
+    // Spans start after the +/- prefix
+    let mut out = line.content[..1].color(color).to_string();
+    for (range, emphasized) in &line.emphasis {
+        let text = line.content[range.clone()].color(color);
+        out.push_str(&if *emphasized { text.bold() } else { text }.to_string());
+    }
+    out
+}
+
+/// Print one `path | +N/-M` line per changed file, like `git diff --stat`,
+/// then the totals.
+pub fn print_stat(diffs: &[FileDiff]) {
+    let changed: Vec<&FileDiff> = diffs
+        .iter()
+        .filter(|d| d.binary || d.old_content != d.new_content)
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+
+    let width = changed.iter().map(|d| d.file_path.chars().count()).max().unwrap_or(0);
+    let (mut total_added, mut total_removed) = (0, 0);
+    for diff in &changed {
+        let counts = if diff.binary {
+            "binary".dimmed().to_string()
+        } else {
+            let (added, removed) = util::diff::line_counts(&diff.old_content, &diff.new_content);
+            total_added += added;
+            total_removed += removed;
+            format!(
+                "{}/{}",
+                format!("+{}", added).green(),
+                format!("-{}", removed).red()
+            )
+        };
+        println!(" {:<width$} | {}", diff.file_path, counts, width = width);
+    }
+    println!(
+        " {} file(s) changed, {} insertion(s), {} deletion(s)",
+        changed.len(),
+        total_added,
+        total_removed
+    );
+}
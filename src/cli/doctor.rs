@@ -1,45 +1,131 @@
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Duration, Utc};
 use colored::Colorize;
+use serde::Serialize;
 
+use crate::core::audit;
+use crate::core::config::DotsmithConfig;
 use crate::core::detect;
+use crate::core::errors::DotsmithError;
+use crate::core::fix::{self, FixContext};
 use crate::core::manifest::Manifest;
 use crate::core::module::ModuleRegistry;
+use crate::core::output::Printer;
+use crate::core::perms;
 use crate::core::snapshot::SnapshotEngine;
 use crate::core::validate;
 use crate::util;
 
+/// Print a line of the human-readable report; silent with `--json`.
+macro_rules! say {
+    ($result:expr) => {
+        if !$result.json {
+            println!();
+        }
+    };
+    ($result:expr, $($arg:tt)*) => {
+        if !$result.json {
+            println!($($arg)*);
+        }
+    };
+}
+
 struct CheckResult {
     ok: usize,
     warn: usize,
     error: usize,
     hints: Vec<String>,
+    /// Print the report as JSON at the end instead of line by line.
+    json: bool,
+    tools: Vec<ToolReport>,
+}
+
+/// Per-tool results for `dotsmith doctor --json`.
+#[derive(Serialize)]
+struct ToolReport {
+    name: String,
+    installed: bool,
+    existing_paths: usize,
+    total_paths: usize,
+    issues: Vec<String>,
+    /// "ok", "warn", or "error"
+    status: &'static str,
 }
 
 impl CheckResult {
-    fn new() -> Self {
+    fn new(json: bool) -> Self {
         Self {
             ok: 0,
             warn: 0,
             error: 0,
             hints: Vec::new(),
+            json,
+            tools: Vec::new(),
+        }
+    }
+
+    /// Record an issue found after `check_tool` against the tool it last
+    /// checked.
+    fn add_issue(&mut self, issue: String) {
+        if let Some(report) = self.tools.last_mut() {
+            if report.status == "ok" {
+                report.status = "warn";
+            }
+            report.issues.push(issue);
         }
     }
 }
 
-pub fn run(verbose: bool, tool: Option<&str>) -> Result<()> {
+/// Options for a doctor run.
+pub struct DoctorOpts<'a> {
+    pub tool: Option<&'a str>,
+    pub deep: bool,
+    pub fix_perms: bool,
+    pub fix: bool,
+    pub yes: bool,
+    /// Fail when any tool has an error.
+    pub strict: bool,
+    /// Fail when any tool has an error or a warning.
+    pub strict_warnings: bool,
+    pub json: bool,
+}
+
+/// Check dotsmith's setup and every tracked tool, then print a report.
+///
+/// Returns `Ok` whatever was found unless strict mode asks otherwise. With
+/// `strict`, any error makes it return `DotsmithError::DoctorErrors`; with
+/// `strict_warnings`, warnings alone return `DotsmithError::DoctorWarnings`.
+/// `main` maps these to exit codes 2 and 3 (see `DotsmithError::exit_code`),
+/// so scripts can tell failed checks (2, 3) apart from doctor itself failing
+/// to run (1).
+pub fn run(out: &Printer, opts: &DoctorOpts) -> Result<()> {
+    let DoctorOpts {
+        tool,
+        deep,
+        fix_perms,
+        fix,
+        yes,
+        json,
+        ..
+    } = *opts;
+    if fix && json {
+        anyhow::bail!("--fix can't be combined with --json");
+    }
+
     let config_dir = util::paths::config_dir()?;
-    let mut result = CheckResult::new();
+    let mut result = CheckResult::new(json);
+    let verbose = out.is_verbose() && !json;
 
     // --- Dotsmith setup checks ---
-    println!("  Checking dotsmith setup...");
+    say!(result, "  Checking dotsmith setup...");
 
     // Config directory
     if config_dir.exists() {
-        println!("    {}  config directory", "OK".green().bold());
+        say!(result, "    {}  config directory", "OK".green().bold());
         result.ok += 1;
     } else {
-        println!(
+        say!(
+            result,
             "    {}  config directory missing",
             "ERR".red().bold()
         );
@@ -48,14 +134,14 @@ pub fn run(verbose: bool, tool: Option<&str>) -> Result<()> {
             .hints
             .push("run `dotsmith init` to create config directory".to_string());
         // Can't continue without config dir
-        print_summary(&result);
-        return Ok(());
+        return finish(&result, opts);
     }
 
     // Manifest
     let manifest = match Manifest::load(&config_dir) {
         Ok(m) => {
-            println!(
+            say!(
+                result,
                 "    {}  manifest ({} tools tracked)",
                 "OK".green().bold(),
                 m.tools.len()
@@ -64,7 +150,8 @@ pub fn run(verbose: bool, tool: Option<&str>) -> Result<()> {
             m
         }
         Err(_) => {
-            println!(
+            say!(
+                result,
                 "    {}  manifest missing or invalid",
                 "ERR".red().bold()
             );
@@ -72,40 +159,41 @@ pub fn run(verbose: bool, tool: Option<&str>) -> Result<()> {
             result
                 .hints
                 .push("run `dotsmith init` to initialize".to_string());
-            print_summary(&result);
-            return Ok(());
+            return finish(&result, opts);
         }
     };
 
     // Snapshot database
-    match SnapshotEngine::open(&config_dir) {
-        Ok(_) => {
-            println!("    {}  snapshot database", "OK".green().bold());
+    let engine = match SnapshotEngine::open(&config_dir) {
+        Ok(engine) => {
+            say!(result, "    {}  snapshot database", "OK".green().bold());
             result.ok += 1;
+            Some(engine)
         }
         Err(e) => {
-            println!(
+            say!(
+                result,
                 "    {}  snapshot database: {}",
                 "ERR".red().bold(),
                 e
             );
             result.error += 1;
+            None
         }
-    }
+    };
 
     if manifest.tools.is_empty() {
-        println!();
-        println!("  No tools tracked.");
+        say!(result);
+        say!(result, "  No tools tracked.");
         result
             .hints
             .push("run `dotsmith add <tool>` to start tracking".to_string());
-        print_summary(&result);
-        return Ok(());
+        return finish(&result, opts);
     }
 
     // --- Per-tool checks ---
-    println!();
-    println!("  Checking tools...");
+    say!(result);
+    say!(result, "  Checking tools...");
 
     let tools_to_check: Vec<(&String, &crate::core::manifest::ToolEntry)> = match tool {
         Some(name) => {
@@ -118,26 +206,126 @@ pub fn run(verbose: bool, tool: Option<&str>) -> Result<()> {
         None => manifest.tools.iter().collect(),
     };
 
+    let config = DotsmithConfig::load(&config_dir);
+
     for (name, entry) in &tools_to_check {
-        check_tool(name, entry, verbose, &mut result);
+        check_tool(&config_dir, name, entry, config.general.use_shellcheck, verbose, &mut result);
+        check_permissions(entry, &config.general.sensitive_paths, fix_perms, &mut result);
+        if let Some(ref engine) = engine {
+            check_outside_changes(name, entry, engine, &mut result);
+        }
+        if deep {
+            deep_check(&config_dir, name, entry, verbose, &mut result);
+        }
+    }
+
+    say!(result);
+    print_summary(&result)?;
+
+    if fix {
+        let names: Vec<&str> = tools_to_check.iter().map(|(n, _)| n.as_str()).collect();
+        run_fixes(&config_dir, &config, &manifest, &names, yes)?;
     }
 
+    strict_outcome(&result, opts)
+}
+
+fn finish(result: &CheckResult, opts: &DoctorOpts) -> Result<()> {
+    print_summary(result)?;
+    strict_outcome(result, opts)
+}
+
+/// The error strict mode turns the report into, if any. Counts are those
+/// of the checks, before any `--fix` repairs.
+fn strict_outcome(result: &CheckResult, opts: &DoctorOpts) -> Result<()> {
+    if (opts.strict || opts.strict_warnings) && result.error > 0 {
+        return Err(DotsmithError::DoctorErrors(result.error).into());
+    }
+    if opts.strict_warnings && result.warn > 0 {
+        return Err(DotsmithError::DoctorWarnings(result.warn).into());
+    }
+    Ok(())
+}
+
+/// Offer each safe remediation for the checked tools, one at a time. With
+/// `yes` every fix is applied without asking; without it and without a
+/// terminal to ask on, fixes are only listed.
+fn run_fixes(
+    config_dir: &std::path::Path,
+    config: &DotsmithConfig,
+    manifest: &Manifest,
+    tools: &[&str],
+    yes: bool,
+) -> Result<()> {
+    let search_dirs: Vec<std::path::PathBuf> = config
+        .general
+        .repo_path
+        .iter()
+        .chain(std::iter::once(&config.general.configs_dir))
+        .map(|d| util::paths::expand_tilde(d))
+        .collect();
+
+    let fixes: Vec<Box<dyn fix::Fix>> = tools
+        .iter()
+        .filter_map(|name| manifest.tools.get(*name).map(|entry| (name, entry)))
+        .flat_map(|(name, entry)| fix::fixes_for_tool(name, entry, &search_dirs))
+        .collect();
+
     println!();
-    print_summary(&result);
+    if fixes.is_empty() {
+        println!("  Nothing to fix automatically.");
+        return Ok(());
+    }
+    println!("  Fixing...");
+
+    let interactive = util::prompt::is_interactive();
+    let mut ctx = FixContext {
+        config_dir: config_dir.to_path_buf(),
+        manifest: Manifest::load(config_dir)?,
+    };
+    let mut applied = 0;
+
+    for f in &fixes {
+        let description = f.describe();
+        if !yes {
+            if !interactive {
+                println!("    {}  {} (pass --yes to apply)", "--".dimmed(), description);
+                continue;
+            }
+            if !util::prompt::confirm_default(&format!("    {}?", description), true)? {
+                continue;
+            }
+        }
+
+        match f.apply(&mut ctx) {
+            Ok(report) => {
+                println!("    {}  {}", "OK".green().bold(), report);
+                applied += 1;
+            }
+            Err(e) => println!("    {}  {}: {}", "ERR".red().bold(), description, e),
+        }
+    }
+
+    if applied > 0 {
+        ctx.manifest.save(config_dir)?;
+    }
+    println!("  Applied {} of {} fix(es)", applied, fixes.len());
 
     Ok(())
 }
 
 fn check_tool(
+    config_dir: &std::path::Path,
     name: &str,
     entry: &crate::core::manifest::ToolEntry,
+    use_shellcheck: bool,
     verbose: bool,
     result: &mut CheckResult,
 ) {
     let mut issues: Vec<String> = Vec::new();
 
     // Check if installed
-    let installed = if let Some(module) = ModuleRegistry::get_builtin(name) {
+    let installed = if let Some(module) = ModuleRegistry::load_module(config_dir, name) {
         detect::check_installed(name, &module.metadata.detect_command).is_ok()
     } else {
         // Tier 2: try `which <tool_name>`
@@ -155,8 +343,16 @@ fn check_tool(
     let mut missing_paths = Vec::new();
 
     for path_str in &entry.config_paths {
+        if util::paths::is_user_tilde(path_str) {
+            issues.push(format!("unsupported '~user' path {}", path_str));
+            result.hints.push(format!(
+                "replace '{}' in manifest.toml with a '~/' or absolute path",
+                path_str
+            ));
+        }
         let path = util::paths::expand_tilde(path_str);
-        if path.exists() {
+        // A glob matching nothing yet isn't missing
+        if path.exists() || util::paths::is_glob(path_str) {
             existing += 1;
         } else if util::fs::is_symlink(&path) {
             broken_links.push(path_str.clone());
@@ -175,19 +371,28 @@ fn check_tool(
     }
 
     // Config syntax validation (Tier 1 only)
-    if let Some(module) = ModuleRegistry::get_builtin(name) {
+    if let Some(module) = ModuleRegistry::load_module(config_dir, name) {
+        let rules = ModuleRegistry::load_validation(config_dir, name);
         for path_str in &entry.config_paths {
             let path = util::paths::expand_tilde(path_str);
             if path.is_file() {
-                if let Ok(vr) = validate::validate_config(&path, &module.metadata.config_format) {
+                let format = &module.metadata.config_format;
+                let validated =
+                    validate::validate_config(&path, format, rules.as_ref(), use_shellcheck);
+                if let Ok(vr) = validated {
                     if !vr.valid {
                         issues.push(format!("syntax issues in {}", path_str));
                         if verbose {
                             for err in &vr.errors {
-                                println!("          {} {}", "SYNTAX".yellow(), err);
+                                say!(result, "          {} {}", "SYNTAX".yellow(), err);
                             }
                         }
                     }
+                    if verbose {
+                        for note in &vr.notes {
+                            say!(result, "          {} {}", "NOTE".dimmed(), note);
+                        }
+                    }
                 }
             }
         }
@@ -243,7 +448,7 @@ fn check_tool(
         install_status.to_string()
     };
 
-    println!("    {} {:<12} {}", icon, name, detail.dimmed());
+    say!(result, "    {} {:<12} {}", icon, name, detail.dimmed());
 
     if verbose {
         for path_str in &entry.config_paths {
@@ -255,12 +460,308 @@ fn check_tool(
             } else {
                 "MISSING".yellow().to_string()
             };
-            println!("          {} {}", indicator, path_str);
+            say!(result, "          {} {}", indicator, path_str);
         }
     }
+
+    result.tools.push(ToolReport {
+        name: name.to_string(),
+        installed,
+        existing_paths: existing,
+        total_paths: total,
+        issues,
+        status: if has_error {
+            "error"
+        } else if !is_ok {
+            "warn"
+        } else {
+            "ok"
+        },
+    });
 }
 
-fn print_summary(result: &CheckResult) {
+/// Flag config files that changed a lot, recently, and not through an
+/// operation dotsmith logged (edit, deploy, rollback, profile load) — e.g. an
+/// installer appending to `.zshrc`.
+fn check_outside_changes(
+    name: &str,
+    entry: &crate::core::manifest::ToolEntry,
+    engine: &SnapshotEngine,
+    result: &mut CheckResult,
+) {
+    let Ok(diffs) = engine.diff_current(name, &entry.config_paths) else {
+        return;
+    };
+
+    let now = Utc::now();
+    for diff in diffs {
+        // Binary files and files never snapshotted have nothing to measure against
+        if diff.binary || diff.old_content.is_empty() {
+            continue;
+        }
+
+        let changed = util::diff::changed_lines(&diff.old_content, &diff.new_content);
+        if !is_large_change(changed, diff.old_content.lines().count()) {
+            continue;
+        }
+
+        let path = util::paths::expand_tilde(&diff.file_path);
+        let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) else {
+            continue;
+        };
+        let modified: DateTime<Utc> = modified.into();
+        if now.signed_duration_since(modified) > Duration::hours(RECENT_CHANGE_HOURS) {
+            continue;
+        }
+
+        let last_op = engine.last_operation(&diff.file_path).ok().flatten();
+        if explained_by(modified, last_op.as_ref().map(|(_, at)| *at)) {
+            continue;
+        }
+
+        let age = format_age(now.signed_duration_since(modified));
+        say!(
+            result,
+            "    {}  {} changed {} line(s) {} ago outside dotsmith",
+            "!!".yellow().bold(),
+            diff.file_path,
+            changed,
+            age
+        );
+        result.add_issue(format!(
+            "{} changed {} line(s) {} ago outside dotsmith",
+            diff.file_path, changed, age
+        ));
+        result.warn += 1;
+        result.hints.push(format!(
+            "review with `dotsmith diff {}`; snapshot it if the change is expected",
+            name
+        ));
+    }
+}
+
+/// Flag sensitive files (see `perms::DEFAULT_SENSITIVE_PATHS`) that group or
+/// other users can access, tightening them to 0600 when `fix` is set.
+fn check_permissions(
+    entry: &crate::core::manifest::ToolEntry,
+    sensitive_paths: &[String],
+    fix: bool,
+    result: &mut CheckResult,
+) {
+    let open = match perms::find_open_files(&entry.config_paths, sensitive_paths) {
+        Ok(open) => open,
+        Err(e) => {
+            say!(result, "    {}  couldn't check permissions: {:#}", "!!".yellow().bold(), e);
+            result.add_issue(format!("couldn't check permissions: {:#}", e));
+            result.warn += 1;
+            return;
+        }
+    };
+
+    let mut unfixed = 0;
+    for file in open {
+        let path = util::paths::contract_tilde(&file.path);
+        if fix {
+            match perms::tighten(&file.path) {
+                Ok(()) => {
+                    say!(
+                        result,
+                        "    {}  {} permissions {:o} -> {:o}",
+                        "FIXED".green().bold(),
+                        path,
+                        file.mode,
+                        perms::SENSITIVE_MODE
+                    );
+                    continue;
+                }
+                Err(e) => say!(result, "    {}  {}: {}", "ERR".red().bold(), path, e),
+            }
+        } else {
+            say!(
+                result,
+                "    {}  {} is mode {:o}, accessible by group or other users",
+                "!!".yellow().bold(),
+                path,
+                file.mode
+            );
+        }
+        result.add_issue(format!(
+            "{} is mode {:o}, accessible by group or other users",
+            path, file.mode
+        ));
+        unfixed += 1;
+    }
+
+    if unfixed > 0 {
+        result.warn += 1;
+        if !fix {
+            result.hints.push(
+                "run `dotsmith doctor --fix-perms` to restrict sensitive files to 0600".to_string(),
+            );
+        }
+    }
+}
+
+/// Changes at least this recent are checked for an outside origin.
+const RECENT_CHANGE_HOURS: i64 = 24;
+
+/// A change this many lines long is always worth a look.
+const LARGE_CHANGE_LINES: usize = 20;
+
+/// Whether a diff is big enough to flag: many lines, or at least half of a
+/// small file.
+fn is_large_change(changed: usize, old_lines: usize) -> bool {
+    changed >= LARGE_CHANGE_LINES || (changed >= 5 && changed * 2 >= old_lines)
+}
+
+/// A modification is accounted for if dotsmith logged a write to the file at
+/// or after its mtime (timestamps are stored to the second).
+fn explained_by(modified: DateTime<Utc>, last_op: Option<DateTime<Utc>>) -> bool {
+    last_op.is_some_and(|at| at >= modified - Duration::seconds(2))
+}
+
+fn format_age(age: Duration) -> String {
+    if age.num_hours() > 0 {
+        format!("{}h", age.num_hours())
+    } else {
+        format!("{}m", age.num_minutes().max(1))
+    }
+}
+
+/// Compare each config file with the tool's option catalog (and the shipped
+/// default config, when the module has one).
+fn deep_check(
+    config_dir: &std::path::Path,
+    name: &str,
+    entry: &crate::core::manifest::ToolEntry,
+    verbose: bool,
+    result: &mut CheckResult,
+) {
+    let (Some(module), Some(catalog)) = (
+        ModuleRegistry::load_module(config_dir, name),
+        ModuleRegistry::load_options(config_dir, name),
+    )
+    else {
+        if verbose {
+            say!(result, "          {} no option catalog for {}", "deep".dimmed(), name);
+        }
+        return;
+    };
+    let format = &module.metadata.config_format;
+    let mut typos = 0;
+
+    for (i, path_str) in entry.config_paths.iter().enumerate() {
+        let path = util::paths::expand_tilde(path_str);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let report = audit::audit_config(&content, format, &catalog.options);
+        say!(
+            result,
+            "          {} {}: {} customized, {} at default, {} not in catalog",
+            "deep".cyan(),
+            path_str,
+            report.customized.len(),
+            report.redundant.len(),
+            report.unknown.len()
+        );
+
+        if i == 0
+            && let Some(ref default) = module.metadata.default_config
+        {
+            say!(
+                result,
+                "            {} line(s) differ from the shipped default config",
+                util::diff::changed_lines(default, &content)
+            );
+        }
+
+        for c in &report.customized {
+            let default = match c.default.as_deref() {
+                Some("") | None => "unset".to_string(),
+                Some(d) => d.to_string(),
+            };
+            say!(
+                result,
+                "            {} = {} {}",
+                c.name,
+                c.value,
+                format!("(default: {})", default).dimmed()
+            );
+        }
+
+        for unknown in report.likely_typos() {
+            typos += 1;
+            say!(
+                result,
+                "            {} line {}: unknown option '{}' — did you mean '{}'?",
+                "!!".yellow(),
+                unknown.line,
+                unknown.name,
+                unknown.suggestion.as_deref().unwrap_or_default()
+            );
+        }
+
+        if verbose {
+            for c in &report.redundant {
+                say!(result, "            {} {} is already the default", "=".dimmed(), c.name);
+            }
+            for unknown in report.unknown.iter().filter(|u| u.suggestion.is_none()) {
+                say!(
+                    result,
+                    "            {} line {}: '{}' is not in the catalog",
+                    "?".dimmed(),
+                    unknown.line,
+                    unknown.name
+                );
+            }
+        }
+    }
+
+    if typos > 0 {
+        result.add_issue(format!("{} misspelled option name(s)", typos));
+        result.warn += 1;
+        result.hints.push(format!(
+            "{} option name(s) in {} look misspelled — fix them or check `dotsmith explore {}`",
+            typos, name, name
+        ));
+    }
+}
+
+fn print_summary(result: &CheckResult) -> Result<()> {
+    let mut hints = result.hints.clone();
+    if (result.warn > 0 || result.error > 0) && hints.is_empty() {
+        hints.push("run `dotsmith snapshot` to create initial snapshots".to_string());
+    }
+
+    if result.json {
+        #[derive(Serialize)]
+        struct Summary {
+            ok: usize,
+            warn: usize,
+            error: usize,
+        }
+        #[derive(Serialize)]
+        struct Report<'a> {
+            tools: &'a [ToolReport],
+            summary: Summary,
+            hints: &'a [String],
+        }
+
+        let report = Report {
+            tools: &result.tools,
+            summary: Summary {
+                ok: result.ok,
+                warn: result.warn,
+                error: result.error,
+            },
+            hints: &hints,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
     println!(
         "  Summary: {} healthy, {} warnings, {} errors",
         result.ok.to_string().green(),
@@ -268,16 +769,11 @@ fn print_summary(result: &CheckResult) {
         result.error.to_string().red()
     );
 
-    for hint in &result.hints {
+    for hint in &hints {
         println!("    {}: {}", "hint".cyan(), hint);
     }
 
-    if (result.warn > 0 || result.error > 0) && result.hints.is_empty() {
-        println!(
-            "    {}: run `dotsmith snapshot` to create initial snapshots",
-            "hint".cyan()
-        );
-    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -286,7 +782,7 @@ mod tests {
 
     #[test]
     fn test_check_result_new() {
-        let r = CheckResult::new();
+        let r = CheckResult::new(false);
         assert_eq!(r.ok, 0);
         assert_eq!(r.warn, 0);
         assert_eq!(r.error, 0);
@@ -295,7 +791,7 @@ mod tests {
 
     #[test]
     fn test_check_result_counts() {
-        let mut r = CheckResult::new();
+        let mut r = CheckResult::new(false);
         r.ok = 5;
         r.warn = 2;
         r.error = 1;
@@ -303,4 +799,56 @@ mod tests {
         assert_eq!(r.ok + r.warn + r.error, 8);
         assert_eq!(r.hints.len(), 1);
     }
+
+    #[test]
+    fn test_is_large_change() {
+        assert!(is_large_change(20, 1000));
+        assert!(is_large_change(6, 10));
+        assert!(!is_large_change(6, 100));
+        assert!(!is_large_change(2, 2));
+    }
+
+    #[test]
+    fn test_explained_by() {
+        let modified = Utc::now();
+        assert!(!explained_by(modified, None));
+        assert!(explained_by(modified, Some(modified + Duration::minutes(3))));
+        assert!(explained_by(modified, Some(modified - Duration::seconds(1))));
+        assert!(!explained_by(modified, Some(modified - Duration::hours(1))));
+    }
+
+    #[test]
+    fn test_check_permissions_flags_group_writable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let secret = tmp.path().join("token");
+        std::fs::write(&secret, "x").unwrap();
+        std::fs::set_permissions(&secret, std::fs::Permissions::from_mode(0o620)).unwrap();
+        let entry = crate::core::manifest::ToolEntry {
+            tier: 2,
+            config_paths: vec![secret.display().to_string()],
+            plugins_managed: false,
+            plugin_manager: None,
+            added_at: Utc::now(),
+            last_snapshot: None,
+            plugins: Default::default(),
+        };
+
+        let mut result = CheckResult::new(true);
+        result.tools.push(ToolReport {
+            name: "tool".to_string(),
+            installed: true,
+            existing_paths: 1,
+            total_paths: 1,
+            issues: Vec::new(),
+            status: "ok",
+        });
+        check_permissions(&entry, &[secret.display().to_string()], false, &mut result);
+        assert_eq!(result.warn, 1);
+        assert_eq!(
+            result.tools[0].issues,
+            [format!("{} is mode 620, accessible by group or other users", secret.display())]
+        );
+    }
 }
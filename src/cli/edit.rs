@@ -1,15 +1,30 @@
 use std::fs;
-use std::process::Command;
+use std::path::Path;
 
 use anyhow::{Context, Result};
 use colored::Colorize;
 use sha2::{Digest, Sha256};
 
+use crate::core::config::DotsmithConfig;
+use crate::core::editor;
 use crate::core::manifest::Manifest;
-use crate::core::snapshot::SnapshotEngine;
+use crate::core::module::ModuleRegistry;
+use crate::core::output::Printer;
+use crate::core::snapshot::{self, MessageContext, SnapshotEngine};
+use crate::core::validate;
 use crate::util;
 
-pub fn run(verbose: bool, tool: &str) -> Result<()> {
+/// What to do after the editor exits.
+pub struct EditOpts {
+    /// Check the saved file's syntax.
+    pub validate: bool,
+    /// Roll back an invalid file without asking.
+    pub yes_rollback: bool,
+    /// Open every config file of the tool, not just the first.
+    pub all: bool,
+}
+
+pub fn run(out: &Printer, tool: &str, opts: &EditOpts) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let manifest = Manifest::load(&config_dir)?;
 
@@ -18,75 +33,166 @@ pub fn run(verbose: bool, tool: &str) -> Result<()> {
         .get(tool)
         .ok_or_else(|| anyhow::anyhow!("'{}' is not tracked by dotsmith", tool))?;
 
-    // Find first config file (not directory)
-    let file_path = pick_first_file(&entry.config_paths)
-        .ok_or_else(|| anyhow::anyhow!("no config file found for '{}'", tool))?;
+    // The first config file (not directory), or all of them
+    let files = if opts.all {
+        snapshot::config_files(&entry.config_paths)?
+    } else {
+        pick_first_file(&entry.config_paths).into_iter().collect()
+    };
+    if files.is_empty() {
+        anyhow::bail!("no config file found for '{}'", tool);
+    }
 
-    if verbose {
-        println!("  editing {}", file_path.display());
+    for file_path in &files {
+        out.detail(format_args!("  editing {}", file_path.display()));
     }
 
     // Auto-snapshot before editing
-    let snapshot_engine = SnapshotEngine::open(&config_dir)?;
-    match snapshot_engine.snapshot_tool(tool, &entry.config_paths, Some("pre-edit snapshot")) {
-        Ok(count) => {
-            if verbose {
-                println!("  snapshotted {} file(s) before editing", count);
-            }
-        }
-        Err(e) => {
-            if verbose {
-                eprintln!("  warning: pre-edit snapshot failed: {}", e);
-            }
-        }
+    let config = DotsmithConfig::load(&config_dir);
+    let mut snapshot_engine = SnapshotEngine::open(&config_dir)?;
+    snapshot_engine.keep_secrets();
+    let message = snapshot::format_message(
+        &config.general.snapshot_message,
+        &MessageContext::now(tool, "pre-edit"),
+    );
+    match snapshot_engine.snapshot_tool(tool, &entry.config_paths, Some(&message)) {
+        Ok(count) => out.detail(format_args!("  snapshotted {} file(s) before editing", count)),
+        Err(e) => eprintln!("  warning: pre-edit snapshot failed: {}", e),
     }
+    crate::cli::snapshot::warn_kept_secrets(&snapshot_engine);
 
-    // Hash before editing
-    let hash_before = hash_file(&file_path);
+    // Hash before editing, and the snapshot holding that content for an
+    // exact rollback
+    let mut before = Vec::new();
+    for file_path in &files {
+        let pre_edit_id = match fs::read(file_path) {
+            Ok(content) => snapshot_engine.find_snapshot(
+                tool,
+                &util::paths::contract_tilde(file_path),
+                &content,
+            )?,
+            Err(_) => None,
+        };
+        before.push((hash_file(file_path), pre_edit_id));
+    }
 
     // Open editor
-    let editor = find_editor();
-    if verbose {
-        println!("  using editor: {}", editor);
-    }
+    let format = config_format(&config_dir, tool, &files[0]);
+    let editor = editor::resolve(tool, format.as_deref(), &config);
+    out.detail(format_args!("  using editor: {} (from {})", editor.template, editor.source));
 
-    let status = Command::new(&editor)
-        .arg(&file_path)
+    let status = editor
+        .command(&files)?
         .status()
-        .with_context(|| format!("failed to launch editor '{}'", editor))?;
+        .with_context(|| format!("failed to launch editor '{}'", editor.template))?;
 
     if !status.success() {
         anyhow::bail!("editor exited with non-zero status");
     }
 
-    // Check if file changed
-    let hash_after = hash_file(&file_path);
-    if hash_before != hash_after {
-        println!(
+    // Check which files changed
+    let mut changed = false;
+    for (file_path, (hash_before, pre_edit_id)) in files.iter().zip(before) {
+        if hash_file(file_path) == hash_before {
+            continue;
+        }
+        changed = true;
+        let edited = util::paths::contract_tilde(file_path);
+        if let Err(e) = snapshot_engine.record_operation("edit", &[edited])
+            && out.is_verbose()
+        {
+            eprintln!("  warning: failed to log edit: {}", e);
+        }
+        out.info(format_args!(
             "  {} modified — run {} to review",
-            util::paths::contract_tilde(&file_path).bold(),
+            util::paths::contract_tilde(file_path).bold(),
             format!("dotsmith diff {}", tool).cyan()
-        );
-    } else {
-        println!("  no changes detected");
+        ));
+        if opts.validate {
+            check_edit(
+                &config_dir,
+                &config,
+                &snapshot_engine,
+                tool,
+                file_path,
+                pre_edit_id,
+                opts,
+            )?;
+        }
+    }
+    if !changed {
+        out.info("  no changes detected");
     }
 
     Ok(())
 }
 
-/// Find the user's preferred editor.
-fn find_editor() -> String {
-    if let Ok(editor) = std::env::var("EDITOR") {
-        if !editor.is_empty() {
-            return editor;
-        }
+/// The tool's config format from its module, or guessed from `path`.
+fn config_format(config_dir: &Path, tool: &str, path: &Path) -> Option<String> {
+    match ModuleRegistry::load_module(config_dir, tool) {
+        Some(module) => Some(module.metadata.config_format),
+        None => validate::guess_format(path).map(str::to_string),
     }
-    if let Ok(visual) = std::env::var("VISUAL") {
-        if !visual.is_empty() {
-            return visual;
-        }
+}
+
+/// Validate the edited file and, if it's broken, offer to roll back to
+/// `pre_edit_id`.
+fn check_edit(
+    config_dir: &std::path::Path,
+    config: &DotsmithConfig,
+    engine: &SnapshotEngine,
+    tool: &str,
+    path: &std::path::Path,
+    pre_edit_id: Option<i64>,
+    opts: &EditOpts,
+) -> Result<()> {
+    let Some(format) = config_format(config_dir, tool, path) else {
+        return Ok(());
+    };
+    let rules = ModuleRegistry::load_validation(config_dir, tool);
+    let result =
+        validate::validate_config(path, &format, rules.as_ref(), config.general.use_shellcheck)?;
+    if result.valid {
+        return Ok(());
+    }
+
+    println!(
+        "  {} {} has errors:",
+        "ERR".red().bold(),
+        util::paths::contract_tilde(path)
+    );
+    for line in result.render(path).lines() {
+        println!("       {}", line);
+    }
+
+    let Some(id) = pre_edit_id else {
+        println!("  {} no pre-edit snapshot to roll back to", "!!".yellow());
+        return Ok(());
+    };
+    let rollback = if opts.yes_rollback {
+        true
+    } else if util::prompt::is_interactive() {
+        util::prompt::confirm(&format!("  Roll back to the pre-edit snapshot #{}?", id))?
+    } else {
+        false
+    };
+    if !rollback {
+        println!(
+            "  {} kept the edit — {} restores the previous version",
+            "!!".yellow(),
+            format!("dotsmith rollback {}", id).cyan()
+        );
+        return Ok(());
     }
-    "vi".to_string()
+
+    let restored = engine.rollback(id, &config_dir.join("backups"))?;
+    println!(
+        "{} Rolled back {} to snapshot #{}",
+        "OK".green().bold(),
+        restored.bold(),
+        id
+    );
+    Ok(())
 }
 
 /// Pick the first config file (not directory) from the list.
@@ -117,22 +223,6 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
-    #[test]
-    fn test_find_editor_env() {
-        let original = std::env::var("EDITOR").ok();
-        // SAFETY: Test isolation — no concurrent tests depend on EDITOR
-        unsafe {
-            std::env::set_var("EDITOR", "nano");
-        }
-        assert_eq!(find_editor(), "nano");
-        unsafe {
-            match original {
-                Some(val) => std::env::set_var("EDITOR", val),
-                None => std::env::remove_var("EDITOR"),
-            }
-        }
-    }
-
     #[test]
     fn test_pick_first_file() {
         let tmp = TempDir::new().unwrap();
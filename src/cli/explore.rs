@@ -0,0 +1,66 @@
+use anyhow::{Result, bail};
+use colored::Colorize;
+
+use crate::core::config::DotsmithConfig;
+use crate::core::errors::DotsmithError;
+use crate::tui;
+use crate::util;
+
+pub fn run(tool: Option<&str>, view: Option<&str>, list_views: bool) -> Result<()> {
+    if list_views {
+        return print_views();
+    }
+
+    let Some(view_name) = view else {
+        return tui::run(tool, None);
+    };
+
+    let config_dir = util::paths::config_dir()?;
+    let config = DotsmithConfig::load(&config_dir);
+    let Some(saved) = config.views.get(view_name) else {
+        return Err(DotsmithError::ViewNotFound(view_name.to_string()).into());
+    };
+
+    if let Some(tool) = tool
+        && tool != saved.tool
+    {
+        bail!("view '{}' is for {}, not {}", view_name, saved.tool, tool);
+    }
+
+    tui::run(Some(&saved.tool), Some(saved))
+}
+
+fn print_views() -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let config = DotsmithConfig::load(&config_dir);
+
+    if config.views.is_empty() {
+        println!("No saved views.");
+        println!(
+            "  Press {} in {} to save the current filter.",
+            "v".bold(),
+            "dotsmith explore <tool>".bold()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{:<20} {:<10} {:<16} {}",
+        "View".bold(),
+        "Tool".bold(),
+        "Category".bold(),
+        "Search".bold()
+    );
+    println!("{}", "-".repeat(58));
+
+    for (name, view) in &config.views {
+        let query = if view.query.is_empty() {
+            "-".to_string()
+        } else {
+            format!("\"{}\"", view.query)
+        };
+        println!("{:<20} {:<10} {:<16} {}", name, view.tool, view.category, query);
+    }
+
+    Ok(())
+}
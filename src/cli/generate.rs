@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use colored::Colorize;
+
+use crate::core::generate::{self, SheetFormat};
+use crate::core::manifest::Manifest;
+use crate::core::module::{ModuleRegistry, OptionEntry};
+use crate::core::output::Printer;
+use crate::util;
+
+pub fn run(
+    out: &Printer,
+    tools: &[String],
+    out_path: Option<&Path>,
+    format: Option<&str>,
+) -> Result<()> {
+    let format = match format {
+        Some(name) => match SheetFormat::from_name(name) {
+            Some(f) => f,
+            None => bail!("unknown format '{}' — use md or txt", name),
+        },
+        None => out_path.map(SheetFormat::from_path).unwrap_or(SheetFormat::Md),
+    };
+
+    let config_dir = util::paths::config_dir()?;
+    let names: Vec<String> = if tools.is_empty() {
+        tracked_tools_with_options(&config_dir)?
+    } else {
+        tools.to_vec()
+    };
+
+    let mut sections: Vec<(&str, Vec<OptionEntry>)> = Vec::new();
+    for name in &names {
+        if sections.iter().any(|(n, _)| n == name) {
+            continue;
+        }
+        let Some(db) = ModuleRegistry::load_options(&config_dir, name) else {
+            bail!(
+                "no option database for '{}' — available: {}",
+                name,
+                ModuleRegistry::module_names(&config_dir).join(", ")
+            );
+        };
+        sections.push((name.as_str(), db.options));
+    }
+
+    let content = generate::cheatsheet(&sections, format);
+
+    let Some(out_path) = out_path else {
+        print!("{}", content);
+        return Ok(());
+    };
+
+    util::fs::atomic_write(out_path, &content)?;
+
+    let total: usize = sections.iter().map(|(_, opts)| opts.len()).sum();
+    out.info(format_args!(
+        "  {} Wrote {} ({} option(s) across {} tool(s))",
+        "OK".green().bold(),
+        out_path.display(),
+        total,
+        sections.len()
+    ));
+    for (name, opts) in &sections {
+        out.detail(format_args!("    {:<10} {} option(s)", name, opts.len()));
+    }
+
+    Ok(())
+}
+
+/// Tracked tools that have an option database, in manifest order.
+fn tracked_tools_with_options(config_dir: &Path) -> Result<Vec<String>> {
+    let manifest = Manifest::load(config_dir)?;
+
+    let names: Vec<String> = manifest
+        .tools
+        .keys()
+        .filter(|name| ModuleRegistry::load_options(config_dir, name).is_some())
+        .cloned()
+        .collect();
+
+    if names.is_empty() {
+        bail!("no tracked tools have an option database — pass --tools, e.g. --tools tmux,git");
+    }
+
+    Ok(names)
+}
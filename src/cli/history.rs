@@ -2,31 +2,63 @@ use anyhow::Result;
 use colored::Colorize;
 
 use crate::core::manifest::Manifest;
-use crate::core::snapshot::SnapshotEngine;
+use crate::core::snapshot::{SnapshotEngine, SnapshotSummary};
 use crate::util;
 
-/// Show snapshot history for a tool.
-pub fn run(_verbose: bool, tool: &str, limit: usize) -> Result<()> {
+/// Default number of entries shown when `--limit` is not given.
+const DEFAULT_LIMIT: usize = 20;
+
+/// Show snapshot history for a tool, or for all tools when `tool` is `None`.
+pub fn run(
+    tool: Option<&str>,
+    limit: Option<usize>,
+    csv: bool,
+    json: bool,
+) -> Result<()> {
+    if csv && json {
+        anyhow::bail!("--csv and --json cannot be used together");
+    }
+
     let config_dir = util::paths::config_dir()?;
     let manifest = Manifest::load(&config_dir)?;
+    let engine = SnapshotEngine::open(&config_dir)?;
 
-    if !manifest.has_tool(tool) {
-        anyhow::bail!("'{}' is not tracked by dotsmith", tool);
+    if let Some(tool) = tool {
+        check_known(&manifest, &engine, tool)?;
     }
 
-    let engine = SnapshotEngine::open(&config_dir)?;
-    let history = engine.history(tool, limit)?;
+    // CSV and JSON export everything unless a limit is given explicitly
+    let limit = limit.unwrap_or(if csv || json { usize::MAX } else { DEFAULT_LIMIT });
+
+    let history = match tool {
+        Some(t) => engine.history(t, limit)?,
+        None => engine.history_all(limit)?,
+    };
+
+    if csv {
+        print!("{}", to_csv(&history));
+        return Ok(());
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&history)?);
+        return Ok(());
+    }
+
+    let scope = tool.unwrap_or("all tools");
 
     if history.is_empty() {
-        println!("No snapshots found for {}", tool.bold());
+        println!("No snapshots found for {}", scope.bold());
         println!(
             "  Run {} to take one.",
-            format!("dotsmith snapshot {}", tool).bold()
+            format!("dotsmith snapshot {}", tool.unwrap_or_default())
+                .trim_end()
+                .bold()
         );
         return Ok(());
     }
 
-    println!("{} snapshot history for {}:", "Showing".bold(), tool.bold());
+    println!("{} snapshot history for {}:", "Showing".bold(), scope.bold());
     println!();
 
     for entry in &history {
@@ -36,14 +68,35 @@ pub fn run(_verbose: bool, tool: &str, limit: usize) -> Result<()> {
             .message
             .as_deref()
             .unwrap_or("(no message)");
+        let mut pin = if entry.pinned {
+            format!("  {}", "(pinned)".yellow())
+        } else {
+            String::new()
+        };
+        for tag in &entry.tags {
+            pin.push_str(&format!("  {}", format!("[{}]", tag).green()));
+        }
 
-        println!(
-            "  {}  {}  {}  {}",
-            id,
-            entry.created_at.dimmed(),
-            hash_short.yellow(),
-            entry.file_path
-        );
+        if tool.is_some() {
+            println!(
+                "  {}  {}  {}  {}{}",
+                id,
+                entry.created_at.dimmed(),
+                hash_short.yellow(),
+                entry.file_path,
+                pin
+            );
+        } else {
+            println!(
+                "  {}  {}  {}  {:<10} {}{}",
+                id,
+                entry.created_at.dimmed(),
+                hash_short.yellow(),
+                entry.tool.bold(),
+                entry.file_path,
+                pin
+            );
+        }
         if msg != "(no message)" {
             println!("       {}", msg.dimmed());
         }
@@ -58,3 +111,106 @@ pub fn run(_verbose: bool, tool: &str, limit: usize) -> Result<()> {
 
     Ok(())
 }
+
+/// Fail for a tool that is neither tracked nor has snapshots of its own,
+/// as one snapshotted with `snapshot --file` does.
+fn check_known(manifest: &Manifest, engine: &SnapshotEngine, tool: &str) -> Result<()> {
+    if !manifest.has_tool(tool) && engine.snapshot_paths(tool)?.is_empty() {
+        anyhow::bail!("'{}' is not tracked by dotsmith", tool);
+    }
+    Ok(())
+}
+
+/// Write a tool's snapshot history into `dir` as a patch series.
+pub fn run_export(tool: &str, dir: &str) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let manifest = Manifest::load(&config_dir)?;
+    let engine = SnapshotEngine::open(&config_dir)?;
+    check_known(&manifest, &engine, tool)?;
+
+    let dir = util::paths::expand_tilde_checked(dir)?;
+    let patches = engine.export_patches(tool, &dir)?;
+    if patches.is_empty() {
+        println!("No snapshots found for {}", tool.bold());
+        return Ok(());
+    }
+
+    println!(
+        "{} Wrote {} patch(es) to {}",
+        "OK".green().bold(),
+        patches.len(),
+        util::paths::contract_tilde(&dir)
+    );
+    println!(
+        "  Replay them with {}",
+        format!("git am {}/*.patch", util::paths::contract_tilde(&dir)).bold()
+    );
+    Ok(())
+}
+
+/// Render snapshot summaries as CSV with a header row.
+fn to_csv(history: &[SnapshotSummary]) -> String {
+    let mut out = String::from("id,tool,file_path,hash,message,created_at\n");
+    for entry in history {
+        let fields = [
+            entry.id.to_string(),
+            csv_field(&entry.tool),
+            csv_field(&entry.file_path),
+            csv_field(&entry.hash),
+            csv_field(entry.message.as_deref().unwrap_or("")),
+            csv_field(&entry.created_at),
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quote a CSV field if it contains a delimiter, quote, or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_plain() {
+        assert_eq!(csv_field("tmux"), "tmux");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line1\nline2"), "\"line1\nline2\"");
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let history = vec![SnapshotSummary {
+            id: 3,
+            tool: "tmux".to_string(),
+            file_path: "~/.tmux.conf".to_string(),
+            hash: "abc123".to_string(),
+            message: Some("mouse, finally".to_string()),
+            created_at: "2026-01-01 10:00:00".to_string(),
+            pinned: false,
+            tags: Vec::new(),
+        }];
+        let csv = to_csv(&history);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("id,tool,file_path,hash,message,created_at"));
+        assert_eq!(
+            lines.next(),
+            Some("3,tmux,~/.tmux.conf,abc123,\"mouse, finally\",2026-01-01 10:00:00")
+        );
+        assert_eq!(lines.next(), None);
+    }
+}
@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::Utc;
+use colored::Colorize;
+
+use crate::core::detect;
+use crate::core::import;
+use crate::core::manifest::{Manifest, ToolEntry};
+use crate::core::output::Printer;
+use crate::util;
+
+pub fn run(out: &Printer, path: &Path) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let mut manifest = Manifest::load(&config_dir)?;
+
+    let packages = import::scan_stow_repo(path)?;
+    if packages.is_empty() {
+        anyhow::bail!(
+            "no packages found in {} — expected one directory per tool",
+            path.display()
+        );
+    }
+
+    let mut imported = 0;
+    for (tool, paths) in packages {
+        if manifest.has_tool(&tool) {
+            out.info(format_args!("{} {} already tracked, skipping", "--".dimmed(), tool.bold()));
+            continue;
+        }
+
+        let (duplicate, paths): (Vec<_>, Vec<_>) = paths
+            .into_iter()
+            .partition(|p| import::already_tracked(&manifest, p));
+        if paths.is_empty() {
+            out.info(format_args!(
+                "{} {} files are all tracked by other tools, skipping",
+                "--".dimmed(),
+                tool.bold()
+            ));
+            continue;
+        }
+
+        let targets: Vec<PathBuf> = paths.iter().map(|p| p.target.clone()).collect();
+        let entry = ToolEntry {
+            tier: 2,
+            config_paths: targets.iter().map(|p| util::paths::contract_tilde(p)).collect(),
+            plugins_managed: false,
+            plugin_manager: detect::detect_plugin_manager(&tool, &targets),
+            added_at: Utc::now(),
+            last_snapshot: None,
+            plugins: std::collections::BTreeMap::new(),
+        };
+        manifest.add_tool(&tool, entry)?;
+        imported += 1;
+
+        let deployed = paths.iter().filter(|p| p.deployed).count();
+        out.info(format_args!(
+            "{} Imported {} ({} path(s), {} deployed)",
+            "OK".green().bold(),
+            tool.bold(),
+            paths.len(),
+            deployed
+        ));
+        if out.is_verbose() {
+            for p in &paths {
+                let suffix = if p.deployed {
+                    format!(" {} {}", "->".dimmed(), p.source.display())
+                } else {
+                    format!(" {}", "(not deployed)".dimmed())
+                };
+                let target = util::paths::contract_tilde(&p.target);
+                out.detail(format_args!("    {}{}", target, suffix));
+            }
+        }
+        for p in &duplicate {
+            out.info(format_args!(
+                "  {} already tracked: {}",
+                "--".dimmed(),
+                util::paths::contract_tilde(&p.target)
+            ));
+        }
+    }
+
+    manifest.save(&config_dir)?;
+    out.info(format_args!("  {} tool(s) imported", imported));
+
+    Ok(())
+}
@@ -5,7 +5,11 @@ use anyhow::{Context, Result};
 use colored::Colorize;
 
 use crate::core::config::DotsmithConfig;
+use crate::core::detect;
+use crate::core::hook;
 use crate::core::manifest::Manifest;
+use crate::core::output::Printer;
+use crate::core::repo;
 use crate::util;
 
 /// Ensure dotsmith is initialized. Creates config dir, config.toml, and
@@ -36,15 +40,19 @@ pub fn ensure_initialized() -> Result<()> {
     Ok(())
 }
 
-pub fn run(verbose: bool) -> Result<()> {
+pub fn run(out: &Printer, non_interactive: bool) -> Result<()> {
+    if !non_interactive && util::prompt::is_interactive() {
+        return run_wizard(out);
+    }
+
     let config_dir = util::paths::config_dir()?;
 
     if config_dir.join("manifest.toml").exists() {
-        println!(
+        out.info(format_args!(
             "{} dotsmith is already initialized at {}",
             "OK".green().bold(),
             config_dir.display()
-        );
+        ));
         return Ok(());
     }
 
@@ -62,21 +70,103 @@ pub fn run(verbose: bool) -> Result<()> {
     let manifest = Manifest::default();
     manifest.save(&config_dir)?;
 
-    if verbose {
-        println!("Created {}", config_dir.display());
-        println!("  config.toml  (dotsmith settings)");
-        println!("  manifest.toml (tracked tools)");
-    }
+    out.detail(format_args!("Created {}", config_dir.display()));
+    out.detail("  config.toml  (dotsmith settings)");
+    out.detail("  manifest.toml (tracked tools)");
 
-    println!(
+    out.info(format_args!(
         "{} Initialized dotsmith at {}",
         "OK".green().bold(),
         config_dir.display()
-    );
-    println!(
+    ));
+    out.info(format_args!(
         "  Run {} to start tracking a tool.",
         "dotsmith add <tool>".bold()
+    ));
+
+    Ok(())
+}
+
+/// Guided setup: dotfiles repo, editor, watch hook, and detected tools.
+/// On an initialized directory it offers to reconfigure, starting from the
+/// current settings, and never re-adds tools that are already tracked.
+fn run_wizard(out: &Printer) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+
+    if config_dir.join("manifest.toml").exists() {
+        println!(
+            "dotsmith is already initialized at {}",
+            config_dir.display()
+        );
+        if !util::prompt::confirm("Reconfigure it?")? {
+            return Ok(());
+        }
+    } else {
+        println!("Setting up dotsmith at {}", config_dir.display());
+    }
+    println!("{}\n", "Press Enter to keep the value in brackets.".dimmed());
+
+    ensure_initialized()?;
+    let mut config = DotsmithConfig::load(&config_dir);
+
+    let repo_path = util::prompt::ask(
+        "Dotfiles git repo (blank to skip):",
+        config.general.repo_path.as_deref(),
+    )?;
+    if !repo_path.is_empty() {
+        let expanded = util::paths::expand_tilde_checked(&repo_path)?;
+        repo::init_repo(&expanded)?;
+        config.general.repo_path = Some(repo_path);
+    }
+
+    let editor = util::prompt::ask(
+        "Editor for `dotsmith edit` (blank uses $EDITOR):",
+        config.general.editor.as_deref(),
+    )?;
+    config.general.editor = Some(editor).filter(|e| !e.is_empty());
+
+    config.save(&config_dir)?;
+
+    let hook_installed = hook::watch_hook_installed(&config_dir);
+    let hook_path = if util::prompt::confirm_default(
+        "Snapshot changed configs whenever a shell starts?",
+        hook_installed,
+    )? {
+        Some(hook::install_watch_hook(&config_dir)?)
+    } else {
+        hook::remove_watch_hook(&config_dir)?;
+        None
+    };
+
+    let manifest = Manifest::load(&config_dir)?;
+    let tracked: Vec<&str> = manifest.tools.keys().map(|k| k.as_str()).collect();
+    let detected = detect::detect_builtin_tools(&tracked);
+    if !detected.is_empty() {
+        println!();
+    }
+    for (tool, paths) in &detected {
+        let shown: Vec<String> = paths.iter().map(|p| util::paths::contract_tilde(p)).collect();
+        if util::prompt::confirm_default(
+            &format!("Track {} ({})?", tool.bold(), shown.join(", ")),
+            true,
+        )? && let Err(e) = crate::cli::add::run(out, tool, &[], None)
+        {
+            println!("  {} could not add {}: {}", "!!".yellow(), tool, e);
+        }
+    }
+
+    println!(
+        "\n{} dotsmith is set up at {}",
+        "OK".green().bold(),
+        config_dir.display()
     );
+    if let Some(path) = hook_path {
+        println!(
+            "\n  {} Add this line to your shell rc file (if not already present):",
+            "Hint:".yellow().bold()
+        );
+        println!("    {}", hook::source_line(&path).dimmed());
+    }
 
     Ok(())
 }
@@ -1,16 +1,48 @@
 use anyhow::Result;
 use colored::Colorize;
+use serde::Serialize;
 
 use crate::core::manifest::Manifest;
+use crate::core::output::Printer;
+use crate::core::snapshot::SnapshotEngine;
 use crate::util;
 
-pub fn run(_verbose: bool) -> Result<()> {
+/// One tool in `dotsmith list --json`.
+#[derive(Serialize)]
+struct ListEntry<'a> {
+    name: &'a str,
+    tier: u8,
+    config_path_count: usize,
+    plugin_count: usize,
+    plugin_manager: Option<&'a str>,
+    /// `created_at` of the tool's newest snapshot.
+    last_snapshot: Option<String>,
+}
+
+pub fn run(out: &Printer, json: bool) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let manifest = Manifest::load(&config_dir)?;
 
+    if json {
+        let engine = SnapshotEngine::open(&config_dir)?;
+        let mut entries = Vec::new();
+        for (name, entry) in &manifest.tools {
+            entries.push(ListEntry {
+                name,
+                tier: entry.tier,
+                config_path_count: entry.config_paths.len(),
+                plugin_count: entry.plugins.len(),
+                plugin_manager: entry.plugin_manager.as_deref(),
+                last_snapshot: engine.history(name, 1)?.pop().map(|s| s.created_at),
+            });
+        }
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
     if manifest.tools.is_empty() {
-        println!("No tools tracked yet.");
-        println!("  Run {} to get started.", "dotsmith add <tool>".bold());
+        out.info("No tools tracked yet.");
+        out.info(format_args!("  Run {} to get started.", "dotsmith add <tool>".bold()));
         return Ok(());
     }
 
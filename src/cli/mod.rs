@@ -1,14 +1,21 @@
 pub mod add;
+pub mod apply;
+pub mod audit;
 pub mod deploy;
 pub mod deploy_remote;
 pub mod diff;
 pub mod doctor;
 pub mod edit;
+pub mod explore;
+pub mod generate;
 pub mod history;
+pub mod import;
 pub mod init;
 pub mod list;
 pub mod plugins;
 pub mod profile;
+pub mod prune;
+pub mod pull_remote;
 pub mod reload;
 pub mod remove;
 pub mod repo;
@@ -16,11 +23,14 @@ pub mod rollback;
 pub mod search;
 pub mod snapshot;
 pub mod status;
+pub mod validate;
 pub mod watch;
 
 use clap::{Parser, Subcommand};
 use clap_complete::Shell;
 
+use crate::core::module::OptionType;
+
 #[derive(Parser)]
 #[command(
     name = "dotsmith",
@@ -38,17 +48,51 @@ pub struct DotsmithCli {
     /// Suppress non-essential output
     #[arg(short, long, global = true)]
     pub quiet: bool,
+
+    /// Use this dotsmith config directory instead of ~/.config/dotsmith
+    #[arg(long, global = true, value_name = "DIR")]
+    pub config_dir: Option<std::path::PathBuf>,
+
+    /// Print machine-readable JSON (list, status, history, doctor)
+    #[arg(long, global = true)]
+    pub json: bool,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
-    /// Initialize dotsmith configuration directory
-    Init,
+    /// Initialize dotsmith configuration directory (guided setup on a terminal)
+    Init {
+        /// Create the config files without asking anything
+        #[arg(long)]
+        non_interactive: bool,
+    },
 
     /// Add a tool to dotsmith management
+    #[command(group(clap::ArgGroup::new("tools").required(true).args(["tool", "all", "from"])))]
     Add {
         /// Tool name (e.g., tmux, zsh, git)
-        tool: String,
+        tool: Option<String>,
+
+        /// Track this config path instead of auto-detecting (repeatable)
+        #[arg(long = "path", value_name = "PATH", requires = "tool")]
+        paths: Vec<String>,
+
+        /// Force the support tier (1-3)
+        #[arg(
+            long,
+            value_name = "N",
+            requires = "tool",
+            value_parser = clap::value_parser!(u8).range(1..=3)
+        )]
+        tier: Option<u8>,
+
+        /// Add every installed built-in tool whose config files are found
+        #[arg(long)]
+        all: bool,
+
+        /// Add the tools named in this file, one per line
+        #[arg(long, value_name = "FILE")]
+        from: Option<std::path::PathBuf>,
     },
 
     /// Remove a tool from dotsmith management
@@ -57,58 +101,260 @@ pub enum Commands {
         tool: String,
     },
 
+    /// Adopt a stow-style dotfiles repo: each top-level directory becomes a tool
+    Import {
+        /// Path to the dotfiles repo (e.g., ~/dotfiles)
+        path: std::path::PathBuf,
+    },
+
     /// List all managed tools with status
     List,
 
     /// Show recent changes and warnings
-    Status,
+    Status {
+        /// Also report files changed since the last `repo sync`
+        #[arg(long, alias = "repo")]
+        ahead: bool,
+
+        /// Only list tools with changes since their last snapshot
+        #[arg(long)]
+        changed_only: bool,
+    },
 
     /// Run health checks on tracked tools and configuration
     Doctor {
         /// Specific tool to check (checks all if omitted)
         tool: Option<String>,
+
+        /// Same as the positional tool argument
+        #[arg(long = "tool", value_name = "TOOL", conflicts_with = "tool")]
+        tool_flag: Option<String>,
+
+        /// Compare Tier 1 configs against the option catalog: list
+        /// customizations and flag likely misspelled option names
+        #[arg(long)]
+        deep: bool,
+
+        /// Restrict sensitive files (ssh, gnupg, netrc, ...) that are readable
+        /// by others to mode 0600
+        #[arg(long)]
+        fix_perms: bool,
+
+        /// Offer safe repairs: relink broken symlinks whose source can be
+        /// found, take initial snapshots, and drop missing paths
+        #[arg(long)]
+        fix: bool,
+
+        /// Apply every --fix repair without asking
+        #[arg(short, long, requires = "fix")]
+        yes: bool,
+
+        /// Exit with code 2 if any check found an error
+        #[arg(long)]
+        strict: bool,
+
+        /// Like --strict, and exit with code 3 if there were only warnings
+        #[arg(long)]
+        strict_warnings: bool,
     },
 
     /// Search config options across all Tier 1 tool databases
     Search {
         /// Search query (matches option names, descriptions, and tags)
+        #[arg(default_value = "")]
         query: String,
+
+        /// Only search this tool's option database
+        #[arg(long)]
+        tool: Option<String>,
+
+        /// Only show options of this type
+        #[arg(long = "type", value_name = "TYPE", value_parser = OptionType::NAMES.to_vec())]
+        option_type: Option<String>,
+
+        /// Only show options with this exact tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+
+    /// Write one option reference covering several tools (Markdown or text)
+    Generate {
+        /// Comma-separated tools to include (defaults to tracked tools)
+        #[arg(long, value_delimiter = ',', value_name = "TOOLS")]
+        tools: Vec<String>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        out: Option<std::path::PathBuf>,
+
+        /// Output format (inferred from --out's extension, default md)
+        #[arg(long, value_parser = ["md", "txt"])]
+        format: Option<String>,
     },
 
     /// Take a snapshot of config files
+    #[command(args_conflicts_with_subcommands = true)]
     Snapshot {
+        #[command(subcommand)]
+        action: Option<SnapshotAction>,
+
         /// Tool name (snapshots all tools if omitted)
         tool: Option<String>,
 
+        /// Snapshot this one file (or `-` for stdin) instead of a tracked tool
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["tool", "tag", "dry_run"])]
+        file: Option<String>,
+
+        /// Name to file the `--file` snapshot under (default: the file name)
+        #[arg(long = "as", value_name = "NAME", requires = "file")]
+        as_tool: Option<String>,
+
         /// Message to attach to this snapshot
         #[arg(short, long)]
         message: Option<String>,
+
+        /// Label the snapshot of each file, moving the label if another
+        /// snapshot of that file has it (roll back with `rollback --tag`)
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// List the files that would be snapshotted without storing anything
+        #[arg(long, conflicts_with = "tag")]
+        dry_run: bool,
+
+        /// Store files even if they look like they contain secrets
+        #[arg(long)]
+        no_secrets_check: bool,
+    },
+
+    /// Search the content of every stored snapshot
+    SnapshotGrep {
+        /// Text to look for (case-sensitive)
+        query: String,
+
+        /// Only search this tool's snapshots
+        #[arg(short, long)]
+        tool: Option<String>,
     },
 
     /// Show snapshot history for a tool
     History {
         /// Tool name
-        tool: String,
+        #[arg(required_unless_present = "all")]
+        tool: Option<String>,
+
+        /// Show history for all tracked tools
+        #[arg(long, conflicts_with = "tool")]
+        all: bool,
+
+        /// Maximum number of entries to show (default: 20, unlimited with --csv or --json)
+        #[arg(short, long)]
+        limit: Option<usize>,
 
-        /// Maximum number of entries to show
-        #[arg(short, long, default_value = "20")]
-        limit: usize,
+        /// Write history as CSV (id, tool, file_path, hash, message, created_at)
+        #[arg(long)]
+        csv: bool,
+
+        /// Write every snapshot as a numbered patch into DIR, for `git am`
+        #[arg(
+            long,
+            value_name = "DIR",
+            requires = "tool",
+            conflicts_with_all = ["all", "csv", "limit"]
+        )]
+        export: Option<String>,
     },
 
     /// Show diff between current configs and last snapshot
     Diff {
         /// Tool name (diffs all tools if omitted)
         tool: Option<String>,
+
+        /// Diff against the files saved in this profile instead of the last snapshot
+        #[arg(long, conflicts_with = "remote")]
+        profile: Option<String>,
+
+        /// Diff against the live files on this host (hostname, IP, or SSH alias),
+        /// fetched over scp
+        #[arg(long)]
+        remote: Option<String>,
+
+        /// SSH user for --remote (defaults to current user / ssh config)
+        #[arg(short, long, requires = "remote")]
+        user: Option<String>,
+
+        /// Print a per-file +N/-M summary instead of the full diffs
+        #[arg(long)]
+        stat: bool,
     },
 
     /// Rollback a config file to a specific snapshot
     Rollback {
         /// Snapshot ID to rollback to (from history output)
-        snapshot_id: i64,
+        #[arg(
+            required_unless_present_any = ["tag", "at"],
+            conflicts_with_all = ["tag", "at", "tool"]
+        )]
+        snapshot_id: Option<i64>,
+
+        /// Roll back every file to its snapshot with this tag
+        #[arg(long, conflicts_with = "at")]
+        tag: Option<String>,
+
+        /// Roll back all of --tool's files to their state at this UTC time
+        /// (e.g. "2026-02-01 12:00")
+        #[arg(long, requires = "tool")]
+        at: Option<String>,
+
+        /// With --tag, only roll back this tool's files; with --at, the tool
+        /// to roll back
+        #[arg(short, long)]
+        tool: Option<String>,
 
         /// Preview changes without applying them
         #[arg(long)]
         dry_run: bool,
+
+        /// Skip the confirmation prompt for large rollbacks
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Apply a patch to a tool's config, snapshotting first so it can be rolled back
+    Apply {
+        /// Tool whose config the patch changes
+        tool: String,
+
+        /// Unified diff to apply (`-` reads it from stdin)
+        patch: String,
+
+        /// Show the changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Apply without asking for confirmation
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Delete old snapshots and reclaim database space
+    #[command(group(clap::ArgGroup::new("policy").required(true).multiple(true)))]
+    Prune {
+        /// Keep only the newest N snapshots of each file
+        #[arg(long, value_name = "N", group = "policy")]
+        keep: Option<usize>,
+
+        /// Delete snapshots older than this (e.g. 12h, 90d, 8w)
+        #[arg(long, value_name = "AGE", group = "policy")]
+        older_than: Option<String>,
+
+        /// Compress snapshots stored before compression was added
+        #[arg(long, group = "policy")]
+        recompress: bool,
+
+        /// Show what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Deploy config symlinks from source to target
@@ -122,35 +368,99 @@ pub enum Commands {
         /// Preview changes without applying them
         #[arg(long)]
         dry_run: bool,
+
+        /// Link each file individually instead of the whole directory
+        #[arg(long)]
+        files: bool,
+
+        /// Copy instead of symlinking, for filesystems without symlink support
+        #[arg(long)]
+        copy: bool,
+
+        /// Render unset {{env.VAR}} placeholders in .tmpl files as empty instead of failing
+        #[arg(long)]
+        allow_missing_env: bool,
     },
 
     /// Open a tool's config file in your editor (auto-snapshots before editing)
     Edit {
         /// Tool name to edit
         tool: String,
+
+        /// Don't check the file's syntax after the editor exits
+        #[arg(long)]
+        no_validate: bool,
+
+        /// If the edited file is invalid, roll it back without asking
+        #[arg(long, conflicts_with = "no_validate")]
+        yes_rollback: bool,
+
+        /// Open all of the tool's config files, not just the first
+        #[arg(long)]
+        all: bool,
     },
 
     /// Watch tracked configs for changes and auto-snapshot on save
     Watch {
         /// Specific tool to watch (watches all if omitted)
         tool: Option<String>,
+
+        /// Snapshot files changed since their last snapshot, then exit
+        #[arg(long)]
+        once: bool,
+
+        /// Wait this long after a file's last write before snapshotting
+        /// (default: general.watch_debounce_ms, 500)
+        #[arg(long, value_name = "MS")]
+        debounce: Option<u64>,
+
+        /// Reload the tool after each snapshot (default: general.watch_reload)
+        #[arg(long)]
+        reload: bool,
+    },
+
+    /// Compare a Tier 1 tool's config with its option database: unknown and
+    /// deprecated options in use, and documented options never set
+    Audit {
+        /// Tool name
+        tool: String,
+    },
+
+    /// Check config file syntax, showing the offending lines
+    Validate {
+        /// Tool name (validates all tools if omitted)
+        tool: Option<String>,
     },
 
     /// Reload configuration for a running tool
     Reload {
         /// Tool name to reload
-        tool: String,
+        #[arg(required_unless_present = "all")]
+        tool: Option<String>,
+
+        /// Reload every tracked tool that has a reload command or hook
+        #[arg(long, conflicts_with = "tool")]
+        all: bool,
     },
 
     /// Explore config options for a tool (interactive TUI)
     Explore {
-        /// Tool name (e.g., tmux, zsh, git)
-        tool: String,
+        /// Tool name (e.g., tmux, zsh, git); optional with --view
+        #[arg(required_unless_present_any = ["view", "list_views"])]
+        tool: Option<String>,
+
+        /// Open with a saved view's category and search applied
+        #[arg(long, conflicts_with = "list_views")]
+        view: Option<String>,
+
+        /// List saved views and exit
+        #[arg(long)]
+        list_views: bool,
     },
 
-    /// Manage plugins for a tool (zsh, tmux)
+    /// Manage plugins for a tool (zsh, tmux, fish)
     Plugins {
-        /// Tool name (e.g., zsh, tmux)
+        /// Tool name (e.g., zsh, tmux, fish)
         tool: String,
 
         #[command(subcommand)]
@@ -163,6 +473,13 @@ pub enum Commands {
         shell: Shell,
     },
 
+    /// Print a shell hook that snapshots tracked configs on shell exit
+    Hook {
+        /// Shell to print the hook for
+        #[arg(value_parser = crate::core::hook::EXIT_HOOK_SHELLS.to_vec())]
+        shell: String,
+    },
+
     /// Generate man page (hidden, for packaging)
     #[command(hide = true)]
     Mangen,
@@ -191,6 +508,24 @@ pub enum Commands {
         dry_run: bool,
     },
 
+    /// Pull tracked config files from a remote host back into their local paths
+    PullRemote {
+        /// Remote host (hostname, IP, or SSH alias from ~/.ssh/config)
+        host: String,
+
+        /// SSH user (defaults to current user / ssh config)
+        #[arg(short, long)]
+        user: Option<String>,
+
+        /// Pull only specific tool(s) (can be specified multiple times)
+        #[arg(short, long)]
+        tool: Option<Vec<String>>,
+
+        /// Show what would change without touching local files
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Manage dotfile git repo for backups
     Repo {
         #[command(subcommand)]
@@ -207,18 +542,94 @@ pub enum RepoAction {
     },
 
     /// Sync tracked configs into the repo and commit
-    Sync,
+    Sync {
+        /// Only sync and commit these tools (repeatable)
+        #[arg(long = "only", value_name = "TOOL")]
+        only: Vec<String>,
+
+        /// Push the new commit to the remote (default: general.repo_auto_push)
+        #[arg(long)]
+        push: bool,
+
+        /// Copy files into the repo even if they look like they contain secrets
+        #[arg(long)]
+        no_secrets_check: bool,
+    },
+
+    /// Copy tracked configs from the repo back into place
+    Restore {
+        /// Show what would be restored without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Set the remote that `repo sync --push` pushes to
+    Remote {
+        /// Remote URL (e.g., git@github.com:you/dots.git)
+        url: String,
+    },
 
     /// Show repo status
     Status,
 }
 
+#[derive(Subcommand)]
+pub enum SnapshotAction {
+    /// Delete snapshots of tools that are no longer tracked
+    Gc {
+        /// List orphaned snapshots without deleting them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Delete without asking for confirmation
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Protect a snapshot from prune and gc
+    Pin {
+        /// Snapshot ID (from `dotsmith history`)
+        id: i64,
+    },
+
+    /// Let prune and gc remove a pinned snapshot again
+    Unpin {
+        /// Snapshot ID (from `dotsmith history`)
+        id: i64,
+    },
+
+    /// Set or extend the message of an existing snapshot
+    Annotate {
+        /// Snapshot ID (from `dotsmith history`)
+        id: i64,
+
+        /// New message (empty clears it)
+        #[arg(short, long)]
+        message: String,
+
+        /// Add to the current message instead of replacing it
+        #[arg(long)]
+        append: bool,
+    },
+
+    /// Report tracked files whose latest snapshots have identical content
+    Dupes,
+}
+
 #[derive(Subcommand)]
 pub enum ProfileAction {
     /// Save current configs as a named profile
     Save {
         /// Profile name (e.g., workstation, laptop, minimal)
         name: String,
+
+        /// Also store the snapshot history of the profile's tools
+        #[arg(long)]
+        with_history: bool,
+
+        /// Machine this profile is for (default: this machine's hostname)
+        #[arg(long)]
+        hostname: Option<String>,
     },
 
     /// Restore config files from a saved profile
@@ -233,6 +644,50 @@ pub enum ProfileAction {
         /// Preview changes without applying them
         #[arg(long)]
         dry_run: bool,
+
+        /// Merge the profile's stored snapshot history into snapshots.db
+        #[arg(long)]
+        with_history: bool,
+    },
+
+    /// Load the profile saved for this machine's hostname
+    Auto {
+        /// Add tools from the profile that aren't currently tracked
+        #[arg(long)]
+        add_untracked: bool,
+
+        /// Preview changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Merge the profile's stored snapshot history into snapshots.db
+        #[arg(long)]
+        with_history: bool,
+    },
+
+    /// Show how current config files differ from a saved profile
+    Diff {
+        /// Profile name to compare against
+        name: String,
+    },
+
+    /// Pack a saved profile into a .tar.gz archive
+    Export {
+        /// Profile name to export
+        name: String,
+
+        /// Archive to write (e.g., workstation.tar.gz)
+        path: std::path::PathBuf,
+    },
+
+    /// Unpack a profile archive written by `profile export`
+    Import {
+        /// Archive to import
+        path: std::path::PathBuf,
+
+        /// Replace an existing profile with the same name
+        #[arg(long)]
+        force: bool,
     },
 
     /// List saved profiles
@@ -260,17 +715,61 @@ pub enum PluginAction {
     },
 
     /// List installed plugins
-    List,
+    List {
+        /// Print tab-separated `name repo init url` lines with no header or color
+        #[arg(long)]
+        parseable: bool,
+    },
 
     /// Update one or all plugins
     Update {
         /// Plugin name (updates all if omitted)
         name: Option<String>,
+
+        /// Number of plugins to update in parallel (default: CPU count)
+        #[arg(short, long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: Option<u16>,
     },
 
+    /// Fetch and list plugins with upstream commits, without updating them
+    Outdated,
+
     /// Show plugin info from README (description, configuration, URL)
     Info {
         /// Plugin name (shows all if omitted)
         name: Option<String>,
     },
+
+    /// Freeze a plugin at its current commit (skipped by update)
+    Pin {
+        /// Plugin name to pin
+        name: String,
+    },
+
+    /// Clear a plugin's pin so updates resume
+    Unpin {
+        /// Plugin name to unpin
+        name: String,
+    },
+
+    /// Change where a plugin is sourced in the generated loader
+    #[command(group(clap::ArgGroup::new("position").required(true)))]
+    Move {
+        /// Plugin name to move
+        name: String,
+
+        /// Load it directly before this plugin
+        #[arg(long, value_name = "PLUGIN", group = "position")]
+        before: Option<String>,
+
+        /// Load it directly after this plugin
+        #[arg(long, value_name = "PLUGIN", group = "position")]
+        after: Option<String>,
+    },
+
+    /// Record the exact commit of every installed plugin (all tools) in plugins.lock
+    Lock,
+
+    /// Install this tool's plugins at the commits recorded in plugins.lock
+    Sync,
 }
@@ -3,43 +3,61 @@ use colored::Colorize;
 
 use crate::cli::PluginAction;
 use crate::core::manifest::Manifest;
+use crate::core::output::Printer;
 use crate::core::plugin;
 use crate::core::plugin_info;
 use crate::util;
 
-pub fn run(verbose: bool, tool: &str, action: &PluginAction) -> Result<()> {
+pub fn run(out: &Printer, tool: &str, action: &PluginAction) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
 
     match action {
-        PluginAction::Add { repo } => run_add(verbose, &config_dir, tool, repo),
-        PluginAction::Remove { name } => run_remove(&config_dir, tool, name),
-        PluginAction::List => run_list(&config_dir, tool),
-        PluginAction::Update { name } => run_update(verbose, &config_dir, tool, name.as_deref()),
+        PluginAction::Add { repo } => run_add(out, &config_dir, tool, repo),
+        PluginAction::Remove { name } => run_remove(out, &config_dir, tool, name),
+        PluginAction::List { parseable } => run_list(&config_dir, tool, *parseable),
+        PluginAction::Update { name, jobs } => {
+            run_update(out, &config_dir, tool, name.as_deref(), jobs.map(usize::from))
+        }
+        PluginAction::Outdated => run_outdated(out, &config_dir, tool),
         PluginAction::Info { name } => run_info(&config_dir, tool, name.as_deref()),
+        PluginAction::Pin { name } => run_pin(out, &config_dir, tool, name),
+        PluginAction::Unpin { name } => run_unpin(out, &config_dir, tool, name),
+        PluginAction::Move {
+            name,
+            before,
+            after,
+        } => run_move(out, &config_dir, tool, name, before.as_deref(), after.as_deref()),
+        PluginAction::Lock => run_lock(out, &config_dir),
+        PluginAction::Sync => run_sync(out, &config_dir, tool),
     }
 }
 
 fn run_add(
-    verbose: bool,
+    out: &Printer,
     config_dir: &std::path::Path,
     tool: &str,
     repo: &str,
 ) -> Result<()> {
     let mut manifest = Manifest::load(config_dir)?;
 
-    if verbose {
-        println!("Cloning {} for {}...", repo.bold(), tool.bold());
-    }
+    out.detail(format_args!("Cloning {} for {}...", repo.bold(), tool.bold()));
 
     let (name, init_file) = plugin::add_plugin(config_dir, &mut manifest, tool, repo)?;
 
-    println!(
+    out.info(format_args!(
         "{} Added plugin {} for {}",
         "OK".green().bold(),
         name.bold(),
         tool.bold()
-    );
-    println!("  Init file: {}", init_file);
+    ));
+    out.info(format_args!("  Init file: {}", init_file));
+    if let Some(pinned) = manifest
+        .get_tool(tool)
+        .and_then(|t| t.plugins.get(&name))
+        .and_then(|p| p.pinned_ref.as_deref())
+    {
+        out.info(format_args!("  Pinned at: {} (skipped by update)", pinned.yellow()));
+    }
 
     let loader = plugin::loader_path(config_dir, tool);
     let loader_contracted = util::paths::contract_tilde(&loader);
@@ -52,38 +70,58 @@ fn run_add(
     let rc_file = match tool {
         "tmux" => "tmux.conf",
         "zsh" => ".zshrc",
+        "fish" => "config.fish",
         _ => "config",
     };
 
-    println!(
+    out.info(format_args!(
         "\n  {} Add this line to your {} (if not already present):",
         "Hint:".yellow().bold(),
         rc_file
-    );
-    println!("    {}", source_line.dimmed());
+    ));
+    out.info(format_args!("    {}", source_line.dimmed()));
 
     Ok(())
 }
 
-fn run_remove(config_dir: &std::path::Path, tool: &str, name: &str) -> Result<()> {
+fn run_remove(out: &Printer, config_dir: &std::path::Path, tool: &str, name: &str) -> Result<()> {
     let mut manifest = Manifest::load(config_dir)?;
 
     plugin::remove_plugin(config_dir, &mut manifest, tool, name)?;
 
-    println!(
+    out.info(format_args!(
         "{} Removed plugin {} from {}",
         "OK".green().bold(),
         name.bold(),
         tool.bold()
-    );
+    ));
 
     Ok(())
 }
 
-fn run_list(config_dir: &std::path::Path, tool: &str) -> Result<()> {
+fn run_list(config_dir: &std::path::Path, tool: &str, parseable: bool) -> Result<()> {
     let manifest = Manifest::load(config_dir)?;
 
+    if let Some(manager) = plugin::external_manager(tool) {
+        return run_list_external(&manifest, tool, manager, parseable);
+    }
+
     let plugins = plugin::list_plugins(&manifest, tool)?;
+    if parseable {
+        for (name, repo, init) in &plugins {
+            let dir = plugin::plugin_dir(config_dir, tool, name);
+            let info = plugin_info::scan_plugin(&dir, name, repo);
+            println!("{}", parseable_line(&[name, repo, init, &info.url]));
+        }
+        return Ok(());
+    }
+
+    let pinned = |name: &str| {
+        manifest
+            .get_tool(tool)
+            .and_then(|t| t.plugins.get(name))
+            .is_some_and(|p| p.pinned_ref.is_some())
+    };
 
     if plugins.is_empty() {
         println!("No plugins installed for {}.", tool.bold());
@@ -109,54 +147,328 @@ fn run_list(config_dir: &std::path::Path, tool: &str) -> Result<()> {
     println!("  {}", "-".repeat(80));
 
     for (name, repo, init) in &plugins {
-        println!("  {:<30} {:<40} {}", name, repo.dimmed(), init.dimmed());
+        let marker = if pinned(name) {
+            format!(" {}", "(pinned)".yellow())
+        } else {
+            String::new()
+        };
+        println!(
+            "  {:<30} {:<40} {}{}",
+            name,
+            repo.dimmed(),
+            init.dimmed(),
+            marker
+        );
+    }
+
+    Ok(())
+}
+
+/// List plugins another manager owns, from its lockfile. Read-only.
+fn run_list_external(
+    manifest: &Manifest,
+    tool: &str,
+    manager: &str,
+    parseable: bool,
+) -> Result<()> {
+    let plugins = plugin::list_lazy_plugins(manifest, tool)?;
+    if parseable {
+        for p in &plugins {
+            let branch = p.branch.as_deref().unwrap_or_default();
+            println!("{}", parseable_line(&[&p.name, branch, &p.commit]));
+        }
+        return Ok(());
+    }
+
+    if plugins.is_empty() {
+        println!("No plugins locked by {} for {}.", manager, tool.bold());
+        return Ok(());
+    }
+
+    println!(
+        "{} plugin(s) locked by {} for {} (read-only):\n",
+        plugins.len(),
+        manager,
+        tool.bold()
+    );
+    println!(
+        "  {:<30} {:<20} {}",
+        "Name".bold(),
+        "Branch".bold(),
+        "Commit".bold()
+    );
+    println!("  {}", "-".repeat(80));
+
+    for p in &plugins {
+        println!(
+            "  {:<30} {:<20} {}",
+            p.name,
+            p.branch.as_deref().unwrap_or("-").dimmed(),
+            p.commit.dimmed()
+        );
     }
 
     Ok(())
 }
 
+/// Join fields with tabs, escaping backslashes, tabs, and line breaks so each
+/// record stays on one line with a fixed column count.
+fn parseable_line(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            let mut escaped = String::with_capacity(field.len());
+            for c in field.chars() {
+                match c {
+                    '\\' => escaped.push_str("\\\\"),
+                    '\t' => escaped.push_str("\\t"),
+                    '\n' => escaped.push_str("\\n"),
+                    '\r' => escaped.push_str("\\r"),
+                    _ => escaped.push(c),
+                }
+            }
+            escaped
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
 fn run_update(
-    verbose: bool,
+    out: &Printer,
     config_dir: &std::path::Path,
     tool: &str,
     name: Option<&str>,
+    jobs: Option<usize>,
 ) -> Result<()> {
     let manifest = Manifest::load(config_dir)?;
 
-    if verbose {
-        match name {
-            Some(n) => println!("Updating {} for {}...", n.bold(), tool.bold()),
-            None => println!("Updating all plugins for {}...", tool.bold()),
-        }
+    match name {
+        Some(n) => out.detail(format_args!("Updating {} for {}...", n.bold(), tool.bold())),
+        None => out.detail(format_args!("Updating all plugins for {}...", tool.bold())),
     }
 
-    let results = plugin::update_plugins(config_dir, &manifest, tool, name)?;
+    let results = plugin::update_plugins(config_dir, &manifest, tool, name, jobs)?;
 
     let updated_count = results.iter().filter(|r| r.updated).count();
-    let up_to_date = results.len() - updated_count;
+    let pinned_count = results.iter().filter(|r| r.pinned).count();
+    let up_to_date = results.len() - updated_count - pinned_count;
 
     for result in &results {
         if result.updated {
             let old_short = &result.old_commit[..7.min(result.old_commit.len())];
             let new_short = &result.new_commit[..7.min(result.new_commit.len())];
-            println!(
+            out.info(format_args!(
                 "  {} {} ({}..{})",
                 "updated".green(),
                 result.name.bold(),
                 old_short,
                 new_short,
-            );
-        } else if verbose {
-            println!("  {} {}", "up to date".dimmed(), result.name);
+            ));
+        } else if result.pinned {
+            let pin_short = &result.old_commit[..7.min(result.old_commit.len())];
+            out.info(format_args!(
+                "  {} {} ({})",
+                "pinned".yellow(),
+                result.name.bold(),
+                pin_short
+            ));
+        } else {
+            out.detail(format_args!("  {} {}", "up to date".dimmed(), result.name));
         }
     }
 
-    println!(
+    let mut summary = format!(
         "\n{} Updated {} plugin(s), {} already up to date",
         "OK".green().bold(),
         updated_count,
         up_to_date
     );
+    if pinned_count > 0 {
+        summary.push_str(&format!(", {} pinned", pinned_count));
+    }
+    out.info(summary);
+
+    Ok(())
+}
+
+fn run_outdated(out: &Printer, config_dir: &std::path::Path, tool: &str) -> Result<()> {
+    let manifest = Manifest::load(config_dir)?;
+
+    out.detail(format_args!("Checking plugins for {}...", tool.bold()));
+
+    let results = plugin::check_updates(config_dir, &manifest, tool)?;
+    let outdated: Vec<_> = results
+        .iter()
+        .filter(|(_, behind)| behind.as_ref().is_ok_and(|&b| b > 0))
+        .collect();
+    let failed = results.iter().filter(|(_, behind)| behind.is_err()).count();
+
+    for (name, behind) in &results {
+        match behind {
+            Ok(behind) if *behind > 0 => println!(
+                "  {} {} ({} commit(s) behind)",
+                "outdated".yellow(),
+                name.bold(),
+                behind
+            ),
+            Ok(_) => out.detail(format_args!("  {} {}", "up to date".dimmed(), name)),
+            Err(e) => eprintln!("  {} {}: {}", "!!".yellow(), name.bold(), e),
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("couldn't check {} of {} plugin(s) for updates", failed, results.len());
+    }
+    if outdated.is_empty() {
+        println!("{} All {} plugin(s) up to date", "OK".green().bold(), results.len());
+    } else {
+        println!(
+            "\n{} {} of {} plugin(s) have updates — run {}",
+            "!!".yellow(),
+            outdated.len(),
+            results.len(),
+            format!("dotsmith plugins {} update", tool).bold()
+        );
+    }
+
+    Ok(())
+}
+
+fn run_pin(out: &Printer, config_dir: &std::path::Path, tool: &str, name: &str) -> Result<()> {
+    let mut manifest = Manifest::load(config_dir)?;
+
+    let commit = plugin::pin_plugin(config_dir, &mut manifest, tool, name)?;
+
+    out.info(format_args!(
+        "{} Pinned {} at {}",
+        "OK".green().bold(),
+        name.bold(),
+        &commit[..7.min(commit.len())]
+    ));
+    out.info(format_args!(
+        "  Run {} to resume updates.",
+        format!("dotsmith plugins {} unpin {}", tool, name).bold()
+    ));
+
+    Ok(())
+}
+
+fn run_move(
+    out: &Printer,
+    config_dir: &std::path::Path,
+    tool: &str,
+    name: &str,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> Result<()> {
+    let mut manifest = Manifest::load(config_dir)?;
+
+    let (other, is_before) = match (before, after) {
+        (Some(other), _) => (other, true),
+        (None, Some(other)) => (other, false),
+        (None, None) => anyhow::bail!("specify --before or --after"),
+    };
+    plugin::move_plugin(config_dir, &mut manifest, tool, name, other, is_before)?;
+
+    out.info(format_args!(
+        "{} {} now loads {} {}",
+        "OK".green().bold(),
+        name.bold(),
+        if is_before { "before" } else { "after" },
+        other.bold()
+    ));
+
+    let names: Vec<String> = plugin::list_plugins(&manifest, tool)?
+        .into_iter()
+        .map(|(name, _, _)| name)
+        .collect();
+    out.info(format_args!("  Load order: {}", names.join(", ")));
+
+    Ok(())
+}
+
+fn run_unpin(out: &Printer, config_dir: &std::path::Path, tool: &str, name: &str) -> Result<()> {
+    let mut manifest = Manifest::load(config_dir)?;
+
+    match plugin::unpin_plugin(config_dir, &mut manifest, tool, name)? {
+        Some(_) => out.info(format_args!(
+            "{} Unpinned {}, updates resume",
+            "OK".green().bold(),
+            name.bold()
+        )),
+        None => out.info(format_args!("{} was not pinned", name.bold())),
+    }
+
+    Ok(())
+}
+
+fn run_lock(out: &Printer, config_dir: &std::path::Path) -> Result<()> {
+    let manifest = Manifest::load(config_dir)?;
+
+    let locked = plugin::write_lockfile(config_dir, &manifest)?;
+
+    for entry in &locked {
+        out.detail(format_args!(
+            "  {}/{} {}",
+            entry.tool,
+            entry.name.bold(),
+            &entry.commit[..7.min(entry.commit.len())]
+        ));
+    }
+    out.info(format_args!(
+        "{} Locked {} plugin(s) in {}",
+        "OK".green().bold(),
+        locked.len(),
+        util::paths::contract_tilde(&plugin::lockfile_path(config_dir))
+    ));
+
+    Ok(())
+}
+
+fn run_sync(out: &Printer, config_dir: &std::path::Path, tool: &str) -> Result<()> {
+    let mut manifest = Manifest::load(config_dir)?;
+
+    let results = plugin::install_from_lockfile(config_dir, &mut manifest, tool)?;
+
+    if results.is_empty() {
+        out.info(format_args!("No plugins locked for {}.", tool.bold()));
+        return Ok(());
+    }
+
+    let mut changed = 0;
+    for result in &results {
+        let short = &result.commit[..7.min(result.commit.len())];
+        match result.status {
+            plugin::LockSyncStatus::Installed => {
+                changed += 1;
+                out.info(format_args!(
+                    "  {} {} ({})",
+                    "installed".green(),
+                    result.name.bold(),
+                    short
+                ));
+            }
+            plugin::LockSyncStatus::CheckedOut => {
+                changed += 1;
+                out.info(format_args!(
+                    "  {} {} ({})",
+                    "checked out".green(),
+                    result.name.bold(),
+                    short
+                ));
+            }
+            plugin::LockSyncStatus::UpToDate => {
+                out.detail(format_args!("  {} {}", "up to date".dimmed(), result.name));
+            }
+        }
+    }
+
+    out.info(format_args!(
+        "\n{} Synced {} plugin(s), {} already at the locked commit",
+        "OK".green().bold(),
+        changed,
+        results.len() - changed
+    ));
 
     Ok(())
 }
@@ -175,12 +487,13 @@ fn run_info(config_dir: &std::path::Path, tool: &str, name: Option<&str>) -> Res
 
     let plugins_to_show: Vec<(&String, &crate::core::manifest::PluginEntry)> = match name {
         Some(n) => {
-            if let Some((key, entry)) = tool_entry.plugins.get_key_value(n) {
-                vec![(key, entry)]
-            } else {
-                println!("Plugin '{}' not found for {}.", n.bold(), tool.bold());
-                return Ok(());
-            }
+            let (key, entry) = tool_entry.plugins.get_key_value(n).ok_or_else(|| {
+                crate::core::errors::DotsmithError::PluginNotInstalled(
+                    n.to_string(),
+                    tool.to_string(),
+                )
+            })?;
+            vec![(key, entry)]
         }
         None => tool_entry.plugins.iter().collect(),
     };
@@ -208,9 +521,34 @@ fn run_info(config_dir: &std::path::Path, tool: &str, name: Option<&str>) -> Res
             }
         }
 
+        if !info.readme_found {
+            println!();
+            println!("  {}", "No README found in plugin directory.".dimmed());
+        } else if info.description.is_none() && info.config_excerpt.is_none() {
+            println!();
+            println!(
+                "  {}",
+                "README has no description or configuration section.".dimmed()
+            );
+        }
+
         println!();
         println!("  {}", "─".repeat(60).dimmed());
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parseable_line_escapes_separators() {
+        assert_eq!(parseable_line(&["a", "b/c", "d.zsh"]), "a\tb/c\td.zsh");
+        assert_eq!(
+            parseable_line(&["tab\there", "line\nbreak", "back\\slash"]),
+            "tab\\there\tline\\nbreak\tback\\\\slash"
+        );
+    }
+}
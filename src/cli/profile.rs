@@ -3,124 +3,244 @@ use colored::Colorize;
 
 use crate::cli::ProfileAction;
 use crate::core::manifest::Manifest;
+use crate::core::output::Printer;
 use crate::core::profile;
+use crate::core::snapshot::SnapshotEngine;
 use crate::util;
 
-pub fn run(verbose: bool, action: &ProfileAction) -> Result<()> {
+pub fn run(out: &Printer, action: &ProfileAction) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
 
     match action {
-        ProfileAction::Save { name } => run_save(verbose, &config_dir, name),
+        ProfileAction::Save {
+            name,
+            with_history,
+            hostname,
+        } => run_save(out, &config_dir, name, *with_history, hostname.as_deref()),
         ProfileAction::Load {
             name,
             add_untracked,
             dry_run,
-        } => run_load(verbose, &config_dir, name, *add_untracked, *dry_run),
+            with_history,
+        } => run_load(
+            out,
+            &config_dir,
+            name,
+            *add_untracked,
+            *dry_run,
+            *with_history,
+        ),
+        ProfileAction::Auto {
+            add_untracked,
+            dry_run,
+            with_history,
+        } => run_auto(out, &config_dir, *add_untracked, *dry_run, *with_history),
+        ProfileAction::Diff { name } => run_diff(&config_dir, name),
+        ProfileAction::Export { name, path } => run_export(out, &config_dir, name, path),
+        ProfileAction::Import { path, force } => run_import(out, &config_dir, path, *force),
         ProfileAction::List => run_list(&config_dir),
-        ProfileAction::Delete { name } => run_delete(&config_dir, name),
+        ProfileAction::Delete { name } => run_delete(out, &config_dir, name),
     }
 }
 
-fn run_save(verbose: bool, config_dir: &std::path::Path, name: &str) -> Result<()> {
+fn run_save(
+    out: &Printer,
+    config_dir: &std::path::Path,
+    name: &str,
+    with_history: bool,
+    hostname: Option<&str>,
+) -> Result<()> {
     let manifest = Manifest::load(config_dir)?;
 
     if manifest.tools.is_empty() {
-        println!("No tools tracked. Add tools with {} first.", "dotsmith add <tool>".bold());
+        out.info(format_args!(
+            "No tools tracked. Add tools with {} first.",
+            "dotsmith add <tool>".bold()
+        ));
         return Ok(());
     }
 
-    let (tool_count, file_count) = profile::save_profile(config_dir, &manifest, name)?;
+    let (tool_count, file_count) = profile::save_profile(config_dir, &manifest, name, hostname)?;
 
-    println!(
+    out.info(format_args!(
         "{} Saved profile '{}' ({} tool(s), {} file(s))",
         "OK".green().bold(),
         name.bold(),
         tool_count,
         file_count,
-    );
+    ));
 
-    if verbose {
-        for tool_name in manifest.tools.keys() {
-            println!("  {}", tool_name);
-        }
+    if with_history {
+        let snapshots = profile::save_profile_history(config_dir, name)?;
+        out.info(format_args!("  {} snapshot(s) of history included", snapshots));
+    }
+
+    for tool_name in manifest.tools.keys() {
+        out.detail(format_args!("  {}", tool_name));
     }
 
     Ok(())
 }
 
 fn run_load(
-    verbose: bool,
+    out: &Printer,
     config_dir: &std::path::Path,
     name: &str,
     add_untracked: bool,
     dry_run: bool,
+    with_history: bool,
 ) -> Result<()> {
     if dry_run {
-        return run_load_dry_run(verbose, config_dir, name, add_untracked);
+        return run_load_dry_run(config_dir, name, add_untracked, with_history);
+    }
+
+    // Fail before touching any files if the history can't be merged
+    if with_history && !profile::has_history(config_dir, name) {
+        anyhow::bail!(
+            "profile '{}' has no snapshot history (save it with --with-history)",
+            name
+        );
     }
 
     let mut manifest = Manifest::load(config_dir)?;
     let result = profile::load_profile(config_dir, &mut manifest, name, add_untracked)?;
+    log_profile_load(out, config_dir, name, &result.skipped_tools);
 
-    println!(
+    out.info(format_args!(
         "{} Loaded profile '{}' ({} file(s) restored)",
         "OK".green().bold(),
         name.bold(),
         result.restored_files,
-    );
+    ));
+
+    if with_history {
+        let meta = profile::read_profile_meta(config_dir, name)?;
+        let tools: Vec<&str> = meta
+            .tools
+            .keys()
+            .filter(|t| !result.skipped_tools.contains(t))
+            .map(String::as_str)
+            .collect();
+        let merged = profile::load_profile_history(config_dir, name, &tools)?;
+        out.info(format_args!("  {} snapshot(s) merged into history", merged));
+    }
 
     if result.backed_up_files > 0 {
-        println!(
+        out.info(format_args!(
             "  {} file(s) backed up before overwrite",
             result.backed_up_files,
-        );
+        ));
     }
 
     if !result.tools_added.is_empty() {
-        println!(
+        out.info(format_args!(
             "  {} tool(s) added: {}",
             result.tools_added.len(),
             result.tools_added.join(", "),
-        );
+        ));
     }
 
     if !result.skipped_tools.is_empty() {
-        println!(
+        out.info(format_args!(
             "  {} tool(s) skipped (not tracked, use {}): {}",
             result.skipped_tools.len(),
             "--add-untracked".bold(),
             result.skipped_tools.join(", "),
-        );
+        ));
     }
 
-    if verbose && result.restored_files > 0 {
-        println!(
-            "  Run {} to review changes.",
-            "dotsmith diff".cyan(),
-        );
+    if result.restored_files > 0 {
+        out.detail(format_args!("  Run {} to review changes.", "dotsmith diff".cyan()));
     }
 
     Ok(())
 }
 
+/// Record the config paths a profile load restored so doctor doesn't flag
+/// them as changed outside dotsmith.
+fn log_profile_load(
+    out: &Printer,
+    config_dir: &std::path::Path,
+    name: &str,
+    skipped_tools: &[String],
+) {
+    let logged = profile::read_profile_meta(config_dir, name).and_then(|meta| {
+        let restored: Vec<String> = meta
+            .tools
+            .iter()
+            .filter(|(tool, _)| !skipped_tools.contains(tool))
+            .flat_map(|(_, entry)| entry.config_paths.iter().cloned())
+            .collect();
+        SnapshotEngine::open(config_dir)?.record_operation("profile load", &restored)
+    });
+    if let Err(e) = logged
+        && out.is_verbose()
+    {
+        eprintln!("  warning: failed to log profile load: {}", e);
+    }
+}
+
+fn run_auto(
+    out: &Printer,
+    config_dir: &std::path::Path,
+    add_untracked: bool,
+    dry_run: bool,
+    with_history: bool,
+) -> Result<()> {
+    let Some(hostname) = profile::current_hostname() else {
+        anyhow::bail!("could not determine this machine's hostname");
+    };
+    let profiles = profile::list_profiles(config_dir)?;
+
+    let Some(matched) = profile::match_hostname(&profiles, &hostname) else {
+        println!("No profile is saved for host '{}'.", hostname.bold());
+        if profiles.is_empty() {
+            println!(
+                "  Run {} to create one.",
+                "dotsmith profile save <name>".bold(),
+            );
+        } else {
+            println!("  Candidates:");
+            for p in &profiles {
+                println!(
+                    "    {:<20} {}",
+                    p.name,
+                    p.hostname.as_deref().unwrap_or("(no host)").dimmed(),
+                );
+            }
+            println!(
+                "  Re-save one with {} to pick it here.",
+                format!("dotsmith profile save <name> --hostname {}", hostname).bold(),
+            );
+        }
+        anyhow::bail!("no profile matches host '{}'", hostname);
+    };
+
+    out.info(format_args!(
+        "Host '{}' matches profile '{}'",
+        hostname.bold(),
+        matched.name.bold(),
+    ));
+    run_load(
+        out,
+        config_dir,
+        &matched.name,
+        add_untracked,
+        dry_run,
+        with_history,
+    )
+}
+
 fn run_load_dry_run(
-    _verbose: bool,
     config_dir: &std::path::Path,
     name: &str,
     add_untracked: bool,
+    with_history: bool,
 ) -> Result<()> {
     let manifest = Manifest::load(config_dir)?;
 
     // Load profile metadata without applying
-    let profile_dir = profile::profiles_dir(config_dir).join(name);
-    if !profile_dir.exists() {
-        return Err(
-            crate::core::errors::DotsmithError::ProfileNotFound(name.to_string()).into(),
-        );
-    }
-
-    let meta_content = std::fs::read_to_string(profile_dir.join("profile.toml"))?;
-    let meta: profile::ProfileMeta = toml::from_str(&meta_content)?;
+    let meta = profile::read_profile_meta(config_dir, name)?;
 
     println!("Profile '{}' contains:\n", name.bold());
 
@@ -140,6 +260,27 @@ fn run_load_dry_run(
         }
     }
 
+    let diffs = profile::diff_profile(config_dir, &manifest, name)?;
+    println!();
+    if diffs.is_empty() {
+        println!("  Current files already match the profile");
+    } else {
+        println!("  {} file(s) differ from the profile:\n", diffs.len());
+        crate::cli::diff::print_diffs(&diffs);
+    }
+
+    if with_history {
+        println!();
+        if profile::has_history(config_dir, name) {
+            println!("  Snapshot history would be merged for the tools above");
+        } else {
+            println!(
+                "  {} profile has no snapshot history to merge",
+                "!!".yellow(),
+            );
+        }
+    }
+
     println!();
     println!(
         "{} No changes made (dry run)",
@@ -149,6 +290,69 @@ fn run_load_dry_run(
     Ok(())
 }
 
+fn run_diff(config_dir: &std::path::Path, name: &str) -> Result<()> {
+    let manifest = Manifest::load(config_dir)?;
+    let diffs = profile::diff_profile(config_dir, &manifest, name)?;
+
+    if diffs.is_empty() {
+        println!(
+            "No differences between profile '{}' and current files",
+            name.bold()
+        );
+        return Ok(());
+    }
+
+    crate::cli::diff::print_diffs(&diffs);
+    println!(
+        "{} file(s) differ from profile '{}'",
+        diffs.len(),
+        name.bold()
+    );
+
+    Ok(())
+}
+
+fn run_export(
+    out: &Printer,
+    config_dir: &std::path::Path,
+    name: &str,
+    path: &std::path::Path,
+) -> Result<()> {
+    profile::export_profile(config_dir, name, path)?;
+
+    out.info(format_args!(
+        "{} Exported profile '{}' to {}",
+        "OK".green().bold(),
+        name.bold(),
+        path.display(),
+    ));
+
+    Ok(())
+}
+
+fn run_import(
+    out: &Printer,
+    config_dir: &std::path::Path,
+    path: &std::path::Path,
+    force: bool,
+) -> Result<()> {
+    let meta = profile::import_profile(config_dir, path, force)?;
+
+    out.info(format_args!(
+        "{} Imported profile '{}' ({} tool(s), {} file(s))",
+        "OK".green().bold(),
+        meta.name.bold(),
+        meta.tools.len(),
+        meta.checksums.len(),
+    ));
+    out.info(format_args!(
+        "  Run {} to preview it.",
+        format!("dotsmith profile load {} --dry-run", meta.name).bold(),
+    ));
+
+    Ok(())
+}
+
 fn run_list(config_dir: &std::path::Path) -> Result<()> {
     let profiles = profile::list_profiles(config_dir)?;
 
@@ -160,30 +364,31 @@ fn run_list(config_dir: &std::path::Path) -> Result<()> {
         return Ok(());
     }
 
-    println!("{:<20} {:<24} {:>6} {:>6}", "Name", "Created", "Tools", "Files");
-    println!("{}", "-".repeat(60));
+    println!("{:<20} {:<24} {:>6} {:>6}  Host", "Name", "Created", "Tools", "Files");
+    println!("{}", "-".repeat(80));
 
     for p in &profiles {
         println!(
-            "{:<20} {:<24} {:>6} {:>6}",
+            "{:<20} {:<24} {:>6} {:>6}  {}",
             p.name.bold(),
             p.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
             p.tool_count,
             p.file_count,
+            p.hostname.as_deref().unwrap_or("-"),
         );
     }
 
     Ok(())
 }
 
-fn run_delete(config_dir: &std::path::Path, name: &str) -> Result<()> {
+fn run_delete(out: &Printer, config_dir: &std::path::Path, name: &str) -> Result<()> {
     profile::delete_profile(config_dir, name)?;
 
-    println!(
+    out.info(format_args!(
         "{} Deleted profile '{}'",
         "OK".green().bold(),
         name.bold(),
-    );
+    ));
 
     Ok(())
 }
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::core::output::Printer;
+use crate::core::snapshot::SnapshotEngine;
+use crate::util;
+
+/// Remove old snapshots by count and/or age, compress ones stored
+/// uncompressed, then vacuum the database.
+pub fn run(
+    out: &Printer,
+    keep: Option<usize>,
+    older_than: Option<&str>,
+    recompress: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let before = match older_than {
+        Some(age) => Some(chrono::Utc::now() - parse_age(age)?),
+        None => None,
+    };
+
+    let config_dir = util::paths::config_dir()?;
+    let db_path = config_dir.join("snapshots.db");
+    let size_before = std::fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0);
+
+    let engine = SnapshotEngine::open(&config_dir)?;
+    let mut changed = false;
+    if keep.is_some() || before.is_some() {
+        changed |= remove_old(out, &engine, keep, before, dry_run)?;
+    }
+    if recompress {
+        changed |= recompress_content(out, &engine, dry_run)?;
+    }
+
+    if out.is_verbose() && changed && !dry_run {
+        drop(engine);
+        let size_after = std::fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0);
+        out.detail(format_args!(
+            "  {}: {} -> {}",
+            db_path.display(),
+            format_bytes(size_before),
+            format_bytes(size_after)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns whether anything was (or would be) removed.
+fn remove_old(
+    out: &Printer,
+    engine: &SnapshotEngine,
+    keep: Option<usize>,
+    before: Option<chrono::DateTime<chrono::Utc>>,
+    dry_run: bool,
+) -> Result<bool> {
+    let result = engine.prune(keep, before, dry_run)?;
+
+    if result.rows == 0 {
+        out.info("Nothing to prune");
+    } else if dry_run {
+        println!(
+            "{} Would remove {} snapshot(s) ({})",
+            "[dry-run]".yellow().bold(),
+            result.rows,
+            format_bytes(result.bytes)
+        );
+    } else {
+        out.info(format_args!(
+            "{} Removed {} snapshot(s) ({})",
+            "OK".green().bold(),
+            result.rows,
+            format_bytes(result.bytes)
+        ));
+    }
+    Ok(result.rows > 0)
+}
+
+/// Compress old uncompressed snapshots and report what compression saves
+/// across the database. Returns whether anything was (or would be)
+/// rewritten.
+fn recompress_content(out: &Printer, engine: &SnapshotEngine, dry_run: bool) -> Result<bool> {
+    let result = engine.recompress(dry_run)?;
+
+    if result.rows == 0 {
+        out.info("Nothing to recompress");
+    } else if dry_run {
+        println!(
+            "{} Would compress {} snapshot(s), saving {}",
+            "[dry-run]".yellow().bold(),
+            result.rows,
+            format_bytes(result.bytes)
+        );
+    } else {
+        out.info(format_args!(
+            "{} Compressed {} snapshot(s), saving {}",
+            "OK".green().bold(),
+            result.rows,
+            format_bytes(result.bytes)
+        ));
+    }
+
+    let stats = engine.storage_stats()?;
+    out.info(format_args!(
+        "  {} of {} snapshot(s) compressed, {} distinct: {} stored, {} saved",
+        stats.compressed_rows,
+        stats.rows,
+        stats.blobs,
+        format_bytes(stats.stored_bytes),
+        format_bytes(stats.saved_bytes())
+    ));
+    Ok(result.rows > 0)
+}
+
+/// Parse an age like `12h`, `90d` or `8w`.
+fn parse_age(age: &str) -> Result<chrono::Duration> {
+    let invalid = || anyhow::anyhow!("invalid age '{}' (expected e.g. 12h, 90d, 8w)", age);
+
+    let split = age.len().saturating_sub(1);
+    let (num, unit) = age.split_at(split);
+    let n: i64 = num.parse().map_err(|_| invalid())?;
+
+    let duration = match unit {
+        "h" => chrono::Duration::try_hours(n),
+        "d" => chrono::Duration::try_days(n),
+        "w" => chrono::Duration::try_weeks(n),
+        _ => None,
+    };
+    duration.filter(|_| n >= 0).ok_or_else(invalid)
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const KIB: u64 = 1024;
+    const MIB: u64 = KIB * 1024;
+    if bytes >= MIB {
+        format!("{:.1} MiB", bytes as f64 / MIB as f64)
+    } else if bytes >= KIB {
+        format!("{:.1} KiB", bytes as f64 / KIB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("12h").unwrap(), chrono::Duration::hours(12));
+        assert_eq!(parse_age("90d").unwrap(), chrono::Duration::days(90));
+        assert_eq!(parse_age("8w").unwrap(), chrono::Duration::weeks(8));
+    }
+
+    #[test]
+    fn test_parse_age_invalid() {
+        assert!(parse_age("").is_err());
+        assert!(parse_age("90").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("-3d").is_err());
+        assert!(parse_age("3y").is_err());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(2048), "2.0 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
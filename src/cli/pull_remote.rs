@@ -0,0 +1,162 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::core::config::DotsmithConfig;
+use crate::core::manifest::Manifest;
+use crate::core::output::Printer;
+use crate::core::remote::{self, PullStatus, RemoteDeployOpts, RemotePullAction};
+use crate::core::snapshot::SnapshotEngine;
+use crate::util;
+
+pub fn run(
+    out: &Printer,
+    host: &str,
+    user: Option<&str>,
+    tools: Option<&[String]>,
+    dry_run: bool,
+) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let manifest = Manifest::load(&config_dir)?;
+    let config = DotsmithConfig::load(&config_dir);
+    let path_map = config.hosts.get(host).map(|h| h.path_map.as_slice()).unwrap_or_default();
+
+    let tool_refs: Option<Vec<&str>> = tools.map(|t| t.iter().map(|s| s.as_str()).collect());
+
+    let opts = RemoteDeployOpts {
+        host,
+        user,
+        tools: tool_refs,
+        dry_run,
+        path_map,
+    };
+
+    // Remote copies are fetched here first so they can be diffed before anything is touched
+    let staging_dir = config_dir.join("pull-staging");
+    let outcome = pull(out, &config_dir, &manifest, &opts, &staging_dir);
+    let _ = std::fs::remove_dir_all(&staging_dir);
+    outcome
+}
+
+fn pull(
+    out: &Printer,
+    config_dir: &std::path::Path,
+    manifest: &Manifest,
+    opts: &RemoteDeployOpts,
+    staging_dir: &std::path::Path,
+) -> Result<()> {
+    let actions = remote::plan_remote_pull(manifest, opts, staging_dir)?;
+
+    if actions.is_empty() {
+        out.info("No files to pull.");
+        return Ok(());
+    }
+
+    let dest = match opts.user {
+        Some(u) => format!("{}@{}", u, opts.host),
+        None => opts.host.to_string(),
+    };
+
+    out.info(format_args!("Pull from {}:\n", dest.bold()));
+
+    for action in &actions {
+        let status = match action.status {
+            PullStatus::Changed => "update".yellow(),
+            PullStatus::New => "create".green(),
+            PullStatus::Unchanged => "same".dimmed(),
+            PullStatus::MissingRemote => "missing".red(),
+        };
+
+        let tool_prefix = format!("[{}]", action.tool).dimmed();
+        out.info(format_args!(
+            "  {} [{}] {}:{} -> {}{}",
+            tool_prefix,
+            status,
+            dest,
+            action.remote_path,
+            util::paths::contract_tilde(&action.local_path),
+            change_summary(action),
+        ));
+
+        if out.is_verbose()
+            && action.status == PullStatus::Changed
+            && let Some(diff) = text_diff(action)
+        {
+            println!("{}", diff);
+        }
+    }
+
+    if opts.dry_run {
+        out.info("");
+        println!(
+            "{} No local files changed (dry run)",
+            "[dry-run]".yellow().bold(),
+        );
+        return Ok(());
+    }
+
+    let backup_dir = config_dir.join("backups");
+    let result = remote::execute_remote_pull(&actions, &backup_dir)?;
+
+    let pulled: Vec<String> = actions
+        .iter()
+        .filter(|a| matches!(a.status, PullStatus::Changed | PullStatus::New))
+        .map(|a| util::paths::contract_tilde(&a.local_path))
+        .collect();
+    let logged = SnapshotEngine::open(config_dir)
+        .and_then(|engine| engine.record_operation("pull-remote", &pulled));
+    if let Err(e) = logged
+        && out.is_verbose()
+    {
+        eprintln!("  warning: failed to log pull: {}", e);
+    }
+
+    out.info("");
+    out.info(format_args!(
+        "{} Pulled {} file(s) from {} ({} unchanged, {} skipped)",
+        "OK".green().bold(),
+        result.files_pulled,
+        dest,
+        result.files_unchanged,
+        result.files_skipped,
+    ));
+
+    if result.files_backed_up > 0 {
+        out.info(format_args!(
+            "  {} local file(s) backed up to {}",
+            result.files_backed_up,
+            backup_dir.display(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// ` (N line(s) changed)` for text files that differ, empty otherwise.
+fn change_summary(action: &RemotePullAction) -> String {
+    if action.status != PullStatus::Changed {
+        return String::new();
+    }
+    match read_pair(action) {
+        Some((local, fetched)) => format!(
+            " ({} line(s) changed)",
+            util::diff::changed_lines(&local, &fetched)
+        ),
+        None => " (binary)".to_string(),
+    }
+}
+
+fn text_diff(action: &RemotePullAction) -> Option<String> {
+    let (local, fetched) = read_pair(action)?;
+    Some(util::diff::unified_diff(
+        &local,
+        &fetched,
+        &util::paths::contract_tilde(&action.local_path),
+    ))
+}
+
+/// Local and fetched contents, if both are readable text.
+fn read_pair(action: &RemotePullAction) -> Option<(String, String)> {
+    let fetched = std::fs::read_to_string(action.fetched.as_ref()?).ok()?;
+    let local = std::fs::read_to_string(&action.local_path).ok()?;
+    Some((local, fetched))
+}
@@ -1,12 +1,21 @@
+use std::path::Path;
+
 use anyhow::Result;
 use colored::Colorize;
 
-use crate::core::manifest::Manifest;
+use crate::core::config::DotsmithConfig;
+use crate::core::manifest::{Manifest, ToolEntry};
+use crate::core::output::Printer;
 use crate::core::reload;
+use crate::core::snapshot::SnapshotEngine;
 use crate::util;
 
 /// Reload configuration for a tool.
-pub fn run(verbose: bool, tool: &str) -> Result<()> {
+///
+/// On a terminal, changes made since the last snapshot are shown first and
+/// can be snapshotted, so whatever gets applied has a point to roll back to.
+/// Set `general.confirm_reload = false` to skip this.
+pub fn run(out: &Printer, tool: &str) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let manifest = Manifest::load(&config_dir)?;
 
@@ -15,21 +24,105 @@ pub fn run(verbose: bool, tool: &str) -> Result<()> {
         .get(tool)
         .ok_or_else(|| anyhow::anyhow!("'{}' is not tracked by dotsmith", tool))?;
 
+    let config = DotsmithConfig::load(&config_dir);
+    offer_snapshot(&config_dir, &config, tool, entry)?;
+
     // Use the first config path as the reload target
     let config_path = entry.config_paths.first().map(|s| s.as_str());
 
-    if verbose {
-        println!("Reloading {} configuration...", tool.bold());
-    }
+    out.detail(format_args!("Reloading {} configuration...", tool.bold()));
 
-    let description = reload::reload_tool(tool, config_path)?;
+    let description = reload::reload_tool(&config_dir, tool, config_path)?;
 
-    println!(
+    out.info(format_args!(
         "{} {}: {}",
         "OK".green().bold(),
         tool.bold(),
         description
+    ));
+
+    Ok(())
+}
+
+/// Reload every tracked tool that has a reload hook or module reload command.
+/// A failing tool is reported and the rest still run.
+pub fn run_all(out: &Printer) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let manifest = Manifest::load(&config_dir)?;
+    let config = DotsmithConfig::load(&config_dir);
+
+    let tools: Vec<(&String, &ToolEntry)> = manifest
+        .tools
+        .iter()
+        .filter(|(name, _)| reload::resolve_reload_command(&config_dir, &config, name).is_some())
+        .collect();
+
+    if tools.is_empty() {
+        out.info("No tracked tools have a reload command");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for (name, entry) in &tools {
+        offer_snapshot(&config_dir, &config, name, entry)?;
+
+        out.detail(format_args!("Reloading {} configuration...", name.bold()));
+
+        let config_path = entry.config_paths.first().map(|s| s.as_str());
+        match reload::reload_tool(&config_dir, name, config_path) {
+            Ok(description) => {
+                out.info(format_args!("{} {}: {}", "OK".green().bold(), name.bold(), description));
+            }
+            Err(e) => {
+                failed += 1;
+                println!("{} {}: {}", "ERR".red().bold(), name.bold(), e.root_cause());
+            }
+        }
+    }
+
+    out.info("");
+    out.info(format_args!(
+        "Reloaded {} of {} tool(s)",
+        tools.len() - failed,
+        tools.len()
+    ));
+    if failed > 0 {
+        anyhow::bail!("{} tool(s) failed to reload", failed);
+    }
+    Ok(())
+}
+
+/// On a terminal, show changes made since the tool's last snapshot and offer
+/// to snapshot them before reloading.
+fn offer_snapshot(
+    config_dir: &Path,
+    config: &DotsmithConfig,
+    tool: &str,
+    entry: &ToolEntry,
+) -> Result<()> {
+    if !config.general.confirm_reload || !util::prompt::is_interactive() {
+        return Ok(());
+    }
+
+    let mut engine = SnapshotEngine::open(config_dir)?;
+    engine.keep_secrets();
+    let diffs = engine.diff_current(tool, &entry.config_paths)?;
+    if diffs.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "{} {} has {} file(s) changed since the last snapshot:\n",
+        "!!".yellow(),
+        tool.bold(),
+        diffs.len()
     );
+    crate::cli::diff::print_diffs(&diffs);
 
+    if util::prompt::confirm_default("Snapshot these changes before reloading?", true)? {
+        let count = engine.snapshot_tool(tool, &entry.config_paths, Some("pre-reload snapshot"))?;
+        println!("{} Snapshotted {} file(s)", "OK".green().bold(), count);
+        crate::cli::snapshot::warn_kept_secrets(&engine);
+    }
     Ok(())
 }
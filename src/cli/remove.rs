@@ -2,23 +2,24 @@ use anyhow::Result;
 use colored::Colorize;
 
 use crate::core::manifest::Manifest;
+use crate::core::output::Printer;
 use crate::util;
 
-pub fn run(_verbose: bool, tool: &str) -> Result<()> {
+pub fn run(out: &Printer, tool: &str) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let mut manifest = Manifest::load(&config_dir)?;
 
     let removed = manifest.remove_tool(tool)?;
     manifest.save(&config_dir)?;
 
-    println!(
+    out.info(format_args!(
         "{} Removed {} from dotsmith management",
         "OK".green().bold(),
         tool.bold()
-    );
-    println!("  Your config files are untouched:");
+    ));
+    out.info("  Your config files are untouched:");
     for path in &removed.config_paths {
-        println!("    {}", path);
+        out.info(format_args!("    {}", path));
     }
 
     Ok(())
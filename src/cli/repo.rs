@@ -3,11 +3,14 @@ use colored::Colorize;
 
 use crate::core::config::DotsmithConfig;
 use crate::core::manifest::Manifest;
+use crate::core::output::Printer;
 use crate::core::repo;
+use crate::core::secrets::SecretScanner;
+use crate::core::snapshot::SnapshotEngine;
 use crate::util;
 
-pub fn run_init(verbose: bool, path: &str) -> Result<()> {
-    let expanded = util::paths::expand_tilde(path);
+pub fn run_init(out: &Printer, path: &str) -> Result<()> {
+    let expanded = util::paths::expand_tilde_checked(path)?;
     let repo_path = std::path::Path::new(&expanded);
 
     repo::init_repo(repo_path)?;
@@ -18,19 +21,17 @@ pub fn run_init(verbose: bool, path: &str) -> Result<()> {
     config.general.repo_path = Some(path.to_string());
     config.save(&config_dir)?;
 
-    if verbose {
-        println!("Initialized dotfile repo at {}", repo_path.display());
-    }
-    println!(
+    out.detail(format_args!("Initialized dotfile repo at {}", repo_path.display()));
+    out.info(format_args!(
         "{} Repo initialized at {}",
         "done:".green().bold(),
         path
-    );
+    ));
 
     Ok(())
 }
 
-pub fn run_sync(verbose: bool) -> Result<()> {
+pub fn run_sync(out: &Printer, only: &[String], push: bool, secrets_check: bool) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let config = DotsmithConfig::load(&config_dir);
 
@@ -40,33 +41,126 @@ pub fn run_sync(verbose: bool) -> Result<()> {
         .as_deref()
         .ok_or_else(|| anyhow::anyhow!("No repo path configured. Run `dotsmith repo init <path>` first."))?;
 
-    let expanded = util::paths::expand_tilde(repo_path_str);
+    let expanded = util::paths::expand_tilde_checked(repo_path_str)?;
     let repo_path = std::path::Path::new(&expanded);
     let manifest = Manifest::load(&config_dir)?;
 
-    let result = repo::sync_repo(repo_path, &manifest)?;
+    let push = push || config.general.repo_auto_push;
+    let scanner = if secrets_check {
+        Some(SecretScanner::new(&config.general.secret_patterns)?)
+    } else {
+        None
+    };
+    let result = repo::sync_repo_tools(repo_path, &manifest, only, push, scanner.as_ref())?;
 
-    if verbose {
-        println!(
-            "Synced {} file(s), committed: {}",
-            result.files_copied, result.committed
-        );
-    }
+    out.detail(format_args!(
+        "Synced {} file(s), committed: {}",
+        result.files_copied, result.committed
+    ));
 
     if result.committed {
-        println!(
+        out.info(format_args!(
             "{} Synced {} file(s) and committed",
             "done:".green().bold(),
             result.files_copied
-        );
+        ));
     } else {
-        println!("{} No changes to commit", "done:".green().bold());
+        out.info(format_args!("{} No changes to commit", "done:".green().bold()));
+    }
+
+    if result.pushed {
+        out.info(format_args!("{} Pushed to remote", "done:".green().bold()));
+    } else if let Some(err) = result.push_error {
+        println!("{} {}", "!!".yellow(), err);
+        println!(
+            "  The commit is kept locally. Check the remote with {}.",
+            "git -C <repo> remote -v".bold()
+        );
     }
 
     Ok(())
 }
 
-pub fn run_status(verbose: bool) -> Result<()> {
+pub fn run_restore(out: &Printer, dry_run: bool) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let config = DotsmithConfig::load(&config_dir);
+
+    let repo_path_str = config
+        .general
+        .repo_path
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("No repo path configured. Run `dotsmith repo init <path>` first."))?;
+
+    let expanded = util::paths::expand_tilde_checked(repo_path_str)?;
+    let manifest = Manifest::load(&config_dir)?;
+    let backup_dir = config_dir.join("backups");
+    let result = repo::restore_repo(&expanded, &manifest, &backup_dir, dry_run)?;
+
+    if dry_run || out.is_verbose() {
+        for path in &result.restored {
+            out.info(format_args!("  restore {}", path));
+        }
+    }
+
+    if dry_run {
+        println!(
+            "{} Would restore {} file(s) ({} unchanged, {} skipped, {} to back up)",
+            "[dry-run]".yellow().bold(),
+            result.files_copied,
+            result.files_unchanged,
+            result.files_skipped,
+            result.files_backed_up,
+        );
+        return Ok(());
+    }
+
+    let logged = SnapshotEngine::open(&config_dir)
+        .and_then(|engine| engine.record_operation("repo restore", &result.restored));
+    if let Err(e) = logged
+        && out.is_verbose()
+    {
+        eprintln!("  warning: failed to log restore: {}", e);
+    }
+
+    out.info(format_args!(
+        "{} Restored {} file(s) from {} ({} unchanged, {} skipped)",
+        "done:".green().bold(),
+        result.files_copied,
+        repo_path_str,
+        result.files_unchanged,
+        result.files_skipped,
+    ));
+    if result.files_backed_up > 0 {
+        out.info(format_args!(
+            "  {} existing file(s) backed up to {}",
+            result.files_backed_up,
+            backup_dir.display(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn run_remote(out: &Printer, url: &str) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let config = DotsmithConfig::load(&config_dir);
+
+    let repo_path_str = config
+        .general
+        .repo_path
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("No repo path configured. Run `dotsmith repo init <path>` first."))?;
+
+    let expanded = util::paths::expand_tilde_checked(repo_path_str)?;
+    repo::set_remote(&expanded, url)?;
+
+    out.detail(format_args!("Repo at: {}", repo_path_str));
+    out.info(format_args!("{} Remote set to {}", "done:".green().bold(), url));
+
+    Ok(())
+}
+
+pub fn run_status(out: &Printer) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let config = DotsmithConfig::load(&config_dir);
 
@@ -85,9 +179,7 @@ pub fn run_status(verbose: bool) -> Result<()> {
         return Ok(());
     }
 
-    if verbose {
-        println!("Repo at: {}", repo_path_str);
-    }
+    out.detail(format_args!("Repo at: {}", repo_path_str));
 
     if status.changed_files > 0 {
         println!(
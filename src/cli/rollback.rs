@@ -1,62 +1,271 @@
+use std::path::Path;
+
 use anyhow::Result;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use colored::Colorize;
 
-use crate::core::snapshot::SnapshotEngine;
+use crate::core::errors::DotsmithError;
+use crate::core::manifest::Manifest;
+use crate::core::output::Printer;
+use crate::core::snapshot::{self, SnapshotContent, SnapshotEngine};
 use crate::util;
 
+/// Rollbacks that would change more lines than this need confirmation (or `--force`).
+const LARGE_ROLLBACK_LINES: usize = 50;
+
 /// Rollback a config file to a specific snapshot.
-pub fn run(verbose: bool, snapshot_id: i64, dry_run: bool) -> Result<()> {
+pub fn run(out: &Printer, snapshot_id: i64, dry_run: bool, force: bool) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let engine = SnapshotEngine::open(&config_dir)?;
+    rollback_one(out, &engine, &config_dir, snapshot_id, dry_run, force)
+}
+
+/// Roll back every file with a snapshot tagged `tag` (only `tool`'s files if
+/// given) to that snapshot.
+pub fn run_tag(
+    out: &Printer,
+    tag: &str,
+    tool: Option<&str>,
+    dry_run: bool,
+    force: bool,
+) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let engine = SnapshotEngine::open(&config_dir)?;
+
+    let snapshots = engine.snapshot_by_tag(tool, tag)?;
+    if snapshots.is_empty() {
+        return Err(DotsmithError::TagNotFound(tag.to_string()).into());
+    }
+
+    out.info(format_args!("Rolling back {} file(s) tagged {}", snapshots.len(), tag.bold()));
+    for snapshot in &snapshots {
+        out.info("");
+        rollback_one(out, &engine, &config_dir, snapshot.id, dry_run, force)?;
+    }
+    Ok(())
+}
+
+/// Roll back all of `tool`'s files to their newest snapshots at or before
+/// `at`, a UTC time as shown by `history`.
+pub fn run_at(out: &Printer, tool: &str, at: &str, dry_run: bool, force: bool) -> Result<()> {
+    let as_of = parse_at(at)?;
     let config_dir = util::paths::config_dir()?;
     let engine = SnapshotEngine::open(&config_dir)?;
+    let manifest = Manifest::load(&config_dir)?;
+    let config_paths = &manifest
+        .get_tool(tool)
+        .ok_or_else(|| DotsmithError::ToolNotTracked(tool.to_string()))?
+        .config_paths;
+
+    let files = engine.snapshots_as_of(tool, config_paths, as_of)?;
+    if files.is_empty() {
+        anyhow::bail!("no snapshots of {} yet", tool);
+    }
+
+    if dry_run {
+        for (file_path, id) in &files {
+            println!();
+            match id {
+                Some(id) => rollback_one(out, &engine, &config_dir, *id, true, force)?,
+                None => println!(
+                    "{} {}: no snapshot at or before {}",
+                    "!!".yellow(),
+                    file_path,
+                    at
+                ),
+            }
+        }
+        return Ok(());
+    }
+
+    if !force {
+        let mut changed = 0;
+        for id in files.iter().filter_map(|(_, id)| *id) {
+            if let Some((file_path, SnapshotContent::Text(content))) = engine.get_snapshot(id)?
+                && let Ok(current) = std::fs::read_to_string(util::paths::expand_tilde(&file_path))
+            {
+                changed += util::diff::changed_lines(&current, &content);
+            }
+        }
+        if !confirm_large(changed, &format!("{}'s files", tool))? {
+            println!("Rollback cancelled.");
+            return Ok(());
+        }
+    }
+
+    let backup_dir = config_dir.join("backups");
+    let result = engine.rollback_tool(tool, config_paths, as_of, &backup_dir)?;
+
+    for (file_path, id) in &result.restored {
+        out.info(format_args!(
+            "{} Rolled back {} to snapshot #{}",
+            "OK".green().bold(),
+            file_path.bold(),
+            id
+        ));
+    }
+    for file_path in &result.unchanged {
+        out.detail(format_args!("{} {} already matches", "--".dimmed(), file_path));
+    }
+    for file_path in &result.skipped {
+        println!(
+            "{} {}: no snapshot at or before {}, skipped",
+            "!!".yellow(),
+            file_path,
+            at
+        );
+    }
+    out.info(format_args!(
+        "  {} restored, {} unchanged, {} skipped",
+        result.restored.len(),
+        result.unchanged.len(),
+        result.skipped.len()
+    ));
+    if !result.restored.is_empty() {
+        out.detail(format_args!("  Backups saved to {}", backup_dir.display()));
+    }
+
+    Ok(())
+}
+
+/// Parse a UTC time like `2026-02-01 12:00`, with optional seconds, a `T`
+/// separator, or just a date for midnight.
+fn parse_at(at: &str) -> Result<DateTime<Utc>> {
+    const FORMATS: &[&str] = &[
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ];
+
+    let at = at.trim();
+    FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(at, f).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(at, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+        .map(|t| t.and_utc())
+        .ok_or_else(|| {
+            anyhow::anyhow!("invalid time '{}' (expected e.g. \"2026-02-01 12:00\")", at)
+        })
+}
+
+/// Ask before a rollback discards more than [`LARGE_ROLLBACK_LINES`] lines.
+/// Refuses outright when there's no terminal to ask on.
+fn confirm_large(changed: usize, what: &str) -> Result<bool> {
+    if changed <= LARGE_ROLLBACK_LINES {
+        return Ok(true);
+    }
+    println!(
+        "{} This rollback changes {} line(s) in {}",
+        "warning:".yellow().bold(),
+        changed,
+        what
+    );
+    if !util::prompt::is_interactive() {
+        anyhow::bail!(
+            "refusing to discard {} changed line(s) without confirmation — re-run with --force",
+            changed
+        );
+    }
+    util::prompt::confirm("Continue?")
+}
 
+fn rollback_one(
+    out: &Printer,
+    engine: &SnapshotEngine,
+    config_dir: &Path,
+    snapshot_id: i64,
+    dry_run: bool,
+    force: bool,
+) -> Result<()> {
     // First, show what would be rolled back
     let (file_path, content) = engine
         .get_snapshot(snapshot_id)?
         .ok_or_else(|| anyhow::anyhow!("snapshot #{} not found", snapshot_id))?;
+    if file_path == snapshot::STDIN_PATH {
+        anyhow::bail!("snapshot #{} was read from stdin and has no file to restore", snapshot_id);
+    }
 
-    println!(
+    out.info(format_args!(
         "Snapshot #{}: {} ({} bytes)",
         snapshot_id,
         file_path.bold(),
-        content.len()
-    );
+        content.as_bytes().len()
+    ));
+
+    let preview = engine.rollback_preview(snapshot_id)?;
+    let changed = preview
+        .as_ref()
+        .filter(|diff| !diff.binary)
+        .map_or(0, |diff| util::diff::changed_lines(&diff.old_content, &diff.new_content));
+    let exists = util::paths::expand_tilde(&file_path).exists();
 
     if dry_run {
         println!("{} Would rollback {} to snapshot #{}", "[dry-run]".yellow().bold(), file_path, snapshot_id);
 
         // Show diff between current and snapshot
-        let current_path = util::paths::expand_tilde(&file_path);
-        if current_path.exists() {
-            let current = std::fs::read_to_string(&current_path)?;
-            if current != content {
-                let diff = util::diff::unified_diff(&current, &content, &file_path);
-                if !diff.is_empty() {
-                    println!();
-                    println!("{}", diff);
+        match &preview {
+            None => println!("  File is already at this snapshot state."),
+            Some(diff) if diff.binary => println!("  Binary file differs."),
+            Some(diff) => {
+                if !exists {
+                    println!("  File does not exist — would be created.");
                 }
-            } else {
-                println!("  File is already at this snapshot state.");
+                println!("  Would change {} line(s)", changed);
+                println!();
+                crate::cli::diff::print_diffs(std::slice::from_ref(diff));
             }
-        } else {
-            println!("  File does not exist — would be created.");
         }
 
         return Ok(());
     }
 
+    // Guard against a mistyped ID discarding a lot of work
+    if !force && exists && !confirm_large(changed, &file_path)? {
+        println!("Rollback cancelled.");
+        return Ok(());
+    }
+
     let backup_dir = config_dir.join("backups");
     let restored_path = engine.rollback(snapshot_id, &backup_dir)?;
 
-    println!(
+    out.info(format_args!(
         "{} Rolled back {} to snapshot #{}",
         "OK".green().bold(),
         restored_path.bold(),
         snapshot_id
-    );
+    ));
+    out.detail(format_args!("  Backup saved to {}", backup_dir.display()));
+
+    Ok(())
+}
 
-    if verbose {
-        println!("  Backup saved to {}", backup_dir.display());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_at() {
+        let expected = NaiveDate::from_ymd_opt(2026, 2, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+        assert_eq!(parse_at("2026-02-01 12:00").unwrap(), expected);
+        assert_eq!(parse_at("2026-02-01 12:00:00").unwrap(), expected);
+        assert_eq!(parse_at("2026-02-01T12:00").unwrap(), expected);
+        assert_eq!(parse_at("2026-02-01").unwrap(), expected - chrono::Duration::hours(12));
     }
 
-    Ok(())
+    #[test]
+    fn test_parse_at_invalid() {
+        assert!(parse_at("").is_err());
+        assert!(parse_at("yesterday").is_err());
+        assert!(parse_at("2026-02-30 12:00").is_err());
+        assert!(parse_at("12:00").is_err());
+    }
 }
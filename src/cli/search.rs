@@ -1,82 +1,103 @@
-use anyhow::Result;
-use colored::Colorize;
+use std::collections::BTreeMap;
 
-use crate::core::module::{ModuleRegistry, OptionEntry};
+use anyhow::{Result, bail};
+use colored::Colorize;
 
-pub fn run(_verbose: bool, query: &str) -> Result<()> {
-    let query_lower = query.to_lowercase();
-    let mut total_results = 0;
-    let mut tools_with_results = 0;
+use crate::core::module::{ModuleRegistry, OptionType};
+use crate::core::search::{SearchFilters, search_options};
+use crate::util;
+
+pub fn run(
+    query: &str,
+    tool: Option<&str>,
+    option_type: Option<&str>,
+    tag: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    if let Some(name) = tool
+        && ModuleRegistry::load_options(&config_dir, name).is_none()
+    {
+        bail!(
+            "no option database for '{}' — available: {}",
+            name,
+            ModuleRegistry::module_names(&config_dir).join(", ")
+        );
+    }
+    let filters = SearchFilters {
+        tool: tool.map(str::to_string),
+        option_type: option_type.and_then(OptionType::from_name),
+        tag: tag.map(str::to_string),
+    };
+    let hits = search_options(&config_dir, query, &filters);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&hits)?);
+        return Ok(());
+    }
 
-    for tool_name in ModuleRegistry::builtin_names() {
-        let Some(db) = ModuleRegistry::get_options(tool_name) else {
-            continue;
-        };
+    let mut per_tool: Vec<(&str, usize)> = Vec::new();
+    let mut per_type: BTreeMap<String, usize> = BTreeMap::new();
 
-        let matches: Vec<&OptionEntry> = db
-            .options
-            .iter()
-            .filter(|opt| matches_query(opt, &query_lower))
-            .collect();
-
-        if matches.is_empty() {
-            continue;
+    for hit in &hits {
+        match per_tool.last_mut() {
+            Some((name, count)) if *name == hit.tool => *count += 1,
+            _ => {
+                per_tool.push((&hit.tool, 1));
+                println!();
+                println!("  {}", hit.tool.cyan().bold());
+            }
         }
 
-        tools_with_results += 1;
-        total_results += matches.len();
-
-        println!();
-        println!("  {}", tool_name.cyan().bold());
-
-        for opt in &matches {
-            let type_str = format!("{:?}", opt.option_type).to_lowercase();
-            println!(
-                "    {} ({}) [{}]",
-                opt.name.bold(),
-                type_str,
-                opt.category.dimmed()
-            );
-            println!("      {}", opt.description);
-            if let Some(ref example) = opt.example {
-                println!("      Example: {}", example.dimmed());
-            }
-            if let Some(ref url) = opt.url {
-                println!("      {}", url.blue().underline());
-            }
-            println!();
+        let type_str = format!("{:?}", hit.option_type).to_lowercase();
+        *per_type.entry(type_str.clone()).or_insert(0) += 1;
+        println!(
+            "    {} ({}) [{}]",
+            hit.name.bold(),
+            type_str,
+            hit.category.dimmed()
+        );
+        println!("      {}", hit.description);
+        if let Some(ref example) = hit.example {
+            println!("      Example: {}", example.dimmed());
         }
+        if let Some(ref url) = hit.url {
+            println!("      {}", url.blue().underline());
+        }
+        println!();
     }
 
-    if total_results == 0 {
+    if hits.is_empty() && query.is_empty() {
+        println!("No options match the given filters");
+    } else if hits.is_empty() {
         println!("No results for \"{}\"", query);
     } else {
         println!(
-            "  {} result(s) across {} tool(s)",
-            total_results, tools_with_results
+            "  {} result(s) across {} tool(s) ({})",
+            hits.len().to_string().bold(),
+            per_tool.len(),
+            format_breakdown(per_type)
         );
+        let by_tool: Vec<String> = per_tool
+            .iter()
+            .map(|(name, count)| format!("{} {}", name, count))
+            .collect();
+        println!("  {}", by_tool.join(", ").dimmed());
     }
 
     Ok(())
 }
 
-fn matches_query(opt: &OptionEntry, query: &str) -> bool {
-    let q = query.to_lowercase();
-    if opt.name.to_lowercase().contains(&q) {
-        return true;
-    }
-    if opt.description.to_lowercase().contains(&q) {
-        return true;
-    }
-    if opt.category.to_lowercase().contains(&q) {
-        return true;
-    }
-    if let Some(ref tags) = opt.tags {
-        if tags.iter().any(|t| t.to_lowercase().contains(&q)) {
-            return true;
-        }
-    }
-    false
+/// Render type counts as "9 color, 5 string, 4 enum", largest first.
+fn format_breakdown(counts: BTreeMap<String, usize>) -> String {
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    // Stable sort keeps ties in alphabetical order from the BTreeMap
+    counts.sort_by_key(|c| std::cmp::Reverse(c.1));
+    counts
+        .iter()
+        .map(|(name, count)| format!("{} {}", count, name))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 #[cfg(test)]
@@ -84,64 +105,15 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_search_finds_mouse() {
-        let db = ModuleRegistry::get_options("tmux").unwrap();
-        let matches: Vec<&OptionEntry> = db
-            .options
-            .iter()
-            .filter(|opt| matches_query(opt, "mouse"))
-            .collect();
-        assert!(!matches.is_empty(), "should find 'mouse' in tmux options");
-        assert!(matches.iter().any(|o| o.name == "mouse"));
-    }
-
-    #[test]
-    fn test_search_case_insensitive() {
-        let db = ModuleRegistry::get_options("tmux").unwrap();
-        let upper: Vec<&OptionEntry> = db
-            .options
-            .iter()
-            .filter(|opt| matches_query(opt, "MOUSE"))
-            .collect();
-        let lower: Vec<&OptionEntry> = db
-            .options
-            .iter()
-            .filter(|opt| matches_query(opt, "mouse"))
-            .collect();
-        assert_eq!(upper.len(), lower.len());
-    }
-
-    #[test]
-    fn test_search_no_results() {
-        let db = ModuleRegistry::get_options("tmux").unwrap();
-        let matches: Vec<&OptionEntry> = db
-            .options
-            .iter()
-            .filter(|opt| matches_query(opt, "zzzznonexistent"))
-            .collect();
-        assert!(matches.is_empty());
-    }
-
-    #[test]
-    fn test_search_matches_tags() {
-        let db = ModuleRegistry::get_options("tmux").unwrap();
-        // "mouse" option should have a relevant tag
-        let matches: Vec<&OptionEntry> = db
-            .options
-            .iter()
-            .filter(|opt| matches_query(opt, "interaction"))
-            .collect();
-        assert!(!matches.is_empty(), "should match category/tags");
-    }
-
-    #[test]
-    fn test_search_matches_description() {
-        let db = ModuleRegistry::get_options("tmux").unwrap();
-        let matches: Vec<&OptionEntry> = db
-            .options
-            .iter()
-            .filter(|opt| matches_query(opt, "clipboard"))
-            .collect();
-        assert!(!matches.is_empty(), "should match in description");
+    fn test_format_breakdown_orders_by_count() {
+        let mut counts = BTreeMap::new();
+        counts.insert("enum".to_string(), 4);
+        counts.insert("color".to_string(), 9);
+        counts.insert("string".to_string(), 5);
+        counts.insert("bool".to_string(), 4);
+        assert_eq!(
+            format_breakdown(counts),
+            "9 color, 5 string, 4 bool, 4 enum"
+        );
     }
 }
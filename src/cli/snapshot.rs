@@ -1,15 +1,35 @@
+use std::io::Read;
+
 use anyhow::Result;
 use colored::Colorize;
 
 use crate::core::manifest::Manifest;
-use crate::core::snapshot::SnapshotEngine;
+use crate::core::output::Printer;
+use crate::core::secrets::SecretHit;
+use crate::core::snapshot::{self, SnapshotEngine, MAX_MATCHES_PER_SNAPSHOT};
 use crate::util;
 
-/// Take a snapshot of config files for a specific tool or all tools.
-pub fn run(verbose: bool, tool: Option<&str>, message: Option<&str>) -> Result<()> {
+/// Take a snapshot of config files for a specific tool or all tools,
+/// optionally tagging the snapshot of each file. Unless `secrets_check` is
+/// off, a file that looks like it holds a secret stops the snapshot.
+pub fn run(
+    out: &Printer,
+    tool: Option<&str>,
+    message: Option<&str>,
+    tag: Option<&str>,
+    secrets_check: bool,
+) -> Result<()> {
+    // Reject a bad tag before anything is written
+    if let Some(tag) = tag {
+        snapshot::validate_tag(tag)?;
+    }
+
     let config_dir = util::paths::config_dir()?;
     let manifest = Manifest::load(&config_dir)?;
-    let engine = SnapshotEngine::open(&config_dir)?;
+    let mut engine = SnapshotEngine::open(&config_dir)?;
+    if !secrets_check {
+        engine.skip_secrets_check();
+    }
 
     match tool {
         Some(name) => {
@@ -21,34 +41,378 @@ pub fn run(verbose: bool, tool: Option<&str>, message: Option<&str>) -> Result<(
             let count = engine.snapshot_tool(name, &entry.config_paths, message)?;
 
             if count > 0 {
-                println!(
+                out.info(format_args!(
                     "{} Snapshotted {} file(s) for {}",
                     "OK".green().bold(),
                     count,
                     name.bold()
-                );
+                ));
             } else {
-                println!("No changes to snapshot for {}", name.bold());
+                out.info(format_args!("No changes to snapshot for {}", name.bold()));
             }
         }
         None => {
             let count = engine.snapshot_all(&manifest, message)?;
 
             if count > 0 {
-                println!(
+                out.info(format_args!(
                     "{} Snapshotted {} file(s) across all tools",
                     "OK".green().bold(),
                     count
-                );
+                ));
+            } else {
+                out.info("No changes to snapshot across any tracked tools");
+            }
+        }
+    }
+
+    report_secret_hits(&engine);
+
+    if let Some(tag) = tag {
+        let mut tagged = 0;
+        for (name, entry) in &manifest.tools {
+            if tool.is_none_or(|t| t == name) {
+                tagged += engine.tag_current(name, &entry.config_paths, tag)?;
+            }
+        }
+        out.info(format_args!(
+            "{} Tagged {} file(s) as {}",
+            "OK".green().bold(),
+            tagged,
+            tag.bold()
+        ));
+    }
+
+    out.detail(format_args!(
+        "  Snapshots stored in {}",
+        config_dir.join("snapshots.db").display()
+    ));
+
+    Ok(())
+}
+
+/// Snapshot a single file, or stdin when `file` is `-`, under `as_tool`
+/// (default: the file name). The tool doesn't have to be tracked; its
+/// snapshots show up in `history` and `diff` like any other.
+pub fn run_file(
+    out: &Printer,
+    file: &str,
+    as_tool: Option<&str>,
+    message: Option<&str>,
+    secrets_check: bool,
+) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let mut engine = SnapshotEngine::open(&config_dir)?;
+    if !secrets_check {
+        engine.skip_secrets_check();
+    }
+
+    let (name, stored) = if file == "-" {
+        let Some(name) = as_tool else {
+            anyhow::bail!("--file - needs --as <NAME> to file the snapshot under");
+        };
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes)?;
+        (name.to_string(), engine.snapshot_stdin(name, bytes, message)?)
+    } else {
+        let path = std::path::absolute(util::paths::expand_tilde_checked(file)?)?;
+        if !path.is_file() {
+            anyhow::bail!("'{}' is not a file", file);
+        }
+        util::fs::check_path_safety(&path)?;
+        let name = match as_tool {
+            Some(name) => name.to_string(),
+            None => path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| file.to_string()),
+        };
+        let stored = engine.snapshot_path(&name, &path, message)?;
+        (name, stored)
+    };
+
+    if stored {
+        out.info(format_args!("{} Snapshotted 1 file for {}", "OK".green().bold(), name.bold()));
+    } else if !report_secret_hits(&engine) {
+        out.info(format_args!("No changes to snapshot for {}", name.bold()));
+    }
+
+    out.detail(format_args!(
+        "  Snapshots stored in {}",
+        config_dir.join("snapshots.db").display()
+    ));
+
+    Ok(())
+}
+
+/// Warn about files the secrets check held back, and how to store them.
+/// Printed even under `--quiet`, since those files weren't stored. Returns
+/// whether there were any.
+fn report_secret_hits(engine: &SnapshotEngine) -> bool {
+    let skipped = engine.take_secret_hits();
+    for (path, hits) in &skipped {
+        println!("{} Skipped {} — it may contain a secret:", "!!".yellow(), path.bold());
+        print_secret_hits(hits);
+    }
+    if !skipped.is_empty() {
+        println!(
+            "  Pass {} to snapshot it anyway, or set {} to store snapshots encrypted.",
+            "--no-secrets-check".bold(),
+            "general.encrypt".bold()
+        );
+    }
+    !skipped.is_empty()
+}
+
+/// Warn about files a safety snapshot stored even though they look like they
+/// contain secrets (see [`SnapshotEngine::keep_secrets`]).
+pub fn warn_kept_secrets(engine: &SnapshotEngine) {
+    for (path, hits) in engine.take_secret_hits() {
+        println!(
+            "{} {} may contain a secret — kept in the safety snapshot anyway:",
+            "!!".yellow(),
+            path.bold()
+        );
+        print_secret_hits(&hits);
+    }
+}
+
+fn print_secret_hits(hits: &[SecretHit]) {
+    for hit in hits {
+        println!("    line {}: {} ({})", hit.line, hit.kind, hit.preview);
+    }
+}
+
+/// List the files `snapshot` would store for a tool (or all tools) without
+/// storing them.
+pub fn run_dry_run(tool: Option<&str>) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let manifest = Manifest::load(&config_dir)?;
+    let engine = SnapshotEngine::open(&config_dir)?;
+
+    if let Some(name) = tool
+        && !manifest.has_tool(name)
+    {
+        anyhow::bail!("'{}' is not tracked by dotsmith", name);
+    }
+
+    let mut new_count = 0;
+    let mut total = 0;
+    for (name, entry) in &manifest.tools {
+        if tool.is_some_and(|t| t != name) {
+            continue;
+        }
+        for (file_path, is_new) in engine.preview_snapshot(name, &entry.config_paths)? {
+            total += 1;
+            if is_new {
+                new_count += 1;
+                println!("  {} {}: {}", "new".green(), name, file_path);
             } else {
-                println!("No changes to snapshot across any tracked tools");
+                println!("  {} {}: {}", "unchanged".dimmed(), name, file_path);
             }
         }
     }
 
-    if verbose {
-        println!("  Snapshots stored in {}", config_dir.join("snapshots.db").display());
+    println!(
+        "{} Would snapshot {} of {} file(s)",
+        "[dry-run]".yellow().bold(),
+        new_count,
+        total
+    );
+    Ok(())
+}
+
+/// Delete snapshots whose tool is no longer in the manifest.
+pub fn run_gc(out: &Printer, dry_run: bool, force: bool) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let manifest = Manifest::load(&config_dir)?;
+    let engine = SnapshotEngine::open(&config_dir)?;
+
+    let tracked: Vec<&str> = manifest.tools.keys().map(String::as_str).collect();
+    let orphans = engine.orphaned_tools(&tracked)?;
+
+    if orphans.is_empty() {
+        out.info("No orphaned snapshots (pinned snapshots are never collected)");
+        return Ok(());
+    }
+
+    let total: usize = orphans.iter().map(|(_, count)| count).sum();
+    out.info("Snapshots of tools no longer tracked:");
+    for (tool, count) in &orphans {
+        out.info(format_args!("  {} ({} snapshot(s))", tool.bold(), count));
+    }
+
+    if dry_run {
+        out.info("");
+        println!(
+            "{} Would delete {} snapshot(s) across {} tool(s)",
+            "[dry-run]".yellow().bold(),
+            total,
+            orphans.len()
+        );
+        return Ok(());
+    }
+
+    if !force {
+        if !util::prompt::is_interactive() {
+            anyhow::bail!(
+                "refusing to delete {} snapshot(s) without confirmation — re-run with --force",
+                total
+            );
+        }
+        if !util::prompt::confirm(&format!("Delete {} snapshot(s)?", total))? {
+            out.info("Nothing deleted.");
+            return Ok(());
+        }
+    }
+
+    let tools: Vec<&str> = orphans.iter().map(|(tool, _)| tool.as_str()).collect();
+    let removed = engine.delete_tools(&tools)?;
+
+    out.info(format_args!(
+        "{} Deleted {} snapshot(s) across {} tool(s)",
+        "OK".green().bold(),
+        removed,
+        tools.len()
+    ));
+    out.detail(format_args!(
+        "  Snapshots stored in {}",
+        config_dir.join("snapshots.db").display()
+    ));
+
+    Ok(())
+}
+
+/// Pin or unpin a snapshot so cleanup keeps (or may remove) it.
+pub fn run_pin(snapshot_id: i64, pinned: bool) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let engine = SnapshotEngine::open(&config_dir)?;
+
+    if !engine.set_pinned(snapshot_id, pinned)? {
+        anyhow::bail!("snapshot #{} not found", snapshot_id);
+    }
+
+    if pinned {
+        println!(
+            "{} Pinned snapshot #{} — prune and gc will keep it",
+            "OK".green().bold(),
+            snapshot_id
+        );
+    } else {
+        println!("{} Unpinned snapshot #{}", "OK".green().bold(), snapshot_id);
+    }
+
+    Ok(())
+}
+
+pub fn run_annotate(snapshot_id: i64, message: &str, append: bool) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let engine = SnapshotEngine::open(&config_dir)?;
+
+    if !engine.annotate(snapshot_id, message, append)? {
+        anyhow::bail!("snapshot #{} not found", snapshot_id);
+    }
+
+    if message.trim().is_empty() && !append {
+        println!("{} Cleared the message of snapshot #{}", "OK".green().bold(), snapshot_id);
+    } else {
+        println!("{} Annotated snapshot #{}", "OK".green().bold(), snapshot_id);
+    }
+
+    Ok(())
+}
+
+/// List files whose latest snapshots share content, either one file tracked
+/// under several tools or copies at different paths. Read-only.
+pub fn run_dupes(out: &Printer) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let manifest = Manifest::load(&config_dir)?;
+    let engine = SnapshotEngine::open(&config_dir)?;
+
+    let groups = engine.shared_content()?;
+    if groups.is_empty() {
+        println!("No tracked files share content");
+        return Ok(());
+    }
+
+    println!("Files with identical latest snapshots:");
+    let mut tracked_twice = false;
+    for group in &groups {
+        println!();
+        let mut paths: Vec<&str> = group.files.iter().map(|(_, path)| path.as_str()).collect();
+        paths.sort_unstable();
+        paths.dedup();
+        tracked_twice |= paths.len() < group.files.len();
+        let note = if paths.len() == 1 {
+            format!("same file under {} tools", group.files.len())
+        } else {
+            format!("same content at {} paths", paths.len())
+        };
+        println!("  {}  {}", group.hash[..8].yellow(), note.dimmed());
+
+        for (tool, path) in &group.files {
+            let untracked = if manifest.has_tool(tool) {
+                String::new()
+            } else {
+                format!("  {}", "(no longer tracked)".dimmed())
+            };
+            println!("    {:<12} {}{}", tool.bold(), path, untracked);
+        }
+    }
+
+    if tracked_twice {
+        println!();
+        println!(
+            "{} A file tracked under several tools only needs one of them; drop the extra \
+             path from manifest.toml.",
+            "Hint:".yellow().bold()
+        );
+    }
+
+    out.detail(format_args!("  Snapshots stored in {}", config_dir.join("snapshots.db").display()));
+
+    Ok(())
+}
+
+/// Search stored snapshot content for `query` and print the matching lines of
+/// each snapshot that contains it.
+pub fn run_grep(query: &str, tool: Option<&str>, json: bool) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let engine = SnapshotEngine::open(&config_dir)?;
+    let matches = engine.search_content(tool, query)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&matches)?);
+        return Ok(());
+    }
+
+    if matches.is_empty() {
+        println!("No snapshots contain '{}'", query);
+        return Ok(());
+    }
+
+    for m in &matches {
+        let entry = &m.snapshot;
+        println!(
+            "{}  {}  {:<10} {}",
+            format!("#{}", entry.id).cyan(),
+            entry.created_at.dimmed(),
+            entry.tool.bold(),
+            entry.file_path
+        );
+        for line in &m.lines {
+            let text = line.text.replace(query, &query.red().bold().to_string());
+            println!("  {:>5}: {}", line.line.to_string().dimmed(), text);
+        }
+        if m.total > MAX_MATCHES_PER_SNAPSHOT {
+            println!(
+                "         {}",
+                format!("... {} more matching line(s)", m.total - m.lines.len()).dimmed()
+            );
+        }
     }
 
+    println!();
+    println!("{} snapshot(s) contain '{}'", matches.len(), query);
     Ok(())
 }
@@ -1,38 +1,71 @@
 use anyhow::Result;
 use colored::Colorize;
+use serde::Serialize;
 
-use crate::core::manifest::Manifest;
+use crate::core::config::DotsmithConfig;
+use crate::core::manifest::{Manifest, ToolEntry};
+use crate::core::output::Printer;
+use crate::core::repo;
+use crate::core::snapshot::SnapshotEngine;
 use crate::util;
 
-pub fn run(verbose: bool) -> Result<()> {
+/// `dotsmith status --json` output.
+#[derive(Serialize)]
+struct StatusReport<'a> {
+    tools: Vec<ToolStatus<'a>>,
+    warnings: Vec<String>,
+    /// Only present with `--ahead`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ahead: Option<Vec<repo::AheadFile>>,
+}
+
+#[derive(Serialize)]
+struct ToolStatus<'a> {
+    name: &'a str,
+    tier: u8,
+    existing_paths: usize,
+    total_paths: usize,
+    plugin_manager: Option<&'a str>,
+    changed_files: usize,
+    lines_added: usize,
+    lines_removed: usize,
+}
+
+/// How a tool's live files differ from their last snapshots.
+#[derive(Default)]
+struct Changes {
+    files: usize,
+    added: usize,
+    removed: usize,
+}
+
+pub fn run(out: &Printer, ahead: bool, changed_only: bool, json: bool) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let manifest = Manifest::load(&config_dir)?;
+    let engine = SnapshotEngine::open(&config_dir)?;
+
+    if json {
+        return print_json(&config_dir, &manifest, &engine, ahead, changed_only);
+    }
 
     if manifest.tools.is_empty() {
-        println!("No tools tracked.");
-        println!("  Run {} to start.", "dotsmith add <tool>".bold());
+        out.info("No tools tracked.");
+        out.info(format_args!("  Run {} to start.", "dotsmith add <tool>".bold()));
         return Ok(());
     }
 
     let mut warnings: Vec<String> = Vec::new();
+    let mut listed = 0;
 
     for (name, entry) in &manifest.tools {
-        let mut existing_count = 0;
-        let mut total_count = 0;
-
-        for path_str in &entry.config_paths {
-            total_count += 1;
-            let path = util::paths::expand_tilde(path_str);
-
-            if path.exists() {
-                existing_count += 1;
-            } else if util::fs::is_symlink(&path) {
-                // Broken symlink
-                warnings.push(format!("{}: broken symlink {}", name, path_str));
-            } else {
-                warnings.push(format!("{}: missing {}", name, path_str));
-            }
+        let changes = uncommitted_changes(&engine, name, entry, &mut warnings);
+        if changed_only && changes.files == 0 {
+            continue;
         }
+        listed += 1;
+
+        let total_count = entry.config_paths.len();
+        let existing_count = check_paths(name, &entry.config_paths, &mut warnings);
 
         let tier_label = format!("Tier {}", entry.tier);
         let status_icon = if existing_count == total_count {
@@ -46,17 +79,31 @@ pub fn run(verbose: bool) -> Result<()> {
             None => String::new(),
         };
 
+        let (dirty, change_info) = if changes.files == 0 {
+            (" ".to_string(), String::new())
+        } else {
+            (
+                "*".yellow().bold().to_string(),
+                format!(
+                    "  +{}/-{} lines across {} file(s)",
+                    changes.added, changes.removed, changes.files
+                ),
+            )
+        };
+
         println!(
-            "  {} {:<12} {}  {}/{} paths{}",
+            "  {} {} {:<12} {}  {}/{} paths{}{}",
             status_icon,
+            dirty,
             name,
             tier_label.dimmed(),
             existing_count,
             total_count,
+            change_info.yellow(),
             pm_info.dimmed()
         );
 
-        if verbose {
+        if out.is_verbose() {
             for path_str in &entry.config_paths {
                 let path = util::paths::expand_tilde(path_str);
                 let indicator = if path.exists() {
@@ -64,11 +111,15 @@ pub fn run(verbose: bool) -> Result<()> {
                 } else {
                     "!!".red().to_string()
                 };
-                println!("      {} {}", indicator, path_str);
+                out.detail(format_args!("      {} {}", indicator, path_str));
             }
         }
     }
 
+    if changed_only && listed == 0 {
+        println!("No uncommitted changes since the last snapshots.");
+    }
+
     if !warnings.is_empty() {
         println!();
         println!("{}", "Warnings:".yellow().bold());
@@ -77,5 +128,140 @@ pub fn run(verbose: bool) -> Result<()> {
         }
     }
 
+    if ahead {
+        print_ahead(&config_dir, &manifest)?;
+    }
+
+    Ok(())
+}
+
+/// Count the config paths that exist, recording a warning for each missing
+/// path or broken symlink.
+fn check_paths(name: &str, config_paths: &[String], warnings: &mut Vec<String>) -> usize {
+    let mut existing_count = 0;
+    for path_str in config_paths {
+        let path = util::paths::expand_tilde(path_str);
+
+        // A glob matching nothing yet isn't missing
+        if path.exists() || util::paths::is_glob(path_str) {
+            existing_count += 1;
+        } else if util::fs::is_symlink(&path) {
+            // Broken symlink
+            warnings.push(format!("{}: broken symlink {}", name, path_str));
+        } else {
+            warnings.push(format!("{}: missing {}", name, path_str));
+        }
+    }
+    existing_count
+}
+
+/// Sum up the diff between a tool's files and their last snapshots. Hashes
+/// are compared first, so unchanged files cost no diffing. A tool that can't
+/// be compared (e.g. encrypted snapshots without a passphrase) gets a warning
+/// and counts as clean.
+fn uncommitted_changes(
+    engine: &SnapshotEngine,
+    name: &str,
+    entry: &ToolEntry,
+    warnings: &mut Vec<String>,
+) -> Changes {
+    let diffs = match engine.diff_current(name, &entry.config_paths) {
+        Ok(diffs) => diffs,
+        Err(e) => {
+            warnings.push(format!("{}: can't compare with snapshots: {}", name, e));
+            return Changes::default();
+        }
+    };
+    let mut changes = Changes {
+        files: diffs.len(),
+        ..Changes::default()
+    };
+    for diff in diffs.iter().filter(|d| !d.binary) {
+        let (added, removed) = util::diff::line_counts(&diff.old_content, &diff.new_content);
+        changes.added += added;
+        changes.removed += removed;
+    }
+    changes
+}
+
+fn print_json(
+    config_dir: &std::path::Path,
+    manifest: &Manifest,
+    engine: &SnapshotEngine,
+    ahead: bool,
+    changed_only: bool,
+) -> Result<()> {
+    let mut warnings = Vec::new();
+    let mut tools = Vec::new();
+    for (name, entry) in &manifest.tools {
+        let changes = uncommitted_changes(engine, name, entry, &mut warnings);
+        if changed_only && changes.files == 0 {
+            continue;
+        }
+        tools.push(ToolStatus {
+            name,
+            tier: entry.tier,
+            existing_paths: check_paths(name, &entry.config_paths, &mut warnings),
+            total_paths: entry.config_paths.len(),
+            plugin_manager: entry.plugin_manager.as_deref(),
+            changed_files: changes.files,
+            lines_added: changes.added,
+            lines_removed: changes.removed,
+        });
+    }
+    let ahead = if ahead {
+        Some(files_ahead(config_dir, manifest)?)
+    } else {
+        None
+    };
+
+    let report = StatusReport {
+        tools,
+        warnings,
+        ahead,
+    };
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+fn files_ahead(config_dir: &std::path::Path, manifest: &Manifest) -> Result<Vec<repo::AheadFile>> {
+    let config = DotsmithConfig::load(config_dir);
+    let repo_path_str = config
+        .general
+        .repo_path
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("No repo path configured. Run `dotsmith repo init <path>` first."))?;
+    let repo_path = util::paths::expand_tilde(repo_path_str);
+
+    repo::files_ahead(&repo_path, manifest)
+}
+
+/// Report tracked files whose live content differs from the dotfile repo.
+fn print_ahead(config_dir: &std::path::Path, manifest: &Manifest) -> Result<()> {
+    let ahead = files_ahead(config_dir, manifest)?;
+
+    println!();
+    if ahead.is_empty() {
+        println!("{} Repo is up to date with live configs", "repo:".green().bold());
+        return Ok(());
+    }
+
+    println!(
+        "{} {} file(s) ahead of repo:",
+        "repo:".yellow().bold(),
+        ahead.len()
+    );
+    for file in &ahead {
+        let note = if file.in_repo { "modified" } else { "not in repo" };
+        println!(
+            "  {} {}: {} {}",
+            "!!".yellow(),
+            file.tool,
+            file.file_path,
+            format!("({})", note).dimmed()
+        );
+    }
+    println!("  Run {} to commit them.", "dotsmith repo sync".bold());
+
     Ok(())
 }
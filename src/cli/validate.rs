@@ -0,0 +1,112 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::core::config::DotsmithConfig;
+use crate::core::manifest::Manifest;
+use crate::core::module::ModuleRegistry;
+use crate::core::output::Printer;
+use crate::core::validate;
+use crate::util;
+
+/// Check the syntax of each tracked tool's config files (or one tool's),
+/// printing every error with the offending line. Fails if any file is
+/// invalid.
+pub fn run(out: &Printer, tool: Option<&str>) -> Result<()> {
+    let config_dir = util::paths::config_dir()?;
+    let manifest = Manifest::load(&config_dir)?;
+    let use_shellcheck = DotsmithConfig::load(&config_dir).general.use_shellcheck;
+
+    if let Some(tool) = tool
+        && !manifest.has_tool(tool)
+    {
+        anyhow::bail!("'{}' is not tracked by dotsmith", tool);
+    }
+
+    let mut checked = 0;
+    let mut invalid = 0;
+    let mut skipped = 0;
+
+    for (name, entry) in &manifest.tools {
+        if tool.is_some_and(|t| t != name) {
+            continue;
+        }
+        let module = ModuleRegistry::load_module(&config_dir, name);
+        let rules = ModuleRegistry::load_validation(&config_dir, name);
+
+        for path_str in &entry.config_paths {
+            let path = util::paths::expand_tilde(path_str);
+            let prefix = format!("{:<12} {}", name.bold(), path_str);
+
+            if path.is_dir() {
+                skipped += 1;
+                out.detail(format_args!(
+                    "  {} {}  {}",
+                    "--".dimmed(),
+                    prefix,
+                    "skipped (directory)".dimmed()
+                ));
+                continue;
+            }
+            if !path.is_file() {
+                println!("  {} {}  {}", "!!".yellow(), prefix, "missing".yellow());
+                continue;
+            }
+
+            // Tools without a module can still be checked if the file name
+            // gives away its format
+            let format = match module {
+                Some(ref module) => Some(module.metadata.config_format.as_str()),
+                None => validate::guess_format(&path),
+            };
+            let Some(format) = format else {
+                skipped += 1;
+                println!("  {} {}  {}", "--".dimmed(), prefix, "skipped (no validator)".dimmed());
+                continue;
+            };
+            let result = match validate::validate_config(
+                &path,
+                format,
+                rules.as_ref(),
+                use_shellcheck,
+            ) {
+                Ok(result) => result,
+                Err(e) => {
+                    invalid += 1;
+                    println!("  {} {}  {}", "ERR".red().bold(), prefix, e);
+                    continue;
+                }
+            };
+
+            if result.skipped {
+                skipped += 1;
+                println!("  {} {}  {}", "--".dimmed(), prefix, "skipped (no validator)".dimmed());
+                for note in &result.notes {
+                    println!("       note: {}", note);
+                }
+                continue;
+            }
+
+            checked += 1;
+            if result.valid {
+                println!("  {} {}", "OK".green().bold(), prefix);
+            } else {
+                invalid += 1;
+                println!("  {} {}", "ERR".red().bold(), prefix);
+            }
+            for line in result.render(&path).lines() {
+                println!("       {}", line);
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{} file(s) checked, {} invalid, {} skipped",
+        checked, invalid, skipped
+    );
+
+    if invalid > 0 {
+        anyhow::bail!("{} config file(s) failed validation", invalid);
+    }
+    Ok(())
+}
@@ -1,18 +1,26 @@
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::fs;
-use std::path::PathBuf;
-use std::time::{Duration, SystemTime};
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::Result;
 use chrono::Local;
 use colored::Colorize;
 use sha2::{Digest, Sha256};
 
-use crate::core::manifest::Manifest;
-use crate::core::snapshot::SnapshotEngine;
+use crate::core::config::DotsmithConfig;
+use crate::core::manifest::{Manifest, ToolEntry};
+use crate::core::output::Printer;
+use crate::core::reload;
+use crate::core::snapshot::{self, MessageContext, SnapshotEngine};
 use crate::util;
 
-const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often tracked directories are rescanned for new files.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
 
 struct FileState {
     mtime: SystemTime,
@@ -20,12 +28,116 @@ struct FileState {
     tool: String,
 }
 
-pub fn run(verbose: bool, tool: Option<&str>) -> Result<()> {
+/// Maps watched files to the tool that tracks them. When tracked paths nest
+/// (one tool tracks `~/.config`, another `~/.config/nvim`), the most
+/// specific path wins. Glob entries own the files they match.
+struct PathIndex {
+    roots: Vec<(PathBuf, String)>,
+}
+
+impl PathIndex {
+    fn new(tools: &[(&String, &ToolEntry)]) -> Self {
+        let roots = tools
+            .iter()
+            .flat_map(|(name, entry)| {
+                entry
+                    .config_paths
+                    .iter()
+                    .map(|p| (util::paths::expand_tilde(p), name.to_string()))
+            })
+            .collect();
+        Self { roots }
+    }
+
+    /// The tool whose tracked path most specifically contains `path`.
+    fn owner(&self, path: &Path) -> Option<&str> {
+        self.roots
+            .iter()
+            .filter(|(root, _)| {
+                let root_str = root.to_string_lossy();
+                if util::paths::is_glob(&root_str) {
+                    util::glob::matches(&root_str, &path.to_string_lossy())
+                } else {
+                    path.starts_with(root)
+                }
+            })
+            .max_by_key(|(root, _)| root.components().count())
+            .map(|(_, tool)| tool.as_str())
+    }
+
+    /// Every file currently under a tracked path, with its owning tool.
+    fn files(&self) -> Vec<(PathBuf, String)> {
+        let mut files: Vec<PathBuf> = self
+            .roots
+            .iter()
+            .flat_map(|(root, _)| {
+                snapshot::config_files(&[root.display().to_string()]).unwrap_or_default()
+            })
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+            .into_iter()
+            .filter_map(|path| {
+                let tool = self.owner(&path)?.to_string();
+                Some((path, tool))
+            })
+            .collect()
+    }
+}
+
+/// Coalesces bursts of events per key: a key becomes ready once `window` has
+/// passed since its last event, so an editor's several writes for one save
+/// produce a single snapshot.
+struct Debouncer<K> {
+    window: Duration,
+    pending: HashMap<K, Instant>,
+}
+
+impl<K: Eq + Hash + Clone> Debouncer<K> {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Record an event for `key` at `at`, restarting its quiet period.
+    fn touch(&mut self, key: K, at: Instant) {
+        self.pending.insert(key, at);
+    }
+
+    /// Take the keys whose quiet period has elapsed by `now`.
+    fn ready(&mut self, now: Instant) -> Vec<K> {
+        let window = self.window;
+        let done: Vec<K> = self
+            .pending
+            .iter()
+            .filter(|(_, last)| now.duration_since(**last) >= window)
+            .map(|(k, _)| k.clone())
+            .collect();
+        for k in &done {
+            self.pending.remove(k);
+        }
+        done
+    }
+}
+
+pub fn run(
+    out: &Printer,
+    tool: Option<&str>,
+    once: bool,
+    debounce_ms: Option<u64>,
+    reload: bool,
+) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let manifest = Manifest::load(&config_dir)?;
+    let config = DotsmithConfig::load(&config_dir);
+    let debounce = Duration::from_millis(debounce_ms.unwrap_or(config.general.watch_debounce_ms));
+    let reload = reload || config.general.watch_reload;
 
     // Collect files to watch
-    let tools_to_watch: Vec<(&String, &crate::core::manifest::ToolEntry)> = match tool {
+    let tools_to_watch: Vec<(&String, &ToolEntry)> = match tool {
         Some(name) => {
             manifest
                 .tools
@@ -36,92 +148,126 @@ pub fn run(verbose: bool, tool: Option<&str>) -> Result<()> {
         None => manifest.tools.iter().collect(),
     };
 
+    if once {
+        return run_once(out, &config_dir, &config.general.snapshot_message, &tools_to_watch);
+    }
+
     // Build initial file state
-    let mut state = build_file_state(&tools_to_watch);
+    let index = PathIndex::new(&tools_to_watch);
+    let mut state = build_file_state(&index);
 
     let file_count = state.len();
     let tool_count = tools_to_watch.len();
 
     if file_count == 0 {
-        println!("No config files to watch.");
+        out.info("No config files to watch.");
         return Ok(());
     }
 
-    println!(
+    out.info(format_args!(
         "  Watching {} file(s) for {} tool(s)... (Ctrl-C to stop)",
         file_count.to_string().cyan(),
         tool_count.to_string().cyan()
-    );
+    ));
 
-    if verbose {
-        for (path, fs) in &state {
-            println!("    {} [{}]", util::paths::contract_tilde(path), fs.tool);
-        }
+    for (path, fs) in &state {
+        out.detail(format_args!("    {} [{}]", util::paths::contract_tilde(path), fs.tool));
     }
 
-    println!();
+    out.info("");
 
     let snapshot_engine = SnapshotEngine::open(&config_dir)?;
+    let mut debouncer = Debouncer::new(debounce);
+    let mut last_scan = Instant::now();
 
     loop {
         std::thread::sleep(POLL_INTERVAL);
 
         let mut changed_tools: HashMap<String, Vec<String>> = HashMap::new();
 
+        // Pick up files created inside tracked directories. They start with
+        // no hash, so they count as changed once they settle.
+        if last_scan.elapsed() >= RESCAN_INTERVAL {
+            last_scan = Instant::now();
+            for (path, tool) in index.files() {
+                if let Entry::Vacant(slot) = state.entry(path.clone()) {
+                    debouncer.touch(path, Instant::now());
+                    slot.insert(FileState {
+                        mtime: SystemTime::UNIX_EPOCH,
+                        hash: String::new(),
+                        tool,
+                    });
+                }
+            }
+        }
+
+        // Every write restarts the file's quiet period
         for (path, file_state) in state.iter_mut() {
-            let Ok(metadata) = fs::metadata(path) else {
-                continue;
-            };
-            let Ok(mtime) = metadata.modified() else {
+            let Ok(mtime) = fs::metadata(path).and_then(|m| m.modified()) else {
                 continue;
             };
+            if mtime != file_state.mtime {
+                file_state.mtime = mtime;
+                debouncer.touch(path.clone(), Instant::now());
+            }
+        }
 
-            if mtime == file_state.mtime {
+        for path in debouncer.ready(Instant::now()) {
+            let Some(file_state) = state.get_mut(&path) else {
                 continue;
-            }
+            };
 
-            // mtime changed — check hash
-            let new_hash = hash_file(path);
+            // Only act on real content changes
+            let new_hash = hash_file(&path);
             if new_hash == file_state.hash {
-                file_state.mtime = mtime;
                 continue;
             }
 
             // File actually changed
             let now = Local::now().format("%H:%M:%S");
-            let display_path = util::paths::contract_tilde(path);
-            println!(
-                "  {} {} {} changed",
+            let display_path = util::paths::contract_tilde(&path);
+            let event = if file_state.hash.is_empty() { "added" } else { "changed" };
+            out.info(format_args!(
+                "  {} {} {} {}",
                 format!("[{}]", now).dimmed(),
                 file_state.tool.cyan(),
-                display_path
-            );
+                display_path,
+                event
+            ));
 
             changed_tools
                 .entry(file_state.tool.clone())
                 .or_default()
                 .push(display_path);
 
-            file_state.mtime = mtime;
             file_state.hash = new_hash;
         }
 
         // Snapshot changed tools
         for tool_name in changed_tools.keys() {
             if let Some(entry) = manifest.tools.get(tool_name.as_str()) {
+                let message = snapshot::format_message(
+                    &config.general.snapshot_message,
+                    &MessageContext::now(tool_name, "watch"),
+                );
                 match snapshot_engine.snapshot_tool(
                     tool_name,
                     &entry.config_paths,
-                    Some("auto-snapshot (watch)"),
+                    Some(&message),
                 ) {
                     Ok(count) => {
                         let now = Local::now().format("%H:%M:%S");
-                        println!(
-                            "  {} {} snapshotted {} file(s)",
+                        out.info(format_args!(
+                            "  {} {} {} snapshotted {} file(s)",
                             format!("[{}]", now).dimmed(),
                             "OK".green().bold(),
+                            tool_name.cyan(),
                             count
-                        );
+                        ));
+                        warn_secrets(&snapshot_engine);
+                        if reload {
+                            reload_after_snapshot(out, &config_dir, tool_name, &entry.config_paths);
+                        }
                     }
                     Err(e) => {
                         eprintln!("  {} snapshot failed for {}: {}", "!!".yellow(), tool_name, e);
@@ -132,39 +278,105 @@ pub fn run(verbose: bool, tool: Option<&str>) -> Result<()> {
     }
 }
 
-fn build_file_state(
-    tools: &[(&String, &crate::core::manifest::ToolEntry)],
-) -> HashMap<PathBuf, FileState> {
-    let mut state = HashMap::new();
+/// Log files the snapshot held back because they look like they hold secrets.
+fn warn_secrets(engine: &SnapshotEngine) {
+    for (path, hits) in engine.take_secret_hits() {
+        let kinds: Vec<&str> = hits.iter().map(|hit| hit.kind.as_str()).collect();
+        eprintln!(
+            "  {} skipped {}: may contain a secret ({})",
+            "!!".yellow(),
+            path,
+            kinds.join(", ")
+        );
+    }
+}
+
+/// Reload a tool after its changes were snapshotted, reporting the outcome
+/// without stopping the watch.
+fn reload_after_snapshot(
+    out: &Printer,
+    config_dir: &Path,
+    tool: &str,
+    config_paths: &[String],
+) {
+    let now = Local::now().format("%H:%M:%S");
+    let config_path = config_paths.first().map(|s| s.as_str());
+    match reload::reload_tool(config_dir, tool, config_path) {
+        Ok(description) => out.info(format_args!(
+            "  {} {} {}: {}",
+            format!("[{}]", now).dimmed(),
+            "OK".green().bold(),
+            tool.bold(),
+            description
+        )),
+        Err(e) => eprintln!("  {} reload failed for {}: {}", "!!".yellow(), tool, e),
+    }
+}
+
+/// One-shot mode: snapshot every file that differs from its last snapshot, then exit.
+fn run_once(
+    out: &Printer,
+    config_dir: &Path,
+    template: &str,
+    tools: &[(&String, &ToolEntry)],
+) -> Result<()> {
+    let engine = SnapshotEngine::open(config_dir)?;
+    let mut total = 0;
+    let mut tool_count = 0;
 
     for (tool_name, entry) in tools {
-        for path_str in &entry.config_paths {
-            let path = util::paths::expand_tilde(path_str);
-            if !path.is_file() {
-                continue;
-            }
+        let diffs = engine.diff_current(tool_name, &entry.config_paths)?;
+        if diffs.is_empty() {
+            continue;
+        }
 
-            let mtime = fs::metadata(&path)
-                .and_then(|m| m.modified())
-                .unwrap_or(SystemTime::UNIX_EPOCH);
+        let now = Local::now().format("%H:%M:%S");
+        for diff in &diffs {
+            out.info(format_args!(
+                "  {} {} {} changed",
+                format!("[{}]", now).dimmed(),
+                tool_name.cyan(),
+                diff.file_path
+            ));
+        }
 
-            let hash = hash_file(&path);
+        let message =
+            snapshot::format_message(template, &MessageContext::now(tool_name, "watch --once"));
+        let count = engine.snapshot_tool(tool_name, &entry.config_paths, Some(&message))?;
+        warn_secrets(&engine);
+        total += count;
+        tool_count += 1;
+    }
 
-            state.insert(
-                path,
-                FileState {
-                    mtime,
-                    hash,
-                    tool: tool_name.to_string(),
-                },
-            );
-        }
+    if total > 0 {
+        out.info(format_args!(
+            "{} Snapshotted {} file(s) across {} tool(s)",
+            "OK".green().bold(),
+            total,
+            tool_count
+        ));
+    } else {
+        out.info("No changes since last snapshot");
     }
 
-    state
+    Ok(())
 }
 
-fn hash_file(path: &std::path::Path) -> String {
+fn build_file_state(index: &PathIndex) -> HashMap<PathBuf, FileState> {
+    index
+        .files()
+        .into_iter()
+        .map(|(path, tool)| {
+            let mtime = fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            let hash = hash_file(&path);
+            (path, FileState { mtime, hash, tool })
+        })
+        .collect()
+}
+
+fn hash_file(path: &Path) -> String {
     match fs::read(path) {
         Ok(content) => {
             let mut hasher = Sha256::new();
@@ -181,8 +393,8 @@ mod tests {
     use std::collections::BTreeMap;
     use tempfile::TempDir;
 
-    fn sample_entry(paths: Vec<String>) -> crate::core::manifest::ToolEntry {
-        crate::core::manifest::ToolEntry {
+    fn sample_entry(paths: Vec<String>) -> ToolEntry {
+        ToolEntry {
             tier: 1,
             config_paths: paths,
             plugins_managed: false,
@@ -201,9 +413,9 @@ mod tests {
 
         let tool_name = "test-tool".to_string();
         let entry = sample_entry(vec![file.to_string_lossy().to_string()]);
-        let tools: Vec<(&String, &crate::core::manifest::ToolEntry)> = vec![(&tool_name, &entry)];
+        let tools: Vec<(&String, &ToolEntry)> = vec![(&tool_name, &entry)];
 
-        let state = build_file_state(&tools);
+        let state = build_file_state(&PathIndex::new(&tools));
         assert_eq!(state.len(), 1);
         assert!(state.contains_key(&file));
         assert_eq!(state[&file].tool, "test-tool");
@@ -211,17 +423,76 @@ mod tests {
     }
 
     #[test]
-    fn test_build_file_state_skips_dirs() {
+    fn test_build_file_state_walks_dirs() {
         let tmp = TempDir::new().unwrap();
         let dir = tmp.path().join("subdir");
-        std::fs::create_dir(&dir).unwrap();
+        std::fs::create_dir_all(dir.join("lua")).unwrap();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join("lua/init.lua"), "x").unwrap();
+        std::fs::write(dir.join(".git/HEAD"), "x").unwrap();
 
         let tool_name = "test-tool".to_string();
         let entry = sample_entry(vec![dir.to_string_lossy().to_string()]);
-        let tools: Vec<(&String, &crate::core::manifest::ToolEntry)> = vec![(&tool_name, &entry)];
+        let tools: Vec<(&String, &ToolEntry)> = vec![(&tool_name, &entry)];
+
+        let state = build_file_state(&PathIndex::new(&tools));
+        assert_eq!(state.keys().collect::<Vec<_>>(), [&dir.join("lua/init.lua")]);
+    }
 
-        let state = build_file_state(&tools);
-        assert!(state.is_empty());
+    #[test]
+    fn test_path_index_picks_most_specific_tool() {
+        let tmp = TempDir::new().unwrap();
+        let config = tmp.path().join(".config");
+        let nvim = config.join("nvim");
+        std::fs::create_dir_all(nvim.join("lua")).unwrap();
+        std::fs::write(config.join("user-dirs.dirs"), "x").unwrap();
+        std::fs::write(nvim.join("init.lua"), "x").unwrap();
+        std::fs::write(nvim.join("lua/plugins.lua"), "x").unwrap();
+
+        let (dotconfig, neovim, lsp) = ("dotconfig".to_string(), "nvim".to_string(), "lsp".into());
+        let dotconfig_entry = sample_entry(vec![config.to_string_lossy().to_string()]);
+        let nvim_entry = sample_entry(vec![nvim.to_string_lossy().to_string()]);
+        let lsp_entry = sample_entry(vec![nvim.join("lua/plugins.lua").to_string_lossy().into()]);
+        // Listed outermost last, so order doesn't decide
+        let tools: Vec<(&String, &ToolEntry)> =
+            vec![(&lsp, &lsp_entry), (&neovim, &nvim_entry), (&dotconfig, &dotconfig_entry)];
+        let index = PathIndex::new(&tools);
+
+        assert_eq!(index.owner(&config.join("user-dirs.dirs")), Some("dotconfig"));
+        assert_eq!(index.owner(&nvim.join("init.lua")), Some("nvim"));
+        assert_eq!(index.owner(&nvim.join("lua/plugins.lua")), Some("lsp"));
+        assert_eq!(index.owner(&nvim.join("lua/new.lua")), Some("nvim"));
+        assert_eq!(index.owner(&tmp.path().join("elsewhere")), None);
+        // A sibling that merely shares a name prefix isn't inside
+        assert_eq!(index.owner(&tmp.path().join(".config-old/x")), None);
+
+        // Each file is listed once, under its owner
+        let files = index.files();
+        assert_eq!(files.len(), 3);
+        assert!(files.contains(&(nvim.join("init.lua"), "nvim".to_string())));
+        assert!(files.contains(&(nvim.join("lua/plugins.lua"), "lsp".to_string())));
+    }
+
+    #[test]
+    fn test_path_index_owns_glob_matches() {
+        let tmp = TempDir::new().unwrap();
+        let lua = tmp.path().join("nvim/lua");
+        std::fs::create_dir_all(&lua).unwrap();
+        std::fs::write(lua.join("options.lua"), "x").unwrap();
+        std::fs::write(lua.join("README.md"), "x").unwrap();
+
+        let neovim = "nvim".to_string();
+        let entry = sample_entry(vec![format!("{}/*.lua", lua.display())]);
+        let tools: Vec<(&String, &ToolEntry)> = vec![(&neovim, &entry)];
+        let index = PathIndex::new(&tools);
+
+        assert_eq!(index.owner(&lua.join("options.lua")), Some("nvim"));
+        assert_eq!(index.owner(&lua.join("README.md")), None);
+        assert_eq!(index.files(), [(lua.join("options.lua"), neovim.clone())]);
+
+        // Files created later are picked up on the next scan
+        std::fs::write(lua.join("keymaps.lua"), "x").unwrap();
+        assert_eq!(index.files().len(), 2);
     }
 
     #[test]
@@ -237,6 +508,36 @@ mod tests {
         assert_ne!(hash_before, hash_after);
     }
 
+    #[test]
+    fn test_debounce_coalesces_burst() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut debouncer = Debouncer::new(Duration::from_millis(500));
+
+        // An editor writing the same file four times for one save
+        for t in [0, 40, 120, 300] {
+            debouncer.touch("tmux.conf", ms(t));
+            assert!(debouncer.ready(ms(t)).is_empty());
+        }
+
+        // Quiet period counts from the last write, not the first
+        assert!(debouncer.ready(ms(700)).is_empty());
+        assert_eq!(debouncer.ready(ms(800)), vec!["tmux.conf"]);
+        assert!(debouncer.ready(ms(2000)).is_empty());
+    }
+
+    #[test]
+    fn test_debounce_tracks_files_separately() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut debouncer = Debouncer::new(Duration::from_millis(500));
+
+        debouncer.touch("a", ms(0));
+        debouncer.touch("b", ms(400));
+        assert_eq!(debouncer.ready(ms(500)), vec!["a"]);
+        assert_eq!(debouncer.ready(ms(900)), vec!["b"]);
+    }
+
     #[test]
     fn test_no_change() {
         let tmp = TempDir::new().unwrap();
@@ -0,0 +1,542 @@
+use crate::core::module::OptionEntry;
+use crate::util::fuzzy::edit_distance;
+
+/// An option assignment found in a config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetOption {
+    pub name: String,
+    pub value: String,
+    /// 1-based line number.
+    pub line: usize,
+}
+
+/// An option set to something other than its catalog default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Customization {
+    /// Catalog spelling of the option name.
+    pub name: String,
+    pub value: String,
+    pub default: Option<String>,
+    pub line: usize,
+}
+
+/// An option name that isn't in the catalog.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownOption {
+    pub name: String,
+    pub line: usize,
+    /// A catalog name within a couple of edits, when there is one — most
+    /// likely what was meant.
+    pub suggestion: Option<String>,
+}
+
+/// A catalog option that's set but marked deprecated or replaced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeprecatedOption {
+    /// Catalog spelling of the option name.
+    pub name: String,
+    pub line: usize,
+    /// The catalog's `deprecated` note, e.g. the version that dropped it.
+    pub deprecated: Option<String>,
+    pub replaced_by: Option<String>,
+}
+
+/// Result of comparing one config file against a tool's option catalog.
+#[derive(Debug, Default)]
+pub struct AuditReport {
+    pub customized: Vec<Customization>,
+    /// Options set explicitly to their default value.
+    pub redundant: Vec<Customization>,
+    pub unknown: Vec<UnknownOption>,
+    /// Set options the catalog marks as deprecated or replaced. These are
+    /// not counted as customized or redundant.
+    pub deprecated: Vec<DeprecatedOption>,
+}
+
+impl AuditReport {
+    /// Unknown options that look like misspellings of catalog options.
+    pub fn likely_typos(&self) -> impl Iterator<Item = &UnknownOption> {
+        self.unknown.iter().filter(|u| u.suggestion.is_some())
+    }
+}
+
+/// Compare the options set in `content` with the catalog for a tool.
+///
+/// Catalogs only cover the most useful options, so an unknown name on its
+/// own isn't an error; it's reported with a suggestion when it sits within
+/// two edits of a catalog name.
+pub fn audit_config(content: &str, format: &str, catalog: &[OptionEntry]) -> AuditReport {
+    let mut report = AuditReport::default();
+
+    for set in extract_set_options(content, format) {
+        match lookup(format, &set.name, catalog) {
+            Some((entry, _)) if entry.deprecated.is_some() || entry.replaced_by.is_some() => {
+                report.deprecated.push(DeprecatedOption {
+                    name: entry.name.clone(),
+                    line: set.line,
+                    deprecated: entry.deprecated.clone(),
+                    replaced_by: entry.replaced_by.clone(),
+                });
+            }
+            Some((entry, negated)) => {
+                let value = if negated { flip(&set.value) } else { set.value.clone() };
+                let item = Customization {
+                    name: entry.name.clone(),
+                    value,
+                    default: entry.default.clone(),
+                    line: set.line,
+                };
+                let is_default = entry
+                    .default
+                    .as_deref()
+                    .is_some_and(|d| normalize_value(d) == normalize_value(&item.value));
+                if is_default {
+                    report.redundant.push(item);
+                } else {
+                    report.customized.push(item);
+                }
+            }
+            None if is_user_namespace(format, &set.name) => {}
+            None => report.unknown.push(UnknownOption {
+                suggestion: suggest(format, &set.name, catalog),
+                name: set.name,
+                line: set.line,
+            }),
+        }
+    }
+
+    report
+}
+
+/// Catalog options that none of `set` assigns, in catalog order: the
+/// documented options a config doesn't use yet. Plugin options (`plugin:*`
+/// categories) and deprecated ones are left out.
+pub fn unset_options<'a>(
+    format: &str,
+    set: &[SetOption],
+    catalog: &'a [OptionEntry],
+) -> Vec<&'a OptionEntry> {
+    let used: Vec<&str> = set
+        .iter()
+        .filter_map(|s| lookup(format, &s.name, catalog))
+        .map(|(entry, _)| entry.name.as_str())
+        .collect();
+
+    catalog
+        .iter()
+        .filter(|e| !e.category.starts_with("plugin:"))
+        .filter(|e| e.deprecated.is_none() && e.replaced_by.is_none())
+        .filter(|e| !used.contains(&e.name.as_str()))
+        .collect()
+}
+
+/// Extract option assignments for a config format. Formats that can't be
+/// read line by line (arbitrary shell or Lua) only yield the assignments
+/// dotsmith recognizes: `setopt`/`unsetopt`/`NAME=value` for shell and
+/// `vim.opt.name = value`/`beautiful.name = value` for Lua.
+pub fn extract_set_options(content: &str, format: &str) -> Vec<SetOption> {
+    match format {
+        "tmux" => extract_tmux(content),
+        "git" => extract_git(content),
+        "key-value" => extract_key_value(content),
+        "toml" => extract_toml(content),
+        "shell" => extract_shell(content),
+        "lua" => extract_lua(content),
+        _ => Vec::new(),
+    }
+}
+
+fn extract_tmux(content: &str) -> Vec<SetOption> {
+    let set_commands = ["set", "set-option", "setw", "set-window-option"];
+    let mut found = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        if !words.next().is_some_and(|w| set_commands.contains(&w)) {
+            continue;
+        }
+        let mut words = words.skip_while(|w| w.starts_with('-'));
+        if let Some(name) = words.next() {
+            let value = words.collect::<Vec<_>>().join(" ");
+            found.push(SetOption {
+                name: name.to_string(),
+                value: strip_comment(&value, '#').to_string(),
+                line: i + 1,
+            });
+        }
+    }
+
+    found
+}
+
+fn extract_git(content: &str) -> Vec<SetOption> {
+    let mut found = Vec::new();
+    // None inside subsections like [remote "origin"], which have no catalog entries
+    let mut section: Option<String> = None;
+
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+        if let Some(header) = trimmed.strip_prefix('[') {
+            let header = header.split(']').next().unwrap_or("").trim();
+            section = (!header.contains(char::is_whitespace) && !header.contains('.'))
+                .then(|| header.to_string());
+            continue;
+        }
+        let Some(section) = &section else {
+            continue;
+        };
+        let (key, value) = trimmed.split_once('=').unwrap_or((trimmed, "true"));
+        found.push(SetOption {
+            name: format!("{}.{}", section, key.trim()),
+            value: strip_comment(strip_comment(value.trim(), '#'), ';').to_string(),
+            line: i + 1,
+        });
+    }
+
+    found
+}
+
+fn extract_key_value(content: &str) -> Vec<SetOption> {
+    let mut found = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let (name, value) = trimmed
+            .split_once(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or((trimmed, ""));
+        found.push(SetOption {
+            name: name.to_string(),
+            value: value.trim_start_matches([' ', '\t', '=']).trim().to_string(),
+            line: i + 1,
+        });
+    }
+
+    found
+}
+
+fn extract_toml(content: &str) -> Vec<SetOption> {
+    let Ok(value) = content.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+
+    let mut flat = Vec::new();
+    flatten_toml("", &value, &mut flat);
+
+    flat.into_iter()
+        .map(|(name, value)| {
+            let key = name.rsplit('.').next().unwrap_or(&name).to_string();
+            SetOption {
+                line: find_key_line(content, &key),
+                name,
+                value,
+            }
+        })
+        .collect()
+}
+
+fn flatten_toml(prefix: &str, table: &toml::Table, out: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let name = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            toml::Value::Table(inner) => flatten_toml(&name, inner, out),
+            toml::Value::String(s) => out.push((name, s.clone())),
+            other => out.push((name, other.to_string())),
+        }
+    }
+}
+
+/// Best-effort line number for a TOML key (parsing loses positions).
+fn find_key_line(content: &str, key: &str) -> usize {
+    content
+        .lines()
+        .position(|l| {
+            l.trim_start()
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        })
+        .map_or(0, |i| i + 1)
+}
+
+fn extract_shell(content: &str) -> Vec<SetOption> {
+    let mut found = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        let mut words = trimmed.split_whitespace();
+        match words.next() {
+            Some(cmd @ ("setopt" | "unsetopt")) => {
+                let value = if cmd == "setopt" { "on" } else { "off" };
+                for name in words.take_while(|w| !w.starts_with('#')) {
+                    found.push(SetOption {
+                        name: name.to_string(),
+                        value: value.to_string(),
+                        line: i + 1,
+                    });
+                }
+            }
+            Some(_) => {
+                let assignment = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+                if let Some((name, value)) = assignment.split_once('=')
+                    && is_shell_variable(name)
+                {
+                    found.push(SetOption {
+                        name: name.to_string(),
+                        value: value.trim().to_string(),
+                        line: i + 1,
+                    });
+                }
+            }
+            None => {}
+        }
+    }
+
+    found
+}
+
+/// Upper-case names only: lower-case assignments are usually script locals.
+fn is_shell_variable(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn extract_lua(content: &str) -> Vec<SetOption> {
+    let prefixes = ["vim.opt.", "vim.o.", "vim.wo.", "vim.bo.", "vim.go.", "beautiful."];
+    let mut found = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        let Some(prefix) = prefixes.iter().find(|p| trimmed.starts_with(**p)) else {
+            continue;
+        };
+        let Some((lhs, value)) = trimmed.split_once('=') else {
+            continue;
+        };
+        let lhs = lhs.trim();
+        if value.starts_with('=') || lhs.contains(|c: char| c.is_whitespace() || c == '(') {
+            continue;
+        }
+        // neovim's catalog uses bare option names; awesomewm keeps the `beautiful.` prefix
+        let name = if *prefix == "beautiful." { lhs } else { &lhs[prefix.len()..] };
+        found.push(SetOption {
+            name: name.to_string(),
+            value: strip_comment(value.trim(), '-').trim_end_matches(',').trim().to_string(),
+            line: i + 1,
+        });
+    }
+
+    found
+}
+
+fn strip_comment(value: &str, marker: char) -> &str {
+    match value.find(&format!(" {}", marker)) {
+        Some(i) => value[..i].trim_end(),
+        None => value,
+    }
+}
+
+/// Find the catalog entry for a name. The flag is true when a zsh option
+/// matched through its `NO_` form, meaning the value must be inverted.
+fn lookup<'a>(
+    format: &str,
+    name: &str,
+    catalog: &'a [OptionEntry],
+) -> Option<(&'a OptionEntry, bool)> {
+    let key = normalize_name(format, name);
+    let find = |key: &str| catalog.iter().find(|e| normalize_name(format, &e.name) == key);
+
+    if let Some(entry) = find(&key) {
+        return Some((entry, false));
+    }
+    if format == "shell" {
+        // zsh treats NO_FOO as the inverse of FOO
+        let negated = match key.strip_prefix("NO") {
+            Some(rest) => find(rest),
+            None => find(&format!("NO{}", key)),
+        };
+        return negated.map(|entry| (entry, true));
+    }
+    None
+}
+
+/// Names compare the way the tool reads them: git keys are case-insensitive,
+/// and zsh ignores case and underscores in option names.
+fn normalize_name(format: &str, name: &str) -> String {
+    match format {
+        "git" => name.to_ascii_lowercase(),
+        "shell" => name.replace('_', "").to_ascii_uppercase(),
+        _ => name.to_string(),
+    }
+}
+
+fn normalize_value(value: &str) -> String {
+    value.trim().trim_matches(['"', '\'']).to_ascii_lowercase()
+}
+
+fn flip(value: &str) -> String {
+    match value {
+        "on" => "off".to_string(),
+        "off" => "on".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Names users define themselves (git aliases, tmux `@` user options),
+/// which a catalog can't be expected to list.
+fn is_user_namespace(format: &str, name: &str) -> bool {
+    match format {
+        "git" => name.to_ascii_lowercase().starts_with("alias."),
+        "tmux" => name.starts_with('@'),
+        _ => false,
+    }
+}
+
+/// Closest catalog name within two edits, ignoring very short names where
+/// everything is "close".
+fn suggest(format: &str, name: &str, catalog: &[OptionEntry]) -> Option<String> {
+    if name.len() < 4 {
+        return None;
+    }
+    let key = normalize_name(format, name).to_lowercase();
+
+    catalog
+        .iter()
+        .map(|e| (edit_distance(&key, &normalize_name(format, &e.name).to_lowercase()), e))
+        .filter(|(d, _)| *d <= 2)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, e)| e.name.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::module::ModuleRegistry;
+
+    fn catalog(tool: &str) -> Vec<OptionEntry> {
+        ModuleRegistry::get_options(tool).unwrap().options
+    }
+
+    #[test]
+    fn test_tmux_customizations_and_typos() {
+        let content = "set -g mouse on\nset -g base-index 0\nset -g mouze on\n\
+                       set -g @resurrect-dir '~/x'\nset -g totally-custom-thing 1\n";
+        let report = audit_config(content, "tmux", &catalog("tmux"));
+
+        // @resurrect-dir is a catalogued plugin option
+        assert_eq!(report.customized.len(), 2);
+        assert_eq!(report.customized[0].name, "mouse");
+        assert_eq!(report.customized[0].default.as_deref(), Some("off"));
+        assert_eq!(report.redundant.len(), 1);
+
+        let typos: Vec<_> = report.likely_typos().collect();
+        assert_eq!(typos.len(), 1);
+        assert_eq!(typos[0].name, "mouze");
+        assert_eq!(typos[0].line, 3);
+        assert_eq!(typos[0].suggestion.as_deref(), Some("mouse"));
+        // Not close to anything: unknown, but not a typo
+        assert_eq!(report.unknown.len(), 2);
+    }
+
+    #[test]
+    fn test_git_sections_and_case() {
+        let content = "[user]\n\tname = A\n[pull]\n\trebase = true\n\
+                       [push]\n\tautosetupremote\n[remote \"origin\"]\n\turl = x\n\
+                       [alias]\n\tzz = status\n[core]\n\teditr = vim\n";
+        let report = audit_config(content, "git", &catalog("git"));
+
+        let names: Vec<&str> = report.customized.iter().map(|c| c.name.as_str()).collect();
+        assert!(names.contains(&"user.name"));
+        assert!(names.contains(&"pull.rebase"));
+        assert!(names.contains(&"push.autoSetupRemote"));
+        assert_eq!(report.unknown.len(), 1);
+        assert_eq!(report.unknown[0].suggestion.as_deref(), Some("core.editor"));
+    }
+
+    #[test]
+    fn test_zsh_setopt_negation() {
+        let content = "setopt autocd\nunsetopt beep\nHISTSIZE=30\nlocal_var=1\n";
+        let report = audit_config(content, "shell", &catalog("zsh"));
+
+        let names: Vec<&str> = report.customized.iter().map(|c| c.name.as_str()).collect();
+        assert!(names.contains(&"AUTO_CD"));
+        // unsetopt beep == setopt NO_BEEP
+        let no_beep = report.customized.iter().find(|c| c.name == "NO_BEEP").unwrap();
+        assert_eq!(no_beep.value, "on");
+        assert_eq!(report.redundant.len(), 1); // HISTSIZE=30 is the default
+        assert!(report.unknown.is_empty());
+    }
+
+    #[test]
+    fn test_lua_and_toml_extraction() {
+        let lua =
+            extract_lua("vim.opt.number = true -- show\nvim.o.signcolumn = 'yes'\nlocal x = 1\n");
+        assert_eq!(lua[0].name, "number");
+        assert_eq!(lua[0].value, "true");
+        assert_eq!(lua[1].name, "signcolumn");
+
+        let toml = extract_toml("[window]\nopacity = 0.9\n\n[window.padding]\nx = 4\n");
+        assert!(toml.iter().any(|o| o.name == "window.opacity" && o.value == "0.9" && o.line == 2));
+        assert!(toml.iter().any(|o| o.name == "window.padding.x" && o.line == 5));
+    }
+
+    #[test]
+    fn test_unset_options_tmux() {
+        let catalog = catalog("tmux");
+        let set = extract_set_options("set -g mouse on\nsetw -g mode-keys vi\n", "tmux");
+        let names: Vec<&str> = set.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["mouse", "mode-keys"]);
+
+        let unset = unset_options("tmux", &set, &catalog);
+        assert!(!unset.iter().any(|e| e.name == "mouse" || e.name == "mode-keys"));
+        assert!(unset.iter().any(|e| e.name == "escape-time"));
+        assert!(unset.iter().all(|e| !e.category.starts_with("plugin:")));
+        let core = catalog.iter().filter(|e| !e.category.starts_with("plugin:")).count();
+        assert_eq!(unset.len(), core - 2);
+    }
+
+    #[test]
+    fn test_unset_options_git() {
+        let catalog = catalog("git");
+        let content = "[Pull]\n\tRebase = true\n[init]\n\tdefaultBranch = main\n";
+        let set = extract_set_options(content, "git");
+        let names: Vec<&str> = set.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Pull.Rebase", "init.defaultBranch"]);
+
+        // Git keys match case-insensitively
+        let unset = unset_options("git", &set, &catalog);
+        assert!(!unset.iter().any(|e| e.name == "pull.rebase" || e.name == "init.defaultBranch"));
+        assert!(unset.iter().any(|e| e.name == "commit.gpgsign"));
+        assert_eq!(unset.len(), catalog.len() - 2);
+    }
+
+    #[test]
+    fn test_deprecated_options_flagged() {
+        let mut catalog = catalog("tmux");
+        let entry = catalog.iter_mut().find(|e| e.name == "mouse").unwrap();
+        entry.deprecated = Some("2.1".to_string());
+        entry.replaced_by = Some("mouse-mode".to_string());
+
+        let report = audit_config("set -g mouse on\n", "tmux", &catalog);
+        assert!(report.customized.is_empty());
+        assert_eq!(
+            report.deprecated,
+            [DeprecatedOption {
+                name: "mouse".to_string(),
+                line: 1,
+                deprecated: Some("2.1".to_string()),
+                replaced_by: Some("mouse-mode".to_string()),
+            }]
+        );
+        // Deprecated options aren't suggested as ones to start using
+        assert!(!unset_options("tmux", &[], &catalog).iter().any(|e| e.name == "mouse"));
+    }
+}
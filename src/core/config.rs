@@ -1,8 +1,10 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+use crate::core::errors::DotsmithError;
 use crate::util;
 
 /// Dotsmith's own configuration, stored at <config_dir>/config.toml
@@ -10,6 +12,30 @@ use crate::util;
 pub struct DotsmithConfig {
     #[serde(default)]
     pub general: GeneralConfig,
+
+    /// Saved explorer filters, keyed by view name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub views: BTreeMap<String, SavedView>,
+
+    /// Per-host settings for remote deploy and pull, keyed by the host name
+    /// as given on the command line.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hosts: BTreeMap<String, HostConfig>,
+
+    /// Editor commands for `dotsmith edit`, keyed by tool name or config
+    /// format (`lua`, `json`, ...); a tool's entry wins over its format's.
+    /// `{path}` stands for the files to open.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub editors: BTreeMap<String, String>,
+
+    /// Per-tool settings, keyed by tool name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, ToolConfig>,
+
+    /// TUI key bindings, keyed by action name; replaces that action's
+    /// default keys in every view.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keymap: BTreeMap<String, KeyList>,
 }
 
 impl DotsmithConfig {
@@ -39,6 +65,60 @@ pub struct GeneralConfig {
     /// Path to the git repo for dotfile backups.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub repo_path: Option<String>,
+
+    /// Editor for `dotsmith edit`; overrides $EDITOR and $VISUAL when set.
+    /// Entries in `[editors]` take precedence over it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+
+    /// Before `dotsmith reload` on a terminal, show changes made since the
+    /// last snapshot and offer to snapshot them.
+    #[serde(default = "default_true")]
+    pub confirm_reload: bool,
+
+    /// Ask before destructive TUI actions: removing a plugin and rolling
+    /// back a snapshot.
+    #[serde(default = "default_true")]
+    pub confirm_destructive: bool,
+
+    /// Push to the repo's remote after each `repo sync` commit.
+    #[serde(default)]
+    pub repo_auto_push: bool,
+
+    /// How long `dotsmith watch` waits after a file's last write before
+    /// snapshotting it, in milliseconds.
+    #[serde(default = "default_watch_debounce_ms")]
+    pub watch_debounce_ms: u64,
+
+    /// Reload a tool after `dotsmith watch` snapshots it.
+    #[serde(default)]
+    pub watch_reload: bool,
+
+    /// Message for snapshots taken by `edit`, `watch` and the TUI. `{tool}`,
+    /// `{time}`, `{source}` and `{host}` are filled in.
+    #[serde(default = "default_snapshot_message")]
+    pub snapshot_message: String,
+
+    /// Check shell configs with shellcheck (when installed) in `validate`
+    /// and `doctor`.
+    #[serde(default)]
+    pub use_shellcheck: bool,
+
+    /// Encrypt new snapshots and saved profiles with a passphrase, taken from
+    /// `DOTSMITH_PASSPHRASE` or asked for on the terminal.
+    #[serde(default)]
+    pub encrypt: bool,
+
+    /// Extra globs for files that should be mode 0600, on top of
+    /// `~/.ssh/**`, `~/.gnupg/**` and `~/.netrc`. Checked by `dotsmith doctor`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitive_paths: Vec<String>,
+
+    /// Extra regexes for secrets, on top of the built-in AWS key, private
+    /// key, token and password patterns. Checked before `snapshot` and
+    /// `repo sync` store a file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secret_patterns: Vec<String>,
 }
 
 impl Default for GeneralConfig {
@@ -46,6 +126,17 @@ impl Default for GeneralConfig {
         Self {
             configs_dir: default_configs_dir(),
             repo_path: None,
+            repo_auto_push: false,
+            editor: None,
+            confirm_reload: true,
+            confirm_destructive: true,
+            watch_debounce_ms: default_watch_debounce_ms(),
+            watch_reload: false,
+            snapshot_message: default_snapshot_message(),
+            use_shellcheck: false,
+            encrypt: false,
+            sensitive_paths: Vec::new(),
+            secret_patterns: Vec::new(),
         }
     }
 }
@@ -54,6 +145,91 @@ fn default_configs_dir() -> String {
     "~/.config/dotsmith/configs".to_string()
 }
 
+fn default_watch_debounce_ms() -> u64 {
+    500
+}
+
+fn default_snapshot_message() -> String {
+    "{source} @ {time}".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// An explorer filter saved under a name, stored as `[views.<name>]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedView {
+    pub tool: String,
+
+    /// Category name; "All" (or an unknown name) shows every category.
+    #[serde(default = "default_view_category")]
+    pub category: String,
+
+    /// Search text applied on top of the category.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub query: String,
+}
+
+fn default_view_category() -> String {
+    "All".to_string()
+}
+
+/// Settings for one remote host, stored as `[hosts.<name>]`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HostConfig {
+    /// Prefix rewrites from local (tilde-contracted) paths to remote paths.
+    /// Paths that match no rule are used unchanged.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_map: Vec<PathMapping>,
+}
+
+/// Settings for one tracked tool, stored as `[tools.<name>]`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolConfig {
+    /// Command run by `dotsmith reload` instead of the module's
+    /// `reload_command`. `{config_path}` is replaced the same way.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reload_hook: Option<String>,
+}
+
+/// Replace the `local` prefix of a path with `remote`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathMapping {
+    pub local: String,
+    pub remote: String,
+}
+
+/// The keys for one `[keymap]` action: `remove = "x"` or `down = ["j", "down"]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    pub fn keys(&self) -> &[String] {
+        match self {
+            KeyList::One(key) => std::slice::from_ref(key),
+            KeyList::Many(keys) => keys,
+        }
+    }
+}
+
+/// View names are used as TOML keys and typed on the command line, so keep
+/// them to letters, digits, hyphens, and underscores.
+pub fn validate_view_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(DotsmithError::InvalidViewName(name.to_string()).into());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,6 +244,35 @@ mod tests {
             "~/.config/dotsmith/configs"
         );
         assert!(deserialized.general.repo_path.is_none());
+        assert!(deserialized.general.confirm_reload);
+    }
+
+    #[test]
+    fn test_config_confirm_reload_defaults_on() {
+        let config: DotsmithConfig = toml::from_str("[general]\neditor = \"vi\"\n").unwrap();
+        assert!(config.general.confirm_reload);
+        let config: DotsmithConfig =
+            toml::from_str("[general]\nconfirm_reload = false\n").unwrap();
+        assert!(!config.general.confirm_reload);
+    }
+
+    #[test]
+    fn test_config_confirm_destructive_defaults_on() {
+        assert!(DotsmithConfig::default().general.confirm_destructive);
+        let config: DotsmithConfig = toml::from_str("[general]\neditor = \"vi\"\n").unwrap();
+        assert!(config.general.confirm_destructive);
+        let config: DotsmithConfig =
+            toml::from_str("[general]\nconfirm_destructive = false\n").unwrap();
+        assert!(!config.general.confirm_destructive);
+    }
+
+    #[test]
+    fn test_config_snapshot_message_default() {
+        let config: DotsmithConfig = toml::from_str("[general]\neditor = \"vi\"\n").unwrap();
+        assert_eq!(config.general.snapshot_message, "{source} @ {time}");
+        let config: DotsmithConfig =
+            toml::from_str("[general]\nsnapshot_message = \"{tool} ({source})\"\n").unwrap();
+        assert_eq!(config.general.snapshot_message, "{tool} ({source})");
     }
 
     #[test]
@@ -79,6 +284,78 @@ mod tests {
         assert_eq!(deserialized.general.repo_path.as_deref(), Some("~/dots"));
     }
 
+    #[test]
+    fn test_config_hosts_parse() {
+        let config: DotsmithConfig = toml::from_str(
+            r#"
+            [hosts.myserver]
+            path_map = [{ local = "~/.config", remote = "/opt/user/config" }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.hosts["myserver"].path_map,
+            vec![PathMapping {
+                local: "~/.config".to_string(),
+                remote: "/opt/user/config".to_string(),
+            }]
+        );
+        assert!(!toml::to_string_pretty(&DotsmithConfig::default()).unwrap().contains("hosts"));
+    }
+
+    #[test]
+    fn test_config_views_roundtrip() {
+        let mut config = DotsmithConfig::default();
+        config.views.insert(
+            "status-bar".to_string(),
+            SavedView {
+                tool: "tmux".to_string(),
+                category: "status".to_string(),
+                query: "bar".to_string(),
+            },
+        );
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        assert!(serialized.contains("[views.status-bar]"));
+        let deserialized: DotsmithConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.views, config.views);
+    }
+
+    #[test]
+    fn test_config_without_views_omits_section() {
+        let serialized = toml::to_string_pretty(&DotsmithConfig::default()).unwrap();
+        assert!(!serialized.contains("views"));
+        let partial: DotsmithConfig = toml::from_str("[views.x]\ntool = \"git\"\n").unwrap();
+        assert_eq!(partial.views["x"].category, "All");
+        assert!(partial.views["x"].query.is_empty());
+    }
+
+    #[test]
+    fn test_config_keymap_parse() {
+        let config: DotsmithConfig =
+            toml::from_str("[keymap]\nremove = \"x\"\ndown = [\"j\", \"ctrl-n\"]\n").unwrap();
+        assert_eq!(config.keymap["remove"].keys(), ["x"]);
+        assert_eq!(config.keymap["down"].keys(), ["j", "ctrl-n"]);
+        assert!(!toml::to_string_pretty(&DotsmithConfig::default()).unwrap().contains("keymap"));
+    }
+
+    #[test]
+    fn test_config_editors_parse() {
+        let config: DotsmithConfig =
+            toml::from_str("[editors]\nneovim = \"nvim\"\njson = \"code --wait {path}\"\n")
+                .unwrap();
+        assert_eq!(config.editors["neovim"], "nvim");
+        assert_eq!(config.editors["json"], "code --wait {path}");
+        assert!(!toml::to_string_pretty(&DotsmithConfig::default()).unwrap().contains("editors"));
+    }
+
+    #[test]
+    fn test_validate_view_name() {
+        assert!(validate_view_name("status-bar_2").is_ok());
+        assert!(validate_view_name("").is_err());
+        assert!(validate_view_name("a b").is_err());
+        assert!(validate_view_name("a.b").is_err());
+    }
+
     #[test]
     fn test_config_default_values() {
         let config = DotsmithConfig::default();
@@ -0,0 +1,203 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use sha2::Sha256;
+
+use crate::core::errors::DotsmithError;
+use crate::util;
+
+/// Environment variable holding the passphrase, checked before prompting.
+pub const PASSPHRASE_ENV: &str = "DOTSMITH_PASSPHRASE";
+
+/// Starts every encrypted blob, followed by the PBKDF2 round count (u32 LE),
+/// the salt, the nonce, and the sealed content. Everything before the nonce
+/// is authenticated along with the content.
+const MAGIC: &[u8; 6] = b"DSENC1";
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC.len() + 4 + SALT_LEN;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+
+/// PBKDF2 rounds for new blobs. Stored in each blob so it can be raised
+/// without breaking old data.
+const DEFAULT_ROUNDS: u32 = 600_000;
+
+/// Upper bound on the rounds a blob may ask for, so a crafted file can't
+/// stall dotsmith.
+const MAX_ROUNDS: u32 = 10_000_000;
+
+/// Keys already derived, by round count and salt.
+type KeyCache = HashMap<(u32, [u8; SALT_LEN]), [u8; KEY_LEN]>;
+
+/// Encrypts and decrypts snapshot content and profile files with
+/// XChaCha20-Poly1305, keyed by PBKDF2-HMAC-SHA256 over a passphrase.
+///
+/// Each blob carries the salt its key was derived with. A `Cipher` seals
+/// everything with one salt of its own and caches derived keys, so the slow
+/// derivation runs once per salt rather than once per blob.
+pub struct Cipher {
+    passphrase: String,
+    rounds: u32,
+    salt: [u8; SALT_LEN],
+    keys: RefCell<KeyCache>,
+}
+
+impl Cipher {
+    pub fn new(passphrase: &str) -> Result<Self> {
+        Self::with_rounds(passphrase, DEFAULT_ROUNDS)
+    }
+
+    pub(crate) fn with_rounds(passphrase: &str, rounds: u32) -> Result<Self> {
+        if passphrase.is_empty() {
+            return Err(DotsmithError::PassphraseRequired.into());
+        }
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Ok(Self {
+            passphrase: passphrase.to_string(),
+            rounds,
+            salt,
+            keys: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Take the passphrase from `DOTSMITH_PASSPHRASE`, or ask for it when
+    /// stdin is a terminal. With `confirm`, a typed passphrase is asked for
+    /// twice, for when nothing is encrypted with it yet.
+    pub fn from_env_or_prompt(confirm: bool) -> Result<Self> {
+        if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV)
+            && !passphrase.is_empty()
+        {
+            return Self::new(&passphrase);
+        }
+        if !util::prompt::is_interactive() {
+            return Err(DotsmithError::PassphraseRequired.into());
+        }
+
+        let passphrase = util::prompt::ask_secret("Passphrase:")?;
+        if confirm && util::prompt::ask_secret("Repeat passphrase:")? != passphrase {
+            anyhow::bail!("passphrases don't match");
+        }
+        Self::new(&passphrase)
+    }
+
+    /// Encrypt `plaintext` into a self-describing blob.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut blob = Vec::with_capacity(HEADER_LEN + NONCE_LEN + plaintext.len() + 16);
+        blob.extend_from_slice(MAGIC);
+        blob.extend_from_slice(&self.rounds.to_le_bytes());
+        blob.extend_from_slice(&self.salt);
+
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = self
+            .aead(self.rounds, self.salt)
+            .encrypt(&nonce, Payload { msg: plaintext, aad: &blob })
+            .map_err(|_| anyhow::anyhow!("failed to encrypt"))?;
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&sealed);
+        Ok(blob)
+    }
+
+    /// Decrypt a blob made by [`Cipher::encrypt`], with this passphrase or
+    /// any other `Cipher` sharing it.
+    pub fn decrypt(&self, blob: &[u8]) -> Result<Vec<u8>> {
+        if !is_encrypted(blob) || blob.len() < HEADER_LEN + NONCE_LEN {
+            return Err(DotsmithError::DecryptFailed.into());
+        }
+        let (header, rest) = blob.split_at(HEADER_LEN);
+        let (nonce, sealed) = rest.split_at(NONCE_LEN);
+
+        let rounds = u32::from_le_bytes(header[6..10].try_into()?);
+        if rounds == 0 || rounds > MAX_ROUNDS {
+            return Err(DotsmithError::DecryptFailed.into());
+        }
+        let salt: [u8; SALT_LEN] = header[10..].try_into()?;
+
+        self.aead(rounds, salt)
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: sealed, aad: header })
+            .map_err(|_| DotsmithError::DecryptFailed.into())
+    }
+
+    /// Seal new blobs with the salt of `blob`, which must already decrypt,
+    /// so the key derived for it is reused instead of deriving another.
+    pub fn reuse_salt(&mut self, blob: &[u8]) {
+        if let Some(header) = blob.get(..HEADER_LEN)
+            && is_encrypted(header)
+            && header[6..10] == self.rounds.to_le_bytes()
+        {
+            self.salt.copy_from_slice(&header[10..]);
+        }
+    }
+
+    fn aead(&self, rounds: u32, salt: [u8; SALT_LEN]) -> XChaCha20Poly1305 {
+        let key = *self.keys.borrow_mut().entry((rounds, salt)).or_insert_with(|| {
+            let mut key = [0u8; KEY_LEN];
+            pbkdf2::pbkdf2_hmac::<Sha256>(self.passphrase.as_bytes(), &salt, rounds, &mut key);
+            key
+        });
+        XChaCha20Poly1305::new(&key.into())
+    }
+}
+
+/// Whether `data` starts like a blob made by [`Cipher::encrypt`].
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cipher(passphrase: &str) -> Cipher {
+        Cipher::with_rounds(passphrase, 10).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let c = cipher("hunter2");
+        let blob = c.encrypt(b"token = abc123\n").unwrap();
+        assert!(is_encrypted(&blob));
+        assert!(!blob.windows(6).any(|w| w == b"abc123"));
+        assert_eq!(c.decrypt(&blob).unwrap(), b"token = abc123\n");
+
+        // Same passphrase, different salt: still opens
+        let mut other = cipher("hunter2");
+        assert_eq!(other.decrypt(&blob).unwrap(), b"token = abc123\n");
+        other.reuse_salt(&blob);
+        assert_eq!(other.encrypt(b"x").unwrap()[..HEADER_LEN], blob[..HEADER_LEN]);
+    }
+
+    #[test]
+    fn test_same_content_encrypts_differently() {
+        let c = cipher("hunter2");
+        assert_ne!(c.encrypt(b"same").unwrap(), c.encrypt(b"same").unwrap());
+    }
+
+    #[test]
+    fn test_wrong_passphrase_and_tampering_fail() {
+        let blob = cipher("hunter2").encrypt(b"secret").unwrap();
+        assert!(cipher("hunter3").decrypt(&blob).is_err());
+
+        let c = cipher("hunter2");
+        let mut tampered = blob.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(c.decrypt(&tampered).is_err());
+
+        // The header is authenticated too
+        let mut salted = blob.clone();
+        salted[12] ^= 1;
+        assert!(c.decrypt(&salted).is_err());
+
+        assert!(c.decrypt(b"secret").is_err());
+        assert!(c.decrypt(&blob[..HEADER_LEN]).is_err());
+    }
+
+    #[test]
+    fn test_empty_passphrase_rejected() {
+        assert!(Cipher::new("").is_err());
+    }
+}
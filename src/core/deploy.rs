@@ -4,12 +4,17 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 
+use crate::core::template;
+use crate::util;
+
 /// Result of a deploy operation for a single path.
 #[derive(Debug)]
 pub struct DeployAction {
     pub source: PathBuf,
     pub target: PathBuf,
     pub action: DeployActionType,
+    /// Rendered content for template sources, written instead of a symlink.
+    pub rendered: Option<String>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -24,6 +29,31 @@ pub enum DeployActionType {
     Relink,
     /// Source doesn't exist — skip
     SourceMissing,
+    /// Template source: write the rendered file (target doesn't exist)
+    Render,
+    /// Template source: target exists with other content — backup, then render
+    BackupAndRender,
+    /// Copy mode: copy the source (target doesn't exist)
+    Copy,
+    /// Copy mode: target exists with other content — backup, then copy
+    BackupAndCopy,
+}
+
+/// How non-template sources are placed at the target.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DeployMode {
+    #[default]
+    Symlink,
+    /// Copy files and directories, for filesystems that don't honor symlinks.
+    Copy,
+}
+
+/// Options that affect how a deploy is planned.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DeployOpts {
+    /// Render unset `{{env.VAR}}` placeholders as empty instead of failing.
+    pub allow_missing_env: bool,
+    pub mode: DeployMode,
 }
 
 /// Plan a deploy operation for a tool without executing it.
@@ -31,9 +61,12 @@ pub enum DeployActionType {
 ///
 /// `source_dir` is where the config files live (e.g., `~/dotfiles/tmux`)
 /// `target_dir` is where the symlinks should be created (e.g., `~/.config/tmux`)
+///
+/// A `.tmpl` source file is rendered to the target instead of linked.
 pub fn plan_deploy(
     source_dir: &Path,
     target_dir: &Path,
+    opts: DeployOpts,
 ) -> Result<Vec<DeployAction>> {
     let mut actions = Vec::new();
 
@@ -42,32 +75,240 @@ pub fn plan_deploy(
             source: source_dir.to_path_buf(),
             target: target_dir.to_path_buf(),
             action: DeployActionType::SourceMissing,
+            rendered: None,
         });
         return Ok(actions);
     }
 
-    // If the source is a single file, plan a single symlink
+    // If the source is a single file, plan a single symlink (or render)
     if source_dir.is_file() {
-        let action = classify_target(source_dir, target_dir);
-        actions.push(DeployAction {
-            source: source_dir.to_path_buf(),
-            target: target_dir.to_path_buf(),
-            action,
-        });
+        actions.push(plan_file(source_dir, target_dir, opts)?);
         return Ok(actions);
     }
 
-    // For directories, plan a directory-level symlink
-    let action = classify_target(source_dir, target_dir);
+    // A linked directory would expose templates unrendered
+    if let Some(tmpl) = find_template(source_dir)? {
+        anyhow::bail!(
+            "{} is a template and can't be deployed through a directory symlink — use --files",
+            tmpl.display()
+        );
+    }
+
+    // For directories, plan a directory-level symlink (or copy)
+    let action = classify(source_dir, target_dir, opts.mode);
     actions.push(DeployAction {
         source: source_dir.to_path_buf(),
         target: target_dir.to_path_buf(),
         action,
+        rendered: None,
     });
 
     Ok(actions)
 }
 
+/// Plan a per-file deploy: every file under `source_dir` gets its own symlink
+/// at the same relative path under `target_dir`. Directories are created as
+/// real directories rather than linked, and `.git` is skipped.
+///
+/// Each file is classified independently, so re-running after a successful
+/// deploy yields only `AlreadyCorrect` actions. Templates (`name.tmpl`) are
+/// rendered to `name` in the target.
+pub fn plan_deploy_files(
+    source_dir: &Path,
+    target_dir: &Path,
+    opts: DeployOpts,
+) -> Result<Vec<DeployAction>> {
+    if !source_dir.is_dir() {
+        return plan_deploy(source_dir, target_dir, opts);
+    }
+
+    if fs::symlink_metadata(target_dir).is_ok_and(|m| m.file_type().is_symlink()) {
+        anyhow::bail!(
+            "{} is a symlink — per-file deploy needs a real directory (remove the link or deploy without --files)",
+            target_dir.display()
+        );
+    }
+
+    let mut actions = Vec::new();
+    collect_file_actions(source_dir, target_dir, opts, &mut actions)?;
+    Ok(actions)
+}
+
+fn collect_file_actions(
+    source_dir: &Path,
+    target_dir: &Path,
+    opts: DeployOpts,
+    actions: &mut Vec<DeployAction>,
+) -> Result<()> {
+    let mut entries: Vec<_> = fs::read_dir(source_dir)
+        .with_context(|| format!("failed to read {}", source_dir.display()))?
+        .flatten()
+        .collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let source = entry.path();
+        let target = target_dir.join(entry.file_name());
+
+        if source.is_dir() {
+            if entry.file_name() == ".git" {
+                continue;
+            }
+            collect_file_actions(&source, &target, opts, actions)?;
+        } else if template::is_template(&source) {
+            // name.tmpl deploys as name
+            actions.push(plan_file(&source, &target.with_extension(""), opts)?);
+        } else {
+            actions.push(plan_file(&source, &target, opts)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Plan a single file: render templates, link everything else.
+fn plan_file(source: &Path, target: &Path, opts: DeployOpts) -> Result<DeployAction> {
+    if !template::is_template(source) {
+        return Ok(DeployAction {
+            source: source.to_path_buf(),
+            target: target.to_path_buf(),
+            action: classify(source, target, opts.mode),
+            rendered: None,
+        });
+    }
+
+    let raw = fs::read_to_string(source)
+        .with_context(|| format!("failed to read {}", source.display()))?;
+    let rendered = template::render(&raw, source, opts.allow_missing_env, |name| {
+        std::env::var(name).ok()
+    })?;
+
+    Ok(DeployAction {
+        source: source.to_path_buf(),
+        target: target.to_path_buf(),
+        action: classify_render_target(target, &rendered),
+        rendered: Some(rendered),
+    })
+}
+
+/// First template file under `dir`, if any (skipping `.git`).
+fn find_template(dir: &Path) -> Result<Option<PathBuf>> {
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if path.file_name().is_some_and(|n| n == ".git") {
+                continue;
+            }
+            if let Some(found) = find_template(&path)? {
+                return Ok(Some(found));
+            }
+        } else if template::is_template(&path) {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+/// Count `(already_correct, changed)` actions in a plan. Missing sources count as neither.
+pub fn summarize(actions: &[DeployAction]) -> (usize, usize) {
+    let correct = actions
+        .iter()
+        .filter(|a| a.action == DeployActionType::AlreadyCorrect)
+        .count();
+    let changed = actions
+        .iter()
+        .filter(|a| {
+            matches!(
+                a.action,
+                DeployActionType::CreateSymlink
+                    | DeployActionType::BackupAndLink
+                    | DeployActionType::Relink
+                    | DeployActionType::Render
+                    | DeployActionType::BackupAndRender
+                    | DeployActionType::Copy
+                    | DeployActionType::BackupAndCopy
+            )
+        })
+        .count();
+    (correct, changed)
+}
+
+/// What a backup would keep that the deploy replaces: a unified diff from
+/// the target file to the incoming content, or for a directory, the files
+/// that differ from the source. `None` for actions that don't back anything
+/// up, or when there's nothing to show.
+pub fn describe_conflict(action: &DeployAction) -> Option<String> {
+    if !matches!(
+        action.action,
+        DeployActionType::BackupAndLink
+            | DeployActionType::BackupAndRender
+            | DeployActionType::BackupAndCopy
+    ) {
+        return None;
+    }
+
+    let meta = fs::symlink_metadata(&action.target).ok()?;
+    if meta.is_file() {
+        let current = fs::read(&action.target).ok()?;
+        let incoming = match action.rendered {
+            Some(ref rendered) => rendered.clone().into_bytes(),
+            None => fs::read(&action.source).ok()?,
+        };
+        if current == incoming {
+            return None;
+        }
+        let path = util::paths::contract_tilde(&action.target);
+        return Some(match (String::from_utf8(current), String::from_utf8(incoming)) {
+            (Ok(old), Ok(new)) => util::diff::unified_diff(&old, &new, &path),
+            _ => format!("binary file {} differs", path),
+        });
+    }
+
+    if meta.is_dir() && action.source.is_dir() {
+        let mut lines = Vec::new();
+        differing_files(&action.source, &action.target, Path::new(""), &mut lines);
+        if !lines.is_empty() {
+            return Some(lines.join("\n"));
+        }
+    }
+    None
+}
+
+/// List entries that differ between two directory trees, relative to them
+/// (`.git` ignored, like [`same_content`]).
+fn differing_files(source: &Path, target: &Path, rel: &Path, lines: &mut Vec<String>) {
+    let names = |dir: &Path| -> Vec<std::ffi::OsString> {
+        fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|e| e.file_name()).collect())
+            .unwrap_or_default()
+    };
+    let mut all = names(source);
+    all.extend(names(target));
+    all.retain(|n| n != ".git");
+    all.sort();
+    all.dedup();
+
+    for name in all {
+        let (s, t, path) = (source.join(&name), target.join(&name), rel.join(&name));
+        match (fs::symlink_metadata(&s).is_ok(), fs::symlink_metadata(&t).is_ok()) {
+            (false, _) => lines.push(format!("only in target: {}", path.display())),
+            (_, false) => lines.push(format!("only in source: {}", path.display())),
+            _ if s.is_dir() && t.is_dir() && !t.is_symlink() => {
+                differing_files(&s, &t, &path, lines)
+            }
+            _ if !same_content(&s, &t) => lines.push(format!("differs: {}", path.display())),
+            _ => {}
+        }
+    }
+}
+
+fn classify(source: &Path, target: &Path, mode: DeployMode) -> DeployActionType {
+    match mode {
+        DeployMode::Symlink => classify_target(source, target),
+        DeployMode::Copy => classify_copy_target(source, target),
+    }
+}
+
 /// Classify what action is needed for a target path.
 fn classify_target(source: &Path, target: &Path) -> DeployActionType {
     // Check symlink_metadata first (doesn't follow symlinks)
@@ -96,6 +337,76 @@ fn classify_target(source: &Path, target: &Path) -> DeployActionType {
     }
 }
 
+/// Classify a render target: a regular file already holding the rendered
+/// content is correct; anything else in the way is backed up.
+fn classify_render_target(target: &Path, rendered: &str) -> DeployActionType {
+    match fs::symlink_metadata(target) {
+        Ok(meta) if meta.is_file() => {
+            if fs::read(target).is_ok_and(|current| current == rendered.as_bytes()) {
+                DeployActionType::AlreadyCorrect
+            } else {
+                DeployActionType::BackupAndRender
+            }
+        }
+        Ok(_) => DeployActionType::BackupAndRender,
+        Err(_) => DeployActionType::Render,
+    }
+}
+
+/// Classify a copy target: a real file or directory with the same content as
+/// the source is correct; a symlink or anything different is backed up.
+fn classify_copy_target(source: &Path, target: &Path) -> DeployActionType {
+    match fs::symlink_metadata(target) {
+        Ok(meta) if meta.file_type().is_symlink() => DeployActionType::BackupAndCopy,
+        Ok(_) if same_content(source, target) => DeployActionType::AlreadyCorrect,
+        Ok(_) => DeployActionType::BackupAndCopy,
+        Err(_) => DeployActionType::Copy,
+    }
+}
+
+/// Whether `target` holds exactly what copying `source` would produce
+/// (byte-equal files, same entries; `.git` ignored).
+fn same_content(source: &Path, target: &Path) -> bool {
+    if source.is_file() {
+        return target.is_file()
+            && matches!((fs::read(source), fs::read(target)), (Ok(a), Ok(b)) if a == b);
+    }
+    if !source.is_dir() || !target.is_dir() {
+        return false;
+    }
+
+    let names = |dir: &Path| -> Option<Vec<std::ffi::OsString>> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .ok()?
+            .flatten()
+            .map(|e| e.file_name())
+            .filter(|n| n != ".git")
+            .collect();
+        names.sort();
+        Some(names)
+    };
+
+    match (names(source), names(target)) {
+        (Some(a), Some(b)) if a == b => a
+            .iter()
+            .all(|name| same_content(&source.join(name), &target.join(name))),
+        _ => false,
+    }
+}
+
+/// Copy a file or directory tree to `target`.
+fn copy_source(source: &Path, target: &Path) -> Result<()> {
+    let context = || format!("failed to copy {} to {}", source.display(), target.display());
+
+    if source.is_dir() {
+        fs::create_dir_all(target)
+            .with_context(|| format!("failed to create {}", target.display()))?;
+        util::fs::copy_dir_recursive(source, target).with_context(context)
+    } else {
+        fs::copy(source, target).map(|_| ()).with_context(context)
+    }
+}
+
 /// Execute a deploy plan. Creates backups before modifying anything.
 /// Returns a list of paths that were backed up.
 pub fn execute_deploy(
@@ -124,6 +435,11 @@ pub fn execute_deploy(
             }
             DeployActionType::BackupAndLink => {
                 // Backup existing file/dir
+                if let Some(parent) = action.target.parent() {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!("failed to create parent dir {}", parent.display())
+                    })?;
+                }
                 let backup_path = backup_target(&action.target, backup_dir)?;
                 backed_up.push(backup_path);
 
@@ -150,6 +466,34 @@ pub fn execute_deploy(
                     )
                 })?;
             }
+            DeployActionType::Render | DeployActionType::BackupAndRender => {
+                if let Some(parent) = action.target.parent() {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!("failed to create parent dir {}", parent.display())
+                    })?;
+                }
+                if action.action == DeployActionType::BackupAndRender {
+                    backed_up.push(backup_target(&action.target, backup_dir)?);
+                }
+
+                // Rendered files may contain secrets: atomic_write makes them 0600
+                let content = action.rendered.as_deref().unwrap_or_default();
+                util::fs::atomic_write(&action.target, content).with_context(|| {
+                    format!("failed to render {}", action.target.display())
+                })?;
+            }
+            DeployActionType::Copy | DeployActionType::BackupAndCopy => {
+                if let Some(parent) = action.target.parent() {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!("failed to create parent dir {}", parent.display())
+                    })?;
+                }
+                if action.action == DeployActionType::BackupAndCopy {
+                    backed_up.push(backup_target(&action.target, backup_dir)?);
+                }
+
+                copy_source(&action.source, &action.target)?;
+            }
             DeployActionType::AlreadyCorrect | DeployActionType::SourceMissing => {
                 // No action needed
             }
@@ -207,7 +551,7 @@ mod tests {
         fs::create_dir_all(&source).unwrap();
         let target = tmp.path().join("target");
 
-        let actions = plan_deploy(&source, &target).unwrap();
+        let actions = plan_deploy(&source, &target, DeployOpts::default()).unwrap();
         assert_eq!(actions.len(), 1);
         assert_eq!(actions[0].action, DeployActionType::CreateSymlink);
     }
@@ -221,7 +565,7 @@ mod tests {
 
         unix_fs::symlink(&source, &target).unwrap();
 
-        let actions = plan_deploy(&source, &target).unwrap();
+        let actions = plan_deploy(&source, &target, DeployOpts::default()).unwrap();
         assert_eq!(actions.len(), 1);
         assert_eq!(actions[0].action, DeployActionType::AlreadyCorrect);
     }
@@ -234,11 +578,48 @@ mod tests {
         let target = tmp.path().join("target");
         fs::create_dir_all(&target).unwrap();
 
-        let actions = plan_deploy(&source, &target).unwrap();
+        let actions = plan_deploy(&source, &target, DeployOpts::default()).unwrap();
         assert_eq!(actions.len(), 1);
         assert_eq!(actions[0].action, DeployActionType::BackupAndLink);
     }
 
+    #[test]
+    fn test_describe_conflict_file_diff() {
+        let tmp = TempDir::new().unwrap();
+        let source = tmp.path().join("tmux.conf");
+        let target = tmp.path().join(".tmux.conf");
+        fs::write(&source, "set -g mouse on\nset -g base-index 1\n").unwrap();
+        fs::write(&target, "set -g mouse off\nset -g base-index 1\n").unwrap();
+
+        let actions = plan_deploy(&source, &target, DeployOpts::default()).unwrap();
+        assert_eq!(actions[0].action, DeployActionType::BackupAndLink);
+        let description = describe_conflict(&actions[0]).unwrap();
+        assert!(description.contains("-set -g mouse off"));
+        assert!(description.contains("+set -g mouse on"));
+        assert!(!description.contains("-set -g base-index 1"));
+
+        // Same content: the backup keeps nothing new
+        fs::write(&target, "set -g mouse on\nset -g base-index 1\n").unwrap();
+        let actions = plan_deploy(&source, &target, DeployOpts::default()).unwrap();
+        assert_eq!(describe_conflict(&actions[0]), None);
+    }
+
+    #[test]
+    fn test_describe_conflict_lists_directory_files() {
+        let tmp = TempDir::new().unwrap();
+        let (source, target) = sample_tree(&tmp);
+        fs::create_dir_all(target.join("themes")).unwrap();
+        fs::write(target.join("tmux.conf"), "set -g mouse off\n").unwrap();
+        fs::write(target.join("themes/dark.conf"), "dark\n").unwrap();
+        fs::write(target.join("local.conf"), "x\n").unwrap();
+
+        let actions = plan_deploy(&source, &target, DeployOpts::default()).unwrap();
+        assert_eq!(
+            describe_conflict(&actions[0]).unwrap(),
+            "only in target: local.conf\ndiffers: tmux.conf"
+        );
+    }
+
     #[test]
     fn test_plan_deploy_relink() {
         let tmp = TempDir::new().unwrap();
@@ -249,7 +630,7 @@ mod tests {
         let target = tmp.path().join("target");
         unix_fs::symlink(&wrong, &target).unwrap();
 
-        let actions = plan_deploy(&source, &target).unwrap();
+        let actions = plan_deploy(&source, &target, DeployOpts::default()).unwrap();
         assert_eq!(actions.len(), 1);
         assert_eq!(actions[0].action, DeployActionType::Relink);
     }
@@ -260,7 +641,7 @@ mod tests {
         let source = tmp.path().join("nonexistent");
         let target = tmp.path().join("target");
 
-        let actions = plan_deploy(&source, &target).unwrap();
+        let actions = plan_deploy(&source, &target, DeployOpts::default()).unwrap();
         assert_eq!(actions.len(), 1);
         assert_eq!(actions[0].action, DeployActionType::SourceMissing);
     }
@@ -277,6 +658,7 @@ mod tests {
             source: source.clone(),
             target: target.clone(),
             action: DeployActionType::CreateSymlink,
+            rendered: None,
         }];
 
         let backed_up = execute_deploy(&actions, &backup_dir).unwrap();
@@ -298,6 +680,7 @@ mod tests {
             source: source.clone(),
             target: target.clone(),
             action: DeployActionType::BackupAndLink,
+            rendered: None,
         }];
 
         let backed_up = execute_deploy(&actions, &backup_dir).unwrap();
@@ -307,4 +690,177 @@ mod tests {
         let backup_content = fs::read_to_string(&backed_up[0]).unwrap();
         assert_eq!(backup_content, "old content");
     }
+
+    fn sample_tree(tmp: &TempDir) -> (PathBuf, PathBuf) {
+        let source = tmp.path().join("dots");
+        fs::create_dir_all(source.join("themes")).unwrap();
+        fs::create_dir_all(source.join(".git")).unwrap();
+        fs::write(source.join("tmux.conf"), "set -g mouse on\n").unwrap();
+        fs::write(source.join("themes/dark.conf"), "dark\n").unwrap();
+        fs::write(source.join(".git/HEAD"), "ref\n").unwrap();
+        (source, tmp.path().join("target"))
+    }
+
+    #[test]
+    fn test_plan_deploy_files_walks_tree() {
+        let tmp = TempDir::new().unwrap();
+        let (source, target) = sample_tree(&tmp);
+
+        let actions = plan_deploy_files(&source, &target, DeployOpts::default()).unwrap();
+        assert_eq!(actions.len(), 2); // .git skipped
+        assert!(actions.iter().all(|a| a.action == DeployActionType::CreateSymlink));
+        assert!(actions.iter().any(|a| a.target == target.join("themes/dark.conf")));
+    }
+
+    #[test]
+    fn test_deploy_files_idempotent() {
+        let tmp = TempDir::new().unwrap();
+        let (source, target) = sample_tree(&tmp);
+        let backup_dir = tmp.path().join("backups");
+
+        let actions = plan_deploy_files(&source, &target, DeployOpts::default()).unwrap();
+        assert_eq!(summarize(&actions), (0, 2));
+        execute_deploy(&actions, &backup_dir).unwrap();
+        assert!(target.is_dir() && !target.is_symlink());
+        assert!(target.join("themes/dark.conf").is_symlink());
+
+        // Second run: everything already linked
+        let again = plan_deploy_files(&source, &target, DeployOpts::default()).unwrap();
+        assert_eq!(summarize(&again), (2, 0));
+        assert!(execute_deploy(&again, &backup_dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_deploy_files_backs_up_existing_file() {
+        let tmp = TempDir::new().unwrap();
+        let (source, target) = sample_tree(&tmp);
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("tmux.conf"), "old\n").unwrap();
+
+        let actions = plan_deploy_files(&source, &target, DeployOpts::default()).unwrap();
+        assert_eq!(summarize(&actions), (0, 2));
+        let backed_up = execute_deploy(&actions, &tmp.path().join("backups")).unwrap();
+        assert_eq!(backed_up.len(), 1);
+        assert_eq!(fs::read_to_string(&backed_up[0]).unwrap(), "old\n");
+    }
+
+    #[test]
+    fn test_deploy_files_rejects_symlinked_target() {
+        let tmp = TempDir::new().unwrap();
+        let (source, target) = sample_tree(&tmp);
+        unix_fs::symlink(&source, &target).unwrap();
+
+        assert!(plan_deploy_files(&source, &target, DeployOpts::default()).is_err());
+    }
+
+    #[test]
+    fn test_deploy_files_renders_templates() {
+        let tmp = TempDir::new().unwrap();
+        let (source, target) = sample_tree(&tmp);
+        fs::write(source.join("gitconfig.tmpl"), "home = {{env.HOME}}\n").unwrap();
+        let backup_dir = tmp.path().join("backups");
+
+        let actions = plan_deploy_files(&source, &target, DeployOpts::default()).unwrap();
+        let render = actions
+            .iter()
+            .find(|a| a.action == DeployActionType::Render)
+            .unwrap();
+        assert_eq!(render.target, target.join("gitconfig"));
+
+        execute_deploy(&actions, &backup_dir).unwrap();
+        let deployed = target.join("gitconfig");
+        assert!(!deployed.is_symlink());
+        assert_eq!(
+            fs::read_to_string(&deployed).unwrap(),
+            format!("home = {}\n", std::env::var("HOME").unwrap())
+        );
+        // The source keeps its placeholder
+        let raw = fs::read_to_string(source.join("gitconfig.tmpl")).unwrap();
+        assert!(raw.contains("{{env.HOME}}"));
+
+        // Unchanged render is already correct; a hand edit is backed up
+        let again = plan_deploy_files(&source, &target, DeployOpts::default()).unwrap();
+        assert_eq!(summarize(&again), (3, 0));
+        fs::write(&deployed, "edited\n").unwrap();
+        let again = plan_deploy_files(&source, &target, DeployOpts::default()).unwrap();
+        assert!(again.iter().any(|a| a.action == DeployActionType::BackupAndRender));
+    }
+
+    #[test]
+    fn test_template_missing_env_errors() {
+        let tmp = TempDir::new().unwrap();
+        let source = tmp.path().join("token.tmpl");
+        fs::write(&source, "{{env.DOTSMITH_TEST_UNSET_VAR}}\n").unwrap();
+        let target = tmp.path().join("token");
+
+        assert!(plan_deploy(&source, &target, DeployOpts::default()).is_err());
+
+        let opts = DeployOpts {
+            allow_missing_env: true,
+            ..Default::default()
+        };
+        let actions = plan_deploy(&source, &target, opts).unwrap();
+        assert_eq!(actions[0].rendered.as_deref(), Some("\n"));
+    }
+
+    #[test]
+    fn test_directory_link_rejects_templates() {
+        let tmp = TempDir::new().unwrap();
+        let (source, target) = sample_tree(&tmp);
+        fs::write(source.join("themes/secret.tmpl"), "x\n").unwrap();
+
+        assert!(plan_deploy(&source, &target, DeployOpts::default()).is_err());
+    }
+
+    fn copy_opts() -> DeployOpts {
+        DeployOpts {
+            mode: DeployMode::Copy,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_copy_directory() {
+        let tmp = TempDir::new().unwrap();
+        let (source, target) = sample_tree(&tmp);
+        let backup_dir = tmp.path().join("backups");
+
+        let actions = plan_deploy(&source, &target, copy_opts()).unwrap();
+        assert_eq!(actions[0].action, DeployActionType::Copy);
+        execute_deploy(&actions, &backup_dir).unwrap();
+
+        assert!(target.is_dir() && !target.is_symlink());
+        let copied = target.join("themes/dark.conf");
+        assert!(copied.is_file() && !copied.is_symlink());
+        assert_eq!(fs::read_to_string(copied).unwrap(), "dark\n");
+        assert!(!target.join(".git").exists());
+
+        // Copies that still match are left alone
+        let again = plan_deploy(&source, &target, copy_opts()).unwrap();
+        assert_eq!(again[0].action, DeployActionType::AlreadyCorrect);
+    }
+
+    #[test]
+    fn test_copy_files_backs_up_changed_and_linked_targets() {
+        let tmp = TempDir::new().unwrap();
+        let (source, target) = sample_tree(&tmp);
+        let backup_dir = tmp.path().join("backups");
+
+        // A symlinked deploy switched to copy mode replaces the links
+        let linked = plan_deploy_files(&source, &target, DeployOpts::default()).unwrap();
+        execute_deploy(&linked, &backup_dir).unwrap();
+        fs::write(source.join("tmux.conf"), "set -g mouse off\n").unwrap();
+
+        let actions = plan_deploy_files(&source, &target, copy_opts()).unwrap();
+        assert!(actions.iter().all(|a| a.action == DeployActionType::BackupAndCopy));
+        let backed_up = execute_deploy(&actions, &backup_dir).unwrap();
+        assert_eq!(backed_up.len(), 2);
+
+        let conf = target.join("tmux.conf");
+        assert!(conf.is_file() && !conf.is_symlink());
+        assert_eq!(fs::read_to_string(&conf).unwrap(), "set -g mouse off\n");
+
+        let again = plan_deploy_files(&source, &target, copy_opts()).unwrap();
+        assert_eq!(summarize(&again), (2, 0));
+    }
 }
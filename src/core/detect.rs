@@ -5,9 +5,96 @@ use std::process::Command;
 use anyhow::{Context, Result};
 
 use crate::core::errors::DotsmithError;
-use crate::core::module::ModuleDefinition;
+use crate::core::manifest::ToolEntry;
+use crate::core::module::{ModuleDefinition, ModuleRegistry};
 use crate::util;
 
+/// What adding a tool would record, found before the manifest changes.
+#[derive(Debug, Clone)]
+pub struct DetectedTool {
+    pub name: String,
+    pub tier: u8,
+    pub config_paths: Vec<PathBuf>,
+    /// Git include directives that were not followed
+    pub include_notes: Vec<String>,
+    pub plugin_manager: Option<String>,
+}
+
+impl DetectedTool {
+    /// The manifest entry for the tool. Plugins are never managed at first,
+    /// to respect an existing setup.
+    pub fn to_entry(&self) -> ToolEntry {
+        ToolEntry {
+            tier: self.tier,
+            config_paths: self
+                .config_paths
+                .iter()
+                .map(|p| util::paths::contract_tilde(p))
+                .collect(),
+            plugins_managed: false,
+            plugin_manager: self.plugin_manager.clone(),
+            added_at: chrono::Utc::now(),
+            last_snapshot: None,
+            plugins: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+/// Detect `tool` the way `dotsmith add` does: Tier 1 with a module
+/// definition, Tier 2 without (unless `force_tier` says otherwise), check
+/// it's installed, then find its config files -- `paths` when given -- along
+/// with git includes and any plugin manager.
+pub fn detect_tool(
+    config_dir: &Path,
+    tool: &str,
+    paths: Option<Vec<PathBuf>>,
+    force_tier: Option<u8>,
+) -> Result<DetectedTool> {
+    let module_def = ModuleRegistry::load_module(config_dir, tool);
+    let tier = match force_tier {
+        Some(1) if module_def.is_none() => {
+            anyhow::bail!("'{}' has no module definition, so it can't be Tier 1", tool)
+        }
+        Some(forced) => forced,
+        None if module_def.is_some() => 1,
+        None => 2,
+    };
+
+    let detect_cmd = module_def
+        .as_ref()
+        .map(|d| d.metadata.detect_command.clone())
+        .unwrap_or_else(|| format!("which {}", tool));
+    check_installed(tool, &detect_cmd)?;
+
+    let mut config_paths = match (paths, &module_def) {
+        (Some(paths), _) => paths,
+        (None, Some(def)) => find_config_paths_from_module(def)?,
+        (None, None) => auto_detect_config_paths(tool)?,
+    };
+    if config_paths.is_empty() {
+        return Err(DotsmithError::NoConfigFound(tool.to_string()).into());
+    }
+
+    // Git configs pull in more files through include/includeIf
+    let include_notes = if module_def
+        .as_ref()
+        .is_some_and(|d| d.metadata.config_format == "git")
+    {
+        add_git_includes(&mut config_paths)
+    } else {
+        Vec::new()
+    };
+    let plugin_manager = detect_plugin_manager(tool, &config_paths);
+
+    Ok(DetectedTool {
+        name: tool.to_string(),
+        tier,
+        config_paths,
+        include_notes,
+        plugin_manager,
+    })
+}
+
 /// Check if a tool is installed by running its detect command.
 /// `detect_cmd` is a full command string, e.g. "which tmux".
 pub fn check_installed(tool: &str, detect_cmd: &str) -> Result<()> {
@@ -77,6 +164,27 @@ pub fn find_config_paths_from_module(module: &ModuleDefinition) -> Result<Vec<Pa
     Ok(found)
 }
 
+/// Plugin manager checkouts found directly under a config root.
+pub const PLUGIN_DIRS: &[&str] = &[
+    "plugs",
+    "plugins",
+    "tpm",
+    "zinix-mgr",
+    "zinit",
+    "oh-my-zsh",
+    ".antidote",
+];
+
+/// Version control and cache directories, never user config at any depth.
+pub const NOISE_DIRS: &[&str] = &[".git", ".cache", "node_modules", "__pycache__"];
+
+/// Whether a directory should be skipped when collecting config files.
+/// Plugin dirs only count at the top of a config root: a nested directory
+/// such as nvim's `lua/plugins` holds the user's own plugin specs.
+pub fn is_skipped_dir(name: &str, top_level: bool) -> bool {
+    NOISE_DIRS.contains(&name) || (top_level && PLUGIN_DIRS.contains(&name))
+}
+
 /// For a directory config root, discover config files and relevant subdirectories.
 /// Skips plugin directories to avoid tracking third-party code.
 fn discover_config_dir(dir: &Path, found: &mut Vec<PathBuf>) -> Result<()> {
@@ -103,20 +211,6 @@ fn discover_config_dir(dir: &Path, found: &mut Vec<PathBuf>) -> Result<()> {
     // Directories to recurse into (tracked config subdirs)
     let tracked_subdirs = ["conf", "utils", "scripts", "lua", "after", "colors"];
 
-    // Directories to SKIP (plugin dirs, version control, caches)
-    let skip_dirs = [
-        "plugs",
-        "plugins",
-        "tpm",
-        ".git",
-        "zinix-mgr",
-        "zinit",
-        "oh-my-zsh",
-        ".antidote",
-        ".cache",
-        "node_modules",
-        "__pycache__",
-    ];
 
     for entry in entries {
         let entry = entry?;
@@ -147,7 +241,7 @@ fn discover_config_dir(dir: &Path, found: &mut Vec<PathBuf>) -> Result<()> {
                 found.push(path); // Track the directory itself
             }
             // Skip plugin dirs and unrecognized dirs silently
-            if skip_dirs.contains(&name.as_str()) {
+            if is_skipped_dir(&name, true) {
                 continue;
             }
         }
@@ -156,11 +250,36 @@ fn discover_config_dir(dir: &Path, found: &mut Vec<PathBuf>) -> Result<()> {
     Ok(())
 }
 
+/// Built-in tools that are installed and have config files on disk, with
+/// the paths `add` would track. Names in `skip` (e.g. already tracked) are
+/// left out.
+pub fn detect_builtin_tools(skip: &[&str]) -> Vec<(String, Vec<PathBuf>)> {
+    let mut detected = Vec::new();
+
+    for name in ModuleRegistry::builtin_names() {
+        if skip.contains(name) {
+            continue;
+        }
+        let Some(module) = ModuleRegistry::get_builtin(name) else {
+            continue;
+        };
+        if check_installed(name, &module.metadata.detect_command).is_err() {
+            continue;
+        }
+        if let Ok(paths) = find_config_paths_from_module(&module)
+            && !paths.is_empty()
+        {
+            detected.push((name.to_string(), paths));
+        }
+    }
+
+    detected
+}
+
 /// For Tier 2 tools: auto-detect config file locations.
 pub fn auto_detect_config_paths(tool: &str) -> Result<Vec<PathBuf>> {
-    let home =
-        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("cannot determine home directory"))?;
-    let xdg_config = dirs::config_dir().unwrap_or_else(|| home.join(".config"));
+    let home = util::paths::home_dir()?;
+    let xdg_config = util::paths::xdg_config_dir()?;
 
     let candidates = vec![
         xdg_config.join(tool),                    // ~/.config/<tool>/
@@ -197,6 +316,183 @@ pub fn auto_detect_config_paths(tool: &str) -> Result<Vec<PathBuf>> {
     Ok(found)
 }
 
+/// Maximum include nesting, matching git's own limit.
+const GIT_INCLUDE_DEPTH: usize = 10;
+
+/// An `[include]` or `[includeIf "<condition>"]` directive in a git config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitInclude {
+    /// `None` for an unconditional `[include]`.
+    pub condition: Option<String>,
+    /// The `path` value as written (may start with `~/` or be relative).
+    pub path: String,
+}
+
+impl GitInclude {
+    /// Whether dotsmith understands the condition. `gitdir:` and
+    /// `hasconfig:remote.*.url:` depend only on which repository git runs in,
+    /// so the included file is part of the user's config. Other conditions
+    /// (e.g. `onbranch:`) are reported rather than followed.
+    pub fn is_resolvable(&self) -> bool {
+        match &self.condition {
+            None => true,
+            Some(cond) => {
+                cond.starts_with("gitdir:")
+                    || cond.starts_with("gitdir/i:")
+                    || cond.starts_with("hasconfig:remote.*.url:")
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match &self.condition {
+            Some(cond) => format!("[includeIf \"{}\"] path = {}", cond, self.path),
+            None => format!("[include] path = {}", self.path),
+        }
+    }
+}
+
+/// Included git config files reachable from a root config.
+#[derive(Debug, Default)]
+pub struct GitIncludes {
+    /// Existing files within $HOME, in discovery order.
+    pub files: Vec<PathBuf>,
+    /// Directives that were not followed, with the reason.
+    pub unresolved: Vec<String>,
+}
+
+/// Parse `include.path` and `includeIf.<condition>.path` entries from git config text.
+pub fn parse_git_includes(content: &str) -> Vec<GitInclude> {
+    let mut includes = Vec::new();
+    // Some(condition) while inside an include section; condition is None for [include]
+    let mut section: Option<Option<String>> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+
+        if let Some(header) = trimmed.strip_prefix('[') {
+            let header = header.split(']').next().unwrap_or("").trim();
+            let (name, sub) = match header.split_once(char::is_whitespace) {
+                Some((name, sub)) => (name, Some(sub.trim().trim_matches('"').to_string())),
+                None => (header, None),
+            };
+            section = match (name.to_ascii_lowercase().as_str(), sub) {
+                ("include", None) => Some(None),
+                ("includeif", Some(cond)) => Some(Some(cond)),
+                _ => None,
+            };
+            continue;
+        }
+
+        let Some(condition) = &section else {
+            continue;
+        };
+        let Some((key, value)) = trimmed.split_once('=') else {
+            continue;
+        };
+        if !key.trim().eq_ignore_ascii_case("path") {
+            continue;
+        }
+
+        let value = value.trim();
+        let value = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split('"').next().unwrap_or(""),
+            None => value.split([';', '#']).next().unwrap_or("").trim_end(),
+        };
+        if !value.is_empty() {
+            includes.push(GitInclude {
+                condition: condition.clone(),
+                path: value.to_string(),
+            });
+        }
+    }
+
+    includes
+}
+
+/// Follow include directives from a git config file, recursively.
+///
+/// Relative include paths resolve against the including file's directory,
+/// as git does. Files outside $HOME, missing files and conditions dotsmith
+/// can't reason about are listed in `unresolved` instead of `files`.
+pub fn resolve_git_includes(config: &Path) -> GitIncludes {
+    let mut result = GitIncludes::default();
+    let mut seen = vec![config.to_path_buf()];
+    collect_git_includes(config, 0, &mut seen, &mut result);
+    result
+}
+
+fn collect_git_includes(
+    config: &Path,
+    depth: usize,
+    seen: &mut Vec<PathBuf>,
+    result: &mut GitIncludes,
+) {
+    let Ok(content) = fs::read_to_string(config) else {
+        return;
+    };
+    let base = config.parent().unwrap_or(Path::new("."));
+
+    for include in parse_git_includes(&content) {
+        if !include.is_resolvable() {
+            result
+                .unresolved
+                .push(format!("{} (condition not evaluated)", include.describe()));
+            continue;
+        }
+
+        let path = if include.path.starts_with('~') {
+            util::paths::expand_tilde(&include.path)
+        } else {
+            base.join(&include.path)
+        };
+
+        if seen.contains(&path) {
+            continue;
+        }
+        if depth >= GIT_INCLUDE_DEPTH {
+            result
+                .unresolved
+                .push(format!("{} (nested too deeply)", include.describe()));
+            continue;
+        }
+        if !path.is_file() {
+            result.unresolved.push(format!("{} (not found)", include.describe()));
+            continue;
+        }
+        if let Err(e) = util::fs::check_path_safety(&path) {
+            result.unresolved.push(format!("{} ({})", include.describe(), e));
+            continue;
+        }
+
+        seen.push(path.clone());
+        result.files.push(path.clone());
+        collect_git_includes(&path, depth + 1, seen, result);
+    }
+}
+
+/// Extend detected git config paths with the files they include.
+/// Returns notes for include directives that were not followed.
+pub fn add_git_includes(config_paths: &mut Vec<PathBuf>) -> Vec<String> {
+    let mut notes = Vec::new();
+    let roots: Vec<PathBuf> = config_paths.iter().filter(|p| p.is_file()).cloned().collect();
+
+    for root in roots {
+        let includes = resolve_git_includes(&root);
+        for file in includes.files {
+            if !config_paths.contains(&file) {
+                config_paths.push(file);
+            }
+        }
+        notes.extend(includes.unresolved);
+    }
+
+    notes
+}
+
 /// Detect existing plugin managers for a tool.
 /// Returns the name of the detected plugin manager, or None.
 pub fn detect_plugin_manager(tool: &str, config_paths: &[PathBuf]) -> Option<String> {
@@ -239,6 +535,13 @@ pub fn detect_plugin_manager(tool: &str, config_paths: &[PathBuf]) -> Option<Str
             }
             None
         }
+        "fish" => {
+            // fisher records installed plugins in fish_plugins
+            if config_root.join("fish_plugins").exists() {
+                return Some("fisher".to_string());
+            }
+            None
+        }
         "nvim" | "neovim" => {
             // Check for lazy.nvim lockfile
             if config_root.join("lazy-lock.json").exists() {
@@ -279,6 +582,19 @@ mod tests {
         assert_eq!(result, Some("zinix-mgr".to_string()));
     }
 
+    #[test]
+    fn test_detect_plugin_manager_fish_fisher() {
+        let tmp = TempDir::new().unwrap();
+        let fish_dir = tmp.path().join("fish");
+        fs::create_dir_all(&fish_dir).unwrap();
+        fs::write(fish_dir.join("config.fish"), "# config").unwrap();
+        fs::write(fish_dir.join("fish_plugins"), "jorgebucaran/fisher\n").unwrap();
+
+        let paths = vec![fish_dir.clone()];
+        let result = detect_plugin_manager("fish", &paths);
+        assert_eq!(result, Some("fisher".to_string()));
+    }
+
     #[test]
     fn test_detect_plugin_manager_none() {
         let tmp = TempDir::new().unwrap();
@@ -340,8 +656,8 @@ mod tests {
         fs::write(tool_dir.join("config.toml"), "key = 'value'").unwrap();
 
         // This test exercises the structure but can't fully test the function
-        // because auto_detect uses dirs::config_dir() which we can't override.
-        // Integration tests cover this via DOTSMITH_CONFIG_DIR + real paths.
+        // without changing the home directory for every test in the process.
+        // tests/add_test.rs covers it end to end with DOTSMITH_HOME.
     }
 
     #[test]
@@ -367,4 +683,51 @@ mod tests {
         // Paths should be relative to the symlink, not the target
         assert!(found.iter().any(|p| p.starts_with(&config_tmux)));
     }
+
+    #[test]
+    fn test_parse_git_includes() {
+        let content = "[user]\n\tname = A\n\tpath = not-an-include\n\
+                       [include]\n\tpath = ~/.gitconfig.local\n\
+                       [includeIf \"gitdir:~/work/\"]\n\tpath = \"work.inc\" ; work\n\
+                       [IncludeIf \"onbranch:main\"]\n\tpath = branch.inc # note\n";
+        let includes = parse_git_includes(content);
+        assert_eq!(
+            includes,
+            vec![
+                GitInclude { condition: None, path: "~/.gitconfig.local".to_string() },
+                GitInclude {
+                    condition: Some("gitdir:~/work/".to_string()),
+                    path: "work.inc".to_string(),
+                },
+                GitInclude {
+                    condition: Some("onbranch:main".to_string()),
+                    path: "branch.inc".to_string(),
+                },
+            ]
+        );
+        assert!(includes[0].is_resolvable());
+        assert!(includes[1].is_resolvable());
+        assert!(!includes[2].is_resolvable());
+    }
+
+    #[test]
+    fn test_resolve_git_includes_reports_unfollowed() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path();
+        fs::write(
+            dir.join("config"),
+            "[includeIf \"onbranch:main\"]\n\tpath = branch.inc\n\
+             [include]\n\tpath = missing.inc\n",
+        )
+        .unwrap();
+        fs::write(dir.join("branch.inc"), "").unwrap();
+
+        let includes = resolve_git_includes(&dir.join("config"));
+        assert!(includes.files.is_empty());
+        assert_eq!(includes.unresolved.len(), 2);
+        assert!(includes.unresolved[0].contains("onbranch:main"));
+        assert!(includes.unresolved[0].contains("condition not evaluated"));
+        assert!(includes.unresolved[1].contains("missing.inc"));
+        assert!(includes.unresolved[1].contains("not found"));
+    }
 }
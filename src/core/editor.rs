@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Result;
+
+use crate::core::config::DotsmithConfig;
+
+/// Stands for the files to open in an editor command.
+pub const PATH_PLACEHOLDER: &str = "{path}";
+
+/// The editor command for a tool, before the files are filled in.
+#[derive(Debug, PartialEq)]
+pub struct EditorCommand {
+    /// Command line, split on whitespace when run. A word containing
+    /// `{path}` is repeated for each file; without one the files are
+    /// appended.
+    pub template: String,
+    /// Where the command came from, for `--verbose`.
+    pub source: String,
+}
+
+impl EditorCommand {
+    /// The command that opens all of `paths` in one editor.
+    pub fn command(&self, paths: &[PathBuf]) -> Result<Command> {
+        let mut words = self.template.split_whitespace();
+        let Some(program) = words.next() else {
+            anyhow::bail!("empty editor command");
+        };
+        let mut cmd = Command::new(program);
+        let mut placed = false;
+        for word in words {
+            if word == PATH_PLACEHOLDER {
+                cmd.args(paths);
+            } else if word.contains(PATH_PLACEHOLDER) {
+                for path in paths {
+                    cmd.arg(word.replace(PATH_PLACEHOLDER, &path.to_string_lossy()));
+                }
+            } else {
+                cmd.arg(word);
+                continue;
+            }
+            placed = true;
+        }
+        if !placed {
+            cmd.args(paths);
+        }
+        Ok(cmd)
+    }
+}
+
+/// The editor for `tool`: its `[editors]` entry in config.toml, then the
+/// entry for its config format (`lua`, `json`, ...), then `general.editor`,
+/// `$EDITOR`, `$VISUAL`, and finally `vi`.
+pub fn resolve(tool: &str, format: Option<&str>, config: &DotsmithConfig) -> EditorCommand {
+    resolve_with(tool, format, config, |name| std::env::var(name).ok())
+}
+
+fn resolve_with(
+    tool: &str,
+    format: Option<&str>,
+    config: &DotsmithConfig,
+    var: impl Fn(&str) -> Option<String>,
+) -> EditorCommand {
+    let found = |template: Option<String>, source: String| {
+        template
+            .filter(|t| !t.trim().is_empty())
+            .map(|template| EditorCommand { template, source })
+    };
+    let entry = |key: &str| found(config.editors.get(key).cloned(), format!("editors.{}", key));
+
+    entry(tool)
+        .or_else(|| format.and_then(entry))
+        .or_else(|| found(config.general.editor.clone(), "general.editor".to_string()))
+        .or_else(|| found(var("EDITOR"), "$EDITOR".to_string()))
+        .or_else(|| found(var("VISUAL"), "$VISUAL".to_string()))
+        .unwrap_or_else(|| EditorCommand {
+            template: "vi".to_string(),
+            source: "default".to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> DotsmithConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn resolve_in(
+        tool: &str,
+        format: Option<&str>,
+        config: &DotsmithConfig,
+        env: &[(&str, &str)],
+    ) -> String {
+        let var = |name: &str| {
+            env.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        };
+        resolve_with(tool, format, config, var).template
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let full = config(
+            "[general]\neditor = \"hx\"\n\
+             [editors]\nneovim = \"nvim\"\njson = \"code --wait {path}\"\n",
+        );
+        let env = [("EDITOR", "nano"), ("VISUAL", "emacs")];
+
+        // Tool entry beats the format entry
+        assert_eq!(resolve_in("neovim", Some("json"), &full, &env), "nvim");
+        assert_eq!(resolve_in("waybar", Some("json"), &full, &env), "code --wait {path}");
+        assert_eq!(resolve_in("tmux", Some("tmux"), &full, &env), "hx");
+
+        // Then the environment, then vi
+        let plain = DotsmithConfig::default();
+        assert_eq!(resolve_in("tmux", None, &plain, &env), "nano");
+        let visual_only = [("EDITOR", ""), ("VISUAL", "emacs")];
+        assert_eq!(resolve_in("tmux", None, &plain, &visual_only), "emacs");
+        assert_eq!(resolve_in("tmux", None, &plain, &[]), "vi");
+    }
+
+    #[test]
+    fn test_resolve_skips_blank_entries() {
+        let config = config("[general]\neditor = \"\"\n[editors]\ntmux = \" \"\n");
+        let editor = resolve_with("tmux", None, &config, |_| Some("nano".to_string()));
+        assert_eq!(editor.template, "nano");
+        assert_eq!(editor.source, "$EDITOR");
+    }
+
+    fn args(template: &str, paths: &[&str]) -> Vec<String> {
+        let editor = EditorCommand {
+            template: template.to_string(),
+            source: String::new(),
+        };
+        let paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        let cmd = editor.command(&paths).unwrap();
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|s| s.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_command_fills_in_paths() {
+        assert_eq!(args("nvim", &["/a.lua", "/b.lua"]), ["nvim", "/a.lua", "/b.lua"]);
+        assert_eq!(
+            args("code --wait {path} --new-window", &["/a.json"]),
+            ["code", "--wait", "/a.json", "--new-window"]
+        );
+        assert_eq!(args("subl -w {path}:1", &["/a", "/b"]), ["subl", "-w", "/a:1", "/b:1"]);
+        assert!(
+            EditorCommand {
+                template: "  ".to_string(),
+                source: String::new()
+            }
+            .command(&[])
+            .is_err()
+        );
+    }
+}
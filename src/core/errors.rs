@@ -21,6 +21,12 @@ pub enum DotsmithError {
     #[allow(dead_code)]
     PathTraversal { path: String, resolved: String },
 
+    #[error("HOME is not set; set it or pass --config-dir")]
+    HomeNotSet,
+
+    #[error("'~user' paths are not supported: '{0}' — use '~/' or an absolute path")]
+    UnsupportedUserTilde(String),
+
     #[error("plugin '{0}' is already installed for {1}")]
     PluginAlreadyInstalled(String, String),
 
@@ -39,15 +45,30 @@ pub enum DotsmithError {
     #[error("git pull failed for '{0}': {1}")]
     GitPullFailed(String, String),
 
+    #[error("git fetch failed for '{0}': {1}")]
+    GitFetchFailed(String, String),
+
+    #[error("git checkout of '{0}' failed: {1}")]
+    GitCheckoutFailed(String, String),
+
     #[error("invalid plugin repository specifier: '{0}'")]
     InvalidPluginRepo(String),
 
     #[error("plugin management is not supported for '{0}'")]
     PluginsNotSupported(String),
 
+    #[error("plugins for '{0}' are managed by {1} — add, remove, and update them with {1}")]
+    PluginsManagedExternally(String, String),
+
+    #[error("no plugin lockfile at {0} — run `dotsmith plugins <tool> lock` first")]
+    LockfileNotFound(String),
+
     #[error("profile '{0}' already exists — use a different name or delete it first")]
     ProfileAlreadyExists(String),
 
+    #[error("profile '{0}' already exists — pass --force to replace it")]
+    ProfileImportConflict(String),
+
     #[error("profile '{0}' not found")]
     ProfileNotFound(String),
 
@@ -57,6 +78,61 @@ pub enum DotsmithError {
     #[error("ssh is not installed — required for remote deploy")]
     SshNotInstalled,
 
+    #[error("ssh to '{0}' failed: {1}")]
+    SshFailed(String, String),
+
     #[error("scp to '{0}' failed for file '{1}': {2}")]
     ScpFailed(String, String, String),
+
+    #[error("template '{0}' uses unset environment variable(s): {1}")]
+    TemplateEnvMissing(String, String),
+
+    #[error("saved view '{0}' not found — see `dotsmith explore --list-views`")]
+    ViewNotFound(String),
+
+    #[error("invalid view name '{0}' — use only letters, digits, hyphens, and underscores")]
+    InvalidViewName(String),
+
+    #[error("invalid tag '{0}' — use only letters, digits, dots, hyphens, and underscores")]
+    InvalidTagName(String),
+
+    #[error("no snapshot is tagged '{0}'")]
+    TagNotFound(String),
+
+    #[error("encrypted data needs a passphrase — set DOTSMITH_PASSPHRASE or run from a terminal")]
+    PassphraseRequired,
+
+    #[error("failed to decrypt — wrong passphrase, or the data is corrupted")]
+    DecryptFailed,
+
+    #[error("unknown keymap action '{0}' — see the TUI docs for action names")]
+    UnknownKeymapAction(String),
+
+    #[error("invalid key '{0}' for '{1}' in [keymap] — use a character, key name, or ctrl-<key>")]
+    InvalidKey(String, String),
+
+    #[error("{0} may contain a secret — pass --no-secrets-check to store it anyway:\n{1}")]
+    SecretsFound(String, String),
+
+    #[error("doctor found {0} error(s)")]
+    DoctorErrors(usize),
+
+    #[error("doctor found {0} warning(s)")]
+    DoctorWarnings(usize),
+}
+
+impl DotsmithError {
+    /// Process exit code for this error. Failed `doctor --strict` checks get
+    /// their own codes so CI can tell them apart from dotsmith failing:
+    ///
+    /// - 2: `DoctorErrors`
+    /// - 3: `DoctorWarnings`
+    /// - 1: everything else
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::DoctorErrors(_) => 2,
+            Self::DoctorWarnings(_) => 3,
+            _ => 1,
+        }
+    }
 }
@@ -0,0 +1,342 @@
+use crate::core::module::OptionEntry;
+use crate::core::render;
+use crate::core::setting::{self, Setting};
+
+/// Output format for a multi-tool option reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SheetFormat {
+    /// Markdown with a section per tool and per category
+    Md,
+    /// Plain text with underlined headings
+    Txt,
+}
+
+impl SheetFormat {
+    /// Parse a format name as given on the command line (`md` or `txt`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "md" | "markdown" => Some(SheetFormat::Md),
+            "txt" | "text" => Some(SheetFormat::Txt),
+            _ => None,
+        }
+    }
+
+    /// Pick a format from an output file's extension; Markdown unless it's `.txt`.
+    pub fn from_path(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("txt") => SheetFormat::Txt,
+            _ => SheetFormat::Md,
+        }
+    }
+}
+
+/// File extension for a generated snippet in a tool's config format.
+pub fn snippet_extension(config_format: &str) -> &'static str {
+    match config_format {
+        "tmux" => "conf",
+        "git" => "gitconfig",
+        "shell" => "zsh",
+        "lua" => "lua",
+        "key-value" => "conf",
+        "toml" => "toml",
+        _ => "conf",
+    }
+}
+
+/// Build a commented-out config snippet listing `options`, in the comment
+/// syntax of the file type given by `ext`.
+pub fn config_snippet(tool_name: &str, ext: &str, options: &[&OptionEntry]) -> String {
+    let mut content = format!(
+        "# Generated by dotsmith — {} configuration options\n",
+        tool_name
+    );
+    content.push_str(&format!(
+        "# {} option(s) included\n",
+        options.len()
+    ));
+    content.push('\n');
+
+    let comment = match ext {
+        "lua" => "--",
+        _ => "#",
+    };
+
+    let mut current_category = String::new();
+    for opt in options {
+        if opt.category != current_category {
+            current_category = opt.category.clone();
+            content.push_str(&format!(
+                "{} Category: {}\n{}\n",
+                comment, current_category, comment
+            ));
+        }
+
+        content.push_str(&format!("{} {} ({})\n", comment, opt.name, type_and_default(opt)));
+        content.push_str(&format!("{} {}\n", comment, opt.description));
+
+        if let Some(ref example) = opt.example {
+            content.push_str(&format!("{} {}\n", comment, example));
+        }
+
+        content.push_str(&format!("{}\n", comment));
+    }
+
+    content
+}
+
+/// Build a usable config setting each of `options` with
+/// [`render::live_setting`], in the tool's own syntax. Options are grouped
+/// by category and described in comments like [`config_snippet`]; formats
+/// with sections get one header per section, with top-level keys first. An
+/// option with no usable value keeps its example as a comment.
+pub fn live_config(
+    tool_name: &str,
+    config_format: &str,
+    set_format: Option<&str>,
+    options: &[&OptionEntry],
+) -> String {
+    let comment = match config_format {
+        "lua" => "--",
+        _ => "#",
+    };
+
+    let rendered: Vec<(&OptionEntry, Setting, bool)> = options
+        .iter()
+        .map(|&opt| match render::live_setting(opt, config_format, set_format) {
+            Some(setting) => (opt, setting, true),
+            // Still needs its header, to sit with its section
+            None => (opt, setting::render(config_format, set_format, opt, ""), false),
+        })
+        .collect();
+    let live = rendered.iter().filter(|(_, _, live)| *live).count();
+
+    let mut content = format!(
+        "{} Generated by dotsmith — {} configuration\n",
+        comment, tool_name
+    );
+    content.push_str(&format!(
+        "{} {} option(s) set, {} left as comments\n",
+        comment,
+        live,
+        rendered.len() - live
+    ));
+
+    // Top-level keys must come before the first section header
+    let mut sections: Vec<Option<&str>> = vec![None];
+    for (_, setting, _) in &rendered {
+        if !sections.contains(&setting.header.as_deref()) {
+            sections.push(setting.header.as_deref());
+        }
+    }
+
+    for section in sections {
+        let mut current_category = "";
+        let in_section = rendered
+            .iter()
+            .filter(|(_, setting, _)| setting.header.as_deref() == section);
+        for (opt, setting, live) in in_section {
+            if current_category.is_empty()
+                && let Some(header) = section
+            {
+                content.push_str(&format!("\n{}\n", header));
+            }
+            if opt.category != current_category {
+                current_category = &opt.category;
+                content.push_str(&format!(
+                    "\n{} Category: {}\n{}\n",
+                    comment, current_category, comment
+                ));
+            }
+
+            content.push_str(&format!("{} {} ({})\n", comment, opt.name, type_and_default(opt)));
+            content.push_str(&format!("{} {}\n", comment, opt.description));
+            if *live {
+                content.push_str(&format!("{}\n", setting.line));
+            } else if opt.example.is_some() {
+                // With nothing to set, this is the example commented out
+                content.push_str(&format!("{}\n", render::render_option(opt, config_format)));
+            }
+            content.push_str(&format!("{}\n", comment));
+        }
+    }
+
+    content
+}
+
+/// Build one reference document covering several tools, a section per tool
+/// and a subsection per category.
+pub fn cheatsheet(tools: &[(&str, Vec<OptionEntry>)], format: SheetFormat) -> String {
+    let total: usize = tools.iter().map(|(_, opts)| opts.len()).sum();
+    let mut out = String::new();
+
+    match format {
+        SheetFormat::Md => {
+            out.push_str("# Config cheatsheet\n\n");
+            out.push_str(&format!(
+                "Generated by dotsmith — {} option(s) across {} tool(s).\n",
+                total,
+                tools.len()
+            ));
+        }
+        SheetFormat::Txt => {
+            out.push_str(&underline("Config cheatsheet", '='));
+            out.push_str(&format!(
+                "Generated by dotsmith -- {} option(s) across {} tool(s).\n",
+                total,
+                tools.len()
+            ));
+        }
+    }
+
+    for (tool, options) in tools {
+        out.push('\n');
+        match format {
+            SheetFormat::Md => out.push_str(&format!("## {}\n", tool)),
+            SheetFormat::Txt => out.push_str(&underline(tool, '=')),
+        }
+
+        let mut current_category = "";
+        for opt in options {
+            if opt.category != current_category {
+                current_category = &opt.category;
+                out.push('\n');
+                match format {
+                    SheetFormat::Md => out.push_str(&format!("### {}\n\n", current_category)),
+                    SheetFormat::Txt => {
+                        out.push_str(&underline(current_category, '-'));
+                        out.push('\n');
+                    }
+                }
+            }
+
+            match format {
+                SheetFormat::Md => {
+                    out.push_str(&format!(
+                        "- **`{}`** ({}) — {}\n",
+                        opt.name,
+                        type_and_default(opt),
+                        opt.description
+                    ));
+                    if let Some(ref example) = opt.example {
+                        out.push_str(&format!("  - Example: `{}`\n", example));
+                    }
+                }
+                SheetFormat::Txt => {
+                    out.push_str(&format!("  {} ({})\n", opt.name, type_and_default(opt)));
+                    out.push_str(&format!("      {}\n", opt.description));
+                    if let Some(ref example) = opt.example {
+                        out.push_str(&format!("      e.g. {}\n", example));
+                    }
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn type_and_default(opt: &OptionEntry) -> String {
+    let type_str = format!("{:?}", opt.option_type).to_lowercase();
+    match opt.default.as_deref().filter(|d| !d.is_empty()) {
+        Some(d) => format!("{}, default: {}", type_str, d),
+        None => type_str,
+    }
+}
+
+fn underline(title: &str, ch: char) -> String {
+    format!("{}\n{}\n", title, ch.to_string().repeat(title.chars().count()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::module::ModuleRegistry;
+
+    fn options(tool: &str) -> Vec<OptionEntry> {
+        ModuleRegistry::get_options(tool).unwrap().options
+    }
+
+    #[test]
+    fn test_cheatsheet_markdown_sections() {
+        let tools = vec![("tmux", options("tmux")), ("git", options("git"))];
+        let md = cheatsheet(&tools, SheetFormat::Md);
+
+        assert!(md.starts_with("# Config cheatsheet\n"));
+        let tmux = md.find("\n## tmux\n").unwrap();
+        let git = md.find("\n## git\n").unwrap();
+        assert!(tmux < git, "sections follow the requested tool order");
+        assert!(md.contains("- **`mouse`** (boolean, default: off)"));
+        assert!(md.contains("### user"));
+    }
+
+    #[test]
+    fn test_cheatsheet_text() {
+        let txt = cheatsheet(&[("git", options("git"))], SheetFormat::Txt);
+        assert!(txt.contains("git\n===\n"));
+        assert!(txt.contains("  user.name (string"));
+        assert!(!txt.contains("**"));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        use std::path::Path;
+        assert_eq!(SheetFormat::from_path(Path::new("a.txt")), SheetFormat::Txt);
+        assert_eq!(SheetFormat::from_path(Path::new("a.md")), SheetFormat::Md);
+        assert_eq!(SheetFormat::from_path(Path::new("a")), SheetFormat::Md);
+    }
+
+    #[test]
+    fn test_config_snippet_comment_style() {
+        let opts = options("neovim");
+        let refs: Vec<&OptionEntry> = opts.iter().take(2).collect();
+        let lua = config_snippet("neovim", "lua", &refs);
+        assert!(lua.contains("-- Category:"));
+        assert!(lua.contains("# 2 option(s) included"));
+    }
+
+    #[test]
+    fn test_live_config_tmux() {
+        let opts = options("tmux");
+        let refs: Vec<&OptionEntry> = opts.iter().filter(|o| o.name == "mouse").collect();
+        let conf = live_config("tmux", "tmux", None, &refs);
+        assert!(conf.contains("\n# mouse (boolean, default: off)\n"));
+        assert!(conf.contains("\nset -g mouse off\n"));
+        assert!(conf.contains("# 1 option(s) set, 0 left as comments"));
+    }
+
+    #[test]
+    fn test_live_config_groups_sections() {
+        let opts = options("git");
+        let refs: Vec<&OptionEntry> = opts.iter().collect();
+        let conf = live_config("git", "git", None, &refs);
+
+        // Each section header appears once, with its keys under it
+        assert_eq!(conf.matches("\n[merge]\n").count(), 1);
+        let merge = conf.find("[merge]").unwrap();
+        let style = conf.find("\tconflictstyle = merge").unwrap();
+        assert!(merge < style);
+        assert!(conf[merge..style].lines().skip(1).all(|l| !l.starts_with('[')));
+
+        // user.name has no default, so only its example is kept, commented
+        assert!(conf.contains("# git config --global user.name 'Jane Doe'"));
+        assert!(!conf.contains("\tname ="));
+    }
+
+    #[test]
+    fn test_live_config_toml_top_level_first() {
+        let mut opts: Vec<OptionEntry> = options("alacritty")
+            .into_iter()
+            .filter(|o| o.name.starts_with("window."))
+            .collect();
+        let mut top = opts[0].clone();
+        top.name = "live_config_reload".to_string();
+        opts.push(top);
+
+        let refs: Vec<&OptionEntry> = opts.iter().collect();
+        let conf = live_config("alacritty", "toml", None, &refs);
+        // A top-level key listed last still goes before the first table
+        let reload = conf.find("\nlive_config_reload = ").unwrap();
+        assert!(reload < conf.find("\n[window").unwrap());
+        assert!(conf.parse::<toml::Table>().is_ok(), "{}", conf);
+    }
+}
@@ -0,0 +1,162 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::util;
+
+/// Shell snippet that snapshots tracked configs changed since their last
+/// snapshot. It runs in the background so shell startup isn't slowed down.
+const WATCH_HOOK: &str = "\
+# Generated by dotsmith — snapshot tracked configs that changed since the
+# last snapshot. Source this from your shell rc file.
+if command -v dotsmith >/dev/null 2>&1; then
+    (dotsmith watch --once >/dev/null 2>&1 &)
+fi
+";
+
+/// Shells `dotsmith hook` can print an exit hook for.
+pub const EXIT_HOOK_SHELLS: &[&str] = &["zsh", "bash", "fish"];
+
+const ZSH_EXIT_HOOK: &str = r#"# Generated by `dotsmith hook zsh` — snapshot tracked configs
+# when the shell exits. Add to ~/.zshrc: eval "$(dotsmith hook zsh)"
+_dotsmith_exit_snapshot() {
+    [[ -n "$DOTSMITH_IN_EXIT_HOOK" ]] && return
+    command -v dotsmith >/dev/null 2>&1 || return
+    env DOTSMITH_IN_EXIT_HOOK=1 dotsmith --quiet snapshot -m "auto-snapshot (shell exit)" \
+        >/dev/null 2>&1
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook zshexit _dotsmith_exit_snapshot
+"#;
+
+const BASH_EXIT_HOOK: &str = r#"# Generated by `dotsmith hook bash` — snapshot tracked configs
+# when the shell exits. Add to ~/.bashrc: eval "$(dotsmith hook bash)"
+_dotsmith_exit_snapshot() {
+    [ -n "$DOTSMITH_IN_EXIT_HOOK" ] && return
+    command -v dotsmith >/dev/null 2>&1 || return
+    env DOTSMITH_IN_EXIT_HOOK=1 dotsmith --quiet snapshot -m "auto-snapshot (shell exit)" \
+        >/dev/null 2>&1
+}
+_dotsmith_install_exit_hook() {
+    # Keep any existing EXIT trap, and don't add ours twice
+    local prev=""
+    eval "set -- $(trap -p EXIT)"
+    [ $# -ge 3 ] && prev=$3
+    case "$prev" in
+        *_dotsmith_exit_snapshot*) ;;
+        *) trap "_dotsmith_exit_snapshot${prev:+; $prev}" EXIT ;;
+    esac
+}
+_dotsmith_install_exit_hook
+unset -f _dotsmith_install_exit_hook
+"#;
+
+const FISH_EXIT_HOOK: &str = r#"# Generated by `dotsmith hook fish` — snapshot tracked configs
+# when the shell exits. Add to ~/.config/fish/config.fish: dotsmith hook fish | source
+function _dotsmith_exit_snapshot --on-event fish_exit
+    set -q DOTSMITH_IN_EXIT_HOOK; and return
+    command -q dotsmith; or return
+    env DOTSMITH_IN_EXIT_HOOK=1 dotsmith --quiet snapshot -m "auto-snapshot (shell exit)" \
+        >/dev/null 2>&1
+end
+"#;
+
+/// Shell code that snapshots tracked configs when the shell exits, for
+/// `eval`/`source` from an rc file. Loading it more than once registers the
+/// hook once, and `DOTSMITH_IN_EXIT_HOOK` keeps shells started by the
+/// snapshot itself from triggering it again. `None` for unsupported shells.
+pub fn exit_hook(shell: &str) -> Option<&'static str> {
+    match shell {
+        "zsh" => Some(ZSH_EXIT_HOOK),
+        "bash" => Some(BASH_EXIT_HOOK),
+        "fish" => Some(FISH_EXIT_HOOK),
+        _ => None,
+    }
+}
+
+/// Where the watch hook lives inside the config directory.
+pub fn watch_hook_path(config_dir: &Path) -> PathBuf {
+    config_dir.join("hooks").join("watch.sh")
+}
+
+/// Whether the watch hook has been written.
+pub fn watch_hook_installed(config_dir: &Path) -> bool {
+    watch_hook_path(config_dir).is_file()
+}
+
+/// Write the watch hook script, replacing any older copy.
+pub fn install_watch_hook(config_dir: &Path) -> Result<PathBuf> {
+    let path = watch_hook_path(config_dir);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    util::fs::atomic_write(&path, WATCH_HOOK)?;
+    Ok(path)
+}
+
+/// Remove the watch hook script. Returns whether there was one.
+pub fn remove_watch_hook(config_dir: &Path) -> Result<bool> {
+    let path = watch_hook_path(config_dir);
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+    Ok(true)
+}
+
+/// The line to add to a shell rc file to enable the hook.
+pub fn source_line(hook_path: &Path) -> String {
+    format!("source {}", util::paths::contract_tilde(hook_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_exit_hook_mechanisms() {
+        let zsh = exit_hook("zsh").unwrap();
+        assert!(zsh.contains("add-zsh-hook zshexit _dotsmith_exit_snapshot"));
+
+        let bash = exit_hook("bash").unwrap();
+        assert!(bash.contains("trap -p EXIT"));
+        assert!(bash.contains("EXIT ;;"));
+
+        let fish = exit_hook("fish").unwrap();
+        assert!(fish.contains("--on-event fish_exit"));
+
+        assert!(exit_hook("tcsh").is_none());
+    }
+
+    #[test]
+    fn test_exit_hooks_snapshot_with_recursion_guard() {
+        for shell in EXIT_HOOK_SHELLS {
+            let hook = exit_hook(shell).unwrap();
+            assert!(hook.contains("dotsmith --quiet snapshot"), "{}", shell);
+            assert!(hook.contains("env DOTSMITH_IN_EXIT_HOOK=1"), "{}", shell);
+            let guarded = hook.contains("-n \"$DOTSMITH_IN_EXIT_HOOK\"")
+                || hook.contains("set -q DOTSMITH_IN_EXIT_HOOK");
+            assert!(guarded, "{}", shell);
+        }
+    }
+
+    #[test]
+    fn test_install_and_remove_watch_hook() {
+        let tmp = TempDir::new().unwrap();
+        assert!(!watch_hook_installed(tmp.path()));
+
+        let path = install_watch_hook(tmp.path()).unwrap();
+        assert!(watch_hook_installed(tmp.path()));
+        let script = std::fs::read_to_string(&path).unwrap();
+        assert!(script.contains("dotsmith watch --once"));
+
+        // Reinstalling just rewrites the script
+        install_watch_hook(tmp.path()).unwrap();
+
+        assert!(remove_watch_hook(tmp.path()).unwrap());
+        assert!(!remove_watch_hook(tmp.path()).unwrap());
+        assert!(!watch_hook_installed(tmp.path()));
+    }
+}
@@ -0,0 +1,233 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::core::manifest::Manifest;
+use crate::util;
+
+/// A file (or folded directory) of a stow package and where it lives in `$HOME`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigPath {
+    /// The file inside the dotfiles repo.
+    pub source: PathBuf,
+    /// Where stow links it, e.g. `~/.config/nvim/init.lua`.
+    pub target: PathBuf,
+    /// Whether `target` is already a symlink to `source`.
+    pub deployed: bool,
+}
+
+/// Map a stow-style dotfiles repo to tools: each top-level directory is a
+/// package named after its tool, and its contents mirror `$HOME`.
+///
+/// A directory stow has folded into a single symlink is returned as one
+/// deployed path; otherwise every file is listed. Hidden top-level entries
+/// (`.git`, `.github`) and loose files like `README.md` are skipped.
+pub fn scan_stow_repo(path: &Path) -> Result<Vec<(String, Vec<ConfigPath>)>> {
+    scan_stow_repo_into(path, &util::paths::home_dir()?)
+}
+
+fn scan_stow_repo_into(path: &Path, home: &Path) -> Result<Vec<(String, Vec<ConfigPath>)>> {
+    if !path.is_dir() {
+        anyhow::bail!("{} is not a directory", path.display());
+    }
+    // Deployed links resolve to canonical paths, so compare against those
+    let repo = path
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", path.display()))?;
+
+    let mut packages = Vec::new();
+    for entry in sorted_entries(&repo)? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || !entry.path().is_dir() {
+            continue;
+        }
+        let mut paths = Vec::new();
+        collect_package(&entry.path(), home, &mut paths)?;
+        if !paths.is_empty() {
+            packages.push((name, paths));
+        }
+    }
+    Ok(packages)
+}
+
+fn collect_package(
+    source_dir: &Path,
+    target_dir: &Path,
+    paths: &mut Vec<ConfigPath>,
+) -> Result<()> {
+    for entry in sorted_entries(source_dir)? {
+        let name = entry.file_name();
+        if name == ".git" || name == ".stow-local-ignore" {
+            continue;
+        }
+        let source = entry.path();
+        let target = target_dir.join(&name);
+        let deployed = links_to(&target, &source);
+
+        if source.is_dir() && !deployed {
+            collect_package(&source, &target, paths)?;
+        } else {
+            paths.push(ConfigPath {
+                source,
+                target,
+                deployed,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<fs::DirEntry>> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .flatten()
+        .collect();
+    entries.sort_by_key(|e| e.file_name());
+    Ok(entries)
+}
+
+/// Whether `target` is a symlink resolving to `source`.
+fn links_to(target: &Path, source: &Path) -> bool {
+    util::fs::is_symlink(target)
+        && matches!(
+            (target.canonicalize(), source.canonicalize()),
+            (Ok(t), Ok(s)) if t == s
+        )
+}
+
+/// Whether a tool in `manifest` already tracks `path`, by its `$HOME` path or
+/// through a tracked path that resolves into the repo.
+pub fn already_tracked(manifest: &Manifest, path: &ConfigPath) -> bool {
+    let resolved = path.source.canonicalize().unwrap_or_else(|_| path.source.clone());
+    manifest
+        .tools
+        .values()
+        .flat_map(|entry| &entry.config_paths)
+        .map(|p| util::paths::expand_tilde(p))
+        .any(|tracked| {
+            path.target.starts_with(&tracked)
+                || tracked
+                    .canonicalize()
+                    .is_ok_and(|real| resolved.starts_with(real))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::manifest::ToolEntry;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    fn write(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "x").unwrap();
+    }
+
+    /// A repo with a flat package, a nested package, and some clutter.
+    fn fake_stow(tmp: &TempDir) -> (PathBuf, PathBuf) {
+        let repo = tmp.path().join("dotfiles");
+        let home = tmp.path().join("home");
+        write(&repo.join("tmux/.tmux.conf"));
+        write(&repo.join("nvim/.config/nvim/init.lua"));
+        write(&repo.join("nvim/.config/nvim/lua/plugins/lsp.lua"));
+        write(&repo.join("nvim/.stow-local-ignore"));
+        write(&repo.join(".git/config"));
+        write(&repo.join("README.md"));
+        fs::create_dir_all(&home).unwrap();
+        (repo.canonicalize().unwrap(), home)
+    }
+
+    fn targets(paths: &[ConfigPath]) -> Vec<PathBuf> {
+        paths.iter().map(|p| p.target.clone()).collect()
+    }
+
+    #[test]
+    fn test_scan_maps_packages_to_home() {
+        let tmp = TempDir::new().unwrap();
+        let (repo, home) = fake_stow(&tmp);
+
+        let packages = scan_stow_repo_into(&repo, &home).unwrap();
+        let names: Vec<&str> = packages.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["nvim", "tmux"]);
+
+        let (_, nvim) = &packages[0];
+        assert_eq!(
+            targets(nvim),
+            [
+                home.join(".config/nvim/init.lua"),
+                home.join(".config/nvim/lua/plugins/lsp.lua"),
+            ]
+        );
+        assert_eq!(nvim[1].source, repo.join("nvim/.config/nvim/lua/plugins/lsp.lua"));
+        assert!(nvim.iter().all(|p| !p.deployed));
+
+        let (_, tmux) = &packages[1];
+        assert_eq!(targets(tmux), [home.join(".tmux.conf")]);
+    }
+
+    #[test]
+    fn test_scan_detects_deployed_links() {
+        let tmp = TempDir::new().unwrap();
+        let (repo, home) = fake_stow(&tmp);
+        // tmux linked file by file, nvim folded into one directory link
+        symlink(repo.join("tmux/.tmux.conf"), home.join(".tmux.conf")).unwrap();
+        fs::create_dir_all(home.join(".config")).unwrap();
+        symlink(repo.join("nvim/.config/nvim"), home.join(".config/nvim")).unwrap();
+
+        let packages = scan_stow_repo_into(&repo, &home).unwrap();
+        let (_, nvim) = &packages[0];
+        assert_eq!(
+            nvim,
+            &[ConfigPath {
+                source: repo.join("nvim/.config/nvim"),
+                target: home.join(".config/nvim"),
+                deployed: true,
+            }]
+        );
+        assert!(packages[1].1[0].deployed);
+
+        // A link pointing elsewhere isn't a deploy of this repo
+        fs::remove_file(home.join(".tmux.conf")).unwrap();
+        write(&tmp.path().join("other.conf"));
+        symlink(tmp.path().join("other.conf"), home.join(".tmux.conf")).unwrap();
+        let packages = scan_stow_repo_into(&repo, &home).unwrap();
+        assert!(!packages[1].1[0].deployed);
+    }
+
+    #[test]
+    fn test_scan_rejects_missing_repo() {
+        let tmp = TempDir::new().unwrap();
+        assert!(scan_stow_repo_into(&tmp.path().join("nope"), tmp.path()).is_err());
+    }
+
+    #[test]
+    fn test_already_tracked_through_repo_path() {
+        let tmp = TempDir::new().unwrap();
+        let (repo, home) = fake_stow(&tmp);
+        let packages = scan_stow_repo_into(&repo, &home).unwrap();
+        let nvim = &packages[0].1;
+
+        let mut manifest = Manifest::default();
+        assert!(!already_tracked(&manifest, &nvim[0]));
+
+        // Tracking the package directory in the repo covers every file in it
+        manifest
+            .add_tool(
+                "neovim",
+                ToolEntry {
+                    tier: 2,
+                    config_paths: vec![repo.join("nvim/.config/nvim").display().to_string()],
+                    plugins_managed: false,
+                    plugin_manager: None,
+                    added_at: chrono::Utc::now(),
+                    last_snapshot: None,
+                    plugins: Default::default(),
+                },
+            )
+            .unwrap();
+        assert!(nvim.iter().all(|p| already_tracked(&manifest, p)));
+        assert!(!already_tracked(&manifest, &packages[1].1[0]));
+    }
+}
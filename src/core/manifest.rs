@@ -58,6 +58,14 @@ pub struct PluginEntry {
 
     /// When this plugin was added
     pub added_at: DateTime<Utc>,
+
+    /// Commit the plugin is frozen at. Pinned plugins are skipped by updates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_ref: Option<String>,
+
+    /// Position in the generated loader; lower loads first, ties by name.
+    #[serde(default)]
+    pub order: u32,
 }
 
 impl Manifest {
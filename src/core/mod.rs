@@ -1,14 +1,29 @@
+pub mod audit;
 pub mod config;
+pub mod crypto;
 pub mod deploy;
 pub mod detect;
+pub mod editor;
 pub mod errors;
+pub mod fix;
+pub mod generate;
+pub mod hook;
+pub mod import;
 pub mod manifest;
 pub mod module;
+pub mod output;
+pub mod patch;
+pub mod perms;
 pub mod plugin;
 pub mod plugin_info;
 pub mod profile;
 pub mod reload;
+pub mod render;
 pub mod remote;
 pub mod repo;
+pub mod search;
+pub mod secrets;
+pub mod setting;
 pub mod snapshot;
+pub mod template;
 pub mod validate;
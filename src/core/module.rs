@@ -1,3 +1,6 @@
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 /// Metadata about a supported tool, loaded from module.toml.
@@ -34,6 +37,11 @@ pub struct ModuleMetadata {
     /// Config file format (determines which parser to use).
     pub config_format: String,
 
+    /// Line template for setting an option from the explorer; `{name}` and
+    /// `{value}` are replaced. Defaults to the config format's usual syntax.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set_format: Option<String>,
+
     /// Whether dotsmith can manage plugins for this tool.
     #[serde(default)]
     pub plugins_supported: bool,
@@ -118,12 +126,90 @@ pub enum OptionType {
     KeyBinding,
 }
 
+impl OptionType {
+    /// Type names as written in options.toml, in declaration order.
+    pub const NAMES: &'static [&'static str] = &[
+        "boolean",
+        "string",
+        "integer",
+        "float",
+        "enum",
+        "color",
+        "path",
+        "list",
+        "keybinding",
+    ];
+
+    /// Parse a type name as written in options.toml (e.g. `boolean`, `keybinding`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "boolean" => Some(OptionType::Boolean),
+            "string" => Some(OptionType::String),
+            "integer" => Some(OptionType::Integer),
+            "float" => Some(OptionType::Float),
+            "enum" => Some(OptionType::Enum),
+            "color" => Some(OptionType::Color),
+            "path" => Some(OptionType::Path),
+            "list" => Some(OptionType::List),
+            "keybinding" => Some(OptionType::KeyBinding),
+            _ => None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Validation rule types
+// ---------------------------------------------------------------------------
+
+/// Line-based syntax rules for a tool's config, loaded from validate.toml.
+///
+/// Each non-blank line that isn't a comment or an ignored line is checked in
+/// turn: `[section]` headers (when `sections` is set), then the first word
+/// against `known_commands`, then the line against `separators`. Empty lists
+/// skip that check.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ValidationRules {
+    /// Lines starting with any of these are comments.
+    #[serde(default = "default_comment_prefixes")]
+    pub comment_prefixes: Vec<String>,
+
+    /// Lines (after trimming) that are accepted as-is.
+    #[serde(default)]
+    pub ignore_lines: Vec<String>,
+
+    /// Whether `[section]` headers are allowed; they must be closed.
+    #[serde(default)]
+    pub sections: bool,
+
+    /// Words a line may start with.
+    #[serde(default)]
+    pub known_commands: Vec<String>,
+
+    /// A line must contain at least one of these.
+    #[serde(default)]
+    pub separators: Vec<String>,
+
+    /// What a line failing the separator check should have been, for errors.
+    #[serde(default = "default_expected")]
+    pub expected: String,
+}
+
+fn default_comment_prefixes() -> Vec<String> {
+    vec!["#".to_string()]
+}
+
+fn default_expected() -> String {
+    "key-value pair".to_string()
+}
+
 // ---------------------------------------------------------------------------
 // Module registry (embedded Tier 1 data)
 // ---------------------------------------------------------------------------
 
 /// Registry of built-in modules. Uses `include_str!` to embed module data
-/// in the binary at compile time.
+/// in the binary at compile time. The `load_*` functions also look in
+/// `<config_dir>/modules/<name>/`, so users can describe tools dotsmith
+/// doesn't ship or replace a built-in's data.
 pub struct ModuleRegistry;
 
 impl ModuleRegistry {
@@ -172,13 +258,102 @@ impl ModuleRegistry {
         }
     }
 
+    /// Get the built-in validation rules for a tool, if it has any.
+    pub fn get_validation(name: &str) -> Option<ValidationRules> {
+        let toml_str = match name {
+            "git" => include_str!("../../data/modules/git/validate.toml"),
+            "kitty" => include_str!("../../data/modules/kitty/validate.toml"),
+            "tmux" => include_str!("../../data/modules/tmux/validate.toml"),
+            _ => return None,
+        };
+        // Parsing is covered by the tests, so a failure here can't ship
+        toml::from_str(toml_str).ok()
+    }
+
+    /// Validation rules for a tool: `<config_dir>/modules/<name>/validate.toml`
+    /// when present, so rules can be changed or added without rebuilding,
+    /// otherwise the built-in rules.
+    pub fn load_validation(config_dir: &Path, name: &str) -> Option<ValidationRules> {
+        read_user_file(&config_dir.join("modules").join(name).join("validate.toml"))
+            .and_then(Result::ok)
+            .or_else(|| Self::get_validation(name))
+    }
+
+    /// Module definition for a tool: `<config_dir>/modules/<name>/module.toml`
+    /// when present and valid, otherwise the built-in module.
+    pub fn load_module(config_dir: &Path, name: &str) -> Option<ModuleDefinition> {
+        read_user_file(&config_dir.join("modules").join(name).join("module.toml"))
+            .and_then(Result::ok)
+            .or_else(|| Self::get_builtin(name))
+    }
+
+    /// Option database for a tool: `<config_dir>/modules/<name>/options.toml`
+    /// when present and valid, otherwise the built-in database.
+    pub fn load_options(config_dir: &Path, name: &str) -> Option<OptionDatabase> {
+        read_user_file(&config_dir.join("modules").join(name).join("options.toml"))
+            .and_then(Result::ok)
+            .or_else(|| Self::get_options(name))
+    }
+
+    /// A warning for each file under `<config_dir>/modules/` that exists but
+    /// doesn't parse. The `load_*` functions quietly fall back to the
+    /// built-in data for those, so the CLI and TUI report these instead.
+    pub fn user_module_warnings(config_dir: &Path) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(config_dir.join("modules")) else {
+            return Vec::new();
+        };
+        let mut dirs: Vec<_> = entries.flatten().map(|entry| entry.path()).collect();
+        dirs.sort();
+
+        let mut warnings = Vec::new();
+        for dir in dirs {
+            let module = dir.join("module.toml");
+            if let Some(Err(e)) = read_user_file::<ModuleDefinition>(&module) {
+                warnings.push(format!("failed to parse {}: {}", module.display(), e));
+            }
+            let options = dir.join("options.toml");
+            if let Some(Err(e)) = read_user_file::<OptionDatabase>(&options) {
+                warnings.push(format!("failed to parse {}: {}", options.display(), e));
+            }
+            let validate = dir.join("validate.toml");
+            if let Some(Err(e)) = read_user_file::<ValidationRules>(&validate) {
+                warnings.push(format!("failed to parse {}: {}", validate.display(), e));
+            }
+        }
+        warnings
+    }
+
+    /// Built-in module names plus every `<config_dir>/modules/<name>/` that
+    /// has a module.toml or options.toml, sorted.
+    pub fn module_names(config_dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = Self::builtin_names().iter().map(|n| n.to_string()).collect();
+        if let Ok(entries) = std::fs::read_dir(config_dir.join("modules")) {
+            for entry in entries.flatten() {
+                let dir = entry.path();
+                let has_data =
+                    dir.join("module.toml").is_file() || dir.join("options.toml").is_file();
+                if has_data && let Some(name) = entry.file_name().to_str() {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+
     /// List all built-in module names.
-    #[allow(dead_code)]
     pub fn builtin_names() -> &'static [&'static str] {
         &["alacritty", "awesomewm", "git", "kitty", "neovim", "tmux", "zsh"]
     }
 }
 
+/// Parse a user module file, or `None` if it doesn't exist.
+fn read_user_file<T: DeserializeOwned>(path: &Path) -> Option<Result<T, toml::de::Error>> {
+    let content = std::fs::read_to_string(path).ok()?;
+    Some(toml::from_str(&content))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,6 +398,129 @@ mod tests {
         assert_eq!(names.len(), 7);
     }
 
+    #[test]
+    fn test_load_validation_rules() {
+        let tmux = ModuleRegistry::get_validation("tmux").expect("tmux rules should exist");
+        assert!(tmux.known_commands.iter().any(|c| c == "bind-key"));
+        assert_eq!(tmux.ignore_lines, vec!["\\".to_string()]);
+
+        let git = ModuleRegistry::get_validation("git").expect("git rules should exist");
+        assert!(git.sections);
+        assert_eq!(git.comment_prefixes, vec!["#", ";"]);
+
+        assert!(ModuleRegistry::get_validation("kitty").is_some());
+        assert!(ModuleRegistry::get_validation("zsh").is_none());
+    }
+
+    #[test]
+    fn test_user_validation_overrides_builtin() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("modules").join("tmux");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("validate.toml"), "known_commands = [\"set\"]\n").unwrap();
+
+        let rules = ModuleRegistry::load_validation(tmp.path(), "tmux").unwrap();
+        assert_eq!(rules.known_commands, vec!["set"]);
+        assert_eq!(rules.comment_prefixes, vec!["#"]);
+
+        // Malformed overrides fall back to the built-in rules
+        std::fs::write(dir.join("validate.toml"), "known_commands = 1\n").unwrap();
+        let rules = ModuleRegistry::load_validation(tmp.path(), "tmux").unwrap();
+        assert!(rules.known_commands.len() > 1);
+        let warnings = ModuleRegistry::user_module_warnings(tmp.path());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("tmux/validate.toml"));
+
+        // Tools without built-in rules can get them from the config dir
+        let dir = tmp.path().join("modules").join("foot");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("validate.toml"), "separators = [\"=\"]\n").unwrap();
+        assert!(ModuleRegistry::load_validation(tmp.path(), "foot").is_some());
+    }
+
+    const BAT_MODULE: &str = r#"
+[metadata]
+name = "bat"
+display_name = "bat"
+description = "A cat clone with wings"
+homepage = "https://github.com/sharkdp/bat"
+config_paths = ["~/.config/bat/config"]
+detect_command = "bat --version"
+config_format = "key-value"
+"#;
+
+    const BAT_OPTIONS: &str = r#"
+[[options]]
+name = "--theme"
+type = "string"
+category = "appearance"
+description = "Syntax highlighting theme"
+"#;
+
+    #[test]
+    fn test_user_module_resolves() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        assert!(ModuleRegistry::load_module(tmp.path(), "bat").is_none());
+
+        let dir = tmp.path().join("modules").join("bat");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("module.toml"), BAT_MODULE).unwrap();
+        std::fs::write(dir.join("options.toml"), BAT_OPTIONS).unwrap();
+
+        let module = ModuleRegistry::load_module(tmp.path(), "bat").unwrap();
+        assert_eq!(module.metadata.name, "bat");
+        assert_eq!(module.metadata.detect_command, "bat --version");
+        assert!(!module.metadata.plugins_supported);
+
+        let db = ModuleRegistry::load_options(tmp.path(), "bat").unwrap();
+        assert_eq!(db.options.len(), 1);
+        assert_eq!(db.options[0].option_type, OptionType::String);
+
+        let names = ModuleRegistry::module_names(tmp.path());
+        assert!(names.contains(&"bat".to_string()));
+        assert!(names.contains(&"tmux".to_string()));
+        assert_eq!(names.len(), ModuleRegistry::builtin_names().len() + 1);
+    }
+
+    #[test]
+    fn test_user_module_overrides_builtin() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("modules").join("tmux");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("options.toml"), BAT_OPTIONS).unwrap();
+
+        let db = ModuleRegistry::load_options(tmp.path(), "tmux").unwrap();
+        assert_eq!(db.options.len(), 1);
+        // No module.toml override, so the built-in definition is used
+        let module = ModuleRegistry::load_module(tmp.path(), "tmux").unwrap();
+        assert_eq!(module.metadata.name, "tmux");
+        assert_eq!(ModuleRegistry::module_names(tmp.path()).len(), 7);
+    }
+
+    #[test]
+    fn test_malformed_user_module_falls_back() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("modules").join("tmux");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("module.toml"), "[metadata]\nname = 1\n").unwrap();
+        std::fs::write(dir.join("options.toml"), "options = \"nope\"\n").unwrap();
+
+        let module = ModuleRegistry::load_module(tmp.path(), "tmux").unwrap();
+        assert_eq!(module.metadata.name, "tmux");
+        assert!(ModuleRegistry::load_options(tmp.path(), "tmux").unwrap().options.len() > 1);
+
+        let dir = tmp.path().join("modules").join("bat");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("module.toml"), "not toml at all [").unwrap();
+        assert!(ModuleRegistry::load_module(tmp.path(), "bat").is_none());
+
+        let warnings = ModuleRegistry::user_module_warnings(tmp.path());
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("bat/module.toml"));
+        assert!(warnings[1].contains("tmux/module.toml"));
+        assert!(warnings[2].contains("tmux/options.toml"));
+    }
+
     #[test]
     fn test_load_kitty_module() {
         let module = ModuleRegistry::get_builtin("kitty").expect("kitty module should exist");
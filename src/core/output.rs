@@ -0,0 +1,48 @@
+use std::fmt::Display;
+
+/// How much a command prints, from the global `--quiet` and `--verbose`
+/// flags. Info lines are dropped under `--quiet` and details only appear
+/// with `--verbose`. Warnings and errors don't go through here, so they
+/// always print.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Printer {
+    pub quiet: bool,
+    pub verbose: bool,
+}
+
+impl Printer {
+    pub fn new(quiet: bool, verbose: bool) -> Self {
+        Self { quiet, verbose }
+    }
+
+    /// Whether `--verbose` extras should be shown. `--quiet` wins.
+    pub fn is_verbose(&self) -> bool {
+        self.verbose && !self.quiet
+    }
+
+    /// Print a line of regular output, unless `--quiet`.
+    pub fn info(&self, line: impl Display) {
+        if !self.quiet {
+            println!("{}", line);
+        }
+    }
+
+    /// Print a line only with `--verbose`.
+    pub fn detail(&self, line: impl Display) {
+        if self.is_verbose() {
+            println!("{}", line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_wins_over_verbose() {
+        assert!(Printer::new(false, true).is_verbose());
+        assert!(!Printer::new(true, true).is_verbose());
+        assert!(!Printer::default().is_verbose());
+    }
+}
@@ -0,0 +1,331 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::util;
+
+/// The changes a unified diff makes to one file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePatch {
+    /// Path from the `---` line, with any `a/` prefix removed.
+    pub old_path: String,
+    /// Path from the `+++` line, with any `b/` prefix removed.
+    pub new_path: String,
+    pub hunks: Vec<Hunk>,
+}
+
+/// One `@@ -a,b +c,d @@` section of a file patch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    /// 1-based first line of the hunk in the original file.
+    pub old_start: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    /// An added line; `true` when it ends the file without a newline.
+    Add(String, bool),
+}
+
+impl FilePatch {
+    /// The path this patch applies to: the new path, unless the patch
+    /// deletes the file.
+    pub fn target(&self) -> &str {
+        if self.new_path == "/dev/null" {
+            &self.old_path
+        } else {
+            &self.new_path
+        }
+    }
+
+    /// Whether the patch creates or deletes a file rather than changing one.
+    pub fn creates_or_deletes(&self) -> bool {
+        self.old_path == "/dev/null" || self.new_path == "/dev/null"
+    }
+}
+
+/// Parse a unified diff (as produced by `diff -u` or `git diff`) into
+/// per-file patches. Lines outside file sections, such as `diff --git` and
+/// `index` headers, are ignored.
+pub fn parse(text: &str) -> Result<Vec<FilePatch>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+
+        if let Some(old) = line.strip_prefix("--- ")
+            && let Some(new) = lines.get(i + 1).and_then(|l| l.strip_prefix("+++ "))
+        {
+            patches.push(FilePatch {
+                old_path: header_path(old, "a/"),
+                new_path: header_path(new, "b/"),
+                hunks: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+
+        if line.starts_with("@@") {
+            let Some(patch) = patches.last_mut() else {
+                anyhow::bail!("line {}: hunk before any `---`/`+++` file header", i + 1);
+            };
+            let (old_start, old_count, new_count) =
+                parse_hunk_header(line).ok_or_else(|| {
+                    anyhow::anyhow!("line {}: malformed hunk header '{}'", i + 1, line)
+                })?;
+
+            let mut hunk = Hunk {
+                old_start,
+                lines: Vec::new(),
+            };
+            let (mut old_seen, mut new_seen) = (0, 0);
+            i += 1;
+            while (old_seen < old_count || new_seen < new_count) && i < lines.len() {
+                let body = lines[i];
+                match body.chars().next() {
+                    Some(' ') | None => {
+                        hunk.lines.push(HunkLine::Context(body.get(1..).unwrap_or("").to_string()));
+                        old_seen += 1;
+                        new_seen += 1;
+                    }
+                    Some('-') => {
+                        hunk.lines.push(HunkLine::Remove(body[1..].to_string()));
+                        old_seen += 1;
+                    }
+                    Some('+') => {
+                        hunk.lines.push(HunkLine::Add(body[1..].to_string(), false));
+                        new_seen += 1;
+                    }
+                    Some('\\') => {}
+                    _ => anyhow::bail!("line {}: unexpected line in hunk '{}'", i + 1, body),
+                }
+                i += 1;
+            }
+            if old_seen != old_count || new_seen != new_count {
+                anyhow::bail!("hunk at line {} ends early", i);
+            }
+
+            // "\ No newline at end of file" after the last added line
+            if lines.get(i).is_some_and(|l| l.starts_with('\\'))
+                && let Some(HunkLine::Add(_, no_newline)) = hunk.lines.last_mut()
+            {
+                *no_newline = true;
+            }
+
+            patch.hunks.push(hunk);
+            continue;
+        }
+
+        i += 1;
+    }
+
+    patches.retain(|p| !p.hunks.is_empty());
+    if patches.is_empty() {
+        anyhow::bail!("no file changes found in patch");
+    }
+    Ok(patches)
+}
+
+/// Strip the `a/`/`b/` prefix and any tab-separated timestamp from a
+/// `---`/`+++` header path.
+fn header_path(raw: &str, prefix: &str) -> String {
+    let path = raw.split('\t').next().unwrap_or(raw).trim_end();
+    path.strip_prefix(prefix).unwrap_or(path).to_string()
+}
+
+/// Parse `@@ -a[,b] +c[,d] @@` into `(a, b, d)`; counts default to 1.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let mut parts = line.split_whitespace().skip(1);
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+
+    let range = |r: &str| -> Option<(usize, usize)> {
+        match r.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((r.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = range(old)?;
+    let (_, new_count) = range(new)?;
+    Some((old_start, old_count, new_count))
+}
+
+/// Apply a file patch to `original`, returning the new content. Each hunk is
+/// matched exactly, at its stated line or the nearest offset where its
+/// context and removed lines appear.
+pub fn apply(original: &str, patch: &FilePatch) -> Result<String> {
+    let lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let mut out = String::with_capacity(original.len());
+    let mut pos = 0;
+
+    for (n, hunk) in patch.hunks.iter().enumerate() {
+        let old: Vec<&str> = hunk
+            .lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(t) | HunkLine::Remove(t) => Some(t.as_str()),
+                HunkLine::Add(..) => None,
+            })
+            .collect();
+
+        let expected = hunk.old_start.saturating_sub(1).max(pos);
+        let start = find_hunk(&lines, &old, pos, expected).ok_or_else(|| {
+            anyhow::anyhow!(
+                "hunk {} (at line {}) does not apply to {}",
+                n + 1,
+                hunk.old_start,
+                patch.target()
+            )
+        })?;
+
+        lines[pos..start].iter().for_each(|l| out.push_str(l));
+        let mut at = start;
+        for line in &hunk.lines {
+            match line {
+                HunkLine::Context(_) => {
+                    out.push_str(lines[at]);
+                    at += 1;
+                }
+                HunkLine::Remove(_) => at += 1,
+                HunkLine::Add(text, no_newline) => {
+                    out.push_str(text);
+                    if !no_newline {
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+        pos = at;
+    }
+
+    lines[pos..].iter().for_each(|l| out.push_str(l));
+    Ok(out)
+}
+
+/// Find where `old` occurs in `lines` at or after `min`, closest to `expected`.
+fn find_hunk(lines: &[&str], old: &[&str], min: usize, expected: usize) -> Option<usize> {
+    let matches_at = |start: usize| {
+        start + old.len() <= lines.len()
+            && old
+                .iter()
+                .zip(&lines[start..])
+                .all(|(want, have)| have.trim_end_matches('\n').trim_end_matches('\r') == *want)
+    };
+
+    let last = lines.len().saturating_sub(old.len());
+    (0..=last.max(expected))
+        .flat_map(|d| [expected.checked_add(d), expected.checked_sub(d)])
+        .flatten()
+        .filter(|&s| s >= min && s <= last)
+        .find(|&s| matches_at(s))
+}
+
+/// Pick the tracked file a patch path refers to: an exact match after tilde
+/// expansion, otherwise the one tracked file whose path ends with it. When
+/// nothing matches and the tool tracks a single file, that file is used.
+pub fn match_target(patch_path: &str, files: &[PathBuf]) -> Result<PathBuf> {
+    let wanted = util::paths::expand_tilde(patch_path);
+    if let Some(exact) = files.iter().find(|f| **f == wanted) {
+        return Ok(exact.clone());
+    }
+
+    let suffix = Path::new(patch_path);
+    let candidates: Vec<&PathBuf> = files.iter().filter(|f| f.ends_with(suffix)).collect();
+    match (candidates.as_slice(), files) {
+        ([only], _) => Ok((*only).clone()),
+        ([], [single]) => Ok(single.clone()),
+        ([], _) => anyhow::bail!("patch file '{}' doesn't match any tracked file", patch_path),
+        _ => anyhow::bail!(
+            "patch file '{}' matches {} tracked files — use a longer path in the patch",
+            patch_path,
+            candidates.len()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATCH: &str = "\
+diff --git a/.tmux.conf b/.tmux.conf
+index 1111111..2222222 100644
+--- a/.tmux.conf
++++ b/.tmux.conf
+@@ -1,3 +1,4 @@
+ set -g mouse on
+-set -g history-limit 1000
++set -g history-limit 50000
++set -g base-index 1
+ bind r source-file ~/.tmux.conf
+";
+
+    #[test]
+    fn test_parse_git_diff() {
+        let patches = parse(PATCH).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].old_path, ".tmux.conf");
+        assert_eq!(patches[0].target(), ".tmux.conf");
+        assert_eq!(patches[0].hunks[0].old_start, 1);
+        assert_eq!(patches[0].hunks[0].lines.len(), 5);
+    }
+
+    #[test]
+    fn test_parse_rejects_text_without_hunks() {
+        assert!(parse("just some text\n").is_err());
+        assert!(parse("@@ -1 +1 @@\n-a\n+b\n").is_err());
+    }
+
+    #[test]
+    fn test_apply_at_offset() {
+        let patch = &parse(PATCH).unwrap()[0];
+        let original = "# added on top\n\
+                        set -g mouse on\n\
+                        set -g history-limit 1000\n\
+                        bind r source-file ~/.tmux.conf\n";
+        let patched = apply(original, patch).unwrap();
+        assert_eq!(
+            patched,
+            "# added on top\n\
+             set -g mouse on\n\
+             set -g history-limit 50000\n\
+             set -g base-index 1\n\
+             bind r source-file ~/.tmux.conf\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_fails_when_context_differs() {
+        let patch = &parse(PATCH).unwrap()[0];
+        let err = apply("set -g mouse off\n", patch).unwrap_err();
+        assert!(err.to_string().contains("hunk 1"));
+    }
+
+    #[test]
+    fn test_apply_without_trailing_newline() {
+        let text = "--- a/f\n+++ b/f\n@@ -1 +1 @@\n\
+                    -old\n\\ No newline at end of file\n\
+                    +new\n\\ No newline at end of file\n";
+        let patch = &parse(text).unwrap()[0];
+        assert_eq!(apply("old", patch).unwrap(), "new");
+    }
+
+    #[test]
+    fn test_match_target() {
+        let files = vec![
+            PathBuf::from("/home/u/.config/tmux/tmux.conf"),
+            PathBuf::from("/home/u/.config/tmux/theme.conf"),
+        ];
+        assert_eq!(match_target("tmux/theme.conf", &files).unwrap(), files[1]);
+        assert_eq!(match_target("/home/u/.config/tmux/tmux.conf", &files).unwrap(), files[0]);
+        assert!(match_target("other.conf", &files).is_err());
+
+        let single = vec![PathBuf::from("/home/u/.tmux.conf")];
+        assert_eq!(match_target("tmux.conf.orig", &single).unwrap(), single[0]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::core::snapshot;
+use crate::util;
+
+/// Paths that may hold credentials and should only be readable by their
+/// owner. `general.sensitive_paths` in config.toml adds to this list.
+pub const DEFAULT_SENSITIVE_PATHS: &[&str] = &["~/.ssh/**", "~/.gnupg/**", "~/.netrc"];
+
+/// The mode sensitive files are tightened to.
+pub const SENSITIVE_MODE: u32 = 0o600;
+
+/// A sensitive file that group or other users can access.
+#[derive(Debug, PartialEq)]
+pub struct OpenFile {
+    pub path: PathBuf,
+    pub mode: u32,
+}
+
+/// Whether `path` falls under one of the default or `extra` sensitive globs.
+/// Patterns may start with `~/`.
+pub fn is_sensitive(path: &Path, extra: &[String]) -> bool {
+    let path = path.to_string_lossy();
+    DEFAULT_SENSITIVE_PATHS
+        .iter()
+        .copied()
+        .chain(extra.iter().map(String::as_str))
+        .any(|pattern| {
+            let pattern = util::paths::expand_tilde(pattern);
+            util::glob::matches(&pattern.to_string_lossy(), &path)
+        })
+}
+
+/// Find the sensitive files among a tool's config paths whose permission
+/// bits give group or other users any access. Symlinks are checked at the
+/// file they point to.
+pub fn find_open_files(config_paths: &[String], extra: &[String]) -> Result<Vec<OpenFile>> {
+    let mut open = Vec::new();
+    for path in snapshot::config_files(config_paths)? {
+        if !is_sensitive(&path, extra) {
+            continue;
+        }
+        let mode = fs::metadata(&path)
+            .with_context(|| format!("failed to read metadata of {}", path.display()))?
+            .permissions()
+            .mode()
+            & 0o777;
+        if mode & 0o077 != 0 {
+            open.push(OpenFile { path, mode });
+        }
+    }
+    Ok(open)
+}
+
+/// Restrict a file to owner read/write.
+pub fn tighten(path: &Path) -> Result<()> {
+    fs::set_permissions(path, fs::Permissions::from_mode(SENSITIVE_MODE))
+        .with_context(|| format!("failed to set permissions on {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_sensitive_defaults_and_extra() {
+        let home = util::paths::home_dir().unwrap();
+        assert!(is_sensitive(&home.join(".ssh/config"), &[]));
+        assert!(is_sensitive(&home.join(".gnupg/gpg-agent.conf"), &[]));
+        assert!(is_sensitive(&home.join(".netrc"), &[]));
+        assert!(!is_sensitive(&home.join(".tmux.conf"), &[]));
+
+        let extra = vec!["~/.config/git/credentials*".to_string()];
+        assert!(is_sensitive(&home.join(".config/git/credentials"), &extra));
+    }
+
+    #[test]
+    fn test_find_and_tighten_open_files() {
+        let tmp = TempDir::new().unwrap();
+        let secret = tmp.path().join("tokens.conf");
+        let public = tmp.path().join("theme.conf");
+        fs::write(&secret, "token = abc").unwrap();
+        fs::write(&public, "color = blue").unwrap();
+        fs::set_permissions(&secret, fs::Permissions::from_mode(0o644)).unwrap();
+        fs::set_permissions(&public, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let paths = vec![tmp.path().to_string_lossy().to_string()];
+        let extra = vec![format!("{}/tokens.*", tmp.path().display())];
+        let open = find_open_files(&paths, &extra).unwrap();
+        assert_eq!(
+            open,
+            vec![OpenFile {
+                path: secret.clone(),
+                mode: 0o644
+            }]
+        );
+
+        tighten(&secret).unwrap();
+        assert!(find_open_files(&paths, &extra).unwrap().is_empty());
+        let mode = fs::metadata(&secret).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+    }
+}
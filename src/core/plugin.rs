@@ -1,21 +1,29 @@
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use anyhow::{Context, Result};
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 
 use crate::core::errors::DotsmithError;
 use crate::core::manifest::{Manifest, PluginEntry};
 use crate::util;
 
 /// Which tools support dotsmith-managed plugins.
-const SUPPORTED_TOOLS: &[&str] = &["zsh", "tmux"];
+const SUPPORTED_TOOLS: &[&str] = &["zsh", "tmux", "fish"];
+
+/// Tools whose plugins belong to their own plugin manager. dotsmith lists
+/// them from the manager's lockfile but never installs or changes them.
+const EXTERNALLY_MANAGED: &[(&str, &str)] = &[("nvim", "lazy.nvim"), ("neovim", "lazy.nvim")];
 
 /// Result of a plugin update operation.
 #[derive(Debug)]
 pub struct UpdateResult {
     pub name: String,
     pub updated: bool,
+    pub pinned: bool,
     pub old_commit: String,
     pub new_commit: String,
 }
@@ -30,8 +38,30 @@ pub struct UpdateResult {
 /// - GitHub shorthand: `"user/repo"`
 /// - Full HTTPS URL: `"https://github.com/user/repo"` or `"https://github.com/user/repo.git"`
 ///
-/// Returns `(clone_url, plugin_name)`.
-pub fn parse_repo(repo: &str) -> Result<(String, String)> {
+/// Either form may end in `@<ref>` (a tag, branch, or commit) to pin the plugin.
+///
+/// Returns `(clone_url, plugin_name, git_ref)`.
+pub fn parse_repo(repo: &str) -> Result<(String, String, Option<String>)> {
+    let (repo, git_ref) = split_ref(repo)?;
+    let (url, name) = parse_repo_location(repo)?;
+    Ok((url, name, git_ref))
+}
+
+/// Split a trailing `@<ref>` off a repo specifier. An `@` followed by a path
+/// (as in `https://user@host/...`) is part of the URL, not a ref.
+fn split_ref(repo: &str) -> Result<(&str, Option<String>)> {
+    match repo.rsplit_once('@') {
+        Some((base, git_ref)) if !git_ref.contains('/') => {
+            if git_ref.is_empty() || base.is_empty() {
+                return Err(DotsmithError::InvalidPluginRepo(repo.to_string()).into());
+            }
+            Ok((base, Some(git_ref.to_string())))
+        }
+        _ => Ok((repo, None)),
+    }
+}
+
+fn parse_repo_location(repo: &str) -> Result<(String, String)> {
     if repo.starts_with("https://") || repo.starts_with("http://") || repo.starts_with("file://") {
         let name = repo
             .trim_end_matches('/')
@@ -99,6 +129,77 @@ pub fn git_clone(url: &str, dest: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Clone a repository in full and check out `git_ref` (tag, branch, or
+/// commit). A shallow clone can't reach arbitrary commits, so unlike
+/// `git_clone` this fetches the whole history.
+pub fn git_clone_at(url: &str, dest: &Path, git_ref: &str) -> Result<()> {
+    let output = std::process::Command::new("git")
+        .args(["clone", "--quiet"])
+        .arg(url)
+        .arg(dest)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .output()
+        .with_context(|| format!("failed to execute git clone for {}", url))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(
+            DotsmithError::GitCloneFailed(url.to_string(), stderr.trim().to_string()).into(),
+        );
+    }
+
+    let output = std::process::Command::new("git")
+        .args(["checkout", "--quiet", git_ref])
+        .current_dir(dest)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .output()
+        .context("failed to execute git checkout")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let _ = std::fs::remove_dir_all(dest);
+        return Err(
+            DotsmithError::GitCheckoutFailed(git_ref.to_string(), stderr.trim().to_string())
+                .into(),
+        );
+    }
+    Ok(())
+}
+
+/// If HEAD is detached (a plugin added at a ref, since unpinned), switch
+/// back to the remote's default branch so it can be pulled again.
+fn git_reattach(repo_dir: &Path) -> Result<()> {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(repo_dir)
+            .output()
+            .context("failed to execute git")
+    };
+
+    if git(&["symbolic-ref", "-q", "HEAD"])?.status.success() {
+        return Ok(());
+    }
+
+    let output = git(&["rev-parse", "--abbrev-ref", "origin/HEAD"])?;
+    let remote_head = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let Some(branch) = remote_head.strip_prefix("origin/") else {
+        return Ok(());
+    };
+
+    let output = git(&["checkout", "--quiet", branch])?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(
+            DotsmithError::GitCheckoutFailed(branch.to_string(), stderr.trim().to_string())
+                .into(),
+        );
+    }
+    Ok(())
+}
+
 /// Get the current HEAD commit hash of a git repository.
 pub fn git_head_commit(repo_dir: &Path) -> Result<String> {
     let output = std::process::Command::new("git")
@@ -155,6 +256,7 @@ pub fn detect_init_file(tool: &str, plugin_dir: &Path) -> Result<String> {
     match tool {
         "zsh" => detect_zsh_init_file(&entries),
         "tmux" => detect_tmux_init_file(&entries),
+        "fish" => detect_fish_init_file(plugin_dir, &entries),
         _ => Err(DotsmithError::PluginsNotSupported(tool.to_string()).into()),
     }
 }
@@ -209,6 +311,43 @@ fn detect_tmux_init_file(files: &[String]) -> Result<String> {
     .into())
 }
 
+/// Fish plugins keep their code in `conf.d/` and `functions/`, so the init
+/// file may be a relative path like `conf.d/done.fish`.
+fn detect_fish_init_file(plugin_dir: &Path, files: &[String]) -> Result<String> {
+    // Priority 1 and 2: conf.d/*.fish, then functions/*.fish
+    for sub in ["conf.d", "functions"] {
+        let mut fish_files: Vec<String> = std::fs::read_dir(plugin_dir.join(sub))
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+            .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+            .filter(|f| f.ends_with(".fish"))
+            .collect();
+        if !fish_files.is_empty() {
+            fish_files.sort();
+            return Ok(format!("{}/{}", sub, fish_files[0]));
+        }
+    }
+
+    // Priority 3: init.fish
+    if files.iter().any(|f| f == "init.fish") {
+        return Ok("init.fish".to_string());
+    }
+
+    // Priority 4: *.fish (only if exactly one)
+    let fish_files: Vec<&String> = files.iter().filter(|f| f.ends_with(".fish")).collect();
+    if fish_files.len() == 1 {
+        return Ok(fish_files[0].clone());
+    }
+
+    Err(DotsmithError::PluginNoInitFile(
+        "fish plugin".to_string(),
+        "conf.d/*.fish, functions/*.fish, init.fish, or a single *.fish file".to_string(),
+    )
+    .into())
+}
+
 // ---------------------------------------------------------------------------
 // Path helpers
 // ---------------------------------------------------------------------------
@@ -236,6 +375,18 @@ pub fn loader_path(config_dir: &Path, tool: &str) -> PathBuf {
 // Loader generation
 // ---------------------------------------------------------------------------
 
+/// Plugins in the order the loader sources them: by `order`, then by name.
+pub fn load_order(plugins: &BTreeMap<String, PluginEntry>) -> Vec<(&String, &PluginEntry)> {
+    let mut ordered: Vec<_> = plugins.iter().collect();
+    ordered.sort_by_key(|(name, entry)| (entry.order, *name));
+    ordered
+}
+
+/// The `order` a newly added plugin gets, so it loads after the existing ones.
+fn next_order(plugins: &BTreeMap<String, PluginEntry>) -> u32 {
+    plugins.values().map(|p| p.order + 1).max().unwrap_or(0)
+}
+
 /// Generate the loader file content for a tool.
 pub fn generate_loader_content(
     tool: &str,
@@ -249,7 +400,7 @@ pub fn generate_loader_content(
 
     let base = plugin_base_dir(config_dir, tool);
 
-    for (name, entry) in plugins {
+    for (name, entry) in load_order(plugins) {
         let init_path = base.join(name).join(&entry.init);
         let contracted = util::paths::contract_tilde(&init_path);
 
@@ -334,6 +485,11 @@ pub fn zsh_recompile_zwc(plugin_dir: &Path) -> Result<bool> {
 
 /// Validate that plugin management is supported for a tool.
 pub fn validate_tool_supported(tool: &str) -> Result<()> {
+    if let Some(manager) = external_manager(tool) {
+        return Err(
+            DotsmithError::PluginsManagedExternally(tool.to_string(), manager.to_string()).into(),
+        );
+    }
     if !SUPPORTED_TOOLS.contains(&tool) {
         return Err(DotsmithError::PluginsNotSupported(tool.to_string()).into());
     }
@@ -360,7 +516,7 @@ pub fn add_plugin(
     validate_tool_supported(tool)?;
     check_git_installed()?;
 
-    let (clone_url, name) = parse_repo(repo_spec)?;
+    let (clone_url, name, git_ref) = parse_repo(repo_spec)?;
 
     let tool_entry = manifest
         .get_tool_mut(tool)
@@ -372,9 +528,12 @@ pub fn add_plugin(
         );
     }
 
-    // Clone the repository
+    // Clone the repository, at the requested ref if there is one
     let dest = plugin_dir(config_dir, tool, &name);
-    git_clone(&clone_url, &dest)?;
+    match git_ref {
+        Some(ref r) => git_clone_at(&clone_url, &dest, r)?,
+        None => git_clone(&clone_url, &dest)?,
+    }
 
     // Detect init file
     let init_file = match detect_init_file(tool, &dest) {
@@ -387,10 +546,17 @@ pub fn add_plugin(
     };
 
     // Register in manifest
+    // The ref lives in `pinned_ref` so `repo` stays a plain, browsable location
+    let repo = match git_ref {
+        Some(ref r) => repo_spec.strip_suffix(&format!("@{}", r)).unwrap_or(repo_spec),
+        None => repo_spec,
+    };
     let plugin_entry = PluginEntry {
-        repo: repo_spec.to_string(),
+        repo: repo.to_string(),
         init: init_file.clone(),
         added_at: Utc::now(),
+        pinned_ref: git_ref,
+        order: next_order(&tool_entry.plugins),
     };
     tool_entry.plugins.insert(name.clone(), plugin_entry);
     tool_entry.plugins_managed = true;
@@ -456,7 +622,7 @@ pub fn remove_plugin(
     Ok(())
 }
 
-/// List plugins for a tool. Returns `(name, repo, init_file)` tuples.
+/// List plugins for a tool in load order. Returns `(name, repo, init_file)` tuples.
 pub fn list_plugins(manifest: &Manifest, tool: &str) -> Result<Vec<(String, String, String)>> {
     validate_tool_supported(tool)?;
 
@@ -464,19 +630,23 @@ pub fn list_plugins(manifest: &Manifest, tool: &str) -> Result<Vec<(String, Stri
         .get_tool(tool)
         .ok_or_else(|| DotsmithError::ToolNotTracked(tool.to_string()))?;
 
-    Ok(tool_entry
-        .plugins
-        .iter()
+    Ok(load_order(&tool_entry.plugins)
+        .into_iter()
         .map(|(name, entry)| (name.clone(), entry.repo.clone(), entry.init.clone()))
         .collect())
 }
 
 /// Update one or all plugins for a tool.
+///
+/// Plugins are pulled concurrently by up to `jobs` worker threads (default:
+/// the available parallelism). Results come back in load order; if any
+/// update fails, the first failure in that order is returned.
 pub fn update_plugins(
     config_dir: &Path,
     manifest: &Manifest,
     tool: &str,
     name: Option<&str>,
+    jobs: Option<usize>,
 ) -> Result<Vec<UpdateResult>> {
     validate_tool_supported(tool)?;
     check_git_installed()?;
@@ -493,43 +663,573 @@ pub fn update_plugins(
                 .ok_or_else(|| DotsmithError::PluginNotInstalled(n.to_string(), tool.to_string()))?;
             vec![(tool_entry.plugins.keys().find(|k| k.as_str() == n).unwrap(), entry)]
         }
-        None => tool_entry.plugins.iter().collect(),
+        None => load_order(&tool_entry.plugins),
     };
 
-    let mut results = Vec::new();
+    let jobs = jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+        .clamp(1, plugins_to_update.len().max(1));
+
+    // Workers claim plugins by index; each slot is written by exactly one worker
+    let next = AtomicUsize::new(0);
+    let slots: Vec<Mutex<Option<Result<UpdateResult>>>> =
+        plugins_to_update.iter().map(|_| Mutex::new(None)).collect();
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((plugin_name, entry)) = plugins_to_update.get(i) else {
+                        break;
+                    };
+                    let result = update_plugin(config_dir, tool, plugin_name, entry);
+                    *slots[i].lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+                }
+            });
+        }
+    });
+
+    slots
+        .into_iter()
+        .map(|slot| {
+            slot.into_inner()
+                .unwrap_or_else(|e| e.into_inner())
+                .expect("every plugin is claimed by a worker")
+        })
+        .collect()
+}
+
+/// Pull a single plugin, recompiling zsh bytecode when it changed.
+fn update_plugin(
+    config_dir: &Path,
+    tool: &str,
+    plugin_name: &str,
+    entry: &PluginEntry,
+) -> Result<UpdateResult> {
+    if let Some(ref pinned) = entry.pinned_ref {
+        return Ok(UpdateResult {
+            name: plugin_name.to_string(),
+            updated: false,
+            pinned: true,
+            old_commit: pinned.clone(),
+            new_commit: pinned.clone(),
+        });
+    }
 
-    for (plugin_name, _entry) in &plugins_to_update {
-        let dir = plugin_dir(config_dir, tool, plugin_name);
+    let dir = plugin_dir(config_dir, tool, plugin_name);
 
-        if !dir.exists() {
-            results.push(UpdateResult {
-                name: plugin_name.to_string(),
-                updated: false,
-                old_commit: "missing".to_string(),
-                new_commit: "missing".to_string(),
-            });
+    if !dir.exists() {
+        return Ok(UpdateResult {
+            name: plugin_name.to_string(),
+            updated: false,
+            pinned: false,
+            old_commit: "missing".to_string(),
+            new_commit: "missing".to_string(),
+        });
+    }
+
+    let old_commit = git_head_commit(&dir).unwrap_or_default();
+    git_reattach(&dir)?;
+    git_pull(&dir)?;
+    let new_commit = git_head_commit(&dir).unwrap_or_default();
+    let updated = old_commit != new_commit;
+
+    if updated && tool == "zsh" {
+        zsh_recompile_zwc(&dir)?;
+    }
+
+    Ok(UpdateResult {
+        name: plugin_name.to_string(),
+        updated,
+        pinned: false,
+        old_commit,
+        new_commit,
+    })
+}
+
+/// Fetch each unpinned plugin of `tool` and count the upstream commits its
+/// checkout doesn't have yet. Returns `(name, behind)` pairs in load order,
+/// where `behind` holds the error for a plugin that couldn't be fetched, so
+/// one bad remote doesn't hide the rest. Nothing is merged or checked out;
+/// missing plugins are left out.
+pub fn check_updates(
+    config_dir: &Path,
+    manifest: &Manifest,
+    tool: &str,
+) -> Result<Vec<(String, Result<usize>)>> {
+    validate_tool_supported(tool)?;
+    check_git_installed()?;
+
+    let tool_entry = manifest
+        .get_tool(tool)
+        .ok_or_else(|| DotsmithError::ToolNotTracked(tool.to_string()))?;
+
+    let mut behind = Vec::new();
+    for (name, entry) in load_order(&tool_entry.plugins) {
+        let dir = plugin_dir(config_dir, tool, name);
+        if entry.pinned_ref.is_some() || !dir.exists() {
             continue;
         }
+        let count = git_fetch(&dir).and_then(|()| git_behind_count(&dir));
+        behind.push((name.clone(), count));
+    }
+    Ok(behind)
+}
+
+/// Fetch from a plugin's remote without touching the working tree. Git is
+/// told not to prompt for credentials, which would hang the TUI.
+fn git_fetch(repo_dir: &Path) -> Result<()> {
+    let output = std::process::Command::new("git")
+        .args(["fetch", "--quiet"])
+        .current_dir(repo_dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .output()
+        .context("failed to execute git fetch")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(DotsmithError::GitFetchFailed(
+            repo_dir.display().to_string(),
+            stderr.trim().to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Count the commits on the upstream branch that HEAD doesn't have. A
+/// detached checkout (as left by `sync`) is compared to `origin/HEAD`.
+fn git_behind_count(repo_dir: &Path) -> Result<usize> {
+    let count = |range: &str| {
+        std::process::Command::new("git")
+            .args(["rev-list", "--count", range])
+            .current_dir(repo_dir)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .output()
+            .context("failed to execute git rev-list")
+    };
+
+    let mut output = count("HEAD..@{u}")?;
+    if !output.status.success() {
+        output = count("HEAD..origin/HEAD")?;
+    }
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} has no upstream branch to compare against",
+            repo_dir.display()
+        );
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .context("unexpected git rev-list output")
+}
+
+/// Pin a plugin at its current commit so `update_plugins` skips it.
+/// Returns the pinned commit.
+pub fn pin_plugin(
+    config_dir: &Path,
+    manifest: &mut Manifest,
+    tool: &str,
+    name: &str,
+) -> Result<String> {
+    validate_tool_supported(tool)?;
+    check_git_installed()?;
+
+    let tool_entry = manifest
+        .get_tool_mut(tool)
+        .ok_or_else(|| DotsmithError::ToolNotTracked(tool.to_string()))?;
+
+    let entry = tool_entry
+        .plugins
+        .get_mut(name)
+        .ok_or_else(|| DotsmithError::PluginNotInstalled(name.to_string(), tool.to_string()))?;
+
+    let commit = git_head_commit(&plugin_dir(config_dir, tool, name))?;
+    if commit.is_empty() {
+        anyhow::bail!("could not determine current commit of plugin '{}'", name);
+    }
+
+    entry.pinned_ref = Some(commit.clone());
+    manifest.save(config_dir)?;
+
+    Ok(commit)
+}
+
+/// Clear a plugin's pin so updates resume.
+/// Returns the previously pinned commit, if any.
+pub fn unpin_plugin(
+    config_dir: &Path,
+    manifest: &mut Manifest,
+    tool: &str,
+    name: &str,
+) -> Result<Option<String>> {
+    validate_tool_supported(tool)?;
+
+    let tool_entry = manifest
+        .get_tool_mut(tool)
+        .ok_or_else(|| DotsmithError::ToolNotTracked(tool.to_string()))?;
+
+    let entry = tool_entry
+        .plugins
+        .get_mut(name)
+        .ok_or_else(|| DotsmithError::PluginNotInstalled(name.to_string(), tool.to_string()))?;
+
+    let previous = entry.pinned_ref.take();
+    manifest.save(config_dir)?;
+
+    Ok(previous)
+}
+
+/// Move a plugin directly before or after another one in the load order.
+///
+/// Every plugin of the tool is renumbered from 0 so the stored order stays
+/// dense, then the loader is regenerated and the manifest saved.
+pub fn move_plugin(
+    config_dir: &Path,
+    manifest: &mut Manifest,
+    tool: &str,
+    name: &str,
+    other: &str,
+    before: bool,
+) -> Result<()> {
+    validate_tool_supported(tool)?;
+
+    let tool_entry = manifest
+        .get_tool_mut(tool)
+        .ok_or_else(|| DotsmithError::ToolNotTracked(tool.to_string()))?;
+
+    for plugin in [name, other] {
+        if !tool_entry.plugins.contains_key(plugin) {
+            return Err(
+                DotsmithError::PluginNotInstalled(plugin.to_string(), tool.to_string()).into(),
+            );
+        }
+    }
+    if name == other {
+        anyhow::bail!("cannot move plugin '{}' relative to itself", name);
+    }
 
-        let old_commit = git_head_commit(&dir).unwrap_or_default();
-        let updated = git_pull(&dir)?;
-        let new_commit = git_head_commit(&dir).unwrap_or_default();
+    let mut names: Vec<String> = load_order(&tool_entry.plugins)
+        .into_iter()
+        .map(|(n, _)| n.clone())
+        .filter(|n| n != name)
+        .collect();
+    let at = names.iter().position(|n| n == other).unwrap_or(names.len());
+    names.insert(if before { at } else { at + 1 }, name.to_string());
 
-        if updated && tool == "zsh" {
-            zsh_recompile_zwc(&dir)?;
+    for (i, n) in names.iter().enumerate() {
+        if let Some(entry) = tool_entry.plugins.get_mut(n) {
+            entry.order = i as u32;
         }
+    }
 
-        results.push(UpdateResult {
-            name: plugin_name.to_string(),
-            updated,
-            old_commit,
-            new_commit,
+    write_loader(tool, config_dir, &tool_entry.plugins)?;
+    manifest.save(config_dir)?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Externally managed plugins (lazy.nvim)
+// ---------------------------------------------------------------------------
+
+/// The plugin manager that owns a tool's plugins, if dotsmith only lists them.
+pub fn external_manager(tool: &str) -> Option<&'static str> {
+    EXTERNALLY_MANAGED
+        .iter()
+        .find(|(t, _)| *t == tool)
+        .map(|(_, manager)| *manager)
+}
+
+/// A plugin recorded in `lazy-lock.json`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LazyPlugin {
+    pub name: String,
+    pub branch: Option<String>,
+    pub commit: String,
+}
+
+/// Parse a `lazy-lock.json`: an object mapping each plugin name to its
+/// `{ "branch": ..., "commit": ... }`. Entries keep the file's order.
+pub fn parse_lazy_lock(content: &str) -> Result<Vec<LazyPlugin>> {
+    let serde_json::Value::Object(members) =
+        serde_json::from_str(content).context("invalid lazy-lock.json")?
+    else {
+        anyhow::bail!("invalid lazy-lock.json: expected an object of plugins");
+    };
+
+    members
+        .into_iter()
+        .map(|(name, entry)| {
+            let commit = entry
+                .get("commit")
+                .and_then(|c| c.as_str())
+                .ok_or_else(|| anyhow::anyhow!("lazy-lock.json: '{}' has no commit", name))?
+                .to_string();
+            let branch = entry.get("branch").and_then(|b| b.as_str()).map(str::to_string);
+            Ok(LazyPlugin {
+                name,
+                branch,
+                commit,
+            })
+        })
+        .collect()
+}
+
+/// Where lazy.nvim keeps its lockfile: next to the tool's first config path
+/// (the config directory itself, or the directory holding `init.lua`).
+pub fn lazy_lock_path(config_paths: &[String]) -> Option<PathBuf> {
+    let first = util::paths::expand_tilde(config_paths.first()?);
+    let root = if first.is_dir() {
+        first
+    } else {
+        first.parent()?.to_path_buf()
+    };
+    Some(root.join("lazy-lock.json"))
+}
+
+/// Where lazy.nvim clones a plugin: `~/.local/share/nvim/lazy/<name>`.
+pub fn lazy_plugin_dir(name: &str) -> Option<PathBuf> {
+    util::paths::xdg_data_dir()
+        .ok()
+        .map(|d| d.join("nvim").join("lazy").join(name))
+}
+
+/// List the plugins lazy.nvim has locked for a tracked tool.
+pub fn list_lazy_plugins(manifest: &Manifest, tool: &str) -> Result<Vec<LazyPlugin>> {
+    let tool_entry = manifest
+        .get_tool(tool)
+        .ok_or_else(|| DotsmithError::ToolNotTracked(tool.to_string()))?;
+
+    let path = lazy_lock_path(&tool_entry.config_paths).ok_or_else(|| {
+        anyhow::anyhow!("'{}' has no config path to look for lazy-lock.json", tool)
+    })?;
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", util::paths::contract_tilde(&path)))?;
+    parse_lazy_lock(&content)
+}
+
+// ---------------------------------------------------------------------------
+// Lockfile
+// ---------------------------------------------------------------------------
+
+/// Plugin commits recorded by `write_lockfile`, stored as `plugins.lock`.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "plugin")]
+    pub plugins: Vec<LockedPlugin>,
+}
+
+/// One plugin at an exact commit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedPlugin {
+    pub tool: String,
+    pub name: String,
+    pub repo: String,
+    pub commit: String,
+}
+
+/// What `install_from_lockfile` did for one plugin.
+#[derive(Debug, PartialEq)]
+pub enum LockSyncStatus {
+    /// Cloned fresh at the locked commit
+    Installed,
+    /// Already installed, moved to the locked commit
+    CheckedOut,
+    /// Already at the locked commit
+    UpToDate,
+}
+
+/// Result of syncing a single plugin from the lockfile.
+#[derive(Debug)]
+pub struct LockSyncResult {
+    pub name: String,
+    pub commit: String,
+    pub status: LockSyncStatus,
+}
+
+/// Path to the plugin lockfile.
+pub fn lockfile_path(config_dir: &Path) -> PathBuf {
+    config_dir.join("plugins.lock")
+}
+
+/// Record the checked-out commit of every installed plugin, for all tools,
+/// in `plugins.lock`. Entries are ordered by tool, then plugin name, so the
+/// file only changes when a commit does. Returns the recorded plugins.
+pub fn write_lockfile(config_dir: &Path, manifest: &Manifest) -> Result<Vec<LockedPlugin>> {
+    check_git_installed()?;
+
+    let mut plugins = Vec::new();
+    for (tool, tool_entry) in &manifest.tools {
+        for (name, entry) in &tool_entry.plugins {
+            let dir = plugin_dir(config_dir, tool, name);
+            if !dir.is_dir() {
+                anyhow::bail!(
+                    "plugin '{}' for {} is missing from {} — reinstall it before locking",
+                    name,
+                    tool,
+                    dir.display()
+                );
+            }
+            let commit = git_head_commit(&dir)?;
+            if commit.is_empty() {
+                anyhow::bail!("could not determine current commit of plugin '{}'", name);
+            }
+            plugins.push(LockedPlugin {
+                tool: tool.clone(),
+                name: name.clone(),
+                repo: entry.repo.clone(),
+                commit,
+            });
+        }
+    }
+
+    let lockfile = Lockfile { plugins };
+    let content = format!(
+        "# Generated by dotsmith — install with `dotsmith plugins <tool> sync`\n\n{}",
+        toml::to_string_pretty(&lockfile).context("failed to serialize plugin lockfile")?
+    );
+    util::fs::atomic_write(&lockfile_path(config_dir), &content)?;
+
+    Ok(lockfile.plugins)
+}
+
+/// Read `plugins.lock` from the config directory.
+pub fn read_lockfile(config_dir: &Path) -> Result<Lockfile> {
+    let path = lockfile_path(config_dir);
+    if !path.exists() {
+        return Err(DotsmithError::LockfileNotFound(path.display().to_string()).into());
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Install a tool's plugins at the exact commits in `plugins.lock`.
+///
+/// Missing plugins are cloned and registered in the manifest; installed ones
+/// are moved to the locked commit. A pinned plugin stays pinned, at the
+/// locked commit. Plugins not in the lockfile are left alone.
+pub fn install_from_lockfile(
+    config_dir: &Path,
+    manifest: &mut Manifest,
+    tool: &str,
+) -> Result<Vec<LockSyncResult>> {
+    validate_tool_supported(tool)?;
+    check_git_installed()?;
+
+    let lockfile = read_lockfile(config_dir)?;
+
+    let tool_entry = manifest
+        .get_tool_mut(tool)
+        .ok_or_else(|| DotsmithError::ToolNotTracked(tool.to_string()))?;
+
+    let mut results = Vec::new();
+    for locked in lockfile.plugins.iter().filter(|p| p.tool == tool) {
+        let dest = plugin_dir(config_dir, tool, &locked.name);
+
+        let status = if dest.is_dir() {
+            if git_head_commit(&dest)? == locked.commit {
+                LockSyncStatus::UpToDate
+            } else {
+                git_checkout_commit(&dest, &locked.commit)?;
+                LockSyncStatus::CheckedOut
+            }
+        } else {
+            let (clone_url, _, _) = parse_repo(&locked.repo)?;
+            git_clone_at(&clone_url, &dest, &locked.commit)?;
+            LockSyncStatus::Installed
+        };
+
+        match tool_entry.plugins.get_mut(&locked.name) {
+            Some(entry) => {
+                if entry.pinned_ref.is_some() {
+                    entry.pinned_ref = Some(locked.commit.clone());
+                }
+            }
+            None => {
+                let init = detect_init_file(tool, &dest)?;
+                let order = next_order(&tool_entry.plugins);
+                tool_entry.plugins.insert(
+                    locked.name.clone(),
+                    PluginEntry {
+                        repo: locked.repo.clone(),
+                        init,
+                        added_at: Utc::now(),
+                        pinned_ref: None,
+                        order,
+                    },
+                );
+            }
+        }
+
+        if status != LockSyncStatus::UpToDate && tool == "zsh" {
+            zsh_recompile_zwc(&dest)?;
+        }
+
+        results.push(LockSyncResult {
+            name: locked.name.clone(),
+            commit: locked.commit.clone(),
+            status,
         });
     }
 
+    if !results.is_empty() {
+        tool_entry.plugins_managed = true;
+        tool_entry.plugin_manager = Some("dotsmith".to_string());
+        write_loader(tool, config_dir, &tool_entry.plugins)?;
+        manifest.save(config_dir)?;
+    }
+
     Ok(results)
 }
 
+/// Check out `commit` in an existing clone, fetching first if it isn't
+/// available locally (plugins are normally shallow clones).
+fn git_checkout_commit(repo_dir: &Path, commit: &str) -> Result<()> {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(repo_dir)
+            .output()
+            .context("failed to execute git")
+    };
+
+    if git(&["checkout", "--quiet", commit])?.status.success() {
+        return Ok(());
+    }
+
+    let output = git(&["rev-parse", "--is-shallow-repository"])?;
+    let fetch: &[&str] = if String::from_utf8_lossy(&output.stdout).trim() == "true" {
+        &["fetch", "--quiet", "--unshallow"]
+    } else {
+        &["fetch", "--quiet"]
+    };
+    let output = git(fetch)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(DotsmithError::GitPullFailed(
+            repo_dir.display().to_string(),
+            stderr.trim().to_string(),
+        )
+        .into());
+    }
+
+    let output = git(&["checkout", "--quiet", commit])?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(
+            DotsmithError::GitCheckoutFailed(commit.to_string(), stderr.trim().to_string())
+                .into(),
+        );
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -543,30 +1243,59 @@ mod tests {
 
     #[test]
     fn test_parse_repo_shorthand() {
-        let (url, name) = parse_repo("zsh-users/zsh-autosuggestions").unwrap();
+        let (url, name, _) = parse_repo("zsh-users/zsh-autosuggestions").unwrap();
         assert_eq!(url, "https://github.com/zsh-users/zsh-autosuggestions.git");
         assert_eq!(name, "zsh-autosuggestions");
     }
 
     #[test]
     fn test_parse_repo_full_url() {
-        let (url, name) = parse_repo("https://github.com/foo/bar").unwrap();
+        let (url, name, _) = parse_repo("https://github.com/foo/bar").unwrap();
         assert_eq!(url, "https://github.com/foo/bar.git");
         assert_eq!(name, "bar");
     }
 
     #[test]
     fn test_parse_repo_full_url_with_git_suffix() {
-        let (url, name) = parse_repo("https://github.com/foo/bar.git").unwrap();
+        let (url, name, _) = parse_repo("https://github.com/foo/bar.git").unwrap();
         assert_eq!(url, "https://github.com/foo/bar.git");
         assert_eq!(name, "bar");
     }
 
     #[test]
     fn test_parse_repo_full_url_trailing_slash() {
-        let (url, name) = parse_repo("https://github.com/foo/bar/").unwrap();
+        let (url, name, _) = parse_repo("https://github.com/foo/bar/").unwrap();
+        assert_eq!(url, "https://github.com/foo/bar.git");
+        assert_eq!(name, "bar");
+    }
+
+    #[test]
+    fn test_parse_repo_with_ref() {
+        let (url, name, git_ref) = parse_repo("foo/bar@abc123").unwrap();
         assert_eq!(url, "https://github.com/foo/bar.git");
         assert_eq!(name, "bar");
+        assert_eq!(git_ref.as_deref(), Some("abc123"));
+
+        let (url, _, git_ref) = parse_repo("https://github.com/foo/bar.git@v1.2.3").unwrap();
+        assert_eq!(url, "https://github.com/foo/bar.git");
+        assert_eq!(git_ref.as_deref(), Some("v1.2.3"));
+
+        let (_, _, git_ref) = parse_repo("foo/bar").unwrap();
+        assert!(git_ref.is_none());
+    }
+
+    #[test]
+    fn test_parse_repo_userinfo_is_not_a_ref() {
+        let (url, name, git_ref) = parse_repo("https://me@example.com/foo/bar").unwrap();
+        assert_eq!(url, "https://me@example.com/foo/bar.git");
+        assert_eq!(name, "bar");
+        assert!(git_ref.is_none());
+    }
+
+    #[test]
+    fn test_parse_repo_invalid_empty_ref() {
+        assert!(parse_repo("foo/bar@").is_err());
+        assert!(parse_repo("@v1").is_err());
     }
 
     #[test]
@@ -666,6 +1395,61 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_detect_fish_init_conf_d() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("conf.d")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("functions")).unwrap();
+        std::fs::write(tmp.path().join("conf.d/done.fish"), "# conf").unwrap();
+        std::fs::write(tmp.path().join("functions/__done_notify.fish"), "# fn").unwrap();
+        std::fs::write(tmp.path().join("init.fish"), "# init").unwrap();
+
+        let result = detect_init_file("fish", tmp.path()).unwrap();
+        assert_eq!(result, "conf.d/done.fish");
+    }
+
+    #[test]
+    fn test_detect_fish_init_functions() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("functions")).unwrap();
+        std::fs::write(tmp.path().join("functions/z.fish"), "# z").unwrap();
+        std::fs::write(tmp.path().join("functions/__z_add.fish"), "# add").unwrap();
+        std::fs::write(tmp.path().join("init.fish"), "# init").unwrap();
+
+        let result = detect_init_file("fish", tmp.path()).unwrap();
+        assert_eq!(result, "functions/__z_add.fish");
+    }
+
+    #[test]
+    fn test_detect_fish_init_init_fish() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("init.fish"), "# init").unwrap();
+        std::fs::write(tmp.path().join("helpers.fish"), "# helpers").unwrap();
+
+        let result = detect_init_file("fish", tmp.path()).unwrap();
+        assert_eq!(result, "init.fish");
+    }
+
+    #[test]
+    fn test_detect_fish_init_single_fish() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("foo.fish"), "# single fish file").unwrap();
+        std::fs::write(tmp.path().join("README.md"), "# readme").unwrap();
+
+        let result = detect_init_file("fish", tmp.path()).unwrap();
+        assert_eq!(result, "foo.fish");
+    }
+
+    #[test]
+    fn test_detect_fish_init_ambiguous_fails() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("a.fish"), "# a").unwrap();
+        std::fs::write(tmp.path().join("b.fish"), "# b").unwrap();
+
+        let result = detect_init_file("fish", tmp.path());
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_detect_unsupported_tool_fails() {
         let tmp = TempDir::new().unwrap();
@@ -685,6 +1469,8 @@ mod tests {
                 repo: "zsh-users/zsh-autosuggestions".to_string(),
                 init: "zsh-autosuggestions.plugin.zsh".to_string(),
                 added_at: Utc::now(),
+                pinned_ref: None,
+                order: 0,
             },
         );
 
@@ -705,6 +1491,8 @@ mod tests {
                 repo: "tmux-plugins/tmux-sensible".to_string(),
                 init: "sensible.tmux".to_string(),
                 added_at: Utc::now(),
+                pinned_ref: None,
+                order: 0,
             },
         );
 
@@ -715,6 +1503,28 @@ mod tests {
         assert!(!content.contains("source "));
     }
 
+    #[test]
+    fn test_generate_loader_fish() {
+        let tmp = TempDir::new().unwrap();
+        let mut plugins = BTreeMap::new();
+        plugins.insert(
+            "done".to_string(),
+            PluginEntry {
+                repo: "franciscolourenco/done".to_string(),
+                init: "conf.d/done.fish".to_string(),
+                added_at: Utc::now(),
+                pinned_ref: None,
+                order: 0,
+            },
+        );
+
+        let content = generate_loader_content("fish", tmp.path(), &plugins);
+        assert!(content.contains("# Auto-generated by dotsmith"));
+        assert!(content.contains("source "));
+        assert!(content.contains("plugins/fish/done/conf.d/done.fish"));
+        assert!(!content.contains("run-shell"));
+    }
+
     #[test]
     fn test_generate_loader_empty() {
         let tmp = TempDir::new().unwrap();
@@ -724,6 +1534,97 @@ mod tests {
         assert!(!content.contains("source "));
     }
 
+    fn plugins_with_order(entries: &[(&str, u32)]) -> BTreeMap<String, PluginEntry> {
+        entries
+            .iter()
+            .map(|(name, order)| {
+                (
+                    name.to_string(),
+                    PluginEntry {
+                        repo: format!("zsh-users/{}", name),
+                        init: format!("{}.plugin.zsh", name),
+                        added_at: Utc::now(),
+                        pinned_ref: None,
+                        order: *order,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn sourced_plugins(content: &str) -> Vec<&str> {
+        content
+            .lines()
+            .filter_map(|l| l.strip_prefix("source "))
+            .map(|l| l.rsplit('/').nth(1).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_generate_loader_follows_order() {
+        let tmp = TempDir::new().unwrap();
+        let plugins = plugins_with_order(&[
+            ("zsh-syntax-highlighting", 2),
+            ("zsh-autosuggestions", 1),
+            ("zsh-completions", 0),
+        ]);
+
+        let content = generate_loader_content("zsh", tmp.path(), &plugins);
+        assert_eq!(
+            sourced_plugins(&content),
+            ["zsh-completions", "zsh-autosuggestions", "zsh-syntax-highlighting"]
+        );
+    }
+
+    #[test]
+    fn test_generate_loader_ties_sort_by_name() {
+        let tmp = TempDir::new().unwrap();
+        let plugins = plugins_with_order(&[("b", 0), ("a", 0), ("c", 0)]);
+        let content = generate_loader_content("zsh", tmp.path(), &plugins);
+        assert_eq!(sourced_plugins(&content), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_move_plugin_before_and_after() {
+        use crate::core::manifest::ToolEntry;
+
+        let tmp = TempDir::new().unwrap();
+        let mut manifest = Manifest::default();
+        manifest
+            .add_tool(
+                "zsh",
+                ToolEntry {
+                    tier: 1,
+                    config_paths: vec![],
+                    plugins_managed: true,
+                    plugin_manager: Some("dotsmith".to_string()),
+                    added_at: Utc::now(),
+                    last_snapshot: None,
+                    plugins: plugins_with_order(&[("a", 0), ("b", 0), ("c", 0)]),
+                },
+            )
+            .unwrap();
+
+        move_plugin(tmp.path(), &mut manifest, "zsh", "a", "c", false).unwrap();
+        let loader = std::fs::read_to_string(loader_path(tmp.path(), "zsh")).unwrap();
+        assert_eq!(sourced_plugins(&loader), ["b", "c", "a"]);
+
+        move_plugin(tmp.path(), &mut manifest, "zsh", "c", "b", true).unwrap();
+        let loader = std::fs::read_to_string(loader_path(tmp.path(), "zsh")).unwrap();
+        assert_eq!(sourced_plugins(&loader), ["c", "b", "a"]);
+
+        // The new order is persisted
+        let saved = Manifest::load(tmp.path()).unwrap();
+        let orders: Vec<u32> = ["c", "b", "a"]
+            .iter()
+            .map(|n| saved.get_tool("zsh").unwrap().plugins[*n].order)
+            .collect();
+        assert_eq!(orders, [0, 1, 2]);
+
+        assert!(move_plugin(tmp.path(), &mut manifest, "zsh", "a", "a", true).is_err());
+        assert!(move_plugin(tmp.path(), &mut manifest, "zsh", "a", "nope", true).is_err());
+    }
+
     // -- path helper tests --
 
     #[test]
@@ -757,12 +1658,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_loader_path_fish() {
+        let base = Path::new("/home/user/.config/dotsmith");
+        assert_eq!(
+            loader_path(base, "fish"),
+            PathBuf::from("/home/user/.config/dotsmith/plugins/fish/loader.fish")
+        );
+    }
+
     // -- validate_tool_supported tests --
 
     #[test]
     fn test_validate_supported() {
         assert!(validate_tool_supported("zsh").is_ok());
         assert!(validate_tool_supported("tmux").is_ok());
+        assert!(validate_tool_supported("fish").is_ok());
     }
 
     #[test]
@@ -770,4 +1681,386 @@ mod tests {
         assert!(validate_tool_supported("git").is_err());
         assert!(validate_tool_supported("nvim").is_err());
     }
+
+    // -- lazy.nvim tests --
+
+    const LAZY_LOCK: &str = r#"{
+  "lazy.nvim": { "branch": "main", "commit": "7e6c863bc7563efbdd757a310d17ebc95166cef3" },
+  "telescope.nvim": { "branch": "0.1.x", "commit": "a0bbec21143c7bc5f8bb02e0005fa0b982edc026" },
+  "nvim-treesitter": { "commit": "ae6c6ac3a8ee7af1b0ef31ae2fe4a5f1ef6b4e19" }
+}
+"#;
+
+    #[test]
+    fn test_parse_lazy_lock() {
+        let plugins = parse_lazy_lock(LAZY_LOCK).unwrap();
+        let names: Vec<&str> = plugins.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["lazy.nvim", "telescope.nvim", "nvim-treesitter"]);
+        assert_eq!(plugins[1].branch.as_deref(), Some("0.1.x"));
+        assert_eq!(plugins[1].commit, "a0bbec21143c7bc5f8bb02e0005fa0b982edc026");
+        assert_eq!(plugins[2].branch, None);
+
+        assert!(parse_lazy_lock("[]").is_err());
+        assert!(parse_lazy_lock(r#"{"a": {"branch": "main"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_list_lazy_plugins_from_config_dir() {
+        use crate::core::manifest::ToolEntry;
+
+        let tmp = TempDir::new().unwrap();
+        let nvim = tmp.path().join("nvim");
+        std::fs::create_dir_all(&nvim).unwrap();
+        std::fs::write(nvim.join("init.lua"), "require('config.lazy')").unwrap();
+        std::fs::write(nvim.join("lazy-lock.json"), LAZY_LOCK).unwrap();
+
+        let mut manifest = Manifest::default();
+        manifest
+            .add_tool(
+                "neovim",
+                ToolEntry {
+                    tier: 1,
+                    config_paths: vec![nvim.join("init.lua").to_string_lossy().to_string()],
+                    plugins_managed: false,
+                    plugin_manager: Some("lazy".to_string()),
+                    added_at: Utc::now(),
+                    last_snapshot: None,
+                    plugins: BTreeMap::new(),
+                },
+            )
+            .unwrap();
+
+        let plugins = list_lazy_plugins(&manifest, "neovim").unwrap();
+        assert_eq!(plugins.len(), 3);
+        assert_eq!(plugins[0].name, "lazy.nvim");
+    }
+
+    #[test]
+    fn test_lazy_managed_plugins_reject_changes() {
+        let tmp = TempDir::new().unwrap();
+        let mut manifest = Manifest::default();
+
+        let err = add_plugin(tmp.path(), &mut manifest, "nvim", "folke/trouble.nvim").unwrap_err();
+        assert!(err.to_string().contains("managed by lazy.nvim"), "{}", err);
+        let err = remove_plugin(tmp.path(), &mut manifest, "neovim", "trouble.nvim").unwrap_err();
+        assert!(err.to_string().contains("managed by lazy.nvim"), "{}", err);
+        let err = update_plugins(tmp.path(), &manifest, "nvim", None, None).unwrap_err();
+        assert!(err.to_string().contains("managed by lazy.nvim"), "{}", err);
+    }
+
+    // -- pin/unpin tests --
+
+    fn manifest_with_plugin(pinned_ref: Option<&str>) -> Manifest {
+        use crate::core::manifest::ToolEntry;
+
+        let mut plugins = BTreeMap::new();
+        plugins.insert(
+            "zsh-autosuggestions".to_string(),
+            PluginEntry {
+                repo: "zsh-users/zsh-autosuggestions".to_string(),
+                init: "zsh-autosuggestions.plugin.zsh".to_string(),
+                added_at: Utc::now(),
+                pinned_ref: pinned_ref.map(str::to_string),
+                order: 0,
+            },
+        );
+
+        let mut manifest = Manifest::default();
+        manifest
+            .add_tool(
+                "zsh",
+                ToolEntry {
+                    tier: 1,
+                    config_paths: vec![],
+                    plugins_managed: true,
+                    plugin_manager: Some("dotsmith".to_string()),
+                    added_at: Utc::now(),
+                    last_snapshot: None,
+                    plugins,
+                },
+            )
+            .unwrap();
+        manifest
+    }
+
+    #[test]
+    fn test_update_skips_pinned() {
+        let tmp = TempDir::new().unwrap();
+        let manifest = manifest_with_plugin(Some("abc1234"));
+
+        let results = update_plugins(tmp.path(), &manifest, "zsh", None, None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].pinned);
+        assert!(!results[0].updated);
+        assert_eq!(results[0].old_commit, "abc1234");
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}: {:?}", args, output);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn test_update_plugins_concurrently() {
+        use crate::core::manifest::ToolEntry;
+
+        let tmp = TempDir::new().unwrap();
+        let names = ["plugin-a", "plugin-b"];
+        let mut plugins = BTreeMap::new();
+        let mut transitions = Vec::new();
+
+        for name in names {
+            // Upstream with one commit, cloned as the installed plugin
+            let upstream = tmp.path().join("upstream").join(name);
+            std::fs::create_dir_all(&upstream).unwrap();
+            git(&upstream, &["init", "-q"]);
+            git(&upstream, &["commit", "-q", "--allow-empty", "-m", "one"]);
+            let dir = plugin_dir(tmp.path(), "tmux", name);
+            std::fs::create_dir_all(dir.parent().unwrap()).unwrap();
+            git_clone(&format!("file://{}", upstream.display()), &dir).unwrap();
+            let old = git(&dir, &["rev-parse", "HEAD"]);
+
+            // A new upstream commit to pull
+            git(&upstream, &["commit", "-q", "--allow-empty", "-m", "two"]);
+            let new = git(&upstream, &["rev-parse", "HEAD"]);
+            transitions.push((old, new));
+
+            plugins.insert(
+                name.to_string(),
+                PluginEntry {
+                    repo: format!("file://{}", upstream.display()),
+                    init: format!("{}.tmux", name),
+                    added_at: Utc::now(),
+                    pinned_ref: None,
+                    order: 0,
+                },
+            );
+        }
+
+        let mut manifest = Manifest::default();
+        manifest
+            .add_tool(
+                "tmux",
+                ToolEntry {
+                    tier: 1,
+                    config_paths: vec![],
+                    plugins_managed: true,
+                    plugin_manager: Some("dotsmith".to_string()),
+                    added_at: Utc::now(),
+                    last_snapshot: None,
+                    plugins,
+                },
+            )
+            .unwrap();
+
+        let results = update_plugins(tmp.path(), &manifest, "tmux", None, Some(2)).unwrap();
+        assert_eq!(results.len(), 2);
+        for ((result, name), (old, new)) in results.iter().zip(names).zip(&transitions) {
+            assert_eq!(result.name, name, "results keep load order");
+            assert!(result.updated);
+            assert_eq!(&result.old_commit, old);
+            assert_eq!(&result.new_commit, new);
+            assert_ne!(old, new);
+        }
+    }
+
+    #[test]
+    fn test_check_updates_leaves_head_alone() {
+        use crate::core::manifest::ToolEntry;
+
+        let tmp = TempDir::new().unwrap();
+        let mut plugins = BTreeMap::new();
+        for (name, upstream_commits) in [("plugin-a", 1), ("plugin-b", 3)] {
+            let upstream = tmp.path().join("upstream").join(name);
+            std::fs::create_dir_all(&upstream).unwrap();
+            git(&upstream, &["init", "-q"]);
+            git(&upstream, &["commit", "-q", "--allow-empty", "-m", "one"]);
+            let dir = plugin_dir(tmp.path(), "tmux", name);
+            std::fs::create_dir_all(dir.parent().unwrap()).unwrap();
+            git_clone(&format!("file://{}", upstream.display()), &dir).unwrap();
+
+            // plugin-b's upstream moves on after it was installed
+            for i in 1..upstream_commits {
+                let msg = format!("more {}", i);
+                git(&upstream, &["commit", "-q", "--allow-empty", "-m", &msg]);
+            }
+
+            plugins.insert(
+                name.to_string(),
+                PluginEntry {
+                    repo: format!("file://{}", upstream.display()),
+                    init: format!("{}.tmux", name),
+                    added_at: Utc::now(),
+                    pinned_ref: None,
+                    order: 0,
+                },
+            );
+        }
+
+        let mut manifest = Manifest::default();
+        manifest
+            .add_tool(
+                "tmux",
+                ToolEntry {
+                    tier: 1,
+                    config_paths: vec![],
+                    plugins_managed: true,
+                    plugin_manager: Some("dotsmith".to_string()),
+                    added_at: Utc::now(),
+                    last_snapshot: None,
+                    plugins,
+                },
+            )
+            .unwrap();
+
+        let dir = plugin_dir(tmp.path(), "tmux", "plugin-b");
+        let before = git(&dir, &["rev-parse", "HEAD"]);
+
+        let behind = check_updates(tmp.path(), &manifest, "tmux").unwrap();
+        let behind: Vec<(&str, usize)> =
+            behind.iter().map(|(name, b)| (name.as_str(), *b.as_ref().unwrap())).collect();
+        assert_eq!(behind, [("plugin-a", 0), ("plugin-b", 2)]);
+        assert_eq!(git(&dir, &["rev-parse", "HEAD"]), before);
+
+        // A remote that's gone fails only its own plugin
+        std::fs::remove_dir_all(tmp.path().join("upstream/plugin-a")).unwrap();
+        let behind = check_updates(tmp.path(), &manifest, "tmux").unwrap();
+        assert!(behind[0].1.is_err());
+        assert_eq!(*behind[1].1.as_ref().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_lockfile_roundtrip_into_clean_dir() {
+        use crate::core::manifest::ToolEntry;
+
+        let tmp = TempDir::new().unwrap();
+        let upstream = tmp.path().join("upstream").join("tmux-sensible");
+        std::fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "-q"]);
+        std::fs::write(upstream.join("sensible.tmux"), "# one\n").unwrap();
+        git(&upstream, &["add", "."]);
+        git(&upstream, &["commit", "-q", "-m", "one"]);
+        let locked_commit = git(&upstream, &["rev-parse", "HEAD"]);
+        git(&upstream, &["commit", "-q", "--allow-empty", "-m", "two"]);
+        let url = format!("file://{}", upstream.display());
+
+        let tool_entry = |plugins| ToolEntry {
+            tier: 1,
+            config_paths: vec![],
+            plugins_managed: false,
+            plugin_manager: None,
+            added_at: Utc::now(),
+            last_snapshot: None,
+            plugins,
+        };
+
+        // Source machine: the plugin is installed at the older commit
+        let source = tmp.path().join("source");
+        let dir = plugin_dir(&source, "tmux", "tmux-sensible");
+        std::fs::create_dir_all(dir.parent().unwrap()).unwrap();
+        git_clone_at(&url, &dir, &locked_commit).unwrap();
+        let mut plugins = BTreeMap::new();
+        plugins.insert(
+            "tmux-sensible".to_string(),
+            PluginEntry {
+                repo: url.clone(),
+                init: "sensible.tmux".to_string(),
+                added_at: Utc::now(),
+                pinned_ref: None,
+                order: 0,
+            },
+        );
+        let mut manifest = Manifest::default();
+        manifest.add_tool("tmux", tool_entry(plugins)).unwrap();
+
+        let locked = write_lockfile(&source, &manifest).unwrap();
+        assert_eq!(locked.len(), 1);
+        assert_eq!(locked[0].commit, locked_commit);
+        let content = std::fs::read_to_string(lockfile_path(&source)).unwrap();
+        assert!(content.contains("[[plugin]]"));
+        // Same state, same bytes
+        write_lockfile(&source, &manifest).unwrap();
+        assert_eq!(std::fs::read_to_string(lockfile_path(&source)).unwrap(), content);
+
+        // Fresh machine: only the lockfile and a tracked tool
+        let target = tmp.path().join("target");
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::copy(lockfile_path(&source), lockfile_path(&target)).unwrap();
+        let mut manifest = Manifest::default();
+        manifest.add_tool("tmux", tool_entry(BTreeMap::new())).unwrap();
+
+        let results = install_from_lockfile(&target, &mut manifest, "tmux").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].status, LockSyncStatus::Installed);
+        let installed = plugin_dir(&target, "tmux", "tmux-sensible");
+        assert_eq!(git_head_commit(&installed).unwrap(), locked_commit);
+        let entry = &manifest.get_tool("tmux").unwrap().plugins["tmux-sensible"];
+        assert_eq!(entry.repo, url);
+        assert_eq!(entry.init, "sensible.tmux");
+        assert!(loader_path(&target, "tmux").exists());
+
+        // Re-running is a no-op; drifted plugins are moved back
+        let results = install_from_lockfile(&target, &mut manifest, "tmux").unwrap();
+        assert_eq!(results[0].status, LockSyncStatus::UpToDate);
+        git(&installed, &["checkout", "-q", "origin/HEAD"]);
+        assert_ne!(git_head_commit(&installed).unwrap(), locked_commit);
+        let results = install_from_lockfile(&target, &mut manifest, "tmux").unwrap();
+        assert_eq!(results[0].status, LockSyncStatus::CheckedOut);
+        assert_eq!(git_head_commit(&installed).unwrap(), locked_commit);
+    }
+
+    #[test]
+    fn test_sync_without_lockfile_fails() {
+        let tmp = TempDir::new().unwrap();
+        let mut manifest = manifest_with_plugin(None);
+        let err = install_from_lockfile(tmp.path(), &mut manifest, "zsh").unwrap_err();
+        assert!(err.to_string().contains("no plugin lockfile"));
+    }
+
+    #[test]
+    fn test_pin_and_unpin() {
+        let tmp = TempDir::new().unwrap();
+        let mut manifest = manifest_with_plugin(None);
+
+        // Fake an installed plugin: a git repo with one commit
+        let dir = plugin_dir(tmp.path(), "zsh", "zsh-autosuggestions");
+        std::fs::create_dir_all(&dir).unwrap();
+        for args in [
+            vec!["init", "-q"],
+            vec!["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-q", "--allow-empty", "-m", "init"],
+        ] {
+            std::process::Command::new("git")
+                .args(&args)
+                .current_dir(&dir)
+                .output()
+                .unwrap();
+        }
+        let head = git_head_commit(&dir).unwrap();
+
+        let pinned = pin_plugin(tmp.path(), &mut manifest, "zsh", "zsh-autosuggestions").unwrap();
+        assert_eq!(pinned, head);
+        assert_eq!(
+            manifest.tools["zsh"].plugins["zsh-autosuggestions"].pinned_ref.as_deref(),
+            Some(head.as_str())
+        );
+
+        let previous =
+            unpin_plugin(tmp.path(), &mut manifest, "zsh", "zsh-autosuggestions").unwrap();
+        assert_eq!(previous, Some(head));
+        assert!(manifest.tools["zsh"].plugins["zsh-autosuggestions"].pinned_ref.is_none());
+    }
+
+    #[test]
+    fn test_pin_unknown_plugin() {
+        let tmp = TempDir::new().unwrap();
+        let mut manifest = manifest_with_plugin(None);
+        assert!(pin_plugin(tmp.path(), &mut manifest, "zsh", "nope").is_err());
+        assert!(unpin_plugin(tmp.path(), &mut manifest, "zsh", "nope").is_err());
+    }
 }
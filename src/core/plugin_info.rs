@@ -7,6 +7,8 @@ pub struct PluginInfo {
     pub url: String,
     pub description: Option<String>,
     pub config_excerpt: Option<String>,
+    /// Whether a README file was found in the plugin directory.
+    pub readme_found: bool,
 }
 
 /// Scan a plugin directory and extract info from README and git remote.
@@ -15,13 +17,16 @@ pub struct PluginInfo {
 /// `name` is the plugin name, `repo` is the repo specifier from the manifest.
 pub fn scan_plugin(plugin_dir: &Path, name: &str, repo: &str) -> PluginInfo {
     let url = resolve_url(repo, plugin_dir);
-    let (description, config_excerpt) = read_readme(plugin_dir);
+    let readme = read_readme(plugin_dir);
+    let readme_found = readme.is_some();
+    let (description, config_excerpt) = readme.unwrap_or_default();
 
     PluginInfo {
         name: name.to_string(),
         url,
         description,
         config_excerpt,
+        readme_found,
     }
 }
 
@@ -85,7 +90,7 @@ fn clean_git_url(url: &str) -> String {
 
 /// Read README.md (or README, README.rst, README.txt) and extract info.
 /// Returns (description, config_excerpt).
-fn read_readme(plugin_dir: &Path) -> (Option<String>, Option<String>) {
+fn read_readme(plugin_dir: &Path) -> Option<(Option<String>, Option<String>)> {
     let readme_names = ["README.md", "readme.md", "README", "README.rst", "README.txt"];
 
     let readme_path = readme_names
@@ -93,18 +98,13 @@ fn read_readme(plugin_dir: &Path) -> (Option<String>, Option<String>) {
         .map(|name| plugin_dir.join(name))
         .find(|p| p.is_file());
 
-    let Some(path) = readme_path else {
-        return (None, None);
-    };
-
-    let Ok(content) = std::fs::read_to_string(&path) else {
-        return (None, None);
-    };
+    let path = readme_path?;
+    let content = std::fs::read_to_string(&path).ok()?;
 
     let description = extract_description(&content);
     let config_excerpt = extract_config_section(&content);
 
-    (description, config_excerpt)
+    Some((description, config_excerpt))
 }
 
 /// Check if a line is a setext heading underline (=== or ---).
@@ -344,6 +344,7 @@ mod tests {
         assert_eq!(info.url, "https://github.com/user/test-plugin");
         assert!(info.description.is_none());
         assert!(info.config_excerpt.is_none());
+        assert!(!info.readme_found);
     }
 
     #[test]
@@ -355,6 +356,7 @@ mod tests {
         let info = scan_plugin(tmp.path(), "test-plugin", "user/test-plugin");
         assert_eq!(info.description.as_deref(), Some("A useful plugin for testing."));
         assert!(info.config_excerpt.as_deref().unwrap().contains("TEST_OPT"));
+        assert!(info.readme_found);
     }
 
     #[test]
@@ -1,14 +1,21 @@
 use std::collections::BTreeMap;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+use crate::core::config::DotsmithConfig;
+use crate::core::crypto::Cipher;
 use crate::core::errors::DotsmithError;
 use crate::core::manifest::{Manifest, ToolEntry};
+use crate::core::snapshot::{FileDiff, SnapshotContent, SnapshotEngine};
 use crate::util;
 
 /// Metadata about a saved profile, serialized to profile.toml.
@@ -18,6 +25,13 @@ pub struct ProfileMeta {
     pub created_at: DateTime<Utc>,
     pub tools: BTreeMap<String, ToolEntry>,
     pub checksums: BTreeMap<String, String>,
+    /// Machine the profile belongs to, used by `profile auto`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Files are stored encrypted (`general.encrypt` was set when saved).
+    /// Checksums are of the encrypted files.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
 }
 
 /// Summary of a profile for listing.
@@ -27,6 +41,7 @@ pub struct ProfileSummary {
     pub created_at: DateTime<Utc>,
     pub tool_count: usize,
     pub file_count: usize,
+    pub hostname: Option<String>,
 }
 
 /// Result of loading a profile.
@@ -60,6 +75,21 @@ fn validate_profile_name(name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Read and parse a saved profile's profile.toml.
+pub fn read_profile_meta(config_dir: &Path, name: &str) -> Result<ProfileMeta> {
+    validate_profile_name(name)?;
+
+    let profile_dir = profiles_dir(config_dir).join(name);
+    if !profile_dir.exists() {
+        return Err(DotsmithError::ProfileNotFound(name.to_string()).into());
+    }
+
+    let meta_path = profile_dir.join("profile.toml");
+    let meta_content = fs::read_to_string(&meta_path)
+        .with_context(|| format!("failed to read {}", meta_path.display()))?;
+    toml::from_str(&meta_content).with_context(|| "failed to parse profile.toml")
+}
+
 /// Compute SHA-256 hash of a file's contents.
 fn hash_file(path: &Path) -> Result<String> {
     let content = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
@@ -68,11 +98,50 @@ fn hash_file(path: &Path) -> Result<String> {
     Ok(format!("{:x}", hasher.finalize()))
 }
 
+/// Copy `src` to `dest`, encrypting it when `cipher` is given.
+fn store_file(src: &Path, dest: &Path, cipher: Option<&Cipher>) -> Result<()> {
+    let copy_err = || format!("failed to copy {} to {}", src.display(), dest.display());
+    match cipher {
+        Some(cipher) => {
+            let content =
+                fs::read(src).with_context(|| format!("failed to read {}", src.display()))?;
+            util::fs::atomic_write_bytes(dest, &cipher.encrypt(&content)?).with_context(copy_err)
+        }
+        None => fs::copy(src, dest).map(|_| ()).with_context(copy_err),
+    }
+}
+
+/// Write a file stored in a profile back to `dest`, decrypting it when
+/// `cipher` is given.
+fn restore_file(src: &Path, dest: &Path, cipher: Option<&Cipher>) -> Result<()> {
+    let restore_err = || format!("failed to restore {} to {}", src.display(), dest.display());
+    match cipher {
+        Some(cipher) => {
+            let content = read_stored(src, Some(cipher))?;
+            fs::write(dest, content).with_context(restore_err)
+        }
+        None => fs::copy(src, dest).map(|_| ()).with_context(restore_err),
+    }
+}
+
+/// Read a file stored in a profile, decrypting it when `cipher` is given.
+/// A missing file reads as empty.
+fn read_stored(path: &Path, cipher: Option<&Cipher>) -> Result<Vec<u8>> {
+    let Ok(content) = fs::read(path) else {
+        return Ok(Vec::new());
+    };
+    match cipher {
+        Some(cipher) => cipher.decrypt(&content),
+        None => Ok(content),
+    }
+}
+
 /// Copy a single file into the profile's files directory, returning the relative key and hash.
 fn copy_file_to_profile(
     file_path: &Path,
     tool_name: &str,
     files_dir: &Path,
+    cipher: Option<&Cipher>,
 ) -> Result<Option<(String, String)>> {
     if !file_path.is_file() {
         return Ok(None);
@@ -87,8 +156,7 @@ fn copy_file_to_profile(
         .with_context(|| format!("failed to create {}", tool_dir.display()))?;
 
     let dest = tool_dir.join(file_name);
-    fs::copy(file_path, &dest)
-        .with_context(|| format!("failed to copy {} to {}", file_path.display(), dest.display()))?;
+    store_file(file_path, &dest, cipher)?;
 
     let hash = hash_file(&dest)?;
     let key = format!("{}/{}", tool_name, file_name.to_string_lossy());
@@ -101,6 +169,7 @@ fn copy_dir_to_profile(
     dir_path: &Path,
     tool_name: &str,
     files_dir: &Path,
+    cipher: Option<&Cipher>,
 ) -> Result<Vec<(String, String)>> {
     let mut results = Vec::new();
 
@@ -126,9 +195,7 @@ fn copy_dir_to_profile(
                 .file_name()
                 .ok_or_else(|| anyhow::anyhow!("no filename"))?;
             let dest = dest_dir.join(file_name);
-            fs::copy(&path, &dest).with_context(|| {
-                format!("failed to copy {} to {}", path.display(), dest.display())
-            })?;
+            store_file(&path, &dest, cipher)?;
             let hash = hash_file(&dest)?;
             let key = format!(
                 "{}/{}/{}",
@@ -144,10 +211,14 @@ fn copy_dir_to_profile(
 }
 
 /// Save the current manifest and config file contents as a named profile.
+///
+/// The profile is stamped with `hostname`, or with this machine's hostname
+/// when `None`. Files are encrypted when `general.encrypt` is set.
 pub fn save_profile(
     config_dir: &Path,
     manifest: &Manifest,
     name: &str,
+    hostname: Option<&str>,
 ) -> Result<(usize, usize)> {
     validate_profile_name(name)?;
 
@@ -156,6 +227,15 @@ pub fn save_profile(
         return Err(DotsmithError::ProfileAlreadyExists(name.to_string()).into());
     }
 
+    // Taken from the snapshot engine, which checks the passphrase against
+    // encrypted snapshots
+    let engine = if DotsmithConfig::load(config_dir).general.encrypt {
+        Some(SnapshotEngine::open(config_dir)?)
+    } else {
+        None
+    };
+    let cipher = engine.as_ref().map(SnapshotEngine::cipher).transpose()?;
+
     let files_dir = profile_dir.join("files");
     fs::create_dir_all(&files_dir)
         .with_context(|| format!("failed to create {}", files_dir.display()))?;
@@ -164,17 +244,18 @@ pub fn save_profile(
     let mut file_count = 0usize;
 
     for (tool_name, entry) in &manifest.tools {
-        for config_path in &entry.config_paths {
-            let expanded = util::paths::expand_tilde(config_path);
+        let paths = entry.config_paths.iter().flat_map(|p| util::paths::expand_config_path(p));
+        for expanded in paths {
             if expanded.is_file() {
                 if let Some((key, hash)) =
-                    copy_file_to_profile(&expanded, tool_name, &files_dir)?
+                    copy_file_to_profile(&expanded, tool_name, &files_dir, cipher)?
                 {
                     checksums.insert(key, hash);
                     file_count += 1;
                 }
             } else if expanded.is_dir() {
-                let dir_results = copy_dir_to_profile(&expanded, tool_name, &files_dir)?;
+                let dir_results =
+                    copy_dir_to_profile(&expanded, tool_name, &files_dir, cipher)?;
                 file_count += dir_results.len();
                 for (key, hash) in dir_results {
                     checksums.insert(key, hash);
@@ -188,6 +269,8 @@ pub fn save_profile(
         created_at: Utc::now(),
         tools: manifest.tools.clone(),
         checksums,
+        hostname: hostname.map(str::to_string).or_else(current_hostname),
+        encrypted: cipher.is_some(),
     };
 
     let toml_content = toml::to_string_pretty(&meta).context("failed to serialize profile")?;
@@ -204,20 +287,11 @@ pub fn load_profile(
     name: &str,
     add_untracked: bool,
 ) -> Result<ProfileLoadResult> {
-    validate_profile_name(name)?;
-
-    let profile_dir = profiles_dir(config_dir).join(name);
-    if !profile_dir.exists() {
-        return Err(DotsmithError::ProfileNotFound(name.to_string()).into());
-    }
+    let meta = read_profile_meta(config_dir, name)?;
+    let cipher = profile_cipher(&meta)?;
+    let cipher = cipher.as_ref();
 
-    let meta_path = profile_dir.join("profile.toml");
-    let meta_content = fs::read_to_string(&meta_path)
-        .with_context(|| format!("failed to read {}", meta_path.display()))?;
-    let meta: ProfileMeta =
-        toml::from_str(&meta_content).with_context(|| "failed to parse profile.toml")?;
-
-    let files_dir = profile_dir.join("files");
+    let files_dir = profiles_dir(config_dir).join(name).join("files");
     let backup_dir = config_dir.join("backups");
     fs::create_dir_all(&backup_dir)?;
 
@@ -246,7 +320,7 @@ pub fn load_profile(
 
         // Restore files for this tool
         for config_path in &tool_entry.config_paths {
-            let target = util::paths::expand_tilde(config_path);
+            let target = util::paths::expand_tilde_checked(config_path)?;
 
             // Try to find the source file in the profile
             if target.is_file() || !target.exists() {
@@ -279,13 +353,7 @@ pub fn load_profile(
                         fs::create_dir_all(parent)?;
                     }
 
-                    fs::copy(&source, &target).with_context(|| {
-                        format!(
-                            "failed to restore {} to {}",
-                            source.display(),
-                            target.display()
-                        )
-                    })?;
+                    restore_file(&source, &target, cipher)?;
                     result.restored_files += 1;
                 }
             } else if target.is_dir() || !target.exists() {
@@ -312,7 +380,7 @@ pub fn load_profile(
                                 fs::copy(&dest_file, &backup_path)?;
                                 result.backed_up_files += 1;
                             }
-                            fs::copy(&src_file, &dest_file)?;
+                            restore_file(&src_file, &dest_file, cipher)?;
                             result.restored_files += 1;
                         }
                     }
@@ -328,6 +396,367 @@ pub fn load_profile(
     Ok(result)
 }
 
+/// Path of the snapshot history database stored inside a profile.
+fn history_db_path(config_dir: &Path, name: &str) -> PathBuf {
+    profiles_dir(config_dir).join(name).join("history.db")
+}
+
+/// Whether a saved profile carries snapshot history.
+pub fn has_history(config_dir: &Path, name: &str) -> bool {
+    history_db_path(config_dir, name).is_file()
+}
+
+/// Export the snapshot history of every tool in a saved profile into the
+/// profile directory. Returns the number of snapshot rows exported.
+pub fn save_profile_history(config_dir: &Path, name: &str) -> Result<usize> {
+    let meta = read_profile_meta(config_dir, name)?;
+    let tools: Vec<&str> = meta.tools.keys().map(String::as_str).collect();
+
+    let records = SnapshotEngine::open(config_dir)?.export_records(&tools)?;
+    let profile_history = SnapshotEngine::open_file(&history_db_path(config_dir, name))?;
+    profile_history.import_records(&records)
+}
+
+/// Merge a profile's stored snapshot history for `tools` into snapshots.db.
+/// Rows already present are skipped. Returns the number of rows merged.
+pub fn load_profile_history(config_dir: &Path, name: &str, tools: &[&str]) -> Result<usize> {
+    validate_profile_name(name)?;
+    if !has_history(config_dir, name) {
+        anyhow::bail!(
+            "profile '{}' has no snapshot history (save it with --with-history)",
+            name
+        );
+    }
+
+    let profile_history = SnapshotEngine::open_file(&history_db_path(config_dir, name))?;
+    let records = profile_history.export_records(tools)?;
+    SnapshotEngine::open(config_dir)?.import_records(&records)
+}
+
+/// Diff a tool's current on-disk files against the copies stored in a profile.
+///
+/// The profile copy is the "old" side and the current file the "new" side.
+/// Files missing on either side diff against empty content. Only files whose
+/// content differs are returned.
+pub fn diff_profile_tool(config_dir: &Path, name: &str, tool: &str) -> Result<Vec<FileDiff>> {
+    let meta = read_profile_meta(config_dir, name)?;
+    let entry = meta
+        .tools
+        .get(tool)
+        .ok_or_else(|| anyhow::anyhow!("'{}' is not part of profile '{}'", tool, name))?;
+
+    let pairs = file_pairs(config_dir, name, tool, &entry.config_paths);
+    diff_pairs(pairs, &meta.checksums, profile_cipher(&meta)?.as_ref())
+}
+
+/// Diff every file in a profile against the current on-disk files.
+///
+/// For tools the manifest also tracks, its config paths are compared too, so
+/// files tracked locally but absent from the profile show up as additions.
+/// Files whose current hash matches the checksum recorded at save time are
+/// skipped without reading the stored copy.
+pub fn diff_profile(config_dir: &Path, manifest: &Manifest, name: &str) -> Result<Vec<FileDiff>> {
+    let meta = read_profile_meta(config_dir, name)?;
+
+    let mut pairs = Vec::new();
+    for (tool_name, entry) in &meta.tools {
+        let mut config_paths = entry.config_paths.clone();
+        if let Some(local) = manifest.tools.get(tool_name) {
+            for path in &local.config_paths {
+                if !config_paths.contains(path) {
+                    config_paths.push(path.clone());
+                }
+            }
+        }
+        pairs.extend(file_pairs(config_dir, name, tool_name, &config_paths));
+    }
+
+    diff_pairs(pairs, &meta.checksums, profile_cipher(&meta)?.as_ref())
+}
+
+/// The cipher for reading an encrypted profile's files, or `None` for a
+/// plain one.
+fn profile_cipher(meta: &ProfileMeta) -> Result<Option<Cipher>> {
+    meta.encrypted
+        .then(|| Cipher::from_env_or_prompt(false))
+        .transpose()
+}
+
+/// Pair each stored profile file with its current on-disk location, keyed the
+/// same way as `ProfileMeta.checksums`.
+fn file_pairs(
+    config_dir: &Path,
+    name: &str,
+    tool: &str,
+    config_paths: &[String],
+) -> Vec<(String, PathBuf, PathBuf)> {
+    let tool_files = profiles_dir(config_dir).join(name).join("files").join(tool);
+    let mut pairs = Vec::new();
+
+    for config_path in config_paths {
+        let current = util::paths::expand_tilde(config_path);
+        let Some(base_name) = current.file_name() else {
+            continue;
+        };
+        let stored = tool_files.join(base_name);
+        let key = format!("{}/{}", tool, base_name.to_string_lossy());
+
+        if stored.is_dir() || current.is_dir() {
+            // Union of file names on both sides, so added and deleted files show up
+            let mut names = std::collections::BTreeSet::new();
+            for dir in [&stored, &current] {
+                if let Ok(entries) = fs::read_dir(dir) {
+                    for e in entries.flatten() {
+                        if e.path().is_file() {
+                            names.insert(e.file_name());
+                        }
+                    }
+                }
+            }
+            for file_name in names {
+                pairs.push((
+                    format!("{}/{}", key, file_name.to_string_lossy()),
+                    stored.join(&file_name),
+                    current.join(&file_name),
+                ));
+            }
+        } else {
+            pairs.push((key, stored, current));
+        }
+    }
+
+    pairs
+}
+
+fn diff_pairs(
+    pairs: Vec<(String, PathBuf, PathBuf)>,
+    checksums: &BTreeMap<String, String>,
+    cipher: Option<&Cipher>,
+) -> Result<Vec<FileDiff>> {
+    let mut diffs = Vec::new();
+    for (key, stored, current) in pairs {
+        if let Some(saved) = checksums.get(&key)
+            && current.is_file()
+            && hash_file(&current).is_ok_and(|hash| hash == *saved)
+        {
+            continue;
+        }
+
+        let old = SnapshotContent::from_bytes(read_stored(&stored, cipher)?);
+        let new = SnapshotContent::from_bytes(fs::read(&current).unwrap_or_default());
+        if old == new {
+            continue;
+        }
+
+        let file_path = util::paths::contract_tilde(&current);
+        diffs.push(match (old, new) {
+            (SnapshotContent::Text(old_content), SnapshotContent::Text(new_content)) => FileDiff {
+                file_path,
+                old_content,
+                new_content,
+                binary: false,
+            },
+            _ => FileDiff {
+                file_path,
+                old_content: String::new(),
+                new_content: String::new(),
+                binary: true,
+            },
+        });
+    }
+
+    Ok(diffs)
+}
+
+/// Pack a saved profile into a gzipped tarball at `out_path`, holding
+/// `profile.toml`, the `files/` tree, and `history.db` when present.
+pub fn export_profile(config_dir: &Path, name: &str, out_path: &Path) -> Result<()> {
+    read_profile_meta(config_dir, name)?;
+    let profile_dir = profiles_dir(config_dir).join(name);
+
+    let file = fs::File::create(out_path)
+        .with_context(|| format!("failed to create {}", out_path.display()))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    builder.follow_symlinks(false);
+
+    let write_failed = || format!("failed to write {}", out_path.display());
+    builder
+        .append_path_with_name(profile_dir.join("profile.toml"), "profile.toml")
+        .with_context(write_failed)?;
+    if profile_dir.join("files").is_dir() {
+        builder
+            .append_dir_all("files", profile_dir.join("files"))
+            .with_context(write_failed)?;
+    }
+    if profile_dir.join("history.db").is_file() {
+        builder
+            .append_path_with_name(profile_dir.join("history.db"), "history.db")
+            .with_context(write_failed)?;
+    }
+    builder
+        .into_inner()
+        .and_then(GzEncoder::finish)
+        .with_context(write_failed)?;
+
+    Ok(())
+}
+
+/// Unpack a profile tarball written by [`export_profile`]. The profile takes
+/// its name from the archive's `profile.toml`; an existing profile of that
+/// name is only replaced when `force` is set.
+///
+/// Archives holding links, absolute paths, `..` components, or anything
+/// besides the profile's own files are rejected before extraction, and the
+/// unpacked files must match the checksums recorded in `profile.toml`.
+pub fn import_profile(config_dir: &Path, archive_path: &Path, force: bool) -> Result<ProfileMeta> {
+    if !archive_path.is_file() {
+        anyhow::bail!("no such archive: {}", archive_path.display());
+    }
+    check_archive_entries(archive_path)?;
+
+    let dir = profiles_dir(config_dir);
+    let staging = dir.join(format!(".import-{}", std::process::id()));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)
+        .with_context(|| format!("failed to create {}", staging.display()))?;
+
+    let result = unpack_into(config_dir, archive_path, &staging, force);
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+/// Open a profile tarball for reading.
+fn open_archive(archive_path: &Path) -> Result<tar::Archive<GzDecoder<fs::File>>> {
+    let file = fs::File::open(archive_path)
+        .with_context(|| format!("failed to open {}", archive_path.display()))?;
+    Ok(tar::Archive::new(GzDecoder::new(file)))
+}
+
+fn unpack_into(
+    config_dir: &Path,
+    archive_path: &Path,
+    staging: &Path,
+    force: bool,
+) -> Result<ProfileMeta> {
+    open_archive(archive_path)?
+        .unpack(staging)
+        .with_context(|| format!("failed to unpack {}", archive_path.display()))?;
+
+    let meta_path = staging.join("profile.toml");
+    let meta_content = fs::read_to_string(&meta_path)
+        .with_context(|| format!("{} has no profile.toml", archive_path.display()))?;
+    let meta: ProfileMeta =
+        toml::from_str(&meta_content).with_context(|| "failed to parse profile.toml")?;
+    validate_profile_name(&meta.name)?;
+
+    for (key, expected) in &meta.checksums {
+        if !is_plain_relative(Path::new(key)) {
+            anyhow::bail!("unexpected checksum entry '{}' in profile.toml", key);
+        }
+        let path = staging.join("files").join(key);
+        let actual = hash_file(&path)
+            .with_context(|| format!("archive is missing {}", key))?;
+        if actual != *expected {
+            anyhow::bail!("checksum mismatch for {} — the archive may be corrupt", key);
+        }
+    }
+
+    let dest = profiles_dir(config_dir).join(&meta.name);
+    if dest.exists() {
+        if !force {
+            return Err(DotsmithError::ProfileImportConflict(meta.name.clone()).into());
+        }
+        fs::remove_dir_all(&dest)
+            .with_context(|| format!("failed to replace profile at {}", dest.display()))?;
+    }
+    fs::rename(staging, &dest)
+        .with_context(|| format!("failed to move profile into {}", dest.display()))?;
+
+    Ok(meta)
+}
+
+/// Refuse archive entries that could land outside the profile directory.
+fn check_archive_entries(archive_path: &Path) -> Result<()> {
+    let not_a_profile = || format!("{} is not a profile archive", archive_path.display());
+    let mut archive = open_archive(archive_path)?;
+    for entry in archive.entries().with_context(not_a_profile)? {
+        let entry = entry.with_context(not_a_profile)?;
+        let path = entry.path().with_context(not_a_profile)?.into_owned();
+
+        let entry_type = entry.header().entry_type();
+        if !(entry_type.is_file() || entry_type.is_dir()) {
+            anyhow::bail!(
+                "{} contains a link or special file ({}), refusing to import",
+                archive_path.display(),
+                path.display()
+            );
+        }
+
+        let top = path
+            .components()
+            .find(|c| !matches!(c, Component::CurDir))
+            .map(|c| c.as_os_str().to_string_lossy().into_owned());
+        let known = matches!(top.as_deref(), Some("profile.toml" | "files" | "history.db"));
+        if !is_plain_relative(&path) || !known {
+            anyhow::bail!(
+                "unexpected entry '{}' in {}, refusing to import",
+                path.display(),
+                archive_path.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `path` is relative and made only of plain names (and `.`), so
+/// joining it onto a directory stays inside that directory.
+fn is_plain_relative(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// This machine's hostname, or `None` if it can't be determined.
+pub fn current_hostname() -> Option<String> {
+    let from_file = ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .find_map(|p| fs::read_to_string(p).ok());
+    let name = match from_file {
+        Some(name) => name,
+        None => {
+            let output = Command::new("hostname").output().ok()?;
+            String::from_utf8(output.stdout).ok()?
+        }
+    };
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Pick the profile saved for `hostname`. Names compare case-insensitively,
+/// and an exact match beats one on the short name (`laptop` for
+/// `laptop.local`). Ties go to the most recently saved profile.
+pub fn match_hostname<'a>(
+    profiles: &'a [ProfileSummary],
+    hostname: &str,
+) -> Option<&'a ProfileSummary> {
+    let short = |h: &str| h.split('.').next().unwrap_or(h).to_lowercase();
+    let latest = |matches: &dyn Fn(&str) -> bool| {
+        profiles
+            .iter()
+            .filter(|p| p.hostname.as_deref().is_some_and(matches))
+            .max_by_key(|p| p.created_at)
+    };
+
+    latest(&|h| h.eq_ignore_ascii_case(hostname))
+        .or_else(|| latest(&|h| short(h) == short(hostname)))
+}
+
 /// List all saved profiles.
 pub fn list_profiles(config_dir: &Path) -> Result<Vec<ProfileSummary>> {
     let dir = profiles_dir(config_dir);
@@ -356,6 +785,7 @@ pub fn list_profiles(config_dir: &Path) -> Result<Vec<ProfileSummary>> {
                 created_at: meta.created_at,
                 tool_count: meta.tools.len(),
                 file_count: meta.checksums.len(),
+                hostname: meta.hostname,
             });
         }
     }
@@ -404,6 +834,336 @@ mod tests {
         manifest
     }
 
+    #[test]
+    fn test_diff_profile() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let manifest = sample_manifest(tmp.path());
+        save_profile(&config_dir, &manifest, "base", None).unwrap();
+
+        // Unchanged file produces no diff
+        assert!(diff_profile_tool(&config_dir, "base", "faketool").unwrap().is_empty());
+
+        let config_path = manifest.tools["faketool"].config_paths[0].clone();
+        fs::write(&config_path, "key = other\n").unwrap();
+
+        let diffs = diff_profile_tool(&config_dir, "base", "faketool").unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].old_content, "key = value\n");
+        assert_eq!(diffs[0].new_content, "key = other\n");
+    }
+
+    #[test]
+    fn test_diff_profile_unknown_tool() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let manifest = sample_manifest(tmp.path());
+        save_profile(&config_dir, &manifest, "base", None).unwrap();
+
+        assert!(diff_profile_tool(&config_dir, "base", "other").is_err());
+        assert!(diff_profile_tool(&config_dir, "missing", "faketool").is_err());
+    }
+
+    #[test]
+    fn test_diff_whole_profile_reports_changed_file() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let mut manifest = sample_manifest(tmp.path());
+        let other = tmp.path().join("other.conf");
+        fs::write(&other, "a = 1\n").unwrap();
+        let mut entry = manifest.tools["faketool"].clone();
+        entry.config_paths = vec![other.to_string_lossy().to_string()];
+        manifest.add_tool("othertool", entry).unwrap();
+
+        save_profile(&config_dir, &manifest, "base", None).unwrap();
+        assert!(diff_profile(&config_dir, &manifest, "base").unwrap().is_empty());
+
+        fs::write(&other, "a = 2\n").unwrap();
+
+        let diffs = diff_profile(&config_dir, &manifest, "base").unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].file_path, util::paths::contract_tilde(&other));
+        assert_eq!(diffs[0].old_content, "a = 1\n");
+        assert_eq!(diffs[0].new_content, "a = 2\n");
+    }
+
+    #[test]
+    fn test_diff_whole_profile_missing_files() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let mut manifest = sample_manifest(tmp.path());
+        save_profile(&config_dir, &manifest, "base", None).unwrap();
+
+        // In the profile but gone locally
+        let config_path = manifest.tools["faketool"].config_paths[0].clone();
+        fs::remove_file(&config_path).unwrap();
+
+        // Tracked locally but not in the profile
+        let extra = tmp.path().join("extra.conf");
+        fs::write(&extra, "new = yes\n").unwrap();
+        manifest
+            .tools
+            .get_mut("faketool")
+            .unwrap()
+            .config_paths
+            .push(extra.to_string_lossy().to_string());
+
+        let diffs = diff_profile(&config_dir, &manifest, "base").unwrap();
+        assert_eq!(diffs.len(), 2);
+        let removed = diffs.iter().find(|d| d.file_path.ends_with("fake_tool.conf")).unwrap();
+        assert_eq!(removed.old_content, "key = value\n");
+        assert!(removed.new_content.is_empty());
+        let added = diffs.iter().find(|d| d.file_path.ends_with("extra.conf")).unwrap();
+        assert!(added.old_content.is_empty());
+        assert_eq!(added.new_content, "new = yes\n");
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let manifest = sample_manifest(tmp.path());
+        save_profile(&config_dir, &manifest, "travel", None).unwrap();
+
+        // Names that would trip up parsing `tar -tv` output
+        let odd = profiles_dir(&config_dir).join("travel/files/odd name -> x.conf");
+        fs::write(&odd, "x\n").unwrap();
+
+        let archive = tmp.path().join("travel.tar.gz");
+        export_profile(&config_dir, "travel", &archive).unwrap();
+        delete_profile(&config_dir, "travel").unwrap();
+
+        let meta = import_profile(&config_dir, &archive, false).unwrap();
+        assert_eq!(meta.name, "travel");
+        assert_eq!(fs::read_to_string(&odd).unwrap(), "x\n");
+
+        let restored = read_profile_meta(&config_dir, "travel").unwrap();
+        assert_eq!(restored.checksums.len(), 1);
+        let files = profiles_dir(&config_dir).join("travel").join("files");
+        for (key, hash) in &restored.checksums {
+            assert_eq!(&hash_file(&files.join(key)).unwrap(), hash);
+        }
+
+        // No staging directory is left behind
+        let leftovers: Vec<_> = fs::read_dir(profiles_dir(&config_dir))
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with('.'))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_import_existing_requires_force() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let manifest = sample_manifest(tmp.path());
+        save_profile(&config_dir, &manifest, "travel", None).unwrap();
+        let archive = tmp.path().join("travel.tar.gz");
+        export_profile(&config_dir, "travel", &archive).unwrap();
+
+        let err = import_profile(&config_dir, &archive, false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert!(import_profile(&config_dir, &archive, true).is_ok());
+    }
+
+    /// Write a gzipped tarball of raw entries, without the checks
+    /// `tar::Builder` applies to paths.
+    fn write_raw_archive(path: &Path, entries: &[(&str, tar::EntryType, &[u8])]) {
+        let file = fs::File::create(path).unwrap();
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        for (name, entry_type, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_entry_type(*entry_type);
+            if entry_type.is_symlink() {
+                header.set_link_name("/etc/passwd").unwrap();
+            }
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_import_rejects_path_traversal() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let archive = tmp.path().join("evil.tar.gz");
+        write_raw_archive(&archive, &[("../evil", tar::EntryType::Regular, b"x")]);
+
+        let err = import_profile(&config_dir, &archive, false).unwrap_err();
+        assert!(err.to_string().contains("refusing to import"));
+        assert!(list_profiles(&config_dir).unwrap().is_empty());
+        assert!(!tmp.path().join("evil").exists());
+    }
+
+    #[test]
+    fn test_import_rejects_links() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let archive = tmp.path().join("evil.tar.gz");
+        write_raw_archive(&archive, &[("files/passwd", tar::EntryType::Symlink, b"")]);
+
+        let err = import_profile(&config_dir, &archive, false).unwrap_err();
+        assert!(err.to_string().contains("contains a link"));
+    }
+
+    #[test]
+    fn test_import_rejects_escaping_checksum_key() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let meta = "name = \"evil\"\ncreated_at = \"2026-01-01T00:00:00Z\"\n\
+                    [tools]\n[checksums]\n\"../../outside\" = \"abc\"\n";
+        let archive = tmp.path().join("evil.tar.gz");
+        write_raw_archive(
+            &archive,
+            &[("profile.toml", tar::EntryType::Regular, meta.as_bytes())],
+        );
+
+        let err = import_profile(&config_dir, &archive, false).unwrap_err();
+        assert!(err.to_string().contains("unexpected checksum entry"));
+        assert!(list_profiles(&config_dir).unwrap().is_empty());
+    }
+
+    fn summary(name: &str, hostname: Option<&str>, age_days: i64) -> ProfileSummary {
+        ProfileSummary {
+            name: name.to_string(),
+            created_at: Utc::now() - chrono::Duration::days(age_days),
+            tool_count: 1,
+            file_count: 1,
+            hostname: hostname.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_match_hostname() {
+        let profiles = vec![
+            summary("minimal", None, 0),
+            summary("laptop", Some("thinkpad"), 3),
+            summary("workstation", Some("desk.example.com"), 1),
+        ];
+
+        assert_eq!(match_hostname(&profiles, "thinkpad").unwrap().name, "laptop");
+        assert_eq!(match_hostname(&profiles, "THINKPAD").unwrap().name, "laptop");
+        // Short names match either way round
+        assert_eq!(match_hostname(&profiles, "thinkpad.local").unwrap().name, "laptop");
+        assert_eq!(match_hostname(&profiles, "desk").unwrap().name, "workstation");
+        assert!(match_hostname(&profiles, "server").is_none());
+        assert!(match_hostname(&[], "thinkpad").is_none());
+    }
+
+    #[test]
+    fn test_match_hostname_prefers_exact_then_newest() {
+        let profiles = vec![
+            summary("old", Some("desk"), 10),
+            summary("new", Some("desk"), 1),
+            summary("short", Some("desk.lan"), 0),
+        ];
+        assert_eq!(match_hostname(&profiles, "desk").unwrap().name, "new");
+        assert_eq!(match_hostname(&profiles, "desk.lan").unwrap().name, "short");
+    }
+
+    #[test]
+    fn test_save_copies_glob_matches() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        let lua = tmp.path().join("nvim/lua");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::create_dir_all(&lua).unwrap();
+        fs::write(lua.join("options.lua"), "vim.o.nu = true\n").unwrap();
+        fs::write(lua.join("README.md"), "notes\n").unwrap();
+
+        let mut manifest = Manifest::default();
+        let entry = ToolEntry {
+            tier: 1,
+            config_paths: vec![format!("{}/*.lua", lua.display())],
+            plugins_managed: false,
+            plugin_manager: None,
+            added_at: Utc::now(),
+            last_snapshot: None,
+            plugins: BTreeMap::new(),
+        };
+        manifest.add_tool("nvim", entry).unwrap();
+
+        let (_, files) = save_profile(&config_dir, &manifest, "globbed", None).unwrap();
+        assert_eq!(files, 1);
+        let meta = read_profile_meta(&config_dir, "globbed").unwrap();
+        assert!(meta.checksums.contains_key("nvim/options.lua"));
+    }
+
+    #[test]
+    fn test_save_records_hostname() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let manifest = sample_manifest(tmp.path());
+        save_profile(&config_dir, &manifest, "given", Some("thinkpad")).unwrap();
+        save_profile(&config_dir, &manifest, "detected", None).unwrap();
+
+        let given = read_profile_meta(&config_dir, "given").unwrap();
+        assert_eq!(given.hostname.as_deref(), Some("thinkpad"));
+        let detected = read_profile_meta(&config_dir, "detected").unwrap();
+        assert_eq!(detected.hostname, current_hostname());
+    }
+
+    #[test]
+    fn test_profile_history_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let manifest = sample_manifest(tmp.path());
+        let paths = manifest.tools["faketool"].config_paths.clone();
+        SnapshotEngine::open(&config_dir)
+            .unwrap()
+            .snapshot_tool("faketool", &paths, Some("before save"))
+            .unwrap();
+
+        save_profile(&config_dir, &manifest, "base", None).unwrap();
+        assert!(!has_history(&config_dir, "base"));
+        assert!(load_profile_history(&config_dir, "base", &["faketool"]).is_err());
+
+        assert_eq!(save_profile_history(&config_dir, "base").unwrap(), 1);
+        assert!(has_history(&config_dir, "base"));
+
+        // Fresh machine: merge history into an empty snapshots.db
+        let other_dir = tmp.path().join("other");
+        fs::create_dir_all(&other_dir).unwrap();
+        fs::rename(
+            profiles_dir(&config_dir),
+            profiles_dir(&other_dir),
+        )
+        .unwrap();
+        assert_eq!(load_profile_history(&other_dir, "base", &["faketool"]).unwrap(), 1);
+        assert_eq!(load_profile_history(&other_dir, "base", &["faketool"]).unwrap(), 0);
+
+        let history = SnapshotEngine::open(&other_dir).unwrap().history("faketool", 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].message, Some("before save".to_string()));
+    }
+
     #[test]
     fn test_validate_profile_name_valid() {
         assert!(validate_profile_name("workstation").is_ok());
@@ -433,7 +1193,7 @@ mod tests {
         manifest.save(&config_dir).unwrap();
 
         // Save profile
-        let (tools, files) = save_profile(&config_dir, &manifest, "test-profile").unwrap();
+        let (tools, files) = save_profile(&config_dir, &manifest, "test-profile", None).unwrap();
         assert_eq!(tools, 1);
         assert_eq!(files, 1);
 
@@ -458,7 +1218,7 @@ mod tests {
         fs::create_dir_all(&config_dir).unwrap();
 
         let manifest = sample_manifest(tmp.path());
-        save_profile(&config_dir, &manifest, "my-setup").unwrap();
+        save_profile(&config_dir, &manifest, "my-setup", None).unwrap();
 
         let profile_dir = profiles_dir(&config_dir).join("my-setup");
         assert!(profile_dir.join("profile.toml").exists());
@@ -473,7 +1233,7 @@ mod tests {
         fs::create_dir_all(&config_dir).unwrap();
 
         let manifest = sample_manifest(tmp.path());
-        save_profile(&config_dir, &manifest, "checksums-test").unwrap();
+        save_profile(&config_dir, &manifest, "checksums-test", None).unwrap();
 
         let meta_path = profiles_dir(&config_dir)
             .join("checksums-test")
@@ -497,7 +1257,7 @@ mod tests {
         let mut manifest = sample_manifest(tmp.path());
         manifest.save(&config_dir).unwrap();
 
-        save_profile(&config_dir, &manifest, "backup-test").unwrap();
+        save_profile(&config_dir, &manifest, "backup-test", None).unwrap();
 
         let result = load_profile(&config_dir, &mut manifest, "backup-test", false).unwrap();
         assert_eq!(result.backed_up_files, 1);
@@ -516,7 +1276,7 @@ mod tests {
 
         let manifest = sample_manifest(tmp.path());
         manifest.save(&config_dir).unwrap();
-        save_profile(&config_dir, &manifest, "untracked-test").unwrap();
+        save_profile(&config_dir, &manifest, "untracked-test", None).unwrap();
 
         // Start with empty manifest
         let mut empty_manifest = Manifest::default();
@@ -537,7 +1297,7 @@ mod tests {
 
         let manifest = sample_manifest(tmp.path());
         manifest.save(&config_dir).unwrap();
-        save_profile(&config_dir, &manifest, "skip-test").unwrap();
+        save_profile(&config_dir, &manifest, "skip-test", None).unwrap();
 
         let mut empty_manifest = Manifest::default();
         empty_manifest.save(&config_dir).unwrap();
@@ -564,8 +1324,8 @@ mod tests {
         fs::create_dir_all(&config_dir).unwrap();
 
         let manifest = sample_manifest(tmp.path());
-        save_profile(&config_dir, &manifest, "alpha").unwrap();
-        save_profile(&config_dir, &manifest, "beta").unwrap();
+        save_profile(&config_dir, &manifest, "alpha", None).unwrap();
+        save_profile(&config_dir, &manifest, "beta", None).unwrap();
 
         let profiles = list_profiles(&config_dir).unwrap();
         assert_eq!(profiles.len(), 2);
@@ -580,7 +1340,7 @@ mod tests {
         fs::create_dir_all(&config_dir).unwrap();
 
         let manifest = sample_manifest(tmp.path());
-        save_profile(&config_dir, &manifest, "to-delete").unwrap();
+        save_profile(&config_dir, &manifest, "to-delete", None).unwrap();
 
         assert!(profiles_dir(&config_dir).join("to-delete").exists());
         delete_profile(&config_dir, "to-delete").unwrap();
@@ -605,9 +1365,9 @@ mod tests {
         fs::create_dir_all(&config_dir).unwrap();
 
         let manifest = sample_manifest(tmp.path());
-        save_profile(&config_dir, &manifest, "dupe").unwrap();
+        save_profile(&config_dir, &manifest, "dupe", None).unwrap();
 
-        let result = save_profile(&config_dir, &manifest, "dupe");
+        let result = save_profile(&config_dir, &manifest, "dupe", None);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
     }
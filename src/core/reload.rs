@@ -1,34 +1,68 @@
+use std::path::Path;
 use std::process::Command;
 
 use anyhow::{Context, Result};
 
+use crate::core::config::DotsmithConfig;
 use crate::core::module::ModuleRegistry;
 
+/// A tool's reload command, before `{config_path}` is substituted.
+#[derive(Debug, PartialEq)]
+pub struct ReloadCommand {
+    pub command: String,
+    /// What running the command does, for reporting.
+    pub description: String,
+}
+
+/// The reload command for a tool: its `[tools.<name>] reload_hook` from
+/// config.toml when set, otherwise the module's `reload_command` (built-in or
+/// from `<config_dir>/modules/`). `None` if there is neither.
+pub fn resolve_reload_command(
+    config_dir: &Path,
+    config: &DotsmithConfig,
+    tool: &str,
+) -> Option<ReloadCommand> {
+    if let Some(hook) = config.tools.get(tool).and_then(|t| t.reload_hook.as_ref()) {
+        return Some(ReloadCommand {
+            command: hook.clone(),
+            description: "ran reload hook".to_string(),
+        });
+    }
+
+    let module = ModuleRegistry::load_module(config_dir, tool)?;
+    let command = module.metadata.reload_command?;
+    let description = module
+        .metadata
+        .reload_description
+        .unwrap_or_else(|| "reloading configuration".to_string());
+    Some(ReloadCommand {
+        command,
+        description,
+    })
+}
+
+/// Replace `{config_path}` in a reload command. Without a path the
+/// placeholder is left as is.
+pub fn substitute_config_path(cmd: &str, config_path: Option<&str>) -> String {
+    match config_path {
+        Some(path) => cmd.replace("{config_path}", path),
+        None => cmd.to_string(),
+    }
+}
+
 /// Reload configuration for a tool.
-/// Uses the module's reload_command if available, otherwise attempts common methods.
+/// Uses the tool's reload hook or module reload_command if available (see
+/// [`resolve_reload_command`]), otherwise attempts common methods.
 ///
 /// Returns a description of what was done, or an error if reload failed.
-pub fn reload_tool(tool: &str, config_path: Option<&str>) -> Result<String> {
-    // Try built-in module reload command first
-    if let Some(module) = ModuleRegistry::get_builtin(tool)
-        && let Some(ref reload_cmd) = module.metadata.reload_command
-    {
-        let description = module
-            .metadata
-            .reload_description
-            .as_deref()
-            .unwrap_or("reloading configuration");
-
-        let cmd = if let Some(path) = config_path {
-            reload_cmd.replace("{config_path}", path)
-        } else {
-            reload_cmd.clone()
-        };
-
+pub fn reload_tool(config_dir: &Path, tool: &str, config_path: Option<&str>) -> Result<String> {
+    let config = DotsmithConfig::load(config_dir);
+    if let Some(reload) = resolve_reload_command(config_dir, &config, tool) {
+        let cmd = substitute_config_path(&reload.command, config_path);
         execute_reload_command(&cmd)
             .with_context(|| format!("failed to reload {}", tool))?;
 
-        return Ok(description.to_string());
+        return Ok(reload.description);
     }
 
     // Fallback: try common reload methods
@@ -141,10 +175,69 @@ fn reload_wm(tool: &str) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::config::ToolConfig;
+
+    fn with_hook(tool: &str, hook: &str) -> DotsmithConfig {
+        let mut config = DotsmithConfig::default();
+        config.tools.insert(
+            tool.to_string(),
+            ToolConfig {
+                reload_hook: Some(hook.to_string()),
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn test_resolve_uses_module_command() {
+        let config = DotsmithConfig::default();
+        let reload = resolve_reload_command(Path::new("/nonexistent"), &config, "tmux").unwrap();
+        assert_eq!(reload.command, "tmux source-file {config_path}");
+        assert_eq!(reload.description, "Source tmux config");
+    }
+
+    #[test]
+    fn test_reload_hook_overrides_module_command() {
+        let config = with_hook("tmux", "tmux-reload.sh {config_path}");
+        let reload = resolve_reload_command(Path::new("/nonexistent"), &config, "tmux").unwrap();
+        assert_eq!(reload.command, "tmux-reload.sh {config_path}");
+
+        // Hooks also cover tools without a module command
+        let config = with_hook("waybar", "pkill -SIGUSR2 waybar");
+        let reload = resolve_reload_command(Path::new("/nonexistent"), &config, "waybar").unwrap();
+        assert_eq!(reload.command, "pkill -SIGUSR2 waybar");
+
+        let config = DotsmithConfig::default();
+        assert!(resolve_reload_command(Path::new("/nonexistent"), &config, "waybar").is_none());
+    }
+
+    #[test]
+    fn test_substitute_config_path() {
+        assert_eq!(
+            substitute_config_path("tmux source-file {config_path}", Some("~/.tmux.conf")),
+            "tmux source-file ~/.tmux.conf"
+        );
+        assert_eq!(
+            substitute_config_path("tmux source-file {config_path}", None),
+            "tmux source-file {config_path}"
+        );
+        assert_eq!(substitute_config_path("pkill -USR1 kitty", Some("x")), "pkill -USR1 kitty");
+    }
+
+    #[test]
+    fn test_reload_hook_failure_is_reported() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        with_hook("faketool", "false {config_path}").save(tmp.path()).unwrap();
+        let result = reload_tool(tmp.path(), "faketool", Some("x.conf"));
+        assert!(result.unwrap_err().to_string().contains("failed to reload faketool"));
+
+        with_hook("faketool", "true {config_path}").save(tmp.path()).unwrap();
+        assert_eq!(reload_tool(tmp.path(), "faketool", None).unwrap(), "ran reload hook");
+    }
 
     #[test]
     fn test_unknown_tool_reload() {
-        let result = reload_tool("unknown_tool_xyz", None);
+        let result = reload_tool(Path::new("/nonexistent"), "unknown_tool_xyz", None);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("no reload method"));
     }
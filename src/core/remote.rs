@@ -3,8 +3,10 @@ use std::process::Command;
 
 use anyhow::{Context, Result};
 
+use crate::core::config::PathMapping;
 use crate::core::errors::DotsmithError;
 use crate::core::manifest::Manifest;
+use crate::core::snapshot::{FileDiff, SnapshotContent};
 use crate::util;
 
 /// A planned remote deploy action for a single file.
@@ -30,6 +32,41 @@ pub struct RemoteDeployOpts<'a> {
     pub user: Option<&'a str>,
     pub tools: Option<Vec<&'a str>>,
     pub dry_run: bool,
+    /// Prefix rewrites from local to remote paths; empty maps 1:1.
+    pub path_map: &'a [PathMapping],
+}
+
+/// What pulling a single file from the remote host would do.
+#[derive(Debug, PartialEq)]
+pub enum PullStatus {
+    /// The remote copy differs from the local file
+    Changed,
+    /// The file doesn't exist locally yet
+    New,
+    /// The remote copy matches the local file
+    Unchanged,
+    /// The file doesn't exist on the remote host
+    MissingRemote,
+}
+
+/// A planned pull of one remote file into its local config path.
+#[derive(Debug)]
+pub struct RemotePullAction {
+    pub local_path: PathBuf,
+    pub remote_path: String,
+    pub tool: String,
+    pub status: PullStatus,
+    /// Where the remote copy was fetched to, when it exists.
+    pub fetched: Option<PathBuf>,
+}
+
+/// Summary returned after executing a remote pull.
+#[derive(Debug, Default)]
+pub struct RemotePullResult {
+    pub files_pulled: usize,
+    pub files_backed_up: usize,
+    pub files_unchanged: usize,
+    pub files_skipped: usize,
 }
 
 /// Build the SSH destination string.
@@ -40,6 +77,19 @@ fn ssh_dest(host: &str, user: Option<&str>) -> String {
     }
 }
 
+/// Quote a remote path for the remote shell. A leading `~/` is kept outside
+/// the quotes (as `$HOME`) so it still expands.
+fn remote_shell_path(path: &str) -> String {
+    let quote = |p: &str| format!("'{}'", p.replace('\'', "'\\''"));
+    if path == "~" {
+        return "\"$HOME\"".to_string();
+    }
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("\"$HOME\"/{}", quote(rest)),
+        None => quote(path),
+    }
+}
+
 /// Check if ssh is available on the system.
 fn check_ssh_installed() -> Result<()> {
     let status = Command::new("ssh")
@@ -54,19 +104,34 @@ fn check_ssh_installed() -> Result<()> {
     }
 }
 
-/// Check if a remote file exists.
-fn remote_file_exists(dest: &str, remote_path: &str) -> bool {
-    Command::new("ssh")
+/// Check if a remote file exists. `test -e` exits 1 for a missing file,
+/// while ssh exits 255 when it can't reach or log in to the host, which is
+/// an error rather than a missing file.
+fn remote_file_exists(dest: &str, remote_path: &str) -> Result<bool> {
+    let output = Command::new("ssh")
         .args([
             "-o", "BatchMode=yes",
             "-o", "ConnectTimeout=5",
             dest,
-            &format!("test -e '{}'", remote_path),
+            &format!("test -e {}", remote_shell_path(remote_path)),
         ])
         .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .status()
-        .is_ok_and(|s| s.success())
+        .output()
+        .context("failed to run ssh")?;
+
+    match output.status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            let reason = if stderr.is_empty() {
+                format!("ssh exited with {}", output.status)
+            } else {
+                stderr
+            };
+            Err(DotsmithError::SshFailed(dest.to_string(), reason).into())
+        }
+    }
 }
 
 /// Create a backup of a remote file.
@@ -78,7 +143,11 @@ fn remote_backup(dest: &str, remote_path: &str) -> Result<String> {
         .args([
             "-o", "BatchMode=yes",
             dest,
-            &format!("cp -a '{}' '{}'", remote_path, backup_path),
+            &format!(
+                "cp -a {} {}",
+                remote_shell_path(remote_path),
+                remote_shell_path(&backup_path)
+            ),
         ])
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
@@ -98,7 +167,7 @@ fn remote_mkdir_p(dest: &str, remote_dir: &str) -> Result<()> {
         .args([
             "-o", "BatchMode=yes",
             dest,
-            &format!("mkdir -p '{}'", remote_dir),
+            &format!("mkdir -p {}", remote_shell_path(remote_dir)),
         ])
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
@@ -138,6 +207,37 @@ fn scp_file(local_path: &Path, dest: &str, remote_path: &str) -> Result<()> {
     Ok(())
 }
 
+/// The `dest:path` argument scp reads a remote file from.
+fn scp_source(dest: &str, remote_path: &str) -> String {
+    format!("{}:{}", dest, remote_path)
+}
+
+/// Copy a remote file to a local path via scp.
+fn scp_fetch(dest: &str, remote_path: &str, local_path: &Path) -> Result<()> {
+    let status = Command::new("scp")
+        .args([
+            "-q",
+            "-o", "BatchMode=yes",
+            &scp_source(dest, remote_path),
+            &local_path.to_string_lossy(),
+        ])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .context("failed to run scp")?;
+
+    if !status.success() {
+        return Err(DotsmithError::ScpFailed(
+            dest.to_string(),
+            remote_path.to_string(),
+            "scp exited with non-zero status".to_string(),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
 /// Collect files from a directory for deployment.
 fn collect_dir_files(dir: &Path, tool_name: &str, config_path: &str) -> Vec<(PathBuf, String)> {
     let mut files = Vec::new();
@@ -173,6 +273,25 @@ fn collect_dir_files(dir: &Path, tool_name: &str, config_path: &str) -> Vec<(Pat
     files
 }
 
+/// Map a tilde-contracted local path to its path on the remote host. The
+/// longest `local` prefix that matches on a path-component boundary is
+/// replaced by its `remote` prefix; without a match the path is used as-is.
+pub fn map_remote_path(path_map: &[PathMapping], path: &str) -> String {
+    let matched = path_map
+        .iter()
+        .filter_map(|m| {
+            let local = m.local.trim_end_matches('/');
+            let rest = path.strip_prefix(local)?;
+            (rest.is_empty() || rest.starts_with('/')).then_some((local.len(), m, rest))
+        })
+        .max_by_key(|(len, _, _)| *len);
+
+    match matched {
+        Some((_, m, rest)) => format!("{}{}", m.remote.trim_end_matches('/'), rest),
+        None => path.to_string(),
+    }
+}
+
 /// Plan a remote deploy operation.
 pub fn plan_remote_deploy(
     manifest: &Manifest,
@@ -194,26 +313,37 @@ pub fn plan_remote_deploy(
 
     for (tool_name, entry) in &tools {
         for config_path in &entry.config_paths {
+            if util::paths::is_glob(config_path) {
+                // Each matching file goes to the same path on the remote
+                for local in util::paths::expand_glob(config_path) {
+                    let remote_path =
+                        map_remote_path(opts.path_map, &util::paths::contract_tilde(&local));
+                    actions.push(RemoteDeployAction {
+                        remote_exists: remote_file_exists(&dest, &remote_path)?,
+                        local_path: local,
+                        remote_path,
+                        tool: tool_name.to_string(),
+                    });
+                }
+                continue;
+            }
             let local = util::paths::expand_tilde(config_path);
 
             if local.is_file() {
-                let exists = if !opts.dry_run {
-                    remote_file_exists(&dest, config_path)
-                } else {
-                    // In dry-run, still check for accurate display
-                    remote_file_exists(&dest, config_path)
-                };
+                let remote_path = map_remote_path(opts.path_map, config_path);
+                let exists = remote_file_exists(&dest, &remote_path)?;
 
                 actions.push(RemoteDeployAction {
                     local_path: local,
-                    remote_path: config_path.clone(),
+                    remote_path,
                     tool: tool_name.to_string(),
                     remote_exists: exists,
                 });
             } else if local.is_dir() {
                 let dir_files = collect_dir_files(&local, tool_name, config_path);
                 for (file_path, remote_path) in dir_files {
-                    let exists = remote_file_exists(&dest, &remote_path);
+                    let remote_path = map_remote_path(opts.path_map, &remote_path);
+                    let exists = remote_file_exists(&dest, &remote_path)?;
 
                     actions.push(RemoteDeployAction {
                         local_path: file_path,
@@ -230,6 +360,190 @@ pub fn plan_remote_deploy(
     Ok(actions)
 }
 
+/// List the `(tool, local path, remote path)` triples a pull covers: every
+/// tracked config file, and for tracked directories the files they hold
+/// locally. Paths missing locally are still pulled as single files. Remote
+/// paths are rewritten through `path_map`.
+pub fn pull_targets(
+    manifest: &Manifest,
+    tools: Option<&[&str]>,
+    path_map: &[PathMapping],
+) -> Result<Vec<(String, PathBuf, String)>> {
+    let mut targets = Vec::new();
+
+    for (tool_name, entry) in &manifest.tools {
+        if tools.is_some_and(|filter| !filter.contains(&tool_name.as_str())) {
+            continue;
+        }
+
+        for config_path in &entry.config_paths {
+            if util::paths::is_glob(config_path) {
+                for local in util::paths::expand_glob(config_path) {
+                    let remote_path =
+                        map_remote_path(path_map, &util::paths::contract_tilde(&local));
+                    targets.push((tool_name.clone(), local, remote_path));
+                }
+                continue;
+            }
+            let local = util::paths::expand_tilde_checked(config_path)?;
+            if local.is_dir() {
+                for (file_path, remote_path) in collect_dir_files(&local, tool_name, config_path) {
+                    let remote_path = map_remote_path(path_map, &remote_path);
+                    targets.push((tool_name.clone(), file_path, remote_path));
+                }
+            } else {
+                let remote_path = map_remote_path(path_map, config_path);
+                targets.push((tool_name.clone(), local, remote_path));
+            }
+        }
+    }
+
+    Ok(targets)
+}
+
+/// Plan a remote pull: fetch each tracked file from the host into
+/// `staging_dir` and compare it with the local copy. Nothing outside
+/// `staging_dir` is modified.
+pub fn plan_remote_pull(
+    manifest: &Manifest,
+    opts: &RemoteDeployOpts,
+    staging_dir: &Path,
+) -> Result<Vec<RemotePullAction>> {
+    check_ssh_installed()?;
+
+    let dest = ssh_dest(opts.host, opts.user);
+    let targets = pull_targets(manifest, opts.tools.as_deref(), opts.path_map)?;
+    let mut actions = Vec::new();
+
+    if !targets.is_empty() {
+        std::fs::create_dir_all(staging_dir)
+            .with_context(|| format!("failed to create {}", staging_dir.display()))?;
+    }
+
+    for (i, (tool, local_path, remote_path)) in targets.into_iter().enumerate() {
+        if !remote_file_exists(&dest, &remote_path)? {
+            actions.push(RemotePullAction {
+                local_path,
+                remote_path,
+                tool,
+                status: PullStatus::MissingRemote,
+                fetched: None,
+            });
+            continue;
+        }
+
+        let fetched = staging_dir.join(i.to_string());
+        scp_fetch(&dest, &remote_path, &fetched)?;
+
+        let status = match std::fs::read(&local_path) {
+            Ok(local) if std::fs::read(&fetched)? == local => PullStatus::Unchanged,
+            Ok(_) => PullStatus::Changed,
+            Err(_) => PullStatus::New,
+        };
+
+        actions.push(RemotePullAction {
+            local_path,
+            remote_path,
+            tool,
+            status,
+            fetched: Some(fetched),
+        });
+    }
+
+    Ok(actions)
+}
+
+/// Diff tracked files against their live copies on the remote host. The
+/// remote copy is the "old" side and the local file the "new" side; a file
+/// missing on either side diffs against empty content. Only files that
+/// differ are returned. Remote copies are fetched into a temporary directory
+/// that is removed afterwards.
+pub fn diff_remote(manifest: &Manifest, opts: &RemoteDeployOpts) -> Result<Vec<FileDiff>> {
+    let staging = tempfile::TempDir::new().context("failed to create a staging directory")?;
+    let actions = plan_remote_pull(manifest, opts, staging.path())?;
+    Ok(actions.iter().filter_map(pull_diff).collect())
+}
+
+/// The diff between a fetched remote copy and the local file, if they differ.
+fn pull_diff(action: &RemotePullAction) -> Option<FileDiff> {
+    if action.status == PullStatus::Unchanged {
+        return None;
+    }
+    let fetched = action.fetched.as_ref().and_then(|f| std::fs::read(f).ok());
+    let old = SnapshotContent::from_bytes(fetched.unwrap_or_default());
+    let new = SnapshotContent::from_bytes(std::fs::read(&action.local_path).unwrap_or_default());
+    if old == new {
+        return None;
+    }
+
+    let file_path = util::paths::contract_tilde(&action.local_path);
+    Some(match (old, new) {
+        (SnapshotContent::Text(old_content), SnapshotContent::Text(new_content)) => FileDiff {
+            file_path,
+            old_content,
+            new_content,
+            binary: false,
+        },
+        _ => FileDiff {
+            file_path,
+            old_content: String::new(),
+            new_content: String::new(),
+            binary: true,
+        },
+    })
+}
+
+/// Execute a remote pull plan. Local files that would be overwritten are
+/// copied to `backup_dir` first; symlinked config paths are written through.
+pub fn execute_remote_pull(
+    actions: &[RemotePullAction],
+    backup_dir: &Path,
+) -> Result<RemotePullResult> {
+    let mut result = RemotePullResult::default();
+
+    for action in actions {
+        let fetched = match (&action.status, &action.fetched) {
+            (PullStatus::Changed | PullStatus::New, Some(fetched)) => fetched,
+            (PullStatus::Unchanged, _) => {
+                result.files_unchanged += 1;
+                continue;
+            }
+            _ => {
+                result.files_skipped += 1;
+                continue;
+            }
+        };
+
+        if action.status == PullStatus::Changed {
+            std::fs::create_dir_all(backup_dir)
+                .with_context(|| format!("failed to create {}", backup_dir.display()))?;
+            let backup_name = format!(
+                "{}.{}.bak",
+                action
+                    .local_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("file"),
+                chrono::Utc::now().format("%Y%m%d_%H%M%S")
+            );
+            std::fs::copy(&action.local_path, backup_dir.join(backup_name)).with_context(|| {
+                format!("failed to backup {}", action.local_path.display())
+            })?;
+            result.files_backed_up += 1;
+        } else if let Some(parent) = action.local_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+
+        std::fs::copy(fetched, &action.local_path).with_context(|| {
+            format!("failed to write {}", action.local_path.display())
+        })?;
+        result.files_pulled += 1;
+    }
+
+    Ok(result)
+}
+
 /// Execute a remote deploy plan.
 pub fn execute_remote_deploy(
     actions: &[RemoteDeployAction],
@@ -280,6 +594,75 @@ mod tests {
         assert_eq!(ssh_dest("example.com", None), "example.com");
     }
 
+    #[test]
+    fn test_scp_source_joins_dest_and_path() {
+        let dest = ssh_dest("box", Some("alice"));
+        assert_eq!(scp_source(&dest, "~/.tmux.conf"), "alice@box:~/.tmux.conf");
+        assert_eq!(
+            scp_source(&ssh_dest("box", None), "/opt/alice/config/tmux/tmux.conf"),
+            "box:/opt/alice/config/tmux/tmux.conf"
+        );
+    }
+
+    #[test]
+    fn test_remote_shell_path_expands_home() {
+        assert_eq!(remote_shell_path("~/.zshrc"), "\"$HOME\"/'.zshrc'");
+        assert_eq!(remote_shell_path("~"), "\"$HOME\"");
+        assert_eq!(remote_shell_path("/etc/x y"), "'/etc/x y'");
+        assert_eq!(remote_shell_path("/tmp/it's"), "'/tmp/it'\\''s'");
+    }
+
+    #[test]
+    fn test_map_remote_path_longest_prefix() {
+        let rule = |local: &str, remote: &str| PathMapping {
+            local: local.to_string(),
+            remote: remote.to_string(),
+        };
+        let map = vec![
+            rule("~/.config", "/opt/me/config"),
+            rule("~/.config/tmux/", "~/tmux"),
+        ];
+
+        assert_eq!(map_remote_path(&map, "~/.config/tmux/tmux.conf"), "~/tmux/tmux.conf");
+        assert_eq!(map_remote_path(&map, "~/.config/tmux"), "~/tmux");
+        assert_eq!(map_remote_path(&map, "~/.config/git/config"), "/opt/me/config/git/config");
+        // Only whole components match
+        assert_eq!(map_remote_path(&map, "~/.configs/x"), "~/.configs/x");
+        assert_eq!(map_remote_path(&map, "~/.zshrc"), "~/.zshrc");
+        assert_eq!(map_remote_path(&[], "~/.zshrc"), "~/.zshrc");
+    }
+
+    #[test]
+    fn test_pull_targets_apply_path_map() {
+        use crate::core::manifest::ToolEntry;
+        use chrono::Utc;
+        use std::collections::BTreeMap;
+
+        let mut manifest = Manifest::default();
+        manifest
+            .add_tool(
+                "tmux",
+                ToolEntry {
+                    tier: 1,
+                    config_paths: vec!["/nonexistent/local/tmux.conf".to_string()],
+                    plugins_managed: false,
+                    plugin_manager: None,
+                    added_at: Utc::now(),
+                    last_snapshot: None,
+                    plugins: BTreeMap::new(),
+                },
+            )
+            .unwrap();
+
+        let map = vec![PathMapping {
+            local: "/nonexistent/local".to_string(),
+            remote: "/srv/dots".to_string(),
+        }];
+        let targets = pull_targets(&manifest, None, &map).unwrap();
+        assert_eq!(targets[0].1, PathBuf::from("/nonexistent/local/tmux.conf"));
+        assert_eq!(targets[0].2, "/srv/dots/tmux.conf");
+    }
+
     #[test]
     fn test_plan_empty_manifest() {
         let manifest = Manifest::default();
@@ -288,6 +671,7 @@ mod tests {
             user: None,
             tools: None,
             dry_run: true,
+            path_map: &[],
         };
 
         // This will fail if ssh is not installed, which is expected in CI
@@ -349,6 +733,7 @@ mod tests {
             user: None,
             tools: Some(vec!["tool1"]),
             dry_run: true,
+            path_map: &[],
         };
 
         match plan_remote_deploy(&manifest, &opts) {
@@ -363,6 +748,141 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pull_targets_with_tool_filter() {
+        use crate::core::manifest::ToolEntry;
+        use chrono::Utc;
+        use std::collections::BTreeMap;
+        use tempfile::TempDir;
+
+        let tmp = TempDir::new().unwrap();
+        let file1 = tmp.path().join("tool1.conf");
+        let dir2 = tmp.path().join("tool2");
+        std::fs::write(&file1, "content1").unwrap();
+        std::fs::create_dir(&dir2).unwrap();
+        std::fs::write(dir2.join("a.conf"), "a").unwrap();
+
+        let entry = |paths: Vec<String>| ToolEntry {
+            tier: 2,
+            config_paths: paths,
+            plugins_managed: false,
+            plugin_manager: None,
+            added_at: Utc::now(),
+            last_snapshot: None,
+            plugins: BTreeMap::new(),
+        };
+
+        let mut manifest = Manifest::default();
+        manifest
+            .add_tool(
+                "tool1",
+                entry(vec![
+                    file1.to_string_lossy().to_string(),
+                    "/nonexistent/only-remote.conf".to_string(),
+                ]),
+            )
+            .unwrap();
+        manifest
+            .add_tool("tool2", entry(vec![dir2.to_string_lossy().to_string()]))
+            .unwrap();
+
+        let only_tool1 = pull_targets(&manifest, Some(&["tool1"]), &[]).unwrap();
+        assert_eq!(only_tool1.len(), 2);
+        assert!(only_tool1.iter().all(|(tool, _, _)| tool == "tool1"));
+        // Missing locally is still a pull target
+        assert_eq!(only_tool1[1].2, "/nonexistent/only-remote.conf");
+
+        let all = pull_targets(&manifest, None, &[]).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[2].1, dir2.join("a.conf"));
+        assert!(all[2].2.ends_with("tool2/a.conf"));
+    }
+
+    #[test]
+    fn test_execute_pull_backs_up_and_overwrites() {
+        use tempfile::TempDir;
+
+        let tmp = TempDir::new().unwrap();
+        let staging = tmp.path().join("staging");
+        std::fs::create_dir(&staging).unwrap();
+
+        let changed = tmp.path().join("changed.conf");
+        let same = tmp.path().join("same.conf");
+        let new = tmp.path().join("sub/new.conf");
+        std::fs::write(&changed, "old").unwrap();
+        std::fs::write(&same, "same").unwrap();
+        std::fs::write(staging.join("0"), "remote").unwrap();
+        std::fs::write(staging.join("1"), "same").unwrap();
+        std::fs::write(staging.join("2"), "brand new").unwrap();
+
+        let action = |local: &Path, status, fetched: Option<&str>| RemotePullAction {
+            local_path: local.to_path_buf(),
+            remote_path: "~/x".to_string(),
+            tool: "t".to_string(),
+            status,
+            fetched: fetched.map(|f| staging.join(f)),
+        };
+        let actions = vec![
+            action(&changed, PullStatus::Changed, Some("0")),
+            action(&same, PullStatus::Unchanged, Some("1")),
+            action(&new, PullStatus::New, Some("2")),
+            action(&tmp.path().join("gone.conf"), PullStatus::MissingRemote, None),
+        ];
+
+        let backup_dir = tmp.path().join("backups");
+        let result = execute_remote_pull(&actions, &backup_dir).unwrap();
+        assert_eq!(result.files_pulled, 2);
+        assert_eq!(result.files_backed_up, 1);
+        assert_eq!(result.files_unchanged, 1);
+        assert_eq!(result.files_skipped, 1);
+
+        assert_eq!(std::fs::read_to_string(&changed).unwrap(), "remote");
+        assert_eq!(std::fs::read_to_string(&new).unwrap(), "brand new");
+        let backups: Vec<_> = std::fs::read_dir(&backup_dir).unwrap().flatten().collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(std::fs::read_to_string(backups[0].path()).unwrap(), "old");
+    }
+
+    #[test]
+    fn test_pull_from_unreachable_host_fails() {
+        use crate::core::manifest::ToolEntry;
+        use chrono::Utc;
+        use std::collections::BTreeMap;
+        use tempfile::TempDir;
+
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("tool.conf");
+        std::fs::write(&file, "content").unwrap();
+
+        let mut manifest = Manifest::default();
+        manifest
+            .add_tool(
+                "tool",
+                ToolEntry {
+                    tier: 2,
+                    config_paths: vec![file.to_string_lossy().to_string()],
+                    plugins_managed: false,
+                    plugin_manager: None,
+                    added_at: Utc::now(),
+                    last_snapshot: None,
+                    plugins: BTreeMap::new(),
+                },
+            )
+            .unwrap();
+
+        let opts = RemoteDeployOpts {
+            host: "dotsmith-test.invalid",
+            user: None,
+            tools: None,
+            dry_run: true,
+            path_map: &[],
+        };
+
+        // A host that can't be reached is an error, not a missing file
+        let err = plan_remote_pull(&manifest, &opts, &tmp.path().join("staging")).unwrap_err();
+        assert!(err.to_string().contains("ssh"));
+    }
+
     #[test]
     fn test_plan_skips_missing_local_files() {
         use crate::core::manifest::ToolEntry;
@@ -390,6 +910,7 @@ mod tests {
             user: None,
             tools: None,
             dry_run: true,
+            path_map: &[],
         };
 
         match plan_remote_deploy(&manifest, &opts) {
@@ -0,0 +1,70 @@
+use crate::core::module::OptionEntry;
+use crate::core::setting::{self, Setting};
+
+/// `opt` as an active line in a `format` config, set to its
+/// [`setting::live_value`]: `set -g mouse off` for tmux, or
+/// `[commit]\n\tgpgsign = false` for git. An option with no usable value
+/// comes out as its example, commented.
+pub fn render_option(opt: &OptionEntry, format: &str) -> String {
+    match live_setting(opt, format, None) {
+        Some(Setting { header: Some(header), line, .. }) => format!("{}\n{}", header, line),
+        Some(Setting { line, .. }) => line,
+        None => {
+            let comment = if format == "lua" { "--" } else { "#" };
+            format!("{} {}", comment, opt.example.as_deref().unwrap_or(&opt.name))
+        }
+    }
+}
+
+/// `opt` as a [`Setting`] set to its [`setting::live_value`], using the
+/// module's `set_format` template if it has one. `None` when the option has
+/// no usable value.
+pub fn live_setting(
+    opt: &OptionEntry,
+    config_format: &str,
+    set_format: Option<&str>,
+) -> Option<Setting> {
+    let value = setting::live_value(opt, config_format)?;
+    Some(setting::render(config_format, set_format, opt, &value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::module::ModuleRegistry;
+
+    fn option(tool: &str, name: &str) -> OptionEntry {
+        ModuleRegistry::get_options(tool)
+            .unwrap()
+            .options
+            .into_iter()
+            .find(|o| o.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_option_tmux() {
+        let line = |name| render_option(&option("tmux", name), "tmux");
+        assert_eq!(line("mouse"), "set -g mouse off");
+        assert_eq!(line("status-position"), "set -g status-position bottom");
+        // No usable default: the value comes from the example
+        assert_eq!(line("@resurrect-strategy-nvim"), "set -g @resurrect-strategy-nvim session");
+    }
+
+    #[test]
+    fn test_render_option_git() {
+        let line = |name| render_option(&option("git", name), "git");
+        assert_eq!(line("commit.gpgsign"), "[commit]\n\tgpgsign = false");
+        assert_eq!(line("merge.conflictstyle"), "[merge]\n\tconflictstyle = merge");
+        // An empty default and a `git config` example give nothing to set
+        assert_eq!(line("user.name"), "# git config --global user.name 'Jane Doe'");
+    }
+
+    #[test]
+    fn test_live_setting_uses_set_format() {
+        let opt = option("kitty", "background_opacity");
+        let setting = live_setting(&opt, "key-value", Some("{name} {value}")).unwrap();
+        assert_eq!(setting.header, None);
+        assert!(setting.line.starts_with("background_opacity "), "{}", setting.line);
+    }
+}
@@ -0,0 +1,267 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::core::module::{ModuleRegistry, OptionEntry, OptionType};
+use crate::util::fuzzy;
+
+/// Restrictions applied on top of the text query.
+#[derive(Debug, Default, Clone)]
+pub struct SearchFilters {
+    /// Only search this tool's database
+    pub tool: Option<String>,
+    /// Only keep options of this type
+    pub option_type: Option<OptionType>,
+    /// Only keep options carrying this tag (exact, case-insensitive)
+    pub tag: Option<String>,
+}
+
+/// One matching option, tagged with the tool whose database it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub tool: String,
+    pub name: String,
+    pub category: String,
+    #[serde(rename = "type")]
+    pub option_type: OptionType,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Search the built-in option databases and any user modules under
+/// `<config_dir>/modules/`.
+///
+/// The query is matched with [`score_option`]; an empty query matches
+/// everything, so filters can be used on their own. Hits are grouped by tool,
+/// sorted by name, and ranked best match first within each tool.
+pub fn search_options(config_dir: &Path, query: &str, filters: &SearchFilters) -> Vec<SearchHit> {
+    let mut hits = Vec::new();
+
+    for tool_name in ModuleRegistry::module_names(config_dir) {
+        if let Some(ref tool) = filters.tool
+            && *tool != tool_name
+        {
+            continue;
+        }
+        let Some(db) = ModuleRegistry::load_options(config_dir, &tool_name) else {
+            continue;
+        };
+
+        let mut scored: Vec<(u32, OptionEntry)> = db
+            .options
+            .into_iter()
+            .filter(|opt| matches_filters(opt, filters))
+            .filter_map(|opt| score_option(&opt, query).map(|s| (s, opt)))
+            .collect();
+        // Stable, so equal scores keep database order
+        scored.sort_by_key(|(s, _)| std::cmp::Reverse(*s));
+
+        for (_, opt) in scored {
+            hits.push(SearchHit {
+                tool: tool_name.clone(),
+                name: opt.name,
+                category: opt.category,
+                option_type: opt.option_type,
+                description: opt.description,
+                example: opt.example,
+                url: opt.url,
+            });
+        }
+    }
+
+    hits
+}
+
+/// How well `query` matches an option, higher is better; `None` if it
+/// doesn't. The name is matched fuzzily (see [`fuzzy::score`]), tags
+/// likewise but ranked below the name, and the category and description only
+/// by substring, since nearly any short query is a subsequence of a sentence.
+/// An empty query matches with score 0.
+pub fn score_option(opt: &OptionEntry, query: &str) -> Option<u32> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let q = query.to_lowercase();
+
+    let name = fuzzy::score(query, &opt.name);
+    let tags = opt
+        .tags
+        .iter()
+        .flatten()
+        .filter_map(|t| fuzzy::score(query, t))
+        .max()
+        .map(|s| s * 3 / 4);
+    let category = opt.category.to_lowercase().contains(&q).then_some(400);
+    let description = opt.description.to_lowercase().contains(&q).then_some(300);
+
+    [name, tags, category, description].into_iter().flatten().max()
+}
+
+fn matches_filters(opt: &OptionEntry, filters: &SearchFilters) -> bool {
+    if let Some(ref option_type) = filters.option_type
+        && opt.option_type != *option_type
+    {
+        return false;
+    }
+    if let Some(ref tag) = filters.tag {
+        let tagged = opt
+            .tags
+            .as_ref()
+            .is_some_and(|tags| tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+        if !tagged {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config dir without user modules, so only the built-ins are searched.
+    fn builtin_only() -> &'static Path {
+        Path::new("/nonexistent")
+    }
+
+    fn tmux_matches(query: &str) -> Vec<OptionEntry> {
+        let db = ModuleRegistry::get_options("tmux").unwrap();
+        db.options
+            .into_iter()
+            .filter(|opt| score_option(opt, query).is_some())
+            .collect()
+    }
+
+    #[test]
+    fn test_search_finds_mouse() {
+        let matches = tmux_matches("mouse");
+        assert!(!matches.is_empty(), "should find 'mouse' in tmux options");
+        assert!(matches.iter().any(|o| o.name == "mouse"));
+    }
+
+    #[test]
+    fn test_search_case_insensitive() {
+        assert_eq!(tmux_matches("MOUSE").len(), tmux_matches("mouse").len());
+    }
+
+    #[test]
+    fn test_search_no_results() {
+        assert!(tmux_matches("zzzznonexistent").is_empty());
+    }
+
+    #[test]
+    fn test_search_matches_tags() {
+        // "mouse" option should have a relevant tag
+        assert!(!tmux_matches("interaction").is_empty(), "should match category/tags");
+    }
+
+    #[test]
+    fn test_search_matches_description() {
+        assert!(!tmux_matches("clipboard").is_empty(), "should match in description");
+    }
+
+    #[test]
+    fn test_search_fuzzy_typo_finds_mouse() {
+        let filters = SearchFilters {
+            tool: Some("tmux".to_string()),
+            ..Default::default()
+        };
+        let hits = search_options(builtin_only(), "msoue", &filters);
+        assert!(hits.iter().any(|h| h.name == "mouse"));
+    }
+
+    #[test]
+    fn test_search_ranks_name_matches_first() {
+        let filters = SearchFilters {
+            tool: Some("tmux".to_string()),
+            ..Default::default()
+        };
+        let hits = search_options(builtin_only(), "mouse", &filters);
+        assert_eq!(hits[0].name, "mouse");
+    }
+
+    #[test]
+    fn test_search_tool_filter() {
+        let filters = SearchFilters {
+            tool: Some("tmux".to_string()),
+            ..Default::default()
+        };
+        let hits = search_options(builtin_only(), "color", &filters);
+        assert!(!hits.is_empty());
+        assert!(hits.iter().all(|h| h.tool == "tmux"));
+
+        let everywhere = search_options(builtin_only(), "color", &SearchFilters::default());
+        assert!(everywhere.iter().any(|h| h.tool != "tmux"));
+        assert!(everywhere.len() > hits.len());
+    }
+
+    #[test]
+    fn test_search_type_filter() {
+        let filters = SearchFilters {
+            option_type: Some(OptionType::Boolean),
+            ..Default::default()
+        };
+        let hits = search_options(builtin_only(), "", &filters);
+        assert!(!hits.is_empty());
+        assert!(hits.iter().all(|h| h.option_type == OptionType::Boolean));
+        assert!(hits.iter().any(|h| h.tool == "tmux" && h.name == "mouse"));
+    }
+
+    #[test]
+    fn test_search_tag_filter_is_exact() {
+        let db = ModuleRegistry::get_options("tmux").unwrap();
+        let tag = db
+            .options
+            .iter()
+            .find_map(|o| o.tags.as_ref().and_then(|t| t.first().cloned()))
+            .expect("tmux options should carry tags");
+
+        let filters = SearchFilters {
+            tag: Some(tag.clone()),
+            ..Default::default()
+        };
+        let hits = search_options(builtin_only(), "", &filters);
+        assert!(!hits.is_empty());
+
+        // A prefix of a tag is not the tag
+        let prefix = tag[..tag.len() - 1].to_string();
+        let filters = SearchFilters {
+            tag: Some(prefix.clone()),
+            ..Default::default()
+        };
+        for hit in search_options(builtin_only(), "", &filters) {
+            let db = ModuleRegistry::get_options(&hit.tool).unwrap();
+            let opt = db.options.iter().find(|o| o.name == hit.name).unwrap();
+            assert!(opt.tags.as_ref().unwrap().iter().any(|t| t.eq_ignore_ascii_case(&prefix)));
+        }
+    }
+
+    #[test]
+    fn test_search_user_module() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("modules").join("bat");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("options.toml"),
+            "[[options]]\nname = \"--paging\"\ntype = \"enum\"\n\
+             category = \"output\"\ndescription = \"When to use the pager\"\n",
+        )
+        .unwrap();
+
+        let hits = search_options(tmp.path(), "pager", &SearchFilters::default());
+        assert!(hits.iter().any(|h| h.tool == "bat" && h.name == "--paging"));
+    }
+
+    #[test]
+    fn test_search_unknown_tool_is_empty() {
+        let filters = SearchFilters {
+            tool: Some("nosuchtool".to_string()),
+            ..Default::default()
+        };
+        assert!(search_options(builtin_only(), "", &filters).is_empty());
+    }
+}
@@ -0,0 +1,397 @@
+use crate::core::audit;
+use crate::core::module::{OptionEntry, OptionType};
+
+/// A rendered option setting, ready to be written into a config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting {
+    /// Section header the line belongs under, e.g. `[user]` or
+    /// `[window.padding]`, for formats with sections.
+    pub header: Option<String>,
+    /// Key the line sets within its section, for replacing an existing line.
+    pub key: String,
+    /// The config line itself.
+    pub line: String,
+}
+
+/// Value to pre-fill when setting `opt`: its default, else its first allowed value.
+pub fn initial_value(opt: &OptionEntry) -> String {
+    opt.default
+        .clone()
+        .filter(|d| !d.is_empty())
+        .or_else(|| opt.values.as_ref().and_then(|v| v.first().cloned()))
+        .unwrap_or_default()
+}
+
+/// Value a generated config sets `opt` to: its default, else the value its
+/// example assigns, whichever first passes [`validate_value`]. `None` when
+/// neither does, e.g. for a string option with an empty default.
+pub fn live_value(opt: &OptionEntry, config_format: &str) -> Option<String> {
+    let from_example = opt.example.as_deref().and_then(|example| {
+        audit::extract_set_options(example, config_format)
+            .into_iter()
+            .find(|set| set.name == opt.name)
+            .map(|set| unquote(&set.value).to_string())
+    });
+    opt.default
+        .clone()
+        .into_iter()
+        .chain(from_example)
+        .find(|value| validate_value(opt, value).is_ok())
+}
+
+/// Check `value` against the option's type. The error says what was expected.
+pub fn validate_value(opt: &OptionEntry, value: &str) -> Result<(), String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("value can't be empty".to_string());
+    }
+
+    match opt.option_type {
+        OptionType::Boolean => {
+            parse_bool(value).ok_or_else(|| "expected on or off".to_string())?;
+        }
+        OptionType::Integer => {
+            value
+                .parse::<i64>()
+                .map_err(|_| "expected a whole number".to_string())?;
+        }
+        OptionType::Float => {
+            value
+                .parse::<f64>()
+                .map_err(|_| "expected a number".to_string())?;
+        }
+        OptionType::Enum => {
+            if let Some(ref values) = opt.values
+                && !values.is_empty()
+                && !values.iter().any(|v| v == value)
+            {
+                return Err(format!("expected one of: {}", values.join(", ")));
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Render `value` for `opt` in a config of `config_format`.
+///
+/// `set_format` is the module's line template (`{name}` and `{value}` are
+/// replaced); without one the format's usual syntax is used, e.g.
+/// `set -g mouse on` for tmux or `mouse = on` for key-value files. Git and
+/// TOML options named `section.key` are written as `key = value` under a
+/// `[section]` header. `value` should already have passed [`validate_value`].
+pub fn render(
+    config_format: &str,
+    set_format: Option<&str>,
+    opt: &OptionEntry,
+    value: &str,
+) -> Setting {
+    let value = value.trim();
+    let (header, key) = match config_format {
+        "git" => git_section(&opt.name),
+        "toml" => match opt.name.rsplit_once('.') {
+            Some((table, key)) => (Some(format!("[{}]", table)), key.to_string()),
+            None => (None, opt.name.clone()),
+        },
+        _ => (None, opt.name.clone()),
+    };
+    let formatted = format_value(config_format, opt, value);
+
+    let default_format = match config_format {
+        "tmux" => "set -g {name} {value}",
+        "git" => "\t{name} = {value}",
+        "shell" if is_setopt(opt) => {
+            let line = if parse_bool(value) == Some(true) {
+                format!("setopt {}", opt.name)
+            } else {
+                format!("unsetopt {}", opt.name)
+            };
+            return Setting { header, key, line };
+        }
+        "shell" if opt.example.as_deref().is_some_and(|e| e.starts_with("export ")) => {
+            "export {name}={value}"
+        }
+        "shell" => "{name}={value}",
+        _ => "{name} = {value}",
+    };
+    let line = set_format
+        .unwrap_or(default_format)
+        .replace("{name}", &key)
+        .replace("{value}", &formatted);
+
+    Setting { header, key, line }
+}
+
+/// Write `setting` into `content`.
+///
+/// With a header, the line goes right under an existing matching header
+/// (replacing the key if the section already sets it), or into a new section
+/// at the end. Top-level TOML keys go before the first table, since anything
+/// after a header belongs to that table. Everything else is appended, which
+/// overrides earlier settings in line-oriented configs.
+pub fn apply(content: &str, config_format: &str, setting: &Setting) -> String {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    match setting.header {
+        Some(ref header) => {
+            if let Some(start) = lines.iter().position(|l| l.trim() == header) {
+                let end = lines[start + 1..]
+                    .iter()
+                    .position(|l| l.trim_start().starts_with('['))
+                    .map_or(lines.len(), |i| start + 1 + i);
+                match find_key(&lines[start + 1..end], &setting.key) {
+                    Some(i) => lines[start + 1 + i] = setting.line.clone(),
+                    None => lines.insert(start + 1, setting.line.clone()),
+                }
+            } else {
+                if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.push(header.clone());
+                lines.push(setting.line.clone());
+            }
+        }
+        None if config_format == "toml" => {
+            let end = lines
+                .iter()
+                .position(|l| l.trim_start().starts_with('['))
+                .unwrap_or(lines.len());
+            match find_key(&lines[..end], &setting.key) {
+                Some(i) => lines[i] = setting.line.clone(),
+                None => {
+                    // Keep the blank line that separates the keys from the first table
+                    let mut at = end;
+                    while at > 0 && lines[at - 1].trim().is_empty() {
+                        at -= 1;
+                    }
+                    lines.insert(at, setting.line.clone());
+                }
+            }
+        }
+        None => lines.push(setting.line.clone()),
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Split a git option name into its section header and key:
+/// `user.name` → `[user]` + `name`, `url.<base>.insteadOf` → `[url "<base>"]`
+/// + `insteadOf`.
+fn git_section(name: &str) -> (Option<String>, String) {
+    let Some((section, rest)) = name.split_once('.') else {
+        return (None, name.to_string());
+    };
+    match rest.rsplit_once('.') {
+        Some((sub, key)) => (Some(format!("[{} \"{}\"]", section, sub)), key.to_string()),
+        None => (Some(format!("[{}]", section)), rest.to_string()),
+    }
+}
+
+/// Index of the line in `lines` that assigns `key`, if any.
+fn find_key(lines: &[String], key: &str) -> Option<usize> {
+    lines.iter().position(|l| {
+        l.trim_start()
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    })
+}
+
+/// zsh options set with `setopt`, going by the option's example.
+fn is_setopt(opt: &OptionEntry) -> bool {
+    opt.option_type == OptionType::Boolean
+        && opt
+            .example
+            .as_deref()
+            .is_some_and(|e| e.starts_with("setopt") || e.starts_with("unsetopt"))
+}
+
+/// `value` without one pair of surrounding single or double quotes.
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&q| value.strip_prefix(q).and_then(|v| v.strip_suffix(q)))
+        .unwrap_or(value)
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "on" | "true" | "yes" | "1" => Some(true),
+        "off" | "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Spell and quote a value the way `config_format` expects.
+fn format_value(config_format: &str, opt: &OptionEntry, value: &str) -> String {
+    if let Some(b) = parse_bool(value).filter(|_| opt.option_type == OptionType::Boolean) {
+        let (yes, no) = match config_format {
+            "tmux" => ("on", "off"),
+            "key-value" => ("yes", "no"),
+            "shell" => ("1", "0"),
+            _ => ("true", "false"),
+        };
+        return if b { yes } else { no }.to_string();
+    }
+
+    let numeric = matches!(opt.option_type, OptionType::Integer | OptionType::Float);
+    let quoted = value.starts_with('"') || value.starts_with('\'');
+    match config_format {
+        "lua" | "toml" => {
+            let literal = if config_format == "lua" { '{' } else { '[' };
+            if numeric || quoted || value.starts_with(literal) {
+                value.to_string()
+            } else {
+                format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+        }
+        "tmux" | "shell" => {
+            let plain = !value.contains(|c: char| c.is_whitespace() || "#;'\"$&|<>".contains(c));
+            if numeric || quoted || plain || (config_format == "shell" && value.starts_with('(')) {
+                value.to_string()
+            } else if value.contains('\'') {
+                format!("\"{}\"", value.replace('"', "\\\""))
+            } else {
+                format!("'{}'", value)
+            }
+        }
+        "git" if value.contains(['#', ';']) || value.trim() != value => {
+            format!("\"{}\"", value.replace('"', "\\\""))
+        }
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::module::ModuleRegistry;
+
+    fn option(tool: &str, name: &str) -> OptionEntry {
+        ModuleRegistry::get_options(tool)
+            .unwrap()
+            .options
+            .into_iter()
+            .find(|o| o.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_validate_boolean() {
+        let opt = option("tmux", "mouse");
+        for ok in ["on", "off", "true", "no", "ON", "1"] {
+            assert!(validate_value(&opt, ok).is_ok(), "{} should be accepted", ok);
+        }
+        assert_eq!(validate_value(&opt, "maybe").unwrap_err(), "expected on or off");
+        assert!(validate_value(&opt, "  ").is_err());
+    }
+
+    #[test]
+    fn test_validate_enum() {
+        let opt = option("tmux", "set-clipboard");
+        assert!(validate_value(&opt, "external").is_ok());
+        let err = validate_value(&opt, "sometimes").unwrap_err();
+        assert!(err.contains("on, external, off"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_numbers() {
+        let opt = option("tmux", "base-index");
+        assert!(validate_value(&opt, "1").is_ok());
+        assert!(validate_value(&opt, "-3").is_ok());
+        assert_eq!(validate_value(&opt, "1.5").unwrap_err(), "expected a whole number");
+        assert!(validate_value(&opt, "one").is_err());
+
+        let opt = option("kitty", "background_opacity");
+        assert!(validate_value(&opt, "0.9").is_ok());
+        assert!(validate_value(&opt, "opaque").is_err());
+    }
+
+    #[test]
+    fn test_validate_string_accepts_anything() {
+        let opt = option("git", "user.name");
+        assert!(validate_value(&opt, "Jane Doe").is_ok());
+    }
+
+    #[test]
+    fn test_initial_value() {
+        assert_eq!(initial_value(&option("tmux", "mouse")), "off");
+        let mut opt = option("tmux", "set-clipboard");
+        opt.default = None;
+        assert_eq!(initial_value(&opt), "on");
+    }
+
+    #[test]
+    fn test_render_per_format() {
+        let mouse = option("tmux", "mouse");
+        assert_eq!(render("tmux", None, &mouse, "true").line, "set -g mouse on");
+        assert_eq!(render("key-value", None, &mouse, "on").line, "mouse = yes");
+
+        let opt = option("tmux", "@resurrect-processes");
+        assert_eq!(
+            render("tmux", None, &opt, "ssh watch").line,
+            "set -g @resurrect-processes 'ssh watch'"
+        );
+
+        let opt = option("kitty", "background_opacity");
+        let setting = render("key-value", Some("{name} {value}"), &opt, "0.9");
+        assert_eq!(setting.line, "background_opacity 0.9");
+
+        let opt = option("neovim", "signcolumn");
+        let setting = render("lua", Some("vim.o.{name} = {value}"), &opt, "yes");
+        assert_eq!(setting.line, "vim.o.signcolumn = \"yes\"");
+
+        let opt = option("zsh", "HIST_IGNORE_DUPS");
+        assert_eq!(render("shell", None, &opt, "off").line, "unsetopt HIST_IGNORE_DUPS");
+        let opt = option("zsh", "HISTSIZE");
+        assert_eq!(render("shell", None, &opt, "10000").line, "HISTSIZE=10000");
+    }
+
+    #[test]
+    fn test_render_sections() {
+        let opt = option("git", "user.name");
+        let setting = render("git", None, &opt, "Jane Doe");
+        assert_eq!(setting.header.as_deref(), Some("[user]"));
+        assert_eq!(setting.line, "\tname = Jane Doe");
+
+        let opt = option("alacritty", "window.padding.x");
+        let setting = render("toml", None, &opt, "8");
+        assert_eq!(setting.header.as_deref(), Some("[window.padding]"));
+        assert_eq!(setting.line, "x = 8");
+    }
+
+    #[test]
+    fn test_apply_appends_and_replaces() {
+        let mouse = render("tmux", None, &option("tmux", "mouse"), "on");
+        assert_eq!(
+            apply("set -g mouse off", "tmux", &mouse),
+            "set -g mouse off\nset -g mouse on\n"
+        );
+
+        let name = render("git", None, &option("git", "user.name"), "Jane");
+        let git = "[core]\n\tpager = less\n";
+        assert_eq!(apply(git, "git", &name), "[core]\n\tpager = less\n\n[user]\n\tname = Jane\n");
+        let git = "[user]\n\temail = j@x\n\tname = Old\n[core]\n";
+        assert_eq!(apply(git, "git", &name), "[user]\n\temail = j@x\n\tname = Jane\n[core]\n");
+
+        let x = render("toml", None, &option("alacritty", "window.padding.x"), "8");
+        let toml = "[window.padding]\ny = 2\n";
+        assert_eq!(apply(toml, "toml", &x), "[window.padding]\nx = 8\ny = 2\n");
+    }
+
+    #[test]
+    fn test_apply_toml_top_level_before_tables() {
+        let setting = Setting {
+            header: None,
+            key: "live_config_reload".to_string(),
+            line: "live_config_reload = true".to_string(),
+        };
+        let toml = "import = []\n\n[window]\nopacity = 0.9\n";
+        assert_eq!(
+            apply(toml, "toml", &setting),
+            "import = []\nlive_config_reload = true\n\n[window]\nopacity = 0.9\n"
+        );
+    }
+}
@@ -1,24 +1,233 @@
+use std::cell::{OnceCell, RefCell};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
+use std::ops::Range;
 use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
+use base64::prelude::{BASE64_STANDARD, Engine as _};
+use chrono::{DateTime, Local, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, Value, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
 use sha2::{Digest, Sha256};
 
+use crate::core::config::DotsmithConfig;
+use crate::core::crypto::Cipher;
+use crate::core::detect;
+use crate::core::errors::DotsmithError;
 use crate::core::manifest::Manifest;
+use crate::core::secrets::{SecretHit, SecretScanner};
 use crate::util;
 
 /// Summary of a single snapshot for display.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct SnapshotSummary {
     pub id: i64,
-    #[allow(dead_code)]
     pub tool: String,
     pub file_path: String,
     pub hash: String,
     pub message: Option<String>,
     pub created_at: String,
+    /// Pinned snapshots are never removed by prune or gc.
+    pub pinned: bool,
+    /// Labels given with `snapshot --tag`, sorted.
+    pub tags: Vec<String>,
+}
+
+/// A full snapshot row, used to copy history between databases.
+#[derive(Debug)]
+pub struct SnapshotRecord {
+    pub tool: String,
+    pub file_path: String,
+    /// File text, or base64 of the raw bytes when `is_binary` or
+    /// `encrypted` is set.
+    pub content: String,
+    pub hash: String,
+    pub message: Option<String>,
+    pub created_at: String,
+    pub is_binary: bool,
+    /// `content` is an encrypted blob; copied as-is, never decrypted.
+    pub encrypted: bool,
+}
+
+/// Rows and content bytes removed (or that would be removed) by a prune,
+/// or rewritten and saved by a recompress.
+#[derive(Debug, Default, PartialEq)]
+pub struct PruneResult {
+    pub rows: usize,
+    pub bytes: u64,
+}
+
+/// How much space snapshot content takes in the database.
+#[derive(Debug, Default, PartialEq)]
+pub struct StorageStats {
+    pub rows: usize,
+    /// Distinct contents, each stored once however many rows share it.
+    pub blobs: usize,
+    /// Rows whose content is stored zstd-compressed.
+    pub compressed_rows: usize,
+    /// Physical size: the `content` column across all blobs.
+    pub stored_bytes: u64,
+    /// Logical size: what the content would take with a copy per row and
+    /// nothing compressed.
+    pub logical_bytes: u64,
+}
+
+impl StorageStats {
+    /// Bytes saved by sharing and compressing content.
+    pub fn saved_bytes(&self) -> u64 {
+        self.logical_bytes.saturating_sub(self.stored_bytes)
+    }
+}
+
+/// What rolling a tool back to a point in time did to each of its files.
+#[derive(Debug, Default, PartialEq)]
+pub struct ToolRollback {
+    /// Files restored, with the snapshot each came from.
+    pub restored: Vec<(String, i64)>,
+    /// Files already holding their content as of the cutoff.
+    pub unchanged: Vec<String>,
+    /// Files with no snapshot at or before the cutoff.
+    pub skipped: Vec<String>,
+}
+
+/// Tracked files whose latest snapshots have identical content.
+#[derive(Debug, PartialEq)]
+pub struct SharedContent {
+    pub hash: String,
+    /// `(tool, file_path)` pairs, sorted.
+    pub files: Vec<(String, String)>,
+}
+
+/// File path recorded for content snapshotted from stdin. There is no file
+/// behind it, so it can't be diffed or rolled back.
+pub const STDIN_PATH: &str = "<stdin>";
+
+/// What an auto-snapshot's message can refer to, for [`format_message`].
+#[derive(Debug)]
+pub struct MessageContext {
+    pub tool: String,
+    /// What took the snapshot: `pre-edit`, `watch`, `TUI`, ...
+    pub source: String,
+    pub time: DateTime<Local>,
+    pub host: String,
+}
+
+impl MessageContext {
+    /// A snapshot of `tool` taken by `source` on this machine, now.
+    pub fn now(tool: &str, source: &str) -> Self {
+        Self {
+            tool: tool.to_string(),
+            source: source.to_string(),
+            time: Local::now(),
+            host: crate::core::profile::current_hostname()
+                .unwrap_or_else(|| "unknown".to_string()),
+        }
+    }
+}
+
+/// Matching lines kept per snapshot by [`SnapshotEngine::search_content`].
+pub const MAX_MATCHES_PER_SNAPSHOT: usize = 5;
+
+/// A snapshot whose stored content contains a search query.
+#[derive(Debug, Serialize)]
+pub struct ContentMatch {
+    pub snapshot: SnapshotSummary,
+    /// The first few matching lines, at most [`MAX_MATCHES_PER_SNAPSHOT`].
+    pub lines: Vec<MatchedLine>,
+    /// Number of matching lines, including those cut off by the cap.
+    pub total: usize,
+}
+
+/// One line of a snapshot containing the query.
+#[derive(Debug, Serialize)]
+pub struct MatchedLine {
+    /// 1-based line number.
+    pub line: usize,
+    pub text: String,
+}
+
+/// The stored content of a snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotContent {
+    Text(String),
+    /// Non-UTF-8 file, kept byte-for-byte.
+    Binary(Vec<u8>),
+}
+
+impl SnapshotContent {
+    /// Classify raw file bytes: valid UTF-8 is text, anything else binary.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        match String::from_utf8(bytes) {
+            Ok(text) => Self::Text(text),
+            Err(e) => Self::Binary(e.into_bytes()),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Text(text) => text.as_bytes(),
+            Self::Binary(bytes) => bytes,
+        }
+    }
+
+    pub fn is_binary(&self) -> bool {
+        matches!(self, Self::Binary(_))
+    }
+
+    /// Decode an unencrypted `content` column value.
+    fn from_column(content: StoredContent, is_binary: bool, compressed: bool) -> Result<Self> {
+        if compressed {
+            let bytes = zstd::decode_all(content.0.as_slice())
+                .context("compressed snapshot content is damaged")?;
+            return Ok(Self::from_bytes(bytes));
+        }
+        let content = String::from_utf8(content.0).context("snapshot content is not UTF-8")?;
+        if is_binary {
+            Ok(Self::Binary(BASE64_STANDARD.decode(&content)?))
+        } else {
+            Ok(Self::Text(content))
+        }
+    }
+
+    /// Encode as text for the `content` column; binary data is stored as
+    /// base64.
+    fn to_column(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Binary(bytes) => BASE64_STANDARD.encode(bytes),
+        }
+    }
+
+    /// Encode for the `content` column: the zstd frame of the bytes when
+    /// that is smaller than [`SnapshotContent::to_column`], otherwise the
+    /// text. Returns the value and whether it is compressed.
+    fn to_stored(&self) -> (Value, bool) {
+        let text_len = match self {
+            Self::Text(text) => text.len(),
+            Self::Binary(bytes) => bytes.len().div_ceil(3) * 4,
+        };
+        match zstd::bulk::compress(self.as_bytes(), 0) {
+            Ok(frame) if frame.len() < text_len => (Value::Blob(frame), true),
+            _ => (Value::Text(self.to_column()), false),
+        }
+    }
+}
+
+/// A `content` column value as stored: text, or a zstd frame blob on
+/// `compressed` rows. Read as bytes either way; the row's flags say how to
+/// decode it.
+struct StoredContent(Vec<u8>);
+
+impl FromSql for StoredContent {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Text(bytes) | ValueRef::Blob(bytes) => Ok(Self(bytes.to_vec())),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
 }
 
 /// A file diff between two states.
@@ -27,18 +236,111 @@ pub struct FileDiff {
     pub file_path: String,
     pub old_content: String,
     pub new_content: String,
+    /// Either side is binary; the content fields are empty and only the
+    /// fact that the file changed is reported.
+    pub binary: bool,
+}
+
+impl FileDiff {
+    /// Runs of consecutive changed lines, as line ranges of the old and new
+    /// content. Binary diffs have none.
+    pub fn hunks(&self) -> Vec<(Range<usize>, Range<usize>)> {
+        if self.binary {
+            return Vec::new();
+        }
+        let diff = similar::TextDiff::from_lines(&self.old_content, &self.new_content);
+        let mut hunks: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+        let mut in_hunk = false;
+        for op in diff.ops() {
+            if op.tag() == similar::DiffTag::Equal {
+                in_hunk = false;
+            } else if in_hunk && let Some((old, new)) = hunks.last_mut() {
+                old.end = op.old_range().end;
+                new.end = op.new_range().end;
+            } else {
+                hunks.push((op.old_range(), op.new_range()));
+                in_hunk = true;
+            }
+        }
+        hunks
+    }
+
+    /// The new content with the hunks numbered in `chosen` (indices into
+    /// [`FileDiff::hunks`]) taken back from the old content.
+    pub fn restore_hunks(&self, chosen: &BTreeSet<usize>) -> String {
+        let diff = similar::TextDiff::from_lines(&self.old_content, &self.new_content);
+        let (old_lines, new_lines) = (diff.old_slices(), diff.new_slices());
+
+        let mut restored = String::with_capacity(self.new_content.len());
+        let mut next = 0;
+        for (i, (old, new)) in self.hunks().into_iter().enumerate() {
+            restored.extend(new_lines[next..new.start].iter().copied());
+            if chosen.contains(&i) {
+                restored.extend(old_lines[old].iter().copied());
+            } else {
+                restored.extend(new_lines[new.clone()].iter().copied());
+            }
+            next = new.end;
+        }
+        restored.extend(new_lines[next..].iter().copied());
+        restored
+    }
 }
 
 /// The snapshot engine manages point-in-time copies of config files in SQLite.
 pub struct SnapshotEngine {
     conn: Connection,
+    /// Store new snapshots encrypted (`general.encrypt`).
+    encrypt: bool,
+    /// Asked for the first time encrypted content is written or read.
+    cipher: OnceCell<Cipher>,
+    /// Keeps new text content that looks like it holds a secret out of the
+    /// database; such files are skipped and listed in `secret_hits`.
+    secrets: Option<SecretScanner>,
+    secret_hits: RefCell<Vec<(String, Vec<SecretHit>)>>,
+    /// Store files the scanner flags anyway, still listing them in
+    /// `secret_hits` (safety snapshots).
+    keep_secrets: bool,
 }
 
 impl SnapshotEngine {
     /// Open (or create) the snapshot database at `<config_dir>/snapshots.db`.
-    /// Sets 0600 permissions on the DB file.
+    /// Sets 0600 permissions on the DB file. New snapshots are encrypted when
+    /// `general.encrypt` is set in the config; otherwise they are checked for
+    /// secrets first.
     pub fn open(config_dir: &Path) -> Result<Self> {
-        let db_path = config_dir.join("snapshots.db");
+        let mut engine = Self::open_file(&config_dir.join("snapshots.db"))?;
+        let config = DotsmithConfig::load(config_dir);
+        engine.encrypt = config.general.encrypt;
+        if !engine.encrypt {
+            engine.secrets = Some(SecretScanner::new(&config.general.secret_patterns)?);
+        }
+        Ok(engine)
+    }
+
+    /// Store files even when they look like they contain secrets
+    /// (`--no-secrets-check`).
+    pub fn skip_secrets_check(&mut self) {
+        self.secrets = None;
+    }
+
+    /// Store files that look like they contain secrets, but still list them
+    /// in [`SnapshotEngine::take_secret_hits`]. For snapshots taken before a
+    /// change, which a rollback can't do without.
+    pub fn keep_secrets(&mut self) {
+        self.keep_secrets = true;
+    }
+
+    /// Files skipped (or kept, see [`SnapshotEngine::keep_secrets`]) since
+    /// the last call because they look like they contain secrets, with what
+    /// was found in each.
+    pub fn take_secret_hits(&self) -> Vec<(String, Vec<SecretHit>)> {
+        self.secret_hits.take()
+    }
+
+    /// Open (or create) a snapshot database at an explicit path.
+    pub fn open_file(db_path: &Path) -> Result<Self> {
+        let db_path = db_path.to_path_buf();
         let conn = Connection::open(&db_path)
             .with_context(|| format!("failed to open snapshot database at {}", db_path.display()))?;
 
@@ -50,32 +352,190 @@ impl SnapshotEngine {
         // Enable WAL mode for better concurrent reads
         conn.pragma_update(None, "journal_mode", "WAL")?;
 
-        let engine = Self { conn };
+        let engine = Self {
+            conn,
+            encrypt: false,
+            cipher: OnceCell::new(),
+            secrets: None,
+            secret_hits: RefCell::new(Vec::new()),
+            keep_secrets: false,
+        };
         engine.init_schema()?;
         Ok(engine)
     }
 
     /// Create the schema if it doesn't exist.
     fn init_schema(&self) -> Result<()> {
-        self.conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS snapshots (
+        self.conn.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS snapshots ({});
+            CREATE TABLE IF NOT EXISTS blobs (
+                hash        TEXT PRIMARY KEY,
+                content     TEXT NOT NULL,
+                is_binary   INTEGER NOT NULL DEFAULT 0,
+                encrypted   INTEGER NOT NULL DEFAULT 0,
+                compressed  INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS operations (
                 id          INTEGER PRIMARY KEY AUTOINCREMENT,
-                tool        TEXT NOT NULL,
                 file_path   TEXT NOT NULL,
-                content     TEXT NOT NULL,
-                hash        TEXT NOT NULL,
-                message     TEXT,
+                kind        TEXT NOT NULL,
+                created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX IF NOT EXISTS idx_operations_path ON operations(file_path);
+            CREATE TABLE IF NOT EXISTS tags (
+                snapshot_id INTEGER NOT NULL,
+                tag         TEXT NOT NULL,
                 created_at  TEXT NOT NULL DEFAULT (datetime('now')),
-                UNIQUE(tool, file_path, hash)
+                PRIMARY KEY (snapshot_id, tag)
             );
-            CREATE INDEX IF NOT EXISTS idx_snapshots_tool ON snapshots(tool);
-            CREATE INDEX IF NOT EXISTS idx_snapshots_created ON snapshots(created_at);",
+            CREATE INDEX IF NOT EXISTS idx_tags_tag ON tags(tag);",
+            SNAPSHOTS_COLUMNS
+        ))?;
+        self.migrate()?;
+        self.conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_snapshots_tool ON snapshots(tool);
+            CREATE INDEX IF NOT EXISTS idx_snapshots_created ON snapshots(created_at);
+            CREATE INDEX IF NOT EXISTS idx_snapshots_hash ON snapshots(hash);",
         )?;
         Ok(())
     }
 
+    /// Bring databases created by older versions up to the current schema.
+    fn migrate(&self) -> Result<()> {
+        let has_column = |table: &str, column: &str| -> Result<bool> {
+            Ok(self.conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+                params![table, column],
+                |row| row.get::<_, i64>(0).map(|n| n > 0),
+            )?)
+        };
+        let add_column = |table: &str, column: &str| -> Result<()> {
+            if !has_column(table, column)? {
+                self.conn.execute(
+                    &format!(
+                        "ALTER TABLE {} ADD COLUMN {} INTEGER NOT NULL DEFAULT 0",
+                        table, column
+                    ),
+                    [],
+                )?;
+            }
+            Ok(())
+        };
+
+        add_column("snapshots", "pinned")?;
+        add_column("blobs", "encrypted")?;
+        add_column("blobs", "compressed")?;
+        if has_column("snapshots", "content")? {
+            add_column("snapshots", "is_binary")?;
+            self.move_content_to_blobs()?;
+        }
+        Ok(())
+    }
+
+    /// One-time move from content stored in every `snapshots` row to one
+    /// `blobs` row per hash.
+    fn move_content_to_blobs(&self) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute_batch(&format!(
+            "INSERT OR IGNORE INTO blobs (hash, content, is_binary)
+                SELECT hash, content, is_binary FROM snapshots ORDER BY id DESC;
+            CREATE TABLE snapshots_new ({});
+            INSERT INTO snapshots_new (id, tool, file_path, hash, message, created_at, pinned)
+                SELECT id, tool, file_path, hash, message, created_at, pinned FROM snapshots;
+            UPDATE sqlite_sequence
+                SET seq = (SELECT seq FROM sqlite_sequence WHERE name = 'snapshots')
+                WHERE name = 'snapshots_new';
+            DROP TABLE snapshots;
+            ALTER TABLE snapshots_new RENAME TO snapshots;",
+            SNAPSHOTS_COLUMNS
+        ))?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Encrypt new snapshots with `cipher`, regardless of the config.
+    #[cfg(test)]
+    fn with_cipher(mut self, mut cipher: Cipher) -> Result<Self> {
+        self.check_passphrase(&mut cipher)?;
+        self.encrypt = true;
+        self.cipher = OnceCell::from(cipher);
+        Ok(self)
+    }
+
+    /// The cipher for encrypted content, asking for the passphrase on first
+    /// use. A passphrase that can't open the existing encrypted snapshots is
+    /// rejected, so new ones are never sealed with a mistyped key.
+    pub fn cipher(&self) -> Result<&Cipher> {
+        if let Some(cipher) = self.cipher.get() {
+            return Ok(cipher);
+        }
+        let mut cipher = Cipher::from_env_or_prompt(self.latest_encrypted()?.is_none())?;
+        self.check_passphrase(&mut cipher)?;
+        Ok(self.cipher.get_or_init(|| cipher))
+    }
+
+    /// Ask for the passphrase now if encrypted content will be touched, for
+    /// callers that can't prompt later (the TUI).
+    pub fn unlock(&self) -> Result<()> {
+        if self.encrypt || self.latest_encrypted()?.is_some() {
+            self.cipher()?;
+        }
+        Ok(())
+    }
+
+    fn check_passphrase(&self, cipher: &mut Cipher) -> Result<()> {
+        if let Some(content) = self.latest_encrypted()? {
+            let blob = BASE64_STANDARD.decode(&content)?;
+            cipher.decrypt(&blob)?;
+            cipher.reuse_salt(&blob);
+        }
+        Ok(())
+    }
+
+    /// The content column of the newest encrypted blob.
+    fn latest_encrypted(&self) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT content FROM blobs WHERE encrypted = 1 ORDER BY rowid DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .ok())
+    }
+
+    /// Encode content for the `content` column: encrypted when enabled,
+    /// otherwise compressed if that saves space. Returns the column value and
+    /// whether it is encrypted and whether it is compressed.
+    fn encode(&self, content: &SnapshotContent) -> Result<(Value, bool, bool)> {
+        if !self.encrypt {
+            let (column, compressed) = content.to_stored();
+            return Ok((column, false, compressed));
+        }
+        let blob = self.cipher()?.encrypt(content.as_bytes())?;
+        Ok((Value::Text(BASE64_STANDARD.encode(&blob)), true, false))
+    }
+
+    /// Decode a `content` column value, decompressing or decrypting it as
+    /// its flags say.
+    fn decode(
+        &self,
+        content: StoredContent,
+        is_binary: bool,
+        encrypted: bool,
+        compressed: bool,
+    ) -> Result<SnapshotContent> {
+        if !encrypted {
+            return SnapshotContent::from_column(content, is_binary, compressed);
+        }
+        let blob = BASE64_STANDARD.decode(&content.0)?;
+        Ok(SnapshotContent::from_bytes(self.cipher()?.decrypt(&blob)?))
+    }
+
     /// Take a snapshot of all config files for a tool.
-    /// Returns the number of files snapshotted (skips unchanged files via hash dedup).
+    /// Returns the number of files snapshotted (skips unchanged files via hash
+    /// dedup, and files that look like they hold secrets; see
+    /// [`SnapshotEngine::take_secret_hits`]).
     pub fn snapshot_tool(
         &self,
         tool: &str,
@@ -84,14 +544,12 @@ impl SnapshotEngine {
     ) -> Result<usize> {
         let mut count = 0;
 
-        for path_str in config_paths {
-            let path = util::paths::expand_tilde(path_str);
-
+        for path in config_paths.iter().flat_map(|p| util::paths::expand_config_path(p)) {
             if path.is_dir() {
                 // Snapshot all files within the directory
                 count += self.snapshot_directory(tool, &path, message)?;
             } else if path.is_file()
-                && self.snapshot_file(tool, &path, message)?
+                && self.snapshot_path(tool, &path, message)?
             {
                 count += 1;
             }
@@ -101,7 +559,8 @@ impl SnapshotEngine {
         Ok(count)
     }
 
-    /// Snapshot all config files in a directory (non-recursive for now).
+    /// Snapshot all config files under a directory, recursing into
+    /// subdirectories except plugin, VCS and cache dirs.
     fn snapshot_directory(
         &self,
         tool: &str,
@@ -109,47 +568,119 @@ impl SnapshotEngine {
         message: Option<&str>,
     ) -> Result<usize> {
         let mut count = 0;
-        if !dir.is_dir() {
-            return Ok(0);
-        }
-
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file()
-                && self.snapshot_file(tool, &path, message)?
-            {
+        for path in walk_config_dir(dir, true)? {
+            if self.snapshot_path(tool, &path, message)? {
                 count += 1;
             }
         }
-
         Ok(count)
     }
 
-    /// Snapshot a single file. Returns true if a new snapshot was created,
-    /// false if the content hasn't changed since the last snapshot.
-    fn snapshot_file(
+    /// Snapshot one file under `tool`, whether or not the tool tracks it.
+    /// Returns true if a new snapshot was created.
+    pub fn snapshot_path(&self, tool: &str, path: &Path, message: Option<&str>) -> Result<bool> {
+        let bytes = fs::read(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        self.snapshot_file(tool, &util::paths::contract_tilde(path), bytes, message)
+    }
+
+    /// Snapshot content read from stdin under `tool`, recorded as
+    /// [`STDIN_PATH`]. Returns true if a new snapshot was created.
+    pub fn snapshot_stdin(
         &self,
         tool: &str,
-        path: &Path,
+        bytes: Vec<u8>,
         message: Option<&str>,
     ) -> Result<bool> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("failed to read {}", path.display()))?;
+        self.snapshot_file(tool, STDIN_PATH, bytes, message)
+    }
 
-        let hash = compute_hash(&content);
-        let path_str = util::paths::contract_tilde(path);
+    /// Snapshot the content of one file, keyed by `path_str`. Returns true if
+    /// a new snapshot was created, false if the content hasn't changed since
+    /// the last snapshot or was held back by the secrets check.
+    /// Non-UTF-8 content is stored base64-encoded and flagged as binary.
+    fn snapshot_file(
+        &self,
+        tool: &str,
+        path_str: &str,
+        bytes: Vec<u8>,
+        message: Option<&str>,
+    ) -> Result<bool> {
+        let hash = compute_hash(&bytes);
+        let path_str = path_str.to_string();
+        // Checked up front so an unchanged file never needs the passphrase
+        if self.find_snapshot_by_hash(tool, &path_str, &hash)?.is_some() {
+            return Ok(false);
+        }
 
+        let content = SnapshotContent::from_bytes(bytes);
+        if let (Some(scanner), SnapshotContent::Text(text)) = (&self.secrets, &content) {
+            let hits = scanner.scan(text);
+            if !hits.is_empty() {
+                self.secret_hits.borrow_mut().push((path_str.clone(), hits));
+                if !self.keep_secrets {
+                    return Ok(false);
+                }
+            }
+        }
+        // Content already stored for another file or tool is shared, but a
+        // plaintext copy is encrypted first when encryption is on
+        if !self.has_blob(&hash, self.encrypt)? {
+            let (column, encrypted, compressed) = self.encode(&content)?;
+            self.conn.execute(
+                "INSERT OR REPLACE INTO blobs (hash, content, is_binary, encrypted, compressed)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![hash, column, content.is_binary(), encrypted, compressed],
+            )?;
+        }
         // INSERT OR IGNORE — skips if this exact content was already snapshotted
         let rows = self.conn.execute(
-            "INSERT OR IGNORE INTO snapshots (tool, file_path, content, hash, message)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![tool, path_str, content, hash, message],
+            "INSERT OR IGNORE INTO snapshots (tool, file_path, hash, message)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![tool, path_str, hash, message],
         )?;
 
         Ok(rows > 0)
     }
 
+    /// Whether content with `hash` is stored, and encrypted if `encrypted`.
+    fn has_blob(&self, hash: &str, encrypted: bool) -> Result<bool> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) FROM blobs WHERE hash = ?1 AND (encrypted = 1 OR ?2 = 0)",
+            params![hash, encrypted],
+            |row| row.get::<_, i64>(0).map(|n| n > 0),
+        )?)
+    }
+
+    /// Drop blobs no snapshot refers to anymore. Returns how many went.
+    fn delete_orphaned_blobs(&self) -> Result<usize> {
+        Ok(self.conn.execute(
+            "DELETE FROM blobs WHERE hash NOT IN (SELECT hash FROM snapshots)",
+            [],
+        )?)
+    }
+
+    /// Which of a tool's files [`SnapshotEngine::snapshot_tool`] would store,
+    /// without storing anything: each file with `true` when its content isn't
+    /// in the database yet.
+    pub fn preview_snapshot(
+        &self,
+        tool: &str,
+        config_paths: &[String],
+    ) -> Result<Vec<(String, bool)>> {
+        let mut preview = Vec::new();
+        for path in config_files(config_paths)? {
+            let bytes = fs::read(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let path_str = util::paths::contract_tilde(&path);
+            let is_new = self
+                .find_snapshot_by_hash(tool, &path_str, &compute_hash(&bytes))?
+                .is_none();
+            preview.push((path_str, is_new));
+        }
+        Ok(preview)
+    }
+
     /// Take snapshots of ALL tracked tools.
     pub fn snapshot_all(
         &self,
@@ -167,9 +698,7 @@ impl SnapshotEngine {
     pub fn diff_current(&self, tool: &str, config_paths: &[String]) -> Result<Vec<FileDiff>> {
         let mut diffs = Vec::new();
 
-        for path_str in config_paths {
-            let path = util::paths::expand_tilde(path_str);
-
+        for path in config_paths.iter().flat_map(|p| util::paths::expand_config_path(p)) {
             if path.is_dir() {
                 self.diff_directory(tool, &path, &mut diffs)?;
             } else if path.is_file()
@@ -189,75 +718,91 @@ impl SnapshotEngine {
         dir: &Path,
         diffs: &mut Vec<FileDiff>,
     ) -> Result<()> {
-        if !dir.is_dir() {
-            return Ok(());
-        }
-
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file()
-                && let Some(diff) = self.diff_file(tool, &path)?
-            {
+        for path in walk_config_dir(dir, true)? {
+            if let Some(diff) = self.diff_file(tool, &path)? {
                 diffs.push(diff);
             }
         }
-
         Ok(())
     }
 
     /// Diff a single file against its last snapshot. Returns None if unchanged.
     fn diff_file(&self, tool: &str, path: &Path) -> Result<Option<FileDiff>> {
-        let current_content = fs::read_to_string(path)
+        let current = fs::read(path)
             .with_context(|| format!("failed to read {}", path.display()))?;
-
         let path_str = util::paths::contract_tilde(path);
 
         // Get the last snapshot for this file
-        let last_content: Option<String> = self
+        let last: Option<(String, StoredContent, bool, bool, bool)> = self
             .conn
             .query_row(
-                "SELECT content FROM snapshots
+                "SELECT hash, content, is_binary, encrypted, compressed
+                 FROM snapshots JOIN blobs USING (hash)
                  WHERE tool = ?1 AND file_path = ?2
                  ORDER BY id DESC LIMIT 1",
                 params![tool, path_str],
-                |row| row.get(0),
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
             )
             .ok();
 
-        let old_content = last_content.unwrap_or_default();
+        // Matching hashes settle it without decoding (or decrypting) the snapshot
+        let old = match last {
+            Some((hash, ..)) if hash == compute_hash(&current) => return Ok(None),
+            Some((_, content, is_binary, encrypted, compressed)) => {
+                self.decode(content, is_binary, encrypted, compressed)?
+            }
+            None => SnapshotContent::Text(String::new()),
+        };
+        let current = SnapshotContent::from_bytes(current);
 
-        if old_content == current_content {
+        if old.as_bytes() == current.as_bytes() {
             return Ok(None);
         }
 
-        Ok(Some(FileDiff {
-            file_path: path_str,
-            old_content,
-            new_content: current_content,
-        }))
+        let diff = match (old, current) {
+            (SnapshotContent::Text(old_content), SnapshotContent::Text(new_content)) => FileDiff {
+                file_path: path_str,
+                old_content,
+                new_content,
+                binary: false,
+            },
+            _ => FileDiff {
+                file_path: path_str,
+                old_content: String::new(),
+                new_content: String::new(),
+                binary: true,
+            },
+        };
+
+        Ok(Some(diff))
+    }
+
+    /// Every file path `tool` has snapshots of, sorted. Lets tools that
+    /// aren't in the manifest, like ones snapshotted with `--file`, be diffed.
+    pub fn snapshot_paths(&self, tool: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT file_path FROM snapshots WHERE tool = ?1 ORDER BY file_path",
+        )?;
+        let rows = stmt.query_map(params![tool], |row| row.get(0))?;
+        let mut paths = Vec::new();
+        for row in rows {
+            paths.push(row?);
+        }
+        Ok(paths)
     }
 
     /// List snapshot history for a tool.
     pub fn history(&self, tool: &str, limit: usize) -> Result<Vec<SnapshotSummary>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, tool, file_path, hash, message, created_at
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}
              FROM snapshots
              WHERE tool = ?1
              ORDER BY id DESC
              LIMIT ?2",
-        )?;
+            SUMMARY_COLUMNS
+        ))?;
 
-        let rows = stmt.query_map(params![tool, limit as i64], |row| {
-            Ok(SnapshotSummary {
-                id: row.get(0)?,
-                tool: row.get(1)?,
-                file_path: row.get(2)?,
-                hash: row.get(3)?,
-                message: row.get(4)?,
-                created_at: row.get(5)?,
-            })
-        })?;
+        let rows = stmt.query_map(params![tool, limit as i64], summary_from_row)?;
 
         let mut summaries = Vec::new();
         for row in rows {
@@ -267,95 +812,1139 @@ impl SnapshotEngine {
         Ok(summaries)
     }
 
-    /// Get snapshot content by ID (for rollback).
-    pub fn get_snapshot(&self, snapshot_id: i64) -> Result<Option<(String, String)>> {
-        let result = self
-            .conn
-            .query_row(
-                "SELECT file_path, content FROM snapshots WHERE id = ?1",
-                params![snapshot_id],
-                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
-            )
-            .ok();
+    /// List snapshot history across all tools, newest first.
+    pub fn history_all(&self, limit: usize) -> Result<Vec<SnapshotSummary>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}
+             FROM snapshots
+             ORDER BY id DESC
+             LIMIT ?1",
+            SUMMARY_COLUMNS
+        ))?;
 
-        Ok(result)
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = stmt.query_map(params![limit], summary_from_row)?;
+
+        let mut summaries = Vec::new();
+        for row in rows {
+            summaries.push(row?);
+        }
+
+        Ok(summaries)
     }
 
-    /// Rollback a file to a specific snapshot.
-    /// Creates a backup of the current file first.
-    pub fn rollback(&self, snapshot_id: i64, backup_dir: &Path) -> Result<String> {
-        let (path_str, content) = self
-            .get_snapshot(snapshot_id)?
-            .ok_or_else(|| anyhow::anyhow!("snapshot {} not found", snapshot_id))?;
+    /// Find snapshots whose content contains `query` (case-sensitive), newest
+    /// first, optionally only for one tool. Binary snapshots are skipped.
+    /// Encrypted snapshots are decrypted to be searched.
+    pub fn search_content(&self, tool: Option<&str>, query: &str) -> Result<Vec<ContentMatch>> {
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, content, encrypted, compressed
+             FROM snapshots JOIN blobs USING (hash)
+             WHERE is_binary = 0
+               AND (encrypted = 1 OR compressed = 1 OR instr(content, ?1) > 0)
+               AND (?2 IS NULL OR tool = ?2)
+             ORDER BY id DESC",
+            SUMMARY_COLUMNS
+        ))?;
+
+        let rows = stmt.query_map(params![query, tool], |row| {
+            Ok((summary_from_row(row)?, row.get(8)?, row.get(9)?, row.get(10)?))
+        })?;
 
-        let path = util::paths::expand_tilde(&path_str);
-
-        // Create backup of current state
-        if path.exists() {
-            fs::create_dir_all(backup_dir)?;
-            let backup_name = format!(
-                "{}.{}.bak",
-                path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("file"),
-                chrono::Utc::now().format("%Y%m%d_%H%M%S")
-            );
-            let backup_path = backup_dir.join(backup_name);
-            fs::copy(&path, &backup_path)
-                .with_context(|| format!("failed to backup {}", path.display()))?;
+        let mut matches = Vec::new();
+        for row in rows {
+            let (snapshot, content, encrypted, compressed) = row?;
+            let SnapshotContent::Text(content) =
+                self.decode(content, false, encrypted, compressed)?
+            else {
+                continue;
+            };
+            let mut lines = Vec::new();
+            let mut total = 0;
+            for (i, text) in content.lines().enumerate() {
+                if !text.contains(query) {
+                    continue;
+                }
+                total += 1;
+                if lines.len() < MAX_MATCHES_PER_SNAPSHOT {
+                    lines.push(MatchedLine {
+                        line: i + 1,
+                        text: text.to_string(),
+                    });
+                }
+            }
+            // A query spanning lines matches the content but no single line
+            if total > 0 {
+                matches.push(ContentMatch {
+                    snapshot,
+                    lines,
+                    total,
+                });
+            }
         }
+        Ok(matches)
+    }
 
-        // Write the snapshot content back
-        util::fs::atomic_write(&path, &content)?;
+    /// Export every snapshot row for the given tools, oldest first.
+    /// Compressed content is exported decompressed.
+    pub fn export_records(&self, tools: &[&str]) -> Result<Vec<SnapshotRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT tool, file_path, content, hash, message, created_at, is_binary, encrypted,
+                    compressed
+             FROM snapshots JOIN blobs USING (hash)
+             WHERE tool = ?1
+             ORDER BY id ASC",
+        )?;
 
-        Ok(path_str)
+        let mut records = Vec::new();
+        for tool in tools {
+            let rows = stmt.query_map(params![tool], |row| {
+                let record = SnapshotRecord {
+                    tool: row.get(0)?,
+                    file_path: row.get(1)?,
+                    content: String::new(),
+                    hash: row.get(3)?,
+                    message: row.get(4)?,
+                    created_at: row.get(5)?,
+                    is_binary: row.get(6)?,
+                    encrypted: row.get(7)?,
+                };
+                Ok((record, row.get::<_, StoredContent>(2)?, row.get::<_, bool>(8)?))
+            })?;
+            for row in rows {
+                let (mut record, content, compressed) = row?;
+                record.content = if compressed {
+                    SnapshotContent::from_column(content, record.is_binary, true)?.to_column()
+                } else {
+                    String::from_utf8(content.0).context("snapshot content is not UTF-8")?
+                };
+                records.push(record);
+            }
+        }
+
+        Ok(records)
     }
-}
 
-/// Compute SHA-256 hash of content.
-fn compute_hash(content: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
-    format!("{:x}", hasher.finalize())
-}
+    /// Write a tool's snapshots, oldest first, into `dir` as a numbered
+    /// patch series for `git am`: each patch takes its file from the previous
+    /// snapshot of that file to this one (the first adds it), with the
+    /// snapshot message as subject and its time as the date. Paths are made
+    /// relative to the home directory, or to `/` outside it. Binary
+    /// snapshots have no text diff and are left out. Returns the patch files
+    /// written.
+    pub fn export_patches(&self, tool: &str, dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+        let mut history = self.history(tool, usize::MAX)?;
+        history.reverse();
+
+        let mut latest: HashMap<String, String> = HashMap::new();
+        let mut patches = Vec::new();
+        for entry in &history {
+            let Some((_, SnapshotContent::Text(content))) = self.get_snapshot(entry.id)? else {
+                continue;
+            };
+            let previous = latest.insert(entry.file_path.clone(), content.clone());
+            if previous.as_ref() == Some(&content) {
+                continue;
+            }
+            patches.push((entry, previous, content));
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        let total = patches.len();
+        let mut written = Vec::new();
+        for (i, (entry, previous, content)) in patches.into_iter().enumerate() {
+            let subject = entry
+                .message
+                .clone()
+                .unwrap_or_else(|| format!("Update {}", entry.file_path));
+            let patch = format_patch(entry, &subject, i + 1, total, previous.as_deref(), &content);
+            let path = dir.join(patch_file_name(i + 1, &subject));
+            fs::write(&path, patch)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
 
-    fn setup() -> (TempDir, SnapshotEngine, TempDir) {
-        let config_tmp = TempDir::new().unwrap();
-        let engine = SnapshotEngine::open(config_tmp.path()).unwrap();
-        let files_tmp = TempDir::new().unwrap();
-        (config_tmp, engine, files_tmp)
+    /// Merge snapshot rows into this database, keeping their original
+    /// timestamps. Rows already present (same tool, path and hash) are skipped.
+    /// Unencrypted content is compressed as new snapshots are. Returns the
+    /// number of rows inserted.
+    pub fn import_records(&self, records: &[SnapshotRecord]) -> Result<usize> {
+        let mut inserted = 0;
+        for r in records {
+            let (column, compressed) = if r.encrypted {
+                (Value::Text(r.content.clone()), false)
+            } else {
+                let content = StoredContent(r.content.clone().into_bytes());
+                SnapshotContent::from_column(content, r.is_binary, false)?.to_stored()
+            };
+            // An encrypted copy replaces a plaintext one, never the reverse
+            self.conn.execute(
+                "INSERT INTO blobs (hash, content, is_binary, encrypted, compressed)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (hash) DO UPDATE
+                 SET content = excluded.content, encrypted = 1, compressed = 0
+                 WHERE excluded.encrypted = 1 AND blobs.encrypted = 0",
+                params![r.hash, column, r.is_binary, r.encrypted, compressed],
+            )?;
+            inserted += self.conn.execute(
+                "INSERT OR IGNORE INTO snapshots (tool, file_path, hash, message, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![r.tool, r.file_path, r.hash, r.message, r.created_at],
+            )?;
+        }
+        Ok(inserted)
     }
 
-    #[test]
-    fn test_snapshot_and_history() {
-        let (_config_tmp, engine, files_tmp) = setup();
+    /// Delete old snapshots. A row is removed when it falls outside the newest
+    /// `keep` snapshots of its `(tool, file_path)` or was created before
+    /// `before`. The newest snapshot of every file and pinned or tagged
+    /// snapshots are always kept. Unless
+    /// `dry_run` is set, the database is vacuumed afterward to reclaim space.
+    pub fn prune(
+        &self,
+        keep: Option<usize>,
+        before: Option<DateTime<Utc>>,
+        dry_run: bool,
+    ) -> Result<PruneResult> {
+        // rank 1 = newest snapshot of a file
+        const CANDIDATES: &str = "SELECT id
+             FROM (
+                 SELECT id, created_at, pinned,
+                        ROW_NUMBER() OVER (PARTITION BY tool, file_path ORDER BY id DESC) AS rank
+                 FROM snapshots
+             )
+             WHERE rank > 1 AND pinned = 0
+               AND id NOT IN (SELECT snapshot_id FROM tags)
+               AND ((?1 IS NOT NULL AND rank > ?1) OR (?2 IS NOT NULL AND created_at < ?2))";
+
+        let keep = keep.map(|k| i64::try_from(k).unwrap_or(i64::MAX));
+        let before = before.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string());
+
+        // Only content no remaining snapshot shares is freed
+        let (rows, bytes): (i64, i64) = self.conn.query_row(
+            &format!(
+                "SELECT (SELECT COUNT(*) FROM ({0})),
+                        (SELECT COALESCE(SUM(LENGTH(CAST(content AS BLOB))), 0) FROM blobs
+                         WHERE hash IN (SELECT hash FROM snapshots WHERE id IN ({0}))
+                           AND hash NOT IN (SELECT hash FROM snapshots WHERE id NOT IN ({0})))",
+                CANDIDATES
+            ),
+            params![keep, before],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
 
-        // Create a config file
-        let conf = files_tmp.path().join("tmux.conf");
-        fs::write(&conf, "set -g mouse on\n").unwrap();
+        if !dry_run && rows > 0 {
+            self.conn.execute(
+                &format!("DELETE FROM snapshots WHERE id IN (SELECT id FROM ({}))", CANDIDATES),
+                params![keep, before],
+            )?;
+            self.delete_orphaned_blobs()?;
+            self.conn.execute_batch("VACUUM")?;
+        }
 
-        let path_str = util::paths::contract_tilde(&conf);
+        Ok(PruneResult {
+            rows: rows as usize,
+            bytes: bytes as u64,
+        })
+    }
 
-        // Take a snapshot
-        let count = engine
-            .snapshot_tool("tmux", &[path_str.clone()], Some("initial"))
-            .unwrap();
-        assert_eq!(count, 1);
+    /// Compress the content of snapshots stored before compression existed,
+    /// where that saves space. Encrypted snapshots are left alone. Unless
+    /// `dry_run` is set, the database is vacuumed afterward to reclaim the
+    /// space. Returns the blobs rewritten and the bytes saved.
+    pub fn recompress(&self, dry_run: bool) -> Result<PruneResult> {
+        let mut stmt = self.conn.prepare(
+            "SELECT hash, content, is_binary FROM blobs
+             WHERE compressed = 0 AND encrypted = 0",
+        )?;
+        let rows: Vec<(String, StoredContent, bool)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut result = PruneResult::default();
+        let tx = self.conn.unchecked_transaction()?;
+        for (hash, content, is_binary) in rows {
+            let stored_len = content.0.len() as u64;
+            let content = SnapshotContent::from_column(content, is_binary, false)?;
+            let (Value::Blob(frame), _) = content.to_stored() else {
+                continue;
+            };
+            result.rows += 1;
+            result.bytes += stored_len - frame.len() as u64;
+            if !dry_run {
+                tx.execute(
+                    "UPDATE blobs SET content = ?2, compressed = 1 WHERE hash = ?1",
+                    params![hash, frame],
+                )?;
+            }
+        }
+        tx.commit()?;
 
-        // History should have one entry
-        let history = engine.history("tmux", 10).unwrap();
-        assert_eq!(history.len(), 1);
-        assert_eq!(history[0].tool, "tmux");
-        assert_eq!(history[0].message, Some("initial".to_string()));
+        if !dry_run && result.rows > 0 {
+            self.conn.execute_batch("VACUUM")?;
+        }
+        Ok(result)
     }
 
-    #[test]
-    fn test_snapshot_dedup() {
+    /// Measure the space snapshot content takes (physical), what it would
+    /// take stored once per row and uncompressed (logical), and so what
+    /// sharing and compression save.
+    pub fn storage_stats(&self) -> Result<StorageStats> {
+        // The frame header alone gives a compressed blob's original size
+        let mut stmt = self.conn.prepare(
+            "SELECT LENGTH(CAST(content AS BLOB)), is_binary, compressed,
+                    CASE WHEN compressed = 1 THEN substr(content, 1, 18) END,
+                    (SELECT COUNT(*) FROM snapshots WHERE snapshots.hash = blobs.hash)
+             FROM blobs",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)? as u64,
+                row.get::<_, bool>(1)?,
+                row.get::<_, bool>(2)?,
+                row.get::<_, Option<Vec<u8>>>(3)?,
+                row.get::<_, i64>(4)? as u64,
+            ))
+        })?;
+
+        let mut stats = StorageStats::default();
+        for row in rows {
+            let (stored, is_binary, compressed, header, refs) = row?;
+            stats.blobs += 1;
+            stats.rows += refs as usize;
+            stats.stored_bytes += stored;
+            if !compressed {
+                stats.logical_bytes += stored * refs;
+                continue;
+            }
+            stats.compressed_rows += refs as usize;
+            let size = header
+                .and_then(|h| zstd::zstd_safe::get_frame_content_size(&h).ok().flatten())
+                .context("compressed snapshot has a damaged header")?;
+            // Binary content would be stored as base64
+            stats.logical_bytes += if is_binary { size.div_ceil(3) * 4 } else { size } * refs;
+        }
+        Ok(stats)
+    }
+
+    /// Tools that have unpinned snapshots but aren't in `tracked`, with the
+    /// number of unpinned rows.
+    pub fn orphaned_tools(&self, tracked: &[&str]) -> Result<Vec<(String, usize)>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT tool, COUNT(*) FROM snapshots WHERE pinned = 0 GROUP BY tool ORDER BY tool",
+            )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+
+        let mut orphans = Vec::new();
+        for row in rows {
+            let (tool, count) = row?;
+            if !tracked.contains(&tool.as_str()) {
+                orphans.push((tool, count as usize));
+            }
+        }
+        Ok(orphans)
+    }
+
+    /// Delete every unpinned snapshot of the given tools and vacuum the
+    /// database. Returns the number of rows removed.
+    pub fn delete_tools(&self, tools: &[&str]) -> Result<usize> {
+        let mut removed = 0;
+        for tool in tools {
+            removed += self
+                .conn
+                .execute(
+                    "DELETE FROM snapshots WHERE tool = ?1 AND pinned = 0",
+                    params![tool],
+                )?;
+        }
+        if removed > 0 {
+            self.delete_orphaned_blobs()?;
+            self.conn.execute(
+                "DELETE FROM tags WHERE snapshot_id NOT IN (SELECT id FROM snapshots)",
+                [],
+            )?;
+            self.conn.execute_batch("VACUUM")?;
+        }
+        Ok(removed)
+    }
+
+    /// Group files by the content of their latest snapshot and return the
+    /// groups with more than one `(tool, file_path)` — the same file tracked
+    /// under several tools, or copies stored at different paths. Empty files
+    /// are left out since they all match.
+    pub fn shared_content(&self) -> Result<Vec<SharedContent>> {
+        let mut stmt = self.conn.prepare(
+            "WITH latest AS (
+                SELECT tool, file_path, hash FROM snapshots
+                WHERE id IN (SELECT MAX(id) FROM snapshots GROUP BY tool, file_path)
+                  AND hash != ?1
+            )
+            SELECT hash, tool, file_path FROM latest
+            WHERE hash IN (SELECT hash FROM latest GROUP BY hash HAVING COUNT(*) > 1)
+            ORDER BY hash, tool, file_path",
+        )?;
+        let rows = stmt.query_map(params![compute_hash(b"")], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        let mut groups: Vec<SharedContent> = Vec::new();
+        for row in rows {
+            let (hash, tool, file_path) = row?;
+            match groups.last_mut() {
+                Some(group) if group.hash == hash => group.files.push((tool, file_path)),
+                _ => groups.push(SharedContent {
+                    hash,
+                    files: vec![(tool, file_path)],
+                }),
+            }
+        }
+        Ok(groups)
+    }
+
+    /// Log that dotsmith itself wrote `paths` (edit, deploy, rollback, ...), so
+    /// doctor can tell those changes apart from ones made behind its back.
+    /// A directory path covers every file below it.
+    pub fn record_operation(&self, kind: &str, paths: &[String]) -> Result<()> {
+        for path in paths {
+            self.conn.execute(
+                "INSERT INTO operations (file_path, kind) VALUES (?1, ?2)",
+                params![path, kind],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// When dotsmith last wrote `file_path` (or a directory containing it),
+    /// and what kind of operation it was.
+    pub fn last_operation(&self, file_path: &str) -> Result<Option<(String, DateTime<Utc>)>> {
+        let row: Option<(String, String)> = self
+            .conn
+            .query_row(
+                "SELECT kind, created_at FROM operations
+                 WHERE file_path = ?1
+                    OR substr(?1, 1, length(file_path) + 1) = file_path || '/'
+                 ORDER BY created_at DESC, id DESC LIMIT 1",
+                params![file_path],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok();
+
+        let Some((kind, created_at)) = row else {
+            return Ok(None);
+        };
+        let at = chrono::NaiveDateTime::parse_from_str(&created_at, "%Y-%m-%d %H:%M:%S")
+            .with_context(|| format!("invalid operation timestamp '{}'", created_at))?
+            .and_utc();
+        Ok(Some((kind, at)))
+    }
+
+    /// Pin or unpin a snapshot. Returns false if no snapshot has this ID.
+    pub fn set_pinned(&self, snapshot_id: i64, pinned: bool) -> Result<bool> {
+        let updated = self.conn.execute(
+            "UPDATE snapshots SET pinned = ?2 WHERE id = ?1",
+            params![snapshot_id, pinned],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Set the message of an existing snapshot, or with `append` add to the
+    /// one it has, separated by `; `. An empty message clears it. Only the
+    /// message changes, never the content or hash. Returns false if no
+    /// snapshot has this ID.
+    pub fn annotate(&self, snapshot_id: i64, message: &str, append: bool) -> Result<bool> {
+        let message = message.trim();
+        let sql = if append {
+            "UPDATE snapshots
+             SET message = CASE
+                 WHEN ?2 = '' THEN message
+                 WHEN message IS NULL OR message = '' THEN ?2
+                 ELSE message || '; ' || ?2
+             END
+             WHERE id = ?1"
+        } else {
+            "UPDATE snapshots SET message = NULLIF(?2, '') WHERE id = ?1"
+        };
+        let updated = self.conn.execute(sql, params![snapshot_id, message])?;
+        Ok(updated > 0)
+    }
+
+    /// Label a snapshot with `tag`. A tag marks one snapshot per file, so if
+    /// another snapshot of the same tool and file has it, the tag moves here.
+    /// Returns false if no snapshot has this ID.
+    pub fn tag_snapshot(&self, snapshot_id: i64, tag: &str) -> Result<bool> {
+        validate_tag(tag)?;
+        let tx = self.conn.unchecked_transaction()?;
+        let target: Option<(String, String)> = tx
+            .query_row(
+                "SELECT tool, file_path FROM snapshots WHERE id = ?1",
+                params![snapshot_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((tool, file_path)) = target else {
+            return Ok(false);
+        };
+        tx.execute(
+            "DELETE FROM tags WHERE tag = ?1 AND snapshot_id IN
+                 (SELECT id FROM snapshots WHERE tool = ?2 AND file_path = ?3)",
+            params![tag, tool, file_path],
+        )?;
+        tx.execute(
+            "INSERT INTO tags (snapshot_id, tag) VALUES (?1, ?2)",
+            params![snapshot_id, tag],
+        )?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Tag the snapshot matching each file's current content, for every file
+    /// under `config_paths`. Files without such a snapshot are skipped, so
+    /// snapshot first. Returns the number of files tagged.
+    pub fn tag_current(&self, tool: &str, config_paths: &[String], tag: &str) -> Result<usize> {
+        validate_tag(tag)?;
+        let mut tagged = 0;
+        for path in config_files(config_paths)? {
+            let content = fs::read(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let path_str = util::paths::contract_tilde(&path);
+            if let Some(id) = self.find_snapshot(tool, &path_str, &content)?
+                && self.tag_snapshot(id, tag)?
+            {
+                tagged += 1;
+            }
+        }
+        Ok(tagged)
+    }
+
+    /// The snapshots carrying `tag`, one per file, optionally only for one
+    /// tool. Sorted by tool and file.
+    pub fn snapshot_by_tag(&self, tool: Option<&str>, tag: &str) -> Result<Vec<SnapshotSummary>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}
+             FROM snapshots
+             WHERE id IN (SELECT snapshot_id FROM tags WHERE tag = ?1)
+               AND (?2 IS NULL OR tool = ?2)
+             ORDER BY tool, file_path",
+            SUMMARY_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![tag, tool], summary_from_row)?;
+
+        let mut summaries = Vec::new();
+        for row in rows {
+            summaries.push(row?);
+        }
+        Ok(summaries)
+    }
+
+    /// Find the snapshot of `file_path` for a tool that holds exactly `content`.
+    pub fn find_snapshot(
+        &self,
+        tool: &str,
+        file_path: &str,
+        content: &[u8],
+    ) -> Result<Option<i64>> {
+        self.find_snapshot_by_hash(tool, file_path, &compute_hash(content))
+    }
+
+    fn find_snapshot_by_hash(
+        &self,
+        tool: &str,
+        file_path: &str,
+        hash: &str,
+    ) -> Result<Option<i64>> {
+        let id = self
+            .conn
+            .query_row(
+                "SELECT id FROM snapshots WHERE tool = ?1 AND file_path = ?2 AND hash = ?3",
+                params![tool, file_path, hash],
+                |row| row.get(0),
+            )
+            .ok();
+        Ok(id)
+    }
+
+    /// Get snapshot content by ID (for rollback).
+    pub fn get_snapshot(&self, snapshot_id: i64) -> Result<Option<(String, SnapshotContent)>> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT file_path, content, is_binary, encrypted, compressed
+                 FROM snapshots JOIN blobs USING (hash)
+                 WHERE id = ?1",
+                params![snapshot_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, StoredContent>(1)?,
+                        row.get::<_, bool>(2)?,
+                        row.get::<_, bool>(3)?,
+                        row.get::<_, bool>(4)?,
+                    ))
+                },
+            )
+            .ok();
+
+        match row {
+            Some((path, content, is_binary, encrypted, compressed)) => {
+                Ok(Some((path, self.decode(content, is_binary, encrypted, compressed)?)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Rollback a file to a specific snapshot.
+    /// Creates a backup of the current file first.
+    pub fn rollback(&self, snapshot_id: i64, backup_dir: &Path) -> Result<String> {
+        let (path_str, content) = self
+            .get_snapshot(snapshot_id)?
+            .ok_or_else(|| anyhow::anyhow!("snapshot {} not found", snapshot_id))?;
+
+        if path_str == STDIN_PATH {
+            anyhow::bail!(
+                "snapshot {} was read from stdin and has no file to restore",
+                snapshot_id
+            );
+        }
+        let path = util::paths::expand_tilde_checked(&path_str)?;
+        backup_current(&path, backup_dir)?;
+
+        // Write the snapshot content back
+        util::fs::atomic_write_bytes(&path, content.as_bytes())?;
+        // Best effort: the file is already restored, and the log only keeps
+        // doctor from reporting the write as an outside edit
+        let _ = self.record_operation("rollback", std::slice::from_ref(&path_str));
+
+        Ok(path_str)
+    }
+
+    /// What [`SnapshotEngine::rollback`] would do to the file, without
+    /// touching it: the current content on the old side, the snapshot on the
+    /// new one. A missing file diffs as empty. `None` if nothing would change.
+    pub fn rollback_preview(&self, snapshot_id: i64) -> Result<Option<FileDiff>> {
+        let (file_path, content) = self
+            .get_snapshot(snapshot_id)?
+            .ok_or_else(|| anyhow::anyhow!("snapshot #{} not found", snapshot_id))?;
+        if file_path == STDIN_PATH {
+            anyhow::bail!(
+                "snapshot #{} was read from stdin and has no file to restore",
+                snapshot_id
+            );
+        }
+
+        let path = util::paths::expand_tilde(&file_path);
+        let current = if path.exists() {
+            SnapshotContent::from_bytes(
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?,
+            )
+        } else {
+            SnapshotContent::Text(String::new())
+        };
+        if current.as_bytes() == content.as_bytes() {
+            return Ok(None);
+        }
+
+        let diff = match (current, content) {
+            (SnapshotContent::Text(old_content), SnapshotContent::Text(new_content)) => FileDiff {
+                file_path,
+                old_content,
+                new_content,
+                binary: false,
+            },
+            _ => FileDiff {
+                file_path,
+                old_content: String::new(),
+                new_content: String::new(),
+                binary: true,
+            },
+        };
+        Ok(Some(diff))
+    }
+
+    /// Roll back only some hunks of a file: the hunks in `chosen` (indices
+    /// into [`FileDiff::hunks`]) are restored from the old side of `diff`
+    /// and the rest of the current file is kept. The current file is backed
+    /// up like [`SnapshotEngine::rollback`]. Returns the content written.
+    pub fn rollback_hunks(
+        &self,
+        diff: &FileDiff,
+        chosen: &BTreeSet<usize>,
+        backup_dir: &Path,
+    ) -> Result<String> {
+        if diff.binary {
+            anyhow::bail!("{} is binary; roll back the whole file instead", diff.file_path);
+        }
+        let path = util::paths::expand_tilde_checked(&diff.file_path)?;
+        let current = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if current != diff.new_content {
+            anyhow::bail!("{} changed since the diff was taken", diff.file_path);
+        }
+
+        let restored = diff.restore_hunks(chosen);
+        backup_current(&path, backup_dir)?;
+        util::fs::atomic_write_bytes(&path, restored.as_bytes())?;
+        // Best effort, as in `rollback`
+        let _ = self.record_operation("rollback", std::slice::from_ref(&diff.file_path));
+
+        Ok(restored)
+    }
+
+    /// The newest snapshot of each of `tool`'s files taken at or before
+    /// `as_of`, or `None` for files first snapshotted after it. Only files
+    /// `config_paths` still covers are listed: those [`config_files`] finds,
+    /// and tracked single files that have since been deleted.
+    pub fn snapshots_as_of(
+        &self,
+        tool: &str,
+        config_paths: &[String],
+        as_of: DateTime<Utc>,
+    ) -> Result<Vec<(String, Option<i64>)>> {
+        let mut tracked: HashSet<String> = config_files(config_paths)?
+            .iter()
+            .map(|path| util::paths::contract_tilde(path))
+            .collect();
+        tracked.extend(
+            config_paths
+                .iter()
+                .filter(|p| !util::paths::is_glob(p))
+                .map(|p| util::paths::contract_tilde(&util::paths::expand_tilde(p))),
+        );
+
+        let mut stmt = self.conn.prepare(
+            "SELECT file_path,
+                    (SELECT id FROM snapshots AS s
+                     WHERE s.tool = ?1 AND s.file_path = f.file_path AND s.created_at <= ?2
+                     ORDER BY s.created_at DESC, s.id DESC LIMIT 1)
+             FROM (SELECT DISTINCT file_path FROM snapshots WHERE tool = ?1) AS f
+             ORDER BY file_path",
+        )?;
+        let as_of = as_of.format("%Y-%m-%d %H:%M:%S").to_string();
+        let rows = stmt.query_map(params![tool, as_of], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut files = Vec::new();
+        for row in rows {
+            let (file_path, id): (String, Option<i64>) = row?;
+            if tracked.contains(&file_path) {
+                files.push((file_path, id));
+            }
+        }
+        Ok(files)
+    }
+
+    /// Restore every file `config_paths` covers to its newest snapshot of
+    /// `tool` at or before `as_of`, backing up current contents like
+    /// [`SnapshotEngine::rollback`]. Files already in that state are left
+    /// alone, and files with no snapshot that old are skipped.
+    pub fn rollback_tool(
+        &self,
+        tool: &str,
+        config_paths: &[String],
+        as_of: DateTime<Utc>,
+        backup_dir: &Path,
+    ) -> Result<ToolRollback> {
+        let mut result = ToolRollback::default();
+        for (file_path, id) in self.snapshots_as_of(tool, config_paths, as_of)? {
+            let Some(id) = id.filter(|_| file_path != STDIN_PATH) else {
+                result.skipped.push(file_path);
+                continue;
+            };
+            let current = fs::read(util::paths::expand_tilde(&file_path)).ok();
+            if let Some(current) = current
+                && self.find_snapshot(tool, &file_path, &current)? == Some(id)
+            {
+                result.unchanged.push(file_path);
+                continue;
+            }
+            self.rollback(id, backup_dir)?;
+            result.restored.push((file_path, id));
+        }
+        Ok(result)
+    }
+}
+
+/// Copy `path` to `<name>.<timestamp>.bak` in `backup_dir` before it is
+/// overwritten. Two backups of the same name within a millisecond get a
+/// counter, so neither overwrites the other. Missing files have nothing to
+/// back up.
+pub fn backup_current(path: &Path, backup_dir: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(backup_dir)
+        .with_context(|| format!("failed to create {}", backup_dir.display()))?;
+    let stem = format!(
+        "{}.{}",
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("file"),
+        chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f")
+    );
+    let mut backup_path = backup_dir.join(format!("{}.bak", stem));
+    let mut n = 1;
+    while backup_path.exists() {
+        backup_path = backup_dir.join(format!("{}.{}.bak", stem, n));
+        n += 1;
+    }
+    fs::copy(path, &backup_path)
+        .with_context(|| format!("failed to backup {}", path.display()))?;
+    Ok(())
+}
+
+/// Columns of the `snapshots` table. Content is stored once per hash, in
+/// `blobs`.
+const SNAPSHOTS_COLUMNS: &str = "
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    tool        TEXT NOT NULL,
+    file_path   TEXT NOT NULL,
+    hash        TEXT NOT NULL,
+    message     TEXT,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+    pinned      INTEGER NOT NULL DEFAULT 0,
+    UNIQUE(tool, file_path, hash)";
+
+/// Columns read by [`summary_from_row`], tags joined with commas.
+const SUMMARY_COLUMNS: &str = "id, tool, file_path, hash, message, created_at, pinned,
+     (SELECT group_concat(tag, ',') FROM tags WHERE snapshot_id = snapshots.id)";
+
+fn summary_from_row(row: &rusqlite::Row) -> rusqlite::Result<SnapshotSummary> {
+    let mut tags: Vec<String> = row
+        .get::<_, Option<String>>(7)?
+        .map(|t| t.split(',').map(str::to_string).collect())
+        .unwrap_or_default();
+    tags.sort();
+    Ok(SnapshotSummary {
+        id: row.get(0)?,
+        tool: row.get(1)?,
+        file_path: row.get(2)?,
+        hash: row.get(3)?,
+        message: row.get(4)?,
+        created_at: row.get(5)?,
+        pinned: row.get(6)?,
+        tags,
+    })
+}
+
+/// Fill in a snapshot message template (`general.snapshot_message`).
+/// `{tool}`, `{time}` (local, to the minute), `{source}` and `{host}` are
+/// replaced; any other `{...}` is kept as written.
+pub fn format_message(template: &str, ctx: &MessageContext) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        match &rest[1..end] {
+            "tool" => out.push_str(&ctx.tool),
+            "time" => out.push_str(&ctx.time.format("%Y-%m-%d %H:%M").to_string()),
+            "source" => out.push_str(&ctx.source),
+            "host" => out.push_str(&ctx.host),
+            _ => out.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Check a tag name: letters, digits, dots, hyphens, and underscores.
+pub fn validate_tag(tag: &str) -> Result<()> {
+    if tag.is_empty()
+        || !tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(DotsmithError::InvalidTagName(tag.to_string()).into());
+    }
+    Ok(())
+}
+
+/// List the files a tool's config paths cover, expanding tracked directories
+/// and globs the same way snapshots do. Missing paths are skipped.
+pub fn config_files(config_paths: &[String]) -> Result<Vec<std::path::PathBuf>> {
+    let mut files = Vec::new();
+    for path in config_paths.iter().flat_map(|p| util::paths::expand_config_path(p)) {
+        if path.is_dir() {
+            files.extend(walk_config_dir(&path, true)?);
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// One snapshot as a mail-formatted patch, the way `git format-patch`
+/// writes it. `previous` is `None` for the snapshot that adds the file.
+fn format_patch(
+    entry: &SnapshotSummary,
+    subject: &str,
+    number: usize,
+    total: usize,
+    previous: Option<&str>,
+    content: &str,
+) -> String {
+    let date = chrono::NaiveDateTime::parse_from_str(&entry.created_at, "%Y-%m-%d %H:%M:%S")
+        .map(|at| at.and_utc())
+        .unwrap_or_else(|_| Utc::now());
+    let path = entry
+        .file_path
+        .strip_prefix("~/")
+        .unwrap_or_else(|| entry.file_path.trim_start_matches('/'));
+
+    let mut patch = format!(
+        "From {} Mon Sep 17 00:00:00 2001\n\
+         From: dotsmith <dotsmith@localhost>\n\
+         Date: {}\n\
+         Subject: [PATCH {}/{}] {}\n\n---\n\
+         diff --git a/{path} b/{path}\n",
+        &entry.hash[..40.min(entry.hash.len())],
+        date.to_rfc2822(),
+        number,
+        total,
+        subject.lines().next().unwrap_or_default(),
+        path = path,
+    );
+    if previous.is_none() {
+        patch.push_str(&format!("new file mode 100644\n--- /dev/null\n+++ b/{}\n", path));
+    } else {
+        patch.push_str(&format!("--- a/{}\n+++ b/{}\n", path, path));
+    }
+    let diff = similar::TextDiff::from_lines(previous.unwrap_or_default(), content);
+    patch.push_str(&diff.unified_diff().context_radius(3).to_string());
+    patch.push_str("-- \ndotsmith\n\n");
+    patch
+}
+
+/// `0001-tmux-mouse-support.patch`: the patch number and a slug of its
+/// subject, like `git format-patch` names them.
+fn patch_file_name(number: usize, subject: &str) -> String {
+    let slug: String = subject
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let slug: Vec<&str> = slug.split('-').filter(|s| !s.is_empty()).collect();
+    let mut slug = slug.join("-");
+    slug.truncate(52);
+    format!("{:04}-{}.patch", number, slug.trim_end_matches('-'))
+}
+
+/// Recursively list the files under a tracked directory in a stable order.
+/// Directories rejected by `detect::is_skipped_dir` are ignored and symlinked
+/// directories are not followed, so plugin checkouts and link loops never end
+/// up in snapshots.
+fn walk_config_dir(dir: &Path, top_level: bool) -> Result<Vec<std::path::PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+
+    let mut entries: Vec<fs::DirEntry> = fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory {}", dir.display()))?
+        .collect::<std::io::Result<_>>()?;
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if detect::is_skipped_dir(&entry.file_name().to_string_lossy(), top_level) {
+                continue;
+            }
+            files.extend(walk_config_dir(&path, false)?);
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Compute SHA-256 hash of content.
+fn compute_hash(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, SnapshotEngine, TempDir) {
+        let config_tmp = TempDir::new().unwrap();
+        let engine = SnapshotEngine::open(config_tmp.path()).unwrap();
+        let files_tmp = TempDir::new().unwrap();
+        (config_tmp, engine, files_tmp)
+    }
+
+    #[test]
+    fn test_snapshot_and_history() {
+        let (_config_tmp, engine, files_tmp) = setup();
+
+        // Create a config file
+        let conf = files_tmp.path().join("tmux.conf");
+        fs::write(&conf, "set -g mouse on\n").unwrap();
+
+        let path_str = util::paths::contract_tilde(&conf);
+
+        // Take a snapshot
+        let count = engine
+            .snapshot_tool("tmux", &[path_str.clone()], Some("initial"))
+            .unwrap();
+        assert_eq!(count, 1);
+
+        // History should have one entry
+        let history = engine.history("tmux", 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].tool, "tmux");
+        assert_eq!(history[0].message, Some("initial".to_string()));
+    }
+
+    #[test]
+    fn test_snapshot_glob_picks_up_new_files() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        let lua = files_tmp.path().join("lua");
+        fs::create_dir_all(lua.join("plugins")).unwrap();
+        fs::write(lua.join("options.lua"), "vim.o.number = true\n").unwrap();
+        fs::write(lua.join("notes.txt"), "x\n").unwrap();
+        let paths = [format!("{}/**/*.lua", lua.display())];
+
+        assert_eq!(engine.snapshot_tool("nvim", &paths, None).unwrap(), 1);
+
+        // A module added later is tracked without touching the manifest
+        fs::write(lua.join("plugins/lsp.lua"), "return {}\n").unwrap();
+        assert_eq!(engine.snapshot_tool("nvim", &paths, None).unwrap(), 1);
+        fs::write(lua.join("plugins/lsp.lua"), "return { 'x' }\n").unwrap();
+        let diffs = engine.diff_current("nvim", &paths).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert!(diffs[0].file_path.ends_with("plugins/lsp.lua"));
+    }
+
+    #[test]
+    fn test_export_patches_replay_with_git_am() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        let conf = files_tmp.path().join("tmux.conf");
+        let paths = [util::paths::contract_tilde(&conf)];
+        let versions = [
+            ("set -g mouse on\n", "initial"),
+            ("set -g mouse on\nset -g base-index 1\n", "Start windows at 1"),
+            ("set -g mouse off\nset -g base-index 1", "Turn the mouse off"),
+        ];
+        for (content, message) in versions {
+            fs::write(&conf, content).unwrap();
+            engine.snapshot_tool("tmux", &paths, Some(message)).unwrap();
+        }
+
+        let out = files_tmp.path().join("patches");
+        let patches = engine.export_patches("tmux", &out).unwrap();
+        assert_eq!(patches.len(), 3);
+        let names: Vec<String> = patches
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "0001-initial.patch",
+                "0002-start-windows-at-1.patch",
+                "0003-turn-the-mouse-off.patch",
+            ]
+        );
+
+        let first = fs::read_to_string(&patches[0]).unwrap();
+        assert!(first.contains("\nSubject: [PATCH 1/3] initial\n"));
+        assert!(first.contains("\nnew file mode 100644\n--- /dev/null\n"));
+        let middle = fs::read_to_string(&patches[1]).unwrap();
+        assert!(middle.contains("\nSubject: [PATCH 2/3] Start windows at 1\n"));
+        assert!(middle.contains("\n set -g mouse on\n+set -g base-index 1\n"));
+        assert!(!middle.contains("new file mode"));
+        let last = fs::read_to_string(&patches[2]).unwrap();
+        assert!(last.contains("\nSubject: [PATCH 3/3] Turn the mouse off\n"));
+        assert!(last.contains("\n-set -g mouse on\n"));
+        assert!(last.contains("\n+set -g mouse off\n"));
+        assert!(last.contains("\n\\ No newline at end of file\n"));
+        assert!(last.contains("\nDate: "));
+
+        // Applying the series in order rebuilds the latest snapshot
+        let repo = files_tmp.path().join("repo");
+        crate::core::repo::init_repo(&repo).unwrap();
+        let status = std::process::Command::new("git")
+            .arg("am")
+            .args(&patches)
+            .current_dir(&repo)
+            .output()
+            .unwrap();
+        assert!(status.status.success(), "{}", String::from_utf8_lossy(&status.stderr));
+        let rel = paths[0].strip_prefix("~/").unwrap_or(paths[0].trim_start_matches('/'));
+        assert_eq!(
+            fs::read_to_string(repo.join(rel)).unwrap(),
+            "set -g mouse off\nset -g base-index 1"
+        );
+    }
+
+    #[test]
+    fn test_preview_snapshot() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        let conf = files_tmp.path().join("tmux.conf");
+        fs::write(&conf, "set -g mouse on\n").unwrap();
+        let path_str = util::paths::contract_tilde(&conf);
+        let paths = [path_str.clone()];
+
+        assert_eq!(engine.preview_snapshot("tmux", &paths).unwrap(), [(path_str.clone(), true)]);
+        engine.snapshot_tool("tmux", &paths, None).unwrap();
+        assert_eq!(engine.preview_snapshot("tmux", &paths).unwrap(), [(path_str.clone(), false)]);
+
+        fs::write(&conf, "set -g mouse off\n").unwrap();
+        assert_eq!(engine.preview_snapshot("tmux", &paths).unwrap(), [(path_str, true)]);
+
+        // Previewing stores nothing
+        assert_eq!(engine.history("tmux", 10).unwrap().len(), 1);
+        assert!(engine.preview_snapshot("tmux", &["/nonexistent".into()]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_history_all() {
+        let (_config_tmp, engine, files_tmp) = setup();
+
+        let tmux = files_tmp.path().join("tmux.conf");
+        let kitty = files_tmp.path().join("kitty.conf");
+        fs::write(&tmux, "set -g mouse on\n").unwrap();
+        fs::write(&kitty, "font_size 12\n").unwrap();
+
+        engine
+            .snapshot_tool("tmux", &[util::paths::contract_tilde(&tmux)], None)
+            .unwrap();
+        engine
+            .snapshot_tool("kitty", &[util::paths::contract_tilde(&kitty)], None)
+            .unwrap();
+
+        let all = engine.history_all(usize::MAX).unwrap();
+        assert_eq!(all.len(), 2);
+        // Newest first
+        assert_eq!(all[0].tool, "kitty");
+        assert_eq!(all[1].tool, "tmux");
+
+        assert_eq!(engine.history_all(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_dedup() {
         let (_config_tmp, engine, files_tmp) = setup();
 
         let conf = files_tmp.path().join("tmux.conf");
@@ -477,6 +2066,902 @@ mod tests {
         assert!(backup_dir.exists());
         let backups: Vec<_> = fs::read_dir(&backup_dir).unwrap().collect();
         assert_eq!(backups.len(), 1);
+
+        // The rollback is logged as a known write
+        let path_str = util::paths::contract_tilde(&conf);
+        let (kind, _) = engine.last_operation(&path_str).unwrap().unwrap();
+        assert_eq!(kind, "rollback");
+    }
+
+    #[test]
+    fn test_backup_current_keeps_every_backup() {
+        let tmp = TempDir::new().unwrap();
+        let conf = tmp.path().join("tmux.conf");
+        let backup_dir = tmp.path().join("backups");
+        for content in ["one", "two", "three"] {
+            fs::write(&conf, content).unwrap();
+            backup_current(&conf, &backup_dir).unwrap();
+        }
+
+        let mut backups: Vec<String> = fs::read_dir(&backup_dir)
+            .unwrap()
+            .map(|e| fs::read_to_string(e.unwrap().path()).unwrap())
+            .collect();
+        backups.sort();
+        assert_eq!(backups, ["one", "three", "two"]);
+    }
+
+    #[test]
+    fn test_rollback_preview() {
+        let (_config_tmp, engine, files_tmp) = setup();
+
+        let conf = files_tmp.path().join("tmux.conf");
+        fs::write(&conf, "set -g mouse on\nset -g base-index 1\n").unwrap();
+        let path_str = util::paths::contract_tilde(&conf);
+        engine.snapshot_tool("tmux", std::slice::from_ref(&path_str), None).unwrap();
+
+        // Nothing to roll back yet
+        assert!(engine.rollback_preview(1).unwrap().is_none());
+
+        fs::write(&conf, "set -g mouse off\nset -g base-index 1\nset -g history-limit 5000\n")
+            .unwrap();
+        let diff = engine.rollback_preview(1).unwrap().unwrap();
+        assert_eq!(diff.file_path, path_str);
+        assert!(!diff.binary);
+        // Rolling back removes the new lines and restores the old ones
+        assert_eq!(util::diff::line_counts(&diff.old_content, &diff.new_content), (1, 2));
+        assert_eq!(diff.new_content, "set -g mouse on\nset -g base-index 1\n");
+
+        // The file is left alone
+        assert_eq!(
+            fs::read_to_string(&conf).unwrap(),
+            "set -g mouse off\nset -g base-index 1\nset -g history-limit 5000\n"
+        );
+        assert!(engine.rollback_preview(99).is_err());
+    }
+
+    fn message_context() -> MessageContext {
+        MessageContext {
+            tool: "tmux".to_string(),
+            source: "watch".to_string(),
+            time: chrono::TimeZone::with_ymd_and_hms(&Local, 2026, 2, 1, 12, 30, 0).unwrap(),
+            host: "laptop".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_format_message_fills_placeholders() {
+        let ctx = message_context();
+        assert_eq!(format_message("{source} @ {time}", &ctx), "watch @ 2026-02-01 12:30");
+        assert_eq!(
+            format_message("{tool} on {host} by {source} at {time}", &ctx),
+            "tmux on laptop by watch at 2026-02-01 12:30"
+        );
+        assert_eq!(format_message("{tool}{tool}", &ctx), "tmuxtmux");
+        assert_eq!(format_message("plain", &ctx), "plain");
+    }
+
+    #[test]
+    fn test_format_message_keeps_unknown_placeholders() {
+        let ctx = message_context();
+        assert_eq!(format_message("{user}: {tool}", &ctx), "{user}: tmux");
+        assert_eq!(format_message("{} {tool", &ctx), "{} {tool");
+        assert_eq!(format_message("{{tool}}", &ctx), "{{tool}}");
+    }
+
+    fn text_diff(old: &str, new: &str) -> FileDiff {
+        FileDiff {
+            file_path: "tmux.conf".to_string(),
+            old_content: old.to_string(),
+            new_content: new.to_string(),
+            binary: false,
+        }
+    }
+
+    #[test]
+    fn test_hunks_are_runs_of_changes() {
+        let diff = text_diff("a\nb\nc\nd\ne\n", "a\nB\nc\nd\nE\nf\n");
+        assert_eq!(diff.hunks(), vec![(1..2, 1..2), (4..5, 4..6)]);
+
+        // Adjacent deletes and inserts are one hunk
+        let diff = text_diff("a\nb\n", "x\ny\nz\n");
+        assert_eq!(diff.hunks(), vec![(0..2, 0..3)]);
+
+        assert!(text_diff("same\n", "same\n").hunks().is_empty());
+    }
+
+    #[test]
+    fn test_restore_hunks() {
+        let diff = text_diff(
+            "one\ntwo\nthree\nfour\nfive\n",
+            "ONE\ntwo\nthree\nfive\nsix\n",
+        );
+        assert_eq!(diff.hunks().len(), 3);
+
+        let chosen = |ids: &[usize]| ids.iter().copied().collect::<BTreeSet<_>>();
+        assert_eq!(diff.restore_hunks(&chosen(&[])), diff.new_content);
+        assert_eq!(diff.restore_hunks(&chosen(&[0, 1, 2])), diff.old_content);
+        assert_eq!(
+            diff.restore_hunks(&chosen(&[0])),
+            "one\ntwo\nthree\nfive\nsix\n"
+        );
+        // Re-adding a deleted line and dropping an appended one
+        assert_eq!(
+            diff.restore_hunks(&chosen(&[1, 2])),
+            "ONE\ntwo\nthree\nfour\nfive\n"
+        );
+    }
+
+    #[test]
+    fn test_restore_hunks_without_trailing_newline() {
+        let diff = text_diff("a\nb", "a\nc\nb\nd");
+        let all: BTreeSet<usize> = (0..diff.hunks().len()).collect();
+        assert_eq!(diff.restore_hunks(&all), "a\nb");
+    }
+
+    #[test]
+    fn test_rollback_hunks() {
+        let (config_tmp, engine, files_tmp) = setup();
+        let conf = files_tmp.path().join("tmux.conf");
+        fs::write(&conf, "mouse on\nprefix C-a\n").unwrap();
+        let path_str = util::paths::contract_tilde(&conf);
+        engine.snapshot_tool("tmux", std::slice::from_ref(&path_str), None).unwrap();
+
+        fs::write(&conf, "mouse off\nprefix C-b\n").unwrap();
+        let diff = text_diff("mouse on\nprefix C-a\n", "mouse off\nprefix C-b\n");
+        let diff = FileDiff {
+            file_path: path_str,
+            ..diff
+        };
+        assert_eq!(diff.hunks().len(), 1);
+
+        let backup_dir = config_tmp.path().join("backups");
+        let chosen = BTreeSet::from([0]);
+        engine.rollback_hunks(&diff, &chosen, &backup_dir).unwrap();
+        assert_eq!(fs::read_to_string(&conf).unwrap(), "mouse on\nprefix C-a\n");
+        assert_eq!(fs::read_dir(&backup_dir).unwrap().count(), 1);
+
+        // The file no longer matches the diff, so a second apply is refused
+        assert!(engine.rollback_hunks(&diff, &chosen, &backup_dir).is_err());
+    }
+
+    #[test]
+    fn test_rollback_tool_to_point_in_time() {
+        let (config_tmp, engine, files_tmp) = setup();
+        let a = files_tmp.path().join("a.conf");
+        let b = files_tmp.path().join("b.conf");
+        let a_str = util::paths::contract_tilde(&a);
+        let b_str = util::paths::contract_tilde(&b);
+
+        // a v1 10:00, b v1 11:00, a v2 12:00, b v2 13:00
+        let steps = [
+            (&a, &a_str, "a1\n"),
+            (&b, &b_str, "b1\n"),
+            (&a, &a_str, "a2\n"),
+            (&b, &b_str, "b2\n"),
+        ];
+        for (hour, (path, path_str, content)) in (10..).zip(steps) {
+            fs::write(path, content).unwrap();
+            engine.snapshot_tool("app", std::slice::from_ref(path_str), None).unwrap();
+            engine
+                .conn
+                .execute(
+                    "UPDATE snapshots SET created_at = ?1 WHERE id = last_insert_rowid()",
+                    params![format!("2026-02-01 {}:00:00", hour)],
+                )
+                .unwrap();
+        }
+        fs::write(&a, "a3\n").unwrap();
+
+        let at = |time: &str| {
+            chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S")
+                .unwrap()
+                .and_utc()
+        };
+        let backup_dir = config_tmp.path().join("backups");
+        let paths = [a_str.clone(), b_str.clone()];
+
+        // Between the two rounds: both files back to v1
+        let result =
+            engine.rollback_tool("app", &paths, at("2026-02-01 11:30:00"), &backup_dir).unwrap();
+        assert_eq!(result.restored, [(a_str.clone(), 1), (b_str.clone(), 2)]);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a1\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "b1\n");
+
+        // Before b was ever snapshotted: b is skipped, a is already there
+        let result =
+            engine.rollback_tool("app", &paths, at("2026-02-01 10:30:00"), &backup_dir).unwrap();
+        assert_eq!(result.unchanged, std::slice::from_ref(&a_str));
+        assert_eq!(result.skipped, std::slice::from_ref(&b_str));
+        assert!(result.restored.is_empty());
+
+        // The cutoff is inclusive
+        let result =
+            engine.rollback_tool("app", &paths, at("2026-02-01 13:00:00"), &backup_dir).unwrap();
+        assert_eq!(result.restored, [(a_str.clone(), 3), (b_str.clone(), 4)]);
+        assert_eq!(fs::read_to_string(&b).unwrap(), "b2\n");
+
+        // A file the tool no longer tracks is left alone, even once deleted
+        fs::remove_file(&b).unwrap();
+        let only_a = std::slice::from_ref(&a_str);
+        let files = engine.snapshots_as_of("app", only_a, at("2026-02-01 13:00:00")).unwrap();
+        assert_eq!(files, [(a_str.clone(), Some(3))]);
+        let result =
+            engine.rollback_tool("app", only_a, at("2026-02-01 11:30:00"), &backup_dir).unwrap();
+        assert_eq!(result.restored, [(a_str.clone(), 1)]);
+        assert!(!b.exists());
+
+        // A tracked file that was deleted can still be brought back
+        let result =
+            engine.rollback_tool("app", &paths, at("2026-02-01 13:00:00"), &backup_dir).unwrap();
+        assert_eq!(result.restored, [(a_str, 3), (b_str, 4)]);
+    }
+
+    #[test]
+    fn test_operation_log_covers_directories() {
+        let (_config_tmp, engine, _files_tmp) = setup();
+
+        assert!(engine.last_operation("~/.config/nvim/init.lua").unwrap().is_none());
+
+        engine
+            .record_operation("deploy", &["~/.config/nvim".to_string()])
+            .unwrap();
+        let (kind, at) = engine.last_operation("~/.config/nvim/init.lua").unwrap().unwrap();
+        assert_eq!(kind, "deploy");
+        assert!(Utc::now().signed_duration_since(at).num_minutes() < 5);
+
+        // A sibling with a shared prefix is not covered
+        assert!(engine.last_operation("~/.config/nvim-old/init.lua").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_export_import_records() {
+        let (_config_tmp, engine, files_tmp) = setup();
+
+        let tmux = files_tmp.path().join("tmux.conf");
+        let kitty = files_tmp.path().join("kitty.conf");
+        fs::write(&tmux, "set -g mouse on\n").unwrap();
+        fs::write(&kitty, "font_size 12\n").unwrap();
+        let tmux_path = util::paths::contract_tilde(&tmux);
+        engine.snapshot_tool("tmux", std::slice::from_ref(&tmux_path), Some("v1")).unwrap();
+        fs::write(&tmux, "set -g mouse off\n").unwrap();
+        engine.snapshot_tool("tmux", &[tmux_path], Some("v2")).unwrap();
+        engine
+            .snapshot_tool("kitty", &[util::paths::contract_tilde(&kitty)], None)
+            .unwrap();
+
+        let records = engine.export_records(&["tmux"]).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].message, Some("v1".to_string()));
+
+        let other_tmp = TempDir::new().unwrap();
+        let other = SnapshotEngine::open(other_tmp.path()).unwrap();
+        assert_eq!(other.import_records(&records).unwrap(), 2);
+        // Merging again is a no-op
+        assert_eq!(other.import_records(&records).unwrap(), 0);
+
+        let history = other.history("tmux", 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].created_at, records[0].created_at);
+        assert!(other.history("kitty", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_directory_recursive() {
+        let (_config_tmp, engine, files_tmp) = setup();
+
+        let root = files_tmp.path().join("nvim");
+        fs::create_dir_all(root.join("lua/plugins")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("plugins/vendored")).unwrap();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::write(root.join("init.lua"), "require('plugins')\n").unwrap();
+        fs::write(root.join("lua/options.lua"), "vim.o.number = true\n").unwrap();
+        fs::write(root.join("lua/plugins/init.lua"), "return {}\n").unwrap();
+        fs::write(root.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(root.join("node_modules/pkg/index.js"), "\n").unwrap();
+        fs::write(root.join("plugins/vendored/init.lua"), "-- third party\n").unwrap();
+
+        let root_str = util::paths::contract_tilde(&root);
+        let count = engine.snapshot_tool("nvim", std::slice::from_ref(&root_str), None).unwrap();
+        assert_eq!(count, 3);
+
+        let mut paths: Vec<String> = engine
+            .history("nvim", 10)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.file_path)
+            .collect();
+        paths.sort();
+        let expected: Vec<String> = ["init.lua", "lua/options.lua", "lua/plugins/init.lua"]
+            .iter()
+            .map(|rel| util::paths::contract_tilde(&root.join(rel)))
+            .collect();
+        assert_eq!(paths, expected);
+
+        // Nested edits show up in the diff
+        assert!(engine.diff_current("nvim", std::slice::from_ref(&root_str)).unwrap().is_empty());
+        fs::write(root.join("lua/plugins/init.lua"), "return { 'x' }\n").unwrap();
+        let diffs = engine.diff_current("nvim", &[root_str]).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert!(diffs[0].file_path.ends_with("lua/plugins/init.lua"));
+    }
+
+    /// PNG signature plus bytes that are not valid UTF-8.
+    const PNG_BYTES: &[u8] = &[
+        0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0xff, 0xfe, 0x00,
+    ];
+
+    #[test]
+    fn test_binary_snapshot_and_rollback() {
+        let (config_tmp, engine, files_tmp) = setup();
+
+        let icon = files_tmp.path().join("icon.png");
+        fs::write(&icon, PNG_BYTES).unwrap();
+        let path_str = util::paths::contract_tilde(&icon);
+
+        let paths = std::slice::from_ref(&path_str);
+        assert_eq!(engine.snapshot_tool("kitty", paths, None).unwrap(), 1);
+        assert!(engine.diff_current("kitty", paths).unwrap().is_empty());
+
+        let (_, content) = engine.get_snapshot(1).unwrap().unwrap();
+        assert!(content.is_binary());
+        assert_eq!(content.as_bytes(), PNG_BYTES);
+
+        fs::write(&icon, [0xffu8, 0x00, 0x01]).unwrap();
+        let diffs = engine.diff_current("kitty", &[path_str]).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert!(diffs[0].binary);
+        assert!(diffs[0].old_content.is_empty());
+
+        engine.rollback(1, &config_tmp.path().join("backups")).unwrap();
+        assert_eq!(fs::read(&icon).unwrap(), PNG_BYTES);
+    }
+
+    #[test]
+    fn test_migrate_adds_is_binary_column() {
+        let config_tmp = TempDir::new().unwrap();
+        let db_path = config_tmp.path().join("snapshots.db");
+        {
+            // Schema as written by earlier versions
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE snapshots (
+                    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                    tool        TEXT NOT NULL,
+                    file_path   TEXT NOT NULL,
+                    content     TEXT NOT NULL,
+                    hash        TEXT NOT NULL,
+                    message     TEXT,
+                    created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+                    UNIQUE(tool, file_path, hash)
+                );
+                INSERT INTO snapshots (tool, file_path, content, hash)
+                VALUES ('tmux', '/tmp/tmux.conf', 'set -g mouse on', 'abc');",
+            )
+            .unwrap();
+        }
+
+        let engine = SnapshotEngine::open(config_tmp.path()).unwrap();
+        let (_, content) = engine.get_snapshot(1).unwrap().unwrap();
+        assert_eq!(content, SnapshotContent::Text("set -g mouse on".to_string()));
+
+        // Opening again must not try to add the column twice
+        drop(engine);
+        SnapshotEngine::open(config_tmp.path()).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_moves_content_to_blobs() {
+        let config_tmp = TempDir::new().unwrap();
+        let db_path = config_tmp.path().join("snapshots.db");
+        {
+            // Content stored in every row, as before blobs
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE snapshots (
+                    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                    tool        TEXT NOT NULL,
+                    file_path   TEXT NOT NULL,
+                    content     TEXT NOT NULL,
+                    hash        TEXT NOT NULL,
+                    message     TEXT,
+                    created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+                    is_binary   INTEGER NOT NULL DEFAULT 0,
+                    pinned      INTEGER NOT NULL DEFAULT 0,
+                    UNIQUE(tool, file_path, hash)
+                );
+                INSERT INTO snapshots (tool, file_path, content, hash, message, pinned)
+                VALUES ('tmux', '~/.tmux.conf', 'set -g mouse on', 'abc', 'first', 1),
+                       ('tmux', '~/.config/tmux/tmux.conf', 'set -g mouse on', 'abc', NULL, 0),
+                       ('kitty', '~/kitty.conf', 'font_size 12', 'def', NULL, 0),
+                       ('kitty', '~/gone.conf', 'gone', 'ghi', NULL, 0);
+                DELETE FROM snapshots WHERE id = 4;",
+            )
+            .unwrap();
+        }
+
+        let engine = SnapshotEngine::open(config_tmp.path()).unwrap();
+        assert_eq!(engine.storage_stats().unwrap().blobs, 2);
+        let (path, content) = engine.get_snapshot(2).unwrap().unwrap();
+        assert_eq!(path, "~/.config/tmux/tmux.conf");
+        assert_eq!(content, SnapshotContent::Text("set -g mouse on".to_string()));
+
+        // Row details survive, and deleted IDs aren't handed out again
+        let tmux = engine.history("tmux", 10).unwrap();
+        assert_eq!(tmux[1].message.as_deref(), Some("first"));
+        assert!(tmux[1].pinned);
+        let files_tmp = TempDir::new().unwrap();
+        let conf = files_tmp.path().join("kitty.conf");
+        fs::write(&conf, "font_size 13\n").unwrap();
+        let conf_str = util::paths::contract_tilde(&conf);
+        engine.snapshot_tool("kitty", std::slice::from_ref(&conf_str), None).unwrap();
+        assert_eq!(engine.history("kitty", 1).unwrap()[0].id, 5);
+
+        drop(engine);
+        SnapshotEngine::open(config_tmp.path()).unwrap();
+    }
+
+    #[test]
+    fn test_identical_content_shares_one_blob() {
+        let (config_tmp, engine, files_tmp) = setup();
+        let text: String = (0..50).map(|i| format!("set -g @option-{} on\n", i)).collect();
+        let first = files_tmp.path().join("tmux.conf");
+        let second = files_tmp.path().join("tmux.conf.bak");
+        fs::write(&first, &text).unwrap();
+        fs::write(&second, &text).unwrap();
+        engine.snapshot_tool("tmux", &[util::paths::contract_tilde(&first)], None).unwrap();
+        engine.snapshot_tool("backup", &[util::paths::contract_tilde(&second)], None).unwrap();
+
+        let stats = engine.storage_stats().unwrap();
+        assert_eq!((stats.rows, stats.blobs), (2, 1));
+        assert_eq!(stats.logical_bytes, 2 * text.len() as u64);
+        assert!(stats.stored_bytes < text.len() as u64);
+
+        // Removing one file's snapshots keeps the shared content
+        engine.delete_tools(&["tmux"]).unwrap();
+        assert_eq!(engine.storage_stats().unwrap().blobs, 1);
+        fs::write(&second, "changed\n").unwrap();
+        engine.rollback(2, &config_tmp.path().join("backups")).unwrap();
+        assert_eq!(fs::read_to_string(&second).unwrap(), text);
+
+        engine.delete_tools(&["backup"]).unwrap();
+        assert_eq!(engine.storage_stats().unwrap(), StorageStats::default());
+    }
+
+    #[test]
+    fn test_content_is_stored_compressed() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        let conf = files_tmp.path().join("tmux.conf");
+        let text: String =
+            (0..200).map(|i| format!("bind-key -n M-{} select-window\n", i)).collect();
+        fs::write(&conf, &text).unwrap();
+        let path_str = util::paths::contract_tilde(&conf);
+        engine.snapshot_tool("tmux", std::slice::from_ref(&path_str), None).unwrap();
+
+        let (column, compressed): (Vec<u8>, bool) = engine
+            .conn
+            .query_row("SELECT content, compressed FROM blobs", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert!(compressed);
+        assert!(column.len() < text.len() / 4);
+
+        // Every read path decompresses
+        let (_, content) = engine.get_snapshot(1).unwrap().unwrap();
+        assert_eq!(content, SnapshotContent::Text(text.clone()));
+        fs::write(&conf, "set -g mouse on\n").unwrap();
+        let diffs = engine.diff_current("tmux", &[path_str]).unwrap();
+        assert_eq!(diffs[0].old_content, text);
+        assert_eq!(engine.search_content(None, "M-42 ").unwrap().len(), 1);
+        assert_eq!(engine.export_records(&["tmux"]).unwrap()[0].content, text);
+
+        let stats = engine.storage_stats().unwrap();
+        assert_eq!((stats.rows, stats.compressed_rows), (1, 1));
+        assert_eq!(stats.logical_bytes, text.len() as u64);
+        assert_eq!(stats.saved_bytes(), (text.len() - column.len()) as u64);
+
+        // Too small to gain anything: stored as text
+        let tiny = files_tmp.path().join("tiny.conf");
+        fs::write(&tiny, "a\n").unwrap();
+        engine.snapshot_tool("tiny", &[util::paths::contract_tilde(&tiny)], None).unwrap();
+        assert_eq!(engine.storage_stats().unwrap().compressed_rows, 1);
+        let (_, content) = engine.get_snapshot(2).unwrap().unwrap();
+        assert_eq!(content, SnapshotContent::Text("a\n".to_string()));
+    }
+
+    #[test]
+    fn test_legacy_uncompressed_rows_read_and_recompress() {
+        let (_config_tmp, engine, _files_tmp) = setup();
+        let text: String = (0..100).map(|i| format!("alias g{}='git {}'\n", i % 10, i)).collect();
+        let binary = [&[0xFFu8, 0xFE][..], &[0u8; 300]].concat();
+        // Rows as written before compression existed
+        engine
+            .conn
+            .execute(
+                "INSERT INTO blobs (hash, content, is_binary) VALUES ('a', ?1, 0), ('b', ?2, 1)",
+                params![text, BASE64_STANDARD.encode(&binary)],
+            )
+            .unwrap();
+        engine
+            .conn
+            .execute_batch(
+                "INSERT INTO snapshots (tool, file_path, hash)
+                 VALUES ('zsh', '~/.zshrc', 'a'), ('zsh', '~/.zsh.bin', 'b')",
+            )
+            .unwrap();
+
+        let (_, content) = engine.get_snapshot(1).unwrap().unwrap();
+        assert_eq!(content, SnapshotContent::Text(text.clone()));
+        assert_eq!(engine.storage_stats().unwrap().compressed_rows, 0);
+
+        let preview = engine.recompress(true).unwrap();
+        assert_eq!(preview.rows, 2);
+        assert_eq!(engine.storage_stats().unwrap().compressed_rows, 0);
+
+        assert_eq!(engine.recompress(false).unwrap(), preview);
+        let stats = engine.storage_stats().unwrap();
+        assert_eq!(stats.compressed_rows, 2);
+        assert_eq!(stats.saved_bytes(), preview.bytes);
+        let (_, content) = engine.get_snapshot(1).unwrap().unwrap();
+        assert_eq!(content, SnapshotContent::Text(text));
+        let (_, content) = engine.get_snapshot(2).unwrap().unwrap();
+        assert_eq!(content, SnapshotContent::Binary(binary));
+
+        // Nothing left to do
+        assert_eq!(engine.recompress(false).unwrap().rows, 0);
+    }
+
+    fn snapshot_versions(engine: &SnapshotEngine, tool: &str, path: &Path, versions: usize) {
+        let path_str = util::paths::contract_tilde(path);
+        for v in 0..versions {
+            fs::write(path, format!("version {}\n", v)).unwrap();
+            engine.snapshot_tool(tool, std::slice::from_ref(&path_str), None).unwrap();
+        }
+    }
+
+    fn row_count(engine: &SnapshotEngine) -> i64 {
+        engine
+            .conn
+            .query_row("SELECT COUNT(*) FROM snapshots", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_prune_keep() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        snapshot_versions(&engine, "tmux", &files_tmp.path().join("tmux.conf"), 5);
+        snapshot_versions(&engine, "kitty", &files_tmp.path().join("kitty.conf"), 2);
+        assert_eq!(row_count(&engine), 7);
+
+        let preview = engine.prune(Some(2), None, true).unwrap();
+        assert_eq!(preview.rows, 3);
+        // kitty still has versions 0 and 1, so only version 2's content goes
+        assert_eq!(preview.bytes, "version 2\n".len() as u64);
+        assert_eq!(row_count(&engine), 7);
+
+        assert_eq!(engine.prune(Some(2), None, false).unwrap(), preview);
+        assert_eq!(row_count(&engine), 4);
+
+        // The newest versions survive
+        let tmux = engine.history("tmux", 10).unwrap();
+        assert_eq!(tmux.len(), 2);
+        let (_, content) = engine.get_snapshot(tmux[0].id).unwrap().unwrap();
+        assert_eq!(content, SnapshotContent::Text("version 4\n".to_string()));
+    }
+
+    #[test]
+    fn test_prune_older_than_keeps_latest() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        snapshot_versions(&engine, "tmux", &files_tmp.path().join("tmux.conf"), 3);
+        engine
+            .conn
+            .execute("UPDATE snapshots SET created_at = '2000-01-01 00:00:00'", [])
+            .unwrap();
+
+        let result = engine.prune(None, Some(Utc::now()), false).unwrap();
+        assert_eq!(result.rows, 2);
+        assert_eq!(row_count(&engine), 1);
+
+        // Nothing left to prune: the sole snapshot is never deleted
+        assert_eq!(engine.prune(Some(1), Some(Utc::now()), false).unwrap().rows, 0);
+        assert_eq!(row_count(&engine), 1);
+    }
+
+    #[test]
+    fn test_orphaned_tools_and_delete() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        snapshot_versions(&engine, "tmux", &files_tmp.path().join("tmux.conf"), 2);
+        snapshot_versions(&engine, "old", &files_tmp.path().join("old.conf"), 3);
+
+        let orphans = engine.orphaned_tools(&["tmux"]).unwrap();
+        assert_eq!(orphans, vec![("old".to_string(), 3)]);
+        assert!(engine.orphaned_tools(&["tmux", "old"]).unwrap().is_empty());
+
+        assert_eq!(engine.delete_tools(&["old"]).unwrap(), 3);
+        assert_eq!(row_count(&engine), 2);
+        assert!(engine.orphaned_tools(&["tmux"]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_shared_content() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        let shared = files_tmp.path().join("shared.conf");
+        let copy = files_tmp.path().join("copy.conf");
+        let other = files_tmp.path().join("other.conf");
+        let empty = files_tmp.path().join("empty.conf");
+        fs::write(&shared, "set -g mouse on\n").unwrap();
+        fs::write(&copy, "set -g mouse on\n").unwrap();
+        fs::write(&other, "font_size 12\n").unwrap();
+        fs::write(&empty, "").unwrap();
+
+        let shared_str = util::paths::contract_tilde(&shared);
+        let copy_str = util::paths::contract_tilde(&copy);
+        let empty_str = util::paths::contract_tilde(&empty);
+        let tracked = [shared_str.clone(), empty_str.clone()];
+        engine.snapshot_tool("tmux", &tracked, None).unwrap();
+        engine.snapshot_tool("zsh", &tracked, None).unwrap();
+        engine.snapshot_tool("kitty", &[util::paths::contract_tilde(&other)], None).unwrap();
+        engine.snapshot_tool("backup", std::slice::from_ref(&copy_str), None).unwrap();
+
+        let groups = engine.shared_content().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(
+            groups[0].files,
+            vec![
+                ("backup".to_string(), copy_str.clone()),
+                ("tmux".to_string(), shared_str.clone()),
+                ("zsh".to_string(), shared_str.clone()),
+            ]
+        );
+
+        // Only the latest content counts
+        fs::write(&copy, "set -g mouse off\n").unwrap();
+        engine.snapshot_tool("backup", &[copy_str], None).unwrap();
+        let groups = engine.shared_content().unwrap();
+        assert_eq!(groups[0].files.len(), 2);
+    }
+
+    #[test]
+    fn test_search_content() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        let conf = files_tmp.path().join("tmux.conf");
+        let paths = [util::paths::contract_tilde(&conf)];
+
+        fs::write(&conf, "set -g mouse on\n").unwrap();
+        engine.snapshot_tool("tmux", &paths, None).unwrap();
+        fs::write(&conf, "set -g mouse on\nset -g status-position top\n").unwrap();
+        engine.snapshot_tool("tmux", &paths, None).unwrap();
+        fs::write(&conf, "set -g mouse on\nset -g status-position bottom\n").unwrap();
+        engine.snapshot_tool("tmux", &paths, None).unwrap();
+
+        let kitty = files_tmp.path().join("kitty.conf");
+        fs::write(&kitty, "# status-position top is a tmux thing\n").unwrap();
+        engine.snapshot_tool("kitty", &[util::paths::contract_tilde(&kitty)], None).unwrap();
+
+        let history = engine.history("tmux", 10).unwrap();
+        let with_top = history[1].id;
+
+        let found = engine.search_content(Some("tmux"), "status-position top").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].snapshot.id, with_top);
+        assert_eq!(found[0].lines[0].line, 2);
+        assert_eq!(found[0].lines[0].text, "set -g status-position top");
+
+        // Every tool, newest first
+        let found = engine.search_content(None, "status-position top").unwrap();
+        let tools: Vec<&str> = found.iter().map(|m| m.snapshot.tool.as_str()).collect();
+        assert_eq!(tools, vec!["kitty", "tmux"]);
+
+        assert_eq!(engine.search_content(Some("tmux"), "mouse").unwrap().len(), 3);
+        assert!(engine.search_content(None, "Status-Position").unwrap().is_empty());
+        assert!(engine.search_content(None, "").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_content_caps_lines() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        let conf = files_tmp.path().join("zshrc");
+        let content: String = (0..20).map(|i| format!("alias a{}=ls\n", i)).collect();
+        fs::write(&conf, content).unwrap();
+        engine.snapshot_tool("zsh", &[util::paths::contract_tilde(&conf)], None).unwrap();
+
+        let found = engine.search_content(None, "alias").unwrap();
+        assert_eq!(found[0].lines.len(), MAX_MATCHES_PER_SNAPSHOT);
+        assert_eq!(found[0].total, 20);
+    }
+
+    #[test]
+    fn test_pinned_snapshots_survive_prune_and_gc() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        snapshot_versions(&engine, "tmux", &files_tmp.path().join("tmux.conf"), 4);
+        snapshot_versions(&engine, "old", &files_tmp.path().join("old.conf"), 2);
+
+        // Pin the oldest tmux snapshot and one of the orphan's
+        assert!(engine.set_pinned(1, true).unwrap());
+        assert!(engine.set_pinned(5, true).unwrap());
+        assert!(!engine.set_pinned(999, true).unwrap());
+
+        assert_eq!(engine.prune(Some(1), None, false).unwrap().rows, 2);
+        let ids: Vec<i64> = engine.history("tmux", 10).unwrap().iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![4, 1]);
+        assert!(engine.history("tmux", 10).unwrap()[1].pinned);
+
+        assert_eq!(engine.orphaned_tools(&["tmux"]).unwrap(), vec![("old".to_string(), 1)]);
+        assert_eq!(engine.delete_tools(&["old"]).unwrap(), 1);
+        let old = engine.history("old", 10).unwrap();
+        assert_eq!((old.len(), old[0].id), (1, 5));
+        assert!(engine.orphaned_tools(&["tmux"]).unwrap().is_empty());
+
+        // Unpinned, it goes like any other
+        assert!(engine.set_pinned(1, false).unwrap());
+        assert_eq!(engine.prune(Some(1), None, false).unwrap().rows, 1);
+    }
+
+    #[test]
+    fn test_annotate_changes_only_the_message() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        snapshot_versions(&engine, "tmux", &files_tmp.path().join("tmux.conf"), 2);
+        let before = engine.history("tmux", 10).unwrap();
+        let (_, content) = engine.get_snapshot(1).unwrap().unwrap();
+
+        assert!(engine.annotate(1, "before the status bar rewrite", false).unwrap());
+        assert!(engine.annotate(1, "known good", true).unwrap());
+        assert!(!engine.annotate(999, "nope", false).unwrap());
+
+        let after = engine.history("tmux", 10).unwrap();
+        assert_eq!(
+            after[1].message.as_deref(),
+            Some("before the status bar rewrite; known good")
+        );
+        assert_eq!(after[1].hash, before[1].hash);
+        assert_eq!(after[0].message, before[0].message);
+        assert_eq!(engine.get_snapshot(1).unwrap().unwrap().1, content);
+
+        // Appending to no message, then clearing
+        assert!(engine.annotate(2, "v1", true).unwrap());
+        assert_eq!(engine.history("tmux", 1).unwrap()[0].message.as_deref(), Some("v1"));
+        assert!(engine.annotate(2, "  ", false).unwrap());
+        assert_eq!(engine.history("tmux", 1).unwrap()[0].message, None);
+
+        // Still deduplicates against the annotated rows
+        fs::write(files_tmp.path().join("tmux.conf"), "version 1\n").unwrap();
+        let path_str = util::paths::contract_tilde(&files_tmp.path().join("tmux.conf"));
+        assert_eq!(engine.snapshot_tool("tmux", &[path_str], None).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_tag_snapshot_and_move() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        snapshot_versions(&engine, "tmux", &files_tmp.path().join("tmux.conf"), 3);
+        snapshot_versions(&engine, "tmux", &files_tmp.path().join("extra.conf"), 1);
+
+        assert!(engine.tag_snapshot(1, "known-good").unwrap());
+        assert!(engine.tag_snapshot(4, "known-good").unwrap());
+        assert!(!engine.tag_snapshot(999, "known-good").unwrap());
+        assert!(engine.tag_snapshot(1, "has space").is_err());
+
+        let history = engine.history("tmux", 10).unwrap();
+        let tags = |id: i64| history.iter().find(|s| s.id == id).unwrap().tags.clone();
+        assert_eq!(tags(1), vec!["known-good"]);
+        assert_eq!(tags(4), vec!["known-good"]);
+
+        // Re-tagging another snapshot of the same file moves the label;
+        // the other file keeps its own
+        assert!(engine.tag_snapshot(2, "known-good").unwrap());
+        assert!(engine.tag_snapshot(2, "v1").unwrap());
+        let history = engine.history("tmux", 10).unwrap();
+        let tags = |id: i64| history.iter().find(|s| s.id == id).unwrap().tags.clone();
+        assert!(tags(1).is_empty());
+        assert_eq!(tags(2), vec!["known-good", "v1"]);
+        assert_eq!(tags(4), vec!["known-good"]);
+    }
+
+    #[test]
+    fn test_snapshot_by_tag_resolves_per_file() {
+        let (config_tmp, engine, files_tmp) = setup();
+        let file = files_tmp.path().join("tmux.conf");
+        snapshot_versions(&engine, "tmux", &file, 1);
+        snapshot_versions(&engine, "zsh", &files_tmp.path().join(".zshrc"), 1);
+        let paths = |p: &Path| vec![util::paths::contract_tilde(p)];
+
+        // Tags the snapshot holding each file's current content
+        assert_eq!(engine.tag_current("tmux", &paths(&file), "good").unwrap(), 1);
+        assert_eq!(
+            engine.tag_current("zsh", &paths(&files_tmp.path().join(".zshrc")), "good").unwrap(),
+            1
+        );
+        fs::write(&file, "broken\n").unwrap();
+
+        let tagged = engine.snapshot_by_tag(None, "good").unwrap();
+        let tools: Vec<&str> = tagged.iter().map(|s| s.tool.as_str()).collect();
+        assert_eq!(tools, ["tmux", "zsh"]);
+        let tmux = engine.snapshot_by_tag(Some("tmux"), "good").unwrap();
+        assert_eq!(tmux.len(), 1);
+        assert!(engine.snapshot_by_tag(None, "missing").unwrap().is_empty());
+
+        engine.rollback(tmux[0].id, &config_tmp.path().join("backups")).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "version 0\n");
+    }
+
+    #[test]
+    fn test_tagged_snapshots_survive_prune() {
+        let (_config_tmp, engine, files_tmp) = setup();
+        snapshot_versions(&engine, "tmux", &files_tmp.path().join("tmux.conf"), 3);
+        engine.tag_snapshot(1, "known-good").unwrap();
+
+        assert_eq!(engine.prune(Some(1), None, false).unwrap().rows, 1);
+        let ids: Vec<i64> = engine.history("tmux", 10).unwrap().iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+    }
+
+    #[test]
+    fn test_encrypted_snapshot_round_trip() {
+        let (config_tmp, engine, files_tmp) = setup();
+        let engine = engine.with_cipher(Cipher::with_rounds("hunter2", 10).unwrap()).unwrap();
+
+        let conf = files_tmp.path().join("gh.yml");
+        fs::write(&conf, "oauth_token: ghp_s3cret\n").unwrap();
+        let path_str = util::paths::contract_tilde(&conf);
+        assert_eq!(engine.snapshot_tool("gh", std::slice::from_ref(&path_str), None).unwrap(), 1);
+
+        // Neither the column nor the database files hold the plaintext
+        let (column, encrypted): (String, bool) = engine
+            .conn
+            .query_row("SELECT content, encrypted FROM blobs", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert!(encrypted);
+        assert!(!column.contains("ghp_s3cret"));
+        for name in ["snapshots.db", "snapshots.db-wal"] {
+            let raw = fs::read(config_tmp.path().join(name)).unwrap_or_default();
+            assert!(!raw.windows(10).any(|w| w == b"ghp_s3cret"), "{} leaks", name);
+        }
+
+        // Unchanged content still dedups; reads decrypt
+        assert_eq!(engine.snapshot_tool("gh", std::slice::from_ref(&path_str), None).unwrap(), 0);
+        fs::write(&conf, "oauth_token: ghp_rotated\n").unwrap();
+        let diffs = engine.diff_current("gh", std::slice::from_ref(&path_str)).unwrap();
+        assert_eq!(diffs[0].old_content, "oauth_token: ghp_s3cret\n");
+        assert_eq!(engine.search_content(None, "s3cret").unwrap().len(), 1);
+
+        engine.rollback(1, &config_tmp.path().join("backups")).unwrap();
+        assert_eq!(fs::read_to_string(&conf).unwrap(), "oauth_token: ghp_s3cret\n");
+
+        // A different passphrase can't open the database
+        let other = SnapshotEngine::open(config_tmp.path()).unwrap();
+        assert!(other.with_cipher(Cipher::with_rounds("hunter3", 10).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_encryption_upgrades_shared_plaintext_blob() {
+        let (config_tmp, engine, files_tmp) = setup();
+        let first = files_tmp.path().join("hosts.yml");
+        let second = files_tmp.path().join("hosts.yml.bak");
+        fs::write(&first, "oauth_token: ghp_s3cret\n").unwrap();
+        fs::write(&second, "oauth_token: ghp_s3cret\n").unwrap();
+        let first_str = util::paths::contract_tilde(&first);
+        engine.snapshot_tool("gh", std::slice::from_ref(&first_str), None).unwrap();
+
+        // The same content snapshotted with encryption on is not left in plaintext
+        let engine = engine.with_cipher(Cipher::with_rounds("hunter2", 10).unwrap()).unwrap();
+        let second_str = util::paths::contract_tilde(&second);
+        engine.snapshot_tool("backup", std::slice::from_ref(&second_str), None).unwrap();
+        let (column, encrypted): (String, bool) = engine
+            .conn
+            .query_row("SELECT content, encrypted FROM blobs", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert!(encrypted);
+        assert!(!column.contains("ghp_s3cret"));
+
+        // The older plaintext snapshot reads through the upgraded blob
+        fs::write(&first, "changed\n").unwrap();
+        engine.rollback(1, &config_tmp.path().join("backups")).unwrap();
+        assert_eq!(fs::read_to_string(&first).unwrap(), "oauth_token: ghp_s3cret\n");
     }
 
     #[test]
@@ -0,0 +1,131 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::core::errors::DotsmithError;
+
+/// File extension marking a source file as a template to render at deploy time.
+pub const TEMPLATE_EXTENSION: &str = "tmpl";
+
+/// Whether a path names a template (`*.tmpl`).
+pub fn is_template(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == TEMPLATE_EXTENSION)
+}
+
+/// Render `{{env.VAR}}` placeholders (whitespace inside the braces is allowed)
+/// using `lookup`. Unset variables are an error listing every missing name,
+/// unless `allow_missing` is set, in which case they render as empty strings.
+/// Text that isn't an `env.` placeholder is left untouched.
+pub fn render(
+    template: &str,
+    source: &Path,
+    allow_missing: bool,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut missing: Vec<String> = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+
+        let Some(end) = after_open.find("}}") else {
+            // Unterminated braces are literal text
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+
+        let inner = after_open[..end].trim();
+        match inner.strip_prefix("env.").filter(|name| is_var_name(name)) {
+            Some(name) => match lookup(name) {
+                Some(value) => out.push_str(&value),
+                None if allow_missing => {}
+                None => {
+                    if !missing.iter().any(|m| m == name) {
+                        missing.push(name.to_string());
+                    }
+                }
+            },
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+
+        rest = &after_open[end + 2..];
+    }
+    out.push_str(rest);
+
+    if !missing.is_empty() {
+        return Err(DotsmithError::TemplateEnvMissing(
+            source.display().to_string(),
+            missing.join(", "),
+        )
+        .into());
+    }
+
+    Ok(out)
+}
+
+fn is_var_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(name: &str) -> Option<String> {
+        match name {
+            "TOKEN" => Some("s3cret".to_string()),
+            "USER_NAME" => Some("alice".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_render_substitutes() {
+        let out = render(
+            "user = {{env.USER_NAME}}\ntoken = {{ env.TOKEN }}\n",
+            Path::new("a.tmpl"),
+            false,
+            env,
+        )
+        .unwrap();
+        assert_eq!(out, "user = alice\ntoken = s3cret\n");
+    }
+
+    #[test]
+    fn test_render_missing_errors_with_all_names() {
+        let err = render(
+            "{{env.A}} {{env.B}} {{env.A}}",
+            Path::new("a.tmpl"),
+            false,
+            env,
+        )
+        .unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("A, B"), "{}", msg);
+    }
+
+    #[test]
+    fn test_render_allow_missing() {
+        let out = render("x={{env.NOPE}};", Path::new("a.tmpl"), true, env).unwrap();
+        assert_eq!(out, "x=;");
+    }
+
+    #[test]
+    fn test_render_leaves_other_braces() {
+        let input = "{{ other }} {{env.}} {{env.1X}} {{ unterminated";
+        let out = render(input, Path::new("a.tmpl"), false, env).unwrap();
+        assert_eq!(out, input);
+    }
+
+    #[test]
+    fn test_is_template() {
+        assert!(is_template(Path::new("gitconfig.tmpl")));
+        assert!(!is_template(Path::new("gitconfig")));
+        assert!(!is_template(Path::new("tmpl")));
+    }
+}
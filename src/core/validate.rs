@@ -1,13 +1,69 @@
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::core::detect;
+use crate::core::module::ValidationRules;
+use crate::util;
+
+/// One syntax problem found in a config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// 1-based line number, when the problem is on a single line.
+    pub line: Option<usize>,
+    /// 1-based column the problem starts at.
+    pub column: Option<usize>,
+    pub message: String,
+    /// The included file the error is in, when it isn't the validated file.
+    pub file: Option<PathBuf>,
+}
+
+impl ValidationError {
+    fn at(line: usize, column: usize, message: String) -> Self {
+        Self {
+            line: Some(line),
+            column: Some(column),
+            message,
+            file: None,
+        }
+    }
+
+    fn whole_file(message: String) -> Self {
+        Self {
+            line: None,
+            column: None,
+            message,
+            file: None,
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref file) = self.file {
+            write!(f, "{}: ", util::paths::contract_tilde(file))?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
 
 /// Result of validating a config file's syntax.
 #[derive(Debug)]
 pub struct ValidationResult {
     pub valid: bool,
-    pub errors: Vec<String>,
+    pub errors: Vec<ValidationError>,
+    /// Informational messages that don't affect validity (e.g. includes that
+    /// were not followed).
+    pub notes: Vec<String>,
+    /// No validator exists for the file's format, so nothing was checked.
+    pub skipped: bool,
 }
 
 impl ValidationResult {
@@ -15,33 +71,125 @@ impl ValidationResult {
         Self {
             valid: true,
             errors: Vec::new(),
+            notes: Vec::new(),
+            skipped: false,
+        }
+    }
+
+    fn skipped() -> Self {
+        Self {
+            skipped: true,
+            ..Self::ok()
         }
     }
 
-    fn with_errors(errors: Vec<String>) -> Self {
+    fn with_errors(errors: Vec<ValidationError>) -> Self {
         Self {
             valid: errors.is_empty(),
             errors,
+            ..Self::ok()
         }
     }
+
+    /// Errors and notes for display, one per line. Each error on a known
+    /// line is followed by that line of the file and a caret under the
+    /// column. `path` is the validated file; errors in included files are
+    /// shown from those files.
+    pub fn render(&self, path: &Path) -> String {
+        let mut out = String::new();
+        let mut cache: Vec<(PathBuf, Vec<String>)> = Vec::new();
+
+        for err in &self.errors {
+            out.push_str(&format!("{}\n", err));
+            let Some(line_no) = err.line else {
+                continue;
+            };
+
+            let file = err.file.as_deref().unwrap_or(path);
+            let lines = match cache.iter().position(|(p, _)| p == file) {
+                Some(i) => &cache[i].1,
+                None => {
+                    let content = fs::read_to_string(file).unwrap_or_default();
+                    cache.push((file.to_path_buf(), content.lines().map(String::from).collect()));
+                    &cache[cache.len() - 1].1
+                }
+            };
+            let Some(text) = lines.get(line_no - 1) else {
+                continue;
+            };
+
+            let gutter = line_no.to_string();
+            out.push_str(&format!("  {} | {}\n", gutter, text));
+            // Keep tabs before the caret so it lines up under the text
+            let column = err.column.unwrap_or(1).max(1);
+            let pad: String = text
+                .chars()
+                .take(column - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            out.push_str(&format!("  {} | {}^\n", " ".repeat(gutter.len()), pad));
+        }
+
+        for note in &self.notes {
+            out.push_str(&format!("note: {}\n", note));
+        }
+        out
+    }
 }
 
 /// Validate a config file's syntax based on its format.
 ///
-/// Supported formats: "toml", "key-value", "git", "tmux".
-/// Formats "shell" and "lua" are skipped (too complex to parse).
-pub fn validate_config(path: &Path, format: &str) -> Result<ValidationResult> {
+/// "toml", "yaml", "json" and "jsonc" (JSON with comments) files are parsed;
+/// "lua" is skipped (too complex to parse), and so
+/// is "shell" unless `use_shellcheck` is set and shellcheck is installed (see
+/// [`validate_shell_external`]). Other formats are checked line by line
+/// against the tool's `rules`, including every file a "git" config includes.
+/// Without rules the file is not checked. Unchecked files come back valid
+/// with `skipped` set.
+pub fn validate_config(
+    path: &Path,
+    format: &str,
+    rules: Option<&ValidationRules>,
+    use_shellcheck: bool,
+) -> Result<ValidationResult> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("failed to read {}", path.display()))?;
 
-    match format {
-        "toml" => validate_toml(&content),
-        "key-value" => Ok(validate_key_value(&content)),
-        "git" => Ok(validate_git_config(&content)),
-        "tmux" => Ok(validate_tmux(&content)),
+    match (format, rules) {
+        ("toml", _) => validate_toml(&content),
+        ("yaml", _) => Ok(validate_yaml(&content)),
+        ("json", _) => Ok(validate_json(&content, false)),
+        ("jsonc", _) => Ok(validate_json(&content, true)),
+        ("shell", _) if use_shellcheck => Ok(validate_shell_external(path, &content)),
         // Shell and Lua are too complex to parse correctly
-        "shell" | "lua" => Ok(ValidationResult::ok()),
-        _ => Ok(ValidationResult::ok()),
+        ("shell" | "lua", _) => Ok(ValidationResult::skipped()),
+        ("git", Some(rules)) => Ok(validate_git_with_includes(path, &content, rules)),
+        (_, Some(rules)) => Ok(validate_lines(&content, rules)),
+        (_, None) => Ok(ValidationResult::skipped()),
+    }
+}
+
+/// Guess the format of a file from its name, for tools without a module.
+/// Only formats that have a validator are recognized.
+pub fn guess_format(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_string_lossy();
+    let bare = name.trim_start_matches('.');
+    let shell_rc = [
+        "bashrc",
+        "bash_profile",
+        "bash_login",
+        "bash_logout",
+        "bash_aliases",
+        "profile",
+    ];
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => Some("toml"),
+        Some("yaml" | "yml") => Some("yaml"),
+        Some("json") => Some("json"),
+        Some("jsonc") => Some("jsonc"),
+        Some("sh" | "bash") => Some("shell"),
+        _ if shell_rc.contains(&bare) => Some("shell"),
+        _ => None,
     }
 }
 
@@ -49,63 +197,194 @@ pub fn validate_config(path: &Path, format: &str) -> Result<ValidationResult> {
 fn validate_toml(content: &str) -> Result<ValidationResult> {
     match content.parse::<toml::Value>() {
         Ok(_) => Ok(ValidationResult::ok()),
-        Err(e) => Ok(ValidationResult::with_errors(vec![e.to_string()])),
+        Err(e) => {
+            let message = e.message().to_string();
+            let error = match e.span() {
+                Some(span) => {
+                    let (line, column) = line_and_column(content, span.start);
+                    ValidationError::at(line, column, message)
+                }
+                None => ValidationError::whole_file(message),
+            };
+            Ok(ValidationResult::with_errors(vec![error]))
+        }
     }
 }
 
-/// Validate key-value format (kitty-style).
-/// Non-blank, non-comment lines must have a whitespace or `=` separator.
-fn validate_key_value(content: &str) -> ValidationResult {
-    let mut errors = Vec::new();
+/// Parse YAML using yaml-rust2, every document of a multi-document file.
+fn validate_yaml(content: &str) -> ValidationResult {
+    match yaml_rust2::YamlLoader::load_from_str(content) {
+        Ok(_) => ValidationResult::ok(),
+        Err(e) => {
+            // The marker's column is 0-based
+            let at = e.marker();
+            let error = ValidationError::at(at.line(), at.col() + 1, e.info().to_string());
+            ValidationResult::with_errors(vec![error])
+        }
+    }
+}
 
-    for (i, line) in content.lines().enumerate() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with('#') {
-            continue;
+/// Parse JSON; with `comments`, `//` and `/* */` comments and trailing commas
+/// are allowed too.
+fn validate_json(content: &str, comments: bool) -> ValidationResult {
+    let parsed = if comments {
+        serde_json::from_str::<serde_json::Value>(&util::json::strip_comments(content))
+    } else {
+        serde_json::from_str::<serde_json::Value>(content)
+    };
+    match parsed {
+        Ok(_) => ValidationResult::ok(),
+        Err(e) => {
+            // Column 0 means before the first character of the line
+            let message = without_position(&e.to_string());
+            let error = ValidationError::at(e.line(), e.column().max(1), message);
+            ValidationResult::with_errors(vec![error])
         }
+    }
+}
 
-        // Must contain at least one space, tab, or equals sign
-        if !trimmed.contains(' ') && !trimmed.contains('\t') && !trimmed.contains('=') {
-            errors.push(format!(
-                "line {}: expected key-value pair, got '{}'",
-                i + 1,
-                truncate(trimmed, 40)
-            ));
+/// Drop the " at line L column C" serde_json appends to their
+/// messages; the position is reported separately.
+fn without_position(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(i) => message[..i].to_string(),
+        None => message.to_string(),
+    }
+}
+
+/// 1-based line and column of a byte offset into `content`.
+fn line_and_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Check a shell config with shellcheck, if it is installed. zsh files are
+/// skipped since shellcheck doesn't support zsh; files without a shebang
+/// (most rc files) are checked as bash.
+pub fn validate_shell_external(path: &Path, content: &str) -> ValidationResult {
+    if is_zsh(path, content) {
+        let mut result = ValidationResult::skipped();
+        result.notes.push("shellcheck does not support zsh".to_string());
+        return result;
+    }
+
+    let mut cmd = Command::new("shellcheck");
+    cmd.arg("--format=json1");
+    if !content.starts_with("#!") {
+        cmd.arg("--shell=bash");
+    }
+    let output = match cmd.arg(path).output() {
+        Ok(output) => output,
+        // Not installed: same as having no validator
+        Err(_) => return ValidationResult::skipped(),
+    };
+
+    // 0 means clean and 1 means findings; anything else is shellcheck failing
+    match output.status.code() {
+        Some(0 | 1) => match parse_shellcheck_json(&output.stdout) {
+            Ok(errors) => ValidationResult::with_errors(errors),
+            Err(e) => {
+                let mut result = ValidationResult::skipped();
+                result.notes.push(format!("couldn't read shellcheck output: {}", e));
+                result
+            }
+        },
+        _ => {
+            let mut result = ValidationResult::skipped();
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            result.notes.push(format!("shellcheck failed: {}", stderr.trim()));
+            result
         }
     }
+}
 
-    ValidationResult::with_errors(errors)
+/// shellcheck's `--format=json1` output.
+#[derive(Deserialize)]
+struct ShellcheckOutput {
+    comments: Vec<ShellcheckComment>,
+}
+
+#[derive(Deserialize)]
+struct ShellcheckComment {
+    line: usize,
+    column: usize,
+    level: String,
+    code: u32,
+    message: String,
 }
 
-/// Validate git config INI format.
-/// Lines must be blank, comments, `[section]` headers, or `key = value`.
-fn validate_git_config(content: &str) -> ValidationResult {
+/// Parse shellcheck's `--format=json1` output. Every severity counts as an
+/// error.
+fn parse_shellcheck_json(output: &[u8]) -> serde_json::Result<Vec<ValidationError>> {
+    let output: ShellcheckOutput = serde_json::from_slice(output)?;
+    Ok(output
+        .comments
+        .into_iter()
+        .map(|c| {
+            let message = format!("{}: {} [SC{}]", c.level, c.message, c.code);
+            ValidationError::at(c.line, c.column, message)
+        })
+        .collect())
+}
+
+/// A zsh script by name (`.zshrc`, `foo.zsh`, ...) or shebang.
+fn is_zsh(path: &Path, content: &str) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let by_name = ["zshrc", "zshenv", "zprofile", "zlogin", "zlogout"]
+        .contains(&name.trim_start_matches('.'))
+        || name.ends_with(".zsh");
+    let shebang = content.lines().next().unwrap_or("");
+    by_name || (shebang.starts_with("#!") && shebang.contains("zsh"))
+}
+
+/// Check each line against a tool's rules. Blank lines, comments, and
+/// ignored lines always pass.
+fn validate_lines(content: &str, rules: &ValidationRules) -> ValidationResult {
     let mut errors = Vec::new();
 
     for (i, line) in content.lines().enumerate() {
         let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+        let column = line.chars().take_while(|c| c.is_whitespace()).count() + 1;
+        if trimmed.is_empty()
+            || rules.comment_prefixes.iter().any(|p| trimmed.starts_with(p.as_str()))
+            || rules.ignore_lines.iter().any(|l| l == trimmed)
+        {
             continue;
         }
 
         // Section header
-        if trimmed.starts_with('[') {
+        if rules.sections && trimmed.starts_with('[') {
             if !trimmed.ends_with(']') {
-                errors.push(format!(
-                    "line {}: unclosed section header '{}'",
+                errors.push(ValidationError::at(
                     i + 1,
-                    truncate(trimmed, 40)
+                    column,
+                    format!("unclosed section header '{}'", truncate(trimmed, 40)),
                 ));
             }
             continue;
         }
 
-        // Key = value (or key with whitespace value)
-        if !trimmed.contains('=') && !trimmed.contains(' ') && !trimmed.contains('\t') {
-            errors.push(format!(
-                "line {}: expected key = value, got '{}'",
+        if !rules.known_commands.is_empty() {
+            let first_word = trimmed.split_whitespace().next().unwrap_or("");
+            if !rules.known_commands.iter().any(|c| c == first_word) {
+                errors.push(ValidationError::at(
+                    i + 1,
+                    column,
+                    format!("unrecognized command '{}'", truncate(first_word, 40)),
+                ));
+                continue;
+            }
+        }
+
+        if !rules.separators.is_empty()
+            && !rules.separators.iter().any(|sep| trimmed.contains(sep.as_str()))
+        {
+            errors.push(ValidationError::at(
                 i + 1,
-                truncate(trimmed, 40)
+                column,
+                format!("expected {}, got '{}'", rules.expected, truncate(trimmed, 40)),
             ));
         }
     }
@@ -113,47 +392,31 @@ fn validate_git_config(content: &str) -> ValidationResult {
     ValidationResult::with_errors(errors)
 }
 
-/// Light validation for tmux config.
-/// Non-blank, non-comment lines should start with a known tmux command word.
-fn validate_tmux(content: &str) -> ValidationResult {
-    let known_commands: &[&str] = &[
-        "set", "bind", "unbind", "source", "run", "if", "set-option", "set-window-option",
-        "bind-key", "unbind-key", "source-file", "display", "display-message", "new",
-        "new-session", "new-window", "send", "send-keys", "select", "select-pane",
-        "select-window", "split", "split-window", "swap", "move", "resize", "copy",
-        "paste", "choose", "command", "confirm", "break", "join", "kill", "last", "link",
-        "list", "load", "lock", "next", "pipe", "previous", "refresh", "rename", "respawn",
-        "rotate", "save", "show", "switch", "wait", "has", "attach", "detach", "setw",
-        "set-environment", "setenv", "is-prefix", "%if", "%endif", "%else", "%hidden",
-    ];
-
-    let mut errors = Vec::new();
-
-    for (i, line) in content.lines().enumerate() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with('#') {
-            continue;
-        }
-
-        // Line continuation with backslash is valid
-        if trimmed == "\\" {
-            continue;
-        }
-
-        // Get first word
-        let first_word = trimmed.split_whitespace().next().unwrap_or("");
-
-        // Strip leading - (for set -g, etc.)
-        if !known_commands.contains(&first_word) {
-            errors.push(format!(
-                "line {}: unrecognized command '{}'",
-                i + 1,
-                truncate(first_word, 40)
-            ));
+/// Validate a git config and every file it includes.
+/// Errors from included files are prefixed with that file's path; include
+/// directives that couldn't be followed become notes.
+fn validate_git_with_includes(
+    path: &Path,
+    content: &str,
+    rules: &ValidationRules,
+) -> ValidationResult {
+    let mut errors = validate_lines(content, rules).errors;
+    let includes = detect::resolve_git_includes(path);
+
+    for file in &includes.files {
+        let mut file_errors = match fs::read_to_string(file) {
+            Ok(included) => validate_lines(&included, rules).errors,
+            Err(e) => vec![ValidationError::whole_file(format!("failed to read: {}", e))],
+        };
+        for err in &mut file_errors {
+            err.file = Some(file.clone());
         }
+        errors.extend(file_errors);
     }
 
-    ValidationResult::with_errors(errors)
+    let mut result = ValidationResult::with_errors(errors);
+    result.notes = includes.unresolved;
+    result
 }
 
 fn truncate(s: &str, max_len: usize) -> &str {
@@ -167,8 +430,25 @@ fn truncate(s: &str, max_len: usize) -> &str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::module::ModuleRegistry;
     use tempfile::TempDir;
 
+    fn rules(tool: &str) -> ValidationRules {
+        ModuleRegistry::get_validation(tool).unwrap()
+    }
+
+    fn validate_key_value(content: &str) -> ValidationResult {
+        validate_lines(content, &rules("kitty"))
+    }
+
+    fn validate_git_config(content: &str) -> ValidationResult {
+        validate_lines(content, &rules("git"))
+    }
+
+    fn validate_tmux(content: &str) -> ValidationResult {
+        validate_lines(content, &rules("tmux"))
+    }
+
     #[test]
     fn test_valid_toml() {
         let result = validate_toml("[section]\nkey = \"value\"\n").unwrap();
@@ -195,7 +475,8 @@ mod tests {
         let content = "font_family JetBrains Mono\nbadline\n";
         let result = validate_key_value(content);
         assert!(!result.valid);
-        assert!(result.errors[0].contains("line 2"));
+        assert_eq!(result.errors[0].line, Some(2));
+        assert!(result.errors[0].to_string().starts_with("line 2: "));
     }
 
     #[test]
@@ -210,7 +491,7 @@ mod tests {
         let content = "[user\n\tname = John\n";
         let result = validate_git_config(content);
         assert!(!result.valid);
-        assert!(result.errors[0].contains("unclosed section"));
+        assert!(result.errors[0].message.contains("unclosed section"));
     }
 
     #[test]
@@ -225,7 +506,7 @@ mod tests {
         let content = "set -g mouse on\nfoobar something\n";
         let result = validate_tmux(content);
         assert!(!result.valid);
-        assert!(result.errors[0].contains("foobar"));
+        assert!(result.errors[0].message.contains("foobar"));
     }
 
     #[test]
@@ -233,8 +514,9 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("test.zsh");
         fs::write(&path, "this is not valid anything {{{").unwrap();
-        let result = validate_config(&path, "shell").unwrap();
+        let result = validate_config(&path, "shell", None, false).unwrap();
         assert!(result.valid);
+        assert!(result.skipped);
     }
 
     #[test]
@@ -242,7 +524,7 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("test.lua");
         fs::write(&path, "this is not valid lua {{{").unwrap();
-        let result = validate_config(&path, "lua").unwrap();
+        let result = validate_config(&path, "lua", None, false).unwrap();
         assert!(result.valid);
     }
 
@@ -251,7 +533,7 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("test.conf");
         fs::write(&path, "whatever").unwrap();
-        let result = validate_config(&path, "unknown").unwrap();
+        let result = validate_config(&path, "unknown", None, false).unwrap();
         assert!(result.valid);
     }
 
@@ -260,7 +542,7 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("test.toml");
         fs::write(&path, "[window]\nopacity = 0.9\n").unwrap();
-        let result = validate_config(&path, "toml").unwrap();
+        let result = validate_config(&path, "toml", None, false).unwrap();
         assert!(result.valid);
     }
 
@@ -269,7 +551,7 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("test.toml");
         fs::write(&path, "[window\nopacity = ").unwrap();
-        let result = validate_config(&path, "toml").unwrap();
+        let result = validate_config(&path, "toml", None, false).unwrap();
         assert!(!result.valid);
     }
 
@@ -287,6 +569,168 @@ mod tests {
         assert!(result.valid);
     }
 
+    #[test]
+    fn test_git_config_notes_unfollowed_includes() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("config");
+        fs::write(&path, "[includeIf \"onbranch:main\"]\n\tpath = branch.inc\n").unwrap();
+        let result = validate_config(&path, "git", Some(&rules("git")), false).unwrap();
+        assert!(result.valid);
+        assert_eq!(result.notes.len(), 1);
+        assert!(result.notes[0].contains("onbranch:main"));
+    }
+
+    #[test]
+    fn test_invalid_git_config_names_expected_syntax() {
+        let result = validate_git_config("[core]\n\tbare\n");
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].to_string(), "line 2: expected key = value, got 'bare'");
+    }
+
+    #[test]
+    fn test_format_without_rules_skipped() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("tmux.conf");
+        fs::write(&path, "foobar\n").unwrap();
+        assert!(validate_config(&path, "tmux", None, false).unwrap().skipped);
+        let result = validate_config(&path, "tmux", Some(&rules("tmux")), false).unwrap();
+        assert!(!result.valid);
+        assert!(!result.skipped);
+    }
+
+    #[test]
+    fn test_invalid_toml_has_line_and_column() {
+        let result = validate_toml("[window]\nopacity = 0.9\nsize = = 3\n").unwrap();
+        assert_eq!(result.errors[0].line, Some(3));
+        assert_eq!(result.errors[0].column, Some(8));
+    }
+
+    #[test]
+    fn test_render_shows_line_and_caret() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("tmux.conf");
+        fs::write(&path, "set -g mouse on\n  foobar something\n").unwrap();
+        let result = validate_config(&path, "tmux", Some(&rules("tmux")), false).unwrap();
+        assert_eq!(
+            result.render(&path),
+            "line 2: unrecognized command 'foobar'\n  2 |   foobar something\n    |   ^\n"
+        );
+    }
+
+    #[test]
+    fn test_render_included_file_errors() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("config");
+        let included = tmp.path().join("extra.inc");
+        fs::write(&path, "[include]\n\tpath = extra.inc\n").unwrap();
+        fs::write(&included, "[alias]\n\tbroken\n").unwrap();
+
+        let mut error = ValidationError::at(2, 2, "expected key = value".to_string());
+        error.file = Some(included.clone());
+        let result = ValidationResult::with_errors(vec![error]);
+        let rendered = result.render(&path);
+        assert!(rendered.starts_with(&util::paths::contract_tilde(&included)));
+        assert!(rendered.contains("  2 | \tbroken\n    | \t^\n"), "{}", rendered);
+    }
+
+    #[test]
+    fn test_parse_shellcheck_json() {
+        let output = br#"{"comments":[
+            {"file":"/home/me/a:b.sh","line":3,"endLine":3,"column":6,"endColumn":10,
+             "level":"info","code":2086,"fix":null,
+             "message":"Double quote to prevent globbing and word splitting."},
+            {"file":"/home/me/a:b.sh","line":7,"endLine":7,"column":1,"endColumn":3,
+             "level":"error","code":1073,"fix":null,
+             "message":"Couldn't parse this if: expected 'then'."}
+        ]}"#;
+        let errors = parse_shellcheck_json(output).unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, Some(3));
+        assert_eq!(errors[0].column, Some(6));
+        assert_eq!(
+            errors[0].to_string(),
+            "line 3: info: Double quote to prevent globbing and word splitting. [SC2086]"
+        );
+        // A ':' in the message or file name stays where it belongs
+        assert_eq!(
+            errors[1].to_string(),
+            "line 7: error: Couldn't parse this if: expected 'then'. [SC1073]"
+        );
+        assert!(parse_shellcheck_json(b"In /home/me/.bashrc line 9").is_err());
+    }
+
+    #[test]
+    fn test_shellcheck_renders_unquoted_variable() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("bashrc");
+        fs::write(&path, "dir=~/src\ncd $dir\n").unwrap();
+        let output = br#"{"comments":[{"file":"bashrc","line":2,"endLine":2,"column":4,
+            "endColumn":8,"level":"info","code":2086,"fix":null,
+            "message":"Double quote to prevent globbing and word splitting."}]}"#;
+        let result = ValidationResult::with_errors(parse_shellcheck_json(output).unwrap());
+        assert!(!result.valid);
+        assert!(result.render(&path).contains("  2 | cd $dir\n    |    ^\n"));
+    }
+
+    #[test]
+    fn test_shellcheck_skips_zsh() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join(".zshrc");
+        fs::write(&path, "setopt autocd\n").unwrap();
+        let result = validate_config(&path, "shell", None, true).unwrap();
+        assert!(result.valid);
+        assert!(result.skipped);
+        assert!(!is_zsh(Path::new("/home/me/.bashrc"), "alias ll='ls -l'\n"));
+        assert!(is_zsh(Path::new("/home/me/prompt"), "#!/usr/bin/env zsh\n"));
+    }
+
+    #[test]
+    fn test_guess_format() {
+        assert_eq!(guess_format(Path::new("/home/me/.bashrc")), Some("shell"));
+        assert_eq!(guess_format(Path::new("/home/me/.profile")), Some("shell"));
+        assert_eq!(guess_format(Path::new("/home/me/bin/deploy.sh")), Some("shell"));
+        assert_eq!(guess_format(Path::new("/home/me/.config/starship.toml")), Some("toml"));
+        assert_eq!(guess_format(Path::new("/home/me/.config/gh/config.yml")), Some("yaml"));
+        assert_eq!(guess_format(Path::new("/home/me/.config/settings.jsonc")), Some("jsonc"));
+        assert_eq!(guess_format(Path::new("/home/me/.config/foo/config")), None);
+    }
+
+    #[test]
+    fn test_malformed_yaml_mapping() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("config.yml");
+        fs::write(&path, "window:\n  opacity: 0.9\n    padding: 2\n").unwrap();
+        let result = validate_config(&path, "yaml", None, false).unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.errors[0].line, Some(3));
+        assert_eq!(result.errors[0].column, Some(12));
+        assert_eq!(result.errors[0].message, "mapping values are not allowed in this context");
+
+        fs::write(&path, "window:\n  opacity: 0.9\n  padding: 2\n").unwrap();
+        assert!(validate_config(&path, "yaml", None, false).unwrap().valid);
+    }
+
+    #[test]
+    fn test_unterminated_json_object() {
+        let result = validate_json("{\n  \"editor.fontSize\": 14,\n  \"a\": [1, 2]\n", false);
+        assert!(!result.valid);
+        assert_eq!(result.errors[0].line, Some(4));
+        assert_eq!(result.errors[0].message, "EOF while parsing an object");
+
+        assert!(validate_json("{\"a\": 1}", false).valid);
+    }
+
+    #[test]
+    fn test_jsonc_allows_comments() {
+        let content = "{\n  // size in points\n  \"editor.fontSize\": 14, /* big */\n}\n";
+        assert!(!validate_json(content, false).valid);
+        assert!(validate_json(content, true).valid);
+
+        let result = validate_json("{\n  // unterminated\n  \"a\": 1\n", true);
+        assert!(!result.valid);
+        assert_eq!(result.errors[0].line, Some(4));
+    }
+
     #[test]
     fn test_tmux_with_setw() {
         let content = "setw -g mode-keys vi\n";
@@ -7,84 +7,330 @@ mod core;
 mod tui;
 mod util;
 
-use cli::{Commands, DotsmithCli, RepoAction};
+use cli::{Commands, DotsmithCli, RepoAction, SnapshotAction};
+use core::output::Printer;
 
 fn main() -> Result<()> {
     let cli = DotsmithCli::parse();
 
+    if let Some(ref dir) = cli.config_dir {
+        // SAFETY: set once at startup, before any threads are spawned.
+        // util::paths::config_dir() reads this variable.
+        unsafe {
+            std::env::set_var("DOTSMITH_CONFIG_DIR", dir);
+        }
+    }
+
     // Auto-initialize for commands that need config infrastructure.
-    // Skip for: Init (has its own UX), Completions, Mangen, Search (standalone).
+    // Skip for: Init (has its own UX), Completions, Hook, Mangen, Search and Generate
+    // (standalone).
     let skip_init = matches!(
         cli.command,
-        Some(Commands::Init)
+        Some(Commands::Init { .. })
             | Some(Commands::Completions { .. })
+            | Some(Commands::Hook { .. })
             | Some(Commands::Mangen)
             | Some(Commands::Search { .. })
+            | Some(Commands::Generate { .. })
     );
     if !skip_init {
         cli::init::ensure_initialized()?;
     }
 
+    // The TUI shows these itself, since it takes over the terminal
+    let runs_tui = matches!(cli.command, None | Some(Commands::Explore { .. }));
+    if !runs_tui && let Ok(config_dir) = util::paths::config_dir() {
+        for warning in core::module::ModuleRegistry::user_module_warnings(&config_dir) {
+            eprintln!("warning: {}", warning);
+        }
+    }
+
+    let out = Printer::new(cli.quiet, cli.verbose);
     let result = match cli.command {
-        None => tui::run(None),
-        Some(Commands::Explore { ref tool }) => tui::run(Some(tool)),
-        Some(Commands::Init) => cli::init::run(cli.verbose),
-        Some(Commands::Add { ref tool }) => cli::add::run(cli.verbose, tool),
-        Some(Commands::Remove { ref tool }) => cli::remove::run(cli.verbose, tool),
-        Some(Commands::List) => cli::list::run(cli.verbose),
-        Some(Commands::Status) => cli::status::run(cli.verbose),
-        Some(Commands::Doctor { ref tool }) => cli::doctor::run(cli.verbose, tool.as_deref()),
-        Some(Commands::Search { ref query }) => cli::search::run(cli.verbose, query),
+        None => tui::run(None, None),
+        Some(Commands::Explore {
+            ref tool,
+            ref view,
+            list_views,
+        }) => cli::explore::run(tool.as_deref(), view.as_deref(), list_views),
+        Some(Commands::Init { non_interactive }) => cli::init::run(&out, non_interactive),
+        Some(Commands::Add {
+            tool: Some(ref tool),
+            ref paths,
+            tier,
+            ..
+        }) => cli::add::run(&out, tool, paths, tier),
+        Some(Commands::Add { ref from, .. }) => cli::add::run_bulk(&out, from.as_deref()),
+        Some(Commands::Remove { ref tool }) => cli::remove::run(&out, tool),
+        Some(Commands::Import { ref path }) => cli::import::run(&out, path),
+        Some(Commands::List) => cli::list::run(&out, cli.json),
+        Some(Commands::Status {
+            ahead,
+            changed_only,
+        }) => cli::status::run(&out, ahead, changed_only, cli.json),
+        Some(Commands::Doctor {
+            ref tool,
+            ref tool_flag,
+            deep,
+            fix_perms,
+            fix,
+            yes,
+            strict,
+            strict_warnings,
+        }) => cli::doctor::run(
+            &out,
+            &cli::doctor::DoctorOpts {
+                tool: tool.as_deref().or(tool_flag.as_deref()),
+                deep,
+                fix_perms,
+                fix,
+                yes,
+                strict,
+                strict_warnings,
+                json: cli.json,
+            },
+        ),
+        Some(Commands::Search {
+            ref query,
+            ref tool,
+            ref option_type,
+            ref tag,
+        }) => cli::search::run(
+            query,
+            tool.as_deref(),
+            option_type.as_deref(),
+            tag.as_deref(),
+            cli.json,
+        ),
+        Some(Commands::Generate {
+            ref tools,
+            out: ref out_path,
+            ref format,
+        }) => cli::generate::run(&out, tools, out_path.as_deref(), format.as_deref()),
+        Some(Commands::Snapshot {
+            action: Some(SnapshotAction::Gc { dry_run, force }),
+            ..
+        }) => cli::snapshot::run_gc(&out, dry_run, force),
+        Some(Commands::Snapshot {
+            action: Some(SnapshotAction::Pin { id }),
+            ..
+        }) => cli::snapshot::run_pin(id, true),
         Some(Commands::Snapshot {
+            action: Some(SnapshotAction::Unpin { id }),
+            ..
+        }) => cli::snapshot::run_pin(id, false),
+        Some(Commands::Snapshot {
+            action:
+                Some(SnapshotAction::Annotate {
+                    id,
+                    ref message,
+                    append,
+                }),
+            ..
+        }) => cli::snapshot::run_annotate(id, message, append),
+        Some(Commands::Snapshot {
+            action: Some(SnapshotAction::Dupes),
+            ..
+        }) => cli::snapshot::run_dupes(&out),
+        Some(Commands::Snapshot {
+            action: None,
+            file: Some(ref file),
+            ref as_tool,
+            ref message,
+            no_secrets_check,
+            ..
+        }) => cli::snapshot::run_file(
+            &out,
+            file,
+            as_tool.as_deref(),
+            message.as_deref(),
+            !no_secrets_check,
+        ),
+        Some(Commands::Snapshot {
+            action: None,
             ref tool,
             ref message,
-        }) => cli::snapshot::run(cli.verbose, tool.as_deref(), message.as_deref()),
-        Some(Commands::History { ref tool, limit }) => cli::history::run(cli.verbose, tool, limit),
-        Some(Commands::Diff { ref tool }) => cli::diff::run(cli.verbose, tool.as_deref()),
+            ref tag,
+            dry_run: false,
+            no_secrets_check,
+            ..
+        }) => cli::snapshot::run(
+            &out,
+            tool.as_deref(),
+            message.as_deref(),
+            tag.as_deref(),
+            !no_secrets_check,
+        ),
+        Some(Commands::Snapshot {
+            action: None,
+            ref tool,
+            dry_run: true,
+            ..
+        }) => cli::snapshot::run_dry_run(tool.as_deref()),
+        Some(Commands::SnapshotGrep {
+            ref query,
+            ref tool,
+        }) => cli::snapshot::run_grep(query, tool.as_deref(), cli.json),
+        Some(Commands::History {
+            ref tool,
+            all: _,
+            limit,
+            csv,
+            ref export,
+        }) => match export {
+            // clap requires a tool alongside --export
+            Some(dir) => cli::history::run_export(tool.as_deref().unwrap_or_default(), dir),
+            None => cli::history::run(tool.as_deref(), limit, csv, cli.json),
+        },
+        Some(Commands::Diff {
+            ref tool,
+            ref profile,
+            ref remote,
+            ref user,
+            stat,
+        }) => {
+            let base = match (profile, remote) {
+                (Some(name), _) => cli::diff::DiffBase::Profile(name),
+                (_, Some(host)) => cli::diff::DiffBase::Remote(host, user.as_deref()),
+                _ => cli::diff::DiffBase::Snapshot,
+            };
+            cli::diff::run(tool.as_deref(), base, stat)
+        }
+        Some(Commands::Rollback {
+            snapshot_id: Some(snapshot_id),
+            dry_run,
+            force,
+            ..
+        }) => cli::rollback::run(&out, snapshot_id, dry_run, force),
         Some(Commands::Rollback {
-            snapshot_id,
+            at: Some(ref at),
+            ref tool,
+            dry_run,
+            force,
+            ..
+        }) => cli::rollback::run_at(
+            &out,
+            tool.as_deref().unwrap_or_default(),
+            at,
+            dry_run,
+            force,
+        ),
+        Some(Commands::Rollback {
+            snapshot_id: None,
+            ref tag,
+            ref tool,
+            dry_run,
+            force,
+            at: None,
+        }) => cli::rollback::run_tag(
+            &out,
+            tag.as_deref().unwrap_or_default(),
+            tool.as_deref(),
+            dry_run,
+            force,
+        ),
+        Some(Commands::Apply {
+            ref tool,
+            ref patch,
+            dry_run,
+            force,
+        }) => cli::apply::run(&out, tool, patch, dry_run, force),
+        Some(Commands::Prune {
+            keep,
+            ref older_than,
+            recompress,
             dry_run,
-        }) => cli::rollback::run(cli.verbose, snapshot_id, dry_run),
+        }) => cli::prune::run(&out, keep, older_than.as_deref(), recompress, dry_run),
         Some(Commands::Deploy {
             ref source,
             ref target,
             dry_run,
-        }) => cli::deploy::run(cli.verbose, source, target, dry_run),
-        Some(Commands::Edit { ref tool }) => cli::edit::run(cli.verbose, tool),
-        Some(Commands::Watch { ref tool }) => cli::watch::run(cli.verbose, tool.as_deref()),
-        Some(Commands::Reload { ref tool }) => cli::reload::run(cli.verbose, tool),
+            files,
+            copy,
+            allow_missing_env,
+        }) => {
+            cli::deploy::run(&out, source, target, dry_run, files, copy, allow_missing_env)
+        }
+        Some(Commands::Edit {
+            ref tool,
+            no_validate,
+            yes_rollback,
+            all,
+        }) => cli::edit::run(
+            &out,
+            tool,
+            &cli::edit::EditOpts {
+                validate: !no_validate,
+                yes_rollback,
+                all,
+            },
+        ),
+        Some(Commands::Watch {
+            ref tool,
+            once,
+            debounce,
+            reload,
+        }) => cli::watch::run(&out, tool.as_deref(), once, debounce, reload),
+        Some(Commands::Audit { ref tool }) => cli::audit::run(&out, tool),
+        Some(Commands::Validate { ref tool }) => cli::validate::run(&out, tool.as_deref()),
+        Some(Commands::Reload {
+            tool: Some(ref tool),
+            ..
+        }) => cli::reload::run(&out, tool),
+        Some(Commands::Reload { tool: None, .. }) => cli::reload::run_all(&out),
         Some(Commands::Plugins {
             ref tool,
             ref action,
-        }) => cli::plugins::run(cli.verbose, tool, action),
+        }) => cli::plugins::run(&out, tool, action),
         Some(Commands::Completions { shell }) => {
             let mut cmd = DotsmithCli::command();
             generate(shell, &mut cmd, "dotsmith", &mut std::io::stdout());
             Ok(())
         }
+        Some(Commands::Hook { ref shell }) => {
+            if let Some(hook) = core::hook::exit_hook(shell) {
+                print!("{}", hook);
+            }
+            Ok(())
+        }
         Some(Commands::Mangen) => {
             let cmd = DotsmithCli::command();
             let man = clap_mangen::Man::new(cmd);
             man.render(&mut std::io::stdout())?;
             Ok(())
         }
-        Some(Commands::Profile { ref action }) => cli::profile::run(cli.verbose, action),
+        Some(Commands::Profile { ref action }) => cli::profile::run(&out, action),
         Some(Commands::DeployRemote {
             ref host,
             ref user,
             ref tool,
             dry_run,
-        }) => cli::deploy_remote::run(cli.verbose, host, user.as_deref(), tool.as_deref(), dry_run),
+        }) => cli::deploy_remote::run(&out, host, user.as_deref(), tool.as_deref(), dry_run),
+        Some(Commands::PullRemote {
+            ref host,
+            ref user,
+            ref tool,
+            dry_run,
+        }) => cli::pull_remote::run(&out, host, user.as_deref(), tool.as_deref(), dry_run),
         Some(Commands::Repo { action }) => match action {
-            RepoAction::Init { path } => cli::repo::run_init(cli.verbose, &path),
-            RepoAction::Sync => cli::repo::run_sync(cli.verbose),
-            RepoAction::Status => cli::repo::run_status(cli.verbose),
+            RepoAction::Init { path } => cli::repo::run_init(&out, &path),
+            RepoAction::Sync {
+                only,
+                push,
+                no_secrets_check,
+            } => cli::repo::run_sync(&out, &only, push, !no_secrets_check),
+            RepoAction::Restore { dry_run } => cli::repo::run_restore(&out, dry_run),
+            RepoAction::Remote { url } => cli::repo::run_remote(&out, &url),
+            RepoAction::Status => cli::repo::run_status(&out),
         },
     };
 
     if let Err(e) = result {
         eprintln!("{}: {}", colored::Colorize::red("error"), e);
-        std::process::exit(1);
+        let code = e
+            .downcast_ref::<core::errors::DotsmithError>()
+            .map_or(1, |e| e.exit_code());
+        std::process::exit(code);
     }
 
     Ok(())
@@ -0,0 +1,128 @@
+use crate::core::detect::DetectedTool;
+use crate::core::manifest::Manifest;
+
+/// State for the add-tool wizard: pick a module that isn't tracked yet,
+/// detect it, then confirm what it would track.
+pub struct AddToolState {
+    /// Module names not in the manifest, sorted
+    pub candidates: Vec<String>,
+    /// Typed to narrow `candidates`, or to name a tool without a module
+    pub query: String,
+    /// Indices into `candidates` of the names containing `query`
+    pub filtered: Vec<usize>,
+    /// Position in `filtered`, not in `candidates`
+    pub selected: usize,
+    /// Detection result for the chosen tool, waiting on confirmation
+    pub preview: Option<DetectedTool>,
+}
+
+impl AddToolState {
+    /// Offer the names in `modules` that `manifest` doesn't track.
+    pub fn new(modules: &[String], manifest: &Manifest) -> Self {
+        let candidates: Vec<String> = modules
+            .iter()
+            .filter(|name| !manifest.has_tool(name))
+            .cloned()
+            .collect();
+        let filtered = (0..candidates.len()).collect();
+        Self {
+            candidates,
+            query: String::new(),
+            filtered,
+            selected: 0,
+            preview: None,
+        }
+    }
+
+    /// Narrow `filtered` to candidates containing `query`, ignoring case.
+    pub fn apply_filter(&mut self) {
+        let query = self.query.trim().to_lowercase();
+        self.filtered = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| name.to_lowercase().contains(&query))
+            .map(|(i, _)| i)
+            .collect();
+        self.selected = self.selected.min(self.filtered.len().saturating_sub(1));
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.filtered.is_empty() {
+            self.selected = (self.selected + 1).min(self.filtered.len() - 1);
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// The tool `Enter` detects: the selected candidate, or the typed name
+    /// when no candidate matches it.
+    pub fn chosen(&self) -> Option<String> {
+        match self.filtered.get(self.selected) {
+            Some(&i) => Some(self.candidates[i].clone()),
+            None => Some(self.query.trim().to_string()).filter(|q| !q.is_empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::manifest::ToolEntry;
+
+    fn state() -> AddToolState {
+        let mut manifest = Manifest::default();
+        manifest
+            .add_tool(
+                "git",
+                ToolEntry {
+                    tier: 1,
+                    config_paths: vec!["~/.gitconfig".to_string()],
+                    plugins_managed: false,
+                    plugin_manager: None,
+                    added_at: chrono::Utc::now(),
+                    last_snapshot: None,
+                    plugins: Default::default(),
+                },
+            )
+            .unwrap();
+        let modules: Vec<String> = ["git", "kitty", "neovim", "tmux"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        AddToolState::new(&modules, &manifest)
+    }
+
+    #[test]
+    fn test_candidates_exclude_tracked_tools() {
+        let state = state();
+        assert_eq!(state.candidates, ["kitty", "neovim", "tmux"]);
+        assert_eq!(state.chosen().as_deref(), Some("kitty"));
+    }
+
+    #[test]
+    fn test_filter_narrows_and_falls_back_to_typed_name() {
+        let mut state = state();
+        state.select_next();
+        state.select_next();
+        state.select_next();
+        assert_eq!(state.chosen().as_deref(), Some("tmux"));
+
+        state.query = "VIM".to_string();
+        state.apply_filter();
+        assert_eq!(state.filtered, [1]);
+        assert_eq!(state.chosen().as_deref(), Some("neovim"));
+
+        // Nothing matches: the typed name is added as-is
+        state.query = "ranger ".to_string();
+        state.apply_filter();
+        assert!(state.filtered.is_empty());
+        assert_eq!(state.chosen().as_deref(), Some("ranger"));
+
+        state.query.clear();
+        state.filtered.clear();
+        assert_eq!(state.chosen(), None);
+    }
+}
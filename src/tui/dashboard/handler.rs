@@ -1,6 +1,8 @@
 use crossterm::event::{KeyCode, KeyEvent};
 
 use super::{DashboardMode, DashboardState};
+use crate::core::detect::DetectedTool;
+use crate::tui::keymap::{Action, Keymap, View};
 
 /// Action returned by the dashboard key handler.
 pub enum DashboardAction {
@@ -20,36 +22,54 @@ pub enum DashboardAction {
     ShowPlugins(String),
     /// Sync the git repo.
     SyncRepo,
-    /// Enter add-tool input mode.
+    /// Open the add-tool wizard.
     EnterAddMode,
-    /// Add a tool by name.
-    AddTool(String),
+    /// Detect a tool picked in the wizard, to preview what it would track.
+    DetectTool(String),
+    /// Add a detected tool to the manifest.
+    AddTool(DetectedTool),
     /// Remove the selected tool.
     RemoveTool(String),
+    /// Open the key help overlay.
+    ShowHelp,
     /// User wants to quit.
     Quit,
 }
 
 /// Handle a key event in the dashboard view.
-pub fn handle_key(key: KeyEvent, state: &mut DashboardState) -> DashboardAction {
+pub fn handle_key(key: KeyEvent, state: &mut DashboardState, keymap: &Keymap) -> DashboardAction {
     match state.mode {
-        DashboardMode::Normal => handle_normal_key(key, state),
-        DashboardMode::AddInput => handle_add_input_key(key, state),
+        DashboardMode::Normal => handle_normal_key(key, state, keymap),
+        DashboardMode::AddTool => handle_add_tool_key(key, state),
+        DashboardMode::Filter => handle_filter_key(key, state),
     }
 }
 
-fn handle_normal_key(key: KeyEvent, state: &mut DashboardState) -> DashboardAction {
-    match key.code {
-        KeyCode::Char('q') | KeyCode::Esc => DashboardAction::Quit,
-        KeyCode::Char('j') | KeyCode::Down => {
+fn handle_normal_key(
+    key: KeyEvent,
+    state: &mut DashboardState,
+    keymap: &Keymap,
+) -> DashboardAction {
+    // With a filter applied, Esc clears it before it can quit
+    if key.code == KeyCode::Esc && !state.filter_query.is_empty() {
+        state.clear_filter();
+        return DashboardAction::None;
+    }
+    let Some(action) = keymap.lookup(View::Dashboard, &key) else {
+        return DashboardAction::None;
+    };
+    match action {
+        Action::Quit => DashboardAction::Quit,
+        Action::Help => DashboardAction::ShowHelp,
+        Action::Down => {
             state.select_next();
             DashboardAction::None
         }
-        KeyCode::Char('k') | KeyCode::Up => {
+        Action::Up => {
             state.select_prev();
             DashboardAction::None
         }
-        KeyCode::Char('e') | KeyCode::Enter => {
+        Action::Explore => {
             if let Some(tool) = state.selected_tool() {
                 if tool.has_option_db {
                     DashboardAction::Explore(tool.name.clone())
@@ -60,70 +80,128 @@ fn handle_normal_key(key: KeyEvent, state: &mut DashboardState) -> DashboardActi
                 DashboardAction::None
             }
         }
-        KeyCode::Char('a') => DashboardAction::EnterAddMode,
-        KeyCode::Char('x') => state
+        Action::Add => DashboardAction::EnterAddMode,
+        Action::Remove => state
             .selected_tool()
             .map(|t| DashboardAction::RemoveTool(t.name.clone()))
             .unwrap_or(DashboardAction::None),
-        KeyCode::Char('s') => DashboardAction::SnapshotAll,
-        KeyCode::Char('r') => state
+        Action::Snapshot => DashboardAction::SnapshotAll,
+        Action::Reload => state
             .selected_tool()
             .map(|t| DashboardAction::ReloadSelected(t.name.clone()))
             .unwrap_or(DashboardAction::None),
-        KeyCode::Char('d') => state
+        Action::Diff => state
             .selected_tool()
             .map(|t| DashboardAction::ShowDiff(t.name.clone()))
             .unwrap_or(DashboardAction::None),
-        KeyCode::Char('h') => state
+        Action::History => state
             .selected_tool()
             .map(|t| DashboardAction::ShowHistory(t.name.clone()))
             .unwrap_or(DashboardAction::None),
-        KeyCode::Char('p') => state
+        Action::Plugins => state
             .selected_tool()
             .map(|t| DashboardAction::ShowPlugins(t.name.clone()))
             .unwrap_or(DashboardAction::None),
-        KeyCode::Char('g') => DashboardAction::SyncRepo,
+        Action::Sync => DashboardAction::SyncRepo,
+        Action::Search => {
+            state.mode = DashboardMode::Filter;
+            DashboardAction::None
+        }
+        Action::Sort => {
+            state.cycle_sort();
+            DashboardAction::None
+        }
+        Action::SortReverse => {
+            state.reverse_sort();
+            DashboardAction::None
+        }
         _ => DashboardAction::None,
     }
 }
 
-fn handle_add_input_key(key: KeyEvent, state: &mut DashboardState) -> DashboardAction {
+fn handle_add_tool_key(key: KeyEvent, state: &mut DashboardState) -> DashboardAction {
+    let Some(ref mut add) = state.add_tool else {
+        state.mode = DashboardMode::Normal;
+        return DashboardAction::None;
+    };
+
+    // Detected: confirm adding it, or go back to the list
+    if add.preview.is_some() {
+        return match key.code {
+            KeyCode::Enter | KeyCode::Char('y' | 'Y') => {
+                let detected = add.preview.take();
+                state.mode = DashboardMode::Normal;
+                state.add_tool = None;
+                detected
+                    .map(DashboardAction::AddTool)
+                    .unwrap_or(DashboardAction::None)
+            }
+            KeyCode::Esc | KeyCode::Char('n' | 'N') => {
+                add.preview = None;
+                DashboardAction::None
+            }
+            _ => DashboardAction::None,
+        };
+    }
+
     match key.code {
         KeyCode::Esc => {
             state.mode = DashboardMode::Normal;
-            state.input_buffer.clear();
-            DashboardAction::None
+            state.add_tool = None;
+            return DashboardAction::None;
         }
         KeyCode::Enter => {
-            let tool_name = state.input_buffer.trim().to_string();
+            return add
+                .chosen()
+                .map(DashboardAction::DetectTool)
+                .unwrap_or(DashboardAction::None);
+        }
+        KeyCode::Down => add.select_next(),
+        KeyCode::Up => add.select_prev(),
+        KeyCode::Backspace => {
+            add.query.pop();
+            add.apply_filter();
+        }
+        KeyCode::Char(c) => {
+            add.query.push(c);
+            add.apply_filter();
+        }
+        _ => {}
+    }
+    DashboardAction::None
+}
+
+fn handle_filter_key(key: KeyEvent, state: &mut DashboardState) -> DashboardAction {
+    match key.code {
+        KeyCode::Esc => {
             state.mode = DashboardMode::Normal;
-            state.input_buffer.clear();
-            if tool_name.is_empty() {
-                DashboardAction::None
-            } else {
-                DashboardAction::AddTool(tool_name)
-            }
+            state.clear_filter();
         }
+        // Keep the filter and go back to acting on the narrowed list
+        KeyCode::Enter => state.mode = DashboardMode::Normal,
         KeyCode::Backspace => {
-            state.input_buffer.pop();
-            DashboardAction::None
+            state.filter_query.pop();
+            state.apply_filter();
         }
         KeyCode::Char(c) => {
-            state.input_buffer.push(c);
-            DashboardAction::None
+            state.filter_query.push(c);
+            state.apply_filter();
         }
-        _ => DashboardAction::None,
+        _ => {}
     }
+    DashboardAction::None
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::core::manifest::{Manifest, ToolEntry};
+    use crate::tui::dashboard::add::AddToolState;
     use crate::tui::dashboard::{DashboardMode, DashboardState};
     use chrono::Utc;
     use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
     use std::collections::BTreeMap;
+    use std::path::PathBuf;
 
     fn make_key(code: KeyCode) -> KeyEvent {
         KeyEvent {
@@ -160,14 +238,26 @@ mod tests {
                 plugins: BTreeMap::new(),
             },
         );
-        DashboardState::from_manifest(&m)
+        DashboardState::from_manifest(&m, std::path::Path::new("/nonexistent"))
+    }
+
+    fn sample_entry() -> ToolEntry {
+        ToolEntry {
+            tier: 1,
+            config_paths: vec![],
+            plugins_managed: false,
+            plugin_manager: None,
+            added_at: Utc::now(),
+            last_snapshot: None,
+            plugins: BTreeMap::new(),
+        }
     }
 
     #[test]
     fn test_quit_q() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('q')), &mut state),
+            handle_key(make_key(KeyCode::Char('q')), &mut state, &Keymap::default()),
             DashboardAction::Quit
         ));
     }
@@ -176,7 +266,7 @@ mod tests {
     fn test_quit_esc() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Esc), &mut state),
+            handle_key(make_key(KeyCode::Esc), &mut state, &Keymap::default()),
             DashboardAction::Quit
         ));
     }
@@ -185,7 +275,7 @@ mod tests {
     fn test_navigate_down() {
         let mut state = sample_state();
         assert_eq!(state.selected, 0);
-        handle_key(make_key(KeyCode::Char('j')), &mut state);
+        handle_key(make_key(KeyCode::Char('j')), &mut state, &Keymap::default());
         assert_eq!(state.selected, 1);
     }
 
@@ -193,21 +283,21 @@ mod tests {
     fn test_navigate_up() {
         let mut state = sample_state();
         state.selected = 1;
-        handle_key(make_key(KeyCode::Char('k')), &mut state);
+        handle_key(make_key(KeyCode::Char('k')), &mut state, &Keymap::default());
         assert_eq!(state.selected, 0);
     }
 
     #[test]
     fn test_explore_tier1() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('e')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('e')), &mut state, &Keymap::default());
         assert!(matches!(action, DashboardAction::Explore(name) if name == "tmux"));
     }
 
     #[test]
     fn test_explore_enter() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Enter), &mut state);
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
         assert!(matches!(action, DashboardAction::Explore(name) if name == "tmux"));
     }
 
@@ -215,7 +305,7 @@ mod tests {
     fn test_snapshot_all() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('s')), &mut state),
+            handle_key(make_key(KeyCode::Char('s')), &mut state, &Keymap::default()),
             DashboardAction::SnapshotAll
         ));
     }
@@ -223,28 +313,28 @@ mod tests {
     #[test]
     fn test_reload_selected() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('r')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('r')), &mut state, &Keymap::default());
         assert!(matches!(action, DashboardAction::ReloadSelected(name) if name == "tmux"));
     }
 
     #[test]
     fn test_show_diff() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('d')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('d')), &mut state, &Keymap::default());
         assert!(matches!(action, DashboardAction::ShowDiff(name) if name == "tmux"));
     }
 
     #[test]
     fn test_show_history() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('h')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('h')), &mut state, &Keymap::default());
         assert!(matches!(action, DashboardAction::ShowHistory(name) if name == "tmux"));
     }
 
     #[test]
     fn test_show_plugins() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('p')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('p')), &mut state, &Keymap::default());
         assert!(matches!(action, DashboardAction::ShowPlugins(name) if name == "tmux"));
     }
 
@@ -252,7 +342,7 @@ mod tests {
     fn test_sync_repo() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('g')), &mut state),
+            handle_key(make_key(KeyCode::Char('g')), &mut state, &Keymap::default()),
             DashboardAction::SyncRepo
         ));
     }
@@ -261,7 +351,7 @@ mod tests {
     fn test_unknown_key() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('z')), &mut state),
+            handle_key(make_key(KeyCode::Char('z')), &mut state, &Keymap::default()),
             DashboardAction::None
         ));
     }
@@ -270,63 +360,107 @@ mod tests {
     fn test_enter_add_mode() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('a')), &mut state),
+            handle_key(make_key(KeyCode::Char('a')), &mut state, &Keymap::default()),
             DashboardAction::EnterAddMode
         ));
     }
 
+    fn add_state(state: &DashboardState) -> AddToolState {
+        let modules: Vec<String> = ["git", "kitty", "tmux"].iter().map(|s| s.to_string()).collect();
+        let mut m = Manifest::default();
+        for tool in state.visible_tools() {
+            m.tools.insert(tool.name.clone(), sample_entry());
+        }
+        AddToolState::new(&modules, &m)
+    }
+
+    fn detected(name: &str) -> DetectedTool {
+        DetectedTool {
+            name: name.to_string(),
+            tier: 1,
+            config_paths: vec![PathBuf::from("/home/u/.config/kitty/kitty.conf")],
+            include_notes: vec![],
+            plugin_manager: None,
+        }
+    }
+
     #[test]
-    fn test_add_input_type_and_submit() {
+    fn test_add_tool_pick_detect_and_confirm() {
         let mut state = sample_state();
-        state.mode = DashboardMode::AddInput;
+        let keymap = Keymap::default();
+        state.add_tool = Some(add_state(&state));
+        state.mode = DashboardMode::AddTool;
 
-        handle_key(make_key(KeyCode::Char('g')), &mut state);
-        handle_key(make_key(KeyCode::Char('i')), &mut state);
-        handle_key(make_key(KeyCode::Char('t')), &mut state);
-        assert_eq!(state.input_buffer, "git");
+        // Letters narrow the list instead of acting
+        for c in ['k', 'i', 't'] {
+            handle_key(make_key(KeyCode::Char(c)), &mut state, &keymap);
+        }
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &keymap);
+        assert!(matches!(action, DashboardAction::DetectTool(ref name) if name == "kitty"));
+        assert_eq!(state.mode, DashboardMode::AddTool);
 
-        let action = handle_key(make_key(KeyCode::Enter), &mut state);
-        assert!(matches!(action, DashboardAction::AddTool(name) if name == "git"));
+        state.add_tool.as_mut().unwrap().preview = Some(detected("kitty"));
+        let action = handle_key(make_key(KeyCode::Char('j')), &mut state, &keymap);
+        assert!(matches!(action, DashboardAction::None));
+
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &keymap);
+        assert!(matches!(action, DashboardAction::AddTool(ref d) if d.name == "kitty"));
         assert_eq!(state.mode, DashboardMode::Normal);
-        assert!(state.input_buffer.is_empty());
+        assert!(state.add_tool.is_none());
     }
 
     #[test]
-    fn test_add_input_cancel() {
+    fn test_add_tool_back_and_cancel() {
         let mut state = sample_state();
-        state.mode = DashboardMode::AddInput;
-        state.input_buffer = "git".to_string();
-
-        let action = handle_key(make_key(KeyCode::Esc), &mut state);
+        let keymap = Keymap::default();
+        state.add_tool = Some(add_state(&state));
+        state.mode = DashboardMode::AddTool;
+
+        // Esc on the preview returns to the list
+        handle_key(make_key(KeyCode::Down), &mut state, &keymap);
+        state.add_tool.as_mut().unwrap().preview = Some(detected("kitty"));
+        handle_key(make_key(KeyCode::Esc), &mut state, &keymap);
+        let add = state.add_tool.as_ref().unwrap();
+        assert!(add.preview.is_none());
+        assert_eq!(add.chosen().as_deref(), Some("kitty"));
+
+        // Esc on the list closes the wizard without adding
+        let action = handle_key(make_key(KeyCode::Esc), &mut state, &keymap);
         assert!(matches!(action, DashboardAction::None));
         assert_eq!(state.mode, DashboardMode::Normal);
-        assert!(state.input_buffer.is_empty());
+        assert!(state.add_tool.is_none());
     }
 
     #[test]
-    fn test_add_input_backspace() {
+    fn test_remove_tool() {
         let mut state = sample_state();
-        state.mode = DashboardMode::AddInput;
-        state.input_buffer = "git".to_string();
-
-        handle_key(make_key(KeyCode::Backspace), &mut state);
-        assert_eq!(state.input_buffer, "gi");
+        let action = handle_key(make_key(KeyCode::Char('x')), &mut state, &Keymap::default());
+        assert!(matches!(action, DashboardAction::RemoveTool(name) if name == "tmux"));
     }
 
     #[test]
-    fn test_add_input_empty_submit() {
+    fn test_filter_mode() {
         let mut state = sample_state();
-        state.mode = DashboardMode::AddInput;
-
-        let action = handle_key(make_key(KeyCode::Enter), &mut state);
-        assert!(matches!(action, DashboardAction::None));
+        let keymap = Keymap::default();
+        handle_key(make_key(KeyCode::Char('/')), &mut state, &keymap);
+        assert_eq!(state.mode, DashboardMode::Filter);
+
+        // Letters are typed into the filter, not taken as actions
+        handle_key(make_key(KeyCode::Char('z')), &mut state, &keymap);
+        assert_eq!(state.filter_query, "z");
+        handle_key(make_key(KeyCode::Enter), &mut state, &keymap);
         assert_eq!(state.mode, DashboardMode::Normal);
-    }
 
-    #[test]
-    fn test_remove_tool() {
-        let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('x')), &mut state);
-        assert!(matches!(action, DashboardAction::RemoveTool(name) if name == "tmux"));
+        let action = handle_key(make_key(KeyCode::Char('d')), &mut state, &keymap);
+        assert!(matches!(action, DashboardAction::ShowDiff(name) if name == "zsh"));
+
+        // The first Esc clears the filter, the next quits
+        let action = handle_key(make_key(KeyCode::Esc), &mut state, &keymap);
+        assert!(matches!(action, DashboardAction::None));
+        assert_eq!(state.visible_tools().count(), 2);
+        assert!(matches!(
+            handle_key(make_key(KeyCode::Esc), &mut state, &keymap),
+            DashboardAction::Quit
+        ));
     }
 }
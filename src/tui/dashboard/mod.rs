@@ -1,11 +1,17 @@
+pub mod add;
 pub mod handler;
 pub mod view;
 
+use std::cmp::Ordering;
+use std::path::Path;
+
 use chrono::{DateTime, Utc};
 
 use crate::core::manifest::Manifest;
 use crate::core::module::ModuleRegistry;
 
+use add::AddToolState;
+
 /// A row in the dashboard table.
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -23,25 +29,66 @@ pub struct ToolRow {
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DashboardMode {
     Normal,
-    AddInput,
+    AddTool,
+    Filter,
+}
+
+/// Column the dashboard table is ordered by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Name,
+    Tier,
+    Plugins,
+    /// Oldest snapshot first; never-snapshotted tools count as oldest
+    LastSnapshot,
+}
+
+impl SortKey {
+    /// The key after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Name => SortKey::Tier,
+            SortKey::Tier => SortKey::Plugins,
+            SortKey::Plugins => SortKey::LastSnapshot,
+            SortKey::LastSnapshot => SortKey::Name,
+        }
+    }
+
+    fn compare(self, a: &ToolRow, b: &ToolRow) -> Ordering {
+        match self {
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::Tier => a.tier.cmp(&b.tier),
+            SortKey::Plugins => a.plugin_count.cmp(&b.plugin_count),
+            // `None < Some(_)`, so tools never snapshotted sort as oldest
+            SortKey::LastSnapshot => a.last_snapshot.cmp(&b.last_snapshot),
+        }
+    }
 }
 
 /// State for the dashboard view.
 pub struct DashboardState {
     pub tools: Vec<ToolRow>,
+    /// Indices into `tools` of the rows matching `filter_query`, in order
+    pub filtered: Vec<usize>,
+    /// Position in `filtered`, not in `tools`
     pub selected: usize,
+    pub filter_query: String,
+    pub sort_key: SortKey,
+    pub sort_desc: bool,
     pub mode: DashboardMode,
-    pub input_buffer: String,
+    /// The add-tool wizard, while `mode` is `AddTool`
+    pub add_tool: Option<AddToolState>,
 }
 
 impl DashboardState {
-    /// Build dashboard state from a loaded manifest.
-    pub fn from_manifest(manifest: &Manifest) -> Self {
+    /// Build dashboard state from a loaded manifest. `config_dir` is where
+    /// user-defined option databases are looked up.
+    pub fn from_manifest(manifest: &Manifest, config_dir: &Path) -> Self {
         let tools: Vec<ToolRow> = manifest
             .tools
             .iter()
             .map(|(name, entry)| {
-                let has_option_db = ModuleRegistry::get_options(name).is_some();
+                let has_option_db = ModuleRegistry::load_options(config_dir, name).is_some();
                 let tier_label = match entry.tier {
                     1 => "Full".to_string(),
                     2 => "Auto".to_string(),
@@ -59,17 +106,77 @@ impl DashboardState {
             })
             .collect();
 
+        let filtered = (0..tools.len()).collect();
         Self {
             tools,
+            filtered,
             selected: 0,
+            filter_query: String::new(),
+            sort_key: SortKey::Name,
+            sort_desc: false,
             mode: DashboardMode::Normal,
-            input_buffer: String::new(),
+            add_tool: None,
         }
     }
 
+    /// Narrow `filtered` to tools whose name contains `filter_query`,
+    /// ignoring case, and keep the selection inside the shorter list.
+    pub fn apply_filter(&mut self) {
+        let query = self.filter_query.to_lowercase();
+        self.filtered = self
+            .tools
+            .iter()
+            .enumerate()
+            .filter(|(_, tool)| tool.name.to_lowercase().contains(&query))
+            .map(|(i, _)| i)
+            .collect();
+        self.selected = self.selected.min(self.filtered.len().saturating_sub(1));
+    }
+
+    /// Order `tools` by `sort_key` and `sort_desc`, ties broken by name. The
+    /// selected tool stays selected wherever it moves.
+    pub fn apply_sort(&mut self) {
+        let selected = self.selected_tool().map(|t| t.name.clone());
+        let (key, desc) = (self.sort_key, self.sort_desc);
+        self.tools.sort_by(|a, b| {
+            let order = key.compare(a, b);
+            let order = if desc { order.reverse() } else { order };
+            order.then_with(|| a.name.cmp(&b.name))
+        });
+        self.apply_filter();
+        let moved_to = selected.and_then(|name| self.visible_tools().position(|t| t.name == name));
+        if let Some(pos) = moved_to {
+            self.selected = pos;
+        }
+    }
+
+    /// Sort by the next column, ascending.
+    pub fn cycle_sort(&mut self) {
+        self.sort_key = self.sort_key.next();
+        self.sort_desc = false;
+        self.apply_sort();
+    }
+
+    /// Flip the direction of the current sort.
+    pub fn reverse_sort(&mut self) {
+        self.sort_desc = !self.sort_desc;
+        self.apply_sort();
+    }
+
+    /// Drop the filter and show every tool again.
+    pub fn clear_filter(&mut self) {
+        self.filter_query.clear();
+        self.apply_filter();
+    }
+
+    /// The rows shown in the table, in order.
+    pub fn visible_tools(&self) -> impl Iterator<Item = &ToolRow> {
+        self.filtered.iter().map(|&i| &self.tools[i])
+    }
+
     pub fn select_next(&mut self) {
-        if !self.tools.is_empty() {
-            self.selected = (self.selected + 1).min(self.tools.len() - 1);
+        if !self.filtered.is_empty() {
+            self.selected = (self.selected + 1).min(self.filtered.len() - 1);
         }
     }
 
@@ -79,7 +186,7 @@ impl DashboardState {
 
     /// Get the currently selected tool name, if any.
     pub fn selected_tool(&self) -> Option<&ToolRow> {
-        self.tools.get(self.selected)
+        self.filtered.get(self.selected).map(|&i| &self.tools[i])
     }
 }
 
@@ -125,7 +232,7 @@ mod tests {
     #[test]
     fn test_from_manifest() {
         let manifest = sample_manifest();
-        let state = DashboardState::from_manifest(&manifest);
+        let state = DashboardState::from_manifest(&manifest, Path::new("/nonexistent"));
         assert_eq!(state.tools.len(), 2);
         assert_eq!(state.selected, 0);
     }
@@ -133,7 +240,7 @@ mod tests {
     #[test]
     fn test_tool_rows() {
         let manifest = sample_manifest();
-        let state = DashboardState::from_manifest(&manifest);
+        let state = DashboardState::from_manifest(&manifest, Path::new("/nonexistent"));
 
         let tmux = state.tools.iter().find(|t| t.name == "tmux").unwrap();
         assert_eq!(tmux.tier, 1);
@@ -150,7 +257,7 @@ mod tests {
     #[test]
     fn test_navigation() {
         let manifest = sample_manifest();
-        let mut state = DashboardState::from_manifest(&manifest);
+        let mut state = DashboardState::from_manifest(&manifest, Path::new("/nonexistent"));
 
         assert_eq!(state.selected, 0);
         state.select_next();
@@ -166,9 +273,134 @@ mod tests {
     #[test]
     fn test_empty_manifest() {
         let manifest = Manifest::default();
-        let state = DashboardState::from_manifest(&manifest);
+        let state = DashboardState::from_manifest(&manifest, Path::new("/nonexistent"));
         assert!(state.tools.is_empty());
         assert_eq!(state.selected, 0);
         assert!(state.selected_tool().is_none());
     }
+
+    fn state_with(names: &[&str]) -> DashboardState {
+        let mut m = Manifest::default();
+        for name in names {
+            m.tools.insert(
+                name.to_string(),
+                ToolEntry {
+                    tier: 2,
+                    config_paths: vec![format!("~/.config/{}/config", name)],
+                    plugins_managed: false,
+                    plugin_manager: None,
+                    added_at: Utc::now(),
+                    last_snapshot: None,
+                    plugins: BTreeMap::new(),
+                },
+            );
+        }
+        DashboardState::from_manifest(&m, Path::new("/nonexistent"))
+    }
+
+    #[test]
+    fn test_filter_by_name() {
+        let mut state = state_with(&["alacritty", "kitty", "tmux", "zsh"]);
+        state.filter_query = "TT".to_string();
+        state.apply_filter();
+
+        let names: Vec<&str> = state.visible_tools().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["alacritty", "kitty"]);
+
+        // Actions act on the filtered row, not on the same position in `tools`
+        state.select_next();
+        assert_eq!(state.selected_tool().unwrap().name, "kitty");
+        state.select_next();
+        assert_eq!(state.selected_tool().unwrap().name, "kitty");
+    }
+
+    #[test]
+    fn test_filter_clamps_selection() {
+        let mut state = state_with(&["alacritty", "kitty", "tmux", "zsh"]);
+        state.selected = 3;
+        state.filter_query = "k".to_string();
+        state.apply_filter();
+        assert_eq!(state.selected, 0);
+        assert_eq!(state.selected_tool().unwrap().name, "kitty");
+
+        state.filter_query = "nothing".to_string();
+        state.apply_filter();
+        assert_eq!(state.selected, 0);
+        assert!(state.selected_tool().is_none());
+        state.select_next();
+        assert_eq!(state.selected, 0);
+    }
+
+    #[test]
+    fn test_clear_filter() {
+        let mut state = state_with(&["alacritty", "kitty", "tmux", "zsh"]);
+        state.filter_query = "zsh".to_string();
+        state.apply_filter();
+        assert_eq!(state.visible_tools().count(), 1);
+
+        state.clear_filter();
+        assert!(state.filter_query.is_empty());
+        assert_eq!(state.visible_tools().count(), 4);
+        assert_eq!(state.selected_tool().unwrap().name, "alacritty");
+    }
+
+    fn row(name: &str, tier: u8, plugin_count: usize, snapshot_days_ago: Option<i64>) -> ToolRow {
+        ToolRow {
+            name: name.to_string(),
+            tier,
+            tier_label: String::new(),
+            config_path_count: 1,
+            plugin_count,
+            last_snapshot: snapshot_days_ago.map(|d| Utc::now() - chrono::Duration::days(d)),
+            has_option_db: false,
+        }
+    }
+
+    fn sorted(key: SortKey, desc: bool) -> Vec<String> {
+        let mut state = state_with(&[]);
+        state.tools = vec![
+            row("zsh", 1, 3, Some(1)),
+            row("kitty", 2, 0, None),
+            row("tmux", 1, 5, Some(30)),
+            row("bat", 2, 0, Some(7)),
+        ];
+        state.sort_key = key;
+        state.sort_desc = desc;
+        state.apply_sort();
+        state.visible_tools().map(|t| t.name.clone()).collect()
+    }
+
+    #[test]
+    fn test_sort_by_each_key() {
+        assert_eq!(sorted(SortKey::Name, false), ["bat", "kitty", "tmux", "zsh"]);
+        assert_eq!(sorted(SortKey::Name, true), ["zsh", "tmux", "kitty", "bat"]);
+        // Ties fall back to name order in both directions
+        assert_eq!(sorted(SortKey::Tier, false), ["tmux", "zsh", "bat", "kitty"]);
+        assert_eq!(sorted(SortKey::Tier, true), ["bat", "kitty", "tmux", "zsh"]);
+        assert_eq!(sorted(SortKey::Plugins, true), ["tmux", "zsh", "bat", "kitty"]);
+    }
+
+    #[test]
+    fn test_sort_by_last_snapshot_never_is_oldest() {
+        assert_eq!(sorted(SortKey::LastSnapshot, false), ["kitty", "tmux", "bat", "zsh"]);
+        assert_eq!(sorted(SortKey::LastSnapshot, true), ["zsh", "bat", "tmux", "kitty"]);
+    }
+
+    #[test]
+    fn test_sort_keeps_selection_and_filter() {
+        let mut state = state_with(&["alacritty", "kitty", "tmux", "zsh"]);
+        state.filter_query = "t".to_string();
+        state.apply_filter();
+        state.select_next();
+        assert_eq!(state.selected_tool().unwrap().name, "kitty");
+
+        state.reverse_sort();
+        let names: Vec<&str> = state.visible_tools().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["tmux", "kitty", "alacritty"]);
+        assert_eq!(state.selected_tool().unwrap().name, "kitty");
+
+        state.cycle_sort();
+        assert_eq!(state.sort_key, SortKey::Tier);
+        assert!(!state.sort_desc);
+    }
 }
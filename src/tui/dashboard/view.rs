@@ -7,10 +7,14 @@ use ratatui::{
     widgets::{Block, Borders, Cell, Row, Table},
 };
 
-use super::{DashboardMode, DashboardState};
+use super::add::AddToolState;
+use super::{DashboardMode, DashboardState, SortKey};
+use crate::tui::keymap::{Action, Keymap, View};
+use crate::tui::widgets::centered;
 use crate::tui::widgets::help_bar::{HelpBar, HelpItem};
+use crate::util;
 
-pub fn draw_dashboard(f: &mut Frame, area: Rect, state: &DashboardState) {
+pub fn draw_dashboard(f: &mut Frame, area: Rect, state: &DashboardState, keymap: &Keymap) {
     let chunks = Layout::vertical([
         Constraint::Min(5),    // table
         Constraint::Length(1), // input / help bar
@@ -19,25 +23,37 @@ pub fn draw_dashboard(f: &mut Frame, area: Rect, state: &DashboardState) {
 
     draw_table(f, chunks[0], state);
 
-    match state.mode {
-        DashboardMode::Normal => draw_help(f, chunks[1]),
-        DashboardMode::AddInput => draw_add_input(f, chunks[1], state),
+    match (state.mode, &state.add_tool) {
+        (DashboardMode::AddTool, Some(add)) => {
+            draw_add_popup(f, chunks[0], add);
+            draw_add_input(f, chunks[1], add);
+        }
+        (DashboardMode::Filter, _) => draw_filter_input(f, chunks[1], state),
+        _ => draw_help(f, chunks[1], keymap),
     }
 }
 
 fn draw_table(f: &mut Frame, area: Rect, state: &DashboardState) {
+    // Mark the sorted column with its direction
+    let heading = |label: &str, key: Option<SortKey>| {
+        if key == Some(state.sort_key) {
+            let arrow = if state.sort_desc { "▼" } else { "▲" };
+            Cell::from(format!("{} {}", label, arrow))
+        } else {
+            Cell::from(label.to_string())
+        }
+    };
     let header = Row::new(vec![
-        Cell::from("Tool"),
-        Cell::from("Tier"),
-        Cell::from("Paths"),
-        Cell::from("Plugins"),
-        Cell::from("Last Snapshot"),
+        heading("Tool", Some(SortKey::Name)),
+        heading("Tier", Some(SortKey::Tier)),
+        heading("Paths", None),
+        heading("Plugins", Some(SortKey::Plugins)),
+        heading("Last Snapshot", Some(SortKey::LastSnapshot)),
     ])
     .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
 
     let rows: Vec<Row> = state
-        .tools
-        .iter()
+        .visible_tools()
         .enumerate()
         .map(|(i, tool)| {
             let style = if i == state.selected {
@@ -76,13 +92,22 @@ fn draw_table(f: &mut Frame, area: Rect, state: &DashboardState) {
         Constraint::Min(15),
     ];
 
+    let mut title = vec![Span::styled(
+        " dotsmith ",
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+    )];
+    if !state.filter_query.is_empty() {
+        title.push(Span::styled(
+            format!("[{}/{}] \"{}\" ", state.filtered.len(), state.tools.len(), state.filter_query),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
     let table = Table::new(rows, widths)
         .header(header)
         .block(
             Block::default()
-                .title(Line::from(vec![
-                    Span::styled(" dotsmith ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-                ]))
+                .title(title)
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Color::DarkGray)),
         )
@@ -91,28 +116,191 @@ fn draw_table(f: &mut Frame, area: Rect, state: &DashboardState) {
     f.render_widget(table, area);
 }
 
-fn draw_help(f: &mut Frame, area: Rect) {
-    let help = HelpBar::new(vec![
-        HelpItem { key: "a", action: "add" },
-        HelpItem { key: "x", action: "remove" },
-        HelpItem { key: "j/k", action: "navigate" },
-        HelpItem { key: "e", action: "explore" },
-        HelpItem { key: "s", action: "snapshot" },
-        HelpItem { key: "d", action: "diff" },
-        HelpItem { key: "h", action: "history" },
-        HelpItem { key: "p", action: "plugins" },
-        HelpItem { key: "g", action: "sync repo" },
-        HelpItem { key: "q", action: "quit" },
-    ]);
-    f.render_widget(help, area);
+/// Every key of the dashboard, shown in the help bar and the `?` overlay.
+pub fn help_items(keymap: &Keymap) -> Vec<HelpItem> {
+    vec![
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Add]),
+            action: "add",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Remove]),
+            action: "remove",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Down, Action::Up]),
+            action: "navigate",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Explore]),
+            action: "explore",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Search]),
+            action: "filter",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Sort, Action::SortReverse]),
+            action: "sort",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Snapshot]),
+            action: "snapshot",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Reload]),
+            action: "reload",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Diff]),
+            action: "diff",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::History]),
+            action: "history",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Plugins]),
+            action: "plugins",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Sync]),
+            action: "sync repo",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Help]),
+            action: "help",
+        },
+        HelpItem {
+            key: keymap.hint(View::Dashboard, &[Action::Quit]),
+            action: "quit",
+        },
+    ]
+}
+
+fn draw_help(f: &mut Frame, area: Rect, keymap: &Keymap) {
+    f.render_widget(HelpBar::new(help_items(keymap)), area);
+}
+
+fn draw_add_input(f: &mut Frame, area: Rect, add: &AddToolState) {
+    use ratatui::widgets::Paragraph;
+
+    let hint = Style::default().fg(Color::DarkGray);
+    let line = match add.preview {
+        Some(ref detected) => Line::from(vec![
+            Span::styled(format!("Add {}? ", detected.name), Style::default().fg(Color::Yellow)),
+            Span::styled("[y] add  [Esc] back", hint),
+        ]),
+        None => Line::from(vec![
+            Span::styled("Add tool: ", Style::default().fg(Color::Yellow)),
+            Span::raw(&add.query),
+            Span::styled("_", Style::default().fg(Color::Yellow)),
+            Span::styled("  up/down pick, Enter detect, Esc cancel", hint),
+        ]),
+    };
+    f.render_widget(Paragraph::new(line), area);
+}
+
+/// The wizard popup: modules to pick from, or what the picked one would
+/// track once detected.
+fn draw_add_popup(f: &mut Frame, area: Rect, add: &AddToolState) {
+    use ratatui::widgets::{Clear, Paragraph};
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let (title, lines) = match add.preview {
+        Some(ref detected) => {
+            let tier = match detected.tier {
+                1 => "Tier 1, full support",
+                2 => "Tier 2, basic tracking",
+                _ => "Tier 3, user-enriched",
+            };
+            let mut lines = vec![
+                Line::styled(tier, dim),
+                Line::raw(format!("Tracking {} config path(s):", detected.config_paths.len())),
+            ];
+            lines.extend(
+                detected
+                    .config_paths
+                    .iter()
+                    .map(|p| Line::raw(format!("  {}", util::paths::contract_tilde(p)))),
+            );
+            if let Some(ref pm) = detected.plugin_manager {
+                lines.push(Line::raw(format!("Plugin manager: {}", pm)));
+            }
+            lines.extend(
+                detected
+                    .include_notes
+                    .iter()
+                    .map(|note| Line::styled(format!("not tracking include: {}", note), dim)),
+            );
+            (format!(" Add {} ", detected.name), lines)
+        }
+        None if add.filtered.is_empty() => {
+            let note = if add.query.trim().is_empty() {
+                "Every module is tracked; type a tool name"
+            } else {
+                "No module matches; Enter detects the name as typed"
+            };
+            (" Add tool ".to_string(), vec![Line::styled(note, dim)])
+        }
+        None => {
+            let lines = add
+                .filtered
+                .iter()
+                .enumerate()
+                .map(|(pos, &i)| {
+                    let name = add.candidates[i].as_str();
+                    if pos == add.selected {
+                        Line::styled(
+                            format!("> {}", name),
+                            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                        )
+                    } else {
+                        Line::raw(format!("  {}", name))
+                    }
+                })
+                .collect();
+            (" Add tool ".to_string(), lines)
+        }
+    };
+
+    let width = lines
+        .iter()
+        .map(|line| line.width())
+        .chain([title.len()])
+        .max()
+        .unwrap_or(0)
+        + 4;
+    let popup = centered(area, width as u16, lines.len() as u16 + 2);
+    let block = Block::default()
+        .title(Span::styled(
+            title,
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    // Keep the selection in view when the list is taller than the popup
+    let inner_height = popup.height.saturating_sub(2) as usize;
+    let scroll = if add.preview.is_none() {
+        (add.selected + 1).saturating_sub(inner_height)
+    } else {
+        0
+    };
+
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines).block(block).scroll((scroll as u16, 0)),
+        popup,
+    );
 }
 
-fn draw_add_input(f: &mut Frame, area: Rect, state: &DashboardState) {
+fn draw_filter_input(f: &mut Frame, area: Rect, state: &DashboardState) {
     use ratatui::widgets::Paragraph;
 
     let line = Line::from(vec![
-        Span::styled("Add tool: ", Style::default().fg(Color::Yellow)),
-        Span::raw(&state.input_buffer),
+        Span::styled("/", Style::default().fg(Color::Yellow)),
+        Span::raw(&state.filter_query),
         Span::styled("_", Style::default().fg(Color::Yellow)),
     ]);
     f.render_widget(Paragraph::new(line), area);
@@ -1,41 +1,74 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::KeyEvent;
 
 use super::DiffState;
+use crate::tui::keymap::{Action, Keymap, View};
 
 pub enum DiffAction {
     None,
     Back,
+    ShowHelp,
+    /// Roll back the marked hunks.
+    RollbackHunks,
     Quit,
 }
 
-pub fn handle_key(key: KeyEvent, state: &mut DiffState) -> DiffAction {
-    match key.code {
-        KeyCode::Char('q') => DiffAction::Quit,
-        KeyCode::Esc => DiffAction::Back,
-        KeyCode::Char('j') | KeyCode::Down => {
+pub fn handle_key(key: KeyEvent, state: &mut DiffState, keymap: &Keymap) -> DiffAction {
+    let Some(action) = keymap.lookup(View::Diff, &key) else {
+        return DiffAction::None;
+    };
+    match action {
+        Action::Quit => DiffAction::Quit,
+        Action::Help => DiffAction::ShowHelp,
+        Action::Back => DiffAction::Back,
+        Action::Down => {
             state.scroll_down();
             DiffAction::None
         }
-        KeyCode::Char('k') | KeyCode::Up => {
+        Action::Up => {
             state.scroll_up();
             DiffAction::None
         }
-        KeyCode::Char('d') | KeyCode::PageDown => {
+        Action::PageDown => {
             state.page_down();
             DiffAction::None
         }
-        KeyCode::Char('u') | KeyCode::PageUp => {
+        Action::PageUp => {
             state.page_up();
             DiffAction::None
         }
-        KeyCode::Char('g') | KeyCode::Home => {
+        Action::Top => {
             state.scroll_to_top();
             DiffAction::None
         }
-        KeyCode::Char('G') | KeyCode::End => {
+        Action::Bottom => {
             state.scroll_to_end();
             DiffAction::None
         }
+        Action::Expand => {
+            state.expand_fold_in_view();
+            DiffAction::None
+        }
+        Action::FoldAll => {
+            state.toggle_all_folds();
+            DiffAction::None
+        }
+        Action::Split => {
+            state.toggle_mode();
+            DiffAction::None
+        }
+        Action::FocusNext => {
+            state.next_hunk();
+            DiffAction::None
+        }
+        Action::FocusPrev => {
+            state.prev_hunk();
+            DiffAction::None
+        }
+        Action::Toggle => {
+            state.toggle_hunk();
+            DiffAction::None
+        }
+        Action::Rollback => DiffAction::RollbackHunks,
         _ => DiffAction::None,
     }
 }
@@ -43,7 +76,7 @@ pub fn handle_key(key: KeyEvent, state: &mut DiffState) -> DiffAction {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tui::diff::DiffState;
+    use crate::tui::diff::{DiffState, DiffViewMode};
     use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
 
     fn make_key(code: KeyCode) -> KeyEvent {
@@ -63,7 +96,7 @@ mod tests {
     fn test_quit() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('q')), &mut state),
+            handle_key(make_key(KeyCode::Char('q')), &mut state, &Keymap::default()),
             DiffAction::Quit
         ));
     }
@@ -72,7 +105,7 @@ mod tests {
     fn test_back() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Esc), &mut state),
+            handle_key(make_key(KeyCode::Esc), &mut state, &Keymap::default()),
             DiffAction::Back
         ));
     }
@@ -81,7 +114,54 @@ mod tests {
     fn test_scroll() {
         let mut state = sample_state();
         state.visible_height = 2;
-        handle_key(make_key(KeyCode::Char('j')), &mut state);
+        handle_key(make_key(KeyCode::Char('j')), &mut state, &Keymap::default());
         assert!(state.scroll_offset > 0 || state.lines.len() <= state.visible_height);
     }
+
+    #[test]
+    fn test_fold_keys() {
+        let old: String = (1..=20).map(|i| format!("{}\n", i)).collect();
+        let new = format!("{}x\n", old);
+        let mut state = DiffState::from_strings("tmux", "tmux.conf", &old, &new);
+        assert!(state.folds[0].collapsed);
+
+        handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
+        assert!(!state.folds[0].collapsed);
+
+        handle_key(make_key(KeyCode::Char('z')), &mut state, &Keymap::default());
+        assert!(state.folds[0].collapsed);
+    }
+
+    #[test]
+    fn test_toggle_side_by_side() {
+        let mut state = sample_state();
+        assert_eq!(state.mode, DiffViewMode::Unified);
+        handle_key(make_key(KeyCode::Char('s')), &mut state, &Keymap::default());
+        assert_eq!(state.mode, DiffViewMode::SideBySide);
+        handle_key(make_key(KeyCode::Char('s')), &mut state, &Keymap::default());
+        assert_eq!(state.mode, DiffViewMode::Unified);
+    }
+
+    #[test]
+    fn test_mark_hunks() {
+        let mut state =
+            DiffState::from_strings("tmux", "tmux.conf", "a\nb\nc\nd\n", "A\nb\nc\nD\n");
+        assert_eq!(state.hunks.len(), 2);
+        let keymap = Keymap::default();
+
+        handle_key(make_key(KeyCode::Char(' ')), &mut state, &keymap);
+        handle_key(make_key(KeyCode::Char('n')), &mut state, &keymap);
+        assert_eq!(state.current_hunk, 1);
+        handle_key(make_key(KeyCode::Char(' ')), &mut state, &keymap);
+        assert_eq!(state.selected.iter().copied().collect::<Vec<_>>(), [0, 1]);
+
+        // Space again unmarks
+        handle_key(make_key(KeyCode::Char(' ')), &mut state, &keymap);
+        assert_eq!(state.selected.iter().copied().collect::<Vec<_>>(), [0]);
+
+        assert!(matches!(
+            handle_key(make_key(KeyCode::Char('r')), &mut state, &keymap),
+            DiffAction::RollbackHunks
+        ));
+    }
 }
@@ -1,16 +1,26 @@
 pub mod handler;
 pub mod view;
 
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::Result;
 use similar::ChangeTag;
 
 use crate::core::manifest::Manifest;
-use crate::core::snapshot::{FileDiff, SnapshotEngine};
+use crate::core::profile;
+use crate::core::snapshot::{FileDiff, SnapshotContent, SnapshotEngine};
 
 /// A parsed diff line for TUI rendering.
 #[derive(Debug, Clone)]
 pub struct DiffLine {
     pub kind: DiffLineKind,
     pub content: String,
+    /// Byte ranges of `content` (after the +/- prefix) flagged true where the
+    /// words changed relative to the paired line. Empty when the whole line
+    /// is uniformly added, removed or unchanged.
+    pub emphasis: Vec<(Range<usize>, bool)>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -23,10 +33,82 @@ pub enum DiffLineKind {
     Empty,
 }
 
+/// Context runs longer than this many lines are collapsed by default.
+pub const FOLD_THRESHOLD: usize = 8;
+
+/// A run of consecutive context lines that can be collapsed into one marker row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fold {
+    /// Index of the first context line in `DiffState::lines`.
+    pub start: usize,
+    pub len: usize,
+    pub collapsed: bool,
+}
+
+/// A single rendered row: either a diff line or a collapsed fold marker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffRow {
+    /// Index into `DiffState::lines`.
+    Line(usize),
+    /// Index into `DiffState::folds`.
+    Fold(usize),
+}
+
+/// A row in the side-by-side layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitRow {
+    /// Spans both panels: hunk headers, file separators and fold markers.
+    Full(DiffRow),
+    /// Old line on the left, new line on the right (indices into
+    /// `DiffState::lines`). `None` leaves that side blank.
+    Pair {
+        left: Option<usize>,
+        right: Option<usize>,
+    },
+}
+
+/// A run of changed lines that can be rolled back on its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    /// Index into `DiffState::files`.
+    pub file: usize,
+    /// Index into that file's [`FileDiff::hunks`].
+    pub index: usize,
+    /// Its removed and added lines, as indices into `DiffState::lines`.
+    pub lines: Range<usize>,
+}
+
+/// How the diff view lays out changes.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DiffViewMode {
+    #[default]
+    Unified,
+    SideBySide,
+}
+
+impl DiffViewMode {
+    /// Status bar label for this sub-mode.
+    pub fn label(self) -> &'static str {
+        match self {
+            DiffViewMode::Unified => "DIFF",
+            DiffViewMode::SideBySide => "DIFF SPLIT",
+        }
+    }
+}
+
 /// State for the diff view.
 pub struct DiffState {
     pub tool_name: String,
+    /// The diffs rendered, kept so selected hunks can be rolled back.
+    pub files: Vec<FileDiff>,
     pub lines: Vec<DiffLine>,
+    pub folds: Vec<Fold>,
+    pub hunks: Vec<Hunk>,
+    /// Index into `hunks` that `toggle` marks.
+    pub current_hunk: usize,
+    /// Hunks marked for rollback, as indices into `hunks`.
+    pub selected: BTreeSet<usize>,
+    pub mode: DiffViewMode,
     pub scroll_offset: usize,
     pub visible_height: usize,
     pub total_files: usize,
@@ -42,43 +124,336 @@ impl DiffState {
             .map(|e| engine.diff_current(tool, &e.config_paths).unwrap_or_default())
             .unwrap_or_default();
 
-        let lines = build_diff_lines(&diffs);
-        let total_files = diffs.len();
         let has_changes = !diffs.is_empty();
+        Self::new(tool, diffs, has_changes)
+    }
 
-        Self {
-            tool_name: tool.to_string(),
-            lines,
-            scroll_offset: 0,
-            visible_height: 20,
-            total_files,
-            has_changes,
-        }
+    /// Build diff state from a tool's current files vs the copies saved in
+    /// profile `profile_name`.
+    pub fn from_profile(tool: &str, config_dir: &Path, profile_name: &str) -> Result<Self> {
+        let diffs = profile::diff_profile_tool(config_dir, profile_name, tool)?;
+        let has_changes = !diffs.is_empty();
+        Ok(Self::new(tool, diffs, has_changes))
     }
 
-    /// Build diff state from two known strings (for viewing a snapshot diff).
+    /// Build diff state from two known strings.
+    #[cfg(test)]
     pub fn from_strings(tool: &str, file_path: &str, old: &str, new: &str) -> Self {
-        let diffs = vec![FileDiff {
-            file_path: file_path.to_string(),
-            old_content: old.to_string(),
-            new_content: new.to_string(),
+        Self::from_contents(
+            tool,
+            file_path,
+            &SnapshotContent::Text(old.to_string()),
+            &SnapshotContent::Text(new.to_string()),
+        )
+    }
+
+    /// Build diff state from snapshot contents, which may be binary.
+    pub fn from_contents(
+        tool: &str,
+        file_path: &str,
+        old: &SnapshotContent,
+        new: &SnapshotContent,
+    ) -> Self {
+        let diffs = vec![match (old, new) {
+            (SnapshotContent::Text(old), SnapshotContent::Text(new)) => FileDiff {
+                file_path: file_path.to_string(),
+                old_content: old.clone(),
+                new_content: new.clone(),
+                binary: false,
+            },
+            _ => FileDiff {
+                file_path: file_path.to_string(),
+                old_content: String::new(),
+                new_content: String::new(),
+                binary: true,
+            },
         }];
 
-        let lines = build_diff_lines(&diffs);
-        let has_changes = old != new;
+        Self::new(tool, diffs, old != new)
+    }
 
-        Self {
+    fn new(tool: &str, files: Vec<FileDiff>, has_changes: bool) -> Self {
+        let mut state = Self {
             tool_name: tool.to_string(),
-            lines,
+            total_files: files.len(),
+            files,
+            lines: Vec::new(),
+            folds: Vec::new(),
+            hunks: Vec::new(),
+            current_hunk: 0,
+            selected: BTreeSet::new(),
+            mode: DiffViewMode::Unified,
             scroll_offset: 0,
             visible_height: 20,
-            total_files: 1,
             has_changes,
+        };
+        state.rebuild();
+        state
+    }
+
+    /// Recompute lines, folds and hunks from `files`, dropping the selection.
+    fn rebuild(&mut self) {
+        (self.lines, self.hunks) = if self.has_changes {
+            build_diff_lines(&self.files, None)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        self.folds = find_folds(&self.lines, FOLD_THRESHOLD);
+        self.selected.clear();
+        self.current_hunk = self.current_hunk.min(self.hunks.len().saturating_sub(1));
+        self.clamp_scroll();
+    }
+
+    /// The hunk that line `i` of `lines` belongs to, if it is a change.
+    pub fn hunk_at(&self, i: usize) -> Option<usize> {
+        self.hunks.iter().position(|h| h.lines.contains(&i))
+    }
+
+    /// Mark or unmark the current hunk for rollback.
+    pub fn toggle_hunk(&mut self) {
+        if self.current_hunk >= self.hunks.len() {
+            return;
         }
+        if !self.selected.remove(&self.current_hunk) {
+            self.selected.insert(self.current_hunk);
+        }
+    }
+
+    /// Move to the next hunk and scroll it into view.
+    pub fn next_hunk(&mut self) {
+        if self.current_hunk + 1 < self.hunks.len() {
+            self.current_hunk += 1;
+        }
+        self.scroll_to_hunk();
+    }
+
+    /// Move to the previous hunk and scroll it into view.
+    pub fn prev_hunk(&mut self) {
+        self.current_hunk = self.current_hunk.saturating_sub(1);
+        self.scroll_to_hunk();
+    }
+
+    /// Scroll so the current hunk's first line is on screen, with a couple
+    /// of rows of context above it when it had to move.
+    fn scroll_to_hunk(&mut self) {
+        let Some(hunk) = self.hunks.get(self.current_hunk) else {
+            return;
+        };
+        let line = hunk.lines.start;
+        let row = match self.mode {
+            DiffViewMode::Unified => {
+                self.rows().iter().position(|r| *r == DiffRow::Line(line))
+            }
+            DiffViewMode::SideBySide => self.split_rows().iter().position(|r| {
+                matches!(r, SplitRow::Pair { left, right }
+                    if *left == Some(line) || *right == Some(line))
+            }),
+        };
+        if let Some(row) = row
+            && (row < self.scroll_offset || row >= self.scroll_offset + self.page_size())
+        {
+            self.scroll_offset = row.saturating_sub(2).min(self.max_scroll());
+        }
+    }
+
+    /// The marked hunks grouped by file: indices into `files`, each with
+    /// its chosen [`FileDiff::hunks`] indices.
+    pub fn selected_by_file(&self) -> Vec<(usize, BTreeSet<usize>)> {
+        let mut by_file: Vec<(usize, BTreeSet<usize>)> = Vec::new();
+        for hunk in self.selected.iter().map(|&h| &self.hunks[h]) {
+            match by_file.last_mut() {
+                Some((file, chosen)) if *file == hunk.file => {
+                    chosen.insert(hunk.index);
+                }
+                _ => by_file.push((hunk.file, BTreeSet::from([hunk.index]))),
+            }
+        }
+        by_file
+    }
+
+    /// Replace the new side of a file after some of its hunks were rolled
+    /// back, and redraw the diff from it.
+    pub fn set_new_content(&mut self, file: usize, content: String) {
+        self.files[file].new_content = content;
+        self.has_changes = self
+            .files
+            .iter()
+            .any(|f| f.binary || f.old_content != f.new_content);
+        self.rebuild();
+    }
+
+    /// Rows as rendered, with collapsed folds replaced by a single marker row.
+    pub fn rows(&self) -> Vec<DiffRow> {
+        let mut rows = Vec::with_capacity(self.lines.len());
+        let mut folds = self.folds.iter().enumerate().peekable();
+        let mut i = 0;
+
+        while i < self.lines.len() {
+            if let Some(&(fi, fold)) = folds.peek()
+                && fold.start == i
+            {
+                folds.next();
+                if fold.collapsed {
+                    rows.push(DiffRow::Fold(fi));
+                    i += fold.len;
+                    continue;
+                }
+            }
+            rows.push(DiffRow::Line(i));
+            i += 1;
+        }
+
+        rows
+    }
+
+    /// Rows for the side-by-side layout. Context lines sit on both sides of
+    /// one row; a run of removed lines is paired row by row with the added
+    /// lines that follow it, and whichever side runs out is left blank.
+    pub fn split_rows(&self) -> Vec<SplitRow> {
+        let mut split = Vec::new();
+        let mut removed: Vec<usize> = Vec::new();
+        let mut added: Vec<usize> = Vec::new();
+
+        fn flush(split: &mut Vec<SplitRow>, removed: &mut Vec<usize>, added: &mut Vec<usize>) {
+            for k in 0..removed.len().max(added.len()) {
+                split.push(SplitRow::Pair {
+                    left: removed.get(k).copied(),
+                    right: added.get(k).copied(),
+                });
+            }
+            removed.clear();
+            added.clear();
+        }
+
+        for row in self.rows() {
+            let DiffRow::Line(i) = row else {
+                flush(&mut split, &mut removed, &mut added);
+                split.push(SplitRow::Full(row));
+                continue;
+            };
+
+            match self.lines[i].kind {
+                DiffLineKind::Removed if added.is_empty() => removed.push(i),
+                DiffLineKind::Removed => {
+                    flush(&mut split, &mut removed, &mut added);
+                    removed.push(i);
+                }
+                DiffLineKind::Added => added.push(i),
+                // `--- a/` goes left and `+++ b/` right, like a change pair
+                DiffLineKind::Header if self.lines[i].content.starts_with("---") => {
+                    flush(&mut split, &mut removed, &mut added);
+                    removed.push(i);
+                }
+                DiffLineKind::Header => added.push(i),
+                DiffLineKind::Context => {
+                    flush(&mut split, &mut removed, &mut added);
+                    split.push(SplitRow::Pair {
+                        left: Some(i),
+                        right: Some(i),
+                    });
+                }
+                DiffLineKind::HunkHeader | DiffLineKind::Empty => {
+                    flush(&mut split, &mut removed, &mut added);
+                    split.push(SplitRow::Full(row));
+                }
+            }
+        }
+        flush(&mut split, &mut removed, &mut added);
+
+        split
+    }
+
+    /// Number of rendered rows in the current mode (what scrolling operates on).
+    pub fn row_count(&self) -> usize {
+        match self.mode {
+            DiffViewMode::Unified => self.rows().len(),
+            DiffViewMode::SideBySide => self.split_rows().len(),
+        }
+    }
+
+    /// Switch between unified and side-by-side layout.
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            DiffViewMode::Unified => DiffViewMode::SideBySide,
+            DiffViewMode::SideBySide => DiffViewMode::Unified,
+        };
+        self.clamp_scroll();
+    }
+
+    /// Fold markers among the rows currently on screen, in the active layout.
+    fn folds_in_view(&self) -> Vec<usize> {
+        let fold_of = |row: DiffRow| match row {
+            DiffRow::Fold(fi) => Some(fi),
+            DiffRow::Line(_) => None,
+        };
+        let (skip, take) = (self.scroll_offset, self.visible_height.max(1));
+
+        match self.mode {
+            DiffViewMode::Unified => self
+                .rows()
+                .into_iter()
+                .skip(skip)
+                .take(take)
+                .filter_map(fold_of)
+                .collect(),
+            DiffViewMode::SideBySide => self
+                .split_rows()
+                .into_iter()
+                .skip(skip)
+                .take(take)
+                .filter_map(|row| match row {
+                    SplitRow::Full(row) => fold_of(row),
+                    SplitRow::Pair { .. } => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Expand the first collapsed fold currently in view. Returns true if one was expanded.
+    pub fn expand_fold_in_view(&mut self) -> bool {
+        let target = self.folds_in_view().first().copied();
+
+        match target {
+            Some(fi) => {
+                self.folds[fi].collapsed = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Collapse every fold if any is expanded, otherwise expand them all.
+    pub fn toggle_all_folds(&mut self) {
+        let collapse = self.folds.iter().any(|f| !f.collapsed);
+        for fold in &mut self.folds {
+            fold.collapsed = collapse;
+        }
+        self.clamp_scroll();
+    }
+
+    /// Record the number of diff rows that fit on screen, from the area
+    /// measured at draw time. Keeps the scroll position valid when the
+    /// terminal is resized.
+    pub fn set_visible_height(&mut self, height: usize) {
+        self.visible_height = height;
+        self.clamp_scroll();
+    }
+
+    /// Rows moved by page up/down; at least one so paging works on tiny terminals.
+    fn page_size(&self) -> usize {
+        self.visible_height.max(1)
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.row_count().saturating_sub(self.visible_height)
+    }
+
+    fn clamp_scroll(&mut self) {
+        self.scroll_offset = self.scroll_offset.min(self.max_scroll());
     }
 
     pub fn scroll_down(&mut self) {
-        let max = self.lines.len().saturating_sub(self.visible_height);
+        let max = self.max_scroll();
         self.scroll_offset = (self.scroll_offset + 1).min(max);
     }
 
@@ -87,12 +462,12 @@ impl DiffState {
     }
 
     pub fn page_down(&mut self) {
-        let max = self.lines.len().saturating_sub(self.visible_height);
-        self.scroll_offset = (self.scroll_offset + self.visible_height).min(max);
+        let max = self.max_scroll();
+        self.scroll_offset = (self.scroll_offset + self.page_size()).min(max);
     }
 
     pub fn page_up(&mut self) {
-        self.scroll_offset = self.scroll_offset.saturating_sub(self.visible_height);
+        self.scroll_offset = self.scroll_offset.saturating_sub(self.page_size());
     }
 
     pub fn scroll_to_top(&mut self) {
@@ -100,60 +475,254 @@ impl DiffState {
     }
 
     pub fn scroll_to_end(&mut self) {
-        self.scroll_offset = self.lines.len().saturating_sub(self.visible_height);
+        self.scroll_offset = self.max_scroll();
     }
 }
 
-fn build_diff_lines(diffs: &[FileDiff]) -> Vec<DiffLine> {
+/// Find runs of context lines longer than `threshold`. All folds start collapsed.
+fn find_folds(lines: &[DiffLine], threshold: usize) -> Vec<Fold> {
+    let mut folds = Vec::new();
+    let mut run_start = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        match (line.kind == DiffLineKind::Context, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                if i - start > threshold {
+                    folds.push(Fold {
+                        start,
+                        len: i - start,
+                        collapsed: true,
+                    });
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start) = run_start
+        && lines.len() - start > threshold
+    {
+        folds.push(Fold {
+            start,
+            len: lines.len() - start,
+            collapsed: true,
+        });
+    }
+
+    folds
+}
+
+/// Render `diffs` as lines, with the changed-line runs of each file as
+/// hunks. Text files without changes are left out. `context` limits the
+/// unchanged lines kept around each change; `None` keeps whole files.
+pub fn build_diff_lines(
+    diffs: &[FileDiff],
+    context: Option<usize>,
+) -> (Vec<DiffLine>, Vec<Hunk>) {
     let mut lines = Vec::new();
+    let mut hunks: Vec<Hunk> = Vec::new();
 
-    for file_diff in diffs {
+    for (file, file_diff) in diffs.iter().enumerate() {
+        if !file_diff.binary && file_diff.old_content == file_diff.new_content {
+            continue;
+        }
         lines.push(DiffLine {
             kind: DiffLineKind::Header,
             content: format!("--- a/{}", file_diff.file_path),
+            emphasis: Vec::new(),
         });
         lines.push(DiffLine {
             kind: DiffLineKind::Header,
             content: format!("+++ b/{}", file_diff.file_path),
+            emphasis: Vec::new(),
         });
 
+        if file_diff.binary {
+            lines.push(DiffLine {
+                kind: DiffLineKind::Context,
+                content: "Binary file changed".to_string(),
+                emphasis: Vec::new(),
+            });
+            lines.push(DiffLine {
+                kind: DiffLineKind::Empty,
+                content: String::new(),
+                emphasis: Vec::new(),
+            });
+            continue;
+        }
+
         let text_diff = similar::TextDiff::from_lines(
             &file_diff.old_content,
             &file_diff.new_content,
         );
 
-        for hunk in text_diff.unified_diff().context_radius(3).iter_hunks() {
+        // By default keep the whole file as context; long unchanged stretches
+        // are folded by `find_folds` instead of being dropped from the hunk.
+        let radius = context
+            .unwrap_or(text_diff.old_slices().len() + text_diff.new_slices().len());
+        let mut index = 0;
+        let mut in_hunk = false;
+
+        for hunk in text_diff.unified_diff().context_radius(radius).iter_hunks() {
             lines.push(DiffLine {
                 kind: DiffLineKind::HunkHeader,
                 content: format!("{}", hunk.header()),
+                emphasis: Vec::new(),
             });
-            for change in hunk.iter_changes() {
-                let (kind, prefix) = match change.tag() {
-                    ChangeTag::Delete => (DiffLineKind::Removed, "-"),
-                    ChangeTag::Insert => (DiffLineKind::Added, "+"),
-                    ChangeTag::Equal => (DiffLineKind::Context, " "),
-                };
-                let text = change.to_string_lossy();
-                lines.push(DiffLine {
-                    kind,
-                    content: format!("{}{}", prefix, text.trim_end_matches('\n')),
-                });
+            for op in hunk.ops() {
+                for change in text_diff.iter_inline_changes(op) {
+                    let (kind, prefix) = match change.tag() {
+                        ChangeTag::Delete => (DiffLineKind::Removed, "-"),
+                        ChangeTag::Insert => (DiffLineKind::Added, "+"),
+                        ChangeTag::Equal => (DiffLineKind::Context, " "),
+                    };
+
+                    let mut content = prefix.to_string();
+                    let mut emphasis = Vec::new();
+                    for (emphasized, text) in change.iter_strings_lossy() {
+                        let text = text.trim_end_matches('\n');
+                        let start = content.len();
+                        content.push_str(text);
+                        if start < content.len() {
+                            emphasis.push((start..content.len(), emphasized));
+                        }
+                    }
+                    // Only keep spans when part of the line is actually highlighted
+                    if !emphasis.iter().any(|(_, emphasized)| *emphasized) {
+                        emphasis.clear();
+                    }
+
+                    // Same runs as `FileDiff::hunks`: consecutive changed lines
+                    match (kind == DiffLineKind::Context, in_hunk) {
+                        (true, _) => in_hunk = false,
+                        (false, true) => {
+                            if let Some(hunk) = hunks.last_mut() {
+                                hunk.lines.end += 1;
+                            }
+                        }
+                        (false, false) => {
+                            hunks.push(Hunk {
+                                file,
+                                index,
+                                lines: lines.len()..lines.len() + 1,
+                            });
+                            index += 1;
+                            in_hunk = true;
+                        }
+                    }
+
+                    lines.push(DiffLine {
+                        kind,
+                        content,
+                        emphasis,
+                    });
+                }
             }
         }
 
         lines.push(DiffLine {
             kind: DiffLineKind::Empty,
             content: String::new(),
+            emphasis: Vec::new(),
         });
     }
 
-    lines
+    (lines, hunks)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn emphasized_words(line: &DiffLine) -> Vec<&str> {
+        line.emphasis
+            .iter()
+            .filter(|(_, emphasized)| *emphasized)
+            .map(|(range, _)| &line.content[range.clone()])
+            .collect()
+    }
+
+    #[test]
+    fn test_word_level_emphasis() {
+        let state = DiffState::from_strings("tmux", "tmux.conf", "foo bar baz\n", "foo qux baz\n");
+
+        let removed = state.lines.iter().find(|l| l.kind == DiffLineKind::Removed).unwrap();
+        let added = state.lines.iter().find(|l| l.kind == DiffLineKind::Added).unwrap();
+        assert_eq!(removed.content, "-foo bar baz");
+        assert_eq!(emphasized_words(removed), vec!["bar"]);
+        assert_eq!(emphasized_words(added), vec!["qux"]);
+
+        // Spans cover everything after the prefix, in order
+        let covered: String = added
+            .emphasis
+            .iter()
+            .map(|(range, _)| &added.content[range.clone()])
+            .collect();
+        assert_eq!(covered, "foo qux baz");
+    }
+
+    #[test]
+    fn test_unpaired_lines_have_no_emphasis() {
+        let state = DiffState::from_strings("tmux", "tmux.conf", "a\n", "a\nnew line\n");
+        let added = state.lines.iter().find(|l| l.kind == DiffLineKind::Added).unwrap();
+        assert!(added.emphasis.is_empty());
+        assert!(state
+            .lines
+            .iter()
+            .filter(|l| l.kind == DiffLineKind::Context)
+            .all(|l| l.emphasis.is_empty()));
+    }
+
+    #[test]
+    fn test_split_rows_alignment() {
+        let state = DiffState::from_strings("tmux", "tmux.conf", "a\nb\nc\n", "a\nB\nc\nd\n");
+        let pair = |left, right| SplitRow::Pair { left, right };
+
+        assert_eq!(
+            state.split_rows(),
+            vec![
+                pair(Some(0), Some(1)), // --- a/ | +++ b/
+                SplitRow::Full(DiffRow::Line(2)),
+                pair(Some(3), Some(3)), // a
+                pair(Some(4), Some(5)), // -b | +B
+                pair(Some(6), Some(6)), // c
+                pair(None, Some(7)),    // +d
+                SplitRow::Full(DiffRow::Line(8)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_side_by_side_scrolls_split_rows() {
+        let old: String = (1..=20).map(|i| format!("{}\n", i)).collect();
+        let new = old.replace("5\n", "five\n").replace("15\n", "fifteen\n");
+        let mut state = DiffState::from_strings("tmux", "tmux.conf", &old, &new);
+        let unified = state.row_count();
+
+        state.toggle_mode();
+        assert_eq!(state.row_count(), state.split_rows().len());
+        assert!(state.row_count() < unified);
+
+        // Fold markers are found in the split layout too
+        state.visible_height = state.row_count();
+        assert!(state.expand_fold_in_view());
+    }
+
+    #[test]
+    fn test_binary_diff_has_no_hunks() {
+        let state = DiffState::from_contents(
+            "kitty",
+            "icon.png",
+            &SnapshotContent::Binary(vec![0xff, 0x00]),
+            &SnapshotContent::Binary(vec![0xfe]),
+        );
+        assert!(state.has_changes);
+        assert!(state.lines.iter().any(|l| l.content == "Binary file changed"));
+        assert!(!state.lines.iter().any(|l| l.kind == DiffLineKind::HunkHeader));
+    }
+
     #[test]
     fn test_from_strings_no_changes() {
         let state = DiffState::from_strings("tmux", "tmux.conf", "hello\n", "hello\n");
@@ -174,6 +743,47 @@ mod tests {
         assert!(state.lines.iter().any(|l| l.kind == DiffLineKind::Removed));
     }
 
+    #[test]
+    fn test_from_profile_shows_local_changes() {
+        use crate::core::manifest::ToolEntry;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config_dir = tmp.path().join("dotsmith");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let conf = tmp.path().join("tmux.conf");
+        std::fs::write(&conf, "set -g mouse on\n").unwrap();
+
+        let mut manifest = Manifest::default();
+        let entry = ToolEntry {
+            tier: 1,
+            config_paths: vec![conf.to_string_lossy().to_string()],
+            plugins_managed: false,
+            plugin_manager: None,
+            added_at: chrono::Utc::now(),
+            last_snapshot: None,
+            plugins: Default::default(),
+        };
+        manifest.add_tool("tmux", entry).unwrap();
+        profile::save_profile(&config_dir, &manifest, "laptop", None).unwrap();
+
+        let state = DiffState::from_profile("tmux", &config_dir, "laptop").unwrap();
+        assert!(!state.has_changes);
+
+        std::fs::write(&conf, "set -g mouse off\n").unwrap();
+        let state = DiffState::from_profile("tmux", &config_dir, "laptop").unwrap();
+        assert!(state.has_changes);
+        assert_eq!(state.tool_name, "tmux");
+        let changed: Vec<&str> = state
+            .lines
+            .iter()
+            .filter(|l| matches!(l.kind, DiffLineKind::Added | DiffLineKind::Removed))
+            .map(|l| l.content.as_str())
+            .collect();
+        assert_eq!(changed, ["-set -g mouse on", "+set -g mouse off"]);
+
+        assert!(DiffState::from_profile("tmux", &config_dir, "desktop").is_err());
+    }
+
     #[test]
     fn test_scroll() {
         let mut state = DiffState::from_strings("tmux", "tmux.conf", "a\nb\nc\n", "x\ny\nz\n");
@@ -207,4 +817,156 @@ mod tests {
         state.page_up();
         assert_eq!(state.scroll_offset, 0);
     }
+
+    #[test]
+    fn test_resize_reclamps_scroll() {
+        let old: String = (1..=30).map(|i| format!("{}\n", i)).collect();
+        let new: String = (1..=30).map(|i| format!("x{}\n", i)).collect();
+        let mut state = DiffState::from_strings("tmux", "tmux.conf", &old, &new);
+
+        state.set_visible_height(10);
+        state.scroll_to_end();
+        assert_eq!(state.scroll_offset, state.row_count() - 10);
+
+        // Taller terminal: the end of the diff stays at the bottom of the view
+        state.set_visible_height(50);
+        assert_eq!(state.scroll_offset, state.row_count().saturating_sub(50));
+
+        // Paging uses the measured height, not a fixed default
+        state.set_visible_height(7);
+        state.scroll_to_top();
+        state.page_down();
+        assert_eq!(state.scroll_offset, 7);
+    }
+
+    #[test]
+    fn test_page_down_on_tiny_terminal() {
+        let mut state = DiffState::from_strings("tmux", "tmux.conf", "a\nb\nc\n", "x\ny\nz\n");
+        state.set_visible_height(0);
+        state.page_down();
+        assert_eq!(state.scroll_offset, 1);
+    }
+
+    fn long_context_state() -> DiffState {
+        let old: String = (1..=30).map(|i| format!("line {}\n", i)).collect();
+        let new = old.replacen("line 1\n", "changed 1\n", 1);
+        DiffState::from_strings("tmux", "tmux.conf", &old, &new)
+    }
+
+    #[test]
+    fn test_long_context_is_folded() {
+        let state = long_context_state();
+        assert_eq!(state.folds.len(), 1);
+        assert_eq!(state.folds[0].len, 29);
+        assert!(state.folds[0].collapsed);
+
+        // Changed lines stay visible, the unchanged tail is one row
+        let rows = state.rows();
+        assert!(rows.contains(&DiffRow::Fold(0)));
+        assert!(rows.len() < state.lines.len());
+        assert!(rows.iter().any(|r| matches!(r, DiffRow::Line(i)
+            if state.lines[*i].kind == DiffLineKind::Added)));
+    }
+
+    #[test]
+    fn test_short_context_not_folded() {
+        let state = DiffState::from_strings("tmux", "tmux.conf", "a\nb\nc\n", "a\nx\nc\n");
+        assert!(state.folds.is_empty());
+        assert_eq!(state.row_count(), state.lines.len());
+    }
+
+    #[test]
+    fn test_expand_fold_in_view() {
+        let mut state = long_context_state();
+        state.visible_height = 20;
+        assert!(state.expand_fold_in_view());
+        assert!(!state.folds[0].collapsed);
+        assert_eq!(state.row_count(), state.lines.len());
+        // Nothing left to expand
+        assert!(!state.expand_fold_in_view());
+    }
+
+    #[test]
+    fn test_toggle_all_folds() {
+        let mut state = long_context_state();
+        state.visible_height = 5;
+        state.toggle_all_folds();
+        assert!(state.folds.iter().all(|f| !f.collapsed));
+        state.scroll_to_end();
+        let expanded_offset = state.scroll_offset;
+
+        state.toggle_all_folds();
+        assert!(state.folds.iter().all(|f| f.collapsed));
+        // Scroll is clamped to the shorter folded view
+        assert!(state.scroll_offset < expanded_offset);
+        assert!(state.scroll_offset <= state.row_count());
+    }
+
+    #[test]
+    fn test_hunks_match_file_diff() {
+        let state =
+            DiffState::from_strings("tmux", "tmux.conf", "a\nb\nc\nd\n", "A\nb\nc\nD\nE\n");
+        assert_eq!(state.hunks.len(), state.files[0].hunks().len());
+
+        let changed: Vec<Vec<&str>> = state
+            .hunks
+            .iter()
+            .map(|h| state.lines[h.lines.clone()].iter().map(|l| l.content.as_str()).collect())
+            .collect();
+        assert_eq!(changed, [vec!["-a", "+A"], vec!["-d", "+D", "+E"]]);
+        assert_eq!(state.hunk_at(state.hunks[1].lines.start), Some(1));
+        assert_eq!(state.hunk_at(0), None);
+    }
+
+    #[test]
+    fn test_selected_by_file() {
+        let diff = |path: &str| FileDiff {
+            file_path: path.to_string(),
+            old_content: "a\nb\nc\n".to_string(),
+            new_content: "x\nb\ny\n".to_string(),
+            binary: false,
+        };
+        let mut state = DiffState::new("tmux", vec![diff("one.conf"), diff("two.conf")], true);
+        assert_eq!(state.hunks.len(), 4);
+
+        state.selected.extend([0, 2, 3]);
+        assert_eq!(
+            state.selected_by_file(),
+            vec![(0, BTreeSet::from([0])), (1, BTreeSet::from([0, 1]))]
+        );
+    }
+
+    #[test]
+    fn test_next_hunk_scrolls_into_view() {
+        let old: String = (1..=40).map(|i| format!("line {}\n", i)).collect();
+        let new = old.replace("line 2\n", "two\n").replace("line 35\n", "thirty-five\n");
+        let mut state = DiffState::from_strings("tmux", "tmux.conf", &old, &new);
+        state.toggle_all_folds();
+        state.set_visible_height(10);
+
+        state.next_hunk();
+        assert_eq!(state.current_hunk, 1);
+        let row = state
+            .rows()
+            .iter()
+            .position(|r| *r == DiffRow::Line(state.hunks[1].lines.start))
+            .unwrap();
+        assert!((state.scroll_offset..state.scroll_offset + 10).contains(&row));
+
+        // Stays on the last hunk
+        state.next_hunk();
+        assert_eq!(state.current_hunk, 1);
+        state.prev_hunk();
+        assert_eq!(state.current_hunk, 0);
+    }
+
+    #[test]
+    fn test_set_new_content_redraws() {
+        let mut state = DiffState::from_strings("tmux", "tmux.conf", "a\n", "b\n");
+        state.selected.insert(0);
+        state.set_new_content(0, "a\n".to_string());
+        assert!(!state.has_changes);
+        assert!(state.lines.is_empty());
+        assert!(state.selected.is_empty());
+    }
 }
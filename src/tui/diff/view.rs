@@ -6,17 +6,19 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 
-use super::{DiffLineKind, DiffState};
+use super::{DiffLine, DiffLineKind, DiffRow, DiffState, DiffViewMode, SplitRow};
+use crate::tui::keymap::{Action, Keymap, View};
 use crate::tui::widgets::help_bar::{HelpBar, HelpItem};
 
-pub fn draw_diff(f: &mut Frame, area: Rect, state: &mut DiffState) {
+pub fn draw_diff(f: &mut Frame, area: Rect, state: &mut DiffState, keymap: &Keymap) {
     let chunks = Layout::vertical([
         Constraint::Min(5),    // diff content
         Constraint::Length(1), // help bar
     ])
     .split(area);
 
-    state.visible_height = chunks[0].height.saturating_sub(2) as usize;
+    // Inside the borders; refreshed every frame so resizes take effect
+    state.set_visible_height(chunks[0].height.saturating_sub(2) as usize);
 
     if !state.has_changes {
         let msg = Paragraph::new("No changes detected since last snapshot")
@@ -29,68 +31,194 @@ pub fn draw_diff(f: &mut Frame, area: Rect, state: &mut DiffState) {
             .alignment(Alignment::Center);
         f.render_widget(msg, chunks[0]);
     } else {
-        let visible_lines: Vec<Line> = state
-            .lines
-            .iter()
-            .skip(state.scroll_offset)
-            .take(state.visible_height)
-            .map(|dl| {
-                let style = match dl.kind {
-                    DiffLineKind::Header => {
-                        Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
-                    }
-                    DiffLineKind::HunkHeader => Style::default().fg(Color::Cyan),
-                    DiffLineKind::Added => Style::default().fg(Color::Green),
-                    DiffLineKind::Removed => Style::default().fg(Color::Red),
-                    DiffLineKind::Context => Style::default(),
-                    DiffLineKind::Empty => Style::default(),
-                };
-                Line::from(Span::styled(dl.content.clone(), style))
-            })
-            .collect();
-
-        let title = format!(
-            " Diff: {} ({} file(s)) [{}/{}] ",
-            state.tool_name,
-            state.total_files,
-            state.scroll_offset + 1,
-            state.lines.len(),
-        );
-
-        let paragraph = Paragraph::new(visible_lines).block(
-            Block::default()
-                .title(title)
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::DarkGray)),
-        );
-        f.render_widget(paragraph, chunks[0]);
+        match state.mode {
+            DiffViewMode::Unified => draw_unified(f, chunks[0], state),
+            DiffViewMode::SideBySide => draw_side_by_side(f, chunks[0], state),
+        }
     }
 
-    draw_help(f, chunks[1]);
+    draw_help(f, chunks[1], keymap);
 }
 
-fn draw_help(f: &mut Frame, area: Rect) {
-    let help = HelpBar::new(vec![
+fn draw_unified(f: &mut Frame, area: Rect, state: &DiffState) {
+    let rows = state.rows();
+    let visible_lines: Vec<Line> = rows
+        .iter()
+        .skip(state.scroll_offset)
+        .take(state.visible_height)
+        .map(|&row| render_row(state, row))
+        .collect();
+
+    let paragraph = Paragraph::new(visible_lines).block(
+        Block::default()
+            .title(diff_title(state, rows.len()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    f.render_widget(paragraph, area);
+}
+
+/// Two panels, old on the left and new on the right, scrolled together.
+fn draw_side_by_side(f: &mut Frame, area: Rect, state: &DiffState) {
+    let panels = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let rows = state.split_rows();
+    let mut left = Vec::with_capacity(state.visible_height);
+    let mut right = Vec::with_capacity(state.visible_height);
+
+    for row in rows.iter().skip(state.scroll_offset).take(state.visible_height) {
+        match *row {
+            SplitRow::Full(row) => {
+                left.push(render_row(state, row));
+                right.push(render_row(state, row));
+            }
+            SplitRow::Pair { left: l, right: r } => {
+                left.push(l.map_or_else(Line::default, |i| render_change(state, i)));
+                right.push(r.map_or_else(Line::default, |i| render_change(state, i)));
+            }
+        }
+    }
+
+    let block = |title: String| {
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray))
+    };
+    f.render_widget(
+        Paragraph::new(left).block(block(diff_title(state, rows.len()))),
+        panels[0],
+    );
+    f.render_widget(Paragraph::new(right).block(block(" New ".to_string())), panels[1]);
+}
+
+fn diff_title(state: &DiffState, row_count: usize) -> String {
+    let marked = match state.selected.len() {
+        0 => String::new(),
+        n => format!(" {} hunk(s) marked", n),
+    };
+    format!(
+        " Diff: {} ({} file(s)){} [{}/{}] ",
+        state.tool_name,
+        state.total_files,
+        marked,
+        state.scroll_offset + 1,
+        row_count,
+    )
+}
+
+fn render_row(state: &DiffState, row: DiffRow) -> Line<'static> {
+    match row {
+        DiffRow::Line(i) => render_change(state, i),
+        DiffRow::Fold(fi) => Line::from(Span::styled(
+            format!("   … {} unchanged lines …", state.folds[fi].len),
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        )),
+    }
+}
+
+/// A diff line behind a one-column gutter pointing at the current hunk.
+/// Lines of marked hunks get a highlighted background.
+fn render_change(state: &DiffState, i: usize) -> Line<'static> {
+    let hunk = state.hunk_at(i);
+    let gutter = if hunk.is_some_and(|h| h == state.current_hunk) {
+        Span::styled("▌", Style::default().fg(Color::Cyan))
+    } else {
+        Span::raw(" ")
+    };
+
+    let mut line = render_line(&state.lines[i]);
+    if hunk.is_some_and(|h| state.selected.contains(&h)) {
+        line = line.patch_style(Style::default().bg(Color::DarkGray));
+    }
+    line.spans.insert(0, gutter);
+    line
+}
+
+fn render_line(dl: &DiffLine) -> Line<'static> {
+    let style = match dl.kind {
+        DiffLineKind::Header => Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        DiffLineKind::HunkHeader => Style::default().fg(Color::Cyan),
+        DiffLineKind::Added => Style::default().fg(Color::Green),
+        DiffLineKind::Removed => Style::default().fg(Color::Red),
+        DiffLineKind::Context => Style::default(),
+        DiffLineKind::Empty => Style::default(),
+    };
+    if dl.emphasis.is_empty() {
+        return Line::from(Span::styled(dl.content.clone(), style));
+    }
+
+    // Prefix column, then each word run; changed words are reversed
+    let prefix_end = dl.emphasis[0].0.start;
+    let mut spans = vec![Span::styled(dl.content[..prefix_end].to_string(), style)];
+    for (range, emphasized) in &dl.emphasis {
+        let span_style = if *emphasized {
+            style.add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            style
+        };
+        spans.push(Span::styled(dl.content[range.clone()].to_string(), span_style));
+    }
+    Line::from(spans)
+}
+
+/// Every key of the diff view, shown in the help bar and the `?` overlay.
+pub fn help_items(keymap: &Keymap) -> Vec<HelpItem> {
+    vec![
         HelpItem {
-            key: "j/k",
+            key: keymap.hint(View::Diff, &[Action::Down, Action::Up]),
             action: "scroll",
         },
         HelpItem {
-            key: "d/u",
+            key: keymap.hint(View::Diff, &[Action::PageDown, Action::PageUp]),
             action: "page",
         },
         HelpItem {
-            key: "g/G",
+            key: keymap.hint(View::Diff, &[Action::Top, Action::Bottom]),
             action: "top/bottom",
         },
         HelpItem {
-            key: "Esc",
+            key: keymap.hint(View::Diff, &[Action::Expand]),
+            action: "expand",
+        },
+        HelpItem {
+            key: keymap.hint(View::Diff, &[Action::FoldAll]),
+            action: "fold all",
+        },
+        HelpItem {
+            key: keymap.hint(View::Diff, &[Action::Split]),
+            action: "split",
+        },
+        HelpItem {
+            key: keymap.hint(View::Diff, &[Action::FocusNext, Action::FocusPrev]),
+            action: "next/prev hunk",
+        },
+        HelpItem {
+            key: keymap.hint(View::Diff, &[Action::Toggle]),
+            action: "mark hunk",
+        },
+        HelpItem {
+            key: keymap.hint(View::Diff, &[Action::Rollback]),
+            action: "roll back marked",
+        },
+        HelpItem {
+            key: keymap.hint(View::Diff, &[Action::Back]),
             action: "back",
         },
         HelpItem {
-            key: "q",
+            key: keymap.hint(View::Diff, &[Action::Help]),
+            action: "help",
+        },
+        HelpItem {
+            key: keymap.hint(View::Diff, &[Action::Quit]),
             action: "quit",
         },
-    ]);
-    f.render_widget(help, area);
+    ]
+}
+
+fn draw_help(f: &mut Frame, area: Rect, keymap: &Keymap) {
+    f.render_widget(HelpBar::new(help_items(keymap)), area);
 }
@@ -1,6 +1,8 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent};
 
 use super::{ExploreState, Panel};
+use crate::core::setting;
+use crate::tui::keymap::{Action, Keymap, View};
 
 /// Action returned by the explore key handler.
 pub enum ExploreAction {
@@ -8,6 +10,8 @@ pub enum ExploreAction {
     None,
     /// User wants to go back to the dashboard.
     Back,
+    /// Open the key help overlay.
+    ShowHelp,
     /// User wants to quit.
     Quit,
     /// Snapshot the current tool.
@@ -16,56 +20,169 @@ pub enum ExploreAction {
     Reload(String),
     /// Generate a config snippet file for the current tool.
     GenerateConfig(String),
+    /// Generate a config for the current tool that sets each option.
+    GenerateLiveConfig(String),
+    /// Save the current filter as a named view.
+    SaveView(String),
+    /// Write `value` for option `name` to the tool's config.
+    SetOption { name: String, value: String },
+    /// Copy option `name` to the clipboard, as its example or a config line.
+    Copy(String),
 }
 
 /// Handle a key event in the explore view.
-pub fn handle_key(key: KeyEvent, state: &mut ExploreState) -> ExploreAction {
+pub fn handle_key(key: KeyEvent, state: &mut ExploreState, keymap: &Keymap) -> ExploreAction {
     if state.search_mode {
         return handle_search_key(key, state);
     }
+    if state.view_name_input.is_some() {
+        return handle_view_name_key(key, state);
+    }
+    if state.value_input.is_some() {
+        return handle_value_key(key, state);
+    }
 
-    match key.code {
-        KeyCode::Char('q') => ExploreAction::Quit,
-        KeyCode::Esc => ExploreAction::Back,
-        KeyCode::Tab => {
-            if key.modifiers.contains(KeyModifiers::SHIFT) {
-                state.cycle_focus_backward();
-            } else {
-                state.cycle_focus_forward();
-            }
+    let Some(action) = keymap.lookup(View::Explore, &key) else {
+        return ExploreAction::None;
+    };
+    match action {
+        Action::Quit => ExploreAction::Quit,
+        Action::Help => ExploreAction::ShowHelp,
+        Action::Back => ExploreAction::Back,
+        Action::FocusNext => {
+            state.cycle_focus_forward();
             ExploreAction::None
         }
-        KeyCode::BackTab => {
+        Action::FocusPrev => {
             state.cycle_focus_backward();
             ExploreAction::None
         }
-        KeyCode::Char('j') | KeyCode::Down => {
+        Action::Down => {
             match state.focus {
                 Panel::Categories => state.select_next_category(),
                 Panel::Options | Panel::Details => state.select_next_option(),
             }
             ExploreAction::None
         }
-        KeyCode::Char('k') | KeyCode::Up => {
+        Action::Up => {
             match state.focus {
                 Panel::Categories => state.select_prev_category(),
                 Panel::Options | Panel::Details => state.select_prev_option(),
             }
             ExploreAction::None
         }
-        KeyCode::Enter => {
+        Action::Select => {
             if state.focus == Panel::Categories {
                 state.focus = Panel::Options;
+            } else if let Some(opt) = state.selected_option() {
+                state.value_input = Some(setting::initial_value(opt));
+                state.value_error = None;
             }
             ExploreAction::None
         }
-        KeyCode::Char('/') => {
+        Action::Search => {
             state.search_mode = true;
             ExploreAction::None
         }
-        KeyCode::Char('s') => ExploreAction::Snapshot(state.tool_name.clone()),
-        KeyCode::Char('r') => ExploreAction::Reload(state.tool_name.clone()),
-        KeyCode::Char('g') => ExploreAction::GenerateConfig(state.tool_name.clone()),
+        Action::Snapshot => ExploreAction::Snapshot(state.tool_name.clone()),
+        Action::Reload => ExploreAction::Reload(state.tool_name.clone()),
+        Action::Generate => ExploreAction::GenerateConfig(state.tool_name.clone()),
+        Action::GenerateLive => ExploreAction::GenerateLiveConfig(state.tool_name.clone()),
+        Action::SaveView => {
+            state.view_name_input = Some(String::new());
+            ExploreAction::None
+        }
+        Action::Toggle => {
+            if state.focus != Panel::Categories {
+                state.toggle_selected();
+            }
+            ExploreAction::None
+        }
+        Action::Copy => state
+            .selected_option()
+            .map(|opt| ExploreAction::Copy(opt.name.clone()))
+            .unwrap_or(ExploreAction::None),
+        _ => ExploreAction::None,
+    }
+}
+
+/// Handle keys while typing a name for the saved view.
+fn handle_view_name_key(key: KeyEvent, state: &mut ExploreState) -> ExploreAction {
+    let Some(name) = state.view_name_input.as_mut() else {
+        return ExploreAction::None;
+    };
+
+    match key.code {
+        KeyCode::Esc => {
+            state.view_name_input = None;
+            ExploreAction::None
+        }
+        KeyCode::Enter => {
+            let name = state.view_name_input.take().unwrap_or_default();
+            if name.is_empty() {
+                ExploreAction::None
+            } else {
+                ExploreAction::SaveView(name)
+            }
+        }
+        KeyCode::Backspace => {
+            name.pop();
+            ExploreAction::None
+        }
+        KeyCode::Char(c) => {
+            name.push(c);
+            ExploreAction::None
+        }
+        _ => ExploreAction::None,
+    }
+}
+
+/// Handle keys while typing a value for the selected option. Enter only
+/// accepts a value that fits the option's type; otherwise the prompt stays
+/// open with the reason.
+fn handle_value_key(key: KeyEvent, state: &mut ExploreState) -> ExploreAction {
+    match key.code {
+        KeyCode::Esc => {
+            state.value_input = None;
+            state.value_error = None;
+            ExploreAction::None
+        }
+        KeyCode::Enter => {
+            let Some(opt) = state.selected_option() else {
+                state.value_input = None;
+                return ExploreAction::None;
+            };
+            let value = state.value_input.clone().unwrap_or_default();
+            match setting::validate_value(opt, &value) {
+                Ok(()) => {
+                    let name = opt.name.clone();
+                    state.value_input = None;
+                    state.value_error = None;
+                    ExploreAction::SetOption {
+                        name,
+                        value: value.trim().to_string(),
+                    }
+                }
+                Err(e) => {
+                    state.value_error = Some(e);
+                    ExploreAction::None
+                }
+            }
+        }
+        KeyCode::Backspace => {
+            if let Some(ref mut value) = state.value_input {
+                value.pop();
+            }
+            state.value_error = None;
+            ExploreAction::None
+        }
+        KeyCode::Char(c) => {
+            if let Some(ref mut value) = state.value_input {
+                value.push(c);
+            }
+            state.value_error = None;
+            ExploreAction::None
+        }
         _ => ExploreAction::None,
     }
 }
@@ -124,23 +241,33 @@ mod tests {
     }
 
     fn sample_state() -> ExploreState {
-        ExploreState::new("tmux").unwrap()
+        ExploreState::new("tmux", std::path::Path::new("/nonexistent")).unwrap()
     }
 
     #[test]
     fn test_quit() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('q')), &mut state),
+            handle_key(make_key(KeyCode::Char('q')), &mut state, &Keymap::default()),
             ExploreAction::Quit
         ));
     }
 
+    #[test]
+    fn test_copy_selected_option() {
+        let mut state = sample_state();
+        let name = state.selected_option().unwrap().name.clone();
+        assert!(matches!(
+            handle_key(make_key(KeyCode::Char('y')), &mut state, &Keymap::default()),
+            ExploreAction::Copy(n) if n == name
+        ));
+    }
+
     #[test]
     fn test_back() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Esc), &mut state),
+            handle_key(make_key(KeyCode::Esc), &mut state, &Keymap::default()),
             ExploreAction::Back
         ));
     }
@@ -149,25 +276,25 @@ mod tests {
     fn test_tab_focus() {
         let mut state = sample_state();
         assert_eq!(state.focus, Panel::Categories);
-        handle_key(make_key(KeyCode::Tab), &mut state);
+        handle_key(make_key(KeyCode::Tab), &mut state, &Keymap::default());
         assert_eq!(state.focus, Panel::Options);
-        handle_key(make_key(KeyCode::Tab), &mut state);
+        handle_key(make_key(KeyCode::Tab), &mut state, &Keymap::default());
         assert_eq!(state.focus, Panel::Details);
-        handle_key(make_key(KeyCode::Tab), &mut state);
+        handle_key(make_key(KeyCode::Tab), &mut state, &Keymap::default());
         assert_eq!(state.focus, Panel::Categories);
     }
 
     #[test]
     fn test_shift_tab_focus() {
         let mut state = sample_state();
-        handle_key(make_key_shift(KeyCode::Tab), &mut state);
+        handle_key(make_key_shift(KeyCode::Tab), &mut state, &Keymap::default());
         assert_eq!(state.focus, Panel::Details);
     }
 
     #[test]
     fn test_backtab_focus() {
         let mut state = sample_state();
-        handle_key(make_key(KeyCode::BackTab), &mut state);
+        handle_key(make_key(KeyCode::BackTab), &mut state, &Keymap::default());
         assert_eq!(state.focus, Panel::Details);
     }
 
@@ -175,9 +302,9 @@ mod tests {
     fn test_navigate_categories() {
         let mut state = sample_state();
         assert_eq!(state.category_selected, 0);
-        handle_key(make_key(KeyCode::Char('j')), &mut state);
+        handle_key(make_key(KeyCode::Char('j')), &mut state, &Keymap::default());
         assert_eq!(state.category_selected, 1);
-        handle_key(make_key(KeyCode::Char('k')), &mut state);
+        handle_key(make_key(KeyCode::Char('k')), &mut state, &Keymap::default());
         assert_eq!(state.category_selected, 0);
     }
 
@@ -186,14 +313,14 @@ mod tests {
         let mut state = sample_state();
         state.focus = Panel::Options;
         assert_eq!(state.option_selected, 0);
-        handle_key(make_key(KeyCode::Char('j')), &mut state);
+        handle_key(make_key(KeyCode::Char('j')), &mut state, &Keymap::default());
         assert_eq!(state.option_selected, 1);
     }
 
     #[test]
     fn test_enter_search() {
         let mut state = sample_state();
-        handle_key(make_key(KeyCode::Char('/')), &mut state);
+        handle_key(make_key(KeyCode::Char('/')), &mut state, &Keymap::default());
         assert!(state.search_mode);
     }
 
@@ -201,12 +328,12 @@ mod tests {
     fn test_search_type_and_confirm() {
         let mut state = sample_state();
         state.search_mode = true;
-        handle_key(make_key(KeyCode::Char('m')), &mut state);
-        handle_key(make_key(KeyCode::Char('o')), &mut state);
+        handle_key(make_key(KeyCode::Char('m')), &mut state, &Keymap::default());
+        handle_key(make_key(KeyCode::Char('o')), &mut state, &Keymap::default());
         assert_eq!(state.search_query, "mo");
         assert!(state.search_mode);
 
-        handle_key(make_key(KeyCode::Enter), &mut state);
+        handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
         assert!(!state.search_mode);
         assert_eq!(state.search_query, "mo"); // kept
     }
@@ -215,10 +342,10 @@ mod tests {
     fn test_search_cancel() {
         let mut state = sample_state();
         state.search_mode = true;
-        handle_key(make_key(KeyCode::Char('x')), &mut state);
+        handle_key(make_key(KeyCode::Char('x')), &mut state, &Keymap::default());
         assert_eq!(state.search_query, "x");
 
-        handle_key(make_key(KeyCode::Esc), &mut state);
+        handle_key(make_key(KeyCode::Esc), &mut state, &Keymap::default());
         assert!(!state.search_mode);
         assert!(state.search_query.is_empty()); // cleared
     }
@@ -227,11 +354,11 @@ mod tests {
     fn test_search_backspace() {
         let mut state = sample_state();
         state.search_mode = true;
-        handle_key(make_key(KeyCode::Char('a')), &mut state);
-        handle_key(make_key(KeyCode::Char('b')), &mut state);
+        handle_key(make_key(KeyCode::Char('a')), &mut state, &Keymap::default());
+        handle_key(make_key(KeyCode::Char('b')), &mut state, &Keymap::default());
         assert_eq!(state.search_query, "ab");
 
-        handle_key(make_key(KeyCode::Backspace), &mut state);
+        handle_key(make_key(KeyCode::Backspace), &mut state, &Keymap::default());
         assert_eq!(state.search_query, "a");
     }
 
@@ -239,7 +366,7 @@ mod tests {
     fn test_enter_on_category_switches_to_options() {
         let mut state = sample_state();
         assert_eq!(state.focus, Panel::Categories);
-        handle_key(make_key(KeyCode::Enter), &mut state);
+        handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
         assert_eq!(state.focus, Panel::Options);
     }
 
@@ -247,7 +374,7 @@ mod tests {
     fn test_q_in_search_mode_types_q() {
         let mut state = sample_state();
         state.search_mode = true;
-        let action = handle_key(make_key(KeyCode::Char('q')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('q')), &mut state, &Keymap::default());
         assert!(matches!(action, ExploreAction::None));
         assert_eq!(state.search_query, "q");
     }
@@ -255,10 +382,103 @@ mod tests {
     #[test]
     fn test_generate_config() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('g')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('g')), &mut state, &Keymap::default());
         assert!(matches!(action, ExploreAction::GenerateConfig(_)));
         if let ExploreAction::GenerateConfig(tool) = action {
             assert_eq!(tool, "tmux");
         }
     }
+
+    #[test]
+    fn test_generate_live_config() {
+        let mut state = sample_state();
+        let action = handle_key(make_key(KeyCode::Char('G')), &mut state, &Keymap::default());
+        assert!(matches!(action, ExploreAction::GenerateLiveConfig(ref tool) if tool == "tmux"));
+    }
+
+    #[test]
+    fn test_save_view_prompt() {
+        let mut state = sample_state();
+        handle_key(make_key(KeyCode::Char('v')), &mut state, &Keymap::default());
+        assert_eq!(state.view_name_input.as_deref(), Some(""));
+
+        // Keys go to the prompt, not to the normal bindings
+        for c in "bar".chars() {
+            assert!(matches!(
+                handle_key(make_key(KeyCode::Char(c)), &mut state, &Keymap::default()),
+                ExploreAction::None
+            ));
+        }
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
+        assert!(matches!(action, ExploreAction::SaveView(ref name) if name == "bar"));
+        assert!(state.view_name_input.is_none());
+    }
+
+    #[test]
+    fn test_save_view_prompt_cancel() {
+        let mut state = sample_state();
+        handle_key(make_key(KeyCode::Char('v')), &mut state, &Keymap::default());
+        handle_key(make_key(KeyCode::Char('x')), &mut state, &Keymap::default());
+        handle_key(make_key(KeyCode::Esc), &mut state, &Keymap::default());
+        assert!(state.view_name_input.is_none());
+    }
+
+    #[test]
+    fn test_set_value_prompt() {
+        let mut state = sample_state();
+        state.focus = Panel::Options;
+        state.option_selected = state
+            .filtered_indices
+            .iter()
+            .position(|&i| state.all_options[i].name == "mouse")
+            .unwrap();
+
+        handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
+        assert_eq!(state.value_input.as_deref(), Some("off"));
+
+        // An invalid value keeps the prompt open with the reason
+        for _ in 0..3 {
+            handle_key(make_key(KeyCode::Backspace), &mut state, &Keymap::default());
+        }
+        handle_key(make_key(KeyCode::Char('x')), &mut state, &Keymap::default());
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
+        assert!(matches!(action, ExploreAction::None));
+        assert_eq!(state.value_error.as_deref(), Some("expected on or off"));
+
+        handle_key(make_key(KeyCode::Backspace), &mut state, &Keymap::default());
+        for c in "on".chars() {
+            handle_key(make_key(KeyCode::Char(c)), &mut state, &Keymap::default());
+        }
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
+        assert!(matches!(
+            action,
+            ExploreAction::SetOption { ref name, ref value } if name == "mouse" && value == "on"
+        ));
+        assert!(state.value_input.is_none());
+    }
+
+    #[test]
+    fn test_set_value_prompt_cancel() {
+        let mut state = sample_state();
+        state.focus = Panel::Options;
+        handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
+        assert!(state.value_input.is_some());
+        handle_key(make_key(KeyCode::Char('q')), &mut state, &Keymap::default());
+        handle_key(make_key(KeyCode::Esc), &mut state, &Keymap::default());
+        assert!(state.value_input.is_none());
+    }
+
+    #[test]
+    fn test_space_marks_option() {
+        let mut state = sample_state();
+        // Ignored while the categories panel has focus
+        handle_key(make_key(KeyCode::Char(' ')), &mut state, &Keymap::default());
+        assert!(state.selected_indices.is_empty());
+
+        state.focus = Panel::Options;
+        handle_key(make_key(KeyCode::Char(' ')), &mut state, &Keymap::default());
+        assert!(state.selected_indices.contains(&state.filtered_indices[0]));
+        handle_key(make_key(KeyCode::Char(' ')), &mut state, &Keymap::default());
+        assert!(state.selected_indices.is_empty());
+    }
 }
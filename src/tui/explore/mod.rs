@@ -1,7 +1,12 @@
 pub mod handler;
 pub mod view;
 
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::core::config::SavedView;
 use crate::core::module::{ModuleRegistry, OptionEntry};
+use crate::core::search::score_option;
 
 /// Which panel has focus in the explore view.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -24,18 +29,28 @@ pub struct ExploreState {
     pub all_options: Vec<OptionEntry>,
     pub categories: Vec<CategoryItem>,
     pub filtered_indices: Vec<usize>,
+    /// Options marked for generation, as indices into `all_options` so the
+    /// marks survive re-filtering.
+    pub selected_indices: HashSet<usize>,
     pub focus: Panel,
     pub category_selected: usize,
     pub option_selected: usize,
     pub search_mode: bool,
     pub search_query: String,
+    /// Name being typed for "save view"; `Some` while the prompt is open.
+    pub view_name_input: Option<String>,
+    /// Value being typed for the selected option; `Some` while the prompt is open.
+    pub value_input: Option<String>,
+    /// Why the last value entered was rejected, shown in the value prompt.
+    pub value_error: Option<String>,
 }
 
 impl ExploreState {
-    /// Create a new explore state for a Tier 1 tool.
+    /// Create a new explore state for a tool with an option database,
+    /// built-in or from `<config_dir>/modules/`.
     /// Returns `None` if the tool has no option database.
-    pub fn new(tool_name: &str) -> Option<Self> {
-        let db = ModuleRegistry::get_options(tool_name)?;
+    pub fn new(tool_name: &str, config_dir: &Path) -> Option<Self> {
+        let db = ModuleRegistry::load_options(config_dir, tool_name)?;
         let all_options = db.options;
 
         // Build category list with counts, starting with "All"
@@ -64,45 +79,34 @@ impl ExploreState {
             all_options,
             categories,
             filtered_indices,
+            selected_indices: HashSet::new(),
             focus: Panel::Categories,
             category_selected: 0,
             option_selected: 0,
             search_mode: false,
             search_query: String::new(),
+            view_name_input: None,
+            value_input: None,
+            value_error: None,
         })
     }
 
-    /// Apply the current category filter (and search if active).
+    /// Apply the current category filter (and search if active). Search
+    /// matches fuzzily and lists the best matches first.
     pub fn apply_filters(&mut self) {
         let category = &self.categories[self.category_selected].name;
         let is_all = category == "All";
-        let query = self.search_query.to_lowercase();
 
-        self.filtered_indices = self
+        let mut scored: Vec<(usize, u32)> = self
             .all_options
             .iter()
             .enumerate()
-            .filter(|(_, opt)| {
-                // Category filter
-                if !is_all && opt.category != *category {
-                    return false;
-                }
-                // Search filter
-                if !query.is_empty() {
-                    let name_match = opt.name.to_lowercase().contains(&query);
-                    let desc_match = opt.description.to_lowercase().contains(&query);
-                    let tag_match = opt
-                        .tags
-                        .as_ref()
-                        .is_some_and(|tags| tags.iter().any(|t| t.to_lowercase().contains(&query)));
-                    if !name_match && !desc_match && !tag_match {
-                        return false;
-                    }
-                }
-                true
-            })
-            .map(|(i, _)| i)
+            .filter(|(_, opt)| is_all || opt.category == *category)
+            .filter_map(|(i, opt)| score_option(opt, &self.search_query).map(|s| (i, s)))
             .collect();
+        // Stable, so equal scores (and an empty query) keep database order
+        scored.sort_by_key(|&(_, s)| std::cmp::Reverse(s));
+        self.filtered_indices = scored.into_iter().map(|(i, _)| i).collect();
 
         // Clamp option selection
         if self.filtered_indices.is_empty() {
@@ -119,6 +123,53 @@ impl ExploreState {
             .map(|&i| &self.all_options[i])
     }
 
+    /// Mark or unmark the focused option for generation.
+    pub fn toggle_selected(&mut self) {
+        if let Some(&i) = self.filtered_indices.get(self.option_selected)
+            && !self.selected_indices.remove(&i)
+        {
+            self.selected_indices.insert(i);
+        }
+    }
+
+    /// Options to generate: the marked ones in database order, or every
+    /// visible option when nothing is marked.
+    pub fn generation_options(&self) -> Vec<&OptionEntry> {
+        if self.selected_indices.is_empty() {
+            return self
+                .filtered_indices
+                .iter()
+                .map(|&i| &self.all_options[i])
+                .collect();
+        }
+        let mut marked: Vec<usize> = self.selected_indices.iter().copied().collect();
+        marked.sort_unstable();
+        marked.into_iter().map(|i| &self.all_options[i]).collect()
+    }
+
+    /// The current category and search as a view that can be saved.
+    pub fn saved_view(&self) -> SavedView {
+        SavedView {
+            tool: self.tool_name.clone(),
+            category: self.categories[self.category_selected].name.clone(),
+            query: self.search_query.clone(),
+        }
+    }
+
+    /// Restore a saved category and search. A category that no longer
+    /// exists falls back to "All".
+    pub fn apply_view(&mut self, view: &SavedView) {
+        self.category_selected = self
+            .categories
+            .iter()
+            .position(|c| c.name == view.category)
+            .unwrap_or(0);
+        self.search_query = view.query.clone();
+        self.option_selected = 0;
+        self.focus = Panel::Options;
+        self.apply_filters();
+    }
+
     pub fn select_next_category(&mut self) {
         if !self.categories.is_empty() {
             self.category_selected = (self.category_selected + 1).min(self.categories.len() - 1);
@@ -164,7 +215,7 @@ mod tests {
 
     #[test]
     fn test_new_tmux() {
-        let state = ExploreState::new("tmux").unwrap();
+        let state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
         assert_eq!(state.tool_name, "tmux");
         assert!(state.all_options.len() >= 31, "should have at least 31 native + plugin options");
         assert!(state.categories.len() > 1);
@@ -175,54 +226,54 @@ mod tests {
 
     #[test]
     fn test_new_zsh() {
-        let state = ExploreState::new("zsh").unwrap();
+        let state = ExploreState::new("zsh", Path::new("/nonexistent")).unwrap();
         assert_eq!(state.tool_name, "zsh");
         assert!(state.all_options.len() >= 33, "should have at least 33 native + plugin options");
     }
 
     #[test]
     fn test_new_git() {
-        let state = ExploreState::new("git").unwrap();
+        let state = ExploreState::new("git", Path::new("/nonexistent")).unwrap();
         assert_eq!(state.tool_name, "git");
         assert_eq!(state.all_options.len(), 31);
     }
 
     #[test]
     fn test_new_kitty() {
-        let state = ExploreState::new("kitty").unwrap();
+        let state = ExploreState::new("kitty", Path::new("/nonexistent")).unwrap();
         assert_eq!(state.tool_name, "kitty");
         assert_eq!(state.all_options.len(), 31);
     }
 
     #[test]
     fn test_new_neovim() {
-        let state = ExploreState::new("neovim").unwrap();
+        let state = ExploreState::new("neovim", Path::new("/nonexistent")).unwrap();
         assert_eq!(state.tool_name, "neovim");
         assert_eq!(state.all_options.len(), 31);
     }
 
     #[test]
     fn test_new_alacritty() {
-        let state = ExploreState::new("alacritty").unwrap();
+        let state = ExploreState::new("alacritty", Path::new("/nonexistent")).unwrap();
         assert_eq!(state.tool_name, "alacritty");
         assert_eq!(state.all_options.len(), 31);
     }
 
     #[test]
     fn test_new_awesomewm() {
-        let state = ExploreState::new("awesomewm").unwrap();
+        let state = ExploreState::new("awesomewm", Path::new("/nonexistent")).unwrap();
         assert_eq!(state.tool_name, "awesomewm");
         assert_eq!(state.all_options.len(), 31);
     }
 
     #[test]
     fn test_new_nonexistent() {
-        assert!(ExploreState::new("nonexistent").is_none());
+        assert!(ExploreState::new("nonexistent", Path::new("/nonexistent")).is_none());
     }
 
     #[test]
     fn test_category_filter() {
-        let mut state = ExploreState::new("tmux").unwrap();
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
 
         // "All" shows everything
         assert_eq!(state.filtered_indices.len(), state.all_options.len());
@@ -241,7 +292,7 @@ mod tests {
 
     #[test]
     fn test_search_filter() {
-        let mut state = ExploreState::new("tmux").unwrap();
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
         state.search_query = "mouse".to_string();
         state.apply_filters();
         assert!(!state.filtered_indices.is_empty());
@@ -254,7 +305,7 @@ mod tests {
 
     #[test]
     fn test_search_case_insensitive() {
-        let mut state = ExploreState::new("tmux").unwrap();
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
         state.search_query = "MOUSE".to_string();
         state.apply_filters();
         assert!(state
@@ -265,7 +316,7 @@ mod tests {
 
     #[test]
     fn test_search_no_results() {
-        let mut state = ExploreState::new("tmux").unwrap();
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
         state.search_query = "zzzznonexistent".to_string();
         state.apply_filters();
         assert!(state.filtered_indices.is_empty());
@@ -274,7 +325,7 @@ mod tests {
 
     #[test]
     fn test_category_plus_search() {
-        let mut state = ExploreState::new("git").unwrap();
+        let mut state = ExploreState::new("git", Path::new("/nonexistent")).unwrap();
         // Select "core" category
         let core_idx = state
             .categories
@@ -292,7 +343,7 @@ mod tests {
 
     #[test]
     fn test_focus_cycling() {
-        let mut state = ExploreState::new("tmux").unwrap();
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
         assert_eq!(state.focus, Panel::Categories);
         state.cycle_focus_forward();
         assert_eq!(state.focus, Panel::Options);
@@ -307,7 +358,7 @@ mod tests {
 
     #[test]
     fn test_option_navigation() {
-        let mut state = ExploreState::new("tmux").unwrap();
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
         assert_eq!(state.option_selected, 0);
         state.select_next_option();
         assert_eq!(state.option_selected, 1);
@@ -319,8 +370,115 @@ mod tests {
 
     #[test]
     fn test_selected_option() {
-        let state = ExploreState::new("tmux").unwrap();
+        let state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
         let opt = state.selected_option().unwrap();
         assert_eq!(opt, &state.all_options[0]);
     }
+
+    #[test]
+    fn test_saved_view_roundtrip() {
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
+        state.category_selected = 1;
+        state.search_query = "a".to_string();
+        state.apply_filters();
+        let view = state.saved_view();
+        let expected = state.filtered_indices.clone();
+
+        let mut reopened = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
+        reopened.apply_view(&view);
+        assert_eq!(reopened.category_selected, 1);
+        assert_eq!(reopened.search_query, "a");
+        assert_eq!(reopened.filtered_indices, expected);
+    }
+
+    #[test]
+    fn test_apply_view_unknown_category_falls_back_to_all() {
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
+        state.apply_view(&SavedView {
+            tool: "tmux".to_string(),
+            category: "no-such-category".to_string(),
+            query: String::new(),
+        });
+        assert_eq!(state.category_selected, 0);
+        assert_eq!(state.filtered_indices.len(), state.all_options.len());
+    }
+
+    #[test]
+    fn test_toggle_selected() {
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
+        state.toggle_selected();
+        state.select_next_option();
+        state.toggle_selected();
+        assert_eq!(state.selected_indices.len(), 2);
+
+        state.toggle_selected();
+        assert_eq!(state.selected_indices.len(), 1);
+        assert!(state.selected_indices.contains(&0));
+    }
+
+    #[test]
+    fn test_generation_options() {
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
+        // Nothing marked: everything visible
+        assert_eq!(state.generation_options().len(), state.all_options.len());
+
+        state.option_selected = 3;
+        state.toggle_selected();
+        state.option_selected = 1;
+        state.toggle_selected();
+        let names: Vec<&str> = state.generation_options().iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, vec![state.all_options[1].name.as_str(), &state.all_options[3].name]);
+    }
+
+    #[test]
+    fn test_selection_survives_refiltering() {
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
+        state.search_query = "mouse".to_string();
+        state.apply_filters();
+        let mouse = state
+            .filtered_indices
+            .iter()
+            .position(|&i| state.all_options[i].name == "mouse")
+            .unwrap();
+        state.option_selected = mouse;
+        state.toggle_selected();
+
+        // Switch category and search: the mark stays on the same option
+        state.search_query = "status".to_string();
+        state.select_next_category();
+        state.search_query.clear();
+        state.category_selected = 0;
+        state.apply_filters();
+
+        let marked = state.generation_options();
+        assert_eq!(marked.len(), 1);
+        assert_eq!(marked[0].name, "mouse");
+        let row = state
+            .filtered_indices
+            .iter()
+            .position(|&i| state.all_options[i].name == "mouse")
+            .unwrap();
+        assert!(state.selected_indices.contains(&state.filtered_indices[row]));
+    }
+
+    #[test]
+    fn test_fuzzy_search_finds_typo() {
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
+        state.search_query = "msoue".to_string();
+        state.apply_filters();
+        let names: Vec<&str> = state
+            .filtered_indices
+            .iter()
+            .map(|&i| state.all_options[i].name.as_str())
+            .collect();
+        assert!(names.contains(&"mouse"), "{:?}", names);
+    }
+
+    #[test]
+    fn test_search_ranks_best_match_first() {
+        let mut state = ExploreState::new("tmux", Path::new("/nonexistent")).unwrap();
+        state.search_query = "mouse".to_string();
+        state.apply_filters();
+        assert_eq!(state.selected_option().unwrap().name, "mouse");
+    }
 }
@@ -7,9 +7,10 @@ use ratatui::{
 };
 
 use super::{ExploreState, Panel};
+use crate::tui::keymap::{Action, Keymap, View};
 use crate::tui::widgets::help_bar::{HelpBar, HelpItem};
 
-pub fn draw_explore(f: &mut Frame, area: Rect, state: &ExploreState) {
+pub fn draw_explore(f: &mut Frame, area: Rect, state: &ExploreState, keymap: &Keymap) {
     let chunks = Layout::vertical([
         Constraint::Min(5),    // panels
         Constraint::Length(1), // help / search bar
@@ -20,8 +21,12 @@ pub fn draw_explore(f: &mut Frame, area: Rect, state: &ExploreState) {
 
     if state.search_mode {
         draw_search_bar(f, chunks[1], state);
+    } else if let Some(ref name) = state.view_name_input {
+        draw_view_name_prompt(f, chunks[1], name);
+    } else if let Some(ref value) = state.value_input {
+        draw_value_prompt(f, chunks[1], state, value);
     } else {
-        draw_help(f, chunks[1]);
+        draw_help(f, chunks[1], keymap);
     }
 }
 
@@ -83,11 +88,14 @@ fn draw_options(f: &mut Frame, area: Rect, state: &ExploreState) {
     };
 
     let count = state.filtered_indices.len();
-    let title = if state.search_query.is_empty() {
+    let mut title = if state.search_query.is_empty() {
         format!(" Options [{}] ", count)
     } else {
         format!(" Options [{}] \"{}\" ", count, state.search_query)
     };
+    if !state.selected_indices.is_empty() {
+        title.push_str(&format!("{} marked ", state.selected_indices.len()));
+    }
 
     let items: Vec<ListItem> = state
         .filtered_indices
@@ -101,9 +109,10 @@ fn draw_options(f: &mut Frame, area: Rect, state: &ExploreState) {
                 Style::default()
             };
 
-            // Show name + abbreviated category
+            // Show mark + name + abbreviated category
+            let mark = if state.selected_indices.contains(&opt_idx) { "[x]" } else { "[ ]" };
             let cat_abbrev: String = opt.category.chars().take(5).collect();
-            let text = format!("{:<24} {}", opt.name, cat_abbrev);
+            let text = format!("{} {:<24} {}", mark, opt.name, cat_abbrev);
             ListItem::new(text).style(style)
         })
         .collect();
@@ -250,15 +259,86 @@ fn draw_search_bar(f: &mut Frame, area: Rect, state: &ExploreState) {
     f.render_widget(Paragraph::new(line), area);
 }
 
-fn draw_help(f: &mut Frame, area: Rect) {
-    let help = HelpBar::new(vec![
-        HelpItem { key: "/", action: "search" },
-        HelpItem { key: "Tab", action: "panel" },
-        HelpItem { key: "s", action: "snapshot" },
-        HelpItem { key: "r", action: "reload" },
-        HelpItem { key: "g", action: "generate" },
-        HelpItem { key: "Esc", action: "back" },
-        HelpItem { key: "q", action: "quit" },
+fn draw_view_name_prompt(f: &mut Frame, area: Rect, name: &str) {
+    let line = Line::from(vec![
+        Span::styled("Save view as: ", Style::default().fg(Color::Yellow)),
+        Span::raw(name),
+        Span::styled("_", Style::default().fg(Color::Yellow)),
     ]);
-    f.render_widget(help, area);
+    f.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_value_prompt(f: &mut Frame, area: Rect, state: &ExploreState, value: &str) {
+    let name = state.selected_option().map_or("", |o| o.name.as_str());
+    let mut spans = vec![
+        Span::styled(format!("Set {}: ", name), Style::default().fg(Color::Yellow)),
+        Span::raw(value),
+        Span::styled("_", Style::default().fg(Color::Yellow)),
+    ];
+    if let Some(ref error) = state.value_error {
+        spans.push(Span::styled(format!("  {}", error), Style::default().fg(Color::Red)));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Every key of the explorer, shown in the help bar and the `?` overlay.
+pub fn help_items(keymap: &Keymap) -> Vec<HelpItem> {
+    vec![
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::Search]),
+            action: "search",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::FocusNext]),
+            action: "panel",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::Select]),
+            action: "set",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::Snapshot]),
+            action: "snapshot",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::Reload]),
+            action: "reload",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::Toggle]),
+            action: "mark",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::Copy]),
+            action: "copy",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::Generate]),
+            action: "generate",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::GenerateLive]),
+            action: "generate live",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::SaveView]),
+            action: "save view",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::Back]),
+            action: "back",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::Help]),
+            action: "help",
+        },
+        HelpItem {
+            key: keymap.hint(View::Explore, &[Action::Quit]),
+            action: "quit",
+        },
+    ]
+}
+
+fn draw_help(f: &mut Frame, area: Rect, keymap: &Keymap) {
+    f.render_widget(HelpBar::new(help_items(keymap)), area);
 }
@@ -1,39 +1,99 @@
 use crossterm::event::{KeyCode, KeyEvent};
 
 use super::HistoryState;
+use crate::tui::keymap::{Action, Keymap, View};
 
 pub enum HistoryAction {
     None,
     Back,
+    ShowHelp,
     Quit,
     ViewSnapshot(i64),
     Rollback(i64),
+    /// Tag a snapshot: `(id, tag)`.
+    Tag(i64, String),
+    /// Replace a snapshot's message: `(id, message)`; empty clears it.
+    Annotate(i64, String),
 }
 
-pub fn handle_key(key: KeyEvent, state: &mut HistoryState) -> HistoryAction {
-    match key.code {
-        KeyCode::Char('q') => HistoryAction::Quit,
-        KeyCode::Esc => HistoryAction::Back,
-        KeyCode::Char('j') | KeyCode::Down => {
+pub fn handle_key(key: KeyEvent, state: &mut HistoryState, keymap: &Keymap) -> HistoryAction {
+    if state.tag_input.is_some() {
+        let id = state.selected_entry().map(|e| e.id);
+        return match (id, handle_prompt_key(key, &mut state.tag_input)) {
+            (Some(id), Some(tag)) if !tag.is_empty() => HistoryAction::Tag(id, tag),
+            _ => HistoryAction::None,
+        };
+    }
+    if state.message_input.is_some() {
+        let id = state.selected_entry().map(|e| e.id);
+        return match (id, handle_prompt_key(key, &mut state.message_input)) {
+            (Some(id), Some(message)) => HistoryAction::Annotate(id, message),
+            _ => HistoryAction::None,
+        };
+    }
+
+    let Some(action) = keymap.lookup(View::History, &key) else {
+        return HistoryAction::None;
+    };
+    match action {
+        Action::Quit => HistoryAction::Quit,
+        Action::Help => HistoryAction::ShowHelp,
+        Action::Back => HistoryAction::Back,
+        Action::Down => {
             state.select_next();
             HistoryAction::None
         }
-        KeyCode::Char('k') | KeyCode::Up => {
+        Action::Up => {
             state.select_prev();
             HistoryAction::None
         }
-        KeyCode::Enter => state
+        Action::Select => state
             .selected_entry()
             .map(|e| HistoryAction::ViewSnapshot(e.id))
             .unwrap_or(HistoryAction::None),
-        KeyCode::Char('r') => state
+        Action::Rollback => state
             .selected_entry()
             .map(|e| HistoryAction::Rollback(e.id))
             .unwrap_or(HistoryAction::None),
+        Action::Tag => {
+            if state.selected_entry().is_some() {
+                state.tag_input = Some(String::new());
+            }
+            HistoryAction::None
+        }
+        Action::Annotate => {
+            // Start from the current message so it can be edited
+            if let Some(entry) = state.selected_entry() {
+                state.message_input = Some(entry.message.clone().unwrap_or_default());
+            }
+            HistoryAction::None
+        }
         _ => HistoryAction::None,
     }
 }
 
+/// Handle keys while typing into a prompt. Returns the text on `Enter`;
+/// `Esc` closes the prompt without it.
+fn handle_prompt_key(key: KeyEvent, input: &mut Option<String>) -> Option<String> {
+    let text = input.as_mut()?;
+    match key.code {
+        KeyCode::Esc => {
+            *input = None;
+            None
+        }
+        KeyCode::Enter => input.take(),
+        KeyCode::Backspace => {
+            text.pop();
+            None
+        }
+        KeyCode::Char(c) => {
+            text.push(c);
+            None
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,6 +121,8 @@ mod tests {
                     hash: "abc12345".into(),
                     message: Some("test".into()),
                     created_at: "2026-02-08".into(),
+                    pinned: false,
+                    tags: Vec::new(),
                 },
                 SnapshotSummary {
                     id: 2,
@@ -69,9 +131,13 @@ mod tests {
                     hash: "def67890".into(),
                     message: None,
                     created_at: "2026-02-07".into(),
+                    pinned: false,
+                    tags: Vec::new(),
                 },
             ],
             selected: 0,
+            tag_input: None,
+            message_input: None,
         }
     }
 
@@ -79,7 +145,7 @@ mod tests {
     fn test_quit() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('q')), &mut state),
+            handle_key(make_key(KeyCode::Char('q')), &mut state, &Keymap::default()),
             HistoryAction::Quit
         ));
     }
@@ -88,7 +154,7 @@ mod tests {
     fn test_back() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Esc), &mut state),
+            handle_key(make_key(KeyCode::Esc), &mut state, &Keymap::default()),
             HistoryAction::Back
         ));
     }
@@ -96,23 +162,68 @@ mod tests {
     #[test]
     fn test_view_snapshot() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Enter), &mut state);
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
         assert!(matches!(action, HistoryAction::ViewSnapshot(3)));
     }
 
     #[test]
     fn test_rollback() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('r')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('r')), &mut state, &Keymap::default());
         assert!(matches!(action, HistoryAction::Rollback(3)));
     }
 
     #[test]
     fn test_navigate() {
         let mut state = sample_state();
-        handle_key(make_key(KeyCode::Char('j')), &mut state);
+        handle_key(make_key(KeyCode::Char('j')), &mut state, &Keymap::default());
         assert_eq!(state.selected, 1);
-        handle_key(make_key(KeyCode::Char('k')), &mut state);
+        handle_key(make_key(KeyCode::Char('k')), &mut state, &Keymap::default());
         assert_eq!(state.selected, 0);
     }
+
+    #[test]
+    fn test_tag_prompt() {
+        let mut state = sample_state();
+        state.selected = 1;
+        let keymap = Keymap::default();
+        handle_key(make_key(KeyCode::Char('t')), &mut state, &keymap);
+        assert_eq!(state.tag_input.as_deref(), Some(""));
+
+        // Keys are typed into the prompt, not taken as actions
+        for c in "good".chars() {
+            handle_key(make_key(KeyCode::Char(c)), &mut state, &keymap);
+        }
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &keymap);
+        assert!(matches!(action, HistoryAction::Tag(2, ref tag) if tag == "good"));
+        assert!(state.tag_input.is_none());
+
+        handle_key(make_key(KeyCode::Char('t')), &mut state, &keymap);
+        let action = handle_key(make_key(KeyCode::Esc), &mut state, &keymap);
+        assert!(matches!(action, HistoryAction::None));
+        assert!(state.tag_input.is_none());
+    }
+
+    #[test]
+    fn test_annotate_prompt_edits_current_message() {
+        let mut state = sample_state();
+        let keymap = Keymap::default();
+        handle_key(make_key(KeyCode::Char('m')), &mut state, &keymap);
+        assert_eq!(state.message_input.as_deref(), Some("test"));
+
+        for key in [KeyCode::Backspace, KeyCode::Char('x'), KeyCode::Char('q')] {
+            handle_key(make_key(key), &mut state, &keymap);
+        }
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &keymap);
+        assert!(matches!(action, HistoryAction::Annotate(3, ref m) if m == "tesxq"));
+        assert!(state.message_input.is_none());
+
+        // No message yet: the prompt starts empty; Esc cancels
+        state.selected = 1;
+        handle_key(make_key(KeyCode::Char('m')), &mut state, &keymap);
+        assert_eq!(state.message_input.as_deref(), Some(""));
+        let action = handle_key(make_key(KeyCode::Esc), &mut state, &keymap);
+        assert!(matches!(action, HistoryAction::None));
+        assert!(state.message_input.is_none());
+    }
 }
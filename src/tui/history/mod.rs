@@ -8,6 +8,10 @@ pub struct HistoryState {
     pub tool_name: String,
     pub entries: Vec<SnapshotSummary>,
     pub selected: usize,
+    /// Tag being typed for the selected snapshot, if the prompt is open
+    pub tag_input: Option<String>,
+    /// Message being edited for the selected snapshot, if the prompt is open
+    pub message_input: Option<String>,
 }
 
 impl HistoryState {
@@ -17,6 +21,8 @@ impl HistoryState {
             tool_name: tool.to_string(),
             entries,
             selected: 0,
+            tag_input: None,
+            message_input: None,
         }
     }
 
@@ -49,6 +55,8 @@ mod tests {
                 hash: "abc12345".into(),
                 message: Some("test snapshot".into()),
                 created_at: "2026-02-08 12:00:00".into(),
+                pinned: false,
+                tags: Vec::new(),
             },
             SnapshotSummary {
                 id: 2,
@@ -57,12 +65,16 @@ mod tests {
                 hash: "def67890".into(),
                 message: None,
                 created_at: "2026-02-07 12:00:00".into(),
+                pinned: false,
+                tags: Vec::new(),
             },
         ];
         HistoryState {
             tool_name: "tmux".to_string(),
             entries,
             selected: 0,
+            tag_input: None,
+            message_input: None,
         }
     }
 
@@ -93,6 +105,8 @@ mod tests {
             tool_name: "tmux".to_string(),
             entries: vec![],
             selected: 0,
+            tag_input: None,
+            message_input: None,
         };
         assert!(state.selected_entry().is_none());
     }
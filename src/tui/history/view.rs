@@ -7,9 +7,11 @@ use ratatui::{
 };
 
 use super::HistoryState;
+use crate::core::snapshot::SnapshotSummary;
+use crate::tui::keymap::{Action, Keymap, View};
 use crate::tui::widgets::help_bar::{HelpBar, HelpItem};
 
-pub fn draw_history(f: &mut Frame, area: Rect, state: &HistoryState) {
+pub fn draw_history(f: &mut Frame, area: Rect, state: &HistoryState, keymap: &Keymap) {
     let chunks = Layout::vertical([
         Constraint::Min(5),    // table
         Constraint::Length(1), // help bar
@@ -55,13 +57,7 @@ pub fn draw_history(f: &mut Frame, area: Rect, state: &HistoryState) {
                     Cell::from(entry.created_at.clone()),
                     Cell::from(entry.file_path.clone()),
                     Cell::from(entry.hash.get(..8).unwrap_or(&entry.hash).to_string()),
-                    Cell::from(
-                        entry
-                            .message
-                            .as_deref()
-                            .unwrap_or("-")
-                            .to_string(),
-                    ),
+                    Cell::from(message_cell(entry)),
                 ])
                 .style(style)
             })
@@ -92,31 +88,73 @@ pub fn draw_history(f: &mut Frame, area: Rect, state: &HistoryState) {
         f.render_widget(table, chunks[0]);
     }
 
-    draw_help(f, chunks[1]);
+    match (&state.tag_input, &state.message_input) {
+        (Some(tag), _) => draw_prompt(f, chunks[1], "Tag snapshot as: ", tag),
+        (_, Some(message)) => draw_prompt(f, chunks[1], "Snapshot message: ", message),
+        _ => draw_help(f, chunks[1], keymap),
+    }
+}
+
+/// The message column, with pinned snapshots and tags marked.
+fn message_cell(entry: &SnapshotSummary) -> Line<'static> {
+    let mut spans = Vec::new();
+    if entry.pinned {
+        spans.push(Span::styled("[pinned] ", Style::default().fg(Color::Yellow)));
+    }
+    for tag in &entry.tags {
+        spans.push(Span::styled(format!("[{}] ", tag), Style::default().fg(Color::Green)));
+    }
+    spans.push(Span::raw(entry.message.as_deref().unwrap_or("-").to_string()));
+    Line::from(spans)
+}
+
+fn draw_prompt(f: &mut Frame, area: Rect, label: &'static str, text: &str) {
+    let line = Line::from(vec![
+        Span::styled(label, Style::default().fg(Color::Yellow)),
+        Span::raw(text),
+        Span::styled("_", Style::default().fg(Color::Yellow)),
+    ]);
+    f.render_widget(ratatui::widgets::Paragraph::new(line), area);
 }
 
-fn draw_help(f: &mut Frame, area: Rect) {
-    let help = HelpBar::new(vec![
+/// Every key of the history view, shown in the help bar and the `?` overlay.
+pub fn help_items(keymap: &Keymap) -> Vec<HelpItem> {
+    vec![
         HelpItem {
-            key: "j/k",
+            key: keymap.hint(View::History, &[Action::Down, Action::Up]),
             action: "navigate",
         },
         HelpItem {
-            key: "Enter",
+            key: keymap.hint(View::History, &[Action::Select]),
             action: "view diff",
         },
         HelpItem {
-            key: "r",
+            key: keymap.hint(View::History, &[Action::Rollback]),
             action: "rollback",
         },
         HelpItem {
-            key: "Esc",
+            key: keymap.hint(View::History, &[Action::Tag]),
+            action: "tag",
+        },
+        HelpItem {
+            key: keymap.hint(View::History, &[Action::Annotate]),
+            action: "message",
+        },
+        HelpItem {
+            key: keymap.hint(View::History, &[Action::Back]),
             action: "back",
         },
         HelpItem {
-            key: "q",
+            key: keymap.hint(View::History, &[Action::Help]),
+            action: "help",
+        },
+        HelpItem {
+            key: keymap.hint(View::History, &[Action::Quit]),
             action: "quit",
         },
-    ]);
-    f.render_widget(help, area);
+    ]
+}
+
+fn draw_help(f: &mut Frame, area: Rect, keymap: &Keymap) {
+    f.render_widget(HelpBar::new(help_items(keymap)), area);
 }
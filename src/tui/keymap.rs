@@ -0,0 +1,480 @@
+use std::collections::BTreeMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::core::config::KeyList;
+use crate::core::errors::DotsmithError;
+
+/// The TUI screens, each with its own default bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Dashboard,
+    Explore,
+    Diff,
+    History,
+    Plugins,
+}
+
+/// A named action that keys are bound to. A name means the same thing in
+/// every view that offers it, so rebinding `remove` affects the dashboard and
+/// the plugins view alike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Back,
+    Down,
+    Up,
+    Select,
+    Explore,
+    Add,
+    Remove,
+    Snapshot,
+    Reload,
+    Diff,
+    History,
+    Plugins,
+    Sync,
+    FocusNext,
+    FocusPrev,
+    Search,
+    Generate,
+    GenerateLive,
+    SaveView,
+    PageDown,
+    PageUp,
+    Top,
+    Bottom,
+    Expand,
+    FoldAll,
+    Split,
+    Rollback,
+    Update,
+    UpdateAll,
+    CheckUpdates,
+    MoveDown,
+    MoveUp,
+    Info,
+    Toggle,
+    Sort,
+    SortReverse,
+    Tag,
+    Annotate,
+    Copy,
+    Help,
+}
+
+/// Action names as written in the `[keymap]` table.
+const ACTION_NAMES: &[(Action, &str)] = &[
+    (Action::Quit, "quit"),
+    (Action::Back, "back"),
+    (Action::Down, "down"),
+    (Action::Up, "up"),
+    (Action::Select, "select"),
+    (Action::Explore, "explore"),
+    (Action::Add, "add"),
+    (Action::Remove, "remove"),
+    (Action::Snapshot, "snapshot"),
+    (Action::Reload, "reload"),
+    (Action::Diff, "diff"),
+    (Action::History, "history"),
+    (Action::Plugins, "plugins"),
+    (Action::Sync, "sync"),
+    (Action::FocusNext, "focus_next"),
+    (Action::FocusPrev, "focus_prev"),
+    (Action::Search, "search"),
+    (Action::Generate, "generate"),
+    (Action::GenerateLive, "generate_live"),
+    (Action::SaveView, "save_view"),
+    (Action::PageDown, "page_down"),
+    (Action::PageUp, "page_up"),
+    (Action::Top, "top"),
+    (Action::Bottom, "bottom"),
+    (Action::Expand, "expand"),
+    (Action::FoldAll, "fold_all"),
+    (Action::Split, "split"),
+    (Action::Rollback, "rollback"),
+    (Action::Update, "update"),
+    (Action::UpdateAll, "update_all"),
+    (Action::CheckUpdates, "check_updates"),
+    (Action::MoveDown, "move_down"),
+    (Action::MoveUp, "move_up"),
+    (Action::Info, "info"),
+    (Action::Toggle, "toggle"),
+    (Action::Sort, "sort"),
+    (Action::SortReverse, "sort_reverse"),
+    (Action::Tag, "tag"),
+    (Action::Annotate, "annotate"),
+    (Action::Copy, "copy"),
+    (Action::Help, "help"),
+];
+
+type Defaults = &'static [(Action, &'static [&'static str])];
+
+const DASHBOARD_KEYS: Defaults = &[
+    (Action::Quit, &["q", "esc"]),
+    (Action::Down, &["j", "down"]),
+    (Action::Up, &["k", "up"]),
+    (Action::Explore, &["e", "enter"]),
+    (Action::Add, &["a"]),
+    (Action::Remove, &["x"]),
+    (Action::Snapshot, &["s"]),
+    (Action::Reload, &["r"]),
+    (Action::Diff, &["d"]),
+    (Action::History, &["h"]),
+    (Action::Plugins, &["p"]),
+    (Action::Sync, &["g"]),
+    (Action::Search, &["/"]),
+    (Action::Sort, &["o"]),
+    (Action::SortReverse, &["O"]),
+    (Action::Help, &["?"]),
+];
+
+const EXPLORE_KEYS: Defaults = &[
+    (Action::Quit, &["q"]),
+    (Action::Back, &["esc"]),
+    (Action::FocusNext, &["tab"]),
+    (Action::FocusPrev, &["backtab"]),
+    (Action::Down, &["j", "down"]),
+    (Action::Up, &["k", "up"]),
+    (Action::Select, &["enter"]),
+    (Action::Search, &["/"]),
+    (Action::Snapshot, &["s"]),
+    (Action::Reload, &["r"]),
+    (Action::Generate, &["g"]),
+    (Action::GenerateLive, &["G"]),
+    (Action::SaveView, &["v"]),
+    (Action::Toggle, &["space"]),
+    (Action::Copy, &["y"]),
+    (Action::Help, &["?"]),
+];
+
+const DIFF_KEYS: Defaults = &[
+    (Action::Quit, &["q"]),
+    (Action::Back, &["esc"]),
+    (Action::Down, &["j", "down"]),
+    (Action::Up, &["k", "up"]),
+    (Action::PageDown, &["d", "pagedown"]),
+    (Action::PageUp, &["u", "pageup"]),
+    (Action::Top, &["g", "home"]),
+    (Action::Bottom, &["G", "end"]),
+    (Action::Expand, &["enter", "o"]),
+    (Action::FoldAll, &["z"]),
+    (Action::Split, &["s"]),
+    (Action::FocusNext, &["n", "tab"]),
+    (Action::FocusPrev, &["N", "backtab"]),
+    (Action::Toggle, &["space"]),
+    (Action::Rollback, &["r"]),
+    (Action::Help, &["?"]),
+];
+
+const HISTORY_KEYS: Defaults = &[
+    (Action::Quit, &["q"]),
+    (Action::Back, &["esc"]),
+    (Action::Down, &["j", "down"]),
+    (Action::Up, &["k", "up"]),
+    (Action::Select, &["enter"]),
+    (Action::Rollback, &["r"]),
+    (Action::Tag, &["t"]),
+    (Action::Annotate, &["m"]),
+    (Action::Help, &["?"]),
+];
+
+const PLUGINS_KEYS: Defaults = &[
+    (Action::Quit, &["q"]),
+    (Action::Back, &["esc"]),
+    (Action::Down, &["j", "down"]),
+    (Action::Up, &["k", "up"]),
+    (Action::Add, &["a"]),
+    (Action::Remove, &["d"]),
+    (Action::Update, &["u"]),
+    (Action::UpdateAll, &["U"]),
+    (Action::CheckUpdates, &["c"]),
+    (Action::MoveDown, &["J"]),
+    (Action::MoveUp, &["K"]),
+    (Action::Info, &["i"]),
+    (Action::Copy, &["y"]),
+    (Action::Help, &["?"]),
+];
+
+impl View {
+    fn defaults(self) -> Defaults {
+        match self {
+            View::Dashboard => DASHBOARD_KEYS,
+            View::Explore => EXPLORE_KEYS,
+            View::Diff => DIFF_KEYS,
+            View::History => HISTORY_KEYS,
+            View::Plugins => PLUGINS_KEYS,
+        }
+    }
+}
+
+impl Action {
+    pub fn from_name(name: &str) -> Option<Self> {
+        ACTION_NAMES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(action, _)| *action)
+    }
+}
+
+/// A single key, optionally with Ctrl. Shift is part of the character for
+/// letters (`J`); Shift+Tab is spelled `backtab`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    ctrl: bool,
+}
+
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("escape", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("shift-tab", KeyCode::BackTab),
+    ("space", KeyCode::Char(' ')),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+impl Key {
+    /// Parse a key as written in config: a single character (`x`, `J`, `/`),
+    /// a key name (`enter`, `esc`, `tab`, `backtab`, `space`, `up`, `pagedown`,
+    /// `f5`, ...), either optionally prefixed with `ctrl-`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (ctrl, rest) = match spec.strip_prefix("ctrl-") {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, spec),
+        };
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let lower = rest.to_ascii_lowercase();
+                match NAMED_KEYS.iter().find(|(name, _)| *name == lower) {
+                    Some((_, code)) => *code,
+                    None => {
+                        let n: u8 = lower.strip_prefix('f')?.parse().ok()?;
+                        if !(1..=12).contains(&n) {
+                            return None;
+                        }
+                        KeyCode::F(n)
+                    }
+                }
+            }
+        };
+        Some(Self { code, ctrl })
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        let code = match event.code {
+            KeyCode::Tab if event.modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            code => code,
+        };
+        code == self.code && event.modifiers.contains(KeyModifiers::CONTROL) == self.ctrl
+    }
+
+    /// Short form for the help bar.
+    fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "S-Tab".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            other => format!("{:?}", other),
+        };
+        if self.ctrl {
+            format!("C-{}", key)
+        } else {
+            key
+        }
+    }
+}
+
+/// Bindings for every view, with the user's `[keymap]` overrides applied.
+#[derive(Debug, Default)]
+pub struct Keymap {
+    overrides: Vec<(Action, Vec<Key>)>,
+}
+
+impl Keymap {
+    /// Build a keymap from the `[keymap]` table of config.toml. Each entry
+    /// replaces the default keys of that action in every view.
+    pub fn new(config: &BTreeMap<String, KeyList>) -> Result<Self, DotsmithError> {
+        let mut overrides = Vec::new();
+        for (name, keys) in config {
+            let action = Action::from_name(name)
+                .ok_or_else(|| DotsmithError::UnknownKeymapAction(name.clone()))?;
+            let keys = keys
+                .keys()
+                .iter()
+                .map(|spec| {
+                    Key::parse(spec)
+                        .ok_or_else(|| DotsmithError::InvalidKey(spec.clone(), name.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            overrides.push((action, keys));
+        }
+        Ok(Self { overrides })
+    }
+
+    /// The keys bound to `action` in `view`; empty if the view doesn't offer it.
+    pub fn keys(&self, view: View, action: Action) -> Vec<Key> {
+        if !view.defaults().iter().any(|(a, _)| *a == action) {
+            return Vec::new();
+        }
+        if let Some((_, keys)) = self.overrides.iter().find(|(a, _)| *a == action) {
+            return keys.clone();
+        }
+        view.defaults()
+            .iter()
+            .filter(|(a, _)| *a == action)
+            .flat_map(|(_, specs)| specs.iter().filter_map(|s| Key::parse(s)))
+            .collect()
+    }
+
+    /// The action a key press triggers in `view`. Rebound actions are checked
+    /// first, so a key taken over from another action's defaults goes to the
+    /// rebound one.
+    pub fn lookup(&self, view: View, event: &KeyEvent) -> Option<Action> {
+        let (rebound, default): (Vec<Action>, Vec<Action>) = view
+            .defaults()
+            .iter()
+            .map(|(action, _)| *action)
+            .partition(|action| self.overrides.iter().any(|(a, _)| a == action));
+        rebound
+            .into_iter()
+            .chain(default)
+            .find(|&action| self.keys(view, action).iter().any(|k| k.matches(event)))
+    }
+
+    /// Help bar label for one or more actions, e.g. `j/k` for down and up:
+    /// the first key of each, joined with `/`.
+    pub fn hint(&self, view: View, actions: &[Action]) -> String {
+        actions
+            .iter()
+            .filter_map(|&action| self.keys(view, action).first().map(Key::label))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyEventKind, KeyEventState};
+
+    fn event(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }
+    }
+
+    fn press(c: char) -> KeyEvent {
+        event(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn keymap(entries: &[(&str, &[&str])]) -> Result<Keymap, DotsmithError> {
+        let config = entries
+            .iter()
+            .map(|(name, keys)| {
+                let keys = keys.iter().map(|k| k.to_string()).collect();
+                (name.to_string(), KeyList::Many(keys))
+            })
+            .collect();
+        Keymap::new(&config)
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(Key::parse("x").unwrap().code, KeyCode::Char('x'));
+        assert_eq!(Key::parse("J").unwrap().code, KeyCode::Char('J'));
+        assert_eq!(Key::parse("Enter").unwrap().code, KeyCode::Enter);
+        assert_eq!(Key::parse("shift-tab").unwrap().code, KeyCode::BackTab);
+        assert_eq!(Key::parse("f5").unwrap().code, KeyCode::F(5));
+        assert_eq!(Key::parse("space").unwrap().code, KeyCode::Char(' '));
+        let ctrl_d = Key::parse("ctrl-d").unwrap();
+        assert!(ctrl_d.ctrl && ctrl_d.code == KeyCode::Char('d'));
+        assert!(Key::parse("").is_none());
+        assert!(Key::parse("hyper").is_none());
+        assert!(Key::parse("f13").is_none());
+    }
+
+    #[test]
+    fn test_default_bindings() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.lookup(View::Dashboard, &press('x')), Some(Action::Remove));
+        assert_eq!(keymap.lookup(View::Plugins, &press('d')), Some(Action::Remove));
+        assert_eq!(keymap.lookup(View::Diff, &press('d')), Some(Action::PageDown));
+        assert_eq!(keymap.lookup(View::Dashboard, &press('z')), None);
+        let down = event(KeyCode::Down, KeyModifiers::NONE);
+        assert_eq!(keymap.lookup(View::History, &down), Some(Action::Down));
+        // Ctrl changes the key
+        let ctrl_j = event(KeyCode::Char('j'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.lookup(View::History, &ctrl_j), None);
+    }
+
+    #[test]
+    fn test_shift_tab_is_backtab() {
+        let keymap = Keymap::default();
+        let shift_tab = event(KeyCode::Tab, KeyModifiers::SHIFT);
+        assert_eq!(keymap.lookup(View::Explore, &shift_tab), Some(Action::FocusPrev));
+    }
+
+    #[test]
+    fn test_override_applies_in_every_view() {
+        let keymap = keymap(&[("remove", &["X"])]).unwrap();
+        assert_eq!(keymap.lookup(View::Dashboard, &press('X')), Some(Action::Remove));
+        assert_eq!(keymap.lookup(View::Plugins, &press('X')), Some(Action::Remove));
+        // The old default keys no longer remove
+        assert_eq!(keymap.lookup(View::Plugins, &press('d')), None);
+        assert_eq!(keymap.lookup(View::Dashboard, &press('x')), None);
+    }
+
+    #[test]
+    fn test_override_takes_key_from_default() {
+        // `d` is the dashboard's diff key by default
+        let keymap = keymap(&[("remove", &["d"])]).unwrap();
+        assert_eq!(keymap.lookup(View::Dashboard, &press('d')), Some(Action::Remove));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(matches!(
+            keymap(&[("explode", &["x"])]),
+            Err(DotsmithError::UnknownKeymapAction(name)) if name == "explode"
+        ));
+        assert!(matches!(
+            keymap(&[("quit", &["ctrl-"])]),
+            Err(DotsmithError::InvalidKey(..))
+        ));
+    }
+
+    #[test]
+    fn test_hint() {
+        let defaults = Keymap::default();
+        assert_eq!(defaults.hint(View::Dashboard, &[Action::Down, Action::Up]), "j/k");
+        assert_eq!(defaults.hint(View::Explore, &[Action::FocusNext]), "Tab");
+
+        let rebound = keymap(&[("page_down", &["ctrl-d"]), ("page_up", &["ctrl-u"])]).unwrap();
+        assert_eq!(rebound.hint(View::Diff, &[Action::PageDown, Action::PageUp]), "C-d/C-u");
+    }
+}
@@ -1,8 +1,9 @@
 mod dashboard;
-mod diff;
+pub mod diff;
 mod event;
 mod explore;
 mod history;
+mod keymap;
 mod plugins;
 mod terminal;
 mod widgets;
@@ -11,11 +12,14 @@ use std::path::PathBuf;
 
 use anyhow::{Result, bail};
 
-use crate::core::config::DotsmithConfig;
+use crate::core::config::{DotsmithConfig, SavedView, validate_view_name};
+use crate::core::detect::DetectedTool;
 use crate::core::manifest::Manifest;
-use crate::core::snapshot::SnapshotEngine;
+use crate::core::secrets::SecretScanner;
+use crate::core::snapshot::{self, MessageContext, SnapshotContent, SnapshotEngine};
 use crate::util;
 
+use dashboard::add::AddToolState;
 use dashboard::{DashboardMode, DashboardState};
 use dashboard::handler::{DashboardAction, handle_key as dashboard_handle_key};
 use dashboard::view::draw_dashboard;
@@ -28,9 +32,13 @@ use explore::view::draw_explore;
 use history::HistoryState;
 use history::handler::{HistoryAction, handle_key as history_handle_key};
 use history::view::draw_history;
+use keymap::Keymap;
 use plugins::PluginState;
 use plugins::handler::{PluginAction, handle_key as plugin_handle_key};
 use plugins::view::draw_plugins;
+use widgets::confirm::{ConfirmOutcome, ConfirmState, draw_confirm};
+use widgets::help_bar::HelpItem;
+use widgets::help_overlay::HelpOverlay;
 use widgets::status_bar::{StatusBar, StatusBarData, Toast, ToastLevel};
 
 /// Which view is currently active.
@@ -43,6 +51,14 @@ enum CurrentView {
     Plugins,
 }
 
+/// A destructive action waiting on a yes/no confirmation.
+enum PendingAction {
+    RemovePlugin { tool: String, name: String },
+    Rollback(i64),
+    /// The hunks marked in the diff view.
+    RollbackHunks,
+}
+
 /// Top-level app state.
 struct App {
     current_view: CurrentView,
@@ -54,12 +70,17 @@ struct App {
     return_view: Option<CurrentView>,
     should_quit: bool,
     toast: Option<Toast>,
+    /// The `?` key overlay is open and takes every key press
+    show_help: bool,
+    /// A destructive action asked about; takes every key press until answered
+    confirm: Option<ConfirmState<PendingAction>>,
 
     // Shared state
     config_dir: PathBuf,
     manifest: Manifest,
     snapshot_engine: SnapshotEngine,
     config: DotsmithConfig,
+    keymap: Keymap,
 }
 
 impl App {
@@ -71,6 +92,15 @@ impl App {
         self.toast = Some(Toast::new(msg.into(), ToastLevel::Error));
     }
 
+    /// Report a finished snapshot, as an error when the secrets check held
+    /// files back.
+    fn toast_snapshot(&mut self, msg: String) {
+        match self.snapshot_engine.take_secret_hits().len() {
+            0 => self.toast_success(msg),
+            n => self.toast_error(format!("{}; skipped {} that may contain secrets", msg, n)),
+        }
+    }
+
     fn expire_toast(&mut self) {
         if let Some(ref toast) = self.toast {
             if toast.is_expired() {
@@ -79,6 +109,20 @@ impl App {
         }
     }
 
+    /// Snapshot `tool` with the `general.snapshot_message` template, saying
+    /// `source` took it. Untracked tools have nothing to snapshot.
+    fn snapshot_tool(&self, tool: &str, source: &str) -> Result<usize> {
+        let Some(entry) = self.manifest.tools.get(tool) else {
+            return Ok(0);
+        };
+        let message = snapshot::format_message(
+            &self.config.general.snapshot_message,
+            &MessageContext::now(tool, source),
+        );
+        self.snapshot_engine
+            .snapshot_tool(tool, &entry.config_paths, Some(&message))
+    }
+
     fn current_tool_name(&self) -> Option<&str> {
         match self.current_view {
             CurrentView::Dashboard => self
@@ -102,36 +146,78 @@ impl App {
         match self.current_view {
             CurrentView::Dashboard => "DASHBOARD",
             CurrentView::Explore => "EXPLORE",
-            CurrentView::Diff => "DIFF",
+            CurrentView::Diff => self
+                .diff_view
+                .as_ref()
+                .map_or("DIFF", |d| d.mode.label()),
             CurrentView::History => "HISTORY",
             CurrentView::Plugins => "PLUGINS",
         }
     }
 
+    /// The keys of the current view, as its help bar lists them.
+    fn help_items(&self) -> Vec<HelpItem> {
+        match self.current_view {
+            CurrentView::Dashboard => dashboard::view::help_items(&self.keymap),
+            CurrentView::Explore => explore::view::help_items(&self.keymap),
+            CurrentView::Diff => diff::view::help_items(&self.keymap),
+            CurrentView::History => history::view::help_items(&self.keymap),
+            CurrentView::Plugins => self
+                .plugins_view
+                .as_ref()
+                .map(|state| plugins::view::help_items(state, &self.keymap))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The popup drawn over the view, if one is open.
+    fn overlay(&self) -> Option<HelpOverlay> {
+        let title = format!("{} keys", self.mode_label().to_lowercase());
+        self.show_help
+            .then(|| HelpOverlay::new(title, self.help_items()))
+    }
+
     fn refresh_dashboard(&mut self) {
-        self.dashboard = DashboardState::from_manifest(&self.manifest);
+        let filter_query = std::mem::take(&mut self.dashboard.filter_query);
+        let (sort_key, sort_desc) = (self.dashboard.sort_key, self.dashboard.sort_desc);
+        self.dashboard = DashboardState::from_manifest(&self.manifest, &self.config_dir);
+        // Keep the list narrowed and ordered across adds, removes and snapshots
+        self.dashboard.filter_query = filter_query;
+        self.dashboard.sort_key = sort_key;
+        self.dashboard.sort_desc = sort_desc;
+        self.dashboard.apply_sort();
     }
 }
 
 /// Entry point for the TUI.
 /// - `None` → open the dashboard
-/// - `Some(tool)` → open the explorer directly for that tool
-pub fn run(tool: Option<&str>) -> Result<()> {
+/// - `Some(tool)` → open the explorer directly for that tool, with `view`'s
+///   category and search applied if given
+pub fn run(tool: Option<&str>, view: Option<&SavedView>) -> Result<()> {
     let config_dir = util::paths::config_dir()?;
     let manifest = Manifest::load(&config_dir).unwrap_or_default();
     let snapshot_engine = SnapshotEngine::open(&config_dir)?;
+    // The passphrase can't be asked for once the TUI owns the terminal
+    snapshot_engine.unlock()?;
     let config = DotsmithConfig::load(&config_dir);
-    let dashboard = DashboardState::from_manifest(&manifest);
+    let (keymap, keymap_error) = match Keymap::new(&config.keymap) {
+        Ok(keymap) => (keymap, None),
+        Err(e) => (Keymap::default(), Some(e)),
+    };
+    let dashboard = DashboardState::from_manifest(&manifest, &config_dir);
 
     let (current_view, explore) = if let Some(tool_name) = tool {
-        let explore = ExploreState::new(tool_name);
-        if explore.is_none() {
+        let Some(mut explore) = ExploreState::new(tool_name, &config_dir) else {
             bail!(
-                "'{}' has no option database. Only Tier 1 tools (tmux, zsh, git) support explore.",
+                "'{}' has no option database. Only Tier 1 tools and user modules with an \
+                 options.toml support explore.",
                 tool_name
             );
+        };
+        if let Some(view) = view {
+            explore.apply_view(view);
         }
-        (CurrentView::Explore, explore)
+        (CurrentView::Explore, Some(explore))
     } else {
         (CurrentView::Dashboard, None)
     };
@@ -146,11 +232,21 @@ pub fn run(tool: Option<&str>) -> Result<()> {
         return_view: None,
         should_quit: false,
         toast: None,
+        show_help: false,
+        confirm: None,
         config_dir,
         manifest,
         snapshot_engine,
         config,
+        keymap,
     };
+    if let Some(e) = keymap_error {
+        app.toast_error(format!("{}; using the default keys", e));
+    } else if let Some(warning) =
+        crate::core::module::ModuleRegistry::user_module_warnings(&app.config_dir).first()
+    {
+        app.toast_error(format!("{}; ignoring it", warning));
+    }
 
     let mut terminal = terminal::init()?;
 
@@ -165,60 +261,11 @@ fn run_loop(terminal: &mut terminal::Tui, app: &mut App) -> Result<()> {
     loop {
         app.expire_toast();
 
-        // Draw
-        terminal.draw(|f| {
-            let area = f.area();
-            let chunks = ratatui::layout::Layout::vertical([
-                ratatui::layout::Constraint::Min(3),
-                ratatui::layout::Constraint::Length(1),
-            ])
-            .split(area);
-
-            match app.current_view {
-                CurrentView::Dashboard => draw_dashboard(f, chunks[0], &app.dashboard),
-                CurrentView::Explore => {
-                    if let Some(ref state) = app.explore {
-                        draw_explore(f, chunks[0], state);
-                    }
-                }
-                CurrentView::Diff => {
-                    if let Some(ref mut state) = app.diff_view {
-                        draw_diff(f, chunks[0], state);
-                    }
-                }
-                CurrentView::History => {
-                    if let Some(ref state) = app.history_view {
-                        draw_history(f, chunks[0], state);
-                    }
-                }
-                CurrentView::Plugins => {
-                    if let Some(ref state) = app.plugins_view {
-                        draw_plugins(f, chunks[0], state);
-                    }
-                }
-            }
-
-            // Status bar
-            let tool_name = app.current_tool_name().map(|s| s.to_string());
-            let status = StatusBar {
-                data: StatusBarData {
-                    mode: app.mode_label(),
-                    tool: tool_name.as_deref(),
-                    toast: app.toast.as_ref(),
-                },
-            };
-            f.render_widget(status, chunks[1]);
-        })?;
+        terminal.draw(|f| draw(f, app))?;
 
         // Poll event
         if let Some(key) = event::next_key_event()? {
-            match app.current_view {
-                CurrentView::Dashboard => handle_dashboard_action(key, app),
-                CurrentView::Explore => handle_explore_action(key, app),
-                CurrentView::Diff => handle_diff_action(key, app),
-                CurrentView::History => handle_history_action(key, app),
-                CurrentView::Plugins => handle_plugin_action(key, app),
-            }
+            handle_key(key, app);
         }
 
         if app.should_quit {
@@ -228,34 +275,125 @@ fn run_loop(terminal: &mut terminal::Tui, app: &mut App) -> Result<()> {
     Ok(())
 }
 
+fn draw(f: &mut ratatui::Frame, app: &mut App) {
+    let area = f.area();
+    let chunks = ratatui::layout::Layout::vertical([
+        ratatui::layout::Constraint::Min(3),
+        ratatui::layout::Constraint::Length(1),
+    ])
+    .split(area);
+
+    match app.current_view {
+        CurrentView::Dashboard => draw_dashboard(f, chunks[0], &app.dashboard, &app.keymap),
+        CurrentView::Explore => {
+            if let Some(ref state) = app.explore {
+                draw_explore(f, chunks[0], state, &app.keymap);
+            }
+        }
+        CurrentView::Diff => {
+            if let Some(ref mut state) = app.diff_view {
+                draw_diff(f, chunks[0], state, &app.keymap);
+            }
+        }
+        CurrentView::History => {
+            if let Some(ref state) = app.history_view {
+                draw_history(f, chunks[0], state, &app.keymap);
+            }
+        }
+        CurrentView::Plugins => {
+            if let Some(ref state) = app.plugins_view {
+                draw_plugins(f, chunks[0], state, &app.keymap);
+            }
+        }
+    }
+
+    // Status bar
+    let tool_name = app.current_tool_name().map(|s| s.to_string());
+    let status = StatusBar {
+        data: StatusBarData {
+            mode: app.mode_label(),
+            tool: tool_name.as_deref(),
+            toast: app.toast.as_ref(),
+        },
+    };
+    f.render_widget(status, chunks[1]);
+
+    if let Some(overlay) = app.overlay() {
+        f.render_widget(overlay, area);
+    }
+    if let Some(ref confirm) = app.confirm {
+        draw_confirm(f, area, confirm);
+    }
+}
+
+fn handle_key(key: crossterm::event::KeyEvent, app: &mut App) {
+    if let Some(confirm) = app.confirm.take() {
+        match confirm.handle_key(&key) {
+            ConfirmOutcome::Confirmed(action) => run_pending(app, action),
+            ConfirmOutcome::Cancelled => {}
+            ConfirmOutcome::Pending(confirm) => app.confirm = Some(confirm),
+        }
+        return;
+    }
+
+    // The overlay swallows everything but the keys that close it, so nothing
+    // moves underneath while it's open
+    if app.show_help {
+        let view = match app.current_view {
+            CurrentView::Dashboard => keymap::View::Dashboard,
+            CurrentView::Explore => keymap::View::Explore,
+            CurrentView::Diff => keymap::View::Diff,
+            CurrentView::History => keymap::View::History,
+            CurrentView::Plugins => keymap::View::Plugins,
+        };
+        if key.code == crossterm::event::KeyCode::Esc
+            || app.keymap.lookup(view, &key) == Some(keymap::Action::Help)
+        {
+            app.show_help = false;
+        }
+        return;
+    }
+
+    match app.current_view {
+        CurrentView::Dashboard => handle_dashboard_action(key, app),
+        CurrentView::Explore => handle_explore_action(key, app),
+        CurrentView::Diff => handle_diff_action(key, app),
+        CurrentView::History => handle_history_action(key, app),
+        CurrentView::Plugins => handle_plugin_action(key, app),
+    }
+}
+
 fn handle_dashboard_action(key: crossterm::event::KeyEvent, app: &mut App) {
-    let action = dashboard_handle_key(key, &mut app.dashboard);
+    let action = dashboard_handle_key(key, &mut app.dashboard, &app.keymap);
     match action {
         DashboardAction::Quit => app.should_quit = true,
+        DashboardAction::ShowHelp => app.show_help = true,
         DashboardAction::Explore(tool_name) => {
-            if let Some(state) = ExploreState::new(&tool_name) {
+            if let Some(state) = ExploreState::new(&tool_name, &app.config_dir) {
                 app.explore = Some(state);
                 app.current_view = CurrentView::Explore;
             }
         }
         DashboardAction::SnapshotAll => {
-            match app
-                .snapshot_engine
-                .snapshot_all(&app.manifest, Some("TUI snapshot"))
-            {
+            let result = app
+                .manifest
+                .tools
+                .keys()
+                .try_fold(0, |total, tool| app.snapshot_tool(tool, "TUI").map(|n| total + n));
+            match result {
                 Ok(count) => {
                     // Reload manifest to pick up updated last_snapshot timestamps
                     if let Ok(m) = Manifest::load(&app.config_dir) {
                         app.manifest = m;
                         app.refresh_dashboard();
                     }
-                    app.toast_success(format!("Snapshotted {} file(s)", count));
+                    app.toast_snapshot(format!("Snapshotted {} file(s)", count));
                 }
                 Err(e) => app.toast_error(format!("Snapshot failed: {}", e)),
             }
         }
         DashboardAction::ReloadSelected(tool_name) => {
-            match crate::core::reload::reload_tool(&tool_name, None) {
+            match crate::core::reload::reload_tool(&app.config_dir, &tool_name, None) {
                 Ok(msg) => app.toast_success(msg),
                 Err(e) => app.toast_error(format!("Reload failed: {}", e)),
             }
@@ -279,10 +417,16 @@ fn handle_dashboard_action(key: crossterm::event::KeyEvent, app: &mut App) {
         }
         DashboardAction::SyncRepo => {
             if let Some(ref repo_path_str) = app.config.general.repo_path {
-                let expanded = util::paths::expand_tilde(repo_path_str);
-                match crate::core::repo::sync_repo(&expanded, &app.manifest) {
+                let push = app.config.general.repo_auto_push;
+                let synced = util::paths::expand_tilde_checked(repo_path_str).and_then(|expanded| {
+                    let scanner = SecretScanner::new(&app.config.general.secret_patterns)?;
+                    crate::core::repo::sync_repo(&expanded, &app.manifest, push, Some(&scanner))
+                });
+                match synced {
                     Ok(result) => {
-                        if result.committed {
+                        if let Some(err) = result.push_error {
+                            app.toast_error(format!("Committed, but {}", err));
+                        } else if result.committed {
                             app.toast_success(format!(
                                 "Synced {} file(s), committed",
                                 result.files_copied
@@ -298,11 +442,29 @@ fn handle_dashboard_action(key: crossterm::event::KeyEvent, app: &mut App) {
             }
         }
         DashboardAction::EnterAddMode => {
-            app.dashboard.mode = DashboardMode::AddInput;
-            app.dashboard.input_buffer.clear();
+            use crate::core::module::ModuleRegistry;
+
+            let modules = ModuleRegistry::module_names(&app.config_dir);
+            app.dashboard.add_tool = Some(AddToolState::new(&modules, &app.manifest));
+            app.dashboard.mode = DashboardMode::AddTool;
         }
-        DashboardAction::AddTool(tool_name) => {
-            add_tool_from_tui(app, &tool_name);
+        DashboardAction::DetectTool(tool_name) => {
+            if app.manifest.has_tool(&tool_name) {
+                app.toast_error(format!("'{}' is already tracked", tool_name));
+                return;
+            }
+            // A failure leaves the list open to pick another tool
+            match crate::core::detect::detect_tool(&app.config_dir, &tool_name, None, None) {
+                Ok(detected) => {
+                    if let Some(ref mut add) = app.dashboard.add_tool {
+                        add.preview = Some(detected);
+                    }
+                }
+                Err(e) => app.toast_error(format!("{}", e)),
+            }
+        }
+        DashboardAction::AddTool(detected) => {
+            add_tool_from_tui(app, &detected);
         }
         DashboardAction::RemoveTool(tool_name) => {
             match app.manifest.remove_tool(&tool_name) {
@@ -321,72 +483,8 @@ fn handle_dashboard_action(key: crossterm::event::KeyEvent, app: &mut App) {
     }
 }
 
-fn add_tool_from_tui(app: &mut App, tool: &str) {
-    use crate::core::detect;
-    use crate::core::manifest::ToolEntry;
-    use crate::core::module::ModuleRegistry;
-    use chrono::Utc;
-
-    if app.manifest.has_tool(tool) {
-        app.toast_error(format!("'{}' is already tracked", tool));
-        return;
-    }
-
-    let (tier, module_def) = match ModuleRegistry::get_builtin(tool) {
-        Some(def) => (1u8, Some(def)),
-        None => (2u8, None),
-    };
-
-    let detect_cmd = module_def
-        .as_ref()
-        .map(|d| d.metadata.detect_command.as_str())
-        .unwrap_or(&format!("which {}", tool))
-        .to_string();
-
-    if let Err(e) = detect::check_installed(tool, &detect_cmd) {
-        app.toast_error(format!("{}", e));
-        return;
-    }
-
-    let config_paths = if let Some(ref def) = module_def {
-        match detect::find_config_paths_from_module(def) {
-            Ok(paths) => paths,
-            Err(e) => {
-                app.toast_error(format!("{}", e));
-                return;
-            }
-        }
-    } else {
-        match detect::auto_detect_config_paths(tool) {
-            Ok(paths) => paths,
-            Err(e) => {
-                app.toast_error(format!("{}", e));
-                return;
-            }
-        }
-    };
-
-    if config_paths.is_empty() {
-        app.toast_error(format!("No config files found for '{}'", tool));
-        return;
-    }
-
-    let plugin_manager = detect::detect_plugin_manager(tool, &config_paths);
-
-    let entry = ToolEntry {
-        tier,
-        config_paths: config_paths
-            .iter()
-            .map(|p| util::paths::contract_tilde(p))
-            .collect(),
-        plugins_managed: false,
-        plugin_manager,
-        added_at: Utc::now(),
-        last_snapshot: None,
-        plugins: std::collections::BTreeMap::new(),
-    };
-
-    if let Err(e) = app.manifest.add_tool(tool, entry) {
+fn add_tool_from_tui(app: &mut App, detected: &DetectedTool) {
+    if let Err(e) = app.manifest.add_tool(&detected.name, detected.to_entry()) {
         app.toast_error(format!("{}", e));
         return;
     }
@@ -397,30 +495,32 @@ fn add_tool_from_tui(app: &mut App, tool: &str) {
     }
 
     app.refresh_dashboard();
-    let tier_label = if tier == 1 { "Tier 1" } else { "Tier 2" };
-    app.toast_success(format!("Added {} ({})", tool, tier_label));
+    app.toast_success(format!(
+        "Added {} (Tier {}, {} path(s))",
+        detected.name,
+        detected.tier,
+        detected.config_paths.len()
+    ));
 }
 
 fn handle_explore_action(key: crossterm::event::KeyEvent, app: &mut App) {
     if let Some(ref mut state) = app.explore {
-        let action = explore_handle_key(key, state);
+        let action = explore_handle_key(key, state, &app.keymap);
         match action {
             ExploreAction::Quit => app.should_quit = true,
+            ExploreAction::ShowHelp => app.show_help = true,
             ExploreAction::Back => {
                 app.explore = None;
                 app.current_view = CurrentView::Dashboard;
             }
             ExploreAction::Snapshot(tool_name) => {
-                match app
-                    .snapshot_engine
-                    .snapshot_all(&app.manifest, Some("TUI snapshot"))
-                {
+                match app.snapshot_tool(&tool_name, "TUI") {
                     Ok(count) => {
                         if let Ok(m) = Manifest::load(&app.config_dir) {
                             app.manifest = m;
                             app.refresh_dashboard();
                         }
-                        app.toast_success(format!(
+                        app.toast_snapshot(format!(
                             "Snapshotted {} ({} files)",
                             tool_name, count
                         ));
@@ -429,98 +529,202 @@ fn handle_explore_action(key: crossterm::event::KeyEvent, app: &mut App) {
                 }
             }
             ExploreAction::Reload(tool_name) => {
-                match crate::core::reload::reload_tool(&tool_name, None) {
+                match crate::core::reload::reload_tool(&app.config_dir, &tool_name, None) {
                     Ok(msg) => app.toast_success(msg),
                     Err(e) => app.toast_error(format!("Reload failed: {}", e)),
                 }
             }
             ExploreAction::GenerateConfig(ref tool_name) => {
-                generate_config(app, tool_name);
+                generate_config(app, tool_name, false);
+            }
+            ExploreAction::GenerateLiveConfig(ref tool_name) => {
+                generate_config(app, tool_name, true);
+            }
+            ExploreAction::SaveView(name) => {
+                let view = state.saved_view();
+                save_view(app, name, view);
+            }
+            ExploreAction::SetOption { name, value } => {
+                let tool = state.tool_name.clone();
+                set_option(app, &tool, &name, &value);
+            }
+            ExploreAction::Copy(name) => {
+                let tool = state.tool_name.clone();
+                copy_option(app, &tool, &name);
             }
             ExploreAction::None => {}
         }
     }
 }
 
-fn generate_config(app: &mut App, tool_name: &str) {
+fn save_view(app: &mut App, name: String, view: SavedView) {
+    if let Err(e) = validate_view_name(&name) {
+        app.toast_error(format!("{}", e));
+        return;
+    }
+
+    app.config.views.insert(name.clone(), view);
+    match app.config.save(&app.config_dir) {
+        Ok(()) => app.toast_success(format!("Saved view '{}'", name)),
+        Err(e) => app.toast_error(format!("Saving view failed: {}", e)),
+    }
+}
+
+/// Write `name = value` into the tool's first config file, in the syntax of
+/// its config format, taking a snapshot first.
+fn set_option(app: &mut App, tool: &str, name: &str, value: &str) {
     use crate::core::module::ModuleRegistry;
+    use crate::core::setting;
 
-    // Get filtered options from explore state
-    let Some(ref state) = app.explore else {
+    let Some(entry) = app.manifest.tools.get(tool) else {
+        app.toast_error(format!("'{}' is not tracked — add it before setting options", tool));
         return;
     };
-
-    let filtered_options: Vec<_> = state
-        .filtered_indices
+    let Some(opt) = app
+        .explore
+        .as_ref()
+        .and_then(|s| s.all_options.iter().find(|o| o.name == name))
+        .cloned()
+    else {
+        return;
+    };
+    let Some((path_str, path)) = entry
+        .config_paths
         .iter()
-        .map(|&i| &state.all_options[i])
-        .collect();
+        .filter_map(|p| Some((p.clone(), util::paths::expand_tilde_checked(p).ok()?)))
+        .find(|(_, p)| p.is_file())
+    else {
+        app.toast_error(format!("No config file for {} to write to", tool));
+        return;
+    };
+    // Write through symlinks so stow-style setups keep their link
+    let target = path.canonicalize().unwrap_or(path);
 
-    if filtered_options.is_empty() {
-        app.toast_error("No options to generate");
+    let content = match std::fs::read_to_string(&target) {
+        Ok(c) => c,
+        Err(e) => {
+            app.toast_error(format!("Failed to read {}: {}", path_str, e));
+            return;
+        }
+    };
+
+    if let Err(e) = app.snapshot_tool(tool, &format!("before setting {}", name)) {
+        app.toast_error(format!("Snapshot failed: {}", e));
+        return;
+    }
+    // Without a snapshot to roll back to, leave the file alone
+    let held_back = app.snapshot_engine.take_secret_hits();
+    if !held_back.is_empty() {
+        app.toast_error(format!(
+            "Not setting {}: {} may contain a secret and wasn't snapshotted",
+            name, held_back[0].0
+        ));
         return;
     }
 
-    // Determine file extension from config_format
-    let ext = match ModuleRegistry::get_builtin(tool_name) {
-        Some(module) => match module.metadata.config_format.as_str() {
-            "tmux" => "conf",
-            "git" => "gitconfig",
-            "shell" => "zsh",
-            "lua" => "lua",
-            "key-value" => "conf",
-            "toml" => "toml",
-            _ => "conf",
-        },
-        None => "conf",
+    let (config_format, set_format) = match ModuleRegistry::load_module(&app.config_dir, tool) {
+        Some(module) => (module.metadata.config_format, module.metadata.set_format),
+        None => ("key-value".to_string(), None),
     };
+    let rendered = setting::render(&config_format, set_format.as_deref(), &opt, value);
+    let updated = setting::apply(&content, &config_format, &rendered);
 
-    // Build commented config content
-    let mut content = format!(
-        "# Generated by dotsmith — {} configuration options\n",
-        tool_name
-    );
-    content.push_str(&format!(
-        "# {} option(s) included\n",
-        filtered_options.len()
-    ));
-    content.push('\n');
+    match util::fs::atomic_write(&target, &updated) {
+        Ok(()) => {
+            let _ = app
+                .snapshot_engine
+                .record_operation("set", std::slice::from_ref(&path_str));
+            app.toast_success(format!("{} in {}", rendered.line.trim(), path_str));
+        }
+        Err(e) => app.toast_error(format!("Write failed: {}", e)),
+    }
+}
+
+/// Copy an option's example, or else a config line setting its default.
+fn copy_option(app: &mut App, tool: &str, name: &str) {
+    use crate::core::module::ModuleRegistry;
+    use crate::core::setting;
 
-    let comment = match ext {
-        "lua" => "--",
-        _ => "#",
+    let Some(opt) = app
+        .explore
+        .as_ref()
+        .and_then(|s| s.all_options.iter().find(|o| o.name == name))
+    else {
+        return;
+    };
+    let text = match opt.example.as_deref().filter(|e| !e.trim().is_empty()) {
+        Some(example) => example.trim().to_string(),
+        None => {
+            let (config_format, set_format) =
+                match ModuleRegistry::load_module(&app.config_dir, tool) {
+                    Some(module) => (module.metadata.config_format, module.metadata.set_format),
+                    None => ("key-value".to_string(), None),
+                };
+            let value = setting::initial_value(opt);
+            setting::render(&config_format, set_format.as_deref(), opt, &value)
+                .line
+                .trim()
+                .to_string()
+        }
     };
+    copy_to_clipboard(app, &text);
+}
 
-    let mut current_category = String::new();
-    for opt in &filtered_options {
-        if opt.category != current_category {
-            current_category = opt.category.clone();
-            content.push_str(&format!(
-                "{} Category: {}\n{}\n",
-                comment, current_category, comment
-            ));
-        }
-
-        let type_str = format!("{:?}", opt.option_type).to_lowercase();
-        let default_str = opt
-            .default
-            .as_deref()
-            .map(|d| format!(", default: {}", d))
-            .unwrap_or_default();
-
-        content.push_str(&format!(
-            "{} {} ({}{})\n",
-            comment, opt.name, type_str, default_str
-        ));
-        content.push_str(&format!("{} {}\n", comment, opt.description));
+fn copy_to_clipboard(app: &mut App, text: &str) {
+    match util::clipboard::copy(text) {
+        Ok(()) => app.toast_success(format!("Copied: {}", text.lines().next().unwrap_or(""))),
+        Err(e) => app.toast_error(format!("Copy failed: {}", e)),
+    }
+}
 
-        if let Some(ref example) = opt.example {
-            content.push_str(&format!("{} {}\n", comment, example));
-        }
+/// Write the options to generate to `generated/`: commented out, or with
+/// `live` set to their defaults as `generated/<tool>.live.<ext>`.
+fn generate_config(app: &mut App, tool_name: &str, live: bool) {
+    use crate::core::generate;
+    use crate::core::module::ModuleRegistry;
+    use crate::core::setting;
+
+    // Get filtered options from explore state
+    let Some(ref state) = app.explore else {
+        return;
+    };
+
+    let filtered_options = state.generation_options();
 
-        content.push_str(&format!("{}\n", comment));
+    if filtered_options.is_empty() {
+        app.toast_error("No options to generate");
+        return;
     }
 
+    let (config_format, set_format) = match ModuleRegistry::load_module(&app.config_dir, tool_name)
+    {
+        Some(module) => (module.metadata.config_format, module.metadata.set_format),
+        None => ("key-value".to_string(), None),
+    };
+    let ext = generate::snippet_extension(&config_format);
+    let (file_name, content, summary) = if live {
+        let set = filtered_options
+            .iter()
+            .filter(|opt| setting::live_value(opt, &config_format).is_some())
+            .count();
+        (
+            format!("{}.live.{}", tool_name, ext),
+            generate::live_config(
+                tool_name,
+                &config_format,
+                set_format.as_deref(),
+                &filtered_options,
+            ),
+            format!("{} settings", set),
+        )
+    } else {
+        (
+            format!("{}.{}", tool_name, ext),
+            generate::config_snippet(tool_name, ext, &filtered_options),
+            format!("{} options", filtered_options.len()),
+        )
+    };
+
     // Write to generated/ directory
     let generated_dir = app.config_dir.join("generated");
     if let Err(e) = std::fs::create_dir_all(&generated_dir) {
@@ -528,15 +732,11 @@ fn generate_config(app: &mut App, tool_name: &str) {
         return;
     }
 
-    let file_path = generated_dir.join(format!("{}.{}", tool_name, ext));
+    let file_path = generated_dir.join(file_name);
     match crate::util::fs::atomic_write(&file_path, &content) {
         Ok(()) => {
             let display_path = crate::util::paths::contract_tilde(&file_path);
-            app.toast_success(format!(
-                "Generated {} ({} options)",
-                display_path,
-                filtered_options.len()
-            ));
+            app.toast_success(format!("Generated {} ({})", display_path, summary));
         }
         Err(e) => app.toast_error(format!("Write failed: {}", e)),
     }
@@ -544,13 +744,27 @@ fn generate_config(app: &mut App, tool_name: &str) {
 
 fn handle_diff_action(key: crossterm::event::KeyEvent, app: &mut App) {
     if let Some(ref mut state) = app.diff_view {
-        let action = diff_handle_key(key, state);
+        let action = diff_handle_key(key, state, &app.keymap);
         match action {
             DiffAction::Quit => app.should_quit = true,
+            DiffAction::ShowHelp => app.show_help = true,
             DiffAction::Back => {
                 app.diff_view = None;
                 app.current_view = app.return_view.take().unwrap_or(CurrentView::Dashboard);
             }
+            DiffAction::RollbackHunks => {
+                let marked = state.selected.len();
+                if marked == 0 {
+                    app.toast_error("No hunks marked");
+                    return;
+                }
+                let prompt = format!(
+                    "Roll back {} hunk(s) in {} file(s)?",
+                    marked,
+                    state.selected_by_file().len()
+                );
+                confirm_or_run(app, prompt, PendingAction::RollbackHunks);
+            }
             DiffAction::None => {}
         }
     }
@@ -561,11 +775,12 @@ fn handle_history_action(key: crossterm::event::KeyEvent, app: &mut App) {
         let Some(ref mut state) = app.history_view else {
             return;
         };
-        history_handle_key(key, state)
+        history_handle_key(key, state, &app.keymap)
     };
 
     match action {
         HistoryAction::Quit => app.should_quit = true,
+        HistoryAction::ShowHelp => app.show_help = true,
         HistoryAction::Back => {
             app.history_view = None;
             app.current_view = CurrentView::Dashboard;
@@ -579,8 +794,10 @@ fn handle_history_action(key: crossterm::event::KeyEvent, app: &mut App) {
             match app.snapshot_engine.get_snapshot(id) {
                 Ok(Some((file_path, snapshot_content))) => {
                     let expanded = util::paths::expand_tilde(&file_path);
-                    let current = std::fs::read_to_string(&expanded).unwrap_or_default();
-                    let diff = DiffState::from_strings(
+                    let current = SnapshotContent::from_bytes(
+                        std::fs::read(&expanded).unwrap_or_default(),
+                    );
+                    let diff = DiffState::from_contents(
                         &tool,
                         &file_path,
                         &snapshot_content,
@@ -595,20 +812,49 @@ fn handle_history_action(key: crossterm::event::KeyEvent, app: &mut App) {
             }
         }
         HistoryAction::Rollback(id) => {
-            let backup_dir = app.config_dir.join("backups");
-            match app.snapshot_engine.rollback(id, &backup_dir) {
-                Ok(file_path) => {
-                    app.toast_success(format!("Rolled back {}", file_path));
-                    // Refresh history
-                    let tool = app
-                        .history_view
-                        .as_ref()
-                        .map(|s| s.tool_name.clone())
-                        .unwrap_or_default();
-                    app.history_view =
-                        Some(HistoryState::new(&tool, &app.snapshot_engine));
+            let file = app
+                .history_view
+                .as_ref()
+                .and_then(|s| s.entries.iter().find(|e| e.id == id))
+                .map(|e| e.file_path.clone())
+                .unwrap_or_default();
+            let change = match app.snapshot_engine.rollback_preview(id) {
+                Ok(Some(diff)) if diff.binary => "binary file differs".to_string(),
+                Ok(Some(diff)) => format!(
+                    "will change {} lines",
+                    util::diff::changed_lines(&diff.old_content, &diff.new_content)
+                ),
+                Ok(None) => "already matches".to_string(),
+                Err(e) => {
+                    app.toast_error(format!("Rollback failed: {}", e));
+                    return;
+                }
+            };
+            let prompt = format!("Roll back {} to snapshot #{}? ({})", file, id, change);
+            confirm_or_run(app, prompt, PendingAction::Rollback(id));
+        }
+        HistoryAction::Tag(id, tag) => match app.snapshot_engine.tag_snapshot(id, &tag) {
+            Ok(_) => {
+                app.toast_success(format!("Tagged snapshot #{} as {}", id, tag));
+                if let Some(ref mut state) = app.history_view {
+                    let selected = state.selected;
+                    *state = HistoryState::new(&state.tool_name, &app.snapshot_engine);
+                    state.selected = selected;
                 }
-                Err(e) => app.toast_error(format!("Rollback failed: {}", e)),
+            }
+            Err(e) => app.toast_error(format!("Tagging failed: {}", e)),
+        },
+        HistoryAction::Annotate(id, message) => {
+            match app.snapshot_engine.annotate(id, &message, false) {
+                Ok(_) => {
+                    app.toast_success(format!("Updated message of snapshot #{}", id));
+                    if let Some(ref mut state) = app.history_view {
+                        let selected = state.selected;
+                        *state = HistoryState::new(&state.tool_name, &app.snapshot_engine);
+                        state.selected = selected;
+                    }
+                }
+                Err(e) => app.toast_error(format!("Updating message failed: {}", e)),
             }
         }
         HistoryAction::None => {}
@@ -620,11 +866,12 @@ fn handle_plugin_action(key: crossterm::event::KeyEvent, app: &mut App) {
         let Some(ref mut state) = app.plugins_view else {
             return;
         };
-        plugin_handle_key(key, state)
+        plugin_handle_key(key, state, &app.keymap)
     };
 
     match action {
         PluginAction::Quit => app.should_quit = true,
+        PluginAction::ShowHelp => app.show_help = true,
         PluginAction::Back => {
             app.plugins_view = None;
             app.current_view = CurrentView::Dashboard;
@@ -655,19 +902,8 @@ fn handle_plugin_action(key: crossterm::event::KeyEvent, app: &mut App) {
                 .as_ref()
                 .map(|s| s.tool_name.clone())
                 .unwrap_or_default();
-            match crate::core::plugin::remove_plugin(
-                &app.config_dir,
-                &mut app.manifest,
-                &tool,
-                &name,
-            ) {
-                Ok(()) => {
-                    app.toast_success(format!("Removed plugin: {}", name));
-                    app.plugins_view = Some(PluginState::new(&tool, &app.manifest, Some(&app.config_dir)));
-                    app.refresh_dashboard();
-                }
-                Err(e) => app.toast_error(format!("Remove failed: {}", e)),
-            }
+            let prompt = format!("Remove plugin {} and delete its clone?", name);
+            confirm_or_run(app, prompt, PendingAction::RemovePlugin { tool, name });
         }
         PluginAction::UpdatePlugin(name) => {
             let tool = app
@@ -680,6 +916,7 @@ fn handle_plugin_action(key: crossterm::event::KeyEvent, app: &mut App) {
                 &app.manifest,
                 &tool,
                 name.as_deref(),
+                None,
             ) {
                 Ok(results) => {
                     let updated: Vec<_> =
@@ -696,6 +933,269 @@ fn handle_plugin_action(key: crossterm::event::KeyEvent, app: &mut App) {
                 Err(e) => app.toast_error(format!("Update failed: {}", e)),
             }
         }
+        PluginAction::CheckUpdates => {
+            let tool = app
+                .plugins_view
+                .as_ref()
+                .map(|s| s.tool_name.clone())
+                .unwrap_or_default();
+            match crate::core::plugin::check_updates(&app.config_dir, &app.manifest, &tool) {
+                Ok(results) => {
+                    if let Some(ref mut state) = app.plugins_view {
+                        for row in &mut state.plugins {
+                            row.behind = results
+                                .iter()
+                                .find(|(name, _)| *name == row.name)
+                                .and_then(|(_, behind)| behind.as_ref().ok().copied());
+                        }
+                        state.show_info = true;
+                    }
+                    let outdated = results
+                        .iter()
+                        .filter(|(_, behind)| behind.as_ref().is_ok_and(|&b| b > 0))
+                        .count();
+                    if let Some((name, Err(e))) = results.iter().find(|(_, b)| b.is_err()) {
+                        app.toast_error(format!("Couldn't check {}: {}", name, e));
+                    } else if outdated == 0 {
+                        app.toast_success("All plugins up to date");
+                    } else {
+                        app.toast_success(format!("{} plugin(s) have updates", outdated));
+                    }
+                }
+                Err(e) => app.toast_error(format!("Check failed: {}", e)),
+            }
+        }
+        PluginAction::MovePlugin(name, other, before) => {
+            let tool = app
+                .plugins_view
+                .as_ref()
+                .map(|s| s.tool_name.clone())
+                .unwrap_or_default();
+            match crate::core::plugin::move_plugin(
+                &app.config_dir,
+                &mut app.manifest,
+                &tool,
+                &name,
+                &other,
+                before,
+            ) {
+                Ok(()) => {
+                    let mut state = PluginState::new(&tool, &app.manifest, Some(&app.config_dir));
+                    if let Some(i) = state.plugins.iter().position(|p| p.name == name) {
+                        state.selected = i;
+                    }
+                    app.plugins_view = Some(state);
+                }
+                Err(e) => app.toast_error(format!("Move failed: {}", e)),
+            }
+        }
+        PluginAction::Copy(url) => copy_to_clipboard(app, &url),
         PluginAction::None => {}
     }
 }
+
+/// Ask before running a destructive action, unless
+/// `general.confirm_destructive` is off.
+fn confirm_or_run(app: &mut App, prompt: String, action: PendingAction) {
+    if app.config.general.confirm_destructive {
+        app.confirm = Some(ConfirmState::new(prompt, action));
+    } else {
+        run_pending(app, action);
+    }
+}
+
+fn run_pending(app: &mut App, action: PendingAction) {
+    match action {
+        PendingAction::RemovePlugin { tool, name } => {
+            match crate::core::plugin::remove_plugin(
+                &app.config_dir,
+                &mut app.manifest,
+                &tool,
+                &name,
+            ) {
+                Ok(()) => {
+                    app.toast_success(format!("Removed plugin: {}", name));
+                    let state = PluginState::new(&tool, &app.manifest, Some(&app.config_dir));
+                    app.plugins_view = Some(state);
+                    app.refresh_dashboard();
+                }
+                Err(e) => app.toast_error(format!("Remove failed: {}", e)),
+            }
+        }
+        PendingAction::Rollback(id) => {
+            let backup_dir = app.config_dir.join("backups");
+            match app.snapshot_engine.rollback(id, &backup_dir) {
+                Ok(file_path) => {
+                    app.toast_success(format!("Rolled back {}", file_path));
+                    // Refresh history
+                    let tool = app
+                        .history_view
+                        .as_ref()
+                        .map(|s| s.tool_name.clone())
+                        .unwrap_or_default();
+                    app.history_view =
+                        Some(HistoryState::new(&tool, &app.snapshot_engine));
+                }
+                Err(e) => app.toast_error(format!("Rollback failed: {}", e)),
+            }
+        }
+        PendingAction::RollbackHunks => {
+            let Some(ref mut state) = app.diff_view else {
+                return;
+            };
+            let backup_dir = app.config_dir.join("backups");
+            let marked = state.selected.len();
+            for (file, chosen) in state.selected_by_file() {
+                let diff = &state.files[file];
+                match app.snapshot_engine.rollback_hunks(diff, &chosen, &backup_dir) {
+                    Ok(content) => state.set_new_content(file, content),
+                    Err(e) => {
+                        app.toast_error(format!("Rollback failed: {}", e));
+                        return;
+                    }
+                }
+            }
+            app.toast_success(format!("Rolled back {} hunk(s)", marked));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{Terminal, backend::TestBackend};
+    use tempfile::TempDir;
+
+    fn app(config_dir: &std::path::Path) -> App {
+        let manifest = Manifest::default();
+        App {
+            current_view: CurrentView::Dashboard,
+            dashboard: DashboardState::from_manifest(&manifest, config_dir),
+            explore: None,
+            diff_view: None,
+            history_view: None,
+            plugins_view: None,
+            return_view: None,
+            should_quit: false,
+            toast: None,
+            show_help: false,
+            confirm: None,
+            config_dir: config_dir.to_path_buf(),
+            manifest,
+            snapshot_engine: SnapshotEngine::open(config_dir).unwrap(),
+            config: DotsmithConfig::default(),
+            keymap: Keymap::default(),
+        }
+    }
+
+    fn screen(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| draw(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    fn press(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_help_flag_selects_overlay() {
+        let tmp = TempDir::new().unwrap();
+        let mut app = app(tmp.path());
+
+        assert!(app.overlay().is_none());
+        assert!(!screen(&mut app).contains("dashboard keys"));
+
+        app.show_help = true;
+        assert!(app.overlay().is_some());
+        let drawn = screen(&mut app);
+        assert!(drawn.contains("dashboard keys"));
+        assert!(drawn.contains("Esc to close"));
+    }
+
+    #[test]
+    fn test_help_overlay_swallows_keys() {
+        let tmp = TempDir::new().unwrap();
+        let mut app = app(tmp.path());
+
+        handle_key(press('?'), &mut app);
+        assert!(app.show_help);
+
+        // `q` would quit and `a` would start adding a tool underneath
+        handle_key(press('q'), &mut app);
+        handle_key(press('a'), &mut app);
+        assert!(app.show_help);
+        assert!(!app.should_quit);
+        assert_eq!(app.dashboard.mode, DashboardMode::Normal);
+
+        handle_key(press('?'), &mut app);
+        assert!(!app.show_help);
+
+        handle_key(press('?'), &mut app);
+        handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE), &mut app);
+        assert!(!app.show_help);
+        assert!(!app.should_quit);
+    }
+
+    #[test]
+    fn test_destructive_action_waits_for_yes() {
+        let tmp = TempDir::new().unwrap();
+        let mut app = app(tmp.path());
+
+        confirm_or_run(&mut app, "Roll back?".to_string(), PendingAction::Rollback(999));
+        assert!(app.confirm.is_some());
+        handle_key(press('j'), &mut app);
+        assert!(app.confirm.is_some());
+        handle_key(press('n'), &mut app);
+        assert!(app.confirm.is_none());
+        assert!(app.toast.is_none());
+
+        // Confirming runs it; there is no snapshot 999, so it reports failure
+        confirm_or_run(&mut app, "Roll back?".to_string(), PendingAction::Rollback(999));
+        handle_key(press('y'), &mut app);
+        assert!(app.confirm.is_none());
+        assert!(app.toast.as_ref().unwrap().message.starts_with("Rollback failed"));
+
+        // With confirmation turned off the action runs straight away
+        app.toast = None;
+        app.config.general.confirm_destructive = false;
+        confirm_or_run(&mut app, "Roll back?".to_string(), PendingAction::Rollback(999));
+        assert!(app.confirm.is_none());
+        assert!(app.toast.is_some());
+    }
+
+    #[test]
+    fn test_rollback_marked_hunks() {
+        let tmp = TempDir::new().unwrap();
+        let mut app = app(tmp.path());
+        let conf = tmp.path().join("tmux.conf");
+        std::fs::write(&conf, "mouse off\nkeep\nprefix C-b\n").unwrap();
+
+        let path = conf.display().to_string();
+        let (old, new) = ("mouse on\nkeep\nprefix C-a\n", "mouse off\nkeep\nprefix C-b\n");
+        app.diff_view = Some(DiffState::from_strings("tmux", &path, old, new));
+        app.current_view = CurrentView::Diff;
+
+        handle_key(press('r'), &mut app);
+        assert!(app.confirm.is_none());
+        assert!(app.toast.as_ref().unwrap().message.starts_with("No hunks"));
+
+        // Mark only the second hunk
+        handle_key(press('n'), &mut app);
+        handle_key(press(' '), &mut app);
+        handle_key(press('r'), &mut app);
+        handle_key(press('y'), &mut app);
+        assert_eq!(
+            std::fs::read_to_string(&conf).unwrap(),
+            "mouse off\nkeep\nprefix C-a\n"
+        );
+        assert!(tmp.path().join("backups").read_dir().unwrap().next().is_some());
+
+        // The view now diffs against what was written
+        let state = app.diff_view.as_ref().unwrap();
+        assert_eq!(state.hunks.len(), 1);
+        assert!(state.selected.is_empty());
+    }
+}
@@ -1,53 +1,85 @@
 use crossterm::event::{KeyCode, KeyEvent};
 
 use super::{PluginMode, PluginState};
+use crate::tui::keymap::{Action, Keymap, View};
 
 pub enum PluginAction {
     None,
     Back,
+    ShowHelp,
     Quit,
     AddPlugin(String),
     RemovePlugin(String),
     UpdatePlugin(Option<String>),
+    /// Fetch every plugin and record how far behind upstream it is
+    CheckUpdates,
+    /// Move `name` directly before (`true`) or after `other` in the load order
+    MovePlugin(String, String, bool),
+    /// Copy `text` to the clipboard.
+    Copy(String),
 }
 
-pub fn handle_key(key: KeyEvent, state: &mut PluginState) -> PluginAction {
+pub fn handle_key(key: KeyEvent, state: &mut PluginState, keymap: &Keymap) -> PluginAction {
     match state.mode {
-        PluginMode::List => handle_list_key(key, state),
+        PluginMode::List => handle_list_key(key, state, keymap),
         PluginMode::AddInput => handle_add_input_key(key, state),
     }
 }
 
-fn handle_list_key(key: KeyEvent, state: &mut PluginState) -> PluginAction {
-    match key.code {
-        KeyCode::Char('q') => PluginAction::Quit,
-        KeyCode::Esc => PluginAction::Back,
-        KeyCode::Char('j') | KeyCode::Down => {
+fn handle_list_key(key: KeyEvent, state: &mut PluginState, keymap: &Keymap) -> PluginAction {
+    let Some(action) = keymap.lookup(View::Plugins, &key) else {
+        return PluginAction::None;
+    };
+    match action {
+        Action::Quit => PluginAction::Quit,
+        Action::Help => PluginAction::ShowHelp,
+        Action::Back => PluginAction::Back,
+        Action::Down => {
             state.select_next();
             PluginAction::None
         }
-        KeyCode::Char('k') | KeyCode::Up => {
+        Action::Up => {
             state.select_prev();
             PluginAction::None
         }
-        KeyCode::Char('a') if state.supported => {
+        Action::Add if state.editable() => {
             state.mode = PluginMode::AddInput;
             state.input_buffer.clear();
             PluginAction::None
         }
-        KeyCode::Char('d') if state.supported => state
+        Action::Remove if state.editable() => state
             .selected_plugin()
             .map(|p| PluginAction::RemovePlugin(p.name.clone()))
             .unwrap_or(PluginAction::None),
-        KeyCode::Char('u') if state.supported => state
+        Action::Update if state.editable() => state
             .selected_plugin()
             .map(|p| PluginAction::UpdatePlugin(Some(p.name.clone())))
             .unwrap_or(PluginAction::None),
-        KeyCode::Char('U') if state.supported => PluginAction::UpdatePlugin(None),
-        KeyCode::Char('i') if state.supported => {
+        Action::UpdateAll if state.editable() => PluginAction::UpdatePlugin(None),
+        Action::CheckUpdates if state.editable() => PluginAction::CheckUpdates,
+        Action::MoveDown if state.editable() => move_selected(state, false),
+        Action::MoveUp if state.editable() => move_selected(state, true),
+        Action::Info if state.supported => {
             state.show_info = !state.show_info;
             PluginAction::None
         }
+        Action::Copy if state.supported => state
+            .selected_plugin()
+            .map(|p| PluginAction::Copy(p.url.clone()))
+            .unwrap_or(PluginAction::None),
+        _ => PluginAction::None,
+    }
+}
+
+/// Swap the selected plugin with its neighbour below (`up == false`) or above.
+fn move_selected(state: &PluginState, up: bool) -> PluginAction {
+    let neighbour = if up {
+        state.selected.checked_sub(1)
+    } else {
+        Some(state.selected + 1)
+    };
+    match (state.selected_plugin(), neighbour.and_then(|i| state.plugins.get(i))) {
+        (Some(p), Some(other)) => PluginAction::MovePlugin(p.name.clone(), other.name.clone(), up),
         _ => PluginAction::None,
     }
 }
@@ -84,6 +116,7 @@ fn handle_add_input_key(key: KeyEvent, state: &mut PluginState) -> PluginAction
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::config::KeyList;
     use crate::tui::plugins::{PluginMode, PluginRow, PluginState};
     use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
 
@@ -107,6 +140,8 @@ mod tests {
                     url: "https://github.com/zsh-users/zsh-autosuggestions".into(),
                     description: None,
                     config_excerpt: None,
+                    pinned: None,
+                    behind: None,
                 },
                 PluginRow {
                     name: "zsh-syntax-highlighting".into(),
@@ -115,6 +150,8 @@ mod tests {
                     url: "https://github.com/zsh-users/zsh-syntax-highlighting".into(),
                     description: None,
                     config_excerpt: None,
+                    pinned: None,
+                    behind: None,
                 },
             ],
             selected: 0,
@@ -122,6 +159,7 @@ mod tests {
             input_buffer: String::new(),
             supported: true,
             show_info: false,
+            managed_by: None,
         }
     }
 
@@ -129,7 +167,7 @@ mod tests {
     fn test_quit() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('q')), &mut state),
+            handle_key(make_key(KeyCode::Char('q')), &mut state, &Keymap::default()),
             PluginAction::Quit
         ));
     }
@@ -138,15 +176,26 @@ mod tests {
     fn test_back() {
         let mut state = sample_state();
         assert!(matches!(
-            handle_key(make_key(KeyCode::Esc), &mut state),
+            handle_key(make_key(KeyCode::Esc), &mut state, &Keymap::default()),
             PluginAction::Back
         ));
     }
 
+    #[test]
+    fn test_copy_selected_url() {
+        let mut state = sample_state();
+        state.selected = 1;
+        let action = handle_key(make_key(KeyCode::Char('y')), &mut state, &Keymap::default());
+        assert!(matches!(
+            action,
+            PluginAction::Copy(url) if url == "https://github.com/zsh-users/zsh-syntax-highlighting"
+        ));
+    }
+
     #[test]
     fn test_enter_add_mode() {
         let mut state = sample_state();
-        handle_key(make_key(KeyCode::Char('a')), &mut state);
+        handle_key(make_key(KeyCode::Char('a')), &mut state, &Keymap::default());
         assert_eq!(state.mode, PluginMode::AddInput);
     }
 
@@ -154,12 +203,12 @@ mod tests {
     fn test_add_input_type_and_submit() {
         let mut state = sample_state();
         state.mode = PluginMode::AddInput;
-        handle_key(make_key(KeyCode::Char('f')), &mut state);
-        handle_key(make_key(KeyCode::Char('o')), &mut state);
-        handle_key(make_key(KeyCode::Char('o')), &mut state);
+        handle_key(make_key(KeyCode::Char('f')), &mut state, &Keymap::default());
+        handle_key(make_key(KeyCode::Char('o')), &mut state, &Keymap::default());
+        handle_key(make_key(KeyCode::Char('o')), &mut state, &Keymap::default());
         assert_eq!(state.input_buffer, "foo");
 
-        let action = handle_key(make_key(KeyCode::Enter), &mut state);
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
         assert!(matches!(action, PluginAction::AddPlugin(s) if s == "foo"));
         assert_eq!(state.mode, PluginMode::List);
     }
@@ -168,8 +217,8 @@ mod tests {
     fn test_add_input_cancel() {
         let mut state = sample_state();
         state.mode = PluginMode::AddInput;
-        handle_key(make_key(KeyCode::Char('x')), &mut state);
-        handle_key(make_key(KeyCode::Esc), &mut state);
+        handle_key(make_key(KeyCode::Char('x')), &mut state, &Keymap::default());
+        handle_key(make_key(KeyCode::Esc), &mut state, &Keymap::default());
         assert_eq!(state.mode, PluginMode::List);
         assert!(state.input_buffer.is_empty());
     }
@@ -178,9 +227,9 @@ mod tests {
     fn test_add_input_backspace() {
         let mut state = sample_state();
         state.mode = PluginMode::AddInput;
-        handle_key(make_key(KeyCode::Char('a')), &mut state);
-        handle_key(make_key(KeyCode::Char('b')), &mut state);
-        handle_key(make_key(KeyCode::Backspace), &mut state);
+        handle_key(make_key(KeyCode::Char('a')), &mut state, &Keymap::default());
+        handle_key(make_key(KeyCode::Char('b')), &mut state, &Keymap::default());
+        handle_key(make_key(KeyCode::Backspace), &mut state, &Keymap::default());
         assert_eq!(state.input_buffer, "a");
     }
 
@@ -188,21 +237,21 @@ mod tests {
     fn test_add_empty_input() {
         let mut state = sample_state();
         state.mode = PluginMode::AddInput;
-        let action = handle_key(make_key(KeyCode::Enter), &mut state);
+        let action = handle_key(make_key(KeyCode::Enter), &mut state, &Keymap::default());
         assert!(matches!(action, PluginAction::None));
     }
 
     #[test]
     fn test_remove_plugin() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('d')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('d')), &mut state, &Keymap::default());
         assert!(matches!(action, PluginAction::RemovePlugin(s) if s == "zsh-autosuggestions"));
     }
 
     #[test]
     fn test_update_plugin() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('u')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('u')), &mut state, &Keymap::default());
         assert!(
             matches!(action, PluginAction::UpdatePlugin(Some(s)) if s == "zsh-autosuggestions")
         );
@@ -211,16 +260,77 @@ mod tests {
     #[test]
     fn test_update_all() {
         let mut state = sample_state();
-        let action = handle_key(make_key(KeyCode::Char('U')), &mut state);
+        let action = handle_key(make_key(KeyCode::Char('U')), &mut state, &Keymap::default());
         assert!(matches!(action, PluginAction::UpdatePlugin(None)));
     }
 
+    #[test]
+    fn test_check_updates() {
+        let mut state = sample_state();
+        let action = handle_key(make_key(KeyCode::Char('c')), &mut state, &Keymap::default());
+        assert!(matches!(action, PluginAction::CheckUpdates));
+
+        state.managed_by = Some("lazy.nvim".to_string());
+        let action = handle_key(make_key(KeyCode::Char('c')), &mut state, &Keymap::default());
+        assert!(matches!(action, PluginAction::None));
+    }
+
+    #[test]
+    fn test_move_plugin_keys() {
+        let mut state = sample_state();
+        let action = handle_key(make_key(KeyCode::Char('J')), &mut state, &Keymap::default());
+        assert!(matches!(
+            action,
+            PluginAction::MovePlugin(n, o, false)
+                if n == "zsh-autosuggestions" && o == "zsh-syntax-highlighting"
+        ));
+        // Already first: nothing to move above
+        let action = handle_key(make_key(KeyCode::Char('K')), &mut state, &Keymap::default());
+        assert!(matches!(action, PluginAction::None));
+
+        state.selected = 1;
+        let action = handle_key(make_key(KeyCode::Char('K')), &mut state, &Keymap::default());
+        assert!(matches!(
+            action,
+            PluginAction::MovePlugin(n, o, true)
+                if n == "zsh-syntax-highlighting" && o == "zsh-autosuggestions"
+        ));
+        let action = handle_key(make_key(KeyCode::Char('J')), &mut state, &Keymap::default());
+        assert!(matches!(action, PluginAction::None));
+    }
+
+    #[test]
+    fn test_remove_with_custom_key() {
+        let config = [("remove".to_string(), KeyList::One("x".to_string()))].into();
+        let keymap = Keymap::new(&config).unwrap();
+        let mut state = sample_state();
+        let action = handle_key(make_key(KeyCode::Char('x')), &mut state, &keymap);
+        assert!(matches!(action, PluginAction::RemovePlugin(s) if s == "zsh-autosuggestions"));
+        let action = handle_key(make_key(KeyCode::Char('d')), &mut state, &keymap);
+        assert!(matches!(action, PluginAction::None));
+    }
+
+    #[test]
+    fn test_read_only_ignores_changes() {
+        let mut state = sample_state();
+        state.managed_by = Some("lazy.nvim".to_string());
+        for c in ['a', 'd', 'u', 'U', 'J'] {
+            assert!(matches!(
+                handle_key(make_key(KeyCode::Char(c)), &mut state, &Keymap::default()),
+                PluginAction::None
+            ));
+        }
+        assert_eq!(state.mode, PluginMode::List);
+        handle_key(make_key(KeyCode::Char('i')), &mut state, &Keymap::default());
+        assert!(state.show_info);
+    }
+
     #[test]
     fn test_navigate() {
         let mut state = sample_state();
-        handle_key(make_key(KeyCode::Char('j')), &mut state);
+        handle_key(make_key(KeyCode::Char('j')), &mut state, &Keymap::default());
         assert_eq!(state.selected, 1);
-        handle_key(make_key(KeyCode::Char('k')), &mut state);
+        handle_key(make_key(KeyCode::Char('k')), &mut state, &Keymap::default());
         assert_eq!(state.selected, 0);
     }
 
@@ -234,13 +344,14 @@ mod tests {
             input_buffer: String::new(),
             supported: false,
             show_info: false,
+            managed_by: None,
         };
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('a')), &mut state),
+            handle_key(make_key(KeyCode::Char('a')), &mut state, &Keymap::default()),
             PluginAction::None
         ));
         assert!(matches!(
-            handle_key(make_key(KeyCode::Char('d')), &mut state),
+            handle_key(make_key(KeyCode::Char('d')), &mut state, &Keymap::default()),
             PluginAction::None
         ));
     }
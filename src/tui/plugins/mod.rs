@@ -15,6 +15,10 @@ pub struct PluginRow {
     pub url: String,
     pub description: Option<String>,
     pub config_excerpt: Option<String>,
+    /// Commit the plugin is held at: its pin, or the lazy-lock.json entry
+    pub pinned: Option<String>,
+    /// Upstream commits not yet pulled, once checked
+    pub behind: Option<usize>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -31,10 +35,16 @@ pub struct PluginState {
     pub input_buffer: String,
     pub supported: bool,
     pub show_info: bool,
+    /// Set when another plugin manager owns the plugins; the list is read-only
+    pub managed_by: Option<String>,
 }
 
 impl PluginState {
     pub fn new(tool: &str, manifest: &Manifest, config_dir: Option<&Path>) -> Self {
+        if let Some(manager) = plugin::external_manager(tool) {
+            return Self::new_external(tool, manager, manifest);
+        }
+
         let supported = plugin::validate_tool_supported(tool).is_ok();
         let plugins = if supported {
             plugin::list_plugins(manifest, tool)
@@ -53,6 +63,11 @@ impl PluginState {
                         ),
                         description: info.as_ref().and_then(|i| i.description.clone()),
                         config_excerpt: info.as_ref().and_then(|i| i.config_excerpt.clone()),
+                        pinned: manifest
+                            .get_tool(tool)
+                            .and_then(|t| t.plugins.get(&name))
+                            .and_then(|p| p.pinned_ref.clone()),
+                        behind: None,
                         name,
                         repo,
                         init,
@@ -71,9 +86,54 @@ impl PluginState {
             input_buffer: String::new(),
             supported,
             show_info: false,
+            managed_by: None,
         }
     }
 
+    /// Read-only rows for plugins another manager owns, from its lockfile.
+    /// README details come from the manager's own clone when it exists.
+    fn new_external(tool: &str, manager: &str, manifest: &Manifest) -> Self {
+        let plugins = plugin::list_lazy_plugins(manifest, tool)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| {
+                let info = plugin::lazy_plugin_dir(&p.name)
+                    .filter(|dir| dir.is_dir())
+                    .map(|dir| plugin_info::scan_plugin(&dir, &p.name, ""));
+                let url = info.as_ref().map(|i| i.url.clone()).unwrap_or_default();
+                PluginRow {
+                    repo: url
+                        .strip_prefix("https://github.com/")
+                        .unwrap_or(&url)
+                        .to_string(),
+                    init: String::new(),
+                    description: info.as_ref().and_then(|i| i.description.clone()),
+                    config_excerpt: info.as_ref().and_then(|i| i.config_excerpt.clone()),
+                    pinned: Some(p.commit),
+                    behind: None,
+                    url,
+                    name: p.name,
+                }
+            })
+            .collect();
+
+        Self {
+            tool_name: tool.to_string(),
+            plugins,
+            selected: 0,
+            mode: PluginMode::List,
+            input_buffer: String::new(),
+            supported: true,
+            show_info: false,
+            managed_by: Some(manager.to_string()),
+        }
+    }
+
+    /// Whether plugins can be added, removed, updated, or reordered here.
+    pub fn editable(&self) -> bool {
+        self.supported && self.managed_by.is_none()
+    }
+
     pub fn select_next(&mut self) {
         if !self.plugins.is_empty() {
             self.selected = (self.selected + 1).min(self.plugins.len() - 1);
@@ -104,6 +164,8 @@ mod tests {
                     url: "https://github.com/zsh-users/zsh-autosuggestions".into(),
                     description: None,
                     config_excerpt: None,
+                    pinned: None,
+                    behind: None,
                 },
                 PluginRow {
                     name: "zsh-syntax-highlighting".into(),
@@ -112,6 +174,8 @@ mod tests {
                     url: "https://github.com/zsh-users/zsh-syntax-highlighting".into(),
                     description: None,
                     config_excerpt: None,
+                    pinned: None,
+                    behind: None,
                 },
             ],
             selected: 0,
@@ -119,6 +183,7 @@ mod tests {
             input_buffer: String::new(),
             supported: true,
             show_info: false,
+            managed_by: None,
         }
     }
 
@@ -151,6 +216,7 @@ mod tests {
             input_buffer: String::new(),
             supported: false,
             show_info: false,
+            managed_by: None,
         };
         assert!(state.selected_plugin().is_none());
         assert!(!state.supported);
@@ -7,9 +7,10 @@ use ratatui::{
 };
 
 use super::{PluginMode, PluginState};
+use crate::tui::keymap::{Action, Keymap, View};
 use crate::tui::widgets::help_bar::{HelpBar, HelpItem};
 
-pub fn draw_plugins(f: &mut Frame, area: Rect, state: &PluginState) {
+pub fn draw_plugins(f: &mut Frame, area: Rect, state: &PluginState, keymap: &Keymap) {
     let chunks = Layout::vertical([
         Constraint::Min(5),    // content
         Constraint::Length(1), // help/input bar
@@ -29,12 +30,16 @@ pub fn draw_plugins(f: &mut Frame, area: Rect, state: &PluginState) {
         )
         .alignment(Alignment::Center);
         f.render_widget(msg, chunks[0]);
-        draw_help_minimal(f, chunks[1]);
+        draw_help(f, chunks[1], state, keymap);
         return;
     }
 
     if state.plugins.is_empty() {
-        let msg = Paragraph::new("No plugins installed. Press 'a' to add one.")
+        let text = match state.managed_by {
+            Some(ref manager) => format!("No plugins locked by {}.", manager),
+            None => "No plugins installed. Press 'a' to add one.".to_string(),
+        };
+        let msg = Paragraph::new(text)
             .block(
                 Block::default()
                     .title(format!(" Plugins: {} ", state.tool_name))
@@ -58,16 +63,20 @@ pub fn draw_plugins(f: &mut Frame, area: Rect, state: &PluginState) {
     }
 
     match state.mode {
-        PluginMode::List => draw_help(f, chunks[1]),
+        PluginMode::List if state.managed_by.is_some() => {
+            draw_help_read_only(f, chunks[1], state, keymap)
+        }
+        PluginMode::List => draw_help(f, chunks[1], state, keymap),
         PluginMode::AddInput => draw_input(f, chunks[1], state),
     }
 }
 
 fn draw_plugin_table(f: &mut Frame, area: Rect, state: &PluginState) {
+    let read_only = state.managed_by.is_some();
     let header = Row::new(vec![
         Cell::from("Name"),
         Cell::from("Repo"),
-        Cell::from("Init File"),
+        Cell::from(if read_only { "Commit" } else { "Init File" }),
     ])
     .style(
         Style::default()
@@ -85,10 +94,16 @@ fn draw_plugin_table(f: &mut Frame, area: Rect, state: &PluginState) {
             } else {
                 Style::default()
             };
+            let last = if read_only {
+                let commit = plugin.pinned.as_deref().unwrap_or_default();
+                commit[..7.min(commit.len())].to_string()
+            } else {
+                plugin.init.clone()
+            };
             Row::new(vec![
                 Cell::from(plugin.name.clone()),
                 Cell::from(plugin.repo.clone()),
-                Cell::from(plugin.init.clone()),
+                Cell::from(last),
             ])
             .style(style)
         })
@@ -174,11 +189,37 @@ fn draw_plugin_detail(f: &mut Frame, area: Rect, state: &PluginState) {
                         .add_modifier(Modifier::UNDERLINED),
                 ),
             ]),
-            Line::from(vec![
+        ];
+
+        if !plugin.init.is_empty() {
+            lines.push(Line::from(vec![
                 Span::styled("Init: ", Style::default().fg(Color::Yellow)),
                 Span::raw(plugin.init.clone()),
-            ]),
-        ];
+            ]));
+        }
+        if let Some(ref commit) = plugin.pinned {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    if state.managed_by.is_some() { "Lock: " } else { "Pin:  " },
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(commit.clone()),
+            ]));
+        }
+        if let Some(behind) = plugin.behind {
+            let status = if behind == 0 {
+                Span::styled("up to date", Style::default().fg(Color::Green))
+            } else {
+                Span::styled(
+                    format!("{} commits behind", behind),
+                    Style::default().fg(Color::Yellow),
+                )
+            };
+            lines.push(Line::from(vec![
+                Span::styled("Upstream: ", Style::default().fg(Color::Yellow)),
+                status,
+            ]));
+        }
 
         if let Some(ref desc) = plugin.description {
             lines.push(Line::from(""));
@@ -232,52 +273,89 @@ fn draw_plugin_detail(f: &mut Frame, area: Rect, state: &PluginState) {
     f.render_widget(paragraph, area);
 }
 
-fn draw_help(f: &mut Frame, area: Rect) {
-    let help = HelpBar::new(vec![
-        HelpItem {
-            key: "a",
-            action: "add",
-        },
-        HelpItem {
-            key: "d",
-            action: "remove",
-        },
-        HelpItem {
-            key: "u",
-            action: "update",
-        },
-        HelpItem {
-            key: "U",
-            action: "update all",
-        },
+/// Every key the plugins view currently offers, shown in the help bar and the
+/// `?` overlay. Unsupported tools only offer leaving, and plugins locked by
+/// another manager can only be inspected.
+pub fn help_items(state: &PluginState, keymap: &Keymap) -> Vec<HelpItem> {
+    let mut items = if !state.supported {
+        Vec::new()
+    } else if state.managed_by.is_some() {
+        vec![
+            HelpItem {
+                key: keymap.hint(View::Plugins, &[Action::Info]),
+                action: "info",
+            },
+            HelpItem {
+                key: keymap.hint(View::Plugins, &[Action::Copy]),
+                action: "copy url",
+            },
+        ]
+    } else {
+        vec![
+            HelpItem {
+                key: keymap.hint(View::Plugins, &[Action::Add]),
+                action: "add",
+            },
+            HelpItem {
+                key: keymap.hint(View::Plugins, &[Action::Remove]),
+                action: "remove",
+            },
+            HelpItem {
+                key: keymap.hint(View::Plugins, &[Action::Update]),
+                action: "update",
+            },
+            HelpItem {
+                key: keymap.hint(View::Plugins, &[Action::UpdateAll]),
+                action: "update all",
+            },
+            HelpItem {
+                key: keymap.hint(View::Plugins, &[Action::CheckUpdates]),
+                action: "check updates",
+            },
+            HelpItem {
+                key: keymap.hint(View::Plugins, &[Action::MoveDown, Action::MoveUp]),
+                action: "reorder",
+            },
+            HelpItem {
+                key: keymap.hint(View::Plugins, &[Action::Info]),
+                action: "info",
+            },
+            HelpItem {
+                key: keymap.hint(View::Plugins, &[Action::Copy]),
+                action: "copy url",
+            },
+        ]
+    };
+    items.extend([
         HelpItem {
-            key: "i",
-            action: "info",
+            key: keymap.hint(View::Plugins, &[Action::Back]),
+            action: "back",
         },
         HelpItem {
-            key: "Esc",
-            action: "back",
+            key: keymap.hint(View::Plugins, &[Action::Help]),
+            action: "help",
         },
         HelpItem {
-            key: "q",
+            key: keymap.hint(View::Plugins, &[Action::Quit]),
             action: "quit",
         },
     ]);
-    f.render_widget(help, area);
+    items
 }
 
-fn draw_help_minimal(f: &mut Frame, area: Rect) {
-    let help = HelpBar::new(vec![
-        HelpItem {
-            key: "Esc",
-            action: "back",
-        },
-        HelpItem {
-            key: "q",
-            action: "quit",
-        },
-    ]);
-    f.render_widget(help, area);
+fn draw_help(f: &mut Frame, area: Rect, state: &PluginState, keymap: &Keymap) {
+    f.render_widget(HelpBar::new(help_items(state, keymap)), area);
+}
+
+fn draw_help_read_only(f: &mut Frame, area: Rect, state: &PluginState, keymap: &Keymap) {
+    let manager = state.managed_by.as_deref().unwrap_or_default();
+    let help = HelpBar::new(help_items(state, keymap));
+    let cols = Layout::horizontal([Constraint::Min(10), Constraint::Length(30)]).split(area);
+    f.render_widget(help, cols[0]);
+    let note = Paragraph::new(format!("read-only: managed by {} ", manager))
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Right);
+    f.render_widget(note, cols[1]);
 }
 
 fn draw_input(f: &mut Frame, area: Rect, state: &PluginState) {
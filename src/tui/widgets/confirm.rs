@@ -0,0 +1,120 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use super::centered;
+
+/// A destructive action held back until the user answers `prompt`.
+pub struct ConfirmState<A> {
+    pub prompt: String,
+    pub on_confirm: A,
+}
+
+/// What a key press did to a pending confirmation.
+pub enum ConfirmOutcome<A> {
+    /// `y`: run the staged action.
+    Confirmed(A),
+    /// `n` or `Esc`: drop it.
+    Cancelled,
+    /// Any other key: keep asking.
+    Pending(ConfirmState<A>),
+}
+
+impl<A> ConfirmState<A> {
+    pub fn new(prompt: impl Into<String>, on_confirm: A) -> Self {
+        Self {
+            prompt: prompt.into(),
+            on_confirm,
+        }
+    }
+
+    /// Resolve the confirmation with a key press. Only an explicit `y`
+    /// confirms, so a stray key never runs the action.
+    pub fn handle_key(self, key: &KeyEvent) -> ConfirmOutcome<A> {
+        match key.code {
+            KeyCode::Char('y' | 'Y') => ConfirmOutcome::Confirmed(self.on_confirm),
+            KeyCode::Char('n' | 'N') | KeyCode::Esc => ConfirmOutcome::Cancelled,
+            _ => ConfirmOutcome::Pending(self),
+        }
+    }
+}
+
+/// Draw the question as a centered popup over `area`.
+pub fn draw_confirm<A>(f: &mut Frame, area: Rect, state: &ConfirmState<A>) {
+    let answers = Line::from(vec![
+        Span::styled("[y]", Style::default().fg(Color::Yellow)),
+        Span::raw(" yes  "),
+        Span::styled("[n]", Style::default().fg(Color::Yellow)),
+        Span::raw(" no"),
+    ]);
+    let width = (state.prompt.chars().count() + 4).clamp(30, 70) as u16;
+    // Room for the prompt wrapped to the popup, a blank line, and the answers
+    let prompt_lines = state.prompt.chars().count().div_ceil(width as usize - 4).max(1);
+    let height = prompt_lines as u16 + 4;
+    let popup = centered(area, width, height);
+
+    let block = Block::default()
+        .title(Span::styled(
+            " Confirm ",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+    let text = vec![Line::raw(state.prompt.as_str()), Line::raw(""), answers];
+
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(text).block(block).wrap(Wrap { trim: false }),
+        popup,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn staged() -> ConfirmState<u32> {
+        ConfirmState::new("Remove plugin?", 7)
+    }
+
+    #[test]
+    fn test_yes_confirms_with_staged_action() {
+        assert!(matches!(
+            staged().handle_key(&key(KeyCode::Char('y'))),
+            ConfirmOutcome::Confirmed(7)
+        ));
+    }
+
+    #[test]
+    fn test_no_and_esc_cancel() {
+        assert!(matches!(
+            staged().handle_key(&key(KeyCode::Char('n'))),
+            ConfirmOutcome::Cancelled
+        ));
+        assert!(matches!(
+            staged().handle_key(&key(KeyCode::Esc)),
+            ConfirmOutcome::Cancelled
+        ));
+    }
+
+    #[test]
+    fn test_other_keys_keep_asking() {
+        // Enter in particular must not count as yes
+        for code in [KeyCode::Enter, KeyCode::Char('d'), KeyCode::Char('j')] {
+            match staged().handle_key(&key(code)) {
+                ConfirmOutcome::Pending(state) => assert_eq!(state.on_confirm, 7),
+                _ => panic!("{:?} resolved the confirmation", code),
+            }
+        }
+    }
+}
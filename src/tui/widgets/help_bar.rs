@@ -8,7 +8,7 @@ use ratatui::{
 
 /// A key-action pair for the help bar.
 pub struct HelpItem {
-    pub key: &'static str,
+    pub key: String,
     pub action: &'static str,
 }
 
@@ -26,7 +26,9 @@ impl HelpBar {
 impl Widget for HelpBar {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let mut spans = Vec::new();
-        for (i, item) in self.items.iter().enumerate() {
+        // Actions unbound in the keymap have no key to show
+        let items = self.items.iter().filter(|item| !item.key.is_empty());
+        for (i, item) in items.enumerate() {
             if i > 0 {
                 spans.push(Span::raw("  "));
             }
@@ -0,0 +1,66 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+
+use super::centered;
+use super::help_bar::HelpItem;
+
+/// A centered popup listing every key of a view, one per line, drawn over
+/// whatever the view rendered.
+pub struct HelpOverlay {
+    title: String,
+    items: Vec<HelpItem>,
+}
+
+impl HelpOverlay {
+    pub fn new(title: impl Into<String>, items: Vec<HelpItem>) -> Self {
+        Self {
+            title: title.into(),
+            items,
+        }
+    }
+}
+
+impl Widget for HelpOverlay {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Actions unbound in the keymap have no key to show
+        let items: Vec<&HelpItem> = self.items.iter().filter(|item| !item.key.is_empty()).collect();
+        let key_width = items.iter().map(|item| item.key.chars().count()).max().unwrap_or(0);
+        let action_width = items.iter().map(|item| item.action.len()).max().unwrap_or(0);
+
+        let lines: Vec<Line> = items
+            .iter()
+            .map(|item| {
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {:>width$} ", item.key, width = key_width),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(format!(" {}", item.action)),
+                ])
+            })
+            .collect();
+
+        let footer = " ? / Esc to close ";
+        let inner_width = (key_width + action_width + 4).max(self.title.len() + 2);
+        let width = (inner_width.max(footer.len()) + 2) as u16;
+        let height = lines.len() as u16 + 2;
+        let popup = centered(area, width, height);
+
+        let block = Block::default()
+            .title(Span::styled(
+                format!(" {} ", self.title),
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            ))
+            .title_bottom(Line::styled(footer, Style::default().fg(Color::DarkGray)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
+
+        Clear.render(popup, buf);
+        Paragraph::new(lines).block(block).render(popup, buf);
+    }
+}
@@ -1,2 +1,31 @@
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+
+pub mod confirm;
 pub mod help_bar;
+pub mod help_overlay;
 pub mod status_bar;
+
+/// A `width` x `height` rect in the middle of `area`, shrunk to fit. Used to
+/// place popups over a view.
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [row] = Layout::vertical([Constraint::Length(height.min(area.height))])
+        .flex(Flex::Center)
+        .areas(area);
+    let [popup] = Layout::horizontal([Constraint::Length(width.min(area.width))])
+        .flex(Flex::Center)
+        .areas(row);
+    popup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_centered_fits_area() {
+        let area = Rect::new(0, 0, 80, 24);
+        assert_eq!(centered(area, 20, 10), Rect::new(30, 7, 20, 10));
+        // Larger than the screen: clamp to it
+        assert_eq!(centered(area, 100, 30), area);
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+
+/// A clipboard command and the arguments that make it read the text from stdin.
+type Backend = (&'static str, &'static [&'static str]);
+
+/// Clipboard commands in the order they're tried.
+const BACKENDS: &[Backend] = &[
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("pbcopy", &[]),
+    ("clip.exe", &[]),
+];
+
+/// Put `text` on the system clipboard using the first clipboard command
+/// found on `PATH`.
+pub fn copy(text: &str) -> Result<()> {
+    let (program, args) = pick_backend(on_path).ok_or_else(|| {
+        anyhow::anyhow!("no clipboard tool found — install wl-copy, xclip, pbcopy, or clip.exe")
+    })?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("failed to run {}", program))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", program, status);
+    }
+    Ok(())
+}
+
+/// The first backend whose command `available` reports as present.
+fn pick_backend(available: impl Fn(&str) -> bool) -> Option<Backend> {
+    BACKENDS.iter().copied().find(|(program, _)| available(program))
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| is_executable(&dir.join(program)))
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pick(available: &[&str]) -> Option<&'static str> {
+        pick_backend(|program| available.contains(&program)).map(|(program, _)| program)
+    }
+
+    #[test]
+    fn test_pick_backend_in_order() {
+        assert_eq!(pick(&["xclip", "wl-copy", "pbcopy"]), Some("wl-copy"));
+        assert_eq!(pick(&["pbcopy", "xclip"]), Some("xclip"));
+        assert_eq!(pick(&["clip.exe", "pbcopy"]), Some("pbcopy"));
+        assert_eq!(pick(&["clip.exe"]), Some("clip.exe"));
+    }
+
+    #[test]
+    fn test_pick_backend_none_available() {
+        assert_eq!(pick(&[]), None);
+        assert_eq!(pick(&["xsel", "cat"]), None);
+    }
+
+    #[test]
+    fn test_xclip_targets_clipboard_selection() {
+        let (_, args) = pick_backend(|p| p == "xclip").unwrap();
+        assert_eq!(args, ["-selection", "clipboard"]);
+    }
+}
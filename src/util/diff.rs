@@ -27,6 +27,25 @@ pub fn unified_diff(old: &str, new: &str, file_path: &str) -> String {
     output
 }
 
+/// Count changed lines (insertions plus deletions) between two strings.
+pub fn changed_lines(old: &str, new: &str) -> usize {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .filter(|c| c.tag() != ChangeTag::Equal)
+        .count()
+}
+
+/// Count inserted and deleted lines between two strings.
+pub fn line_counts(old: &str, new: &str) -> (usize, usize) {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .fold((0, 0), |(added, removed), c| match c.tag() {
+            ChangeTag::Insert => (added + 1, removed),
+            ChangeTag::Delete => (added, removed + 1),
+            ChangeTag::Equal => (added, removed),
+        })
+}
+
 /// Check if two strings have any differences.
 #[allow(dead_code)]
 pub fn has_changes(old: &str, new: &str) -> bool {
@@ -55,6 +74,21 @@ mod tests {
         assert!(!result.is_empty());
     }
 
+    #[test]
+    fn test_changed_lines() {
+        assert_eq!(changed_lines("a\nb\n", "a\nb\n"), 0);
+        // One modified line = one deletion + one insertion
+        assert_eq!(changed_lines("a\nb\nc\n", "a\nx\nc\n"), 2);
+        assert_eq!(changed_lines("", "a\nb\n"), 2);
+    }
+
+    #[test]
+    fn test_line_counts() {
+        assert_eq!(line_counts("a\nb\n", "a\nb\n"), (0, 0));
+        assert_eq!(line_counts("a\nb\nc\n", "a\nx\nc\nd\n"), (2, 1));
+        assert_eq!(line_counts("a\nb\n", ""), (0, 2));
+    }
+
     #[test]
     fn test_no_diff() {
         let content = "same\n";
@@ -4,6 +4,29 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 
+/// Recursively copy a directory's contents into a target directory.
+/// `.git` directories are skipped.
+pub fn copy_dir_recursive(src: &Path, dest: &Path) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let target = dest.join(&file_name);
+
+        if path.is_dir() {
+            // Skip .git directories
+            if file_name == ".git" {
+                continue;
+            }
+            std::fs::create_dir_all(&target)?;
+            copy_dir_recursive(&path, &target)?;
+        } else {
+            std::fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
 /// Check if a path is a symlink (without following it).
 pub fn is_symlink(path: &Path) -> bool {
     path.symlink_metadata()
@@ -23,12 +46,17 @@ pub fn symlink_target(path: &Path) -> Option<PathBuf> {
 /// Write content to a file atomically (write to .tmp then rename).
 /// Sets file permissions to 0600 (owner-only read/write).
 pub fn atomic_write(path: &Path, content: &str) -> anyhow::Result<()> {
+    atomic_write_bytes(path, content.as_bytes())
+}
+
+/// Byte-oriented [`atomic_write`], for content that may not be UTF-8.
+pub fn atomic_write_bytes(path: &Path, content: &[u8]) -> anyhow::Result<()> {
     let tmp_path = path.with_extension("tmp");
 
     {
         let mut file = std::fs::File::create(&tmp_path)
             .with_context(|| format!("failed to create {}", tmp_path.display()))?;
-        file.write_all(content.as_bytes())?;
+        file.write_all(content)?;
         file.sync_all()?;
         file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
     }
@@ -47,14 +75,15 @@ pub fn atomic_write(path: &Path, content: &str) -> anyhow::Result<()> {
 /// Check if a resolved path is within the user's home directory.
 /// Returns Ok(()) if safe, Err with a warning message if the path escapes $HOME.
 pub fn check_path_safety(path: &Path) -> anyhow::Result<()> {
+    // Fail closed: without a home directory there is nothing to check against
+    let home = crate::util::paths::home_dir()?;
+
     let resolved = match path.canonicalize() {
         Ok(p) => p,
         Err(_) => return Ok(()), // Can't resolve — file may not exist yet, not a safety issue
     };
 
-    if let Some(home) = dirs::home_dir()
-        && !resolved.starts_with(&home)
-    {
+    if !resolved.starts_with(&home) {
         anyhow::bail!(
             "path '{}' resolves to '{}' which is outside your home directory",
             path.display(),
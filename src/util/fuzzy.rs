@@ -0,0 +1,211 @@
+/// Score how well `query` matches `text`, ignoring case. Higher is better;
+/// `None` means no match.
+///
+/// Matches fall into tiers, best first: the whole text, a substring (earlier
+/// and at a word start ranks higher), a substring once `-`, `_`, `.` and
+/// spaces are dropped (`statusbar` finds `status-bar`), the query's letters in
+/// order (`stbar` finds `status-bar`), and finally a typo of one word of the
+/// text — one edit or two swapped letters (`msoue` finds `mouse`).
+pub fn score(query: &str, text: &str) -> Option<u32> {
+    let query = query.to_lowercase();
+    let text = text.to_lowercase();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    // Fast path: plain substring
+    if let Some(pos) = text.find(&query) {
+        if text == query {
+            return Some(1000);
+        }
+        let at_word_start = pos == 0 || !text[..pos].ends_with(|c: char| c.is_alphanumeric());
+        let bonus = if pos == 0 {
+            100
+        } else if at_word_start {
+            50
+        } else {
+            0
+        };
+        let extra = (text.len() - query.len()).min(50) as u32;
+        return Some(800 + bonus - extra);
+    }
+
+    let squashed_query = squash(&query);
+    if !squashed_query.is_empty() && squash(&text).contains(&squashed_query) {
+        return Some(700);
+    }
+
+    if let Some(s) = subsequence_score(&query, &text) {
+        return Some(s);
+    }
+
+    typo_score(&query, &text)
+}
+
+/// Drop word separators so `status-bar` and `statusbar` compare equal.
+fn squash(s: &str) -> String {
+    s.chars().filter(|c| !matches!(c, '-' | '_' | '.' | ' ')).collect()
+}
+
+/// Letters of `query` appearing in order in `text`, rewarding runs of
+/// consecutive letters and letters that start a word. Scores 100..=600.
+fn subsequence_score(query: &str, text: &str) -> Option<u32> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    if query.len() < 2 {
+        return None;
+    }
+    let text: Vec<char> = text.chars().collect();
+
+    let mut score: i64 = 300;
+    let mut qi = 0;
+    let mut prev: Option<usize> = None;
+    for (ti, &c) in text.iter().enumerate() {
+        if qi == query.len() {
+            break;
+        }
+        if c != query[qi] {
+            continue;
+        }
+        if prev.is_some_and(|p| p + 1 == ti) {
+            score += 15;
+        } else if let Some(p) = prev {
+            score -= (ti - p - 1).min(10) as i64;
+        }
+        if ti == 0 || !text[ti - 1].is_alphanumeric() {
+            score += 10;
+        }
+        prev = Some(ti);
+        qi += 1;
+    }
+
+    (qi == query.len()).then(|| score.clamp(100, 600) as u32)
+}
+
+/// A word of `text` (or all of it, squashed) within one edit of `query`, or
+/// the same letters with the same first letter. Queries shorter than four
+/// characters are too ambiguous for this. Scores 50 or 80.
+fn typo_score(query: &str, text: &str) -> Option<u32> {
+    let query = squash(query);
+    if query.chars().count() < 4 {
+        return None;
+    }
+
+    let squashed = squash(text);
+    let candidates = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .chain(std::iter::once(squashed.as_str()));
+
+    let mut best = None;
+    for word in candidates {
+        if edit_distance(&query, word) <= 1 {
+            return Some(80);
+        }
+        if same_letters(&query, word) {
+            best = Some(50);
+        }
+    }
+    best
+}
+
+fn same_letters(a: &str, b: &str) -> bool {
+    if a.chars().next() != b.chars().next() {
+        return false;
+    }
+    let mut a: Vec<char> = a.chars().collect();
+    let mut b: Vec<char> = b.chars().collect();
+    a.sort_unstable();
+    b.sort_unstable();
+    a == b
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions,
+/// and swaps of adjacent characters each cost one.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tier_ordering() {
+        let exact = score("mouse", "mouse").unwrap();
+        let prefix = score("mouse", "mouse-mode").unwrap();
+        let inner = score("mouse", "@yank_with_mouse").unwrap();
+        let squashed = score("statusbar", "status-bar").unwrap();
+        let subseq = score("stbar", "status-bar").unwrap();
+        let typo = score("msoue", "mouse").unwrap();
+        assert!(exact > prefix, "{} > {}", exact, prefix);
+        assert!(prefix > inner, "{} > {}", prefix, inner);
+        assert!(inner > squashed);
+        assert!(squashed > subseq);
+        assert!(subseq > typo);
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        assert_eq!(score("MOUSE", "mouse"), score("mouse", "MoUsE"));
+        assert!(score("Hist", "HIST_IGNORE_DUPS").is_some());
+    }
+
+    #[test]
+    fn test_word_start_beats_mid_word() {
+        let start = score("bar", "status-bar").unwrap();
+        let middle = score("bar", "sidebars").unwrap();
+        assert!(start > middle);
+    }
+
+    #[test]
+    fn test_consecutive_subsequence_ranks_higher() {
+        let tight = score("stat", "stxat").unwrap();
+        let loose = score("stat", "sxxxtxxxaxxxt").unwrap();
+        assert!(tight > loose, "{} > {}", tight, loose);
+    }
+
+    #[test]
+    fn test_typos() {
+        assert!(score("msoue", "mouse").is_some());
+        assert!(score("mosue", "mouse").is_some());
+        assert!(score("mosue", "set mouse on").is_some());
+        // Too short to guess at, and too different
+        assert!(score("mse", "xyz").is_none());
+        assert!(score("mode", "mouse").is_none());
+        assert!(score("zzzz", "mouse").is_none());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("mouse", "mouse"), 0);
+        assert_eq!(edit_distance("mosue", "mouse"), 1);
+        assert_eq!(edit_distance("mose", "mouse"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("escape-time", "escpe-tme"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_empty_query_matches_everything() {
+        assert_eq!(score("", "anything"), Some(0));
+    }
+}
@@ -0,0 +1,57 @@
+use globset::{GlobBuilder, GlobMatcher};
+
+/// Compile a glob pattern, or `None` if it isn't valid.
+///
+/// `*` matches any run of characters within one path component, `?` one
+/// character other than `/`, and `**` any number of whole components.
+pub fn matcher(pattern: &str) -> Option<GlobMatcher> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .ok()
+        .map(|glob| glob.compile_matcher())
+}
+
+/// Match a path against a glob pattern, as compiled by [`matcher`].
+/// A pattern ending in `/**` also matches the directory itself.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    if let Some(dir) = pattern.strip_suffix("/**")
+        && matches(dir, path)
+    {
+        return true;
+    }
+    matcher(pattern).is_some_and(|m| m.is_match(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal() {
+        assert!(matches("/home/u/.netrc", "/home/u/.netrc"));
+        assert!(!matches("/home/u/.netrc", "/home/u/.netrc.bak"));
+    }
+
+    #[test]
+    fn test_star_stays_in_component() {
+        assert!(matches("/home/u/.ssh/*", "/home/u/.ssh/config"));
+        assert!(!matches("/home/u/.ssh/*", "/home/u/.ssh/keys/id_ed25519"));
+        assert!(matches("/home/u/*.conf", "/home/u/tmux.conf"));
+        assert!(matches("/home/u/id_?sa", "/home/u/id_rsa"));
+    }
+
+    #[test]
+    fn test_double_star_crosses_components() {
+        assert!(matches("/home/u/.gnupg/**", "/home/u/.gnupg/private-keys-v1.d/a.key"));
+        assert!(matches("/home/u/.gnupg/**", "/home/u/.gnupg"));
+        assert!(matches("**/credentials", "/home/u/.config/git/credentials"));
+        assert!(!matches("/home/u/.gnupg/**", "/home/u/.gnupgx/a"));
+    }
+
+    #[test]
+    fn test_many_stars_stay_fast() {
+        let path = format!("/{}", "a".repeat(64));
+        assert!(!matches("/a*a*a*a*a*a*a*a*a*a*b", &path));
+    }
+}
@@ -0,0 +1,84 @@
+/// Blank out `//` and `/* */` comments and trailing commas so JSON with
+/// comments (VS Code style "jsonc") can be parsed. Everything else,
+/// including line and column positions, is left as it was.
+pub fn strip_comments(text: &str) -> String {
+    let mut out: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    let mut in_string = false;
+    // Index of the last ',' outside a string, until something other than
+    // whitespace or a comment follows it
+    let mut pending_comma: Option<usize> = None;
+
+    while i < out.len() {
+        let c = out[i];
+        if in_string {
+            match c {
+                '\\' => i += 1,
+                '"' => in_string = false,
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+
+        match (c, out.get(i + 1)) {
+            ('/', Some('/')) => {
+                while i < out.len() && out[i] != '\n' {
+                    out[i] = ' ';
+                    i += 1;
+                }
+                continue;
+            }
+            ('/', Some('*')) => {
+                let start = i;
+                i += 2;
+                while i < out.len() && !(out[i - 1] == '*' && out[i] == '/' && i > start + 2) {
+                    i += 1;
+                }
+                let end = (i + 1).min(out.len());
+                for ch in &mut out[start..end] {
+                    if *ch != '\n' {
+                        *ch = ' ';
+                    }
+                }
+                i = end;
+                continue;
+            }
+            ('}' | ']', _) => {
+                if let Some(comma) = pending_comma.take() {
+                    out[comma] = ' ';
+                }
+            }
+            (',', _) => pending_comma = Some(i),
+            ('"', _) => {
+                in_string = true;
+                pending_comma = None;
+            }
+            (c, _) if !c.is_whitespace() => pending_comma = None,
+            _ => {}
+        }
+        i += 1;
+    }
+    out.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_comments() {
+        let text = "{\n  // font\n  \"size\": 12, /* pt */\n  \"url\": \"http://x\",\n}";
+        let stripped = strip_comments(text);
+        assert_eq!(stripped.len(), text.len());
+        assert_eq!(stripped.lines().count(), text.lines().count());
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["size"], 12);
+        assert_eq!(value["url"], "http://x");
+
+        // Errors still point at the original position
+        let text = strip_comments("{\n  /* a */ \"a\" 1\n}");
+        let err = serde_json::from_str::<serde_json::Value>(&text).unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, 15));
+    }
+}
@@ -1,3 +1,8 @@
+pub mod clipboard;
 pub mod diff;
 pub mod fs;
+pub mod fuzzy;
+pub mod glob;
+pub mod json;
 pub mod paths;
+pub mod prompt;
@@ -1,37 +1,196 @@
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
+use crate::core::errors::DotsmithError;
+
+/// Environment variable that stands in for the home directory in everything
+/// dotsmith resolves relative to it, so a run can be kept away from the real one.
+pub const HOME_ENV: &str = "DOTSMITH_HOME";
+
 /// Get the dotsmith config directory.
-/// Priority: DOTSMITH_CONFIG_DIR env var > ~/.config/dotsmith/
+/// Priority: DOTSMITH_CONFIG_DIR env var (or `--config-dir`) > ~/.config/dotsmith/
 pub fn config_dir() -> anyhow::Result<PathBuf> {
     if let Ok(dir) = std::env::var("DOTSMITH_CONFIG_DIR") {
         return Ok(PathBuf::from(dir));
     }
 
-    let base = dirs::config_dir()
-        .ok_or_else(|| anyhow::anyhow!("could not determine config directory"))?;
-    Ok(base.join("dotsmith"))
+    Ok(xdg_config_dir()?.join("dotsmith"))
+}
+
+/// Resolve the user's home directory.
+/// Uses `$DOTSMITH_HOME`, then `$HOME`, falling back to the passwd entry.
+/// All home lookups go through here so a missing home surfaces as one clear
+/// `HomeNotSet` error.
+pub fn home_dir() -> anyhow::Result<PathBuf> {
+    let env_home = std::env::var_os(HOME_ENV)
+        .filter(|home| Path::new(home).is_absolute())
+        .or_else(|| std::env::var_os("HOME"));
+    resolve_home(env_home, dirs::home_dir())
+}
+
+/// The base directory for configs: `$XDG_CONFIG_HOME` or the platform
+/// default. Under `DOTSMITH_HOME` it is always `<home>/.config`.
+pub fn xdg_config_dir() -> anyhow::Result<PathBuf> {
+    xdg_dir(dirs::config_dir(), ".config")
+}
+
+/// Like [`xdg_config_dir`], for data (`~/.local/share`).
+pub fn xdg_data_dir() -> anyhow::Result<PathBuf> {
+    xdg_dir(dirs::data_dir(), ".local/share")
+}
+
+fn xdg_dir(platform: Option<PathBuf>, in_home: &str) -> anyhow::Result<PathBuf> {
+    match platform {
+        Some(dir) if std::env::var_os(HOME_ENV).is_none() => Ok(dir),
+        _ => Ok(home_dir()?.join(in_home)),
+    }
+}
+
+fn resolve_home(env_home: Option<OsString>, fallback: Option<PathBuf>) -> anyhow::Result<PathBuf> {
+    env_home
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or(fallback.filter(|p| p.is_absolute()))
+        .ok_or_else(|| DotsmithError::HomeNotSet.into())
 }
 
 /// Expand `~` to the user's home directory.
-/// Only handles `~/path` — not `~user/path`.
+/// Only handles `~/path` — not `~user/path`. Use `expand_tilde_checked` for
+/// user-supplied paths so those are rejected instead of treated as relative.
+///
+/// Without a resolvable home directory the path is returned unchanged, which
+/// is fine for reading. Anything that writes to the path should use
+/// `expand_tilde_checked` instead.
 pub fn expand_tilde(path: &str) -> PathBuf {
     if let Some(rest) = path.strip_prefix("~/")
-        && let Some(home) = dirs::home_dir()
+        && let Ok(home) = home_dir()
     {
         return home.join(rest);
     }
     if path == "~"
-        && let Some(home) = dirs::home_dir()
+        && let Ok(home) = home_dir()
     {
         return home;
     }
     PathBuf::from(path)
 }
 
+/// Whether a path uses the `~user` form (anything starting with `~` other than `~` or `~/`).
+pub fn is_user_tilde(path: &str) -> bool {
+    path.starts_with('~') && path != "~" && !path.starts_with("~/")
+}
+
+/// Like `expand_tilde`, but never hands back a `~` path as a relative one:
+/// `~user/...` paths are rejected, and so are `~` paths when the home
+/// directory can't be resolved.
+pub fn expand_tilde_checked(path: &str) -> anyhow::Result<PathBuf> {
+    if is_user_tilde(path) {
+        return Err(DotsmithError::UnsupportedUserTilde(path.to_string()).into());
+    }
+    if path == "~" {
+        return home_dir();
+    }
+    match path.strip_prefix("~/") {
+        Some(rest) => Ok(home_dir()?.join(rest)),
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+/// Whether a config path is a glob pattern (`*`, `?` or `**`) rather than a
+/// fixed path.
+pub fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?'])
+}
+
+/// List the files matching a glob pattern such as `~/.config/nvim/lua/**/*.lua`,
+/// sorted. `*` and `?` match within one path component and `**` matches any
+/// number of directories. As in the shell, wildcards don't match names
+/// starting with `.` unless the pattern does, and `**` doesn't descend into
+/// symlinked directories. No match gives an empty list.
+pub fn expand_glob(pattern: &str) -> Vec<PathBuf> {
+    let expanded = expand_tilde(pattern);
+    let Some(matcher) = crate::util::glob::matcher(&expanded.to_string_lossy()) else {
+        return Vec::new();
+    };
+    let mut root = PathBuf::new();
+    let mut parts = Vec::new();
+    for component in expanded.components() {
+        let name = component.as_os_str().to_string_lossy();
+        if parts.is_empty() && !is_glob(&name) {
+            root.push(component);
+        } else {
+            parts.push(name.to_string());
+        }
+    }
+
+    let walk = GlobWalk {
+        matcher,
+        // Hidden names are only visited where the pattern spells out the dot
+        dotted: parts
+            .iter()
+            .filter(|part| part.starts_with('.'))
+            .filter_map(|part| crate::util::glob::matcher(part))
+            .collect(),
+        // Without `**` nothing can match below the pattern's own depth
+        max_depth: (!parts.iter().any(|part| part == "**")).then_some(parts.len()),
+    };
+    let mut matches = Vec::new();
+    walk.visit(&root, 0, &mut matches);
+    matches.sort();
+    matches.dedup();
+    matches
+}
+
+/// The paths a tracked config path stands for: the files a glob matches,
+/// re-expanded on every call so new files are picked up, or else the path
+/// itself with `~` expanded.
+pub fn expand_config_path(path: &str) -> Vec<PathBuf> {
+    if is_glob(path) {
+        expand_glob(path)
+    } else {
+        vec![expand_tilde(path)]
+    }
+}
+
+struct GlobWalk {
+    matcher: globset::GlobMatcher,
+    dotted: Vec<globset::GlobMatcher>,
+    max_depth: Option<usize>,
+}
+
+impl GlobWalk {
+    fn visit(&self, dir: &Path, depth: usize, matches: &mut Vec<PathBuf>) {
+        if self.max_depth.is_some_and(|max| depth >= max) {
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            if name.to_string_lossy().starts_with('.')
+                && !self.dotted.iter().any(|m| m.is_match(&name))
+            {
+                continue;
+            }
+            let path = entry.path();
+            // Symlinked directories are only followed to a bounded depth
+            let linked = entry.file_type().is_ok_and(|t| t.is_symlink());
+            if path.is_dir() {
+                if !linked || self.max_depth.is_some() {
+                    self.visit(&path, depth + 1, matches);
+                }
+            } else if path.is_file() && self.matcher.is_match(&path) {
+                matches.push(path);
+            }
+        }
+    }
+}
+
 /// Contract an absolute path to use `~` for the home directory.
 /// `/home/user/.config/tmux` → `~/.config/tmux`
 pub fn contract_tilde(path: &Path) -> String {
-    if let Some(home) = dirs::home_dir()
+    if let Ok(home) = home_dir()
         && let Ok(suffix) = path.strip_prefix(&home)
     {
         return format!("~/{}", suffix.display());
@@ -63,6 +222,59 @@ mod tests {
         assert_eq!(result, PathBuf::from("/absolute/path"));
     }
 
+    #[test]
+    fn test_resolve_home_prefers_env() {
+        let home = resolve_home(Some("/home/a".into()), Some("/home/b".into())).unwrap();
+        assert_eq!(home, PathBuf::from("/home/a"));
+    }
+
+    #[test]
+    fn test_resolve_home_unset_falls_back() {
+        let home = resolve_home(None, Some("/home/b".into())).unwrap();
+        assert_eq!(home, PathBuf::from("/home/b"));
+        // Empty or relative $HOME is treated as unset
+        let home = resolve_home(Some("".into()), Some("/home/b".into())).unwrap();
+        assert_eq!(home, PathBuf::from("/home/b"));
+        let home = resolve_home(Some("relative".into()), Some("/home/b".into())).unwrap();
+        assert_eq!(home, PathBuf::from("/home/b"));
+    }
+
+    #[test]
+    fn test_resolve_home_unset_errors() {
+        let err = resolve_home(None, None).unwrap_err();
+        assert_eq!(err.to_string(), "HOME is not set; set it or pass --config-dir");
+        assert!(resolve_home(Some("".into()), None).is_err());
+    }
+
+    #[test]
+    fn test_expand_tilde_checked() {
+        let home = dirs::home_dir().expect("home dir");
+        assert_eq!(expand_tilde_checked("~").unwrap(), home);
+        assert_eq!(expand_tilde_checked("~/foo").unwrap(), home.join("foo"));
+        assert_eq!(
+            expand_tilde_checked("/etc/foo").unwrap(),
+            PathBuf::from("/etc/foo")
+        );
+    }
+
+    #[test]
+    fn test_expand_tilde_checked_rejects_user() {
+        let err = expand_tilde_checked("~otheruser/foo").unwrap_err();
+        assert!(err.to_string().contains("~otheruser/foo"));
+        // A bare `~name` is the same form as `~user` in the shell
+        assert!(expand_tilde_checked("~file").is_err());
+    }
+
+    #[test]
+    fn test_expand_tilde_leaves_user_form_untouched() {
+        assert_eq!(expand_tilde("~otheruser/foo"), PathBuf::from("~otheruser/foo"));
+        assert_eq!(expand_tilde("~file"), PathBuf::from("~file"));
+        assert!(is_user_tilde("~root"));
+        assert!(!is_user_tilde("~"));
+        assert!(!is_user_tilde("~/x"));
+        assert!(!is_user_tilde("foo~"));
+    }
+
     #[test]
     fn test_contract_tilde() {
         let home = dirs::home_dir().expect("home dir");
@@ -78,6 +290,82 @@ mod tests {
         assert_eq!(result, "/etc/config");
     }
 
+    /// An nvim-like tree with lua modules at several depths.
+    fn lua_tree(tmp: &tempfile::TempDir) -> PathBuf {
+        let root = tmp.path().join("nvim");
+        for file in [
+            "init.lua",
+            "lua/options.lua",
+            "lua/plugins/lsp.lua",
+            "lua/plugins/ui/theme.lua",
+            "lua/plugins/README.md",
+            "lua/.hidden.lua",
+            ".git/hooks/x.lua",
+        ] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        root
+    }
+
+    fn glob(root: &Path, pattern: &str) -> Vec<String> {
+        expand_glob(&format!("{}/{}", root.display(), pattern))
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_glob_single_level() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = lua_tree(&tmp);
+        assert_eq!(glob(&root, "lua/*.lua"), ["lua/options.lua"]);
+        assert_eq!(glob(&root, "lua/*/l?p.lua"), ["lua/plugins/lsp.lua"]);
+        assert_eq!(glob(&root, "lua/.*.lua"), ["lua/.hidden.lua"]);
+    }
+
+    #[test]
+    fn test_expand_glob_recursive() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = lua_tree(&tmp);
+        assert_eq!(
+            glob(&root, "**/*.lua"),
+            [
+                "init.lua",
+                "lua/options.lua",
+                "lua/plugins/lsp.lua",
+                "lua/plugins/ui/theme.lua",
+            ]
+        );
+        assert_eq!(
+            glob(&root, "lua/plugins/**"),
+            ["lua/plugins/README.md", "lua/plugins/lsp.lua", "lua/plugins/ui/theme.lua"]
+        );
+
+        // New files show up on the next expansion
+        std::fs::write(root.join("lua/keymaps.lua"), "").unwrap();
+        assert!(glob(&root, "lua/**/*.lua").contains(&"lua/keymaps.lua".to_string()));
+    }
+
+    #[test]
+    fn test_expand_glob_no_match() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = lua_tree(&tmp);
+        assert!(glob(&root, "**/*.vim").is_empty());
+        assert!(glob(&root, "missing/**/*.lua").is_empty());
+        // Directories alone don't match
+        assert!(glob(&root, "lu?").is_empty());
+    }
+
+    #[test]
+    fn test_is_glob() {
+        assert!(is_glob("~/.config/nvim/lua/**/*.lua"));
+        assert!(is_glob("~/.config/fis?/conf.d"));
+        assert!(!is_glob("~/.config/nvim/init.lua"));
+        assert_eq!(expand_config_path("/etc/hosts"), [PathBuf::from("/etc/hosts")]);
+    }
+
     #[test]
     fn test_config_dir_env_override() {
         let original = std::env::var("DOTSMITH_CONFIG_DIR").ok();
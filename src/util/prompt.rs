@@ -0,0 +1,111 @@
+use std::io::{BufRead, IsTerminal, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+/// Whether stdin is attached to a terminal (i.e. we can ask the user).
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal()
+}
+
+/// Ask a yes/no question on stdout and read the answer from stdin.
+/// Anything other than `y`/`yes` (case-insensitive) counts as no.
+pub fn confirm(question: &str) -> anyhow::Result<bool> {
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(parse_yes(&answer))
+}
+
+/// Like [`confirm`], but an empty answer picks `default`.
+pub fn confirm_default(question: &str, default: bool) -> anyhow::Result<bool> {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    print!("{} {} ", question, hint);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    if answer.trim().is_empty() {
+        return Ok(default);
+    }
+    Ok(parse_yes(&answer))
+}
+
+/// Ask for a line of text. An empty answer returns `default`, or an empty
+/// string when there is none.
+pub fn ask(question: &str, default: Option<&str>) -> anyhow::Result<String> {
+    match default {
+        Some(d) => print!("{} [{}] ", question, d),
+        None => print!("{} ", question),
+    }
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer_or_default(&answer, default))
+}
+
+/// Ask for a secret without echoing what is typed. Ctrl-C aborts.
+pub fn ask_secret(question: &str) -> anyhow::Result<String> {
+    print!("{} ", question);
+    std::io::stdout().flush()?;
+
+    terminal::enable_raw_mode()?;
+    let mut secret = String::new();
+    let result = loop {
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e.into()),
+        };
+        match key.code {
+            KeyCode::Enter => break Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                break Err(anyhow::anyhow!("cancelled"));
+            }
+            KeyCode::Backspace => {
+                secret.pop();
+            }
+            KeyCode::Char(c) => secret.push(c),
+            _ => {}
+        }
+    };
+    terminal::disable_raw_mode()?;
+    println!();
+    result.map(|()| secret)
+}
+
+fn answer_or_default(answer: &str, default: Option<&str>) -> String {
+    match answer.trim() {
+        "" => default.unwrap_or_default().to_string(),
+        given => given.to_string(),
+    }
+}
+
+fn parse_yes(answer: &str) -> bool {
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_yes() {
+        assert!(parse_yes("y\n"));
+        assert!(parse_yes("YES"));
+        assert!(parse_yes("  yes  "));
+        assert!(!parse_yes("\n"));
+        assert!(!parse_yes("n"));
+        assert!(!parse_yes("yep"));
+    }
+
+    #[test]
+    fn test_answer_or_default() {
+        assert_eq!(answer_or_default("  ~/dots \n", Some("~/x")), "~/dots");
+        assert_eq!(answer_or_default("\n", Some("~/x")), "~/x");
+        assert_eq!(answer_or_default("", None), "");
+    }
+}
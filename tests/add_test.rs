@@ -150,3 +150,235 @@ fn test_add_detects_tpm_plugin_manager() {
             .stdout(predicate::str::contains("plugin manager"));
     }
 }
+
+#[test]
+fn test_add_git_tracks_included_files() {
+    let has_git = std::process::Command::new("which")
+        .arg("git")
+        .output()
+        .is_ok_and(|o| o.status.success());
+    if !has_git {
+        eprintln!("skipping: git not installed");
+        return;
+    }
+
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().join("home");
+    fs::create_dir_all(home.join("work")).unwrap();
+    fs::write(
+        home.join(".gitconfig"),
+        "[user]\n\tname = Test\n\
+         [includeIf \"gitdir:~/work/\"]\n\tpath = ~/.gitconfig-work\n\
+         [includeIf \"onbranch:main\"]\n\tpath = ~/.gitconfig-main\n",
+    )
+    .unwrap();
+    fs::write(home.join(".gitconfig-work"), "[user]\n\temail = me@work.example\n").unwrap();
+    fs::write(home.join(".gitconfig-main"), "").unwrap();
+
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["add", "git"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .env("HOME", &home)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tracking 2 config path(s)"))
+        .stdout(predicate::str::contains("~/.gitconfig-work"))
+        .stdout(predicate::str::contains("onbranch:main"));
+
+    let manifest = fs::read_to_string(config_dir.join("manifest.toml")).unwrap();
+    assert!(manifest.contains("~/.gitconfig-work"));
+    assert!(!manifest.contains("~/.gitconfig-main"));
+}
+
+#[test]
+fn test_add_with_explicit_paths() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().join("home");
+    let dots = home.join("projects/dots");
+    fs::create_dir_all(&dots).unwrap();
+    fs::write(dots.join("shrc"), "set -o vi\n").unwrap();
+    fs::write(dots.join("profile"), "export EDITOR=vi\n").unwrap();
+
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["add", "sh", "--path", "~/projects/dots/shrc"])
+        .arg("--path")
+        .arg(dots.join("profile"))
+        .args(["--tier", "3"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .env("HOME", &home)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tier 3"))
+        .stdout(predicate::str::contains("Tracking 2 config path(s)"));
+
+    let manifest = fs::read_to_string(config_dir.join("manifest.toml")).unwrap();
+    let value: toml::Value = toml::from_str(&manifest).unwrap();
+    let paths: Vec<&str> = value["tools"]["sh"]["config_paths"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p.as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["~/projects/dots/shrc", "~/projects/dots/profile"]);
+    assert_eq!(value["tools"]["sh"]["tier"].as_integer(), Some(3));
+
+    // Missing paths are rejected before anything is recorded
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["add", "env", "--path", "~/projects/dots/nope"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .env("HOME", &home)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not exist"));
+}
+
+#[test]
+fn test_add_and_remove_quiet() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().join("home");
+    fs::create_dir_all(&home).unwrap();
+    fs::write(home.join(".shrc"), "set -o vi\n").unwrap();
+
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    for args in [["add", "sh", "--path", "~/.shrc"].as_slice(), &["remove", "sh"]] {
+        Command::cargo_bin("dotsmith")
+            .unwrap()
+            .arg("--quiet")
+            .args(args)
+            .env("DOTSMITH_CONFIG_DIR", &config_dir)
+            .env("HOME", &home)
+            .assert()
+            .success()
+            .stdout("");
+    }
+}
+
+#[test]
+fn test_add_auto_detects_under_dotsmith_home() {
+    let tmp = TempDir::new().unwrap();
+    // A stand-in tool on PATH, so nothing depends on what's installed
+    let bin = fake_path(&tmp, &["sometool"]);
+
+    let home = tmp.path().join("home");
+    fs::create_dir_all(home.join(".config/sometool")).unwrap();
+    fs::write(home.join(".config/sometool/config.toml"), "key = 'value'\n").unwrap();
+    // The real $HOME has its own copy, which must not be picked up
+    let other = tmp.path().join("other");
+    fs::create_dir_all(&other).unwrap();
+    fs::write(other.join(".sometoolrc"), "").unwrap();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["add", "sometool"])
+        .env_remove("DOTSMITH_CONFIG_DIR")
+        .env_remove("XDG_CONFIG_HOME")
+        .env("DOTSMITH_HOME", &home)
+        .env("HOME", &other)
+        .env("PATH", &bin)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("~/.config/sometool/config.toml"));
+
+    // The config dir defaults to the overridden home too
+    let manifest = fs::read_to_string(home.join(".config/dotsmith/manifest.toml")).unwrap();
+    assert!(manifest.contains("~/.config/sometool/config.toml"));
+    assert!(!other.join(".config").exists());
+}
+
+/// A `bin` dir holding `which` and stand-ins for `tools`, to use as the whole
+/// `PATH` so only those count as installed.
+fn fake_path(tmp: &TempDir, tools: &[&str]) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = tmp.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let which = std::process::Command::new("sh")
+        .args(["-c", "command -v which"])
+        .output()
+        .unwrap();
+    let which = String::from_utf8_lossy(&which.stdout).trim().to_string();
+    std::os::unix::fs::symlink(which, bin.join("which")).unwrap();
+    for tool in tools {
+        let exe = bin.join(tool);
+        fs::write(&exe, "#!/bin/sh\n").unwrap();
+        let mut perms = fs::metadata(&exe).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&exe, perms).unwrap();
+    }
+    bin
+}
+
+#[test]
+fn test_add_all_adds_only_detectable_tools() {
+    let tmp = TempDir::new().unwrap();
+    let bin = fake_path(&tmp, &["kitty", "alacritty"]);
+    let home = tmp.path().join("home");
+    // kitty is installed and configured; alacritty is installed with no config
+    fs::create_dir_all(home.join(".config/kitty")).unwrap();
+    fs::write(home.join(".config/kitty/kitty.conf"), "font_size 12\n").unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+
+    let add_all = || {
+        let mut cmd = Command::cargo_bin("dotsmith").unwrap();
+        cmd.args(["add", "--all"])
+            .env("DOTSMITH_CONFIG_DIR", &config_dir)
+            .env("DOTSMITH_HOME", &home)
+            .env("PATH", &bin);
+        cmd
+    };
+
+    add_all()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added kitty"))
+        .stdout(predicate::str::contains("alacritty has no config files, skipping"))
+        .stdout(predicate::str::contains("tmux is not installed"))
+        .stdout(predicate::str::contains("1 added, 1 skipped, 5 not installed"));
+
+    let manifest = fs::read_to_string(config_dir.join("manifest.toml")).unwrap();
+    assert!(manifest.contains("[tools.kitty]"));
+    assert!(!manifest.contains("alacritty"));
+
+    add_all()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("kitty already tracked, skipping"))
+        .stdout(predicate::str::contains("0 added, 2 skipped, 5 not installed"));
+}
+
+#[test]
+fn test_add_from_file() {
+    let tmp = TempDir::new().unwrap();
+    let bin = fake_path(&tmp, &["kitty", "sometool"]);
+    let home = tmp.path().join("home");
+    fs::create_dir_all(home.join(".config/kitty")).unwrap();
+    fs::write(home.join(".config/kitty/kitty.conf"), "font_size 12\n").unwrap();
+    fs::write(home.join(".sometoolrc"), "x = 1\n").unwrap();
+    let list = tmp.path().join("tools.txt");
+    fs::write(&list, "# terminal\nkitty\n\nsometool\nnotatool\n").unwrap();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["add", "--from"])
+        .arg(&list)
+        .env("DOTSMITH_CONFIG_DIR", tmp.path().join("dotsmith"))
+        .env("DOTSMITH_HOME", &home)
+        .env("PATH", &bin)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added kitty"))
+        .stdout(predicate::str::contains("Added sometool"))
+        .stdout(predicate::str::contains("notatool is not installed"))
+        .stdout(predicate::str::contains("2 added, 0 skipped, 1 not installed"));
+}
@@ -0,0 +1,47 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::os::unix::fs::symlink;
+use tempfile::TempDir;
+
+fn dotsmith(config_dir: &std::path::Path, home: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("dotsmith").unwrap();
+    cmd.env("DOTSMITH_CONFIG_DIR", config_dir).env("HOME", home);
+    cmd
+}
+
+#[test]
+fn test_import_stow_repo() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    let home = tmp.path().join("home");
+    let repo = tmp.path().join("dotfiles");
+    fs::create_dir_all(repo.join("tmux")).unwrap();
+    fs::create_dir_all(repo.join("alacritty/.config/alacritty")).unwrap();
+    fs::create_dir_all(&home).unwrap();
+    fs::write(repo.join("tmux/.tmux.conf"), "set -g mouse on\n").unwrap();
+    fs::write(repo.join("alacritty/.config/alacritty/alacritty.toml"), "").unwrap();
+    symlink(repo.join("tmux/.tmux.conf"), home.join(".tmux.conf")).unwrap();
+
+    dotsmith(&config_dir, &home).arg("init").assert().success();
+    dotsmith(&config_dir, &home)
+        .arg("import")
+        .arg(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported tmux (1 path(s), 1 deployed)"))
+        .stdout(predicate::str::contains("Imported alacritty (1 path(s), 0 deployed)"));
+
+    let manifest = fs::read_to_string(config_dir.join("manifest.toml")).unwrap();
+    assert!(manifest.contains("~/.tmux.conf"));
+    assert!(manifest.contains("~/.config/alacritty/alacritty.toml"));
+
+    // Running it again adopts nothing twice
+    dotsmith(&config_dir, &home)
+        .arg("import")
+        .arg(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tmux already tracked"))
+        .stdout(predicate::str::contains("0 tool(s) imported"));
+}
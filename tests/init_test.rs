@@ -78,6 +78,31 @@ fn test_init_twice_succeeds() {
         .stdout(predicate::str::contains("already initialized"));
 }
 
+#[test]
+fn test_init_non_interactive_keeps_existing_config() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(config_dir.join("manifest.toml"), "").unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[general]\nrepo_path = \"~/dots\"\neditor = \"hx\"\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["init", "--non-interactive"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already initialized"));
+
+    let config = fs::read_to_string(config_dir.join("config.toml")).unwrap();
+    assert!(config.contains("editor = \"hx\""));
+    assert!(config.contains("repo_path = \"~/dots\""));
+}
+
 #[test]
 fn test_init_file_permissions() {
     use std::os::unix::fs::PermissionsExt;
@@ -139,3 +164,20 @@ fn test_auto_init_on_status() {
     assert!(config_dir.join("manifest.toml").exists());
     assert!(config_dir.join("config.toml").exists());
 }
+
+#[test]
+fn test_config_dir_flag_without_home() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+
+    // --config-dir works even when HOME is unset
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["--config-dir", &config_dir.display().to_string(), "init"])
+        .env_remove("DOTSMITH_CONFIG_DIR")
+        .env_remove("HOME")
+        .assert()
+        .success();
+
+    assert!(config_dir.join("manifest.toml").exists());
+}
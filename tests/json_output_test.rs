@@ -0,0 +1,151 @@
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::Command;
+use serde_json::Value;
+use tempfile::TempDir;
+
+/// Initialize dotsmith and track a tier 2 tool with one config file.
+fn setup(tmp: &TempDir) -> std::path::PathBuf {
+    let config_dir = tmp.path().join("dotsmith");
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .arg("init")
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success();
+
+    let conf = tmp.path().join("mytool.conf");
+    fs::write(&conf, "color = blue\n").unwrap();
+    fs::write(
+        config_dir.join("manifest.toml"),
+        format!(
+            "[tools.mytool]\ntier = 2\nconfig_paths = [\"{}\"]\nplugins_managed = false\n\
+             added_at = \"2026-01-01T00:00:00Z\"\n",
+            conf.display()
+        ),
+    )
+    .unwrap();
+    config_dir
+}
+
+fn run_json(config_dir: &Path, args: &[&str]) -> Value {
+    let output = Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(args)
+        .arg("--json")
+        .env("DOTSMITH_CONFIG_DIR", config_dir)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    serde_json::from_str(&stdout).unwrap_or_else(|e| panic!("invalid JSON ({}):\n{}", e, stdout))
+}
+
+fn first(value: &Value) -> &Value {
+    match value {
+        Value::Array(items) => items.first().expect("empty array"),
+        other => panic!("expected an array, got {}", other),
+    }
+}
+
+#[test]
+fn test_list_json() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = setup(&tmp);
+
+    let list = run_json(&config_dir, &["list"]);
+    let tool = first(&list);
+    assert_eq!(tool.get("name").and_then(Value::as_str), Some("mytool"));
+    assert_eq!(tool.get("tier").and_then(Value::as_u64), Some(2));
+    assert_eq!(tool.get("config_path_count").and_then(Value::as_u64), Some(1));
+    assert_eq!(tool.get("plugin_count").and_then(Value::as_u64), Some(0));
+    assert_eq!(tool.get("last_snapshot"), Some(&Value::Null));
+}
+
+#[test]
+fn test_status_json() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = setup(&tmp);
+
+    let status = run_json(&config_dir, &["status"]);
+    let tool = first(status.get("tools").unwrap());
+    assert_eq!(tool.get("existing_paths").and_then(Value::as_u64), Some(1));
+    assert_eq!(tool.get("total_paths").and_then(Value::as_u64), Some(1));
+    assert_eq!(status.get("warnings"), Some(&Value::Array(Vec::new())));
+    assert!(status.get("ahead").is_none());
+}
+
+#[test]
+fn test_history_json() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = setup(&tmp);
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["snapshot", "mytool", "-m", "first"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success();
+
+    let history = run_json(&config_dir, &["history", "mytool"]);
+    let entry = first(&history);
+    assert_eq!(entry.get("tool").and_then(Value::as_str), Some("mytool"));
+    assert_eq!(entry.get("message").and_then(Value::as_str), Some("first"));
+    for key in ["id", "file_path", "hash", "created_at", "pinned"] {
+        assert!(entry.get(key).is_some(), "missing {}", key);
+    }
+
+    let list = run_json(&config_dir, &["list"]);
+    assert!(first(&list).get("last_snapshot").and_then(Value::as_str).is_some());
+}
+
+#[test]
+fn test_doctor_json() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = setup(&tmp);
+
+    let report = run_json(&config_dir, &["doctor"]);
+    let tool = first(report.get("tools").unwrap());
+    assert_eq!(tool.get("name").and_then(Value::as_str), Some("mytool"));
+    for key in ["installed", "existing_paths", "total_paths", "issues", "status"] {
+        assert!(tool.get(key).is_some(), "missing {}", key);
+    }
+
+    let summary = report.get("summary").unwrap();
+    for key in ["ok", "warn", "error"] {
+        assert!(summary.get(key).is_some(), "missing {}", key);
+    }
+    assert!(report.get("hints").is_some());
+}
+
+#[test]
+fn test_history_json_conflicts_with_csv() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = setup(&tmp);
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["history", "mytool", "--csv", "--json"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_search_json_with_filters() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+
+    let hits = run_json(&config_dir, &["search", "mouse", "--tool", "tmux", "--type", "boolean"]);
+    let Value::Array(ref items) = hits else {
+        panic!("expected an array, got {}", hits);
+    };
+    assert!(items.iter().any(|h| h.get("name").and_then(Value::as_str) == Some("mouse")));
+    for hit in items {
+        assert_eq!(hit.get("tool").and_then(Value::as_str), Some("tmux"));
+        assert_eq!(hit.get("type").and_then(Value::as_str), Some("boolean"));
+        for key in ["category", "description"] {
+            assert!(hit.get(key).is_some(), "missing {}", key);
+        }
+    }
+}
@@ -196,7 +196,8 @@ fn test_search_mouse() {
         .success()
         .stdout(predicate::str::contains("tmux"))
         .stdout(predicate::str::contains("mouse"))
-        .stdout(predicate::str::contains("result"));
+        .stdout(predicate::str::contains("result"))
+        .stdout(predicate::str::contains("across"));
 }
 
 #[test]
@@ -209,6 +210,44 @@ fn test_search_no_results() {
         .stdout(predicate::str::contains("No results"));
 }
 
+#[test]
+fn test_generate_combined_markdown() {
+    let tmp = TempDir::new().unwrap();
+    let out = tmp.path().join("combined.md");
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["generate", "--tools", "tmux,git", "--out"])
+        .arg(&out)
+        .env("DOTSMITH_CONFIG_DIR", tmp.path().join("dotsmith"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("across 2 tool(s)"));
+
+    let content = std::fs::read_to_string(&out).unwrap();
+    assert!(content.contains("## tmux"));
+    assert!(content.contains("## git"));
+    assert!(content.contains("**`mouse`**"));
+}
+
+#[test]
+fn test_generate_text_to_stdout_and_unknown_tool() {
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["generate", "--tools", "git", "--format", "txt"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("git\n===\n"))
+        .stdout(predicate::str::contains("**").not());
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["generate", "--tools", "nosuchtool"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no option database for 'nosuchtool'"));
+}
+
 #[test]
 fn test_edit_not_tracked() {
     let tmp = TempDir::new().unwrap();
@@ -248,3 +287,216 @@ fn test_mangen_output() {
         .success()
         .stdout(predicate::str::contains("dotsmith"));
 }
+
+#[test]
+fn test_explore_saved_views() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["explore", "--list-views"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No saved views"));
+
+    let config_path = config_dir.join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).unwrap();
+    config.push_str(
+        "\n[views.status-bar]\ntool = \"tmux\"\ncategory = \"status\"\nquery = \"bar\"\n",
+    );
+    std::fs::write(&config_path, config).unwrap();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["explore", "--list-views"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("status-bar"))
+        .stdout(predicate::str::contains("\"bar\""));
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["explore", "--view", "nope"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("saved view 'nope' not found"));
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["explore", "zsh", "--view", "status-bar"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is for tmux, not zsh"));
+}
+
+#[test]
+fn test_doctor_flags_large_change_outside_dotsmith() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    let conf = tmp.path().join("tmux.conf");
+    std::fs::write(&conf, "set -g mouse on\n").unwrap();
+    std::fs::write(
+        config_dir.join("manifest.toml"),
+        format!(
+            "[tools.tmux]\ntier = 1\nconfig_paths = [\"{}\"]\nplugins_managed = false\n\
+             added_at = \"2026-01-01T00:00:00Z\"\n",
+            conf.display()
+        ),
+    )
+    .unwrap();
+
+    let dotsmith = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("dotsmith").unwrap();
+        cmd.args(args).env("DOTSMITH_CONFIG_DIR", &config_dir);
+        cmd
+    };
+
+    dotsmith(&["snapshot", "tmux"]).assert().success();
+
+    // Something other than dotsmith rewrites the file
+    let injected: String = (0..25).map(|i| format!("set -g @injected-{} on\n", i)).collect();
+    std::fs::write(&conf, injected).unwrap();
+
+    dotsmith(&["doctor", "tmux"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("changed 26 line(s)"))
+        .stdout(predicate::str::contains("outside dotsmith"));
+
+    // Once snapshotted the change is accepted
+    dotsmith(&["snapshot", "tmux"]).assert().success();
+    dotsmith(&["doctor", "tmux"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("outside dotsmith").not());
+}
+
+#[test]
+fn test_doctor_deep_flags_typos() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    let conf = tmp.path().join("tmux.conf");
+    std::fs::write(&conf, "set -g mouse on\nset -g escpe-time 10\n").unwrap();
+    std::fs::write(
+        config_dir.join("manifest.toml"),
+        format!(
+            "[tools.tmux]\ntier = 1\nconfig_paths = [\"{}\"]\nplugins_managed = false\n\
+             added_at = \"2026-01-01T00:00:00Z\"\n",
+            conf.display()
+        ),
+    )
+    .unwrap();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["doctor", "--tool", "tmux", "--deep"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 customized, 0 at default, 1 not in catalog"))
+        .stdout(predicate::str::contains("mouse = on"))
+        .stdout(predicate::str::contains("unknown option 'escpe-time'"))
+        .stdout(predicate::str::contains("did you mean 'escape-time'"));
+
+    // Without --deep none of this runs
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["doctor", "tmux"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("escpe-time").not());
+}
+
+#[test]
+fn test_doctor_strict_exit_codes() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    let dotsmith = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("dotsmith").unwrap();
+        cmd.args(args).env("DOTSMITH_CONFIG_DIR", &config_dir);
+        cmd
+    };
+    let write_manifest = |tool: &str, path: &std::path::Path| {
+        std::fs::write(
+            config_dir.join("manifest.toml"),
+            format!(
+                "[tools.{}]\ntier = 2\nconfig_paths = [\"{}\"]\nplugins_managed = false\n\
+                 added_at = \"2026-01-01T00:00:00Z\"\n",
+                tool,
+                path.display()
+            ),
+        )
+        .unwrap();
+    };
+
+    // A tool that isn't installed and has no config is an error
+    write_manifest("nosuchtool-xyz", &tmp.path().join("missing.conf"));
+    dotsmith(&["doctor"]).assert().success();
+    dotsmith(&["doctor", "--strict"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("doctor found 1 error(s)"));
+    dotsmith(&["doctor", "--strict-warnings"]).assert().code(2);
+
+    // An installed tool that was never snapshotted is only a warning
+    let conf = tmp.path().join("sh.conf");
+    std::fs::write(&conf, "x=1\n").unwrap();
+    write_manifest("sh", &conf);
+    dotsmith(&["doctor", "--strict"]).assert().success();
+    dotsmith(&["doctor", "--strict-warnings"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("warning(s)"));
+}
+
+#[test]
+fn test_validate_reports_broken_tmux_line() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    let conf = tmp.path().join("tmux.conf");
+    let manifest = format!(
+        "[tools.tmux]\ntier = 1\nconfig_paths = [\"{}\"]\nplugins_managed = false\n\
+         added_at = \"2026-01-01T00:00:00Z\"\n\n\
+         [tools.zsh]\ntier = 1\nconfig_paths = [\"{}\"]\nplugins_managed = false\n\
+         added_at = \"2026-01-01T00:00:00Z\"\n",
+        conf.display(),
+        tmp.path().join("zshrc").display()
+    );
+    std::fs::write(config_dir.join("manifest.toml"), manifest).unwrap();
+    std::fs::write(tmp.path().join("zshrc"), "setopt {{{\n").unwrap();
+
+    std::fs::write(&conf, "set -g mouse on\n").unwrap();
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["validate"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("skipped (no validator)"));
+
+    std::fs::write(&conf, "set -g mouse on\nnotacommand here\n").unwrap();
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["validate", "tmux"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("line 2: unrecognized command 'notacommand'"))
+        .stdout(predicate::str::contains("2 | notacommand here"))
+        .stderr(predicate::str::contains("failed validation"));
+}
@@ -220,6 +220,158 @@ fn test_plugin_list_with_plugins() {
         .stdout(predicate::str::contains("test-plugin"));
 }
 
+#[test]
+fn test_plugin_list_parseable() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = init_env(&tmp);
+    add_zsh_to_manifest(&config_dir);
+
+    let repo_url = create_fake_zsh_plugin(&tmp, "test-plugin");
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "add", &repo_url])
+        .assert()
+        .success();
+
+    let output = dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "list", "--parseable"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "no header line: {:?}", lines);
+
+    let fields: Vec<&str> = lines[0].split('\t').collect();
+    assert_eq!(fields.len(), 4);
+    assert_eq!(fields[0], "test-plugin");
+    assert_eq!(fields[1], repo_url);
+    assert_eq!(fields[2], "test-plugin.plugin.zsh");
+    assert!(!stdout.contains('\x1b'), "no color codes");
+}
+
+#[test]
+fn test_plugin_add_at_ref_pins_it() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = init_env(&tmp);
+    add_zsh_to_manifest(&config_dir);
+
+    let repo_url = create_fake_zsh_plugin(&tmp, "pinned-plugin");
+    let repo_dir = tmp.path().join("repos/pinned-plugin");
+    let git = |args: &[&str]| {
+        let out = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@test.com"])
+            .args(args)
+            .current_dir(&repo_dir)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    };
+    let first = git(&["rev-parse", "HEAD"]);
+    fs::write(repo_dir.join("later.zsh"), "# added later\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "later"]);
+
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "add", &format!("{}@{}", repo_url, first)])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Pinned at"));
+
+    let plugin_dir = format!("{}/plugins/zsh/pinned-plugin", config_dir);
+    assert!(!std::path::Path::new(&plugin_dir).join("later.zsh").exists());
+
+    let manifest = fs::read_to_string(format!("{}/manifest.toml", config_dir)).unwrap();
+    assert!(manifest.contains(&format!("pinned_ref = \"{}\"", first)));
+    assert!(manifest.contains(&format!("repo = \"{}\"", repo_url)));
+
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "update"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pinned"));
+    assert!(!std::path::Path::new(&plugin_dir).join("later.zsh").exists());
+
+    // Unpinned, the next update follows the default branch again
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "unpin", "pinned-plugin"])
+        .assert()
+        .success();
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "update"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("updated"));
+    assert!(std::path::Path::new(&plugin_dir).join("later.zsh").exists());
+}
+
+#[test]
+fn test_plugin_add_unknown_ref_fails() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = init_env(&tmp);
+    add_zsh_to_manifest(&config_dir);
+
+    let repo_url = create_fake_zsh_plugin(&tmp, "some-plugin");
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "add", &format!("{}@no-such-tag", repo_url)])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no-such-tag"));
+
+    assert!(!std::path::Path::new(&format!("{}/plugins/zsh/some-plugin", config_dir)).exists());
+}
+
+#[test]
+fn test_plugin_lock_then_sync_reinstalls() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = init_env(&tmp);
+    add_zsh_to_manifest(&config_dir);
+
+    let repo_url = create_fake_zsh_plugin(&tmp, "test-plugin");
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "add", &repo_url])
+        .assert()
+        .success();
+
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "lock"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Locked 1 plugin(s)"));
+    let lockfile = fs::read_to_string(format!("{}/plugins.lock", config_dir)).unwrap();
+    assert!(lockfile.contains("name = \"test-plugin\""));
+
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "remove", "test-plugin"])
+        .assert()
+        .success();
+
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "sync"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("installed"))
+        .stdout(predicate::str::contains("Synced 1 plugin(s)"));
+
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("test-plugin"));
+}
+
 #[test]
 fn test_plugin_remove() {
     let tmp = TempDir::new().unwrap();
@@ -278,6 +430,36 @@ fn test_plugin_update() {
         .stdout(predicate::str::contains("1 already up to date"));
 }
 
+#[test]
+fn test_plugin_info_no_readme() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = init_env(&tmp);
+    add_zsh_to_manifest(&config_dir);
+
+    let repo_url = create_fake_zsh_plugin(&tmp, "test-plugin");
+
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "add", &repo_url])
+        .assert()
+        .success();
+
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "info", "test-plugin"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("test-plugin"))
+        .stdout(predicate::str::contains("No README found"));
+
+    dotsmith()
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .args(["plugins", "zsh", "info", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not installed"));
+}
+
 #[test]
 fn test_plugin_add_duplicate_fails() {
     let tmp = TempDir::new().unwrap();
@@ -239,3 +239,260 @@ fn test_profile_load_nonexistent_fails() {
         .failure()
         .stderr(predicate::str::contains("not found"));
 }
+
+#[test]
+fn test_profile_with_history_roundtrip() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    let tool_file = tmp.path().join("fake.conf");
+    fs::write(&tool_file, "setting = true\n").unwrap();
+    add_fake_tool(&config_dir, &tool_file);
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["snapshot", "faketool", "-m", "first edit"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "save", "full", "--with-history"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 snapshot(s) of history included"));
+
+    // Lose the local history, then restore it from the profile
+    for name in ["snapshots.db", "snapshots.db-wal", "snapshots.db-shm"] {
+        let _ = fs::remove_file(config_dir.join(name));
+    }
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "load", "full", "--with-history"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 snapshot(s) merged into history"));
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["history", "faketool"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("first edit"));
+}
+
+#[test]
+fn test_profile_load_with_history_requires_history() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    let tool_file = tmp.path().join("fake.conf");
+    fs::write(&tool_file, "setting = true\n").unwrap();
+    add_fake_tool(&config_dir, &tool_file);
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "save", "plain"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "load", "plain", "--with-history"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no snapshot history"));
+}
+
+#[test]
+fn test_profile_diff_reports_changed_file() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    let tool_file = tmp.path().join("fake.conf");
+    fs::write(&tool_file, "original = true\n").unwrap();
+    add_fake_tool(&config_dir, &tool_file);
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "save", "diff-test"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "diff", "diff-test"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No differences"));
+
+    fs::write(&tool_file, "modified = true\n").unwrap();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "diff", "diff-test"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-original = true"))
+        .stdout(predicate::str::contains("+modified = true"))
+        .stdout(predicate::str::contains("1 file(s) differ"));
+
+    // The dry run previews the same diff
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "load", "diff-test", "--dry-run"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+modified = true"));
+}
+
+#[test]
+fn test_profile_encrypted_roundtrip() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+    fs::write(config_dir.join("config.toml"), "[general]\nencrypt = true\n").unwrap();
+
+    let tool_file = tmp.path().join("fake.conf");
+    fs::write(&tool_file, "token = tok_0123456789\n").unwrap();
+    add_fake_tool(&config_dir, &tool_file);
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "save", "sealed"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .env("DOTSMITH_PASSPHRASE", "correct horse")
+        .assert()
+        .success();
+
+    let stored = config_dir.join("profiles/sealed/files/faketool/fake.conf");
+    let raw = fs::read(&stored).unwrap();
+    assert!(!raw.windows(14).any(|w| w == b"tok_0123456789"));
+
+    fs::write(&tool_file, "token = rotated\n").unwrap();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "load", "sealed"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .env("DOTSMITH_PASSPHRASE", "wrong horse")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("wrong passphrase"));
+    assert_eq!(fs::read_to_string(&tool_file).unwrap(), "token = rotated\n");
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "load", "sealed"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .env("DOTSMITH_PASSPHRASE", "correct horse")
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(&tool_file).unwrap(), "token = tok_0123456789\n");
+}
+
+#[test]
+fn test_profile_export_import() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    let tool_file = tmp.path().join("fake.conf");
+    fs::write(&tool_file, "setting = true\n").unwrap();
+    add_fake_tool(&config_dir, &tool_file);
+
+    let archive = tmp.path().join("moving.tar.gz");
+    for args in [
+        vec!["profile", "save", "moving"],
+        vec!["profile", "export", "moving", archive.to_str().unwrap()],
+        vec!["profile", "delete", "moving"],
+    ] {
+        Command::cargo_bin("dotsmith")
+            .unwrap()
+            .args(&args)
+            .env("DOTSMITH_CONFIG_DIR", &config_dir)
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "import", archive.to_str().unwrap()])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported profile"));
+
+    let stored = config_dir.join("profiles/moving/files/faketool/fake.conf");
+    assert_eq!(fs::read_to_string(stored).unwrap(), "setting = true\n");
+
+    // A second import collides with the restored profile
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "import", archive.to_str().unwrap()])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+}
+
+#[test]
+fn test_profile_auto() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    let tool_file = tmp.path().join("fake.conf");
+    fs::write(&tool_file, "original = true\n").unwrap();
+    add_fake_tool(&config_dir, &tool_file);
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "save", "elsewhere", "--hostname", "no-such-host-xyz"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success();
+
+    // Nothing saved for this machine yet
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "auto"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("elsewhere"))
+        .stdout(predicate::str::contains("no-such-host-xyz"));
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "save", "here"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success();
+
+    fs::write(&tool_file, "modified = true\n").unwrap();
+
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["profile", "auto"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("matches profile 'here'"));
+
+    assert_eq!(fs::read_to_string(&tool_file).unwrap(), "original = true\n");
+}
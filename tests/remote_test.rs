@@ -73,3 +73,40 @@ fn test_deploy_remote_help() {
         .stdout(predicate::str::contains("remote host"))
         .stdout(predicate::str::contains("--dry-run"));
 }
+
+#[test]
+fn test_pull_remote_empty_manifest() {
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("dotsmith");
+    init_dotsmith(&config_dir);
+
+    let output = Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["pull-remote", "example.com", "--dry-run"])
+        .env("DOTSMITH_CONFIG_DIR", &config_dir)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    // Either "No files to pull" (ssh available) or "ssh" error (ssh not available)
+    assert!(
+        stdout.contains("No files") || stderr.contains("ssh"),
+        "Expected 'No files' or 'ssh' error, got stdout: {}, stderr: {}",
+        stdout,
+        stderr,
+    );
+    assert!(!config_dir.join("pull-staging").exists());
+}
+
+#[test]
+fn test_pull_remote_help() {
+    Command::cargo_bin("dotsmith")
+        .unwrap()
+        .args(["pull-remote", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("remote host"))
+        .stdout(predicate::str::contains("--dry-run"));
+}